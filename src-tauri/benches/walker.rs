@@ -0,0 +1,69 @@
+// 目录遍历与大小计算的基准：在合成目录树上比较串行、并行遍历与从文件索引读取
+// 运行：cargo bench --bench walker
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use disk_sight_lib::dir_listing::calculate_dir_size;
+use disk_sight_lib::index::FileIndex;
+use disk_sight_lib::test_tree::{generate_test_tree, TreeSpec};
+use indicatif::ProgressBar;
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// (名称, 目录数, 文件数, 最深层数)
+const TREES: [(&str, usize, usize, usize); 3] = [
+    ("wide", 200, 20_000, 4),
+    ("deep", 2_000, 10_000, 200),
+    ("small", 50, 1_000, 8),
+];
+
+fn prepare(name: &str, dirs: usize, files: usize, depth: usize) -> PathBuf {
+    let base = std::env::temp_dir().join(format!("disksight-bench-{}", name));
+    let _ = fs::remove_dir_all(&base);
+    let spec = TreeSpec {
+        dirs,
+        files,
+        depth,
+        ..Default::default()
+    };
+    generate_test_tree(&base.join("tree"), &spec).expect("生成测试目录树失败");
+    base
+}
+
+// 建立索引在后台线程中进行，等待完成
+fn build_index(base: &PathBuf) -> FileIndex {
+    let data_dir = base.join("data");
+    fs::create_dir_all(&data_dir).unwrap();
+    let root = base.join("tree").to_string_lossy().into_owned();
+    let index = FileIndex::load(&data_dir);
+    index.set_roots(std::slice::from_ref(&root), true);
+    while index.built_at(&root).is_none() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    index
+}
+
+fn walkers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dir_size");
+    group.sample_size(10);
+    for (name, dirs, files, depth) in TREES {
+        let base = prepare(name, dirs, files, depth);
+        let tree = base.join("tree");
+        let pb = ProgressBar::hidden();
+        group.bench_with_input(BenchmarkId::new("serial", name), &tree, |b, tree| {
+            b.iter(|| black_box(calculate_dir_size(tree, false, &pb, false).0))
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", name), &tree, |b, tree| {
+            b.iter(|| black_box(calculate_dir_size(tree, false, &pb, true).0))
+        });
+        let index = build_index(&base);
+        group.bench_with_input(BenchmarkId::new("cached", name), &tree, |b, tree| {
+            b.iter(|| black_box(index.dir_size(tree)))
+        });
+        let _ = fs::remove_dir_all(&base);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, walkers);
+criterion_main!(benches);
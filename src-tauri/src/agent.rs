@@ -0,0 +1,551 @@
+// 多主机模式：各台机器以 --agent 参数运行 DiskSight 作为代理，中心实例连接代理请求扫描并汇总结果。
+//
+// 协议：代理在 --listen 指定的地址监听（默认 127.0.0.1:7879），客户端连接后先发送一行访问令牌
+// （代理从环境变量 DISKSIGHT_AGENT_TOKEN 读取），通过后代理回复 Ready；之后双方每行一个 JSON，
+// 客户端发送 AgentRequest，代理回复 AgentResponse。代理只提供只读的查询，不接受任何修改操作。
+//
+// 连接不加密，令牌和目录列表都以明文传输。跨主机访问时应让代理只监听本机，
+// 通过 SSH 隧道（如 ssh -L 7879:127.0.0.1:7879 host）或 VPN 连接；
+// 只有显式指定 --listen 才会监听其他地址，此时启动时会打印警告
+use crate::api_auth::constant_time_eq;
+use crate::collation::NameCollation;
+use crate::drives::{list_drives, DriveInfo};
+use crate::ignore_rules::IgnoreMode;
+use crate::models::FileEntry;
+use crate::parallelism::ParallelismSettings;
+use crate::scan_context::ScanEmitter;
+use crate::scan_worker::scan_directory;
+use crate::utils::{human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const AGENT_FLAG: &str = "--agent";
+pub const TOKEN_ENV: &str = "DISKSIGHT_AGENT_TOKEN";
+const DEFAULT_LISTEN: &str = "127.0.0.1:7879";
+// 协议版本，不兼容的修改时递增
+pub const PROTOCOL_VERSION: u32 = 1;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// 每台主机返回的最大条目数上限
+const MAX_TOP_ENTRIES: usize = 200;
+// 令牌行与请求行的最大字节数，超过时断开连接
+const MAX_TOKEN_LINE: u64 = 1024;
+const MAX_REQUEST_LINE: u64 = 64 * 1024;
+
+// 代理端的连接限制
+#[derive(Clone, Copy, Debug)]
+struct Limits {
+    /// 连接后等待令牌的最长时间
+    auth_timeout: Duration,
+    /// 验证通过后两次请求之间的最长间隔
+    idle_timeout: Duration,
+    /// 同时处理的连接数，超出的连接直接关闭
+    max_connections: usize,
+}
+
+const LIMITS: Limits = Limits {
+    auth_timeout: Duration::from_secs(10),
+    idle_timeout: Duration::from_secs(300),
+    max_connections: 8,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AgentEndpoint {
+    /// 在汇总面板中显示的名称
+    pub name: String,
+    /// host:port
+    pub address: String,
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AgentRequest {
+    /// 主机名、操作系统和各卷容量
+    Info,
+    /// 扫描目录，返回总大小和最大的 limit 个直接子项
+    Scan { path: String, limit: usize },
+    /// 处理完当前请求后断开
+    Close,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HostScan {
+    pub path: String,
+    pub total_bytes: u64,
+    pub total_display: String,
+    /// 按大小降序
+    pub top_entries: Vec<FileEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentResponse {
+    Ready {
+        protocol: u32,
+    },
+    Info {
+        host: String,
+        os: String,
+        drives: Vec<DriveInfo>,
+    },
+    Scan {
+        scan: HostScan,
+    },
+    Closed,
+    Error {
+        message: String,
+    },
+}
+
+// 代理入口，main 检测到 --agent 参数时调用
+pub fn run_agent() {
+    let args: Vec<String> = std::env::args().collect();
+    let listen = args
+        .iter()
+        .position(|arg| arg == "--listen")
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_LISTEN);
+    let token = match std::env::var(TOKEN_ENV) {
+        Ok(token) if !token.is_empty() => token,
+        _ => {
+            eprintln!("请通过环境变量 {} 设置代理的访问令牌", TOKEN_ENV);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = serve(listen, &token) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn serve(listen: &str, token: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(listen).map_err(|e| format!("无法监听 {}: {}", listen, e))?;
+    eprintln!("DiskSight 代理已在 {} 上监听", listen);
+    if listener
+        .local_addr()
+        .is_ok_and(|addr| !addr.ip().is_loopback())
+    {
+        eprintln!("警告：代理连接不加密，令牌和扫描结果以明文传输，请只在可信网络中使用或通过 SSH 隧道、VPN 访问");
+    }
+    serve_on(listener, token, LIMITS);
+    Ok(())
+}
+
+// 连接数达到上限时新连接直接关闭；每个连接在退出时释放名额
+fn serve_on(listener: TcpListener, token: &str, limits: Limits) {
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming().flatten() {
+        if active.fetch_add(1, Ordering::SeqCst) >= limits.max_connections {
+            active.fetch_sub(1, Ordering::SeqCst);
+            eprintln!(
+                "代理连接数已达上限 {}，拒绝 {}",
+                limits.max_connections,
+                peer(&stream)
+            );
+            continue;
+        }
+        let token = token.to_string();
+        let active = active.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &token, limits) {
+                eprintln!("代理连接出错: {}", e);
+            }
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+fn peer(stream: &TcpStream) -> String {
+    stream.peer_addr().map_or_else(
+        |_| "未知地址".to_string(),
+        |addr: SocketAddr| addr.to_string(),
+    )
+}
+
+// 读取一行，最多 limit 字节；连接关闭时返回 None，超时或超长时返回错误
+fn read_line_limited(
+    reader: &mut BufReader<TcpStream>,
+    limit: u64,
+) -> Result<Option<String>, String> {
+    let mut line = String::new();
+    let read = reader
+        .by_ref()
+        .take(limit)
+        .read_line(&mut line)
+        .map_err(|e| match e.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => "等待请求超时".to_string(),
+            _ => e.to_string(),
+        })?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && read as u64 >= limit {
+        return Err(format!("请求超过 {} 字节", limit));
+    }
+    Ok(Some(line))
+}
+
+fn handle_connection(stream: TcpStream, token: &str, limits: Limits) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(limits.auth_timeout))
+        .map_err(|e| e.to_string())?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let Some(presented) = read_line_limited(&mut reader, MAX_TOKEN_LINE)? else {
+        return Ok(());
+    };
+    if !constant_time_eq(presented.trim().as_bytes(), token.as_bytes()) {
+        return send(
+            &mut writer,
+            &AgentResponse::Error {
+                message: "无效的访问令牌".to_string(),
+            },
+        );
+    }
+    send(
+        &mut writer,
+        &AgentResponse::Ready {
+            protocol: PROTOCOL_VERSION,
+        },
+    )?;
+
+    reader
+        .get_ref()
+        .set_read_timeout(Some(limits.idle_timeout))
+        .map_err(|e| e.to_string())?;
+    while let Some(line) = read_line_limited(&mut reader, MAX_REQUEST_LINE)? {
+        let response = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(AgentRequest::Close) => {
+                send(&mut writer, &AgentResponse::Closed)?;
+                break;
+            }
+            Ok(request) => handle(request),
+            Err(e) => AgentResponse::Error {
+                message: format!("无法解析请求: {}", e),
+            },
+        };
+        send(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+fn send(writer: &mut impl Write, response: &AgentResponse) -> Result<(), String> {
+    let line = serde_json::to_string(response).map_err(|e| e.to_string())?;
+    writeln!(writer, "{}", line).map_err(|e| e.to_string())
+}
+
+fn handle(request: AgentRequest) -> AgentResponse {
+    match request {
+        AgentRequest::Info => AgentResponse::Info {
+            host: host_name(),
+            os: std::env::consts::OS.to_string(),
+            drives: list_drives(),
+        },
+        AgentRequest::Scan { path, limit } => match scan(&path, limit) {
+            Ok(scan) => AgentResponse::Scan { scan },
+            Err(message) => AgentResponse::Error { message },
+        },
+        AgentRequest::Close => AgentResponse::Closed,
+    }
+}
+
+fn scan(path: &str, limit: usize) -> Result<HostScan, String> {
+    if !Path::new(path).is_dir() {
+        return Err(format!("不是目录: {}", path));
+    }
+    let emitter = ScanEmitter::forwarding(0, None, |_, _| {});
+    let (mut entries, _) = scan_directory(
+        Path::new(path),
+        &ParallelismSettings::default(),
+        IgnoreMode::Off,
+        &[],
+        false,
+        NameCollation::default(),
+        &emitter,
+    )
+    .map_err(|e| e.to_string())?;
+    let total_bytes = sum_sizes(entries.iter().map(|e| e.size_raw));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries.truncate(limit.min(MAX_TOP_ENTRIES));
+    Ok(HostScan {
+        path: path.to_string(),
+        total_bytes,
+        total_display: human_readable_size(total_bytes),
+        top_entries: entries,
+    })
+}
+
+fn host_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|s| s.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// 中心实例一侧
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HostReport {
+    /// 配置中的代理名称
+    pub name: String,
+    pub address: String,
+    /// 代理报告的主机名
+    pub host: Option<String>,
+    pub os: Option<String>,
+    pub drives: Vec<DriveInfo>,
+    pub scan: Option<HostScan>,
+    /// 连接或扫描失败的原因，不影响其他主机
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiHostReport {
+    pub hosts: Vec<HostReport>,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub total_display: String,
+    pub available_display: String,
+}
+
+// 同时查询所有代理；path 不为空时还请求扫描该目录，各主机使用相同的路径
+pub fn query_agents(
+    agents: &[AgentEndpoint],
+    path: Option<&str>,
+    limit: usize,
+    timeout: Option<Duration>,
+) -> MultiHostReport {
+    let hosts: Vec<HostReport> = std::thread::scope(|scope| {
+        let handles: Vec<_> = agents
+            .iter()
+            .map(|agent| scope.spawn(move || query_agent(agent, path, limit, timeout)))
+            .collect();
+        handles
+            .into_iter()
+            .zip(agents)
+            .map(|(handle, agent)| {
+                handle.join().unwrap_or_else(|_| HostReport {
+                    error: Some("查询线程异常退出".to_string()),
+                    ..empty_report(agent)
+                })
+            })
+            .collect()
+    });
+    let drives = || hosts.iter().flat_map(|h| &h.drives);
+    let total_space = sum_sizes(drives().map(|d| d.total_space));
+    let available_space = sum_sizes(drives().map(|d| d.available_space));
+    MultiHostReport {
+        total_display: human_readable_size(total_space),
+        available_display: human_readable_size(available_space),
+        total_space,
+        available_space,
+        hosts,
+    }
+}
+
+fn empty_report(agent: &AgentEndpoint) -> HostReport {
+    HostReport {
+        name: agent.name.clone(),
+        address: agent.address.clone(),
+        host: None,
+        os: None,
+        drives: Vec::new(),
+        scan: None,
+        error: None,
+    }
+}
+
+fn query_agent(
+    agent: &AgentEndpoint,
+    path: Option<&str>,
+    limit: usize,
+    timeout: Option<Duration>,
+) -> HostReport {
+    let mut report = empty_report(agent);
+    if let Err(e) = fill_report(&mut report, agent, path, limit, timeout) {
+        report.error = Some(e);
+    }
+    report
+}
+
+fn fill_report(
+    report: &mut HostReport,
+    agent: &AgentEndpoint,
+    path: Option<&str>,
+    limit: usize,
+    timeout: Option<Duration>,
+) -> Result<(), String> {
+    let mut client = AgentClient::connect(agent, timeout)?;
+    match client.request(&AgentRequest::Info)? {
+        AgentResponse::Info { host, os, drives } => {
+            report.host = Some(host);
+            report.os = Some(os);
+            report.drives = drives;
+        }
+        other => return Err(format!("代理回复异常: {:?}", other)),
+    }
+    if let Some(path) = path {
+        let request = AgentRequest::Scan {
+            path: path.to_string(),
+            limit,
+        };
+        match client.request(&request)? {
+            AgentResponse::Scan { scan } => report.scan = Some(scan),
+            other => return Err(format!("代理回复异常: {:?}", other)),
+        }
+    }
+    let _ = client.request(&AgentRequest::Close);
+    Ok(())
+}
+
+struct AgentClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl AgentClient {
+    // 连接并验证令牌；timeout 为每次读取回复的最长等待时间
+    fn connect(agent: &AgentEndpoint, timeout: Option<Duration>) -> Result<Self, String> {
+        let address = agent
+            .address
+            .to_socket_addrs()
+            .map_err(|e| format!("无法解析地址 {}: {}", agent.address, e))?
+            .next()
+            .ok_or_else(|| format!("无法解析地址 {}", agent.address))?;
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+            .map_err(|e| format!("无法连接代理 {}: {}", agent.address, e))?;
+        stream
+            .set_read_timeout(timeout)
+            .map_err(|e| e.to_string())?;
+        let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+        writeln!(writer, "{}", agent.token).map_err(|e| e.to_string())?;
+        let mut client = AgentClient {
+            reader: BufReader::new(stream),
+            writer,
+        };
+        match client.read()? {
+            AgentResponse::Ready { protocol } if protocol == PROTOCOL_VERSION => Ok(client),
+            AgentResponse::Ready { protocol } => Err(format!(
+                "代理协议版本 {} 与本机 {} 不兼容",
+                protocol, PROTOCOL_VERSION
+            )),
+            AgentResponse::Error { message } => Err(message),
+            other => Err(format!("代理回复异常: {:?}", other)),
+        }
+    }
+
+    fn request(&mut self, request: &AgentRequest) -> Result<AgentResponse, String> {
+        let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        writeln!(self.writer, "{}", line).map_err(|e| format!("代理连接已断开: {}", e))?;
+        match self.read()? {
+            AgentResponse::Error { message } => Err(message),
+            response => Ok(response),
+        }
+    }
+
+    fn read(&mut self) -> Result<AgentResponse, String> {
+        let mut line = String::new();
+        let read = self
+            .reader
+            .read_line(&mut line)
+            .map_err(|e| format!("读取代理回复失败: {}", e))?;
+        if read == 0 {
+            return Err("代理连接已断开".to_string());
+        }
+        serde_json::from_str(&line).map_err(|e| format!("代理回复格式错误: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "secret";
+
+    // 在本机随机端口启动代理，返回其地址
+    fn start_agent(limits: Limits) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || serve_on(listener, TOKEN, limits));
+        address
+    }
+
+    fn endpoint(address: &str, token: &str) -> AgentEndpoint {
+        AgentEndpoint {
+            name: "test".to_string(),
+            address: address.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    #[test]
+    fn agent_checks_token_and_lists_directories() {
+        let address = start_agent(LIMITS);
+        let timeout = Some(Duration::from_secs(10));
+        let error = AgentClient::connect(&endpoint(&address, "wrong"), timeout)
+            .err()
+            .unwrap();
+        assert_eq!(error, "无效的访问令牌");
+
+        let dir = std::env::temp_dir().join(format!("disksight-agent-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("a.bin"), vec![0u8; 4096]).unwrap();
+        let mut client = AgentClient::connect(&endpoint(&address, TOKEN), timeout).unwrap();
+        let request = AgentRequest::Scan {
+            path: dir.to_string_lossy().into_owned(),
+            limit: 10,
+        };
+        let response = client.request(&request);
+        std::fs::remove_dir_all(&dir).unwrap();
+        match response.unwrap() {
+            AgentResponse::Scan { scan } => {
+                assert!(scan.top_entries.iter().any(|e| e.name == "sub"));
+                assert!(scan.total_bytes >= 4096);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(matches!(
+            client.request(&AgentRequest::Close),
+            Ok(AgentResponse::Closed)
+        ));
+    }
+
+    #[test]
+    fn silent_connections_are_closed_after_the_auth_timeout() {
+        let address = start_agent(Limits {
+            auth_timeout: Duration::from_millis(200),
+            ..LIMITS
+        });
+        let stream = TcpStream::connect(&address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut buf = [0u8; 1];
+        let started = std::time::Instant::now();
+        assert_eq!((&stream).read(&mut buf).unwrap(), 0);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn overlong_token_lines_are_rejected() {
+        let address = start_agent(LIMITS);
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let _ = stream.write_all(&vec![b'a'; MAX_TOKEN_LINE as usize + 1]);
+        let mut buf = [0u8; 1];
+        assert!(matches!(stream.read(&mut buf), Ok(0) | Err(_)));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::forecast::Forecast;
+use crate::history::SizePoint;
+use crate::utils::human_readable_size;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// 单次 webhook 请求的超时时间
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+// 同一目录的容量预测提醒至少间隔这么久才再次发送
+const FORECAST_COOLDOWN_SECS: u64 = 24 * 60 * 60;
+// SMTP 密码在系统钥匙串中的服务名
+const KEYRING_SERVICE: &str = "DiskSight";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// 监控目录总大小超过阈值
+    Threshold,
+    /// 两次扫描之间增长过快
+    Growth,
+    /// 预计所在卷即将写满
+    Forecast,
+    /// 保留策略命中了需要处理的文件
+    Retention,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub path: String,
+    pub message: String,
+    /// 本次扫描的目录总大小
+    pub size_raw: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// Slack Incoming Webhook 格式 {"text": ...}，也适用于 Mattermost 等兼容服务
+    Slack,
+    /// 直接发送 Alert 的 JSON
+    Json,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    pub url: String,
+    pub format: WebhookFormat,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// 保存在系统钥匙串中，不写入配置文件，也不返回给前端
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// 监控目录总大小超过该值（字节）时提醒
+    pub threshold_bytes: Option<u64>,
+    /// 相邻两次扫描之间增长超过该值（字节）时提醒
+    pub growth_bytes: Option<u64>,
+    pub webhooks: Vec<WebhookTarget>,
+    /// 可选的邮件通知
+    pub smtp: Option<SmtpSettings>,
+}
+
+impl SmtpSettings {
+    fn keyring_entry(&self) -> Result<keyring::Entry, String> {
+        let account = format!("smtp:{}@{}", self.username, self.host);
+        keyring::Entry::new(KEYRING_SERVICE, &account).map_err(|e| e.to_string())
+    }
+
+    // 把密码写入系统钥匙串，密码为空时不做任何事
+    pub fn store_password(&self) -> Result<(), String> {
+        if self.password.is_empty() {
+            return Ok(());
+        }
+        self.keyring_entry()?
+            .set_password(&self.password)
+            .map_err(|e| format!("无法把 SMTP 密码保存到系统钥匙串: {}", e))
+    }
+
+    // 从系统钥匙串读回密码，没有保存过时保持为空
+    pub fn load_password(&mut self) {
+        match self
+            .keyring_entry()
+            .and_then(|e| e.get_password().map_err(|e| e.to_string()))
+        {
+            Ok(password) => self.password = password,
+            Err(e) => eprintln!("读取 SMTP 密码失败: {}", e),
+        }
+    }
+}
+
+impl AlertSettings {
+    // 是否配置了任何对外投递渠道
+    pub fn has_targets(&self) -> bool {
+        !self.webhooks.is_empty() || self.smtp.is_some()
+    }
+}
+
+// 记录每个目录各类提醒最近一次发送的时间，用于没有自然去重条件的提醒
+#[derive(Default)]
+pub struct AlertCooldown(Mutex<HashMap<(String, AlertKind), u64>>);
+
+impl AlertCooldown {
+    // 去掉仍在冷却期内的容量预测提醒，并为放行的提醒记下发送时间
+    pub fn filter(&self, alerts: Vec<Alert>, now: u64) -> Vec<Alert> {
+        let mut sent = self.0.lock().unwrap();
+        alerts
+            .into_iter()
+            .filter(|alert| {
+                if alert.kind != AlertKind::Forecast {
+                    return true;
+                }
+                let key = (alert.path.clone(), alert.kind);
+                if sent
+                    .get(&key)
+                    .is_some_and(|last| now.saturating_sub(*last) < FORECAST_COOLDOWN_SECS)
+                {
+                    return false;
+                }
+                sent.insert(key, now);
+                true
+            })
+            .collect()
+    }
+}
+
+// 根据监控目录最新的历史记录和预测结果生成需要发送的提醒
+pub fn evaluate(
+    path: &str,
+    points: &[SizePoint],
+    forecast: Option<&Forecast>,
+    settings: &AlertSettings,
+) -> Vec<Alert> {
+    let Some(latest) = points.last() else {
+        return Vec::new();
+    };
+    let mut alerts = Vec::new();
+    if let Some(threshold) = settings.threshold_bytes {
+        // 只在首次越过阈值时提醒，避免每次扫描都重复发送
+        let previous = points.len().checked_sub(2).map(|i| points[i].size_raw);
+        if latest.size_raw >= threshold && previous.is_none_or(|p| p < threshold) {
+            alerts.push(Alert {
+                kind: AlertKind::Threshold,
+                path: path.to_string(),
+                message: format!(
+                    "{} 已达到 {}，超过阈值 {}",
+                    path,
+                    latest.size_display,
+                    human_readable_size(threshold)
+                ),
+                size_raw: latest.size_raw,
+            });
+        }
+    }
+    if let (Some(limit), [.., previous, _]) = (settings.growth_bytes, points) {
+        let growth = latest.size_raw.saturating_sub(previous.size_raw);
+        if growth >= limit {
+            alerts.push(Alert {
+                kind: AlertKind::Growth,
+                path: path.to_string(),
+                message: format!(
+                    "{} 自上次扫描以来增长了 {}，当前 {}",
+                    path,
+                    human_readable_size(growth),
+                    latest.size_display
+                ),
+                size_raw: latest.size_raw,
+            });
+        }
+    }
+    if let Some(message) = forecast.and_then(|f| f.alert.clone()) {
+        alerts.push(Alert {
+            kind: AlertKind::Forecast,
+            path: path.to_string(),
+            message,
+            size_raw: latest.size_raw,
+        });
+    }
+    alerts
+}
+
+// 把提醒发送到所有配置的渠道，返回各渠道的错误信息
+pub fn deliver(alerts: &[Alert], settings: &AlertSettings) -> Vec<String> {
+    let mut errors = Vec::new();
+    for alert in alerts {
+        for webhook in &settings.webhooks {
+            if let Err(e) = send_webhook(webhook, alert) {
+                errors.push(format!("webhook {} 发送失败: {}", webhook.url, e));
+            }
+        }
+        if let Some(smtp) = &settings.smtp {
+            if let Err(e) = send_email(smtp, alert) {
+                errors.push(format!("邮件发送失败: {}", e));
+            }
+        }
+    }
+    errors
+}
+
+fn send_webhook(target: &WebhookTarget, alert: &Alert) -> Result<(), String> {
+    let body = match target.format {
+        WebhookFormat::Slack => serde_json::to_string(&serde_json::json!({
+            "text": format!("DiskSight: {}", alert.message)
+        })),
+        WebhookFormat::Json => serde_json::to_string(alert),
+    }
+    .map_err(|e| e.to_string())?;
+    ureq::post(&target.url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn send_email(settings: &SmtpSettings, alert: &Alert) -> Result<(), String> {
+    let from: Mailbox = settings
+        .from
+        .parse()
+        .map_err(|e| format!("发件人地址无效: {}", e))?;
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(format!("DiskSight 提醒: {}", alert.path));
+    for to in &settings.to {
+        let to: Mailbox = to.parse().map_err(|e| format!("收件人地址无效: {}", e))?;
+        builder = builder.to(to);
+    }
+    let message = builder
+        .body(alert.message.clone())
+        .map_err(|e| e.to_string())?;
+    let mailer = SmtpTransport::relay(&settings.host)
+        .map_err(|e| e.to_string())?
+        .port(settings.port)
+        .credentials(Credentials::new(
+            settings.username.clone(),
+            settings.password.clone(),
+        ))
+        .build();
+    mailer.send(&message).map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(size_raw: u64) -> SizePoint {
+        SizePoint {
+            timestamp: 0,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+        }
+    }
+
+    #[test]
+    fn threshold_fires_once_growth_per_scan() {
+        let settings = AlertSettings {
+            threshold_bytes: Some(100),
+            growth_bytes: Some(30),
+            ..AlertSettings::default()
+        };
+        let kinds = |points: &[SizePoint]| -> Vec<AlertKind> {
+            evaluate("/var/log", points, None, &settings)
+                .iter()
+                .map(|a| a.kind)
+                .collect()
+        };
+        assert_eq!(
+            kinds(&[point(80), point(120)]),
+            [AlertKind::Threshold, AlertKind::Growth]
+        );
+        assert_eq!(kinds(&[point(80), point(120), point(130)]), []);
+        assert_eq!(kinds(&[point(120), point(150)]), [AlertKind::Growth]);
+    }
+
+    #[test]
+    fn forecast_alerts_respect_cooldown() {
+        let alert = |kind, path: &str| Alert {
+            kind,
+            path: path.to_string(),
+            message: String::new(),
+            size_raw: 0,
+        };
+        let cooldown = AlertCooldown::default();
+        let batch = || {
+            vec![
+                alert(AlertKind::Forecast, "/data"),
+                alert(AlertKind::Growth, "/data"),
+            ]
+        };
+        assert_eq!(cooldown.filter(batch(), 1_000).len(), 2);
+        let again = cooldown.filter(batch(), 1_000 + 3600);
+        assert_eq!(again.len(), 1);
+        assert_eq!(again[0].kind, AlertKind::Growth);
+        assert_eq!(
+            cooldown
+                .filter(vec![alert(AlertKind::Forecast, "/other")], 1_000 + 3600)
+                .len(),
+            1
+        );
+        assert_eq!(
+            cooldown
+                .filter(batch(), 1_000 + FORECAST_COOLDOWN_SECS)
+                .len(),
+            2
+        );
+    }
+}
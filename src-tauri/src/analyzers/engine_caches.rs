@@ -0,0 +1,231 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::projects::ProjectKind;
+use crate::utils::{dir_size, home_dir, human_readable_size};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    Unity,
+    Unreal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegenerateSafety {
+    /// 删除后编辑器会自动重新生成，只是下次打开项目较慢
+    Safe,
+    /// 编辑器打开该项目时正在使用，关闭编辑器后才能删除
+    SafeWhenClosed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EngineCacheEntry {
+    pub engine: Engine,
+    /// 所属项目名称，全局缓存为 None
+    pub project: Option<String>,
+    /// 缓存目录名称，如 Library、DerivedDataCache
+    pub folder: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub safety: RegenerateSafety,
+}
+
+// 项目内可以重新生成的目录及其删除条件
+const UNITY_FOLDERS: [(&str, RegenerateSafety); 3] = [
+    ("Library", RegenerateSafety::SafeWhenClosed),
+    ("Temp", RegenerateSafety::SafeWhenClosed),
+    ("Obj", RegenerateSafety::Safe),
+];
+const UNREAL_FOLDERS: [(&str, RegenerateSafety); 3] = [
+    ("DerivedDataCache", RegenerateSafety::Safe),
+    ("Intermediate", RegenerateSafety::SafeWhenClosed),
+    ("Binaries", RegenerateSafety::SafeWhenClosed),
+];
+
+// 查找 root 下所有 Unity、Unreal 项目的缓存目录，以及本机的全局引擎缓存，按大小降序
+pub fn find_engine_caches(root: &Path) -> Vec<EngineCacheEntry> {
+    let mut entries = project_caches(root);
+    for (engine, dir) in global_cache_dirs() {
+        entries.push(build_entry(
+            engine,
+            None,
+            &dir,
+            RegenerateSafety::SafeWhenClosed,
+        ));
+    }
+    entries.retain(|e| e.size_raw > 0);
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries
+}
+
+pub struct EngineCachesAnalyzer;
+
+impl Analyzer for EngineCachesAnalyzer {
+    fn name(&self) -> &str {
+        "engine_caches"
+    }
+
+    fn description(&self) -> &str {
+        "Unity 的 Library/Temp 与 Unreal 的 DerivedDataCache/Intermediate 等可重新生成的目录"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_engine_caches(root)
+            .into_iter()
+            .filter(|e| Path::new(&e.path).starts_with(root))
+            .map(|e| {
+                let note = match e.safety {
+                    RegenerateSafety::Safe => "可安全删除，编辑器会重新生成",
+                    RegenerateSafety::SafeWhenClosed => "关闭编辑器后可删除，编辑器会重新生成",
+                };
+                let label = match e.project {
+                    Some(project) => format!("{} / {}", project, e.folder),
+                    None => e.folder,
+                };
+                AnalyzerItem::new(label, e.path, e.size_raw, Some(note.to_string()))
+            })
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+// 识别到引擎项目后只统计其缓存目录，不再向下查找；其他项目类型同样不再进入
+fn project_caches(dir: &Path) -> Vec<EngineCacheEntry> {
+    let (engine, folders) = match ProjectKind::detect(dir) {
+        Some(ProjectKind::Unity) => (Engine::Unity, &UNITY_FOLDERS),
+        Some(ProjectKind::Unreal) => (Engine::Unreal, &UNREAL_FOLDERS),
+        Some(_) => return Vec::new(),
+        None => {
+            let Ok(entries) = fs::read_dir(dir) else {
+                return Vec::new();
+            };
+            let dirs: Vec<PathBuf> = entries
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|e| e.path())
+                .collect();
+            let found: Vec<Vec<EngineCacheEntry>> =
+                dirs.par_iter().map(|d| project_caches(d)).collect();
+            return found.concat();
+        }
+    };
+    let project = file_name(dir);
+    folders
+        .iter()
+        .map(|(folder, safety)| (dir.join(folder), *safety))
+        .filter(|(path, _)| path.is_dir())
+        .map(|(path, safety)| build_entry(engine, Some(project.clone()), &path, safety))
+        .collect()
+}
+
+fn build_entry(
+    engine: Engine,
+    project: Option<String>,
+    dir: &Path,
+    safety: RegenerateSafety,
+) -> EngineCacheEntry {
+    let size_raw = dir_size(dir);
+    EngineCacheEntry {
+        engine,
+        project,
+        folder: file_name(dir),
+        path: dir.to_string_lossy().into_owned(),
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        safety,
+    }
+}
+
+// 引擎在用户目录下的共享缓存
+fn global_cache_dirs() -> Vec<(Engine, PathBuf)> {
+    let mut dirs = Vec::new();
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        let local = PathBuf::from(local);
+        dirs.push((
+            Engine::Unreal,
+            local
+                .join("UnrealEngine")
+                .join("Common")
+                .join("DerivedDataCache"),
+        ));
+        dirs.push((Engine::Unity, local.join("Unity").join("cache")));
+    }
+    if let Some(home) = home_dir() {
+        dirs.push((
+            Engine::Unreal,
+            home.join("Library/Application Support/Epic/DerivedDataCache"),
+        ));
+        dirs.push((Engine::Unity, home.join("Library/Unity/cache")));
+        dirs.push((Engine::Unity, home.join(".cache").join("unity3d")));
+    }
+    dirs.retain(|(_, dir)| dir.is_dir());
+    dirs
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::fixture_tree;
+
+    #[test]
+    fn finds_cache_folders_only_inside_engine_projects() {
+        let root = fixture_tree(
+            "engine-caches",
+            &[
+                ("games/Platformer/Assets/Player.prefab", 10),
+                ("games/Platformer/ProjectSettings/ProjectVersion.txt", 10),
+                ("games/Platformer/Library/ArtifactDB", 300),
+                ("games/Platformer/Obj/Debug.dll", 200),
+                // 空的 Temp 不报告
+                ("games/Platformer/Temp/", 0),
+                ("games/Shooter/Shooter.uproject", 10),
+                ("games/Shooter/DerivedDataCache/shaders.ddp", 500),
+                ("games/Shooter/Intermediate/Build/obj", 100),
+                ("games/Shooter/Content/Map.umap", 1000),
+                ("games/Shooter/Saved/Logs/Shooter.log", 1000),
+                // 缺少 ProjectSettings，不是 Unity 项目
+                ("notes/Assets/logo.png", 10),
+                ("notes/Library/index", 1000),
+                // 其他类型的项目不再向下查找
+                ("tool/Cargo.toml", 10),
+                ("tool/vendor/Game/Game.uproject", 10),
+                ("tool/vendor/Game/DerivedDataCache/ddc", 1000),
+            ],
+        );
+
+        let report = EngineCachesAnalyzer.scan(&root).unwrap();
+        let found: Vec<(&str, u64)> = report
+            .items
+            .iter()
+            .map(|i| (i.label.as_str(), i.size_raw))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Shooter / DerivedDataCache", 500),
+                ("Platformer / Library", 300),
+                ("Platformer / Obj", 200),
+                ("Shooter / Intermediate", 100),
+            ]
+        );
+        let obj = &report.items[2];
+        assert_eq!(
+            Path::new(&obj.path),
+            root.join("games/Platformer/Obj").as_path()
+        );
+        assert_eq!(obj.note.as_deref(), Some("可安全删除，编辑器会重新生成"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
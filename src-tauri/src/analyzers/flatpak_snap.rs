@@ -0,0 +1,293 @@
+use super::linux_system::LinuxCleanup;
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::utils::{dir_size, home_dir, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SNAPS_DIR: &str = "/var/lib/snapd/snaps";
+const SNAP_MOUNT_DIR: &str = "/snap";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxKind {
+    FlatpakRuntime,
+    SnapRevision,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SandboxEntry {
+    pub kind: SandboxKind,
+    /// 运行时 ID 或 snap 名称
+    pub name: String,
+    /// flatpak 的架构与分支（如 x86_64/23.08），snap 的版本号
+    pub version: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// 属于当前用户的 flatpak 安装
+    pub user_installation: bool,
+    /// 没有应用使用的运行时，或 snap 保留的非当前版本
+    pub removable: bool,
+    pub cleanup: Option<LinuxCleanup>,
+}
+
+// 查找 flatpak 运行时和 snap 的所有版本，标出可移除的部分，按大小降序
+pub fn find_sandbox_entries() -> Vec<SandboxEntry> {
+    collect_sandbox_entries(
+        &flatpak_installations(),
+        Path::new(SNAPS_DIR),
+        Path::new(SNAP_MOUNT_DIR),
+    )
+}
+
+fn collect_sandbox_entries(
+    installations: &[(PathBuf, bool)],
+    snaps_dir: &Path,
+    snap_mount_dir: &Path,
+) -> Vec<SandboxEntry> {
+    let mut entries = flatpak_runtimes(installations);
+    entries.extend(snap_revisions(snaps_dir, snap_mount_dir));
+    entries.retain(|e| e.size_raw > 0);
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries
+}
+
+pub struct FlatpakSnapAnalyzer;
+
+impl Analyzer for FlatpakSnapAnalyzer {
+    fn name(&self) -> &str {
+        "flatpak_snap"
+    }
+
+    fn description(&self) -> &str {
+        "未被任何应用使用的 Flatpak 运行时和 Snap 保留的旧版本"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_sandbox_entries()
+            .into_iter()
+            .filter(|e| e.removable && Path::new(&e.path).starts_with(root))
+            .map(|e| {
+                let note = match e.kind {
+                    SandboxKind::FlatpakRuntime => "没有已安装的应用使用此运行时",
+                    SandboxKind::SnapRevision => "snap 保留的旧版本",
+                };
+                let label = format!("{} {}", e.name, e.version);
+                AnalyzerItem::new(label, e.path, e.size_raw, Some(note.to_string()))
+            })
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+// 系统级与当前用户的 flatpak 安装目录
+fn flatpak_installations() -> Vec<(PathBuf, bool)> {
+    let mut bases = vec![(PathBuf::from("/var/lib/flatpak"), false)];
+    if let Some(home) = home_dir() {
+        bases.push((home.join(".local/share/flatpak"), true));
+    }
+    bases.retain(|(base, _)| base.is_dir());
+    bases
+}
+
+fn flatpak_runtimes(installations: &[(PathBuf, bool)]) -> Vec<SandboxEntry> {
+    // 应用可以使用另一个安装中的运行时，因此汇总所有安装中应用引用的运行时
+    let used: HashSet<(String, String)> = installations
+        .iter()
+        .flat_map(|(base, _)| refs(&base.join("app")))
+        .filter_map(|(_, _, dir)| fs::read_to_string(dir.join("active").join("metadata")).ok())
+        .filter_map(|metadata| parse_runtime(&metadata))
+        .collect();
+
+    let mut entries = Vec::new();
+    for (base, user) in installations {
+        let user = *user;
+        for (id, arch_branch, dir) in refs(&base.join("runtime")) {
+            let branch = arch_branch
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let removable = !used.iter().any(|(used_id, used_branch)| {
+                // 运行时的扩展（如 .Locale、.GL.default）随运行时一起使用
+                (id == *used_id || id.starts_with(&format!("{}.", used_id)))
+                    && branch.starts_with(used_branch.as_str())
+            });
+            let size_raw = dir_size(&dir);
+            entries.push(SandboxEntry {
+                kind: SandboxKind::FlatpakRuntime,
+                name: id,
+                version: arch_branch,
+                path: dir.to_string_lossy().into_owned(),
+                size_raw,
+                size_display: human_readable_size(size_raw),
+                user_installation: user,
+                removable,
+                cleanup: removable.then_some(LinuxCleanup::FlatpakUninstallUnused { user }),
+            });
+        }
+    }
+    entries
+}
+
+// kind_dir 下形如 <id>/<arch>/<branch> 的目录：(id, "arch/branch", 目录)
+fn refs(kind_dir: &Path) -> Vec<(String, String, PathBuf)> {
+    let mut found = Vec::new();
+    for id in sub_dirs(kind_dir) {
+        for arch in sub_dirs(&id) {
+            for branch in sub_dirs(&arch) {
+                found.push((
+                    file_name(&id),
+                    format!("{}/{}", file_name(&arch), file_name(&branch)),
+                    branch,
+                ));
+            }
+        }
+    }
+    found
+}
+
+// 应用 metadata 中 [Application] 段的 runtime=org.gnome.Platform/x86_64/45
+fn parse_runtime(metadata: &str) -> Option<(String, String)> {
+    let value = metadata
+        .lines()
+        .skip_while(|l| l.trim() != "[Application]")
+        .find_map(|l| l.trim().strip_prefix("runtime="))?;
+    let mut parts = value.split('/');
+    let id = parts.next()?.to_string();
+    let branch = parts.nth(1)?.to_string();
+    Some((id, branch))
+}
+
+// /var/lib/snapd/snaps 下的 <name>_<revision>.snap，与 /snap/<name>/current 指向的版本比较
+fn snap_revisions(snaps_dir: &Path, snap_mount_dir: &Path) -> Vec<SandboxEntry> {
+    let Ok(entries) = fs::read_dir(snaps_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path
+                .file_name()?
+                .to_string_lossy()
+                .strip_suffix(".snap")?
+                .to_string();
+            let (name, revision) = stem.rsplit_once('_')?;
+            let current = fs::read_link(snap_mount_dir.join(name).join("current"))
+                .ok()
+                .map(|target| file_name(&target));
+            let removable = current.is_some_and(|c| c != revision);
+            let size_raw = entry.metadata().ok()?.len();
+            Some(SandboxEntry {
+                kind: SandboxKind::SnapRevision,
+                name: name.to_string(),
+                version: revision.to_string(),
+                path: path.to_string_lossy().into_owned(),
+                size_raw,
+                size_display: human_readable_size(size_raw),
+                user_installation: false,
+                removable,
+                cleanup: removable.then(|| LinuxCleanup::RemoveSnapRevision {
+                    name: name.to_string(),
+                    revision: revision.to_string(),
+                }),
+            })
+        })
+        .collect()
+}
+
+fn sub_dirs(path: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::analyzers::fixture_tree;
+
+    #[test]
+    fn marks_unused_runtimes_and_old_snap_revisions_removable() {
+        let root = fixture_tree(
+            "flatpak-snap",
+            &[
+                (
+                    "system/app/org.gimp.GIMP/x86_64/stable/active/files/gimp",
+                    10,
+                ),
+                ("system/runtime/org.gnome.Platform/x86_64/45/files/lib", 100),
+                // 运行时的扩展随运行时一起使用
+                (
+                    "system/runtime/org.gnome.Platform.Locale/x86_64/45/files/zh",
+                    20,
+                ),
+                ("system/runtime/org.gnome.Platform/x86_64/44/files/lib", 80),
+                (
+                    "user/runtime/org.kde.Platform/x86_64/5.15-23.08/files/lib",
+                    50,
+                ),
+                ("snaps/core22_1380.snap", 30),
+                ("snaps/core22_1400.snap", 40),
+                // 没有 current 链接时无法判断，不标为可移除
+                ("snaps/firefox_10.snap", 60),
+                ("snaps/partial/", 0),
+                ("snap/core22/1400/", 0),
+            ],
+        );
+        fs::write(
+            root.join("system/app/org.gimp.GIMP/x86_64/stable/active/metadata"),
+            "[Application]\nname=org.gimp.GIMP\nruntime=org.gnome.Platform/x86_64/45\n",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("1400", root.join("snap/core22/current")).unwrap();
+
+        let entries = collect_sandbox_entries(
+            &[(root.join("system"), false), (root.join("user"), true)],
+            &root.join("snaps"),
+            &root.join("snap"),
+        );
+        let mut found: Vec<(&str, &str, bool)> = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.version.as_str(), e.removable))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("core22", "1380", true),
+                ("core22", "1400", false),
+                ("firefox", "10", false),
+                ("org.gnome.Platform", "x86_64/44", true),
+                ("org.gnome.Platform", "x86_64/45", false),
+                ("org.gnome.Platform.Locale", "x86_64/45", false),
+                ("org.kde.Platform", "x86_64/5.15-23.08", true),
+            ]
+        );
+        let kde = entries
+            .iter()
+            .find(|e| e.name == "org.kde.Platform")
+            .unwrap();
+        assert!(kde.user_installation);
+        assert_eq!(
+            kde.cleanup,
+            Some(LinuxCleanup::FlatpakUninstallUnused { user: true })
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,522 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::drives::volume_space;
+use crate::utils::{dir_size, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 清理日志时保留的大小
+const JOURNAL_KEEP_BYTES: u64 = 200 * 1024 * 1024;
+// paccache 为每个软件包保留的版本数
+const KEEP_VERSIONS: &str = "2";
+// 属于某个内核版本的 apt 软件包名前缀，较长的在前，如 linux-modules-extra-6.8.0-45-generic
+const KERNEL_PACKAGE_PREFIXES: [&str; 5] = [
+    "linux-image-unsigned-",
+    "linux-modules-extra-",
+    "linux-image-",
+    "linux-headers-",
+    "linux-modules-",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Pacman,
+}
+
+impl PackageManager {
+    fn name(self) -> &'static str {
+        match self {
+            PackageManager::Apt => "apt",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Pacman => "pacman",
+        }
+    }
+
+    fn binary(self) -> &'static str {
+        match self {
+            PackageManager::Apt => "/usr/bin/apt-get",
+            PackageManager::Dnf => "/usr/bin/dnf",
+            PackageManager::Pacman => "/usr/bin/pacman",
+        }
+    }
+
+    fn cache_dir(self) -> PathBuf {
+        PathBuf::from(match self {
+            PackageManager::Apt => "/var/cache/apt/archives",
+            PackageManager::Dnf => "/var/cache/dnf",
+            PackageManager::Pacman => "/var/cache/pacman/pkg",
+        })
+    }
+
+    fn installed() -> Vec<PackageManager> {
+        [
+            PackageManager::Apt,
+            PackageManager::Dnf,
+            PackageManager::Pacman,
+        ]
+        .into_iter()
+        .filter(|m| Path::new(m.binary()).exists())
+        .collect()
+    }
+}
+
+// 只允许执行这些固定的清理命令，需要 root 的由管理员辅助进程执行
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LinuxCleanup {
+    /// journalctl --vacuum-size，只保留最近的 keep_bytes 日志
+    VacuumJournal {
+        keep_bytes: u64,
+    },
+    /// 由包管理器移除旧内核，始终保留正在运行的内核和最新安装的内核。
+    /// apt 只卸载 packages 中列出的某个旧版本内核的软件包，不做 autoremove
+    RemoveOldKernels {
+        manager: PackageManager,
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+    CleanPackageCache {
+        manager: PackageManager,
+    },
+    /// flatpak uninstall --unused，user 为 true 时只处理当前用户的安装
+    FlatpakUninstallUnused {
+        user: bool,
+    },
+    /// 删除 snap 保留的旧版本
+    RemoveSnapRevision {
+        name: String,
+        revision: String,
+    },
+}
+
+impl LinuxCleanup {
+    // 只处理当前用户数据的操作不需要管理员权限
+    pub fn needs_root(&self) -> bool {
+        !matches!(self, LinuxCleanup::FlatpakUninstallUnused { user: true })
+    }
+
+    // 要执行的命令，包管理器不支持该操作时为 None
+    pub fn command(&self) -> Option<Vec<String>> {
+        let args: Vec<&str> = match *self {
+            LinuxCleanup::RemoveOldKernels {
+                manager: PackageManager::Apt,
+                ref packages,
+            } => return apt_purge_command(packages, &protected_kernels()),
+            LinuxCleanup::VacuumJournal { keep_bytes } => {
+                return Some(vec![
+                    "journalctl".to_string(),
+                    format!("--vacuum-size={}", keep_bytes),
+                ]);
+            }
+            LinuxCleanup::RemoveOldKernels { manager, .. } => match manager {
+                PackageManager::Dnf => vec!["dnf", "-y", "remove", "--oldinstallonly"],
+                // pacman 只保留一个版本的内核，没有旧内核
+                PackageManager::Apt | PackageManager::Pacman => return None,
+            },
+            LinuxCleanup::CleanPackageCache { manager } => match manager {
+                PackageManager::Apt => vec!["apt-get", "clean"],
+                PackageManager::Dnf => vec!["dnf", "clean", "packages"],
+                PackageManager::Pacman if Path::new("/usr/bin/paccache").exists() => {
+                    vec!["paccache", "-r", "-k", KEEP_VERSIONS]
+                }
+                PackageManager::Pacman => vec!["pacman", "-Sc", "--noconfirm"],
+            },
+            LinuxCleanup::FlatpakUninstallUnused { user } => {
+                let scope = if user { "--user" } else { "--system" };
+                vec![
+                    "flatpak",
+                    "uninstall",
+                    scope,
+                    "--unused",
+                    "-y",
+                    "--noninteractive",
+                ]
+            }
+            LinuxCleanup::RemoveSnapRevision {
+                ref name,
+                ref revision,
+            } => {
+                // 名称和版本作为参数传给 snap，拒绝可能被当作选项的值
+                let valid_name = !name.is_empty()
+                    && !name.starts_with('-')
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+                let valid_revision = revision
+                    .trim_start_matches('x')
+                    .chars()
+                    .all(|c| c.is_ascii_digit())
+                    && !revision.is_empty();
+                if !valid_name || !valid_revision {
+                    return None;
+                }
+                return Some(vec![
+                    "snap".to_string(),
+                    "remove".to_string(),
+                    name.clone(),
+                    format!("--revision={}", revision),
+                ]);
+            }
+        };
+        Some(args.into_iter().map(str::to_string).collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinuxItemKind {
+    Journal,
+    OldKernel,
+    PackageCache,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinuxSystemItem {
+    pub kind: LinuxItemKind,
+    pub label: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub cleanup: Option<LinuxCleanup>,
+    /// 清理时执行的命令，供界面展示
+    pub command: Option<String>,
+}
+
+// 统计 journald 日志、旧内核与包管理器缓存（仅 Linux），按大小降序
+pub fn find_linux_system_items() -> Vec<LinuxSystemItem> {
+    if !cfg!(target_os = "linux") {
+        return Vec::new();
+    }
+    let managers = PackageManager::installed();
+    let mut items = Vec::new();
+
+    if let Some(size_raw) = journal_disk_usage() {
+        items.push(build_item(
+            LinuxItemKind::Journal,
+            "systemd 日志".to_string(),
+            Path::new("/var/log/journal"),
+            size_raw,
+            Some(LinuxCleanup::VacuumJournal {
+                keep_bytes: JOURNAL_KEEP_BYTES,
+            }),
+        ));
+    }
+    let kernel_manager = managers
+        .iter()
+        .copied()
+        .find(|m| *m != PackageManager::Pacman);
+    let kernel_packages = if kernel_manager == Some(PackageManager::Apt) {
+        installed_kernel_packages()
+    } else {
+        Vec::new()
+    };
+    for (version, path, size_raw) in old_kernels() {
+        let cleanup = kernel_manager.map(|manager| LinuxCleanup::RemoveOldKernels {
+            manager,
+            packages: kernel_packages
+                .iter()
+                .filter(|p| package_kernel(p).is_some_and(|k| belongs_to(k, &version)))
+                .cloned()
+                .collect(),
+        });
+        items.push(build_item(
+            LinuxItemKind::OldKernel,
+            format!("内核 {}", version),
+            &path,
+            size_raw,
+            cleanup,
+        ));
+    }
+    for manager in managers {
+        let dir = manager.cache_dir();
+        if dir.is_dir() {
+            items.push(build_item(
+                LinuxItemKind::PackageCache,
+                format!("{} 软件包缓存", manager.name()),
+                &dir,
+                dir_size(&dir),
+                Some(LinuxCleanup::CleanPackageCache { manager }),
+            ));
+        }
+    }
+
+    items.retain(|i| i.size_raw > 0);
+    items.sort_by_key(|i| std::cmp::Reverse(i.size_raw));
+    items
+}
+
+pub struct LinuxSystemAnalyzer;
+
+impl Analyzer for LinuxSystemAnalyzer {
+    fn name(&self) -> &str {
+        "linux_system"
+    }
+
+    fn description(&self) -> &str {
+        "systemd 日志、旧内核以及 apt/dnf/pacman 的软件包缓存"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_linux_system_items()
+            .into_iter()
+            .filter(|i| Path::new(&i.path).starts_with(root))
+            .map(|i| AnalyzerItem::new(i.label, i.path, i.size_raw, i.command))
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+// 执行清理命令，返回根分区可用空间的增加量。除只处理当前用户数据的操作外都在管理员辅助进程中执行
+pub(crate) fn run_cleanup(cleanup: &LinuxCleanup) -> Result<u64, String> {
+    if !cfg!(target_os = "linux") {
+        return Err("仅支持 Linux".to_string());
+    }
+    let command = cleanup
+        .command()
+        .ok_or_else(|| "不支持此清理操作".to_string())?;
+    let before = volume_space(Path::new("/")).map(|(_, free)| free);
+    let output = Command::new(&command[0])
+        .args(&command[1..])
+        .output()
+        .map_err(|e| format!("无法运行 {}: {}", command[0], e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} 执行失败: {}",
+            command.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let after = volume_space(Path::new("/")).map(|(_, free)| free);
+    Ok(match (before, after) {
+        (Some(before), Some(after)) => after.saturating_sub(before),
+        _ => 0,
+    })
+}
+
+fn build_item(
+    kind: LinuxItemKind,
+    label: String,
+    path: &Path,
+    size_raw: u64,
+    cleanup: Option<LinuxCleanup>,
+) -> LinuxSystemItem {
+    LinuxSystemItem {
+        kind,
+        label,
+        path: path.to_string_lossy().into_owned(),
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        command: cleanup
+            .as_ref()
+            .and_then(|c| c.command())
+            .map(|c| c.join(" ")),
+        cleanup,
+    }
+}
+
+fn journal_disk_usage() -> Option<u64> {
+    let output = Command::new("journalctl")
+        .arg("--disk-usage")
+        .output()
+        .ok()?;
+    parse_journal_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+// 解析 "Archived and active journals take up 1.2G in the file system."
+fn parse_journal_usage(output: &str) -> Option<u64> {
+    let value = output.split("take up ").nth(1)?.split_whitespace().next()?;
+    let (number, scale) = match value.char_indices().last()? {
+        (i, 'K') => (&value[..i], 1u64 << 10),
+        (i, 'M') => (&value[..i], 1 << 20),
+        (i, 'G') => (&value[..i], 1 << 30),
+        (i, 'T') => (&value[..i], 1 << 40),
+        (i, 'B') => (&value[..i], 1),
+        _ => (value, 1),
+    };
+    let number: f64 = number.parse().ok()?;
+    Some((number * scale as f64) as u64)
+}
+
+// /lib/modules 下已安装的内核版本，按版本从旧到新
+fn installed_kernels() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/lib/modules") else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    versions.sort_by_key(|v| version_key(v));
+    versions
+}
+
+fn running_kernel() -> String {
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+// 不能卸载的内核：正在运行的和最新安装的
+fn protected_kernels() -> Vec<String> {
+    let mut protected = vec![running_kernel()];
+    protected.extend(installed_kernels().pop());
+    protected.retain(|v| !v.is_empty());
+    protected
+}
+
+// 已安装的 linux-image-*、linux-headers-*、linux-modules-* 软件包
+fn installed_kernel_packages() -> Vec<String> {
+    let Ok(output) = Command::new("dpkg-query")
+        .args(["-W", "-f=${Package}\\n"])
+        .args(["linux-image-*", "linux-headers-*", "linux-modules-*"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|p| package_kernel(p).is_some())
+        .map(str::to_string)
+        .collect()
+}
+
+// 软件包名中的内核版本部分，如 linux-headers-6.8.0-45 中的 6.8.0-45；
+// linux-image-generic 等元软件包不以数字开头，返回 None
+fn package_kernel(package: &str) -> Option<&str> {
+    let valid = package
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '+'));
+    if !valid {
+        return None;
+    }
+    KERNEL_PACKAGE_PREFIXES
+        .iter()
+        .find_map(|prefix| package.strip_prefix(prefix))
+        .filter(|kernel| kernel.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+// 软件包的版本部分是否属于内核 version：完全相同，或是不带变体后缀的公共部分
+// （linux-headers-6.8.0-45 属于 6.8.0-45-generic）
+fn belongs_to(package_kernel: &str, version: &str) -> bool {
+    version == package_kernel
+        || version
+            .strip_prefix(package_kernel)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+// 卸载 packages 的 apt-get 命令。执行前重新检查：只接受内核软件包，
+// 属于正在运行或最新内核的软件包一律拒绝，此时返回 None
+fn apt_purge_command(packages: &[String], protected: &[String]) -> Option<Vec<String>> {
+    if packages.is_empty() {
+        return None;
+    }
+    for package in packages {
+        let kernel = package_kernel(package)?;
+        if protected.iter().any(|v| belongs_to(kernel, v)) {
+            return None;
+        }
+    }
+    let mut command: Vec<String> = ["apt-get", "-y", "purge"]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    command.extend(packages.iter().cloned());
+    Some(command)
+}
+
+// 已安装但既不是正在运行也不是最新版本的内核：(版本, 模块目录, 模块与 /boot 文件的总大小)
+fn old_kernels() -> Vec<(String, PathBuf, u64)> {
+    let running = running_kernel();
+    let modules = Path::new("/lib/modules");
+    let mut versions = installed_kernels();
+    let newest = versions.pop();
+
+    let boot_files: Vec<(String, u64)> = fs::read_dir("/boot")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let size = e.metadata().ok().filter(|m| m.is_file())?.len();
+                    Some((e.file_name().to_string_lossy().into_owned(), size))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    versions
+        .into_iter()
+        .filter(|v| *v != running && Some(v) != newest.as_ref())
+        .map(|version| {
+            let dir = modules.join(&version);
+            let boot: u64 = boot_files
+                .iter()
+                .filter(|(name, _)| name.contains(&version))
+                .map(|(_, size)| size)
+                .sum();
+            let size = dir_size(&dir).saturating_add(boot);
+            (version, dir, size)
+        })
+        .collect()
+}
+
+// 按数字段比较内核版本，如 6.8.0-45-generic 排在 6.8.0-100-generic 之前
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn apt_purges_only_listed_old_kernel_packages() {
+        let protected = names(&["6.8.0-50-generic", "6.8.0-51-generic"]);
+        let old = names(&[
+            "linux-image-6.8.0-45-generic",
+            "linux-modules-extra-6.8.0-45-generic",
+            "linux-headers-6.8.0-45",
+        ]);
+        assert_eq!(
+            apt_purge_command(&old, &protected).unwrap(),
+            names(&[
+                "apt-get",
+                "-y",
+                "purge",
+                "linux-image-6.8.0-45-generic",
+                "linux-modules-extra-6.8.0-45-generic",
+                "linux-headers-6.8.0-45",
+            ])
+        );
+        // 正在运行的内核的公共 headers、元软件包、非内核软件包和空列表都被拒绝
+        assert!(apt_purge_command(&names(&["linux-headers-6.8.0-50"]), &protected).is_none());
+        assert!(apt_purge_command(&names(&["linux-image-generic"]), &protected).is_none());
+        assert!(apt_purge_command(&names(&["libc6"]), &protected).is_none());
+        assert!(apt_purge_command(&names(&["linux-image-6.8.0-45 ; rm"]), &protected).is_none());
+        assert!(apt_purge_command(&[], &protected).is_none());
+    }
+
+    #[test]
+    fn kernel_packages_are_matched_by_version() {
+        assert_eq!(
+            package_kernel("linux-modules-extra-6.8.0-45-generic"),
+            Some("6.8.0-45-generic")
+        );
+        assert!(belongs_to("6.8.0-45", "6.8.0-45-generic"));
+        assert!(belongs_to("6.8.0-45-generic", "6.8.0-45-generic"));
+        assert!(!belongs_to("6.8.0-4", "6.8.0-45-generic"));
+        assert!(!belongs_to("6.8.0-45-generic", "6.8.0-45-lowlatency"));
+    }
+}
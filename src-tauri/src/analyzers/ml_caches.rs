@@ -0,0 +1,317 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::utils::{dir_size, home_dir, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// 不在已知缓存目录中的模型文件至少这么大才报告
+const MIN_MODEL_FILE_BYTES: u64 = 100 * 1024 * 1024;
+const MODEL_EXTENSIONS: [&str; 3] = ["safetensors", "ckpt", "gguf"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MlSource {
+    /// Hugging Face Hub 下载的模型、数据集
+    HuggingFace,
+    /// torch.hub 下载的模型与权重
+    PyTorch,
+    /// Keras、TensorFlow Hub 下载的模型与数据集
+    TensorFlow,
+    /// conda 的软件包缓存
+    Conda,
+    /// 扫描目录中的独立模型文件
+    ModelFile,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MlCacheEntry {
+    pub source: MlSource,
+    /// 模型名称，如 meta-llama/Llama-2-7b
+    pub name: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// 最后访问时间，文件系统不记录访问时间时为修改时间
+    pub last_used: Option<SystemTime>,
+}
+
+// 查找本机的机器学习缓存，以及 root 下的大模型文件，按大小降序
+pub fn find_ml_caches(root: &Path) -> Vec<MlCacheEntry> {
+    collect_ml_caches(
+        root,
+        &huggingface_roots(),
+        &framework_dirs(),
+        &conda_pkgs_dirs(),
+    )
+}
+
+// 在给定的 Hugging Face、框架和 conda 缓存目录中查找，并查找 root 下的模型文件
+fn collect_ml_caches(
+    root: &Path,
+    hubs: &[PathBuf],
+    frameworks: &[(MlSource, PathBuf)],
+    conda_pkgs: &[PathBuf],
+) -> Vec<MlCacheEntry> {
+    let mut entries = Vec::new();
+
+    for hub in hubs {
+        // 缓存目录形如 models--org--name、datasets--org--name
+        for dir in sub_dirs(hub) {
+            let dir_name = file_name(&dir);
+            let Some((_, name)) = dir_name.split_once("--") else {
+                continue;
+            };
+            let name = name.replace("--", "/");
+            entries.push(build_entry(MlSource::HuggingFace, name, &dir));
+        }
+    }
+    for (source, dir) in frameworks {
+        for item in children(dir) {
+            entries.push(build_entry(*source, file_name(&item), &item));
+        }
+    }
+    for dir in conda_pkgs {
+        // 以安装目录命名，如 miniconda3
+        let name = dir.parent().map(file_name).unwrap_or_default();
+        entries.push(build_entry(MlSource::Conda, name, dir));
+    }
+
+    let known: Vec<PathBuf> = entries.iter().map(|e| PathBuf::from(&e.path)).collect();
+    let mut files = Vec::new();
+    collect_model_files(root, &known, &mut files);
+    entries.extend(
+        files
+            .iter()
+            .map(|file| build_entry(MlSource::ModelFile, file_name(file), file)),
+    );
+
+    entries.retain(|e| e.size_raw > 0);
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries
+}
+
+pub struct MlCachesAnalyzer;
+
+impl Analyzer for MlCachesAnalyzer {
+    fn name(&self) -> &str {
+        "machine_learning"
+    }
+
+    fn description(&self) -> &str {
+        "Hugging Face、PyTorch、TensorFlow 模型缓存，conda 软件包缓存及大型模型文件"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_ml_caches(root)
+            .into_iter()
+            .filter(|e| Path::new(&e.path).starts_with(root))
+            .map(|e| AnalyzerItem::new(e.name, e.path, e.size_raw, None))
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+fn build_entry(source: MlSource, name: String, path: &Path) -> MlCacheEntry {
+    let metadata = fs::symlink_metadata(path).ok();
+    let size_raw = match &metadata {
+        Some(m) if m.is_dir() => dir_size(path),
+        Some(m) => m.len(),
+        None => 0,
+    };
+    MlCacheEntry {
+        source,
+        name,
+        path: path.to_string_lossy().into_owned(),
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        last_used: metadata.and_then(|m| m.accessed().or_else(|_| m.modified()).ok()),
+    }
+}
+
+// 递归查找模型文件，跳过已作为缓存统计的目录
+fn collect_model_files(dir: &Path, known: &[PathBuf], found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if !known.iter().any(|k| path.starts_with(k)) {
+                collect_model_files(&path, known, found);
+            }
+        } else if metadata.len() >= MIN_MODEL_FILE_BYTES
+            && path
+                .extension()
+                .is_some_and(|ext| MODEL_EXTENSIONS.iter().any(|m| ext.eq_ignore_ascii_case(m)))
+        {
+            found.push(path);
+        }
+    }
+}
+
+fn huggingface_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(cache) = std::env::var_os("HF_HUB_CACHE") {
+        roots.push(PathBuf::from(cache));
+    }
+    if let Some(hf_home) = std::env::var_os("HF_HOME") {
+        roots.push(PathBuf::from(hf_home).join("hub"));
+    }
+    if let Some(home) = home_dir() {
+        roots.push(home.join(".cache").join("huggingface").join("hub"));
+    }
+    dedup_existing(roots)
+}
+
+fn framework_dirs() -> Vec<(MlSource, PathBuf)> {
+    let mut dirs = Vec::new();
+    let torch_home = std::env::var_os("TORCH_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|h| h.join(".cache").join("torch")));
+    if let Some(torch) = torch_home {
+        dirs.push((MlSource::PyTorch, torch.join("hub").join("checkpoints")));
+        dirs.push((MlSource::PyTorch, torch.join("hub")));
+    }
+    if let Some(cache) = std::env::var_os("TFHUB_CACHE_DIR") {
+        dirs.push((MlSource::TensorFlow, PathBuf::from(cache)));
+    }
+    if let Some(home) = home_dir() {
+        dirs.push((MlSource::TensorFlow, home.join(".keras").join("models")));
+        dirs.push((MlSource::TensorFlow, home.join(".keras").join("datasets")));
+    }
+    dirs.retain(|(_, dir)| dir.is_dir());
+    dirs
+}
+
+fn conda_pkgs_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("CONDA_PKGS_DIRS")
+        .map(|v| std::env::split_paths(&v).collect())
+        .unwrap_or_default();
+    if let Some(home) = home_dir() {
+        for install in [
+            "anaconda3",
+            "miniconda3",
+            "miniforge3",
+            "mambaforge",
+            ".conda",
+        ] {
+            dirs.push(home.join(install).join("pkgs"));
+        }
+    }
+    dedup_existing(dirs)
+}
+
+// 目录的直接子项；torch hub 的 checkpoints 目录单独列出，不作为 hub 的子项
+fn children(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| file_name(p) != "checkpoints")
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn dedup_existing(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut result: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if root.is_dir() && !result.contains(&root) {
+            result.push(root);
+        }
+    }
+    result
+}
+
+fn sub_dirs(path: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::fixture_tree;
+
+    #[test]
+    fn finds_framework_caches_and_large_model_files() {
+        let root = fixture_tree(
+            "ml-caches",
+            &[
+                ("hub/models--meta-llama--Llama-2-7b/blobs/weights", 50),
+                ("hub/datasets--squad/blobs/train", 30),
+                // 空缓存与不符合命名的目录不报告
+                ("hub/models--empty--repo/", 0),
+                ("hub/.locks/lock", 10),
+                ("hub/version.txt", 10),
+                ("torch/hub/checkpoints/resnet50.pth", 40),
+                ("torch/hub/facebookresearch_dino_main/hubconf.py", 20),
+                ("miniconda3/pkgs/numpy-1.26/lib.so", 60),
+                ("work/small.gguf", 10),
+            ],
+        );
+        // 稀疏文件：长度超过阈值但不实际占用空间
+        for large in [
+            "work/model.SafeTensors",
+            "work/weights.bin",
+            // 已作为缓存统计的目录中的模型文件不重复报告
+            "hub/models--meta-llama--Llama-2-7b/blobs/model.safetensors",
+        ] {
+            fs::File::create(root.join(large))
+                .unwrap()
+                .set_len(MIN_MODEL_FILE_BYTES)
+                .unwrap();
+        }
+
+        let torch = root.join("torch/hub");
+        let entries = collect_ml_caches(
+            &root,
+            &[root.join("hub")],
+            &[
+                (MlSource::PyTorch, torch.join("checkpoints")),
+                (MlSource::PyTorch, torch.clone()),
+            ],
+            &[root.join("miniconda3/pkgs")],
+        );
+        let mut found: Vec<(MlSource, &str)> = entries
+            .iter()
+            .map(|e| (e.source, e.name.as_str()))
+            .collect();
+        found.sort_by_key(|(_, name)| name.to_string());
+        assert_eq!(
+            found,
+            [
+                (MlSource::PyTorch, "facebookresearch_dino_main"),
+                (MlSource::HuggingFace, "meta-llama/Llama-2-7b"),
+                (MlSource::Conda, "miniconda3"),
+                (MlSource::ModelFile, "model.SafeTensors"),
+                (MlSource::PyTorch, "resnet50.pth"),
+                (MlSource::HuggingFace, "squad"),
+            ]
+        );
+        let model = entries
+            .iter()
+            .find(|e| e.source == MlSource::ModelFile)
+            .unwrap();
+        assert_eq!(model.size_raw, MIN_MODEL_FILE_BYTES);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
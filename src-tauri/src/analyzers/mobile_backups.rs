@@ -0,0 +1,270 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::utils::{dir_size, home_dir, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MobileBackupKind {
+    /// iTunes / Finder 的 iPhone、iPad 备份
+    IosBackup,
+    /// Android Studio 创建的虚拟设备（AVD）
+    AndroidAvd,
+    /// Android SDK 下载的模拟器系统镜像
+    AndroidSystemImage,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MobileBackupEntry {
+    /// 备份类型
+    pub kind: MobileBackupKind,
+    /// 设备名称或镜像名称
+    pub name: String,
+    pub path: String,
+    /// 原始大小
+    pub size_raw: u64,
+    /// 大小显示
+    pub size_display: String,
+    /// 最后修改时间
+    pub modified_time: Option<SystemTime>,
+}
+
+// 查找本机所有 iOS 备份、Android 虚拟设备和系统镜像，按大小降序返回
+pub fn find_backups() -> Vec<MobileBackupEntry> {
+    collect_backups(&ios_backup_roots(), &avd_roots(), &android_sdk_roots())
+}
+
+// 在给定的 iOS 备份目录、AVD 目录和 Android SDK 目录中查找
+fn collect_backups(
+    ios_roots: &[PathBuf],
+    avd_roots: &[PathBuf],
+    sdk_roots: &[PathBuf],
+) -> Vec<MobileBackupEntry> {
+    let mut entries = Vec::new();
+
+    for root in ios_roots {
+        for dir in sub_dirs(root) {
+            let name = ios_device_name(&dir).unwrap_or_else(|| file_name(&dir));
+            entries.push(build_entry(MobileBackupKind::IosBackup, name, &dir));
+        }
+    }
+
+    for root in avd_roots {
+        for dir in sub_dirs(root) {
+            // 每个虚拟设备对应一个 xxx.avd 目录
+            if dir.extension().is_some_and(|ext| ext == "avd") {
+                let name = dir
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                entries.push(build_entry(MobileBackupKind::AndroidAvd, name, &dir));
+            }
+        }
+    }
+
+    for sdk in sdk_roots {
+        // system-images/android-34 这一层即为一个 API 级别
+        for dir in sub_dirs(&sdk.join("system-images")) {
+            let name = file_name(&dir);
+            entries.push(build_entry(
+                MobileBackupKind::AndroidSystemImage,
+                name,
+                &dir,
+            ));
+        }
+    }
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries
+}
+
+// 作为通用分析器使用时，只返回位于 root 下的备份和镜像
+pub struct MobileBackupsAnalyzer;
+
+impl Analyzer for MobileBackupsAnalyzer {
+    fn name(&self) -> &str {
+        "mobile_backups"
+    }
+
+    fn description(&self) -> &str {
+        "iOS 备份、Android 虚拟设备与模拟器系统镜像"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_backups()
+            .into_iter()
+            .filter(|e| Path::new(&e.path).starts_with(root))
+            .map(|e| AnalyzerItem::new(e.name, e.path, e.size_raw, None))
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+fn build_entry(kind: MobileBackupKind, name: String, dir: &Path) -> MobileBackupEntry {
+    let size_raw = dir_size(dir);
+    MobileBackupEntry {
+        kind,
+        name,
+        path: dir.to_string_lossy().into_owned(),
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        modified_time: fs::metadata(dir).and_then(|m| m.modified()).ok(),
+    }
+}
+
+fn ios_backup_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(home) = home_dir() {
+        // macOS Finder / iTunes
+        roots.push(home.join("Library/Application Support/MobileSync/Backup"));
+        // Microsoft Store 版 iTunes
+        roots.push(home.join("Apple").join("MobileSync").join("Backup"));
+    }
+    // 传统安装版 iTunes
+    if let Some(appdata) = std::env::var_os("APPDATA") {
+        roots.push(
+            PathBuf::from(appdata)
+                .join("Apple Computer")
+                .join("MobileSync")
+                .join("Backup"),
+        );
+    }
+    dedup_existing(roots)
+}
+
+fn avd_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(avd_home) = std::env::var_os("ANDROID_AVD_HOME") {
+        roots.push(PathBuf::from(avd_home));
+    }
+    if let Some(user_home) = std::env::var_os("ANDROID_USER_HOME") {
+        roots.push(PathBuf::from(user_home).join("avd"));
+    }
+    if let Some(home) = home_dir() {
+        roots.push(home.join(".android").join("avd"));
+    }
+    dedup_existing(roots)
+}
+
+fn android_sdk_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for var in ["ANDROID_HOME", "ANDROID_SDK_ROOT"] {
+        if let Some(sdk) = std::env::var_os(var) {
+            roots.push(PathBuf::from(sdk));
+        }
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        roots.push(PathBuf::from(local).join("Android").join("Sdk"));
+    }
+    if let Some(home) = home_dir() {
+        roots.push(home.join("Library/Android/sdk"));
+        roots.push(home.join("Android").join("Sdk"));
+    }
+    dedup_existing(roots)
+}
+
+// 去掉不存在或重复的候选目录
+fn dedup_existing(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut result: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if root.is_dir() && !result.contains(&root) {
+            result.push(root);
+        }
+    }
+    result
+}
+
+fn sub_dirs(path: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// 从备份目录下的 Info.plist（XML 格式）读取设备名称
+fn ios_device_name(backup_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(backup_dir.join("Info.plist")).ok()?;
+    let key_pos = content.find("<key>Device Name</key>")?;
+    let rest = &content[key_pos..];
+    let start = rest.find("<string>")? + "<string>".len();
+    let end = rest[start..].find("</string>")?;
+    Some(rest[start..start + end].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::fixture_tree;
+
+    #[test]
+    fn finds_backups_devices_and_system_images() {
+        let root = fixture_tree(
+            "mobile-backups",
+            &[
+                ("Backup/00008030-A/Manifest.db", 400),
+                // 没有 Info.plist 的备份以目录名命名
+                ("Backup/00008030-B/Manifest.db", 300),
+                ("Backup/.DS_Store", 10),
+                ("avd/Pixel_7.avd/userdata.img", 200),
+                ("avd/Pixel_7.ini", 10),
+                ("avd/snapshots/default", 1000),
+                ("sdk/system-images/android-34/google_apis/system.img", 100),
+                ("sdk/platforms/android-34/android.jar", 1000),
+            ],
+        );
+        fs::write(
+            root.join("Backup/00008030-A/Info.plist"),
+            "<dict>\n<key>Device Name</key>\n<string> Work iPhone </string>\n</dict>",
+        )
+        .unwrap();
+
+        let found = collect_backups(
+            &[root.join("Backup")],
+            &[root.join("avd")],
+            &[root.join("sdk")],
+        );
+        let found: Vec<(MobileBackupKind, &str, &Path)> = found
+            .iter()
+            .map(|e| (e.kind, e.name.as_str(), Path::new(&e.path)))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    MobileBackupKind::IosBackup,
+                    "Work iPhone",
+                    root.join("Backup/00008030-A").as_path()
+                ),
+                (
+                    MobileBackupKind::IosBackup,
+                    "00008030-B",
+                    root.join("Backup/00008030-B").as_path()
+                ),
+                (
+                    MobileBackupKind::AndroidAvd,
+                    "Pixel_7",
+                    root.join("avd/Pixel_7.avd").as_path()
+                ),
+                (
+                    MobileBackupKind::AndroidSystemImage,
+                    "android-34",
+                    root.join("sdk/system-images/android-34").as_path()
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+// 各类专项空间分析器
+pub mod engine_caches;
+pub mod flatpak_snap;
+pub mod linux_system;
+pub mod ml_caches;
+pub mod mobile_backups;
+pub mod plugin;
+pub mod raw_jpeg;
+pub mod screenshots;
+
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// 专项分析器：在指定目录下找出某一类占用空间的内容。
+// 内置分析器和外部插件都实现该 trait，由 AnalyzerRegistry 统一管理
+pub trait Analyzer: Send + Sync {
+    // 唯一名称，作为调用时的标识
+    fn name(&self) -> &str;
+    // 给用户看的简短说明
+    fn description(&self) -> &str;
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String>;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnalyzerItem {
+    /// 显示名称，如项目名、设备名
+    pub label: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// 补充说明，如“可安全删除，重新构建时会再生成”
+    pub note: Option<String>,
+}
+
+impl AnalyzerItem {
+    pub fn new(label: String, path: String, size_raw: u64, note: Option<String>) -> Self {
+        AnalyzerItem {
+            label,
+            path,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            note,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnalyzerReport {
+    pub analyzer: String,
+    /// 按大小降序
+    pub items: Vec<AnalyzerItem>,
+    pub total_raw: u64,
+    pub total_display: String,
+}
+
+impl AnalyzerReport {
+    pub fn new(analyzer: &str, mut items: Vec<AnalyzerItem>) -> Self {
+        items.sort_by_key(|i| std::cmp::Reverse(i.size_raw));
+        let total_raw = items
+            .iter()
+            .fold(0u64, |acc, i| acc.saturating_add(i.size_raw));
+        AnalyzerReport {
+            analyzer: analyzer.to_string(),
+            items,
+            total_raw,
+            total_display: human_readable_size(total_raw),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnalyzerInfo {
+    pub name: String,
+    pub description: String,
+    /// 插件文件路径，内置分析器为 None
+    pub plugin_path: Option<String>,
+}
+
+struct Registered {
+    analyzer: Arc<dyn Analyzer>,
+    plugin_path: Option<PathBuf>,
+}
+
+// 已注册的分析器，名称重复时后注册的被忽略
+#[derive(Default)]
+pub struct AnalyzerRegistry {
+    analyzers: Vec<Registered>,
+}
+
+impl AnalyzerRegistry {
+    // 只包含内置分析器
+    pub fn with_builtins() -> Self {
+        let mut registry = AnalyzerRegistry::default();
+        registry.register(Arc::new(mobile_backups::MobileBackupsAnalyzer), None);
+        registry.register(Arc::new(engine_caches::EngineCachesAnalyzer), None);
+        registry.register(Arc::new(ml_caches::MlCachesAnalyzer), None);
+        registry.register(Arc::new(raw_jpeg::RawJpegAnalyzer), None);
+        registry.register(Arc::new(screenshots::ScreenshotsAnalyzer), None);
+        registry.register(Arc::new(linux_system::LinuxSystemAnalyzer), None);
+        registry.register(Arc::new(flatpak_snap::FlatpakSnapAnalyzer), None);
+        registry
+    }
+
+    pub fn register(&mut self, analyzer: Arc<dyn Analyzer>, plugin_path: Option<PathBuf>) {
+        if self.get(analyzer.name()).is_some() {
+            eprintln!("分析器名称重复，已忽略: {}", analyzer.name());
+            return;
+        }
+        self.analyzers.push(Registered {
+            analyzer,
+            plugin_path,
+        });
+    }
+
+    // 加载插件目录下的所有动态库，返回加载失败的文件及原因
+    pub fn load_plugins(&mut self, dir: &Path) -> Vec<(String, String)> {
+        self.analyzers.retain(|r| r.plugin_path.is_none());
+        let mut errors = Vec::new();
+        for path in plugin::plugin_files(dir) {
+            match plugin::PluginAnalyzer::load(&path) {
+                Ok(analyzer) => self.register(Arc::new(analyzer), Some(path)),
+                Err(e) => errors.push((path.to_string_lossy().into_owned(), e)),
+            }
+        }
+        errors
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Analyzer>> {
+        self.analyzers
+            .iter()
+            .find(|r| r.analyzer.name() == name)
+            .map(|r| r.analyzer.clone())
+    }
+
+    pub fn list(&self) -> Vec<AnalyzerInfo> {
+        self.analyzers
+            .iter()
+            .map(|r| AnalyzerInfo {
+                name: r.analyzer.name().to_string(),
+                description: r.analyzer.description().to_string(),
+                plugin_path: r
+                    .plugin_path
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().into_owned()),
+            })
+            .collect()
+    }
+}
+
+// 在临时目录中按 (相对路径, 字节数) 创建测试用的目录树，以 / 结尾的路径创建为空目录
+#[cfg(test)]
+pub(crate) fn fixture_tree(name: &str, files: &[(&str, usize)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("disksight-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (relative, size) in files {
+        let path = root.join(relative);
+        if relative.ends_with('/') {
+            std::fs::create_dir_all(&path).unwrap();
+        } else {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, vec![0u8; *size]).unwrap();
+        }
+    }
+    root
+}
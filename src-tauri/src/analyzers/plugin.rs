@@ -0,0 +1,135 @@
+// 以动态库形式加载的外部分析器。
+//
+// 插件需导出以下 C ABI 函数，字符串均为 UTF-8、以 0 结尾：
+//   disksight_analyzer_name() -> *const c_char          分析器名称（静态字符串）
+//   disksight_analyzer_description() -> *const c_char   说明（静态字符串）
+//   disksight_analyzer_scan(root: *const c_char) -> *mut c_char
+//       返回 JSON：{"items": [{"label", "path", "size_raw", "note"}]} 或 {"error": "..."}
+//   disksight_analyzer_free(s: *mut c_char)             释放 scan 返回的字符串
+//
+// 插件与应用运行在同一进程内，只应放入可信的插件
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use libloading::Library;
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type StrFn = unsafe extern "C" fn() -> *const c_char;
+type ScanFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+#[derive(Deserialize)]
+struct PluginItem {
+    label: String,
+    path: String,
+    size_raw: u64,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    items: Vec<PluginItem>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+pub struct PluginAnalyzer {
+    name: String,
+    description: String,
+    scan_fn: ScanFn,
+    free_fn: FreeFn,
+    // 函数指针依赖动态库保持加载状态，必须与其一起持有
+    _library: Library,
+}
+
+impl PluginAnalyzer {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let library = unsafe { Library::new(path) }.map_err(|e| format!("无法加载插件: {}", e))?;
+        unsafe {
+            let name_fn = *library
+                .get::<StrFn>(b"disksight_analyzer_name\0")
+                .map_err(|e| format!("缺少 disksight_analyzer_name: {}", e))?;
+            let description_fn = *library
+                .get::<StrFn>(b"disksight_analyzer_description\0")
+                .map_err(|e| format!("缺少 disksight_analyzer_description: {}", e))?;
+            let scan_fn = *library
+                .get::<ScanFn>(b"disksight_analyzer_scan\0")
+                .map_err(|e| format!("缺少 disksight_analyzer_scan: {}", e))?;
+            let free_fn = *library
+                .get::<FreeFn>(b"disksight_analyzer_free\0")
+                .map_err(|e| format!("缺少 disksight_analyzer_free: {}", e))?;
+            let name = static_str(name_fn()).ok_or("插件名称为空")?;
+            Ok(PluginAnalyzer {
+                name,
+                description: static_str(description_fn()).unwrap_or_default(),
+                scan_fn,
+                free_fn,
+                _library: library,
+            })
+        }
+    }
+}
+
+impl Analyzer for PluginAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let root = CString::new(root.to_string_lossy().as_bytes())
+            .map_err(|_| "路径中包含空字符".to_string())?;
+        let json = unsafe {
+            let raw = (self.scan_fn)(root.as_ptr());
+            if raw.is_null() {
+                return Err(format!("插件 {} 没有返回结果", self.name));
+            }
+            let json = CStr::from_ptr(raw).to_string_lossy().into_owned();
+            (self.free_fn)(raw);
+            json
+        };
+        let output: PluginOutput = serde_json::from_str(&json)
+            .map_err(|e| format!("插件 {} 返回的结果格式错误: {}", self.name, e))?;
+        if let Some(error) = output.error {
+            return Err(error);
+        }
+        let items = output
+            .items
+            .into_iter()
+            .map(|i| AnalyzerItem::new(i.label, i.path, i.size_raw, i.note))
+            .collect();
+        Ok(AnalyzerReport::new(&self.name, items))
+    }
+}
+
+unsafe fn static_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let value = CStr::from_ptr(ptr).to_string_lossy().trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+// 插件目录下当前平台的动态库文件
+pub fn plugin_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
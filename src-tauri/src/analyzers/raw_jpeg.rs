@@ -0,0 +1,210 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::normalize::fold;
+use crate::utils::{human_readable_size, sum_sizes};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RAW_EXTENSIONS: [&str; 4] = ["cr2", "nef", "arw", "dng"];
+const JPEG_EXTENSIONS: [&str; 2] = ["jpg", "jpeg"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhotoPair {
+    /// 不含扩展名的文件名，如 IMG_0001
+    pub stem: String,
+    pub raw_path: String,
+    pub raw_bytes: u64,
+    pub jpeg_path: String,
+    pub jpeg_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShootFolder {
+    pub path: String,
+    pub pairs: Vec<PhotoPair>,
+    /// 删除所有配对的 RAW 可释放的空间
+    pub raw_bytes: u64,
+    pub raw_display: String,
+    /// 删除所有配对的 JPEG 可释放的空间
+    pub jpeg_bytes: u64,
+    pub jpeg_display: String,
+}
+
+// 查找 root 下同一目录中文件名相同的 RAW 与 JPEG，按拍摄目录分组，按 RAW 大小降序
+pub fn find_raw_jpeg_pairs(root: &Path) -> Vec<ShootFolder> {
+    let mut folders = walk(root);
+    folders.sort_by_key(|f| std::cmp::Reverse(f.raw_bytes));
+    folders
+}
+
+pub struct RawJpegAnalyzer;
+
+impl Analyzer for RawJpegAnalyzer {
+    fn name(&self) -> &str {
+        "raw_jpeg_pairs"
+    }
+
+    fn description(&self) -> &str {
+        "同时保存了 RAW（CR2/NEF/ARW/DNG）与 JPEG 的照片，按拍摄目录统计删除其中一种可释放的空间"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_raw_jpeg_pairs(root)
+            .into_iter()
+            .map(|f| {
+                let label = Path::new(&f.path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| f.path.clone());
+                let note = format!(
+                    "{} 对照片，删除 JPEG 可释放 {}，删除 RAW 可释放 {}",
+                    f.pairs.len(),
+                    f.jpeg_display,
+                    f.raw_display
+                );
+                AnalyzerItem::new(label, f.path, f.jpeg_bytes, Some(note))
+            })
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+fn walk(dir: &Path) -> Vec<ShootFolder> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            dirs.push(path);
+        } else {
+            files.push((path, metadata.len()));
+        }
+    }
+
+    let found: Vec<Vec<ShootFolder>> = dirs.par_iter().map(|d| walk(d)).collect();
+    let mut folders = found.concat();
+    let pairs = pair_files(files);
+    if !pairs.is_empty() {
+        let raw_bytes = sum_sizes(pairs.iter().map(|p| p.raw_bytes));
+        let jpeg_bytes = sum_sizes(pairs.iter().map(|p| p.jpeg_bytes));
+        folders.push(ShootFolder {
+            path: dir.to_string_lossy().into_owned(),
+            pairs,
+            raw_bytes,
+            raw_display: human_readable_size(raw_bytes),
+            jpeg_bytes,
+            jpeg_display: human_readable_size(jpeg_bytes),
+        });
+    }
+    folders
+}
+
+// 按不区分大小写与 Unicode 规范化形式的文件名配对，同名的 JPEG 有多个时（.jpg 与 .jpeg）只取第一个
+fn pair_files(files: Vec<(PathBuf, u64)>) -> Vec<PhotoPair> {
+    let mut raws: HashMap<String, (PathBuf, u64)> = HashMap::new();
+    let mut jpegs: HashMap<String, (PathBuf, u64)> = HashMap::new();
+    for (path, size) in files {
+        let (Some(stem), Some(ext)) = (path.file_stem(), path.extension()) else {
+            continue;
+        };
+        let key = fold(&stem.to_string_lossy());
+        let ext = ext.to_string_lossy().to_ascii_lowercase();
+        if RAW_EXTENSIONS.contains(&ext.as_str()) {
+            raws.entry(key).or_insert((path, size));
+        } else if JPEG_EXTENSIONS.contains(&ext.as_str()) {
+            jpegs.entry(key).or_insert((path, size));
+        }
+    }
+
+    let mut pairs: Vec<PhotoPair> = raws
+        .into_iter()
+        .filter_map(|(key, (raw_path, raw_bytes))| {
+            let (jpeg_path, jpeg_bytes) = jpegs.remove(&key)?;
+            Some(PhotoPair {
+                stem: raw_path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or(key),
+                raw_path: raw_path.to_string_lossy().into_owned(),
+                raw_bytes,
+                jpeg_path: jpeg_path.to_string_lossy().into_owned(),
+                jpeg_bytes,
+            })
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.stem.cmp(&b.stem));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::fixture_tree;
+
+    #[test]
+    fn pairs_only_raw_and_jpeg_with_the_same_name_in_one_folder() {
+        let root = fixture_tree(
+            "raw-jpeg",
+            &[
+                ("trip/IMG_0001.CR2", 300),
+                ("trip/IMG_0001.jpg", 100),
+                // 扩展名与文件名不区分大小写
+                ("trip/img_0002.arw", 200),
+                ("trip/IMG_0002.JPEG", 50),
+                // 只有 RAW 或只有 JPEG
+                ("trip/IMG_0003.NEF", 400),
+                ("trip/IMG_0004.jpg", 80),
+                // 同名但不在同一目录
+                ("trip/IMG_0005.dng", 250),
+                ("trip/edited/IMG_0005.jpg", 90),
+                // 不是照片
+                ("trip/IMG_0006.xmp", 5),
+                ("trip/IMG_0006.jpg", 70),
+            ],
+        );
+
+        let folders = find_raw_jpeg_pairs(&root);
+        assert_eq!(folders.len(), 1);
+        let folder = &folders[0];
+        assert_eq!(Path::new(&folder.path), root.join("trip").as_path());
+        let pairs: Vec<(&str, &Path, &Path)> = folder
+            .pairs
+            .iter()
+            .map(|p| {
+                (
+                    p.stem.as_str(),
+                    Path::new(&p.raw_path),
+                    Path::new(&p.jpeg_path),
+                )
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                (
+                    "IMG_0001",
+                    root.join("trip/IMG_0001.CR2").as_path(),
+                    root.join("trip/IMG_0001.jpg").as_path()
+                ),
+                (
+                    "img_0002",
+                    root.join("trip/img_0002.arw").as_path(),
+                    root.join("trip/IMG_0002.JPEG").as_path()
+                ),
+            ]
+        );
+        assert_eq!(folder.raw_bytes, 500);
+        assert_eq!(folder.jpeg_bytes, 150);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
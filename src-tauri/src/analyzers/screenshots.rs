@@ -0,0 +1,318 @@
+use super::{Analyzer, AnalyzerItem, AnalyzerReport};
+use crate::time_format::year_month;
+use crate::utils::{home_dir, human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "heic", "gif", "webp", "bmp"];
+const VIDEO_EXTENSIONS: [&str; 4] = ["mov", "mp4", "webm", "mkv"];
+// 各系统截图、录屏工具默认文件名的开头（小写）
+const SCREENSHOT_PREFIXES: [&str; 8] = [
+    "screenshot",
+    "screen shot",
+    "截屏",
+    "截图",
+    "屏幕截图",
+    "bildschirmfoto",
+    "captura de pantalla",
+    "capture d'écran",
+];
+const RECORDING_PREFIXES: [&str; 5] = [
+    "screen recording",
+    "screencast",
+    "录屏",
+    "屏幕录制",
+    "bildschirmaufnahme",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureKind {
+    Screenshot,
+    Recording,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CaptureFolder {
+    pub path: String,
+    /// 系统截图、录屏工具的默认保存目录
+    pub is_default: bool,
+    pub screenshots: u64,
+    pub recordings: u64,
+    pub size_raw: u64,
+    pub size_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonthBucket {
+    /// 按修改时间，如 2024-03
+    pub month: String,
+    pub screenshots: u64,
+    pub recordings: u64,
+    pub size_raw: u64,
+    pub size_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScreenshotReport {
+    /// 按大小降序
+    pub folders: Vec<CaptureFolder>,
+    /// 按月份降序
+    pub months: Vec<MonthBucket>,
+    pub total_raw: u64,
+    pub total_display: String,
+}
+
+struct Capture {
+    folder: PathBuf,
+    kind: CaptureKind,
+    size: u64,
+    month: String,
+}
+
+// 统计默认截图、录屏目录中的全部图片和视频，以及 root 下文件名符合截图、录屏命名规则的文件
+pub fn find_screenshots(root: &Path) -> ScreenshotReport {
+    let defaults = default_folders();
+    let mut captures = Vec::new();
+    for dir in &defaults {
+        collect_all(dir, &mut captures);
+    }
+    if !defaults.iter().any(|d| root.starts_with(d)) {
+        collect_matching(root, &defaults, true, &mut captures);
+    }
+    // macOS 默认把截图保存到桌面
+    if let Some(desktop) = home_dir().map(|h| h.join("Desktop")) {
+        if !desktop.starts_with(root) {
+            collect_matching(&desktop, &defaults, false, &mut captures);
+        }
+    }
+
+    let mut folders: BTreeMap<PathBuf, (u64, u64, u64)> = BTreeMap::new();
+    let mut months: BTreeMap<String, (u64, u64, u64)> = BTreeMap::new();
+    for capture in captures {
+        for slot in [
+            folders.entry(capture.folder).or_default(),
+            months.entry(capture.month).or_default(),
+        ] {
+            match capture.kind {
+                CaptureKind::Screenshot => slot.0 += 1,
+                CaptureKind::Recording => slot.1 += 1,
+            }
+            slot.2 = slot.2.saturating_add(capture.size);
+        }
+    }
+
+    let mut folders: Vec<CaptureFolder> = folders
+        .into_iter()
+        .map(
+            |(path, (screenshots, recordings, size_raw))| CaptureFolder {
+                is_default: defaults.iter().any(|d| path.starts_with(d)),
+                path: path.to_string_lossy().into_owned(),
+                screenshots,
+                recordings,
+                size_raw,
+                size_display: human_readable_size(size_raw),
+            },
+        )
+        .collect();
+    folders.sort_by_key(|f| std::cmp::Reverse(f.size_raw));
+    let months: Vec<MonthBucket> = months
+        .into_iter()
+        .rev()
+        .map(|(month, (screenshots, recordings, size_raw))| MonthBucket {
+            month,
+            screenshots,
+            recordings,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+        })
+        .collect();
+    let total_raw = sum_sizes(folders.iter().map(|f| f.size_raw));
+    ScreenshotReport {
+        folders,
+        months,
+        total_raw,
+        total_display: human_readable_size(total_raw),
+    }
+}
+
+pub struct ScreenshotsAnalyzer;
+
+impl Analyzer for ScreenshotsAnalyzer {
+    fn name(&self) -> &str {
+        "screenshots"
+    }
+
+    fn description(&self) -> &str {
+        "默认截图、录屏目录以及按截图命名规则保存的图片和视频"
+    }
+
+    fn scan(&self, root: &Path) -> Result<AnalyzerReport, String> {
+        let items = find_screenshots(root)
+            .folders
+            .into_iter()
+            .filter(|f| Path::new(&f.path).starts_with(root))
+            .map(|f| {
+                let note = format!("{} 张截图，{} 段录屏", f.screenshots, f.recordings);
+                let label = Path::new(&f.path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| f.path.clone());
+                AnalyzerItem::new(label, f.path, f.size_raw, Some(note))
+            })
+            .collect();
+        Ok(AnalyzerReport::new(self.name(), items))
+    }
+}
+
+fn default_folders() -> Vec<PathBuf> {
+    let Some(home) = home_dir() else {
+        return Vec::new();
+    };
+    [
+        // Windows 截图工具与 GNOME
+        home.join("Pictures").join("Screenshots"),
+        // Xbox Game Bar
+        home.join("Videos").join("Captures"),
+        // GNOME 录屏
+        home.join("Videos").join("Screencasts"),
+    ]
+    .into_iter()
+    .filter(|d| d.is_dir())
+    .collect()
+}
+
+fn capture_kind(path: &Path, require_name: bool) -> Option<CaptureKind> {
+    let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    let by_extension = if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        CaptureKind::Screenshot
+    } else if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+        CaptureKind::Recording
+    } else {
+        return None;
+    };
+    if !require_name {
+        return Some(by_extension);
+    }
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if SCREENSHOT_PREFIXES.iter().any(|p| name.starts_with(p)) {
+        Some(CaptureKind::Screenshot)
+    } else if RECORDING_PREFIXES.iter().any(|p| name.starts_with(p)) {
+        Some(CaptureKind::Recording)
+    } else {
+        None
+    }
+}
+
+fn push_capture(path: &Path, metadata: &fs::Metadata, kind: CaptureKind, found: &mut Vec<Capture>) {
+    found.push(Capture {
+        folder: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        kind,
+        size: metadata.len(),
+        month: metadata
+            .modified()
+            .map(year_month)
+            .unwrap_or_else(|_| "未知".to_string()),
+    });
+}
+
+// 默认目录中的图片和视频都算作截图、录屏
+fn collect_all(dir: &Path, found: &mut Vec<Capture>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_all(&path, found);
+        } else if let Some(kind) = capture_kind(&path, false) {
+            push_capture(&path, &metadata, kind, found);
+        }
+    }
+}
+
+// 其他目录只统计符合命名规则的文件，跳过已整体统计的默认目录
+fn collect_matching(dir: &Path, defaults: &[PathBuf], recursive: bool, found: &mut Vec<Capture>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if recursive && !defaults.contains(&path) {
+                collect_matching(&path, defaults, recursive, found);
+            }
+        } else if let Some(kind) = capture_kind(&path, true) {
+            push_capture(&path, &metadata, kind, found);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(name: &str) -> Option<CaptureKind> {
+        capture_kind(Path::new(name), true)
+    }
+
+    #[test]
+    fn matches_default_capture_names_in_any_case() {
+        for name in [
+            "Screenshot 2024-03-01 at 10.15.42.png",
+            "Screen Shot 2019-05-02 at 9.00.00 AM.PNG",
+            "screenshot_20240301.jpg",
+            "截屏2024-03-01 10.15.42.png",
+            "截图_20240301.jpeg",
+            "屏幕截图 2024-03-01 101542.png",
+            "Bildschirmfoto vom 2024-03-01.png",
+            "Captura de pantalla 2024-03-01.heic",
+            "Capture d'écran 2024-03-01.webp",
+        ] {
+            assert_eq!(kind(name), Some(CaptureKind::Screenshot), "{}", name);
+        }
+        for name in [
+            "Screen Recording 2024-03-01 at 10.15.42.mov",
+            "Screencast from 2024-03-01.webm",
+            "录屏2024-03-01.mp4",
+            "屏幕录制 2024-03-01.mkv",
+            "Bildschirmaufnahme 2024-03-01.MOV",
+        ] {
+            assert_eq!(kind(name), Some(CaptureKind::Recording), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_other_names_and_file_types() {
+        for name in [
+            "IMG_0001.jpg",
+            "my screenshot.png",
+            "holiday-screen shot.png",
+            // 名称符合但不是图片或视频
+            "Screenshot 2024-03-01.pdf",
+            "Screen Recording notes.txt",
+            "Screenshot",
+        ] {
+            assert_eq!(kind(name), None, "{}", name);
+        }
+        // 默认目录中不要求命名规则，只按扩展名区分
+        assert_eq!(
+            capture_kind(Path::new("IMG_0001.jpg"), false),
+            Some(CaptureKind::Screenshot)
+        );
+        assert_eq!(
+            capture_kind(Path::new("clip.mp4"), false),
+            Some(CaptureKind::Recording)
+        );
+        assert_eq!(capture_kind(Path::new("notes.txt"), false), None);
+    }
+}
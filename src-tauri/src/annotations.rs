@@ -0,0 +1,92 @@
+// 用户为路径添加的备注与标签（如“保留”“待团队确认”“发布后删除”），保存在配置中，跨扫描保留
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PathAnnotation {
+    pub path: String,
+    pub note: Option<String>,
+    /// 已去除首尾空白和重复，按添加顺序
+    pub tags: Vec<String>,
+}
+
+impl PathAnnotation {
+    // 标签不区分大小写
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+    }
+}
+
+// 替换路径的标签和备注；两者都为空时移除该路径的标注
+pub fn set_annotation(
+    annotations: &mut Vec<PathAnnotation>,
+    path: String,
+    tags: Vec<String>,
+    note: Option<String>,
+) {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    annotations.retain(|a| a.path != path);
+    if !normalized.is_empty() || note.is_some() {
+        annotations.push(PathAnnotation {
+            path,
+            note,
+            tags: normalized,
+        });
+    }
+}
+
+// 所有使用过的标签，按使用次数降序
+pub fn all_tags(annotations: &[PathAnnotation]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for tag in annotations.iter().flat_map(|a| &a.tags) {
+        match counts.iter_mut().find(|(t, _)| t.eq_ignore_ascii_case(tag)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((tag.clone(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_and_clears_annotations() {
+        let mut annotations = Vec::new();
+        set_annotation(
+            &mut annotations,
+            "/data/build".to_string(),
+            vec![" keep ".to_string(), "Keep".to_string(), String::new()],
+            Some("  ".to_string()),
+        );
+        assert_eq!(annotations[0].tags, vec!["keep".to_string()]);
+        assert_eq!(annotations[0].note, None);
+        assert!(annotations[0].has_tag("KEEP"));
+
+        set_annotation(
+            &mut annotations,
+            "/data/logs".to_string(),
+            vec!["keep".to_string(), "delete after release".to_string()],
+            None,
+        );
+        assert_eq!(all_tags(&annotations)[0], ("keep".to_string(), 2));
+
+        set_annotation(
+            &mut annotations,
+            "/data/build".to_string(),
+            Vec::new(),
+            None,
+        );
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].path, "/data/logs");
+    }
+}
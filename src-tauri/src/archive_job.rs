@@ -0,0 +1,290 @@
+// 先归档再删除：把选中的目录压缩为 tar.gz 放到指定位置，核对归档中的文件清单并计算哈希，
+// 确认无误后才把原目录移到回收站。整个过程作为一个可取消的后台任务，按阶段报告进度
+use crate::hashing::hash_file;
+use crate::platform;
+use crate::time_format::date;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveStage {
+    Compress,
+    Verify,
+    Trash,
+}
+
+impl ArchiveStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            ArchiveStage::Compress => "compress",
+            ArchiveStage::Verify => "verify",
+            ArchiveStage::Trash => "trash",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivedItem {
+    pub source: String,
+    pub archive: String,
+    pub archive_bytes: u64,
+    /// 核对过的文件数
+    pub files: usize,
+    pub sha256: String,
+    /// 移到回收站失败的原因；为 None 表示原目录已移到回收站
+    pub trash_error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ArchiveJobReport {
+    pub job_id: u64,
+    pub items: Vec<ArchivedItem>,
+    /// (路径, 原因)，出错的目录保持原样
+    pub errors: Vec<(String, String)>,
+    /// 任务被取消；已完成的目录不受影响，进行中的目录的未完成归档会被删除
+    pub cancelled: bool,
+}
+
+// 正在运行的归档任务及其取消标志
+#[derive(Default)]
+pub struct ArchiveJobs {
+    next_id: Mutex<u64>,
+    running: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+}
+
+impl ArchiveJobs {
+    pub fn start(&self) -> (u64, Arc<AtomicBool>) {
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        let flag = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap().insert(*next_id, flag.clone());
+        (*next_id, flag)
+    }
+
+    pub fn cancel(&self, job_id: u64) -> Result<(), String> {
+        let running = self.running.lock().unwrap();
+        let flag = running
+            .get(&job_id)
+            .ok_or_else(|| format!("归档任务 {} 不存在或已结束", job_id))?;
+        flag.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn finish(&self, job_id: u64) {
+        self.running.lock().unwrap().remove(&job_id);
+    }
+}
+
+// 依次处理每个目录；on_stage(阶段, 目录, 已完成的目录数, 目录总数)
+pub fn run_job(
+    job_id: u64,
+    sources: &[String],
+    destination: &Path,
+    cancel: &AtomicBool,
+    on_stage: &dyn Fn(ArchiveStage, &str, usize, usize),
+) -> ArchiveJobReport {
+    let mut report = ArchiveJobReport {
+        job_id,
+        ..Default::default()
+    };
+    for (done, source) in sources.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            report.cancelled = true;
+            break;
+        }
+        let stage = |stage: ArchiveStage| on_stage(stage, source, done, sources.len());
+        match archive_one(Path::new(source), destination, cancel, &stage) {
+            Ok(item) => report.items.push(item),
+            Err(_) if cancel.load(Ordering::Relaxed) => {
+                report.cancelled = true;
+                break;
+            }
+            Err(e) => report.errors.push((source.clone(), e)),
+        }
+    }
+    report
+}
+
+fn archive_one(
+    source: &Path,
+    destination: &Path,
+    cancel: &AtomicBool,
+    stage: &dyn Fn(ArchiveStage),
+) -> Result<ArchivedItem, String> {
+    let parent = source
+        .parent()
+        .ok_or_else(|| "不能归档根目录".to_string())?;
+    let name = source
+        .file_name()
+        .ok_or_else(|| "无法确定目录名".to_string())?;
+    let archive = destination.join(format!(
+        "{}-{}.tar.gz",
+        name.to_string_lossy(),
+        date(SystemTime::now())
+    ));
+    if archive.exists() {
+        return Err(format!("归档文件已存在: {}", archive.display()));
+    }
+    if destination.starts_with(source) {
+        return Err("归档位置不能在要归档的目录中".to_string());
+    }
+
+    stage(ArchiveStage::Compress);
+    let mut compress = platform::command("tar");
+    compress
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(parent)
+        .arg(name);
+    let result = run_cancellable(compress, cancel).and_then(|_| {
+        stage(ArchiveStage::Verify);
+        verify(source, &archive, cancel)
+    });
+    let (files, sha256) = match result {
+        Ok(verified) => verified,
+        Err(e) => {
+            // 未完成或核对失败的归档不保留，避免被误当作完整备份
+            let _ = fs::remove_file(&archive);
+            return Err(e);
+        }
+    };
+    let archive_bytes = fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+
+    // 核对完成后不再响应取消，原目录要么留在原处，要么已移到回收站
+    stage(ArchiveStage::Trash);
+    let trash_error = trash::delete(source)
+        .err()
+        .map(|e| format!("移到回收站失败: {}", e));
+    Ok(ArchivedItem {
+        source: source.to_string_lossy().into_owned(),
+        archive: archive.to_string_lossy().into_owned(),
+        archive_bytes,
+        files,
+        sha256,
+        trash_error,
+    })
+}
+
+// 运行命令直至结束，取消时终止进程；返回标准输出
+fn run_cancellable(mut command: Command, cancel: &AtomicBool) -> Result<String, String> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法运行 tar: {}", e))?;
+    // 在单独的线程中读取输出，避免管道写满后子进程阻塞
+    let read = |pipe: Option<Box<dyn std::io::Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut out = String::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_string(&mut out);
+            }
+            out
+        })
+    };
+    let stdout = read(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = read(child.stderr.take().map(|p| Box::new(p) as _));
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("已取消".to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    let out = stdout.join().unwrap_or_default();
+    if !status.success() {
+        let err = stderr.join().unwrap_or_default();
+        return Err(format!("tar 执行失败: {}", err.trim()));
+    }
+    Ok(out)
+}
+
+// 归档中的文件清单必须与原目录完全一致；返回 (文件数, 归档的 SHA-256)
+fn verify(source: &Path, archive: &Path, cancel: &AtomicBool) -> Result<(usize, String), String> {
+    let mut list = platform::command("tar");
+    list.arg("-tzf").arg(archive);
+    let listing = run_cancellable(list, cancel)?;
+    let archived = archived_files(&listing);
+
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut expected = BTreeSet::new();
+    collect_files(source, &name, &mut expected);
+
+    if let Some(missing) = expected.difference(&archived).next() {
+        return Err(format!("归档中缺少 {}", missing));
+    }
+    if let Some(extra) = archived.difference(&expected).next() {
+        return Err(format!("归档中有多余的条目 {}", extra));
+    }
+    let sha256 = hash_file(archive, None, &mut vec![0u8; 1 << 20])?;
+    Ok((expected.len(), sha256))
+}
+
+// tar -t 的输出中目录以 / 结尾，只比较文件
+fn archived_files(listing: &str) -> BTreeSet<String> {
+    listing
+        .lines()
+        .map(|line| line.trim_start_matches("./"))
+        .filter(|line| !line.is_empty() && !line.ends_with('/'))
+        .map(str::to_string)
+        .collect()
+}
+
+// 目录下的所有非目录条目，路径相对其上级目录并以 / 分隔，与 tar 的清单格式一致
+fn collect_files(dir: &Path, prefix: &str, files: &mut BTreeSet<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path: PathBuf = entry.path();
+        let relative = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        // 不跟随符号链接
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => collect_files(&path, &relative, files),
+            Ok(_) => {
+                files.insert(relative);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_is_compared_by_files_only() {
+        let dir = std::env::temp_dir().join(format!("disksight-archive-{}", std::process::id()));
+        fs::create_dir_all(dir.join("project/src")).unwrap();
+        fs::write(dir.join("project/a.txt"), b"1").unwrap();
+        fs::write(dir.join("project/src/main.rs"), b"2").unwrap();
+
+        let mut expected = BTreeSet::new();
+        collect_files(&dir.join("project"), "project", &mut expected);
+        let archived =
+            archived_files("project/\nproject/a.txt\nproject/src/\n./project/src/main.rs\n");
+        assert_eq!(expected, archived);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+use crate::platform;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 强制删除失败时最多列出的无法修改属性的条目数
+const MAX_REPORTED_FAILURES: usize = 5;
+
+#[cfg(windows)]
+const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+#[cfg(windows)]
+const INVALID_FILE_ATTRIBUTES: u32 = u32::MAX;
+
+// 设置 Windows 文件属性，参数为空表示保持不变
+#[cfg(windows)]
+pub fn set_attributes(
+    path: &Path,
+    readonly: Option<bool>,
+    hidden: Option<bool>,
+) -> Result<(), String> {
+    use crate::drives::to_wide;
+    use winapi::um::fileapi::{GetFileAttributesW, SetFileAttributesW};
+    let wide = to_wide(&path.to_string_lossy());
+    unsafe {
+        let current = GetFileAttributesW(wide.as_ptr());
+        if current == INVALID_FILE_ATTRIBUTES {
+            return Err(format!(
+                "无法读取文件属性: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let mut attrs = current;
+        for (flag, value) in [
+            (FILE_ATTRIBUTE_READONLY, readonly),
+            (FILE_ATTRIBUTE_HIDDEN, hidden),
+        ] {
+            match value {
+                Some(true) => attrs |= flag,
+                Some(false) => attrs &= !flag,
+                None => {}
+            }
+        }
+        if attrs != current && SetFileAttributesW(wide.as_ptr(), attrs) == 0 {
+            return Err(format!(
+                "修改文件属性失败: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn set_attributes(
+    _path: &Path,
+    _readonly: Option<bool>,
+    _hidden: Option<bool>,
+) -> Result<(), String> {
+    Err(format!(
+        "{}，请使用 chmod",
+        platform::unsupported("文件属性")
+    ))
+}
+
+// 按八进制权限位设置 Unix 权限，如 0o755
+#[cfg(unix)]
+pub fn chmod(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    if mode > 0o7777 {
+        return Err(format!("无效的权限位: {:o}", mode));
+    }
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|e| format!("修改权限失败: {}", e))
+}
+
+#[cfg(not(unix))]
+pub fn chmod(_path: &Path, _mode: u32) -> Result<(), String> {
+    Err(format!(
+        "{}，请使用文件属性",
+        platform::unsupported("chmod")
+    ))
+}
+
+// 去掉只读标记，使文件可以被删除或修改
+pub fn clear_readonly(path: &Path) -> Result<(), String> {
+    if platform::supports_file_attributes() {
+        return set_attributes(path, Some(false), None);
+    }
+    let metadata = fs::metadata(path).map_err(|e| format!("无法访问路径: {}", e))?;
+    if !metadata.permissions().readonly() {
+        return Ok(());
+    }
+    chmod(path, unix_mode(&metadata) | 0o200)
+}
+
+// 递归去掉目录内所有条目的只读限制，使 remove_dir_all 可以删除整棵树，不跟随符号链接。
+// Windows 上清除每个条目的只读属性；Unix 上删除条目取决于所在目录的权限，只需让目录可写可进入。
+// 返回无法修改的条目及原因，通常是 ACL 拒绝了修改属性或权限
+pub fn clear_readonly_recursive(path: &Path) -> Vec<(PathBuf, String)> {
+    let mut failures = Vec::new();
+    clear_tree(path, &mut failures);
+    failures
+}
+
+fn clear_tree(path: &Path, failures: &mut Vec<(PathBuf, String)>) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) => {
+            failures.push((path.to_path_buf(), e.to_string()));
+            return;
+        }
+    };
+    if metadata.is_dir() {
+        if let Err(e) = make_dir_removable(path, &metadata) {
+            failures.push((path.to_path_buf(), e));
+        }
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    clear_tree(&entry.path(), failures);
+                }
+            }
+            Err(e) => failures.push((path.to_path_buf(), format!("无法读取目录: {}", e))),
+        }
+    } else if platform::supports_file_attributes() && metadata.permissions().readonly() {
+        // 符号链接只修改链接本身的属性
+        if let Err(e) = set_attributes(path, Some(false), None) {
+            failures.push((path.to_path_buf(), e));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn make_dir_removable(path: &Path, metadata: &fs::Metadata) -> Result<(), String> {
+    let mode = unix_mode(metadata);
+    if mode & 0o700 == 0o700 {
+        return Ok(());
+    }
+    chmod(path, mode | 0o700)
+}
+
+#[cfg(not(unix))]
+fn make_dir_removable(path: &Path, metadata: &fs::Metadata) -> Result<(), String> {
+    if !metadata.permissions().readonly() {
+        return Ok(());
+    }
+    set_attributes(path, Some(false), None)
+}
+
+// 把 clear_readonly_recursive 的失败条目整理成附加在删除错误后的说明
+pub fn describe_failures(failures: &[(PathBuf, String)]) -> String {
+    let mut lines: Vec<String> = failures
+        .iter()
+        .take(MAX_REPORTED_FAILURES)
+        .map(|(path, reason)| format!("{}: {}", path.display(), reason))
+        .collect();
+    if failures.len() > MAX_REPORTED_FAILURES {
+        lines.push(format!("等 {} 项", failures.len()));
+    }
+    format!(
+        "以下条目无法去除只读限制（可能受 ACL 限制）: {}",
+        lines.join("; ")
+    )
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &fs::Metadata) -> u32 {
+    0
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn recursive_clear_opens_locked_directories() {
+        let root = std::env::temp_dir().join(format!("disksight-readonly-{}", std::process::id()));
+        let locked = root.join("a").join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(locked.join("file.txt"), b"data").unwrap();
+        fs::set_permissions(locked.join("file.txt"), fs::Permissions::from_mode(0o444)).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+        fs::set_permissions(root.join("a"), fs::Permissions::from_mode(0o500)).unwrap();
+
+        assert!(clear_readonly_recursive(&root).is_empty());
+        for dir in [root.join("a"), locked.clone()] {
+            let mode = fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o700, 0o700, "{}", dir.display());
+        }
+        fs::remove_dir_all(&root).unwrap();
+
+        let failures = clear_readonly_recursive(&root);
+        assert_eq!(failures.len(), 1);
+        assert!(describe_failures(&failures).contains("disksight-readonly"));
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AUDIT_FILE: &str = "audit.log";
+// 日志超过该大小后轮换为 audit.log.1，最多保留 ROTATED_FILES 个旧文件
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+const ROTATED_FILES: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Delete,
+    Trash,
+    Move,
+    Rename,
+    /// 文件被替换为另一个文件的硬链接
+    HardLink,
+    /// 文件数据改为与另一个文件共享数据块（reflink 去重）
+    Reflink,
+    /// 修改了只读模式等与安全相关的设置，path 为设置项名称
+    Settings,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    /// 执行操作的系统用户
+    pub user: String,
+    pub action: AuditAction,
+    pub path: String,
+    /// 移动、重命名的目标路径
+    pub target: Option<String>,
+    /// 涉及的数据量
+    pub bytes: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+type Observer = Box<dyn Fn(&AuditRecord) + Send + Sync>;
+
+// 追加写入的破坏性操作日志，每行一条 JSON 记录
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    lock: Mutex<()>,
+    /// 每条记录写入后调用，用于通知界面刷新受影响的条目
+    observer: OnceLock<Observer>,
+}
+
+impl AuditLog {
+    pub fn new(data_dir: &Path) -> Self {
+        Self::with_max_bytes(data_dir, MAX_LOG_BYTES)
+    }
+
+    fn with_max_bytes(data_dir: &Path, max_bytes: u64) -> Self {
+        AuditLog {
+            path: data_dir.join(AUDIT_FILE),
+            max_bytes,
+            lock: Mutex::new(()),
+            observer: OnceLock::new(),
+        }
+    }
+
+    // 设置记录写入后的回调，只能设置一次
+    pub fn observe(&self, observer: impl Fn(&AuditRecord) + Send + Sync + 'static) {
+        let _ = self.observer.set(Box::new(observer));
+    }
+
+    // 记录一次操作；写日志失败不应影响操作本身，只打印错误
+    pub fn record(
+        &self,
+        action: AuditAction,
+        path: &str,
+        target: Option<&str>,
+        bytes: u64,
+        result: &Result<(), String>,
+    ) {
+        let record = AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            user: current_user(),
+            action,
+            path: path.to_string(),
+            target: target.map(String::from),
+            bytes,
+            success: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+        };
+        if let Err(e) = self.append(&record) {
+            eprintln!("写入审计日志失败: {}", e);
+        }
+        if let Some(observer) = self.observer.get() {
+            observer(&record);
+        }
+    }
+
+    fn append(&self, record: &AuditRecord) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() >= self.max_bytes) {
+            self.rotate()?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    // 旧文件依次后移，超出保留数量的最旧文件被覆盖
+    fn rotate(&self) -> Result<(), String> {
+        for n in (1..ROTATED_FILES).rev() {
+            let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        fs::rename(&self.path, self.rotated(1)).map_err(|e| format!("轮换审计日志失败: {}", e))
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        self.path.with_extension(format!("log.{}", n))
+    }
+
+    // 读取最近的 limit 条记录，最新的在前；当前文件不够时继续读取轮换出的旧文件
+    pub fn read(&self, limit: usize) -> Result<Vec<AuditRecord>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let files =
+            std::iter::once(self.path.clone()).chain((1..=ROTATED_FILES).map(|n| self.rotated(n)));
+        let mut records = Vec::new();
+        for file in files {
+            if records.len() >= limit {
+                break;
+            }
+            let content = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("读取审计日志失败: {}", e)),
+            };
+            records.extend(
+                content
+                    .lines()
+                    .rev()
+                    .filter_map(|line| serde_json::from_str::<AuditRecord>(line).ok())
+                    .take(limit - records.len()),
+            );
+        }
+        Ok(records)
+    }
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("disksight-audit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn records_are_read_back_newest_first() {
+        let dir = temp_dir("read");
+        let log = AuditLog::new(&dir);
+        log.record(AuditAction::Delete, "/data/a", None, 10, &Ok(()));
+        log.record(
+            AuditAction::Move,
+            "/data/b",
+            Some("/archive/b"),
+            20,
+            &Err("磁盘已满".to_string()),
+        );
+
+        let records = log.read(10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].action, AuditAction::Move);
+        assert_eq!(records[0].target.as_deref(), Some("/archive/b"));
+        assert!(!records[0].success);
+        assert_eq!(records[0].error.as_deref(), Some("磁盘已满"));
+        assert_eq!(records[1].path, "/data/a");
+        assert_eq!(records[1].bytes, 10);
+        assert!(records[1].success);
+        assert_eq!(log.read(1).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_and_reads_across_files() {
+        let dir = temp_dir("rotate");
+        // 每条记录都超过上限，每次写入前都会轮换
+        let log = AuditLog::with_max_bytes(&dir, 1);
+        for i in 0..ROTATED_FILES + 3 {
+            log.record(
+                AuditAction::Delete,
+                &format!("/data/{}", i),
+                None,
+                0,
+                &Ok(()),
+            );
+        }
+
+        assert!(log.rotated(ROTATED_FILES).exists());
+        assert!(!log.rotated(ROTATED_FILES + 1).exists());
+        let paths: Vec<String> = log.read(100).unwrap().into_iter().map(|r| r.path).collect();
+        // 当前文件加上保留的旧文件，更早的记录已被丢弃
+        let expected: Vec<String> = (2..ROTATED_FILES + 3)
+            .rev()
+            .map(|i| format!("/data/{}", i))
+            .collect();
+        assert_eq!(paths, expected);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
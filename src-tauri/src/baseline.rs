@@ -0,0 +1,350 @@
+// 机器镜像的基准清单：管理员在干净状态下为目录保存一份清单，之后重新扫描并与之对比，
+// 找出超过阈值的意外新增、增长与缺失，用于维护自助终端、机房等统一镜像的机器
+use crate::history::history_key;
+use crate::models::FileEntry;
+use crate::utils::{human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const BASELINES_DIR: &str = "baselines";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BaselineEntry {
+    /// 相对根目录的路径，分量之间用 / 连接
+    path: String,
+    size_raw: u64,
+    is_dir: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BaselineManifest {
+    root: String,
+    created_at: SystemTime,
+    entries: Vec<BaselineEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaselineSummary {
+    pub root: String,
+    pub created_at: SystemTime,
+    pub entry_count: usize,
+    pub total_bytes: u64,
+    pub total_display: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviationKind {
+    /// 基准中没有的条目，只列出最外层的
+    Added,
+    /// 基准中已有、现在变大的文件
+    Grown,
+    /// 基准中有、现在已不存在的条目，只列出最外层的
+    Removed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaselineDeviation {
+    pub kind: DeviationKind,
+    /// 相对根目录的路径
+    pub path: String,
+    pub is_dir: bool,
+    pub baseline_size: Option<u64>,
+    pub current_size: Option<u64>,
+    /// 增加为正，减少为负
+    pub delta: i64,
+    pub delta_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaselineReport {
+    pub root: String,
+    pub baseline_created_at: SystemTime,
+    pub threshold_bytes: u64,
+    /// 按变化量的绝对值降序
+    pub deviations: Vec<BaselineDeviation>,
+    /// 意外新增与增长的总量
+    pub unexpected_bytes: u64,
+    pub unexpected_display: String,
+}
+
+// 每个目录一份清单，保存在数据目录的 baselines 下
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    pub fn new(data_dir: &Path) -> Self {
+        BaselineStore {
+            dir: data_dir.join(BASELINES_DIR),
+        }
+    }
+
+    // 用当前的扫描结果替换 root 的基准清单
+    pub fn save(&self, root: &Path, entries: &[FileEntry]) -> Result<BaselineSummary, String> {
+        let manifest = BaselineManifest {
+            root: root.to_string_lossy().into_owned(),
+            created_at: SystemTime::now(),
+            entries: relative_entries(root, entries)
+                .into_iter()
+                .map(|(path, entry)| BaselineEntry {
+                    path,
+                    size_raw: entry.size_raw,
+                    is_dir: entry.file_type == 'd',
+                })
+                .collect(),
+        };
+        fs::create_dir_all(&self.dir).map_err(|e| format!("无法创建基准目录: {}", e))?;
+        let content =
+            serde_json::to_string(&manifest).map_err(|e| format!("基准清单序列化失败: {}", e))?;
+        fs::write(self.path_of(root), content).map_err(|e| format!("保存基准清单失败: {}", e))?;
+        Ok(summary(&manifest))
+    }
+
+    pub fn summary(&self, root: &Path) -> Result<BaselineSummary, String> {
+        self.read(root).map(|manifest| summary(&manifest))
+    }
+
+    pub fn delete(&self, root: &Path) -> Result<(), String> {
+        fs::remove_file(self.path_of(root)).map_err(|e| format!("删除基准清单失败: {}", e))
+    }
+
+    // 与 root 的基准清单对比，只列出变化量不小于 threshold 的条目
+    pub fn compare(
+        &self,
+        root: &Path,
+        entries: &[FileEntry],
+        threshold: u64,
+    ) -> Result<BaselineReport, String> {
+        let manifest = self.read(root)?;
+        Ok(compare(&manifest, root, entries, threshold))
+    }
+
+    fn read(&self, root: &Path) -> Result<BaselineManifest, String> {
+        let content = fs::read_to_string(self.path_of(root))
+            .map_err(|_| format!("{} 还没有基准清单", root.display()))?;
+        serde_json::from_str(&content).map_err(|e| format!("基准清单已损坏: {}", e))
+    }
+
+    // 同一目录的不同写法使用同一份清单
+    fn path_of(&self, root: &Path) -> PathBuf {
+        let key = history_key(&root.to_string_lossy());
+        let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+        self.dir.join(format!("{}.json", &hash[..16]))
+    }
+}
+
+fn summary(manifest: &BaselineManifest) -> BaselineSummary {
+    let total_bytes = sum_sizes(
+        manifest
+            .entries
+            .iter()
+            .filter(|e| !e.path.contains('/'))
+            .map(|e| e.size_raw),
+    );
+    BaselineSummary {
+        root: manifest.root.clone(),
+        created_at: manifest.created_at,
+        entry_count: manifest.entries.len(),
+        total_bytes,
+        total_display: human_readable_size(total_bytes),
+    }
+}
+
+fn relative_entries<'a>(root: &Path, entries: &'a [FileEntry]) -> Vec<(String, &'a FileEntry)> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let relative = Path::new(&path).strip_prefix(root).ok()?;
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            Some((components.join("/"), entry))
+        })
+        .filter(|(path, _)| !path.is_empty())
+        .collect()
+}
+
+fn compare(
+    manifest: &BaselineManifest,
+    root: &Path,
+    entries: &[FileEntry],
+    threshold: u64,
+) -> BaselineReport {
+    let baseline: BTreeMap<&str, &BaselineEntry> = manifest
+        .entries
+        .iter()
+        .map(|e| (e.path.as_str(), e))
+        .collect();
+    let current: BTreeMap<String, &FileEntry> =
+        relative_entries(root, entries).into_iter().collect();
+    // 上级目录也是新增（或缺失）时只保留上级目录
+    let parent_in = |path: &str, contains: &dyn Fn(&str) -> bool| {
+        path.rmatch_indices('/').any(|(i, _)| contains(&path[..i]))
+    };
+
+    let mut deviations = Vec::new();
+    for (path, entry) in &current {
+        let is_dir = entry.file_type == 'd';
+        match baseline.get(path.as_str()) {
+            None => {
+                let parent_added = parent_in(path, &|p| {
+                    current.contains_key(p) && !baseline.contains_key(p)
+                });
+                if !parent_added && entry.size_raw >= threshold {
+                    deviations.push(deviation(
+                        DeviationKind::Added,
+                        path,
+                        is_dir,
+                        None,
+                        Some(entry.size_raw),
+                    ));
+                }
+            }
+            Some(old) if !is_dir && entry.size_raw >= old.size_raw.saturating_add(threshold) => {
+                deviations.push(deviation(
+                    DeviationKind::Grown,
+                    path,
+                    is_dir,
+                    Some(old.size_raw),
+                    Some(entry.size_raw),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, old) in &baseline {
+        let parent_removed = parent_in(path, &|p| {
+            baseline.contains_key(p) && !current.contains_key(p)
+        });
+        if !current.contains_key(*path) && !parent_removed && old.size_raw >= threshold {
+            deviations.push(deviation(
+                DeviationKind::Removed,
+                path,
+                old.is_dir,
+                Some(old.size_raw),
+                None,
+            ));
+        }
+    }
+    deviations.sort_by_key(|d| std::cmp::Reverse(d.delta.unsigned_abs()));
+
+    let unexpected_bytes = sum_sizes(
+        deviations
+            .iter()
+            .filter(|d| d.kind != DeviationKind::Removed)
+            .map(|d| d.delta.unsigned_abs()),
+    );
+    BaselineReport {
+        root: manifest.root.clone(),
+        baseline_created_at: manifest.created_at,
+        threshold_bytes: threshold,
+        deviations,
+        unexpected_bytes,
+        unexpected_display: human_readable_size(unexpected_bytes),
+    }
+}
+
+fn deviation(
+    kind: DeviationKind,
+    path: &str,
+    is_dir: bool,
+    baseline_size: Option<u64>,
+    current_size: Option<u64>,
+) -> BaselineDeviation {
+    let before = baseline_size.unwrap_or(0);
+    let after = current_size.unwrap_or(0);
+    let delta = if after >= before {
+        i64::try_from(after - before).unwrap_or(i64::MAX)
+    } else {
+        i64::try_from(before - after)
+            .map(|d| -d)
+            .unwrap_or(i64::MIN)
+    };
+    let sign = if delta < 0 { "-" } else { "+" };
+    BaselineDeviation {
+        kind,
+        path: path.to_string(),
+        is_dir,
+        baseline_size,
+        current_size,
+        delta,
+        delta_display: format!("{}{}", sign, human_readable_size(delta.unsigned_abs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: path.to_string().into(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            severity: None,
+        }
+    }
+
+    #[test]
+    fn reports_outermost_additions_growth_and_removals() {
+        let root = Path::new("/kiosk");
+        let before = [
+            entry("/kiosk/app", 'd', 1000),
+            entry("/kiosk/app/main.bin", '-', 1000),
+            entry("/kiosk/assets", 'd', 500),
+            entry("/kiosk/assets/logo.png", '-', 500),
+        ];
+        let manifest = BaselineManifest {
+            root: "/kiosk".to_string(),
+            created_at: SystemTime::UNIX_EPOCH,
+            entries: relative_entries(root, &before)
+                .into_iter()
+                .map(|(path, e)| BaselineEntry {
+                    path,
+                    size_raw: e.size_raw,
+                    is_dir: e.file_type == 'd',
+                })
+                .collect(),
+        };
+        let after = [
+            entry("/kiosk/app", 'd', 3000),
+            entry("/kiosk/app/main.bin", '-', 3000),
+            entry("/kiosk/games", 'd', 800),
+            entry("/kiosk/games/a.exe", '-', 800),
+            entry("/kiosk/notes.txt", '-', 10),
+        ];
+
+        let report = compare(&manifest, root, &after, 100);
+        let found: Vec<(DeviationKind, &str)> = report
+            .deviations
+            .iter()
+            .map(|d| (d.kind, d.path.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (DeviationKind::Grown, "app/main.bin"),
+                (DeviationKind::Added, "games"),
+                (DeviationKind::Removed, "assets"),
+            ]
+        );
+        assert_eq!(report.unexpected_bytes, 2800);
+        assert_eq!(report.deviations[2].delta_display, "-500.0B");
+    }
+}
@@ -0,0 +1,30 @@
+// 以管理员权限运行的辅助程序，由主程序在需要时启动，只处理 elevation 模块中定义的请求
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use disk_sight_lib::elevation;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    // 令牌从标准输入读取一行，或从 --token-file 指定的文件读取，不出现在命令行参数中
+    let Some(address) = value("--connect") else {
+        eprintln!("用法: disksight-helper --connect <地址> [--token-file <令牌文件>] < 令牌");
+        std::process::exit(2);
+    };
+    let token = match elevation::read_token(value(elevation::TOKEN_FILE_FLAG).as_deref()) {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = elevation::serve(&address, &token) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,152 @@
+// FastCDC 内容定义分块：切分点只取决于附近的内容，文件中间插入或删除数据时
+// 只有相邻的块会变化，其余块的哈希保持不变，因此可以用来估算两个文件的相似度
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::Read;
+use std::path::Path;
+
+pub const MIN_CHUNK: usize = 16 * 1024;
+pub const AVG_CHUNK: usize = 64 * 1024;
+pub const MAX_CHUNK: usize = 256 * 1024;
+
+// 归一化分块：平均长度之前用更严格的掩码，之后用更宽松的，使块长集中在平均值附近
+// 取高位是因为 gear 哈希的高位包含了更长窗口的内容
+const MASK_S: u64 = ((1 << 17) - 1) << 40;
+const MASK_L: u64 = ((1 << 15) - 1) << 40;
+
+const GEAR: [u64; 256] = gear_table();
+
+// 用 splitmix64 生成固定的随机表，保证不同版本、不同机器的分块结果一致
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    pub hash: u64,
+    pub len: u32,
+}
+
+// 在 data 中找到第一个切分点，返回块长度
+pub fn cut_point(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK {
+        return data.len();
+    }
+    let end = data.len().min(MAX_CHUNK);
+    let normal = end.min(AVG_CHUNK);
+    let mut fp = 0u64;
+    let mut i = MIN_CHUNK;
+    while i < normal {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        if fp & MASK_S == 0 {
+            return i + 1;
+        }
+        i += 1;
+    }
+    while i < end {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        if fp & MASK_L == 0 {
+            return i + 1;
+        }
+        i += 1;
+    }
+    end
+}
+
+// 把一段完整数据切分成块
+pub fn chunk_bytes(mut data: &[u8]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    while !data.is_empty() {
+        let len = cut_point(data);
+        chunks.push(chunk_of(&data[..len]));
+        data = &data[len..];
+    }
+    chunks
+}
+
+// 流式读取文件并分块，内存占用与文件大小无关
+pub fn chunk_file(path: &Path, buffer: &mut [u8]) -> Result<Vec<Chunk>, String> {
+    let mut file = File::open(path).map_err(|e| format!("无法打开文件: {}", e))?;
+    let mut pending: Vec<u8> = Vec::with_capacity(MAX_CHUNK * 2);
+    let mut chunks = Vec::new();
+    let mut eof = false;
+    while !eof || !pending.is_empty() {
+        // 至少保留一个最大块的数据，保证切分点与一次性读入时相同
+        while !eof && pending.len() < MAX_CHUNK {
+            let n = file
+                .read(buffer)
+                .map_err(|e| format!("读取文件失败: {}", e))?;
+            if n == 0 {
+                eof = true;
+            }
+            pending.extend_from_slice(&buffer[..n]);
+        }
+        if pending.is_empty() {
+            break;
+        }
+        let len = cut_point(&pending);
+        chunks.push(chunk_of(&pending[..len]));
+        pending.drain(..len);
+    }
+    Ok(chunks)
+}
+
+fn chunk_of(data: &[u8]) -> Chunk {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(data);
+    Chunk {
+        hash: hasher.finish(),
+        len: data.len() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(len: usize) -> Vec<u8> {
+        let mut state = 1u64;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn insertion_only_changes_nearby_chunks() {
+        let original = pseudo_random(4 * 1024 * 1024);
+        let mut edited = original.clone();
+        edited.splice(1000..1000, [7u8; 100]);
+
+        let a = chunk_bytes(&original);
+        let b = chunk_bytes(&edited);
+        assert!(a.iter().all(|c| (c.len as usize) <= MAX_CHUNK));
+        let shared = a.iter().filter(|c| b.contains(c)).count();
+        assert!(shared + 2 >= a.len(), "shared {} of {}", shared, a.len());
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        let data = pseudo_random(1024 * 1024 + 123);
+        let path = std::env::temp_dir().join(format!("disksight-cdc-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let mut buffer = vec![0u8; 4096];
+        let streamed = chunk_file(&path, &mut buffer).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(streamed, chunk_bytes(&data));
+    }
+}
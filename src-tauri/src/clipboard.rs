@@ -0,0 +1,9 @@
+use arboard::Clipboard;
+
+// 写入系统剪贴板
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut clipboard = Clipboard::new().map_err(|e| format!("无法访问剪贴板: {}", e))?;
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("写入剪贴板失败: {}", e))
+}
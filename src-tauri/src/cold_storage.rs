@@ -0,0 +1,432 @@
+// 冷存储：把不常用的旧数据移到其他卷或 NAS，在目标位置写入清单，在原处留下 .where 说明文件，
+// 同时记入本地索引，数据移走后仍可以按原路径或名称查到去向
+use crate::deletion::refuse_deletion;
+use crate::hashing::hash_file;
+use crate::normalize::fold;
+use crate::time_format::date;
+use crate::utils::{dir_size, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const INDEX_FILE: &str = "cold_storage.json";
+// 目标位置的清单文件，记录移入该目录的所有条目
+pub const MANIFEST_FILE: &str = "disksight-manifest.json";
+pub const STUB_EXTENSION: &str = "where";
+// 校验复制结果时的读取缓冲区大小
+const VERIFY_BUFFER: usize = 1024 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ColdRecord {
+    pub original: String,
+    pub stored: String,
+    pub is_dir: bool,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub moved_at: SystemTime,
+    /// 原处的说明文件，未能写入时为 None
+    pub stub: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ColdStorageOutcome {
+    pub moved: Vec<ColdRecord>,
+    /// (路径, 原因)，出错的条目保持原样
+    pub errors: Vec<(String, String)>,
+    /// 数据已移走但清单、说明文件或索引未能更新
+    pub warnings: Vec<String>,
+}
+
+// 依次移动每个条目；on_item(条目, 已完成数, 总数)
+pub fn move_to_cold_storage(
+    sources: &[String],
+    destination_dir: &Path,
+    on_item: &dyn Fn(&str, usize, usize),
+) -> ColdStorageOutcome {
+    let mut outcome = ColdStorageOutcome::default();
+    for (done, source) in sources.iter().enumerate() {
+        on_item(source, done, sources.len());
+        match move_one(Path::new(source), destination_dir) {
+            Ok(mut record) => {
+                match write_stub(&record) {
+                    Ok(stub) => record.stub = Some(stub),
+                    Err(e) => outcome.warnings.push(format!("{}: {}", source, e)),
+                }
+                outcome.moved.push(record);
+            }
+            Err(e) => outcome.errors.push((source.clone(), e)),
+        }
+    }
+    if !outcome.moved.is_empty() {
+        match append_manifest(destination_dir, &outcome.moved) {
+            Ok(Some(backup)) => outcome.warnings.push(backup),
+            Ok(None) => {}
+            Err(e) => outcome.warnings.push(e),
+        }
+    }
+    outcome
+}
+
+fn move_one(source: &Path, destination_dir: &Path) -> Result<ColdRecord, String> {
+    if let Some(reason) = refuse_deletion(source) {
+        return Err(reason.to_string());
+    }
+    // 不跟随符号链接
+    let metadata = fs::symlink_metadata(source).map_err(|e| format!("无法访问: {}", e))?;
+    if metadata.file_type().is_symlink() {
+        return Err("不能移动符号链接".to_string());
+    }
+    let name = source
+        .file_name()
+        .ok_or_else(|| "无法获取名称".to_string())?;
+    let stored = destination_dir.join(name);
+    if fs::symlink_metadata(&stored).is_ok() {
+        return Err(format!("目标位置已存在 {}", stored.display()));
+    }
+    if destination_dir.starts_with(source) {
+        return Err("不能移动到自身的子目录中".to_string());
+    }
+    let is_dir = metadata.is_dir();
+    let size_raw = if is_dir {
+        dir_size(source)
+    } else {
+        metadata.len()
+    };
+
+    // 同一卷上直接重命名，否则先复制并逐个文件校验，再删除原条目
+    if fs::rename(source, &stored).is_err() {
+        copy_verified(source, &stored, is_dir, size_raw)?;
+        let removed = if is_dir {
+            fs::remove_dir_all(source)
+        } else {
+            fs::remove_file(source)
+        };
+        removed.map_err(|e| format!("已复制到 {}，但未能删除原条目: {}", stored.display(), e))?;
+    }
+    Ok(ColdRecord {
+        original: source.to_string_lossy().into_owned(),
+        stored: stored.to_string_lossy().into_owned(),
+        is_dir,
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        moved_at: SystemTime::now(),
+        stub: None,
+    })
+}
+
+fn copy_verified(source: &Path, stored: &Path, is_dir: bool, size_raw: u64) -> Result<(), String> {
+    let cleanup = |e: String| {
+        let _ = if is_dir {
+            fs::remove_dir_all(stored)
+        } else {
+            fs::remove_file(stored)
+        };
+        e
+    };
+    let copied = if is_dir {
+        fs::create_dir(stored).map_err(|e| format!("无法创建目标目录: {}", e))?;
+        let options = fs_extra::dir::CopyOptions::new().content_only(true);
+        fs_extra::dir::copy(source, stored, &options)
+            .map(|_| dir_size(stored))
+            .map_err(|e| e.to_string())
+    } else {
+        fs::copy(source, stored).map_err(|e| e.to_string())
+    };
+    let copied = copied.map_err(|e| cleanup(format!("复制失败: {}", e)))?;
+    if copied != size_raw {
+        return Err(cleanup("复制后大小不一致，已取消移动".to_string()));
+    }
+    verify_copy(source, stored, is_dir)
+        .map_err(|e| cleanup(format!("复制结果校验失败，已取消移动: {}", e)))
+}
+
+// 逐个核对条目的相对路径、类型和大小，并比较每个文件的 SHA-256；全部一致才允许删除原条目
+fn verify_copy(source: &Path, stored: &Path, is_dir: bool) -> Result<(), String> {
+    let mut buffer = vec![0u8; VERIFY_BUFFER];
+    let mut same_content = |a: &Path, b: &Path| -> Result<(), String> {
+        if hash_file(a, None, &mut buffer)? != hash_file(b, None, &mut buffer)? {
+            return Err(format!("{} 的内容不一致", a.display()));
+        }
+        Ok(())
+    };
+    if !is_dir {
+        let len = |path: &Path| {
+            fs::metadata(path)
+                .map(|m| m.len())
+                .map_err(|e| e.to_string())
+        };
+        if len(source)? != len(stored)? {
+            return Err(format!("{} 的大小不一致", source.display()));
+        }
+        return same_content(source, stored);
+    }
+    let expected = inventory(source)?;
+    let copied = inventory(stored)?;
+    if let Some(path) = expected.keys().find(|p| expected.get(*p) != copied.get(*p)) {
+        return Err(format!("{} 缺失或大小不一致", path.display()));
+    }
+    if let Some(path) = copied.keys().find(|p| !expected.contains_key(*p)) {
+        return Err(format!("目标中多出了 {}", path.display()));
+    }
+    for (relative, _) in expected.iter().filter(|(_, (kind, _))| *kind == '-') {
+        same_content(&source.join(relative), &stored.join(relative))?;
+    }
+    Ok(())
+}
+
+// 目录下所有条目的相对路径到 (类型, 大小)，类型与 FileEntry::file_type 相同，符号链接为 'l'。
+// 不跟随符号链接
+fn inventory(root: &Path) -> Result<BTreeMap<PathBuf, (char, u64)>, String> {
+    let mut entries = BTreeMap::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let dir = root.join(&relative);
+        let read = fs::read_dir(&dir).map_err(|e| format!("无法读取 {}: {}", dir.display(), e))?;
+        for entry in read {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let relative = relative.join(entry.file_name());
+            let metadata = fs::symlink_metadata(entry.path()).map_err(|e| e.to_string())?;
+            let kind = if metadata.file_type().is_symlink() {
+                'l'
+            } else if metadata.is_dir() {
+                pending.push(relative.clone());
+                'd'
+            } else {
+                '-'
+            };
+            let size = if kind == 'd' { 0 } else { metadata.len() };
+            entries.insert(relative, (kind, size));
+        }
+    }
+    Ok(entries)
+}
+
+// <原路径>.where
+fn stub_path(original: &str) -> PathBuf {
+    PathBuf::from(format!("{}.{}", original, STUB_EXTENSION))
+}
+
+fn stub_text(record: &ColdRecord) -> String {
+    format!(
+        "此处原有的{}已移到冷存储，可按下面的位置找回。\n\n原路径: {}\n现位置: {}\n大小: {}\n移动日期: {}\n",
+        if record.is_dir { "目录" } else { "文件" },
+        record.original,
+        record.stored,
+        record.size_display,
+        date(record.moved_at)
+    )
+}
+
+fn write_stub(record: &ColdRecord) -> Result<String, String> {
+    let path = stub_path(&record.original);
+    if fs::symlink_metadata(&path).is_ok() {
+        return Err(format!("{} 已存在，未写入说明文件", path.display()));
+    }
+    fs::write(&path, stub_text(record)).map_err(|e| format!("写入说明文件失败: {}", e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// 在目标目录已有的清单后追加本次移入的条目；已有的清单无法解析时先备份，返回备份的说明
+fn append_manifest(
+    destination_dir: &Path,
+    records: &[ColdRecord],
+) -> Result<Option<String>, String> {
+    let path = destination_dir.join(MANIFEST_FILE);
+    let (mut manifest, backup) = read_records(&path)?;
+    manifest.extend(records.iter().cloned());
+    let content =
+        serde_json::to_string_pretty(&manifest).map_err(|e| format!("清单序列化失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("写入清单 {} 失败: {}", path.display(), e))?;
+    Ok(backup)
+}
+
+// 读取清单或索引。文件不存在时为空；无法解析时改名为 <文件名>.<时间戳>.bak 再按空处理，
+// 避免覆盖掉唯一的去向记录，返回备份的说明；备份失败时返回错误，调用方不应再写入该文件
+fn read_records(path: &Path) -> Result<(Vec<ColdRecord>, Option<String>), String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), None)),
+        Err(e) => return Err(format!("无法读取 {}: {}", path.display(), e)),
+    };
+    if let Ok(records) = serde_json::from_str(&content) {
+        return Ok((records, None));
+    }
+    let backup = backup_path(path);
+    fs::rename(path, &backup)
+        .map_err(|e| format!("{} 无法解析且未能备份，已停止写入: {}", path.display(), e))?;
+    Ok((
+        Vec::new(),
+        Some(format!(
+            "{} 无法解析，已备份为 {}",
+            path.display(),
+            backup.display()
+        )),
+    ))
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", seconds));
+    path.with_file_name(name)
+}
+
+// 移到冷存储的所有条目，保存在数据目录中，供按原路径或名称查找
+pub struct ColdStorageIndex {
+    path: PathBuf,
+    records: Mutex<Vec<ColdRecord>>,
+    /// 加载时无法读取或备份已有的索引，此时不再写入
+    load_error: Option<String>,
+}
+
+impl ColdStorageIndex {
+    // 索引无法解析时备份后从空索引开始；无法备份时保持只读，add 返回错误而不覆盖原文件
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(INDEX_FILE);
+        let (records, error) = match read_records(&path) {
+            Ok((records, backup)) => {
+                if let Some(backup) = backup {
+                    eprintln!("{}", backup);
+                }
+                (records, None)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                (Vec::new(), Some(e))
+            }
+        };
+        ColdStorageIndex {
+            path,
+            records: Mutex::new(records),
+            load_error: error,
+        }
+    }
+
+    pub fn add(&self, moved: &[ColdRecord]) -> Result<(), String> {
+        if let Some(e) = &self.load_error {
+            return Err(e.clone());
+        }
+        let mut records = self.records.lock().unwrap();
+        records.extend(moved.iter().cloned());
+        let content =
+            serde_json::to_string(&*records).map_err(|e| format!("索引序列化失败: {}", e))?;
+        fs::write(&self.path, content).map_err(|e| format!("保存冷存储索引失败: {}", e))
+    }
+
+    // 原路径或现位置中包含 query 的条目（不区分大小写），最近移动的在前
+    pub fn search(&self, query: &str) -> Vec<ColdRecord> {
+        search(&self.records.lock().unwrap(), query)
+    }
+}
+
+fn search(records: &[ColdRecord], query: &str) -> Vec<ColdRecord> {
+    let query = fold(query.trim());
+    let mut hits: Vec<ColdRecord> = records
+        .iter()
+        .filter(|r| fold(&r.original).contains(&query) || fold(&r.stored).contains(&query))
+        .cloned()
+        .collect();
+    hits.sort_by_key(|r| std::cmp::Reverse(r.moved_at));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(original: &str, stored: &str, days: u64) -> ColdRecord {
+        ColdRecord {
+            original: original.to_string(),
+            stored: stored.to_string(),
+            is_dir: true,
+            size_raw: 500,
+            size_display: human_readable_size(500),
+            moved_at: SystemTime::UNIX_EPOCH + Duration::from_secs(days * 24 * 60 * 60),
+            stub: None,
+        }
+    }
+
+    #[test]
+    fn copies_are_verified_file_by_file() {
+        let base = std::env::temp_dir().join(format!("disksight-cold-{}", std::process::id()));
+        let source = base.join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.txt"), b"alpha").unwrap();
+        fs::write(source.join("nested").join("b.txt"), b"bravo").unwrap();
+        let copy = |name: &str| {
+            let stored = base.join(name);
+            fs::create_dir_all(stored.join("nested")).unwrap();
+            fs::write(stored.join("a.txt"), b"alpha").unwrap();
+            stored
+        };
+
+        let good = copy("good");
+        fs::write(good.join("nested").join("b.txt"), b"bravo").unwrap();
+        // 总大小相同，但文件放错了位置
+        let moved = copy("moved");
+        fs::write(moved.join("b.txt"), b"bravo").unwrap();
+        // 大小相同，内容不同
+        let changed = copy("changed");
+        fs::write(changed.join("nested").join("b.txt"), b"BRAVO").unwrap();
+
+        let results = (
+            verify_copy(&source, &good, true),
+            verify_copy(&source, &moved, true),
+            verify_copy(&source, &changed, true),
+            verify_copy(&source.join("a.txt"), &good.join("a.txt"), false),
+            verify_copy(&source.join("a.txt"), &changed.join("nested/b.txt"), false),
+        );
+        fs::remove_dir_all(&base).unwrap();
+        assert!(results.0.is_ok());
+        assert!(results.1.is_err());
+        assert!(results.2.is_err());
+        assert!(results.3.is_ok());
+        assert!(results.4.is_err());
+    }
+
+    #[test]
+    fn unparsable_manifest_is_backed_up_before_writing() {
+        let dir = std::env::temp_dir().join(format!("disksight-manifest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), b"{ not json").unwrap();
+        let backup = append_manifest(&dir, &[record("/data/a", "/cold/a", 1)]);
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".bak"))
+            .map(|e| fs::read(e.path()).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(backup.unwrap().is_some());
+        assert_eq!(backups, vec![b"{ not json".to_vec()]);
+    }
+
+    #[test]
+    fn stubs_point_to_the_new_location_and_index_is_searchable() {
+        let old = record("/data/Projects/2019", "/mnt/nas/cold/2019", 1);
+        let new = record("/data/projects/2020", "/mnt/nas/cold/2020", 2);
+        assert_eq!(
+            stub_path(&old.original),
+            Path::new("/data/Projects/2019.where")
+        );
+        let text = stub_text(&old);
+        assert!(text.contains("现位置: /mnt/nas/cold/2019"));
+        assert!(text.contains("移动日期: 1970-01-02"));
+
+        let records = vec![old, new];
+        let hits: Vec<String> = search(&records, " PROJECTS ")
+            .into_iter()
+            .map(|r| r.original)
+            .collect();
+        assert_eq!(hits, vec!["/data/projects/2020", "/data/Projects/2019"]);
+        assert_eq!(search(&records, "nas/cold/2019").len(), 1);
+        assert!(search(&records, "photos").is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+// 按语言习惯排序名称：数字按数值比较（file2 在 file10 之前），可选按汉语拼音排序汉字，
+// 取代按字节比较的排序
+use icu_collator::{Collator, CollatorOptions, Numeric, Strength};
+use icu_locid::{locale, Locale};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameCollation {
+    /// 与语言无关的通用排序，数字按数值比较
+    #[default]
+    Natural,
+    /// 汉字按拼音排序，数字按数值比较
+    Pinyin,
+}
+
+fn collator(mode: NameCollation) -> Option<Collator> {
+    let locale: Locale = match mode {
+        NameCollation::Natural => locale!("und"),
+        NameCollation::Pinyin => locale!("zh"),
+    };
+    let mut options = CollatorOptions::new();
+    options.strength = Some(Strength::Tertiary);
+    options.numeric = Some(Numeric::On);
+    Collator::try_new(&locale.into(), options).ok()
+}
+
+// 按 mode 排序名称；排序规则不可用时退回按字节比较
+pub fn sort_names(names: &mut [String], mode: NameCollation) {
+    match collator(mode) {
+        // 排序规则认为相同的名称（如仅 Unicode 规范化形式不同）再按字节区分，结果稳定
+        Some(collator) => names.sort_by(|a, b| match collator.compare(a, b) {
+            Ordering::Equal => a.cmp(b),
+            order => order,
+        }),
+        None => names.sort(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str], mode: NameCollation) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        sort_names(&mut names, mode);
+        names
+    }
+
+    #[test]
+    fn numbers_compare_by_value_and_han_by_pinyin() {
+        assert_eq!(
+            sorted(&["file10", "file2", "File1"], NameCollation::Natural),
+            ["File1", "file2", "file10"]
+        );
+        assert_eq!(
+            sorted(&["张三", "阿里", "李四"], NameCollation::Pinyin),
+            ["阿里", "李四", "张三"]
+        );
+        assert_eq!(
+            sorted(&["李2", "李10", "李1"], NameCollation::Pinyin),
+            ["李1", "李2", "李10"]
+        );
+    }
+}
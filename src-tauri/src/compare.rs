@@ -0,0 +1,230 @@
+use crate::models::FileEntry;
+use crate::normalize::nfc;
+use crate::scan_store::StoredScan;
+use crate::utils::{canonical_string, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareStatus {
+    /// 仅存在于 B
+    Added,
+    /// 仅存在于 A
+    Removed,
+    Grown,
+    Shrunk,
+    Unchanged,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompareRow {
+    /// 相对扫描根目录的路径，两侧以此对齐
+    pub relative_path: String,
+    pub name: String,
+    /// 树中的层级，第一级为 0
+    pub depth: usize,
+    pub is_dir: bool,
+    pub size_a: Option<u64>,
+    pub size_a_display: Option<String>,
+    pub size_b: Option<u64>,
+    pub size_b_display: Option<String>,
+    /// B 相对 A 的变化量
+    pub delta: i64,
+    /// 带符号的可读变化量，如 "+1.5MB"
+    pub delta_display: String,
+    pub status: CompareStatus,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompareView {
+    pub root_a: String,
+    pub root_b: String,
+    pub total_a: u64,
+    pub total_b: u64,
+    pub total_delta: i64,
+    pub total_delta_display: String,
+    /// 按树的先序排列，同级按名称排序，可直接渲染为左右分栏
+    pub rows: Vec<CompareRow>,
+}
+
+// 以相对路径对齐两次扫描的条目，生成分栏对比视图需要的行数据
+pub fn compare_view(scan_a: &StoredScan, scan_b: &StoredScan) -> CompareView {
+    let mut aligned: BTreeMap<Vec<String>, (Option<&FileEntry>, Option<&FileEntry>)> =
+        BTreeMap::new();
+    for (key, entry) in relative_entries(scan_a) {
+        aligned.entry(key).or_default().0 = Some(entry);
+    }
+    for (key, entry) in relative_entries(scan_b) {
+        aligned.entry(key).or_default().1 = Some(entry);
+    }
+
+    // 按路径分量排序即为树的先序，父目录总在其子项之前
+    let rows: Vec<CompareRow> = aligned
+        .into_iter()
+        .map(|(components, (a, b))| compare_row(&components, a, b))
+        .collect();
+    let total_a = top_level_total(&rows, |r| r.size_a);
+    let total_b = top_level_total(&rows, |r| r.size_b);
+    let total_delta = signed_delta(total_a, total_b);
+    CompareView {
+        root_a: scan_a.root.to_string_lossy().to_string(),
+        root_b: scan_b.root.to_string_lossy().to_string(),
+        total_a,
+        total_b,
+        total_delta,
+        total_delta_display: delta_display(total_delta),
+        rows,
+    }
+}
+
+fn relative_entries(scan: &StoredScan) -> Vec<(Vec<String>, &FileEntry)> {
+    let canonical_root = canonical_string(&scan.root).map(PathBuf::from);
+    scan.result
+        .entries
+        .iter()
+        .map(|entry| {
+            let path = entry.path();
+            let path = Path::new(&path);
+            let relative = path
+                .strip_prefix(&scan.root)
+                .ok()
+                .or_else(|| {
+                    canonical_root
+                        .as_deref()
+                        .and_then(|r| path.strip_prefix(r).ok())
+                })
+                .unwrap_or(Path::new(&entry.name));
+            let components = relative
+                .components()
+                // 两侧的同名条目可能分别为 NFC 与 NFD 形式（如从 macOS 复制的目录）
+                .map(|c| nfc(&c.as_os_str().to_string_lossy()).into_owned())
+                .collect();
+            (components, entry)
+        })
+        .collect()
+}
+
+fn compare_row(components: &[String], a: Option<&FileEntry>, b: Option<&FileEntry>) -> CompareRow {
+    let size_a = a.map(|e| e.size_raw);
+    let size_b = b.map(|e| e.size_raw);
+    let delta = signed_delta(size_a.unwrap_or(0), size_b.unwrap_or(0));
+    let status = match (size_a, size_b) {
+        (None, _) => CompareStatus::Added,
+        (_, None) => CompareStatus::Removed,
+        _ if delta > 0 => CompareStatus::Grown,
+        _ if delta < 0 => CompareStatus::Shrunk,
+        _ => CompareStatus::Unchanged,
+    };
+    let entry = a.or(b).expect("对齐的行至少一侧存在");
+    CompareRow {
+        relative_path: components.join("/"),
+        name: components
+            .last()
+            .cloned()
+            .unwrap_or_else(|| entry.name.clone()),
+        depth: components.len().saturating_sub(1),
+        is_dir: entry.file_type == 'd',
+        size_a,
+        size_a_display: size_a.map(human_readable_size),
+        size_b,
+        size_b_display: size_b.map(human_readable_size),
+        delta,
+        delta_display: delta_display(delta),
+        status,
+    }
+}
+
+// 只累加第一级条目，目录大小已包含其子项
+fn top_level_total(rows: &[CompareRow], size: impl Fn(&CompareRow) -> Option<u64>) -> u64 {
+    rows.iter()
+        .filter(|r| r.depth == 0)
+        .filter_map(size)
+        .fold(0u64, |acc, s| acc.saturating_add(s))
+}
+
+fn signed_delta(a: u64, b: u64) -> i64 {
+    if b >= a {
+        i64::try_from(b - a).unwrap_or(i64::MAX)
+    } else {
+        i64::try_from(a - b).map(|d| -d).unwrap_or(i64::MIN)
+    }
+}
+
+fn delta_display(delta: i64) -> String {
+    match delta {
+        0 => "0B".to_string(),
+        d if d > 0 => format!("+{}", human_readable_size(d.unsigned_abs())),
+        d => format!("-{}", human_readable_size(d.unsigned_abs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DirectoryResult;
+    use std::time::SystemTime;
+
+    fn entry(root: &str, relative: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: format!("{}/{}", root, relative).into(),
+            name: relative.rsplit('/').next().unwrap().to_string(),
+            severity: None,
+        }
+    }
+
+    fn scan(root: &str, entries: Vec<FileEntry>) -> StoredScan {
+        StoredScan::new(
+            PathBuf::from(root),
+            DirectoryResult {
+                scan_id: None,
+                entries,
+                query_time: 0.0,
+                telemetry: None,
+                scan_plan: None,
+                completeness: Default::default(),
+                skipped: Default::default(),
+            },
+            SystemTime::UNIX_EPOCH,
+        )
+    }
+
+    #[test]
+    fn rows_are_aligned_in_tree_order() {
+        let a = scan(
+            "/a",
+            vec![
+                entry("/a", "docs", 'd', 300),
+                entry("/a", "docs/x.txt", '-', 300),
+                entry("/a", "old.log", '-', 50),
+            ],
+        );
+        let b = scan(
+            "/b",
+            vec![
+                entry("/b", "docs-new", '-', 10),
+                entry("/b", "docs/x.txt", '-', 200),
+                entry("/b", "docs", 'd', 200),
+            ],
+        );
+        let view = compare_view(&a, &b);
+        let paths: Vec<_> = view.rows.iter().map(|r| r.relative_path.as_str()).collect();
+        assert_eq!(paths, ["docs", "docs/x.txt", "docs-new", "old.log"]);
+        assert_eq!(view.rows[0].status, CompareStatus::Shrunk);
+        assert_eq!(view.rows[0].delta_display, "-100.0B");
+        assert_eq!(view.rows[1].depth, 1);
+        assert_eq!(view.rows[2].status, CompareStatus::Added);
+        assert_eq!(view.rows[3].status, CompareStatus::Removed);
+        assert_eq!(
+            (view.total_a, view.total_b, view.total_delta),
+            (350, 210, -140)
+        );
+    }
+}
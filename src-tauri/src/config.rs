@@ -0,0 +1,554 @@
+use crate::agent::AgentEndpoint;
+use crate::alerts::{AlertSettings, SmtpSettings};
+use crate::annotations::PathAnnotation;
+use crate::collation::NameCollation;
+use crate::ignore_rules::{IgnoreMode, ScanOverride};
+use crate::parallelism::ParallelismSettings;
+use crate::power::PowerSettings;
+use crate::retention::RetentionPolicy;
+use crate::time_format::{apply_time_format, TimeFormat, TimeStyle};
+use crate::utils::{apply_size_format, SizeFormat};
+use crate::warm_cache::WarmCacheSettings;
+use crate::watchdog::TimeoutSettings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "config.json";
+const SERVER_CONFIG_FILE: &str = "config.server.json";
+// 启动时选择配置档案的环境变量，也可用命令行参数 --profile server
+const PROFILE_ENV: &str = "DISKSIGHT_PROFILE";
+// 服务器档案下扫描和分析命令的超时时间
+const SERVER_TIMEOUT_SECONDS: u64 = 6 * 60 * 60;
+const GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighlightRule {
+    /// 大小达到该值（字节）时命中
+    pub min_bytes: u64,
+    pub severity: Severity,
+    /// 前端显示用的颜色，如 "#ef4444"
+    pub color: String,
+}
+
+// 配置档案，各自保存在独立的配置文件中，启动时选择
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// 交互使用的桌面档案
+    #[default]
+    Desktop,
+    /// 无人值守的服务器档案：默认只读、超时更长、时间按 UTC 显示
+    Server,
+}
+
+impl Profile {
+    // 依次读取命令行参数 --profile 和环境变量 DISKSIGHT_PROFILE，未指定时为桌面档案
+    pub fn from_startup() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        Self::from_args(&args)
+            .or_else(|| {
+                std::env::var(PROFILE_ENV)
+                    .ok()
+                    .and_then(|v| Self::parse(&v))
+            })
+            .unwrap_or_default()
+    }
+
+    fn from_args(args: &[String]) -> Option<Self> {
+        args.iter().enumerate().find_map(|(i, arg)| {
+            if let Some(value) = arg.strip_prefix("--profile=") {
+                Self::parse(value)
+            } else if arg == "--profile" {
+                args.get(i + 1).and_then(|value| Self::parse(value))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "desktop" => Some(Profile::Desktop),
+            "server" => Some(Profile::Server),
+            other => {
+                eprintln!("未知的配置档案 {}，使用桌面档案", other);
+                None
+            }
+        }
+    }
+
+    fn config_file(self) -> &'static str {
+        match self {
+            Profile::Desktop => CONFIG_FILE,
+            Profile::Server => SERVER_CONFIG_FILE,
+        }
+    }
+
+    // 该档案首次使用时的默认配置
+    pub fn defaults(self) -> AppConfig {
+        match self {
+            Profile::Desktop => AppConfig::default(),
+            Profile::Server => AppConfig {
+                read_only: true,
+                timeouts: TimeoutSettings {
+                    scan_seconds: Some(SERVER_TIMEOUT_SECONDS),
+                    analysis_seconds: Some(SERVER_TIMEOUT_SECONDS),
+                    ..TimeoutSettings::default()
+                },
+                time_format: TimeFormat {
+                    style: TimeStyle::Absolute,
+                    locale: String::new(),
+                    utc_offset_minutes: 0,
+                },
+                ..AppConfig::default()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// 按大小高亮的规则，命中阈值最大的一条生效
+    pub highlight_rules: Vec<HighlightRule>,
+    /// 用户标记为“已知晓、无需处理”的目录，不出现在占用排行和增长提醒中
+    pub acknowledged_paths: Vec<String>,
+    /// 只读模式：禁止删除、重命名、新建等所有修改文件系统的操作
+    pub read_only: bool,
+    /// 后台建立文件名索引的目录
+    pub indexed_roots: Vec<String>,
+    /// 监控大小变化的目录（书签），每次扫描后记录总大小
+    pub monitored_roots: Vec<String>,
+    /// 监控目录的阈值、增长提醒及其投递渠道
+    pub alerts: AlertSettings,
+    /// 保留策略，每次扫描后对扫描范围内的策略重新评估
+    pub retention_policies: Vec<RetentionPolicy>,
+    /// 扫描线程数与读取方式，默认按存储类型自动选择
+    pub parallelism: ParallelismSettings,
+    /// 大小的单位制、固定单位及分隔符，后端生成的显示字符串和前端都按此格式显示
+    pub size_format: SizeFormat,
+    /// 创建、修改时间显示为相对时间还是按语言环境显示的日期
+    pub time_format: TimeFormat,
+    /// 扫描与分析命令的超时时间及无进度检测阈值
+    pub timeouts: TimeoutSettings,
+    /// 在独立的工作进程中扫描目录，扫描器崩溃或耗尽内存时不会拖垮界面
+    pub scan_in_worker: bool,
+    /// 扫描时是否按 .gitignore 与 .dsignore 跳过被忽略的内容，可在每次扫描时单独指定
+    pub ignore_mode: IgnoreMode,
+    /// 多主机汇总面板要查询的代理（以 --agent 模式运行的 DiskSight）
+    pub agents: Vec<AgentEndpoint>,
+    /// 用户为路径添加的备注与标签
+    pub annotations: Vec<PathAnnotation>,
+    /// 隔离的条目保留的天数，超过后在启动时自动清除
+    pub quarantine_days: u64,
+    /// 按路径附加的扫描选项（排除模式、跟随符号链接、忽略文件模式）
+    pub scan_overrides: Vec<ScanOverride>,
+    /// 扫描时先统计条目总数再计算大小，进度显示为准确的百分比；默认直接计算大小
+    pub determinate_scans: bool,
+    /// 扫描时跳过之前反复无权限读取或无响应的路径
+    pub skip_known_inaccessible: bool,
+    /// 名称排序规则：数字按数值比较，可选按拼音排序汉字
+    pub name_collation: NameCollation,
+    /// 启动后在后台预先扫描监控目录
+    pub warm_cache: WarmCacheSettings,
+    /// 使用电池或计费网络时推迟后台任务与远程扫描，及对检测结果的强制指定
+    pub power: PowerSettings,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            highlight_rules: vec![
+                HighlightRule {
+                    min_bytes: 10 * GB,
+                    severity: Severity::Critical,
+                    color: "#ef4444".to_string(),
+                },
+                HighlightRule {
+                    min_bytes: GB,
+                    severity: Severity::Warning,
+                    color: "#f97316".to_string(),
+                },
+            ],
+            acknowledged_paths: Vec::new(),
+            read_only: false,
+            indexed_roots: Vec::new(),
+            monitored_roots: Vec::new(),
+            alerts: AlertSettings::default(),
+            retention_policies: Vec::new(),
+            parallelism: ParallelismSettings::default(),
+            size_format: SizeFormat::default(),
+            time_format: TimeFormat::default(),
+            timeouts: TimeoutSettings::default(),
+            scan_in_worker: true,
+            ignore_mode: IgnoreMode::Off,
+            agents: Vec::new(),
+            annotations: Vec::new(),
+            quarantine_days: 30,
+            scan_overrides: Vec::new(),
+            determinate_scans: false,
+            skip_known_inaccessible: true,
+            name_collation: NameCollation::default(),
+            warm_cache: WarmCacheSettings::default(),
+            power: PowerSettings::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    // 根据高亮规则计算某个大小对应的级别
+    pub fn severity_for(&self, size: u64) -> Option<Severity> {
+        self.highlight_rules
+            .iter()
+            .filter(|rule| size >= rule.min_bytes)
+            .max_by_key(|rule| rule.min_bytes)
+            .map(|rule| rule.severity)
+    }
+
+    // 修改文件系统的命令执行前调用
+    pub fn ensure_writable(&self) -> Result<(), String> {
+        if self.read_only {
+            return Err("当前处于只读模式，已禁止修改文件".to_string());
+        }
+        Ok(())
+    }
+
+    // 只读模式下不允许通过整体替换配置修改会删除文件或连接其他主机的设置：
+    // 保留策略（执行时会移走文件）和代理列表
+    pub fn ensure_change_allowed(&self, new: &AppConfig) -> Result<(), String> {
+        if !self.read_only {
+            return Ok(());
+        }
+        let mut changed = Vec::new();
+        if self.retention_policies != new.retention_policies {
+            changed.push("保留策略");
+        }
+        if self.agents != new.agents {
+            changed.push("代理");
+        }
+        if changed.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("当前处于只读模式，不能修改{}", changed.join("、")))
+        }
+    }
+
+    // 去掉所有密钥后的副本，用于导出等离开本机的场景：代理令牌、SMTP 密码和 webhook 地址
+    // （Slack 等 webhook 的地址本身就是凭据）。新增含密钥的字段时须同时加到这里和 restore_secrets
+    pub fn without_secrets(&self) -> AppConfig {
+        let mut config = self.clone();
+        for agent in &mut config.agents {
+            agent.token.clear();
+        }
+        if let Some(smtp) = &mut config.alerts.smtp {
+            smtp.password.clear();
+        }
+        for webhook in &mut config.alerts.webhooks {
+            webhook.url.clear();
+        }
+        config
+    }
+
+    // 用本机配置补回 without_secrets 清空的密钥：代理按地址、SMTP 按服务器和用户名、
+    // webhook 按位置和格式匹配。本机也没有的密钥无法恢复，返回需要重新填写的项；
+    // 地址为空的 webhook 无法使用，直接去掉
+    pub fn restore_secrets(&mut self, local: &AppConfig) -> Vec<String> {
+        let mut missing = Vec::new();
+        for agent in self.agents.iter_mut().filter(|a| a.token.is_empty()) {
+            match local.agents.iter().find(|l| l.address == agent.address) {
+                Some(local) => agent.token = local.token.clone(),
+                None => missing.push(format!("代理 {} 的访问令牌", agent.name)),
+            }
+        }
+        if let Some(smtp) = self.alerts.smtp.as_mut().filter(|s| s.password.is_empty()) {
+            match &local.alerts.smtp {
+                Some(l) if l.host == smtp.host && l.username == smtp.username => {
+                    smtp.password = l.password.clone()
+                }
+                _ => missing.push(format!("邮件服务器 {} 的密码", smtp.host)),
+            }
+        }
+        let mut index = 0;
+        self.alerts.webhooks.retain_mut(|webhook| {
+            let position = index;
+            index += 1;
+            if webhook.url.is_empty() {
+                match local.alerts.webhooks.get(position) {
+                    Some(l) if l.format == webhook.format => webhook.url = l.url.clone(),
+                    _ => missing.push(format!("第 {} 个 webhook 的地址", position + 1)),
+                }
+            }
+            !webhook.url.is_empty()
+        });
+        missing
+    }
+
+    // 目录是否在监控列表中，传入的路径需已规范化
+    pub fn is_monitored(&self, path: &str) -> bool {
+        self.monitored_roots.iter().any(|root| root == path)
+    }
+
+    // 路径的备注与标签
+    pub fn annotation(&self, path: &str) -> Option<&PathAnnotation> {
+        self.annotations.iter().find(|a| a.path == path)
+    }
+
+    // 路径本身或其任一上级目录已被标记为忽略
+    pub fn is_acknowledged(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        self.acknowledged_paths
+            .iter()
+            .any(|ack| path.starts_with(ack))
+    }
+}
+
+// 持有配置及其保存位置，所有修改都会立即写回磁盘
+pub struct ConfigStore {
+    path: PathBuf,
+    profile: Profile,
+    config: AppConfig,
+    /// 加载时配置文件尚不存在，即首次运行
+    first_run: bool,
+}
+
+impl ConfigStore {
+    // 从配置目录加载档案对应的配置文件，文件不存在或损坏时使用该档案的默认配置
+    pub fn load(dir: &Path, profile: Profile) -> Self {
+        let path = dir.join(profile.config_file());
+        let first_run = !path.exists();
+        let mut config: AppConfig = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("配置文件解析失败，使用默认配置: {}", e);
+                profile.defaults()
+            }),
+            Err(_) => profile.defaults(),
+        };
+        // 旧版本把 SMTP 密码明文写在配置文件里，迁移到钥匙串后重写配置文件
+        let mut migrated = false;
+        if let Some(smtp) = config.alerts.smtp.as_mut() {
+            if smtp.password.is_empty() {
+                smtp.load_password();
+            } else {
+                match smtp.store_password() {
+                    Ok(()) => migrated = true,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+        apply_size_format(config.size_format.clone());
+        apply_time_format(config.time_format.clone());
+        let store = ConfigStore {
+            path,
+            profile,
+            config,
+            first_run,
+        };
+        if migrated {
+            if let Err(e) = store.save() {
+                eprintln!("{}", e);
+            }
+        }
+        store
+    }
+
+    pub fn get(&self) -> &AppConfig {
+        &self.config
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    pub fn is_first_run(&self) -> bool {
+        self.first_run
+    }
+
+    pub fn set(&mut self, mut config: AppConfig) -> Result<(), String> {
+        sync_smtp_password(self.config.alerts.smtp.as_ref(), &mut config)?;
+        self.config = config;
+        apply_size_format(self.config.size_format.clone());
+        apply_time_format(self.config.time_format.clone());
+        self.save()
+    }
+
+    // 修改配置并保存
+    pub fn update(&mut self, f: impl FnOnce(&mut AppConfig)) -> Result<(), String> {
+        let previous = self.config.alerts.smtp.clone();
+        f(&mut self.config);
+        sync_smtp_password(previous.as_ref(), &mut self.config)?;
+        apply_size_format(self.config.size_format.clone());
+        apply_time_format(self.config.time_format.clone());
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&self.config)
+            .map_err(|e| format!("配置序列化失败: {}", e))?;
+        fs::write(&self.path, content).map_err(|e| format!("保存配置失败: {}", e))
+    }
+}
+
+// SMTP 密码不写入配置文件，前端取到的配置里也没有：传回的配置不含密码时沿用同一账户的当前密码，
+// 带有新密码时写入系统钥匙串
+fn sync_smtp_password(
+    previous: Option<&SmtpSettings>,
+    config: &mut AppConfig,
+) -> Result<(), String> {
+    let Some(smtp) = config.alerts.smtp.as_mut() else {
+        return Ok(());
+    };
+    let previous = previous.filter(|p| p.host == smtp.host && p.username == smtp.username);
+    if smtp.password.is_empty() {
+        if let Some(previous) = previous {
+            smtp.password = previous.password.clone();
+        }
+        return Ok(());
+    }
+    if previous.is_some_and(|p| p.password == smtp.password) {
+        return Ok(());
+    }
+    smtp.store_password()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_highest_matching_rule() {
+        let config = AppConfig::default();
+        assert_eq!(config.severity_for(512), None);
+        assert_eq!(config.severity_for(2 * GB), Some(Severity::Warning));
+        assert_eq!(config.severity_for(10 * GB), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn profile_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            Profile::from_args(&args(&["disk-sight", "--profile", "server"])),
+            Some(Profile::Server)
+        );
+        assert_eq!(
+            Profile::from_args(&args(&["disk-sight", "--profile=Desktop"])),
+            Some(Profile::Desktop)
+        );
+        assert_eq!(Profile::from_args(&args(&["disk-sight"])), None);
+        assert!(Profile::Server.defaults().read_only);
+    }
+
+    #[test]
+    fn read_only_config_keeps_retention_policies_and_agents() {
+        let current = Profile::Server.defaults();
+        let mut new = current.clone();
+        new.quarantine_days += 1;
+        assert!(current.ensure_change_allowed(&new).is_ok());
+
+        new.agents.push(AgentEndpoint {
+            name: "nas".to_string(),
+            address: "nas:7879".to_string(),
+            token: "secret".to_string(),
+        });
+        assert!(current.ensure_change_allowed(&new).is_err());
+
+        let writable = AppConfig {
+            read_only: false,
+            ..current
+        };
+        assert!(writable.ensure_change_allowed(&new).is_ok());
+    }
+
+    #[test]
+    fn secrets_are_removed_on_export_and_restored_from_local() {
+        use crate::alerts::{SmtpSettings, WebhookFormat, WebhookTarget};
+        let local = AppConfig {
+            agents: vec![AgentEndpoint {
+                name: "nas".to_string(),
+                address: "nas:7879".to_string(),
+                token: "agent-secret".to_string(),
+            }],
+            alerts: AlertSettings {
+                webhooks: vec![WebhookTarget {
+                    url: "https://hooks.example/secret".to_string(),
+                    format: WebhookFormat::Json,
+                }],
+                smtp: Some(SmtpSettings {
+                    host: "smtp.example".to_string(),
+                    port: 587,
+                    username: "ops".to_string(),
+                    password: "smtp-secret".to_string(),
+                    from: "ops@example".to_string(),
+                    to: Vec::new(),
+                }),
+                ..AlertSettings::default()
+            },
+            ..AppConfig::default()
+        };
+        let exported = local.without_secrets();
+        assert!(exported.agents[0].token.is_empty());
+        assert!(exported.alerts.smtp.as_ref().unwrap().password.is_empty());
+        assert!(exported.alerts.webhooks[0].url.is_empty());
+
+        let mut restored = exported.clone();
+        assert!(restored.restore_secrets(&local).is_empty());
+        assert_eq!(restored.agents[0].token, "agent-secret");
+        assert_eq!(restored.alerts.smtp.unwrap().password, "smtp-secret");
+        assert_eq!(
+            restored.alerts.webhooks[0].url,
+            "https://hooks.example/secret"
+        );
+
+        let mut elsewhere = exported;
+        let missing = elsewhere.restore_secrets(&AppConfig::default());
+        assert_eq!(missing.len(), 3);
+        assert!(elsewhere.alerts.webhooks.is_empty());
+    }
+
+    #[test]
+    fn smtp_password_survives_configs_without_it() {
+        let smtp = |password: &str| SmtpSettings {
+            host: "smtp.example".to_string(),
+            port: 587,
+            username: "ops".to_string(),
+            password: password.to_string(),
+            from: "ops@example".to_string(),
+            to: Vec::new(),
+        };
+        let previous = smtp("secret");
+        let mut config = AppConfig::default();
+        config.alerts.smtp = Some(smtp(""));
+        sync_smtp_password(Some(&previous), &mut config).unwrap();
+        assert_eq!(config.alerts.smtp.as_ref().unwrap().password, "secret");
+
+        let mut other_account = AppConfig::default();
+        other_account.alerts.smtp = Some(SmtpSettings {
+            username: "backup".to_string(),
+            ..smtp("")
+        });
+        sync_smtp_password(Some(&previous), &mut other_account).unwrap();
+        assert!(other_account.alerts.smtp.unwrap().password.is_empty());
+    }
+
+    #[test]
+    fn acknowledged_covers_children() {
+        let config = AppConfig {
+            acknowledged_paths: vec!["/games/steam".to_string()],
+            ..AppConfig::default()
+        };
+        assert!(config.is_acknowledged("/games/steam"));
+        assert!(config.is_acknowledged("/games/steam/common"));
+        assert!(!config.is_acknowledged("/games/steamapps"));
+    }
+}
@@ -0,0 +1,240 @@
+// btrfs 子卷与 ZFS 数据集的占用。写时复制文件系统上快照与克隆共享数据块，逐个文件累加的大小
+// 既不等于删除某个目录能释放的空间，也不包含快照独占的部分，因此直接读取文件系统自己的统计
+use crate::drives::mount_of;
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CowFileSystem {
+    Btrfs,
+    Zfs,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CowVolume {
+    /// btrfs 子卷相对文件系统根的路径，ZFS 数据集名称
+    pub name: String,
+    /// 可通过该子卷或数据集访问的数据量，包括与快照共享的部分
+    pub referenced_bytes: Option<u64>,
+    /// 只属于该子卷或数据集、删除后可以释放的数据量
+    pub unique_bytes: Option<u64>,
+    /// 只被快照引用的数据量，仅 ZFS 提供
+    pub snapshot_bytes: Option<u64>,
+    pub snapshot_count: u64,
+    pub referenced_display: Option<String>,
+    pub unique_display: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CowVolumeReport {
+    pub file_system: CowFileSystem,
+    pub mount_point: String,
+    /// 按独占空间降序，未知时按引用空间
+    pub volumes: Vec<CowVolume>,
+    /// 统计不完整时的说明，如 btrfs 未启用配额
+    pub note: Option<String>,
+}
+
+// 路径不在 btrfs 或 ZFS 上时返回 None
+pub fn cow_volume_report(path: &Path) -> Result<Option<CowVolumeReport>, String> {
+    let mount = mount_of(path).ok_or_else(|| "无法确定路径所在的卷".to_string())?;
+    let (file_system, mut volumes, note) = match mount.file_system.as_str() {
+        "btrfs" => {
+            let (volumes, note) = btrfs_volumes(&mount.mount_point)?;
+            (CowFileSystem::Btrfs, volumes, note)
+        }
+        // ZFS 挂载的设备名即数据集名称
+        "zfs" => (CowFileSystem::Zfs, zfs_volumes(&mount.device)?, None),
+        _ => return Ok(None),
+    };
+    volumes.sort_by_key(|v| std::cmp::Reverse(v.unique_bytes.or(v.referenced_bytes)));
+    Ok(Some(CowVolumeReport {
+        file_system,
+        mount_point: mount.mount_point,
+        volumes,
+        note,
+    }))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("无法运行 {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} 执行失败: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn volume(
+    name: String,
+    referenced: Option<u64>,
+    unique: Option<u64>,
+    snapshots: Option<u64>,
+    snapshot_count: u64,
+) -> CowVolume {
+    CowVolume {
+        name,
+        referenced_bytes: referenced,
+        unique_bytes: unique,
+        snapshot_bytes: snapshots,
+        snapshot_count,
+        referenced_display: referenced.map(human_readable_size),
+        unique_display: unique.map(human_readable_size),
+    }
+}
+
+// 所在数据集及其下所有文件系统数据集
+fn zfs_volumes(dataset: &str) -> Result<Vec<CowVolume>, String> {
+    let datasets = run(
+        "zfs",
+        &[
+            "list",
+            "-H",
+            "-p",
+            "-r",
+            "-t",
+            "filesystem",
+            "-o",
+            "name,referenced,usedbydataset,usedbysnapshots",
+            dataset,
+        ],
+    )?;
+    let snapshots = run(
+        "zfs",
+        &["list", "-H", "-r", "-t", "snapshot", "-o", "name", dataset],
+    )?;
+    Ok(parse_zfs(&datasets, &snapshots))
+}
+
+fn parse_zfs(datasets: &str, snapshots: &str) -> Vec<CowVolume> {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for name in snapshots.lines() {
+        if let Some((dataset, _)) = name.split_once('@') {
+            *counts.entry(dataset).or_default() += 1;
+        }
+    }
+    datasets
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let mut number = || fields.next().and_then(|v| v.parse::<u64>().ok());
+            let (referenced, unique, snapshots) = (number(), number(), number());
+            let count = counts.get(name).copied().unwrap_or(0);
+            Some(volume(
+                name.to_string(),
+                referenced,
+                unique,
+                snapshots,
+                count,
+            ))
+        })
+        .collect()
+}
+
+// 子卷列表需要 root 权限；未启用配额（btrfs quota enable）时没有引用与独占空间
+fn btrfs_volumes(mount_point: &str) -> Result<(Vec<CowVolume>, Option<String>), String> {
+    let list = run("btrfs", &["subvolume", "list", "-q", "-u", mount_point])?;
+    let (qgroups, note) = match run("btrfs", &["qgroup", "show", "--raw", mount_point]) {
+        Ok(output) => (output, None),
+        Err(_) => (
+            String::new(),
+            Some(
+                "未启用 btrfs 配额，无法统计引用与独占空间。可运行 btrfs quota enable 后重试"
+                    .to_string(),
+            ),
+        ),
+    };
+    Ok((parse_btrfs(&list, &qgroups), note))
+}
+
+// subvolume list -q -u 的每行形如
+// "ID 256 gen 10 top level 5 parent_uuid - uuid 0f3e... path @home"，
+// 快照的 parent_uuid 为源子卷的 uuid；qgroup show 的每行形如 "0/256  16384  16384"
+fn parse_btrfs(list: &str, qgroups: &str) -> Vec<CowVolume> {
+    struct Subvolume {
+        id: String,
+        parent_uuid: Option<String>,
+        uuid: String,
+        path: String,
+    }
+    let subvolumes: Vec<Subvolume> = list
+        .lines()
+        .filter_map(|line| {
+            let (fields, path) = line.split_once(" path ")?;
+            let tokens: Vec<&str> = fields.split_whitespace().collect();
+            let value = |key: &str| {
+                let index = tokens.iter().position(|t| *t == key)?;
+                tokens.get(index + 1).map(|v| v.to_string())
+            };
+            Some(Subvolume {
+                id: value("ID")?,
+                parent_uuid: value("parent_uuid").filter(|u| u != "-"),
+                uuid: value("uuid")?,
+                path: path.to_string(),
+            })
+        })
+        .collect();
+
+    let usage: HashMap<&str, (Option<u64>, Option<u64>)> = qgroups
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?.strip_prefix("0/")?;
+            let referenced = fields.next().and_then(|v| v.parse().ok());
+            let exclusive = fields.next().and_then(|v| v.parse().ok());
+            Some((id, (referenced, exclusive)))
+        })
+        .collect();
+
+    subvolumes
+        .iter()
+        .filter(|s| s.parent_uuid.is_none())
+        .map(|s| {
+            let snapshot_count = subvolumes
+                .iter()
+                .filter(|other| other.parent_uuid.as_deref() == Some(s.uuid.as_str()))
+                .count() as u64;
+            let (referenced, unique) = usage.get(s.id.as_str()).copied().unwrap_or_default();
+            volume(s.path.clone(), referenced, unique, None, snapshot_count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zfs_and_btrfs_listings() {
+        let volumes = parse_zfs(
+            "tank/home\t5000\t3000\t2000\ntank/home/alice\t1000\t900\t-\n",
+            "tank/home@daily-1\ntank/home@daily-2\n",
+        );
+        assert_eq!(volumes[0].unique_bytes, Some(3000));
+        assert_eq!(volumes[0].snapshot_count, 2);
+        assert_eq!(volumes[1].snapshot_bytes, None);
+
+        let volumes = parse_btrfs(
+            "ID 256 gen 10 top level 5 parent_uuid - uuid aaa path @home\n\
+             ID 300 gen 12 top level 5 parent_uuid aaa uuid bbb path .snapshots/1/snapshot\n",
+            "qgroupid         rfer         excl\n\
+             --------         ----         ----\n\
+             0/256           16384         8192\n",
+        );
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].name, "@home");
+        assert_eq!(volumes[0].snapshot_count, 1);
+        assert_eq!(volumes[0].unique_bytes, Some(8192));
+    }
+}
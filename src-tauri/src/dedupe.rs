@@ -0,0 +1,599 @@
+use crate::drives::mount_of;
+use crate::hashing::hash_file;
+use crate::utils::{human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupeStatus {
+    /// 已与保留的文件共享数据块
+    Deduplicated,
+    /// 内容不同或文件已变化，未做处理
+    Skipped,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DedupeOutcome {
+    pub path: String,
+    pub status: DedupeStatus,
+    pub reclaimed_bytes: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DedupeReport {
+    /// 保留的源文件
+    pub source: String,
+    pub outcomes: Vec<DedupeOutcome>,
+    pub reclaimed_bytes: u64,
+    pub reclaimed_display: String,
+    /// 需要提醒用户的副作用
+    pub warning: Option<String>,
+}
+
+// 用 reflink（写时复制）把 paths[1..] 的数据替换为与 paths[0] 共享，
+// 所有副本都保留且互不影响，只释放重复占用的空间。
+// 支持 btrfs、XFS（FIDEDUPERANGE）、APFS（clonefile）和 ReFS（块克隆）
+pub fn deduplicate(paths: &[String]) -> Result<DedupeReport, String> {
+    let (source, targets) = paths
+        .split_first()
+        .ok_or_else(|| "至少需要两个文件".to_string())?;
+    if targets.is_empty() {
+        return Err("至少需要两个文件".to_string());
+    }
+    let source_path = Path::new(source);
+    if !source_path.is_file() {
+        return Err("源文件不存在".to_string());
+    }
+    let fs_type = mount_of(source_path)
+        .map(|m| m.file_system.to_lowercase())
+        .unwrap_or_default();
+    if !supports_reflink(&fs_type) {
+        return Err(format!("文件系统 {} 不支持 reflink 去重", fs_type));
+    }
+
+    let outcomes: Vec<DedupeOutcome> = targets
+        .iter()
+        .map(|target| {
+            let result = if !same_volume(source_path, Path::new(target)) {
+                Err("不在同一卷上，无法共享数据块".to_string())
+            } else {
+                share_extents(source_path, Path::new(target))
+            };
+            match result {
+                Ok(Some(bytes)) => outcome(target, DedupeStatus::Deduplicated, bytes, None),
+                Ok(None) => outcome(target, DedupeStatus::Skipped, 0, Some("内容与源文件不同")),
+                Err(e) => outcome(target, DedupeStatus::Failed, 0, Some(&e)),
+            }
+        })
+        .collect();
+    let reclaimed_bytes = sum_sizes(outcomes.iter().map(|o| o.reclaimed_bytes));
+    Ok(DedupeReport {
+        source: source.clone(),
+        outcomes,
+        reclaimed_bytes,
+        reclaimed_display: human_readable_size(reclaimed_bytes),
+        warning: None,
+    })
+}
+
+const HARD_LINK_WARNING: &str =
+    "这些路径现在指向同一个文件：修改其中任意一个，其他路径看到的内容也会一起改变，权限和属主也相同";
+
+// 不支持 reflink 时的替代方案：把 paths[1..] 替换为 paths[0] 的硬链接。
+// 先在目标旁创建临时硬链接再改名覆盖，任何时刻目标路径都存在且内容完整
+pub fn hardlink_duplicates(paths: &[String]) -> Result<DedupeReport, String> {
+    let (source, targets) = paths
+        .split_first()
+        .ok_or_else(|| "至少需要两个文件".to_string())?;
+    if targets.is_empty() {
+        return Err("至少需要两个文件".to_string());
+    }
+    let source_path = Path::new(source);
+    if !source_path.is_file() {
+        return Err("源文件不存在".to_string());
+    }
+
+    let outcomes: Vec<DedupeOutcome> = targets
+        .iter()
+        .map(|target| match link_over(source_path, Path::new(target)) {
+            Ok(Some(bytes)) => outcome(target, DedupeStatus::Deduplicated, bytes, None),
+            Ok(None) => outcome(target, DedupeStatus::Skipped, 0, Some("内容与源文件不同")),
+            Err(e) => outcome(target, DedupeStatus::Failed, 0, Some(&e)),
+        })
+        .collect();
+    let reclaimed_bytes = sum_sizes(outcomes.iter().map(|o| o.reclaimed_bytes));
+    Ok(DedupeReport {
+        source: source.clone(),
+        outcomes,
+        reclaimed_bytes,
+        reclaimed_display: human_readable_size(reclaimed_bytes),
+        warning: (reclaimed_bytes > 0).then(|| HARD_LINK_WARNING.to_string()),
+    })
+}
+
+fn link_over(source: &Path, target: &Path) -> Result<Option<u64>, String> {
+    if !same_volume(source, target) {
+        return Err("硬链接不能跨卷".to_string());
+    }
+    if is_same_file(source, target) {
+        return Err("已经是同一个文件".to_string());
+    }
+    if !contents_match(source, target)? {
+        return Ok(None);
+    }
+    let len = fs::metadata(target).map_err(|e| e.to_string())?.len();
+    replace_with(target, false, |tmp| {
+        fs::hard_link(source, tmp).map_err(|e| format!("创建硬链接失败: {}", e))
+    })?;
+    Ok(Some(len))
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn outcome(path: &str, status: DedupeStatus, bytes: u64, error: Option<&str>) -> DedupeOutcome {
+    DedupeOutcome {
+        path: path.to_string(),
+        status,
+        reclaimed_bytes: bytes,
+        error: error.map(String::from),
+    }
+}
+
+fn supports_reflink(fs_type: &str) -> bool {
+    match fs_type {
+        "btrfs" | "xfs" => cfg!(target_os = "linux"),
+        "apfs" => cfg!(target_os = "macos"),
+        "refs" => cfg!(windows),
+        _ => false,
+    }
+}
+
+fn same_volume(a: &Path, b: &Path) -> bool {
+    match (mount_of(a), mount_of(b)) {
+        (Some(a), Some(b)) => a.mount_point == b.mount_point,
+        _ => false,
+    }
+}
+
+// 内容完全相同时先整体比较哈希，避免替换掉已被修改的文件
+fn contents_match(source: &Path, target: &Path) -> Result<bool, String> {
+    let source_len = fs::metadata(source).map_err(|e| e.to_string())?.len();
+    let target_len = fs::metadata(target).map_err(|e| e.to_string())?.len();
+    if source_len != target_len {
+        return Ok(false);
+    }
+    let mut buffer = vec![0u8; 1024 * 1024];
+    Ok(hash_file(source, None, &mut buffer)? == hash_file(target, None, &mut buffer)?)
+}
+
+// Linux：FIDEDUPERANGE 由内核逐字节比较后再共享数据块，比较与替换是原子的
+#[cfg(target_os = "linux")]
+fn share_extents(source: &Path, target: &Path) -> Result<Option<u64>, String> {
+    use std::os::unix::io::AsRawFd;
+
+    const FIDEDUPERANGE: u64 = 0xc018_9436;
+    const FILE_DEDUPE_RANGE_DIFFERS: i32 = 1;
+    // 部分文件系统单次调用处理的长度有限，分段提交
+    const STEP: u64 = 16 * 1024 * 1024;
+
+    #[repr(C)]
+    struct DedupeRangeInfo {
+        dest_fd: i64,
+        dest_offset: u64,
+        bytes_deduped: u64,
+        status: i32,
+        reserved: u32,
+    }
+    #[repr(C)]
+    struct DedupeRange {
+        src_offset: u64,
+        src_length: u64,
+        dest_count: u16,
+        reserved1: u16,
+        reserved2: u32,
+        info: [DedupeRangeInfo; 1],
+    }
+
+    let src = fs::File::open(source).map_err(|e| format!("无法打开源文件: {}", e))?;
+    let dst = fs::OpenOptions::new()
+        .write(true)
+        .open(target)
+        .map_err(|e| format!("无法打开文件: {}", e))?;
+    let len = src.metadata().map_err(|e| e.to_string())?.len();
+    if dst.metadata().map_err(|e| e.to_string())?.len() != len {
+        return Ok(None);
+    }
+
+    let mut offset = 0u64;
+    let mut deduped = 0u64;
+    while offset < len {
+        let mut range = DedupeRange {
+            src_offset: offset,
+            src_length: STEP.min(len - offset),
+            dest_count: 1,
+            reserved1: 0,
+            reserved2: 0,
+            info: [DedupeRangeInfo {
+                dest_fd: dst.as_raw_fd() as i64,
+                dest_offset: offset,
+                bytes_deduped: 0,
+                status: 0,
+                reserved: 0,
+            }],
+        };
+        let rc = unsafe { libc::ioctl(src.as_raw_fd(), FIDEDUPERANGE as _, &mut range) };
+        if rc != 0 {
+            return Err(format!(
+                "FIDEDUPERANGE 失败: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let info = &range.info[0];
+        if info.status == FILE_DEDUPE_RANGE_DIFFERS {
+            return Ok(None);
+        }
+        if info.status < 0 {
+            return Err(format!(
+                "去重失败: {}",
+                std::io::Error::from_raw_os_error(-info.status)
+            ));
+        }
+        if info.bytes_deduped == 0 {
+            break;
+        }
+        deduped += info.bytes_deduped;
+        offset += info.bytes_deduped;
+    }
+    Ok(Some(deduped))
+}
+
+// macOS：clonefile 生成与源共享数据的新文件，再替换目标。克隆得到的是源文件的扩展属性和 ACL，
+// 与目标原有的不同时放弃替换；属主、权限和时间戳替换时恢复为目标原有的值
+#[cfg(target_os = "macos")]
+fn share_extents(source: &Path, target: &Path) -> Result<Option<u64>, String> {
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+    use std::os::unix::ffi::OsStrExt;
+
+    extern "C" {
+        fn clonefile(src: *const c_char, dst: *const c_char, flags: u32) -> i32;
+    }
+
+    if !contents_match(source, target)? {
+        return Ok(None);
+    }
+    if security_attributes(source)? != security_attributes(target)? {
+        return Err("扩展属性或 ACL 与源文件不同，克隆后会丢失，未做替换".to_string());
+    }
+    let len = fs::metadata(target).map_err(|e| e.to_string())?.len();
+    replace_with(target, true, |tmp| {
+        let src = CString::new(source.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+        let dst = CString::new(tmp.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+        if unsafe { clonefile(src.as_ptr(), dst.as_ptr(), 0) } != 0 {
+            return Err(format!(
+                "clonefile 失败: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    })?;
+    Ok(Some(len))
+}
+
+// 文件的扩展属性（按名称排序的名称与值）和 ACL 文本
+#[cfg(target_os = "macos")]
+type SecurityAttributes = (Vec<(Vec<u8>, Vec<u8>)>, Option<String>);
+
+#[cfg(target_os = "macos")]
+fn security_attributes(path: &Path) -> Result<SecurityAttributes, String> {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_int, c_void};
+    use std::os::unix::ffi::OsStrExt;
+
+    const XATTR_NOFOLLOW: c_int = 0x0001;
+    const ACL_TYPE_EXTENDED: c_int = 0x0000_0100;
+
+    extern "C" {
+        fn listxattr(path: *const c_char, names: *mut c_char, size: usize, options: c_int)
+            -> isize;
+        fn getxattr(
+            path: *const c_char,
+            name: *const c_char,
+            value: *mut c_void,
+            size: usize,
+            position: u32,
+            options: c_int,
+        ) -> isize;
+        fn acl_get_file(path: *const c_char, kind: c_int) -> *mut c_void;
+        fn acl_to_text(acl: *mut c_void, len: *mut isize) -> *mut c_char;
+        fn acl_free(obj: *mut c_void) -> c_int;
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    let error = || {
+        format!(
+            "无法读取 {} 的扩展属性: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        )
+    };
+    let mut xattrs = Vec::new();
+    unsafe {
+        let size = listxattr(c_path.as_ptr(), std::ptr::null_mut(), 0, XATTR_NOFOLLOW);
+        if size < 0 {
+            return Err(error());
+        }
+        let mut names = vec![0u8; size as usize];
+        let size = listxattr(
+            c_path.as_ptr(),
+            names.as_mut_ptr() as *mut c_char,
+            names.len(),
+            XATTR_NOFOLLOW,
+        );
+        if size < 0 {
+            return Err(error());
+        }
+        for name in names[..size as usize]
+            .split(|b| *b == 0)
+            .filter(|n| !n.is_empty())
+        {
+            let c_name = CString::new(name).map_err(|e| e.to_string())?;
+            let get = |buffer: &mut [u8]| {
+                getxattr(
+                    c_path.as_ptr(),
+                    c_name.as_ptr(),
+                    buffer.as_mut_ptr() as *mut c_void,
+                    buffer.len(),
+                    0,
+                    XATTR_NOFOLLOW,
+                )
+            };
+            let len = get(&mut []);
+            if len < 0 {
+                return Err(error());
+            }
+            let mut value = vec![0u8; len as usize];
+            let len = get(&mut value);
+            if len < 0 {
+                return Err(error());
+            }
+            value.truncate(len as usize);
+            xattrs.push((name.to_vec(), value));
+        }
+    }
+    xattrs.sort();
+    // 没有 ACL 时 acl_get_file 返回空指针
+    let acl = unsafe {
+        let acl = acl_get_file(c_path.as_ptr(), ACL_TYPE_EXTENDED);
+        if acl.is_null() {
+            None
+        } else {
+            let text = acl_to_text(acl, std::ptr::null_mut());
+            let result =
+                (!text.is_null()).then(|| CStr::from_ptr(text).to_string_lossy().into_owned());
+            if !text.is_null() {
+                acl_free(text as *mut c_void);
+            }
+            acl_free(acl);
+            result
+        }
+    };
+    Ok((xattrs, acl))
+}
+
+// Windows ReFS：FSCTL_DUPLICATE_EXTENTS_TO_FILE 块克隆，范围需按簇对齐。
+// 直接克隆到目标文件本身，属主、ACL 和备用数据流原样保留，只需恢复被更新的时间戳
+#[cfg(windows)]
+fn share_extents(source: &Path, target: &Path) -> Result<Option<u64>, String> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::ctypes::c_void;
+    use winapi::um::ioapiset::DeviceIoControl;
+
+    const FSCTL_DUPLICATE_EXTENTS_TO_FILE: u32 = 0x0009_8344;
+    // ReFS 的簇大小为 4 KiB 或 64 KiB，按 64 KiB 向上取整对两者都对齐
+    const CLUSTER: u64 = 64 * 1024;
+
+    #[repr(C)]
+    struct DuplicateExtentsData {
+        file_handle: *mut c_void,
+        source_offset: i64,
+        target_offset: i64,
+        byte_count: i64,
+    }
+
+    if !contents_match(source, target)? {
+        return Ok(None);
+    }
+    let original = fs::metadata(target).map_err(|e| e.to_string())?;
+    let len = original.len();
+    let src = fs::File::open(source).map_err(|e| format!("无法打开源文件: {}", e))?;
+    let dst = fs::OpenOptions::new()
+        .write(true)
+        .open(target)
+        .map_err(|e| format!("无法打开文件: {}", e))?;
+    if dst.metadata().map_err(|e| e.to_string())?.len() != len {
+        return Ok(None);
+    }
+    let data = DuplicateExtentsData {
+        file_handle: src.as_raw_handle() as *mut c_void,
+        source_offset: 0,
+        target_offset: 0,
+        byte_count: len.div_ceil(CLUSTER).saturating_mul(CLUSTER) as i64,
+    };
+    let mut returned = 0u32;
+    let ok = unsafe {
+        DeviceIoControl(
+            dst.as_raw_handle() as *mut c_void,
+            FSCTL_DUPLICATE_EXTENTS_TO_FILE,
+            &data as *const _ as *mut c_void,
+            std::mem::size_of::<DuplicateExtentsData>() as u32,
+            std::ptr::null_mut(),
+            0,
+            &mut returned,
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(format!("块克隆失败: {}", std::io::Error::last_os_error()));
+    }
+    dst.set_times(original_times(&original))
+        .map_err(|e| format!("无法恢复时间戳: {}", e))?;
+    Ok(Some(len))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn share_extents(_source: &Path, _target: &Path) -> Result<Option<u64>, String> {
+    Err("当前系统不支持 reflink 去重".to_string())
+}
+
+// 在目标旁边生成临时文件，成功后改名覆盖目标；失败时删除临时文件。
+// keep_metadata 时把目标原有的属主、时间戳和权限恢复到新文件上，属主无法恢复时放弃替换；
+// 硬链接与源文件共用这些属性，不能恢复
+fn replace_with(
+    target: &Path,
+    keep_metadata: bool,
+    create: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    let original = fs::metadata(target).map_err(|e| e.to_string())?;
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = target.with_file_name(format!(".{}.dedupe-tmp", name));
+    let _ = fs::remove_file(&tmp);
+    if let Err(e) = create(&tmp) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    if keep_metadata {
+        if let Err(e) = restore_metadata(&tmp, &original) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+    }
+    fs::rename(&tmp, target).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("替换文件失败: {}", e)
+    })
+}
+
+// 依次恢复属主、时间戳和权限；权限放在最后，只读文件也能先写入时间戳
+fn restore_metadata(path: &Path, original: &fs::Metadata) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let current = fs::metadata(path).map_err(|e| e.to_string())?;
+        if (current.uid(), current.gid()) != (original.uid(), original.gid()) {
+            std::os::unix::fs::chown(path, Some(original.uid()), Some(original.gid()))
+                .map_err(|e| format!("无法恢复文件属主，未做替换: {}", e))?;
+        }
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_times(original_times(original)))
+        .map_err(|e| format!("无法恢复时间戳: {}", e))?;
+    fs::set_permissions(path, original.permissions()).map_err(|e| e.to_string())
+}
+
+// 原有的访问、修改时间，支持的平台上还有创建时间
+fn original_times(original: &fs::Metadata) -> fs::FileTimes {
+    let mut times = fs::FileTimes::new();
+    if let Ok(accessed) = original.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = original.modified() {
+        times = times.set_modified(modified);
+    }
+    #[cfg(target_os = "macos")]
+    if let Ok(created) = original.created() {
+        use std::os::macos::fs::FileTimesExt;
+        times = times.set_created(created);
+    }
+    #[cfg(windows)]
+    if let Ok(created) = original.created() {
+        use std::os::windows::fs::FileTimesExt;
+        times = times.set_created(created);
+    }
+    times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_links_identical_files_only() {
+        let dir = std::env::temp_dir().join(format!("disksight-link-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        fs::write(dir.join("a"), b"payload").unwrap();
+        fs::write(dir.join("b"), b"payload").unwrap();
+        fs::write(dir.join("c"), b"changed").unwrap();
+
+        let report = hardlink_duplicates(&[path("a"), path("b"), path("c")]).unwrap();
+        let linked = is_same_file(&dir.join("a"), &dir.join("b"));
+        let content = fs::read(dir.join("b")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.outcomes[0].status, DedupeStatus::Deduplicated);
+        assert_eq!(report.outcomes[1].status, DedupeStatus::Skipped);
+        assert!(linked);
+        assert_eq!(content, b"payload");
+        assert!(report.warning.is_some());
+    }
+
+    #[test]
+    fn replacement_keeps_timestamps_and_permissions() {
+        let dir = std::env::temp_dir().join(format!("disksight-replace-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target");
+        fs::write(&target, b"old").unwrap();
+        let modified =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&target)
+            .unwrap()
+            .set_times(fs::FileTimes::new().set_modified(modified))
+            .unwrap();
+        let mut permissions = fs::metadata(&target).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&target, permissions).unwrap();
+
+        let result = replace_with(&target, true, |tmp| {
+            fs::write(tmp, b"new").map_err(|e| e.to_string())
+        });
+        let metadata = fs::metadata(&target).unwrap();
+        let content = fs::read(&target).unwrap();
+        let mut permissions = metadata.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&target, permissions).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(content, b"new");
+        assert_eq!(metadata.modified().unwrap(), modified);
+        assert!(metadata.permissions().readonly());
+    }
+}
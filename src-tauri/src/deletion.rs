@@ -0,0 +1,343 @@
+use crate::attributes;
+use crate::drives::mount_of;
+use crate::utils::{home_dir, human_readable_size};
+use crate::vfs::{Fs, FsMetadata, RealFs};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// 无论 force 与否都拒绝删除的路径
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefusedDeletion {
+    /// 磁盘分区或挂载点的根目录
+    VolumeRoot,
+    /// 用户主目录或其上级目录
+    HomeDirectory,
+    /// 本程序所在的安装目录或其上级目录
+    AppDirectory,
+}
+
+impl fmt::Display for RefusedDeletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RefusedDeletion::VolumeRoot => "不能删除磁盘或挂载点的根目录",
+            RefusedDeletion::HomeDirectory => "不能删除用户主目录或其上级目录",
+            RefusedDeletion::AppDirectory => "不能删除本程序的安装目录或其上级目录",
+        })
+    }
+}
+
+// 删除前调用，路径为卷根目录、主目录或安装目录时返回拒绝原因
+pub fn refuse_deletion(path: &Path) -> Option<RefusedDeletion> {
+    let install_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let mount_point = mount_of(path).map(|m| PathBuf::from(m.mount_point));
+    classify(
+        path,
+        mount_point.as_deref(),
+        home_dir().as_deref(),
+        install_dir.as_deref(),
+    )
+}
+
+fn classify(
+    path: &Path,
+    mount_point: Option<&Path>,
+    home: Option<&Path>,
+    install_dir: Option<&Path>,
+) -> Option<RefusedDeletion> {
+    // 规范化后比较，避免通过 .. 或符号链接绕过检查
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let path = canonical(path);
+    if path.parent().is_none() || mount_point.is_some_and(|m| canonical(m) == path) {
+        return Some(RefusedDeletion::VolumeRoot);
+    }
+    // 删除上级目录同样会删掉主目录或安装目录
+    if home.is_some_and(|h| canonical(h).starts_with(&path)) {
+        return Some(RefusedDeletion::HomeDirectory);
+    }
+    if install_dir.is_some_and(|d| canonical(d).starts_with(&path)) {
+        return Some(RefusedDeletion::AppDirectory);
+    }
+    None
+}
+
+// 永久删除文件或目录。只读的条目需要 force；卷根目录、主目录、安装目录即使 force 也不允许删除
+pub fn delete_path(fs: &dyn Fs, path: &Path, force: bool) -> Result<(), String> {
+    let metadata = match fs.metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err("路径不存在".to_string()),
+        Err(e) => return Err(format!("无法访问路径: {}", e)),
+    };
+    if let Some(reason) = refuse_deletion(path) {
+        return Err(reason.to_string());
+    }
+
+    if metadata.readonly {
+        if !force {
+            return Err("路径是只读的。如要强制删除，请设置 force 参数为 true".to_string());
+        }
+        fs.clear_readonly(path)?;
+    }
+    // 强制删除目录时先清除整棵树的只读限制，否则目录内的只读文件仍会导致删除失败
+    let mut clear_failures = Vec::new();
+    if force && metadata.is_dir {
+        clear_failures = fs.clear_readonly_recursive(path);
+    }
+
+    // 符号链接只删除链接本身
+    let result = if metadata.is_dir {
+        fs.remove_dir_all(path)
+    } else {
+        fs.remove_file(path)
+    };
+    let message = match result {
+        Ok(_) => return Ok(()),
+        Err(e) => match e.raw_os_error() {
+            Some(5) => "权限不足，请以管理员身份运行程序或检查路径权限".to_string(),
+            Some(32) => "文件或目录正在被其他程序使用".to_string(),
+            Some(2) => "文件或目录不存在".to_string(),
+            Some(145) => "目录不为空".to_string(),
+            _ => format!("删除失败: {}", e),
+        },
+    };
+    // 附上无法去除只读限制的条目，便于定位是哪些文件的 ACL 阻止了删除
+    if clear_failures.is_empty() {
+        Err(message)
+    } else {
+        Err(format!(
+            "{}。{}",
+            message,
+            attributes::describe_failures(&clear_failures)
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeletionImpact {
+    /// 选中内容的文件大小总和
+    pub total_bytes: u64,
+    pub total_display: String,
+    /// 删除后预计真正释放的空间（扣除仍被其他硬链接引用的数据）
+    pub reclaimable_bytes: u64,
+    pub reclaimable_display: String,
+    pub file_count: u64,
+    pub dir_count: u64,
+    /// 选中内容里最近一次修改的时间
+    pub newest_modified: Option<SystemTime>,
+    /// 在选中范围之外仍有硬链接的文件数
+    pub shared_hard_links: u64,
+    /// 需要在确认对话框中提示的注意事项
+    pub caveats: Vec<String>,
+    /// 已不存在的路径
+    pub missing: Vec<String>,
+}
+
+// 同一 inode 的大小、链接总数、在选中范围内出现的次数
+type InodeLinks = HashMap<(u64, u64), (u64, u64, u64)>;
+
+// 估算删除一组路径的影响，用于填充删除确认对话框
+pub fn deletion_impact(paths: &[String]) -> DeletionImpact {
+    deletion_impact_in(&RealFs, paths)
+}
+
+pub fn deletion_impact_in(fs: &dyn Fs, paths: &[String]) -> DeletionImpact {
+    let mut impact = DeletionImpact::default();
+    let mut inodes = InodeLinks::new();
+    let mut file_systems: Vec<String> = Vec::new();
+
+    for path in paths {
+        let path = Path::new(path);
+        let metadata = match fs.metadata(path) {
+            Ok(m) => m,
+            Err(_) => {
+                impact.missing.push(path.to_string_lossy().into_owned());
+                continue;
+            }
+        };
+        if let Some(mount) = mount_of(path) {
+            if !file_systems.contains(&mount.file_system) {
+                file_systems.push(mount.file_system);
+            }
+        }
+        visit(fs, path, &metadata, &mut impact, &mut inodes);
+    }
+
+    // 只有当某个 inode 的所有链接都在选中范围内时，删除才会真正释放空间
+    let mut shared_bytes = 0u64;
+    for (size, nlink, seen) in inodes.values() {
+        if seen < nlink {
+            // 范围外还有链接，删除后不释放空间；该 inode 每次出现都计入了 total_bytes
+            impact.shared_hard_links += 1;
+            shared_bytes = shared_bytes.saturating_add(size.saturating_mul(*seen));
+        } else {
+            // 同一 inode 被多次统计进 total_bytes，只应释放一次
+            shared_bytes = shared_bytes.saturating_add(size.saturating_mul(seen - 1));
+        }
+    }
+    impact.reclaimable_bytes = impact.total_bytes.saturating_sub(shared_bytes);
+    impact.total_display = human_readable_size(impact.total_bytes);
+    impact.reclaimable_display = human_readable_size(impact.reclaimable_bytes);
+
+    if impact.shared_hard_links > 0 {
+        impact.caveats.push(format!(
+            "{} 个文件在其他位置还有硬链接，删除后只能释放 {}",
+            impact.shared_hard_links, impact.reclaimable_display
+        ));
+    }
+    for fs_type in &file_systems {
+        if let Some(note) = snapshot_caveat(fs_type) {
+            impact.caveats.push(note.to_string());
+        }
+    }
+    impact
+}
+
+fn visit(
+    fs: &dyn Fs,
+    path: &Path,
+    metadata: &FsMetadata,
+    impact: &mut DeletionImpact,
+    inodes: &mut InodeLinks,
+) {
+    if let Some(modified) = metadata.modified {
+        if impact
+            .newest_modified
+            .is_none_or(|newest| modified > newest)
+        {
+            impact.newest_modified = Some(modified);
+        }
+    }
+
+    if metadata.is_dir {
+        impact.dir_count += 1;
+        let Ok(entries) = fs.read_dir(path) else {
+            impact
+                .caveats
+                .push(format!("无法读取目录 {}，统计可能偏小", path.display()));
+            return;
+        };
+        for entry in entries {
+            let child = path.join(&entry.name);
+            if let Ok(child_meta) = fs.metadata(&child) {
+                visit(fs, &child, &child_meta, impact, inodes);
+            }
+        }
+        return;
+    }
+
+    impact.file_count += 1;
+    impact.total_bytes = impact.total_bytes.saturating_add(metadata.len);
+    // 不支持硬链接信息的平台上 nlink 恒为 1
+    if metadata.nlink > 1 {
+        let slot =
+            inodes
+                .entry((metadata.dev, metadata.ino))
+                .or_insert((metadata.len, metadata.nlink, 0));
+        slot.2 += 1;
+    }
+}
+
+// 支持快照的文件系统上，被快照引用的数据删除后不会立即释放
+fn snapshot_caveat(fs_type: &str) -> Option<&'static str> {
+    match fs_type.to_lowercase().as_str() {
+        "btrfs" | "zfs" => Some("该文件系统支持快照，若数据仍被快照引用，删除后空间不会立即释放"),
+        "apfs" => Some("APFS 本地 Time Machine 快照可能仍引用这些数据，空间可能稍后才会释放"),
+        "ntfs" if cfg!(windows) => {
+            Some("若启用了卷影副本（系统还原），部分空间可能仍被卷影副本占用")
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFs;
+    use std::fs;
+
+    #[test]
+    fn read_only_and_failing_entries_are_reported() {
+        let fs = MemoryFs::new();
+        fs.add_file("/mem/project/locked.txt", 10);
+        fs.add_file("/mem/project/build/out.o", 30);
+        fs.set_readonly("/mem/project/locked.txt", true);
+        fs.set_readonly("/mem/project/build/out.o", true);
+
+        let locked = Path::new("/mem/project/locked.txt");
+        assert!(delete_path(&fs, locked, false)
+            .unwrap_err()
+            .contains("只读"));
+        assert!(fs.exists(locked));
+        delete_path(&fs, locked, true).unwrap();
+        assert!(!fs.exists(locked));
+        assert_eq!(delete_path(&fs, locked, false).unwrap_err(), "路径不存在");
+
+        // 目录内的只读文件在 force 时一并清除限制
+        let build = Path::new("/mem/project/build");
+        assert!(delete_path(&fs, build, false).is_err());
+        fs.fail("/mem/project/build/out.o", io::ErrorKind::ResourceBusy);
+        assert!(delete_path(&fs, build, true)
+            .unwrap_err()
+            .contains("无法去除只读限制"));
+        assert!(fs.exists("/mem/project/build/out.o"));
+
+        let impact = deletion_impact_in(&fs, &["/mem/project".to_string()]);
+        assert_eq!((impact.dir_count, impact.file_count), (2, 0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_outside_the_selection_free_nothing() {
+        let base = std::env::temp_dir().join(format!("disksight-links-{}", std::process::id()));
+        let target = base.join("target");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("a.bin"), vec![0u8; 1000]).unwrap();
+        fs::hard_link(target.join("a.bin"), target.join("b.bin")).unwrap();
+        fs::hard_link(target.join("a.bin"), base.join("outside.bin")).unwrap();
+        fs::write(target.join("own.bin"), vec![0u8; 300]).unwrap();
+
+        let impact = deletion_impact(&[target.to_string_lossy().into_owned()]);
+        fs::remove_dir_all(&base).unwrap();
+        assert_eq!(impact.total_bytes, 2300);
+        assert_eq!(impact.shared_hard_links, 1);
+        assert_eq!(impact.reclaimable_bytes, 300);
+    }
+
+    #[test]
+    fn refuses_roots_home_and_install_dir() {
+        let base = std::env::temp_dir().join(format!("disksight-refuse-{}", std::process::id()));
+        let home = base.join("home").join("alice");
+        let install = base.join("opt").join("disksight");
+        let project = home.join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::create_dir_all(&install).unwrap();
+        let check = |path: &Path| classify(path, Some(&base), Some(&home), Some(&install));
+
+        assert_eq!(check(Path::new("/")), Some(RefusedDeletion::VolumeRoot));
+        assert_eq!(check(&base), Some(RefusedDeletion::VolumeRoot));
+        assert_eq!(check(&home), Some(RefusedDeletion::HomeDirectory));
+        assert_eq!(
+            check(&base.join("home")),
+            Some(RefusedDeletion::HomeDirectory)
+        );
+        // 通过 .. 指向主目录也会被识别
+        assert_eq!(
+            check(&project.join("..")),
+            Some(RefusedDeletion::HomeDirectory)
+        );
+        assert_eq!(check(&install), Some(RefusedDeletion::AppDirectory));
+        assert_eq!(
+            check(&base.join("opt")),
+            Some(RefusedDeletion::AppDirectory)
+        );
+        assert_eq!(check(&project), None);
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
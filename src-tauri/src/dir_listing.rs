@@ -1,10 +1,17 @@
+use super::collation::sort_names;
+use super::dir_reader::DirChild;
 use super::models::{Cli, FileEntry};
-use super::utils::{human_readable_size, progress_bar_init};
+use super::parallelism::sort_for_sequential_reads;
+use super::utils::{
+    canonical_string, child_location, human_readable_size, progress_bar_init, sum_sizes,
+};
+use super::vfs::{Fs, RealFs};
 use indicatif::ProgressBar;
 use rayon::prelude::*;
 use std::fs;
 use std::io::Error;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub fn calculate_dir_size(
     path: &Path,
@@ -12,65 +19,8 @@ pub fn calculate_dir_size(
     main_pb: &ProgressBar,
     parallel: bool,
 ) -> (u64, String) {
-    fn inner_calculate(p: &Path, pb: &ProgressBar, parallel: bool) -> u64 {
-        match fs::read_dir(p) {
-            Ok(entries) => {
-                let mut total_size = 0;
-                let entries: Vec<_> = entries
-                    .filter_map(|e| {
-                        pb.tick();
-                        match e {
-                            Ok(entry) => Some(entry),
-                            Err(e) => {
-                                eprintln!("无法读取目录项 {}: {}", p.display(), e);
-                                None
-                            }
-                        }
-                    })
-                    .collect();
-
-                if parallel {
-                    // 使用并行处理
-                    total_size += entries
-                        .par_iter()
-                        .map(|e| process_entry(e, pb, parallel))
-                        .sum::<u64>();
-                } else {
-                    // 使用串行处理
-                    total_size += entries
-                        .iter()
-                        .map(|e| process_entry(e, pb, parallel))
-                        .sum::<u64>();
-                }
-
-                total_size
-            }
-            Err(e) => {
-                eprintln!("无法读取目录 {}: {}", p.display(), e);
-                0 // 返回0表示这个目录本身无法访问，但不影响父目录计算其他项
-            }
-        }
-    }
-
-    // 修改process_entry函数以处理DirEntry引用
-    fn process_entry(e: &std::fs::DirEntry, pb: &ProgressBar, parallel: bool) -> u64 {
-        match e.metadata() {
-            Ok(metadata) => {
-                if metadata.is_dir() {
-                    inner_calculate(&e.path(), pb, parallel)
-                } else {
-                    metadata.len()
-                }
-            }
-            Err(e) => {
-                eprintln!("无法获取文件元数据 {}", e);
-                0 // 返回0表示这个文件无法访问，但不影响目录计算其他项
-            }
-        }
-    }
-
     main_pb.set_message(format!("计算 {}...", path.display()));
-    let total = inner_calculate(path, main_pb, parallel);
+    let total = dir_size_in(&RealFs, path, main_pb, parallel);
     println!("Total size: {}", total);
     main_pb.set_message("处理中...");
 
@@ -81,6 +31,43 @@ pub fn calculate_dir_size(
     };
     (total, converted)
 }
+// 递归累加目录下所有条目的大小，无法读取的子目录按 0 计算
+pub fn dir_size_in(fs: &dyn Fs, path: &Path, pb: &ProgressBar, parallel: bool) -> u64 {
+    // 批量读取目录项及其大小，元数据读取失败的条目已在内部跳过
+    match fs.read_dir(path) {
+        Ok(mut entries) => {
+            if parallel {
+                // 使用并行处理，超出 u64 时饱和
+                entries
+                    .par_iter()
+                    .map(|e| child_size(fs, path, e, pb, parallel))
+                    .reduce(|| 0, u64::saturating_add)
+            } else {
+                // 使用串行处理，按 inode 顺序读取以减少机械硬盘寻道
+                sort_for_sequential_reads(&mut entries);
+                sum_sizes(
+                    entries
+                        .iter()
+                        .map(|e| child_size(fs, path, e, pb, parallel)),
+                )
+            }
+        }
+        Err(e) => {
+            eprintln!("无法读取目录 {}: {}", path.display(), e);
+            0 // 返回0表示这个目录本身无法访问，但不影响父目录计算其他项
+        }
+    }
+}
+
+fn child_size(fs: &dyn Fs, parent: &Path, e: &DirChild, pb: &ProgressBar, parallel: bool) -> u64 {
+    pb.tick();
+    if e.is_dir {
+        dir_size_in(fs, &parent.join(&e.name), pb, parallel)
+    } else {
+        e.size
+    }
+}
+
 pub fn list_directory(path: &Path, args: &Cli) -> Result<Vec<FileEntry>, Error> {
     let entries = match fs::read_dir(path) {
         Ok(entries) => entries,
@@ -96,8 +83,10 @@ pub fn list_directory(path: &Path, args: &Cli) -> Result<Vec<FileEntry>, Error>
         files.push(file_name);
     }
 
-    files.sort();
+    sort_names(&mut files, args.collation);
     let _scan_pb = progress_bar_init(None).unwrap();
+    // 根目录只规范化一次，普通子项共享该字符串作为父目录
+    let canonical_root: Option<Arc<str>> = canonical_string(path).map(Arc::from);
 
     let mut entries = Vec::new(); // 新增存储条目信息的结构
 
@@ -165,19 +154,11 @@ pub fn list_directory(path: &Path, args: &Cli) -> Result<Vec<FileEntry>, Error>
                 ),
                 size_display,
                 size_raw,
-                path: match file_path.canonicalize() {
-                    Ok(canonical_path) => {
-                        let path_str = canonical_path.to_string_lossy().into_owned();
-                        let path_str = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
-                        path_str.to_string()
-                    }
-                    Err(_e) => {
-                        // eprintln!("获取绝对路径失败: {}", e);
-                        file_path.to_string_lossy().into_owned()
-                    }
-                },
+                location: child_location(canonical_root.as_ref(), &file_path),
                 name: file.to_string(),            // 新增字段
                 created_time: metadata.created()?, // 创建时间
+                modified_time: metadata.modified().ok(),
+                severity: None,
             });
         }
 
@@ -200,6 +181,42 @@ pub fn list_directory(path: &Path, args: &Cli) -> Result<Vec<FileEntry>, Error>
     // scan_pb.finish_and_clear(); // 完成后清理进度条
 }
 
+// 重新计算单个文件或目录，生成与 list_directory 相同格式的条目
+pub fn stat_entry(path: &Path, parallel: bool) -> Result<FileEntry, Error> {
+    let metadata = fs::metadata(path)?;
+    let (size_raw, size_display) = if metadata.is_dir() {
+        let pb = progress_bar_init(None).unwrap();
+        let size = calculate_dir_size(path, true, &pb, parallel);
+        pb.finish_and_clear();
+        size
+    } else {
+        (metadata.len(), human_readable_size(metadata.len()))
+    };
+    Ok(FileEntry {
+        file_type: if metadata.is_dir() { 'd' } else { '-' },
+        permissions: format!(
+            "{}-{}-{}",
+            if metadata.permissions().readonly() {
+                "r"
+            } else {
+                " "
+            },
+            "w",
+            "x"
+        ),
+        size_display,
+        size_raw,
+        location: path.to_string_lossy().into_owned().into(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        created_time: metadata.created()?,
+        modified_time: metadata.modified().ok(),
+        severity: None,
+    })
+}
+
 // 需要重写一个函数，是实现传入一个目录，传入一个名称，返回这个目录下面的对应名称文件大小
 fn calculate_dir_size1(
     file_path: PathBuf,
@@ -250,7 +267,7 @@ fn calculate_dir_size1(
                     ),
                     size_display: converted,
                     size_raw: raw,
-                    path: match file_path.canonicalize() {
+                    location: match file_path.canonicalize() {
                         Ok(canonical_path) => {
                             let path_str = canonical_path.to_string_lossy().into_owned();
                             let path_str = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
@@ -260,9 +277,12 @@ fn calculate_dir_size1(
                             eprintln!("获取绝对路径失败: {}", e);
                             "".to_string()
                         }
-                    },
+                    }
+                    .into(),
                     name: file_name, // 新增字段
                     created_time: metadata.created().ok().expect("REASON"), // 创建时间
+                    modified_time: metadata.modified().ok(),
+                    severity: None,
                 });
             }
         } else {
@@ -273,8 +293,53 @@ fn calculate_dir_size1(
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn unreadable_directories_count_as_empty() {
+        let fs = crate::vfs::MemoryFs::new();
+        fs.add_file("/mem/a/1.bin", 100);
+        fs.add_file("/mem/a/b/2.bin", 20);
+        fs.add_file("/mem/locked/3.bin", 5);
+        fs.fail("/mem/locked", std::io::ErrorKind::PermissionDenied);
+        let pb = ProgressBar::hidden();
+        for parallel in [false, true] {
+            assert_eq!(dir_size_in(&fs, Path::new("/mem"), &pb, parallel), 120);
+        }
+    }
+
+    // 用稀疏文件构造 PB 级的目录总大小，并覆盖 FAT32 的 4GiB 单文件上限
+    #[test]
+    fn sparse_files_total_petabytes() {
+        let dir = std::env::temp_dir().join(format!("disksight-sparse-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let over_fat = (4u64 << 30) + 1;
+        fs::File::create(dir.join("over-4g.bin"))
+            .unwrap()
+            .set_len(over_fat)
+            .unwrap();
+        // 8TiB × 128 = 1PiB，ext4 单文件上限为 16TiB
+        let file_size = 1u64 << 43;
+        for i in 0..128 {
+            let file = fs::File::create(dir.join("sub").join(format!("{}.bin", i))).unwrap();
+            if let Err(e) = file.set_len(file_size) {
+                eprintln!("文件系统不支持大稀疏文件，跳过: {}", e);
+                fs::remove_dir_all(&dir).unwrap();
+                return;
+            }
+        }
+
+        let (total, display) = calculate_dir_size(&dir, true, &ProgressBar::hidden(), true);
+        assert_eq!(total, (1u64 << 50) + over_fat);
+        assert_eq!(display, "1.0PB");
+        let (sequential, _) = calculate_dir_size(&dir, false, &ProgressBar::hidden(), false);
+        assert_eq!(sequential, total);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
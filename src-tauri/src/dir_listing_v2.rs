@@ -1,29 +1,75 @@
 use crate::emit_progress;
 
-use super::models::{Cli, FileEntry};
-use super::utils::{human_readable_size, progress_bar_init};
+use super::collation::sort_names;
+use super::dir_reader::DirChild;
+use super::git_repos::GIT_DIR;
+use super::ignore_rules::{IgnoreFilter, IgnoreMode, ScanOverride, Verdict};
+use super::models::{Cli, FileEntry, ProgressStatus};
+use super::parallelism::sort_for_sequential_reads;
+use super::utils::{
+    canonical_string, child_location, human_readable_size, progress_bar_init, sum_sizes,
+};
+use crate::scan_context::ScanEmitter;
+use crate::vfs::Fs;
 use indicatif::ProgressBar;
 use rayon::prelude::*;
-use std::fs;
 use std::io::Error;
 use std::path::{Path, PathBuf};
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+const COUNT_REPORT_INTERVAL: u64 = 1000;
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::vfs::RealFs;
+    use std::fs;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn count_applies_the_same_filters_as_sizing() {
+        let root = std::env::temp_dir().join(format!("disksight-count-{}", std::process::id()));
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("Library/cache")).unwrap();
+        fs::write(root.join("a/one"), b"1").unwrap();
+        fs::write(root.join("a/b/two"), b"2").unwrap();
+        fs::write(root.join("Library/cache/big"), b"3").unwrap();
+        let emitter = ScanEmitter::forwarding(0, None, |_, _| {});
+
+        assert_eq!(
+            count_entries(&RealFs, &root, IgnoreMode::Off, &[], false, &emitter),
+            7
+        );
+        let overrides = [ScanOverride {
+            path: root.to_string_lossy().into_owned(),
+            exclude: vec!["Library/".to_string()],
+            ..Default::default()
+        }];
+        assert_eq!(
+            count_entries(&RealFs, &root, IgnoreMode::Off, &overrides, true, &emitter),
+            4
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
 
-// 使用事件系统的目录列表函数
+// 使用事件系统的目录列表函数，ignore 不为 Off 时按忽略文件过滤统计的内容
 pub fn list_directory_with_events(
+    fs: &dyn Fs,
     path: &Path,
     args: &Cli,
-    app_handle: &AppHandle,
+    ignore: IgnoreMode,
+    overrides: &[ScanOverride],
+    app_handle: &ScanEmitter,
 ) -> Result<Vec<FileEntry>, Error> {
-    let entries = match fs::read_dir(path) {
+    let entries = match fs.read_dir(path) {
         Ok(entries) => entries,
         Err(e) => {
             eprintln!("ls: cannot access '{}': {}", path.display(), e);
@@ -31,55 +77,59 @@ pub fn list_directory_with_events(
         }
     };
 
-    let mut files: Vec<String> = Vec::new();
-    for entry in entries.flatten() {
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        files.push(file_name);
+    let mut files: Vec<String> = entries
+        .iter()
+        .map(|entry| entry.name.to_string_lossy().to_string())
+        .collect();
+    if files.iter().any(|f| f == GIT_DIR) {
+        emit_git_repo(app_handle, path);
     }
+    sort_names(&mut files, args.collation);
     let sorted_files = files.clone();
-    files.sort();
     let total_files = sorted_files.len();
     let mut entries = Vec::new();
+    // 根目录只规范化一次，普通子项共享该字符串作为父目录
+    let canonical_root: Option<Arc<str>> = canonical_string(path).map(Arc::from);
+    let ignore = IgnoreFilter::for_root(path, ignore, overrides);
 
     if args.long_format {
         let process_pb = progress_bar_init(None).unwrap();
         process_pb.set_message("处理中...");
 
+        let mut processed_bytes = 0u64;
         for (index, file) in sorted_files.iter().enumerate() {
-            // 发送处理进度事件
-            emit_progress(app_handle, path, Path::new(file), "processing");
-
             process_pb.tick();
             let file_path = path.join(file);
 
-            // 只在处理大文件或每10%进度时报告
-            if index % std::cmp::max(1, total_files / 10) == 0 {
-                emit_progress(
-                    app_handle,
-                    path,
-                    Path::new(file),
-                    &format!("progress_{}%", (index * 100) / total_files),
-                );
-            }
-
             if args.name.is_some() {
-                let metadata = match file_path.metadata() {
+                let metadata = match fs.stat(&file_path) {
                     Ok(m) => m,
                     Err(e) => {
                         eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
+                        emit_progress(
+                            app_handle,
+                            path,
+                            &file_path,
+                            ProgressStatus::Error { kind: (&e).into() },
+                        );
                         continue;
                     }
                 };
-                if metadata.is_dir() {
+                if metadata.is_dir {
                     if let Some(name) = &args.name {
                         if !file.contains(name) {
+                            let Some(child) = filter_for(ignore.as_ref(), &file_path, true) else {
+                                continue;
+                            };
                             calculate_dir_size_with_events(
+                                fs,
                                 file_path,
                                 args.human_readable,
                                 &process_pb,
                                 args.parallel,
                                 name,
                                 &mut entries,
+                                child.as_ref(),
                                 app_handle,
                             );
                             continue;
@@ -90,68 +140,84 @@ pub fn list_directory_with_events(
                 }
             }
 
-            let metadata = match file_path.metadata() {
+            let metadata = match fs.stat(&file_path) {
                 Ok(m) => m,
                 Err(e) => {
                     eprintln!("ls: cannot access '{}': {}", file_path.display(), e);
+                    emit_progress(
+                        app_handle,
+                        path,
+                        &file_path,
+                        ProgressStatus::Error { kind: (&e).into() },
+                    );
                     continue;
                 }
             };
 
-            let (size_display, size_raw) = if metadata.is_dir() {
-                // 发送开始计算目录大小事件
-                emit_progress(app_handle, path, &file_path, "calculating_directory_size");
+            let Some(child) = filter_for(ignore.as_ref(), &file_path, metadata.is_dir) else {
+                continue;
+            };
+            app_handle.advance();
 
+            // 目录的 EnteringDir / DirCompleted 事件由 calculate_dir_size_with_events_simple 发送
+            let (size_display, size_raw) = if metadata.is_dir {
                 let (raw, converted) = calculate_dir_size_with_events_simple(
+                    fs,
                     &file_path,
                     args.human_readable,
                     &process_pb,
                     args.parallel,
+                    child.as_ref(),
                     app_handle,
                 );
-
-                // 发送完成目录计算事件
-                emit_progress(
-                    app_handle,
-                    path,
-                    &file_path,
-                    "directory_calculation_completed",
-                );
                 (converted, raw)
             } else if args.human_readable {
-                (human_readable_size(metadata.len()), metadata.len())
+                (human_readable_size(metadata.len), metadata.len)
             } else {
-                (metadata.len().to_string(), metadata.len())
+                (metadata.len.to_string(), metadata.len)
             };
 
+            // 反向模式下不含任何被忽略内容的目录不列出
+            if size_raw == 0
+                && child
+                    .as_ref()
+                    .is_some_and(|c| c.mode() == IgnoreMode::OnlyIgnored)
+            {
+                continue;
+            }
+
+            processed_bytes = processed_bytes.saturating_add(size_raw);
             entries.push(FileEntry {
-                file_type: if metadata.is_dir() { 'd' } else { '-' },
+                file_type: if metadata.is_dir { 'd' } else { '-' },
                 permissions: format!(
                     "{}-{}-{}",
-                    if metadata.permissions().readonly() {
-                        "r"
-                    } else {
-                        " "
-                    },
+                    if metadata.readonly { "r" } else { " " },
                     "w",
                     "x"
                 ),
                 size_display,
                 size_raw,
-                path: match file_path.canonicalize() {
-                    Ok(canonical_path) => {
-                        let path_str = canonical_path.to_string_lossy().into_owned();
-                        let path_str = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
-                        path_str.to_string()
-                    }
-                    Err(_e) => file_path.to_string_lossy().into_owned(),
-                },
+                location: child_location(canonical_root.as_ref(), &file_path),
                 name: file.to_string(),
-                created_time: metadata.created()?,
+                // 不支持创建时间的文件系统上用修改时间代替
+                created_time: metadata.created.or(metadata.modified).unwrap_or(UNIX_EPOCH),
+                modified_time: metadata.modified,
+                severity: None,
             });
+            if let Some(entry) = entries.last() {
+                app_handle.entry_done(entry);
+            }
 
-            // 发送完成当前文件事件
-            emit_progress(app_handle, path, &file_path, "completed");
+            emit_progress(
+                app_handle,
+                path,
+                &file_path,
+                ProgressStatus::FileProcessed {
+                    count: index as u64 + 1,
+                    bytes: processed_bytes,
+                    total: Some(total_files as u64),
+                },
+            );
         }
 
         process_pb.finish_and_clear();
@@ -169,31 +235,40 @@ pub fn list_directory_with_events(
 }
 
 // 使用事件系统的目录搜索函数
+#[allow(clippy::too_many_arguments)]
 fn calculate_dir_size_with_events(
+    fs: &dyn Fs,
     file_path: PathBuf,
     human_readable: bool,
     pb: &ProgressBar,
     parallel: bool,
     name: &str,
     entries: &mut Vec<FileEntry>,
-    app_handle: &AppHandle,
+    ignore: Option<&IgnoreFilter>,
+    app_handle: &ScanEmitter,
 ) {
     let sub_path_str = file_path.display().to_string();
     let sub_path = Path::new(&sub_path_str);
 
-    emit_progress(app_handle, sub_path, sub_path, "searching_in_directory");
+    emit_progress(app_handle, sub_path, sub_path, ProgressStatus::EnteringDir);
 
-    let sub_entries = match fs::read_dir(sub_path) {
+    let sub_entries = match fs.read_dir(sub_path) {
         Ok(entries) => entries,
         Err(e) => {
             eprintln!("ls: cannot access '{}': {}", sub_path.display(), e);
+            emit_progress(
+                app_handle,
+                sub_path,
+                sub_path,
+                ProgressStatus::Error { kind: (&e).into() },
+            );
             return;
         }
     };
 
-    for entry in sub_entries.flatten() {
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        let metadata = match entry.metadata() {
+    for entry in sub_entries {
+        let file_name = entry.name.to_string_lossy().to_string();
+        let metadata = match fs.metadata(&sub_path.join(&entry.name)) {
             Ok(m) => m,
             Err(e) => {
                 eprintln!("ls: cannot access '{}': {}", sub_path.display(), e);
@@ -201,52 +276,46 @@ fn calculate_dir_size_with_events(
             }
         };
 
-        emit_progress(app_handle, sub_path, &entry.path(), "checking_file");
-
-        if metadata.is_dir() {
+        if metadata.is_dir {
             let file_path = sub_path.join(&file_name);
+            let Some(child_ignore) = filter_for(ignore, &file_path, true) else {
+                continue;
+            };
             if !file_name.contains(name) {
                 calculate_dir_size_with_events(
+                    fs,
                     file_path,
                     human_readable,
                     pb,
                     parallel,
                     name,
                     entries,
+                    child_ignore.as_ref(),
                     app_handle,
                 );
                 continue;
             } else {
-                emit_progress(
-                    app_handle,
-                    sub_path,
-                    &file_path,
-                    "calculating_matching_directory",
-                );
-
                 let (raw, converted) = calculate_dir_size_with_events_simple(
+                    fs,
                     &file_path,
                     human_readable,
                     pb,
                     parallel,
+                    child_ignore.as_ref(),
                     app_handle,
                 );
 
                 entries.push(FileEntry {
-                    file_type: if metadata.is_dir() { 'd' } else { '-' },
+                    file_type: if metadata.is_dir { 'd' } else { '-' },
                     permissions: format!(
                         "{}-{}-{}",
-                        if metadata.permissions().readonly() {
-                            "r"
-                        } else {
-                            " "
-                        },
+                        if metadata.readonly { "r" } else { " " },
                         "w",
                         "x"
                     ),
                     size_display: converted,
                     size_raw: raw,
-                    path: match file_path.canonicalize() {
+                    location: match file_path.canonicalize() {
                         Ok(canonical_path) => {
                             let path_str = canonical_path.to_string_lossy().into_owned();
                             let path_str = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
@@ -256,17 +325,13 @@ fn calculate_dir_size_with_events(
                             eprintln!("获取绝对路径失败: {}", e);
                             "".to_string()
                         }
-                    },
+                    }
+                    .into(),
                     name: file_name,
-                    created_time: metadata.created().unwrap_or(std::time::SystemTime::now()),
+                    created_time: metadata.created.unwrap_or(std::time::SystemTime::now()),
+                    modified_time: metadata.modified,
+                    severity: None,
                 });
-
-                emit_progress(
-                    app_handle,
-                    sub_path,
-                    &file_path,
-                    "matching_directory_completed",
-                );
             }
         }
     }
@@ -274,79 +339,91 @@ fn calculate_dir_size_with_events(
 
 // 使用事件系统的目录大小计算函数
 pub fn calculate_dir_size_with_events_simple(
+    fs: &dyn Fs,
     path: &Path,
     human_readable: bool,
     main_pb: &ProgressBar,
     parallel: bool,
-    app_handle: &AppHandle,
+    ignore: Option<&IgnoreFilter>,
+    app_handle: &ScanEmitter,
 ) -> (u64, String) {
-    fn inner_calculate(p: &Path, pb: &ProgressBar, parallel: bool, app_handle: &AppHandle) -> u64 {
-        match fs::read_dir(p) {
-            Ok(entries) => {
-                let mut total_size = 0;
-                let entries: Vec<_> = entries
-                    .filter_map(|e| {
-                        pb.tick();
-                        match e {
-                            Ok(entry) => {
-                                // 发送处理文件事件
-                                emit_progress(app_handle, p, &entry.path(), "processing_file");
-                                Some(entry)
-                            }
-                            Err(e) => {
-                                eprintln!("无法读取目录项 {}: {}", p.display(), e);
-                                None
-                            }
-                        }
-                    })
-                    .collect();
-
+    fn inner_calculate(
+        fs: &dyn Fs,
+        p: &Path,
+        pb: &ProgressBar,
+        parallel: bool,
+        ignore: Option<&IgnoreFilter>,
+        app_handle: &ScanEmitter,
+    ) -> u64 {
+        emit_progress(app_handle, p, p, ProgressStatus::EnteringDir);
+        // 批量读取目录项及其大小，元数据读取失败的条目已在内部跳过
+        let size = match fs.read_dir(p) {
+            Ok(mut entries) => {
+                pb.tick();
+                if entries.iter().any(|e| e.name == GIT_DIR) {
+                    emit_git_repo(app_handle, p);
+                }
                 if parallel {
-                    total_size += entries
+                    entries
                         .par_iter()
-                        .map(|e| process_entry_with_events(e, pb, parallel, app_handle))
-                        .sum::<u64>();
+                        .map(|e| {
+                            process_entry_with_events(fs, p, e, pb, parallel, ignore, app_handle)
+                        })
+                        .reduce(|| 0, u64::saturating_add)
                 } else {
-                    total_size += entries
-                        .iter()
-                        .map(|e| process_entry_with_events(e, pb, parallel, app_handle))
-                        .sum::<u64>();
+                    // 按 inode 顺序读取以减少机械硬盘寻道
+                    sort_for_sequential_reads(&mut entries);
+                    sum_sizes(entries.iter().map(|e| {
+                        process_entry_with_events(fs, p, e, pb, parallel, ignore, app_handle)
+                    }))
                 }
-
-                total_size
             }
             Err(e) => {
                 eprintln!("无法读取目录 {}: {}", p.display(), e);
+                emit_progress(
+                    app_handle,
+                    p,
+                    p,
+                    ProgressStatus::Error { kind: (&e).into() },
+                );
                 0
             }
-        }
+        };
+        emit_progress(app_handle, p, p, ProgressStatus::DirCompleted { size });
+        size
     }
 
     fn process_entry_with_events(
-        e: &std::fs::DirEntry,
+        fs: &dyn Fs,
+        parent: &Path,
+        e: &DirChild,
         pb: &ProgressBar,
         parallel: bool,
-        app_handle: &AppHandle,
+        ignore: Option<&IgnoreFilter>,
+        app_handle: &ScanEmitter,
     ) -> u64 {
-        match e.metadata() {
-            Ok(metadata) => {
-                if metadata.is_dir() {
-                    inner_calculate(&e.path(), pb, parallel, app_handle)
-                } else {
-                    metadata.len()
-                }
-            }
-            Err(e) => {
-                eprintln!("无法获取文件元数据 {}", e);
-                0
+        let path = parent.join(&e.name);
+        let Some(child) = filter_for(ignore, &path, e.is_dir) else {
+            return 0;
+        };
+        app_handle.advance();
+        if !e.is_dir {
+            // 设置了跟随符号链接的目录中，统计链接指向的目录
+            if let Some(linked) = ignore.and_then(|ignore| ignore.follow_link(&path)) {
+                return inner_calculate(fs, &path, pb, parallel, Some(&linked), app_handle);
             }
         }
+        let ignore = child;
+        if e.is_dir {
+            inner_calculate(fs, &path, pb, parallel, ignore.as_ref(), app_handle)
+        } else {
+            e.size
+        }
     }
 
     main_pb.set_message(format!("计算 {}...", path.display()));
-    emit_progress(app_handle, path, path, "calculating_directory_size");
 
-    let total = inner_calculate(path, main_pb, parallel, app_handle);
+    let total = inner_calculate(fs, path, main_pb, parallel, ignore, app_handle);
     main_pb.set_message("处理中...");
 
     let converted = if human_readable {
@@ -356,3 +433,96 @@ pub fn calculate_dir_size_with_events_simple(
     };
     (total, converted)
 }
+
+// 两阶段扫描的第一阶段：只读取目录项，按与第二阶段相同的过滤规则统计条目总数。
+// 每统计 COUNT_REPORT_INTERVAL 个条目发送一次 count 阶段的进度，total 为 0 表示总数未知
+pub fn count_entries(
+    fs: &dyn Fs,
+    path: &Path,
+    ignore: IgnoreMode,
+    overrides: &[ScanOverride],
+    parallel: bool,
+    app_handle: &ScanEmitter,
+) -> u64 {
+    let counted = AtomicU64::new(0);
+    let ignore = IgnoreFilter::for_root(path, ignore, overrides);
+    let counter = EntryCounter {
+        fs,
+        root: path,
+        parallel,
+        counted: &counted,
+        app_handle,
+    };
+    counter.count(path, ignore.as_ref())
+}
+
+struct EntryCounter<'a> {
+    fs: &'a dyn Fs,
+    root: &'a Path,
+    parallel: bool,
+    counted: &'a AtomicU64,
+    app_handle: &'a ScanEmitter,
+}
+
+impl EntryCounter<'_> {
+    fn count(&self, dir: &Path, ignore: Option<&IgnoreFilter>) -> u64 {
+        let Ok(children) = self.fs.read_dir(dir) else {
+            return 0;
+        };
+        let count_child = |e: &DirChild| {
+            let path = dir.join(&e.name);
+            let Some(child) = filter_for(ignore, &path, e.is_dir) else {
+                return 0;
+            };
+            let counted = self.counted.fetch_add(1, Ordering::Relaxed) + 1;
+            if counted % COUNT_REPORT_INTERVAL == 0 {
+                emit_progress(
+                    self.app_handle,
+                    self.root,
+                    &path,
+                    ProgressStatus::Stage {
+                        stage: "count".to_string(),
+                        done: counted,
+                        total: 0,
+                    },
+                );
+            }
+            let nested = if e.is_dir {
+                self.count(&path, child.as_ref())
+            } else if let Some(linked) = ignore.and_then(|ignore| ignore.follow_link(&path)) {
+                self.count(&path, Some(&linked))
+            } else {
+                0
+            };
+            nested + 1
+        };
+        if self.parallel {
+            children
+                .par_iter()
+                .map(count_child)
+                .reduce(|| 0, u64::saturating_add)
+        } else {
+            children.iter().map(count_child).sum()
+        }
+    }
+}
+
+// 按忽略规则决定条目是否计入：None 表示跳过；计入时返回进入该条目后使用的过滤器，
+// 整个条目都计入且没有按路径选项（或未启用忽略规则）时为 None
+fn filter_for(
+    ignore: Option<&IgnoreFilter>,
+    path: &Path,
+    is_dir: bool,
+) -> Option<Option<IgnoreFilter>> {
+    match ignore.map(|ignore| (ignore.verdict(path, is_dir), ignore)) {
+        None => Some(None),
+        Some((Verdict::Whole, ignore)) => Some(ignore.whole()),
+        Some((Verdict::Skip, _)) => None,
+        Some((Verdict::Descend, ignore)) => Some(Some(ignore.enter(path))),
+    }
+}
+
+// 扫描中发现 git 仓库时通知前端，详细统计由 find_git_repos 完成
+fn emit_git_repo(app_handle: &ScanEmitter, dir: &Path) {
+    app_handle.emit("git_repo", dir.to_string_lossy().into_owned());
+}
@@ -0,0 +1,596 @@
+use crate::platform;
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriveInfo {
+    /// 挂载点（Windows 下为盘符根目录）
+    pub mount_point: String,
+    /// 设备名，如 /dev/sda1、C:
+    pub device: String,
+    /// 文件系统类型
+    pub file_system: String,
+    /// 总容量
+    pub total_space: u64,
+    /// 可用空间
+    pub available_space: u64,
+    pub total_display: String,
+    pub available_display: String,
+    /// S.M.A.R.T. 健康信息，仅 drive_health 时填充
+    pub health: Option<DriveHealth>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriveHealth {
+    /// 传给 smartctl 的物理设备名
+    pub device: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    /// SMART 总体自检结果
+    pub passed: Option<bool>,
+    /// 当前温度（摄氏度）
+    pub temperature: Option<i64>,
+    pub power_on_hours: Option<u64>,
+    /// 是否预测即将故障
+    pub failure_predicted: bool,
+    /// 需要提醒用户的问题
+    pub warnings: Vec<String>,
+    pub attributes: Vec<SmartAttribute>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmartAttribute {
+    pub id: u64,
+    pub name: String,
+    pub value: u64,
+    pub worst: u64,
+    pub threshold: u64,
+    pub raw: u64,
+    /// 当前值已低于阈值
+    pub failing: bool,
+}
+
+// 温度超过该值时给出提醒
+const TEMPERATURE_WARNING: i64 = 60;
+
+// 列出本机所有已挂载的卷及容量
+pub fn list_drives() -> Vec<DriveInfo> {
+    let mut drives: Vec<DriveInfo> = mounted_volumes()
+        .into_iter()
+        .filter_map(|(mount_point, device, file_system)| {
+            let (total_space, available_space) = volume_space(Path::new(&mount_point))?;
+            Some(DriveInfo {
+                mount_point,
+                device,
+                file_system,
+                total_space,
+                available_space,
+                total_display: human_readable_size(total_space),
+                available_display: human_readable_size(available_space),
+                health: None,
+            })
+        })
+        .collect();
+    drives.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    drives
+}
+
+// 列出卷容量并附带 S.M.A.R.T. 健康信息
+pub fn drive_health() -> Vec<DriveInfo> {
+    let mut drives = list_drives();
+    for drive in drives.iter_mut() {
+        drive.health = physical_device(&drive.device).and_then(|dev| read_smart(&dev));
+    }
+    drives
+}
+
+// 通过 smartctl 的 JSON 输出读取健康信息，未安装 smartctl 或设备不支持时返回 None
+pub fn read_smart(device: &str) -> Option<DriveHealth> {
+    if !platform::supports_smart() {
+        return None;
+    }
+    let output = platform::command("smartctl")
+        .args(["-a", "-j", device])
+        .output()
+        .ok()?;
+    // smartctl 的退出码是位掩码，磁盘异常时同样非零，因此只看输出能否解析
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    Some(parse_smart(device, &json))
+}
+
+fn parse_smart(device: &str, json: &Value) -> DriveHealth {
+    let str_field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(String::from);
+    let passed = json
+        .get("smart_status")
+        .and_then(|s| s.get("passed"))
+        .and_then(|v| v.as_bool());
+    let temperature = json
+        .get("temperature")
+        .and_then(|t| t.get("current"))
+        .and_then(|v| v.as_i64());
+    let power_on_hours = json
+        .get("power_on_time")
+        .and_then(|t| t.get("hours"))
+        .and_then(|v| v.as_u64());
+
+    let mut attributes = Vec::new();
+    if let Some(table) = json
+        .get("ata_smart_attributes")
+        .and_then(|a| a.get("table"))
+        .and_then(|t| t.as_array())
+    {
+        for row in table {
+            let num = |key: &str| row.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            let when_failed = row.get("when_failed").and_then(|v| v.as_str());
+            attributes.push(SmartAttribute {
+                id: num("id"),
+                name: row
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                value: num("value"),
+                worst: num("worst"),
+                threshold: num("thresh"),
+                raw: row
+                    .get("raw")
+                    .and_then(|r| r.get("value"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0),
+                failing: when_failed == Some("now"),
+            });
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut failure_predicted = passed == Some(false);
+    if failure_predicted {
+        warnings.push("SMART 自检未通过，磁盘可能即将损坏，请尽快备份数据".to_string());
+    }
+    for attr in &attributes {
+        if attr.failing {
+            failure_predicted = true;
+            warnings.push(format!("属性 {} 已低于厂商阈值", attr.name));
+        }
+        // 重映射扇区、待映射扇区、无法校正扇区
+        if matches!(attr.id, 5 | 197 | 198) && attr.raw > 0 {
+            warnings.push(format!("{}: {}", attr.name, attr.raw));
+        }
+    }
+    if let Some(nvme) = json.get("nvme_smart_health_information_log") {
+        if nvme
+            .get("critical_warning")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+            != 0
+        {
+            failure_predicted = true;
+            warnings.push("NVMe 控制器报告了严重警告".to_string());
+        }
+        if let Some(used) = nvme.get("percentage_used").and_then(|v| v.as_u64()) {
+            if used >= 90 {
+                warnings.push(format!("NVMe 寿命已消耗 {}%", used));
+            }
+        }
+        if let Some(errors) = nvme.get("media_errors").and_then(|v| v.as_u64()) {
+            if errors > 0 {
+                warnings.push(format!("介质错误: {}", errors));
+            }
+        }
+    }
+    if let Some(temp) = temperature {
+        if temp >= TEMPERATURE_WARNING {
+            warnings.push(format!("磁盘温度过高: {}°C", temp));
+        }
+    }
+
+    DriveHealth {
+        device: device.to_string(),
+        model: str_field("model_name"),
+        serial: str_field("serial_number"),
+        passed,
+        temperature,
+        power_on_hours,
+        failure_predicted,
+        warnings,
+        attributes,
+    }
+}
+
+// 由分区设备名推导 smartctl 可识别的物理磁盘名
+pub(crate) fn physical_device(device: &str) -> Option<String> {
+    if cfg!(windows) {
+        // smartctl 在 Windows 下直接接受 "C:" 形式的盘符
+        return Some(device.trim_end_matches('\\').to_string());
+    }
+    let name = device.strip_prefix("/dev/")?;
+    let disk = if name.starts_with("nvme") || name.starts_with("mmcblk") {
+        // nvme0n1p2 -> nvme0n1
+        match name.rfind('p') {
+            Some(pos) if pos > 0 && name[pos + 1..].chars().all(|c| c.is_ascii_digit()) => {
+                &name[..pos]
+            }
+            _ => name,
+        }
+    } else if let Some(rest) = name.strip_prefix("disk") {
+        // macOS: disk1s2 -> disk1
+        match rest.find('s') {
+            Some(pos) => &name[..pos + 4],
+            None => name,
+        }
+    } else {
+        // sda1 -> sda
+        name.trim_end_matches(|c: char| c.is_ascii_digit())
+    };
+    Some(format!("/dev/{}", disk))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskKind {
+    Ssd,
+    /// 机械硬盘，并发随机读会导致频繁寻道
+    Hdd,
+    Unknown,
+}
+
+// 判断物理磁盘是否为机械硬盘，device 为 physical_device 的返回值
+#[cfg(target_os = "linux")]
+pub fn disk_kind(device: &str) -> DiskKind {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        return DiskKind::Unknown;
+    };
+    match std::fs::read_to_string(format!("/sys/block/{}/queue/rotational", name)) {
+        Ok(value) if value.trim() == "1" => DiskKind::Hdd,
+        Ok(value) if value.trim() == "0" => DiskKind::Ssd,
+        _ => DiskKind::Unknown,
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn disk_kind(device: &str) -> DiskKind {
+    let Ok(output) = platform::command("diskutil")
+        .args(["info", device])
+        .output()
+    else {
+        return DiskKind::Unknown;
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    let solid = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Solid State:"))
+        .map(|value| value.trim().to_string());
+    match solid.as_deref() {
+        Some("Yes") => DiskKind::Ssd,
+        Some("No") => DiskKind::Hdd,
+        _ => DiskKind::Unknown,
+    }
+}
+
+// 通过 IOCTL_STORAGE_QUERY_PROPERTY 查询是否有寻道开销，device 为 "C:" 形式
+#[cfg(windows)]
+pub fn disk_kind(device: &str) -> DiskKind {
+    use winapi::ctypes::c_void;
+    use winapi::um::fileapi::CreateFileW;
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::ioapiset::DeviceIoControl;
+
+    const IOCTL_STORAGE_QUERY_PROPERTY: u32 = 0x002d_1400;
+    const STORAGE_DEVICE_SEEK_PENALTY_PROPERTY: u32 = 7;
+    const FILE_SHARE_READ_WRITE: u32 = 0x1 | 0x2;
+    const OPEN_EXISTING: u32 = 3;
+
+    #[repr(C)]
+    struct StoragePropertyQuery {
+        property_id: u32,
+        query_type: u32,
+        additional: [u8; 1],
+    }
+    #[repr(C)]
+    #[derive(Default)]
+    struct SeekPenaltyDescriptor {
+        version: u32,
+        size: u32,
+        incurs_seek_penalty: u8,
+    }
+
+    let wide = to_wide(&format!(r"\\.\{}", device.trim_end_matches('\\')));
+    unsafe {
+        // 访问权限为 0 即可查询属性，不需要管理员权限
+        let handle = CreateFileW(
+            wide.as_ptr(),
+            0,
+            FILE_SHARE_READ_WRITE,
+            std::ptr::null_mut(),
+            OPEN_EXISTING,
+            0,
+            std::ptr::null_mut(),
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            return DiskKind::Unknown;
+        }
+        let query = StoragePropertyQuery {
+            property_id: STORAGE_DEVICE_SEEK_PENALTY_PROPERTY,
+            query_type: 0,
+            additional: [0],
+        };
+        let mut descriptor = SeekPenaltyDescriptor::default();
+        let mut returned = 0u32;
+        let ok = DeviceIoControl(
+            handle,
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            &query as *const _ as *mut c_void,
+            std::mem::size_of::<StoragePropertyQuery>() as u32,
+            &mut descriptor as *mut _ as *mut c_void,
+            std::mem::size_of::<SeekPenaltyDescriptor>() as u32,
+            &mut returned,
+            std::ptr::null_mut(),
+        );
+        CloseHandle(handle);
+        match (ok, descriptor.incurs_seek_penalty) {
+            (0, _) => DiskKind::Unknown,
+            (_, 0) => DiskKind::Ssd,
+            _ => DiskKind::Hdd,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn disk_kind(_device: &str) -> DiskKind {
+    DiskKind::Unknown
+}
+
+#[derive(Clone, Debug)]
+pub struct MountInfo {
+    pub mount_point: String,
+    pub device: String,
+    pub file_system: String,
+}
+
+// 查找包含该路径的最长挂载点
+pub fn mount_of(path: &Path) -> Option<MountInfo> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    all_mounts()
+        .into_iter()
+        .filter(|(mount_point, _, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _, _)| mount_point.len())
+        .map(|(mount_point, device, file_system)| MountInfo {
+            mount_point,
+            device,
+            file_system,
+        })
+}
+
+// 包含 tmpfs、zfs 数据集等非块设备挂载
+#[cfg(target_os = "linux")]
+fn all_mounts() -> Vec<(String, String, String)> {
+    let content = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let device = parts.next()?;
+            let mount_point = parts.next()?.replace("\\040", " ");
+            let fs_type = parts.next()?;
+            Some((mount_point, device.to_string(), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn all_mounts() -> Vec<(String, String, String)> {
+    mounted_volumes()
+}
+
+// 返回 (挂载点, 设备, 文件系统)
+#[cfg(target_os = "linux")]
+fn mounted_volumes() -> Vec<(String, String, String)> {
+    let content = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let mut volumes: Vec<(String, String, String)> = Vec::new();
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(device), Some(mount_point), Some(fs_type)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // 只保留真实块设备，跳过 snap 等 loop 设备
+        if !device.starts_with("/dev/") || device.starts_with("/dev/loop") {
+            continue;
+        }
+        // 同一设备多次挂载时只保留第一个
+        if volumes.iter().any(|(_, d, _)| d == device) {
+            continue;
+        }
+        volumes.push((
+            mount_point.replace("\\040", " "),
+            device.to_string(),
+            fs_type.to_string(),
+        ));
+    }
+    volumes
+}
+
+#[cfg(target_os = "macos")]
+fn mounted_volumes() -> Vec<(String, String, String)> {
+    use std::ffi::CStr;
+    let mut buf: *mut libc::statfs = std::ptr::null_mut();
+    let count = unsafe { libc::getmntinfo(&mut buf, libc::MNT_NOWAIT) };
+    if count <= 0 || buf.is_null() {
+        return Vec::new();
+    }
+    let stats = unsafe { std::slice::from_raw_parts(buf, count as usize) };
+    stats
+        .iter()
+        .filter_map(|st| {
+            let device = unsafe { CStr::from_ptr(st.f_mntfromname.as_ptr()) }
+                .to_string_lossy()
+                .into_owned();
+            if !device.starts_with("/dev/") {
+                return None;
+            }
+            let mount_point = unsafe { CStr::from_ptr(st.f_mntonname.as_ptr()) }
+                .to_string_lossy()
+                .into_owned();
+            let fs_type = unsafe { CStr::from_ptr(st.f_fstypename.as_ptr()) }
+                .to_string_lossy()
+                .into_owned();
+            Some((mount_point, device, fs_type))
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn mounted_volumes() -> Vec<(String, String, String)> {
+    use winapi::um::fileapi::{GetLogicalDrives, GetVolumeInformationW};
+    let mask = unsafe { GetLogicalDrives() };
+    let mut volumes = Vec::new();
+    for i in 0..26u32 {
+        if mask & (1 << i) == 0 {
+            continue;
+        }
+        let letter = (b'A' + i as u8) as char;
+        let root = format!("{}:\\", letter);
+        let wide = to_wide(&root);
+        let mut fs_name = [0u16; 64];
+        let ok = unsafe {
+            GetVolumeInformationW(
+                wide.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                fs_name.as_mut_ptr(),
+                fs_name.len() as u32,
+            )
+        };
+        // 未插入介质的光驱等会失败，直接跳过
+        if ok == 0 {
+            continue;
+        }
+        let len = fs_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(fs_name.len());
+        volumes.push((
+            root,
+            format!("{}:", letter),
+            String::from_utf16_lossy(&fs_name[..len]),
+        ));
+    }
+    volumes
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn mounted_volumes() -> Vec<(String, String, String)> {
+    Vec::new()
+}
+
+// 查询某路径所在卷的 (总容量, 可用空间)
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn volume_space(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 {
+        return None;
+    }
+    let block = st.f_frsize as u64;
+    Some((st.f_blocks as u64 * block, st.f_bavail as u64 * block))
+}
+
+// 仅 root 可用的保留空间（ext 系列默认保留 5%），计入已用但普通用户无法使用
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn reserved_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 {
+        return None;
+    }
+    Some((st.f_bfree as u64).saturating_sub(st.f_bavail as u64) * st.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn reserved_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+// 卷上的 inode 总数，用于估算 inode 表占用
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn inode_count(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 {
+        return None;
+    }
+    Some(st.f_files as u64)
+}
+
+#[cfg(not(unix))]
+pub fn inode_count(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(windows)]
+pub fn volume_space(path: &Path) -> Option<(u64, u64)> {
+    use winapi::shared::ntdef::ULARGE_INTEGER;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    let wide = to_wide(&path.to_string_lossy());
+    unsafe {
+        let mut available: ULARGE_INTEGER = std::mem::zeroed();
+        let mut total: ULARGE_INTEGER = std::mem::zeroed();
+        let mut free: ULARGE_INTEGER = std::mem::zeroed();
+        if GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) == 0 {
+            return None;
+        }
+        Some((*total.QuadPart(), *available.QuadPart()))
+    }
+}
+
+// 卷的分配单元（簇）大小
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn cluster_size(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 {
+        return None;
+    }
+    Some(st.f_frsize as u64).filter(|&size| size > 0)
+}
+
+#[cfg(windows)]
+pub fn cluster_size(path: &Path) -> Option<u64> {
+    use winapi::um::fileapi::GetDiskFreeSpaceW;
+    let root = mount_of(path)?.mount_point;
+    let wide = to_wide(&root);
+    let (mut sectors_per_cluster, mut bytes_per_sector, mut free, mut total) = (0, 0, 0, 0);
+    let ok = unsafe {
+        GetDiskFreeSpaceW(
+            wide.as_ptr(),
+            &mut sectors_per_cluster,
+            &mut bytes_per_sector,
+            &mut free,
+            &mut total,
+        )
+    };
+    (ok != 0).then(|| sectors_per_cluster as u64 * bytes_per_sector as u64)
+}
+
+#[cfg(windows)]
+pub(crate) fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
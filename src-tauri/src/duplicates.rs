@@ -0,0 +1,262 @@
+use crate::chunking::chunk_file;
+use crate::hashing::{hash_files, process_files};
+use crate::utils::{human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 预筛时只读取文件开头的字节数
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// 完整内容的 SHA-256
+    pub hash: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub paths: Vec<String>,
+    /// 只保留一份时可释放的空间
+    pub wasted_bytes: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub wasted_bytes: u64,
+    pub wasted_display: String,
+    /// 实际计算过完整哈希的文件数
+    pub files_hashed: usize,
+}
+
+// 查找内容完全相同的文件：先按大小分组，再比较开头部分的哈希，
+// 最后只对仍然相同的文件计算完整哈希。同一文件的多个硬链接只算一份
+pub fn find_duplicates(
+    root: &Path,
+    min_size: u64,
+    on_progress: &(dyn Fn(&str, usize, usize) + Sync),
+) -> DuplicateReport {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut seen = HashSet::new();
+    collect_files(root, min_size.max(1), &mut by_size, &mut seen);
+
+    let candidates: Vec<PathBuf> = by_size
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .collect();
+    let partial = hash_files(candidates, Some(PARTIAL_HASH_BYTES), &|done, total| {
+        on_progress("partial", done, total)
+    });
+    let survivors = regroup(partial)
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let files_hashed = survivors.len();
+    let full = hash_files(survivors, None, &|done, total| {
+        on_progress("full", done, total)
+    });
+
+    let mut groups: Vec<DuplicateGroup> = regroup(full)
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, hash), paths)| DuplicateGroup {
+            hash,
+            size_raw: size,
+            size_display: human_readable_size(size),
+            wasted_bytes: size * (paths.len() as u64 - 1),
+            paths: paths
+                .iter()
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect(),
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_bytes));
+    let wasted_bytes = sum_sizes(groups.iter().map(|g| g.wasted_bytes));
+    DuplicateReport {
+        groups,
+        wasted_bytes,
+        wasted_display: human_readable_size(wasted_bytes),
+        files_hashed,
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimilarPair {
+    pub a: String,
+    pub b: String,
+    pub size_a: u64,
+    pub size_b: u64,
+    /// 共享数据占较大文件的百分比
+    pub similarity_percent: f64,
+    /// 两个文件共有的数据量，即去重后可节省的空间估计
+    pub shared_bytes: u64,
+    pub shared_display: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SimilarityReport {
+    pub pairs: Vec<SimilarPair>,
+    pub files_chunked: usize,
+}
+
+// 块哈希 -> (出现次数, 块长度)
+type ChunkCounts = HashMap<u64, (u32, u32)>;
+
+// 出现在过多文件中的块（如全零块）不参与配对，避免两两比较的数量爆炸
+const MAX_POSTING: usize = 64;
+
+// 查找大部分内容相同的大文件（虚拟机镜像、连续的备份等）。
+// 用 FastCDC 分块后比较块哈希，完全相同的文件由 find_duplicates 负责，这里不再列出
+pub fn find_similar(
+    root: &Path,
+    min_size: u64,
+    min_similarity: f64,
+    on_progress: &(dyn Fn(usize, usize) + Sync),
+) -> SimilarityReport {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    collect_files(root, min_size.max(1), &mut by_size, &mut HashSet::new());
+    let files: Vec<PathBuf> = by_size.into_values().flatten().collect();
+
+    let chunked: Vec<(PathBuf, u64, ChunkCounts)> =
+        process_files(files, on_progress, &|path, buffer| chunk_file(path, buffer))
+            .into_iter()
+            .filter_map(|(path, chunks)| {
+                let chunks = chunks.ok()?;
+                let size = sum_sizes(chunks.iter().map(|c| c.len as u64));
+                let mut counts = ChunkCounts::new();
+                for chunk in chunks {
+                    counts.entry(chunk.hash).or_insert((0, chunk.len)).0 += 1;
+                }
+                Some((path, size, counts))
+            })
+            .collect();
+
+    // 倒排表：块哈希 -> 包含它的文件
+    let mut postings: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, (_, _, counts)) in chunked.iter().enumerate() {
+        for hash in counts.keys() {
+            postings.entry(*hash).or_default().push(i);
+        }
+    }
+    let mut shared: HashMap<(usize, usize), u64> = HashMap::new();
+    for (hash, files) in &postings {
+        if files.len() < 2 || files.len() > MAX_POSTING {
+            continue;
+        }
+        for (x, &i) in files.iter().enumerate() {
+            for &j in &files[x + 1..] {
+                let (count_i, len) = chunked[i].2[hash];
+                let count_j = chunked[j].2[hash].0;
+                *shared.entry((i, j)).or_default() += count_i.min(count_j) as u64 * len as u64;
+            }
+        }
+    }
+
+    let mut pairs: Vec<SimilarPair> = shared
+        .into_iter()
+        .filter_map(|((i, j), shared_bytes)| {
+            let (a, size_a, _) = &chunked[i];
+            let (b, size_b, _) = &chunked[j];
+            let identical = size_a == size_b && shared_bytes == *size_a;
+            let similarity = shared_bytes as f64 * 100.0 / (*size_a).max(*size_b).max(1) as f64;
+            (!identical && similarity >= min_similarity).then(|| SimilarPair {
+                a: a.to_string_lossy().into_owned(),
+                b: b.to_string_lossy().into_owned(),
+                size_a: *size_a,
+                size_b: *size_b,
+                similarity_percent: similarity,
+                shared_bytes,
+                shared_display: human_readable_size(shared_bytes),
+            })
+        })
+        .collect();
+    pairs.sort_by_key(|p| std::cmp::Reverse(p.shared_bytes));
+    SimilarityReport {
+        pairs,
+        files_chunked: chunked.len(),
+    }
+}
+
+// 按（大小，哈希）重新分组，读取失败的文件直接丢弃
+fn regroup(results: Vec<crate::hashing::HashResult>) -> HashMap<(u64, String), Vec<PathBuf>> {
+    let mut groups: HashMap<(u64, String), Vec<PathBuf>> = HashMap::new();
+    for result in results {
+        let Ok(hash) = result.hash else {
+            continue;
+        };
+        let Ok(metadata) = fs::metadata(&result.path) else {
+            continue;
+        };
+        groups
+            .entry((metadata.len(), hash))
+            .or_default()
+            .push(result.path);
+    }
+    for paths in groups.values_mut() {
+        paths.sort();
+    }
+    groups
+}
+
+// 不跟随符号链接
+fn collect_files(
+    dir: &Path,
+    min_size: u64,
+    by_size: &mut HashMap<u64, Vec<PathBuf>>,
+    seen: &mut HashSet<(u64, u64)>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&path, min_size, by_size, seen);
+        } else if metadata.is_file() && metadata.len() >= min_size {
+            if let Some(id) = file_id(&metadata) {
+                if !seen.insert(id) {
+                    continue;
+                }
+            }
+            by_size.entry(metadata.len()).or_default().push(path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_identical_files() {
+        let dir = std::env::temp_dir().join(format!("disksight-dup-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.bin"), b"same content").unwrap();
+        fs::write(dir.join("sub/b.bin"), b"same content").unwrap();
+        fs::write(dir.join("c.bin"), b"diff content").unwrap();
+
+        let report = find_duplicates(&dir, 1, &|_, _, _| {});
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].paths.len(), 2);
+        assert_eq!(report.wasted_bytes, 12);
+    }
+}
@@ -0,0 +1,565 @@
+// 以管理员权限运行的辅助进程。主程序始终以普通权限运行，只有读取 MFT、扫描受保护目录、
+// 清理系统缓存等少数操作在需要时启动辅助进程完成，辅助进程只接受下面列出的请求。
+//
+// 协议：主程序在 127.0.0.1 的随机端口监听，以提权方式启动辅助进程并通过参数传入端口；
+// 一次性令牌不放在命令行参数中（其他用户可以从进程列表看到），而是通过标准输入传入，
+// 无法连接标准输入的提权方式（UAC、AppleScript）改为传入只有当前用户可读的令牌文件。
+// 辅助进程连接后先发送令牌，之后双方每行一个 JSON，主程序发送 HelperRequest，辅助进程回复 HelperResponse
+use crate::analyzers::linux_system::{self, LinuxCleanup};
+use crate::api_auth::{constant_time_eq, generate_token};
+use crate::platform;
+use crate::profiles::{self, UserProfile};
+use crate::utils::dir_size;
+use crate::windows_system::{self, ComponentStoreReport};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const HELPER_NAME: &str = "disksight-helper";
+// 辅助进程从该参数指定的文件而不是标准输入读取令牌
+pub const TOKEN_FILE_FLAG: &str = "--token-file";
+// 等待用户确认提权并连接的最长时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(120);
+// 连接建立后等待令牌的最长时间，超时的连接直接关闭
+const TOKEN_TIMEOUT: Duration = Duration::from_secs(5);
+// 令牌行的最大字节数
+const MAX_TOKEN_LINE: u64 = 256;
+// 每次清理最多报告的失败条目
+const MAX_REPORTED_ERRORS: usize = 20;
+
+// 允许以管理员权限清理的系统缓存，辅助进程只会删除这些固定目录中的内容
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemCache {
+    /// Windows\Temp
+    WindowsTemp,
+    /// Windows 更新下载缓存 SoftwareDistribution\Download
+    WindowsUpdate,
+    /// apt 下载的软件包
+    AptArchives,
+    /// /Library/Caches
+    MacLibraryCaches,
+}
+
+impl SystemCache {
+    pub fn path(self) -> Option<PathBuf> {
+        let windows = || {
+            std::env::var_os("SystemRoot")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(r"C:\Windows"))
+        };
+        match self {
+            SystemCache::WindowsTemp if cfg!(windows) => Some(windows().join("Temp")),
+            SystemCache::WindowsUpdate if cfg!(windows) => {
+                Some(windows().join("SoftwareDistribution").join("Download"))
+            }
+            SystemCache::AptArchives if cfg!(target_os = "linux") => {
+                Some(PathBuf::from("/var/cache/apt/archives"))
+            }
+            SystemCache::MacLibraryCaches if cfg!(target_os = "macos") => {
+                Some(PathBuf::from("/Library/Caches"))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum HelperRequest {
+    Ping,
+    /// 统计普通权限无法读取的目录大小
+    DirSize {
+        path: String,
+    },
+    /// 读取 NTFS 卷的 MFT 大小
+    MftSize {
+        volume: String,
+    },
+    CleanSystemCache {
+        cache: SystemCache,
+    },
+    /// 用 DISM 分析 WinSxS 组件存储
+    AnalyzeComponentStore,
+    /// 用 DISM 清理组件存储
+    ComponentCleanup {
+        reset_base: bool,
+    },
+    /// 用系统自带工具清理 journald 日志、旧内核或软件包缓存
+    LinuxCleanup {
+        cleanup: LinuxCleanup,
+    },
+    /// 统计所有用户配置文件的大小
+    ProfilesReport,
+    /// 处理完当前请求后退出
+    Shutdown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HelperResponse {
+    Pong,
+    Size {
+        bytes: u64,
+    },
+    Cleaned {
+        freed_bytes: u64,
+        /// 删除失败的条目，最多 MAX_REPORTED_ERRORS 条
+        errors: Vec<String>,
+    },
+    ComponentStore {
+        report: ComponentStoreReport,
+    },
+    Profiles {
+        profiles: Vec<UserProfile>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+// 辅助进程读取令牌：指定了令牌文件时读取后删除该文件，否则从标准输入读取一行
+pub fn read_token(token_file: Option<&str>) -> Result<String, String> {
+    let token = match token_file {
+        Some(path) => {
+            let token = fs::read_to_string(path).map_err(|e| format!("无法读取令牌文件: {}", e));
+            let _ = fs::remove_file(path);
+            token?
+        }
+        None => {
+            let mut token = String::new();
+            std::io::stdin()
+                .lock()
+                .take(MAX_TOKEN_LINE)
+                .read_line(&mut token)
+                .map_err(|e| format!("无法读取令牌: {}", e))?;
+            token
+        }
+    };
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err("没有收到令牌".to_string());
+    }
+    Ok(token)
+}
+
+// 辅助进程入口：连接主程序、验证令牌后逐行处理请求，连接断开或收到 Shutdown 时退出
+pub fn serve(address: &str, token: &str) -> Result<(), String> {
+    let stream = TcpStream::connect(address).map_err(|e| format!("无法连接主程序: {}", e))?;
+    let mut writer = stream
+        .try_clone()
+        .map_err(|e| format!("无法复制连接: {}", e))?;
+    writeln!(writer, "{}", token).map_err(|e| e.to_string())?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let request: HelperRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                respond(
+                    &mut writer,
+                    &HelperResponse::Error {
+                        message: format!("无法解析请求: {}", e),
+                    },
+                )?;
+                continue;
+            }
+        };
+        let shutdown = matches!(request, HelperRequest::Shutdown);
+        respond(&mut writer, &handle(request))?;
+        if shutdown {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn respond(writer: &mut impl Write, response: &HelperResponse) -> Result<(), String> {
+    let line = serde_json::to_string(response).map_err(|e| e.to_string())?;
+    writeln!(writer, "{}", line).map_err(|e| e.to_string())
+}
+
+fn handle(request: HelperRequest) -> HelperResponse {
+    match request {
+        HelperRequest::Ping | HelperRequest::Shutdown => HelperResponse::Pong,
+        HelperRequest::DirSize { path } => {
+            let path = Path::new(&path);
+            if !path.is_dir() {
+                return HelperResponse::Error {
+                    message: format!("不是目录: {}", path.display()),
+                };
+            }
+            HelperResponse::Size {
+                bytes: dir_size(path),
+            }
+        }
+        HelperRequest::MftSize { volume } => match mft_size(&volume) {
+            Ok(bytes) => HelperResponse::Size { bytes },
+            Err(message) => HelperResponse::Error { message },
+        },
+        HelperRequest::CleanSystemCache { cache } => match cache.path() {
+            Some(dir) => {
+                let (freed_bytes, errors) = clear_dir_contents(&dir);
+                HelperResponse::Cleaned {
+                    freed_bytes,
+                    errors,
+                }
+            }
+            None => HelperResponse::Error {
+                message: "当前系统没有该缓存".to_string(),
+            },
+        },
+        HelperRequest::AnalyzeComponentStore => match windows_system::analyze_component_store() {
+            Ok(report) => HelperResponse::ComponentStore { report },
+            Err(message) => HelperResponse::Error { message },
+        },
+        HelperRequest::ComponentCleanup { reset_base } => {
+            match windows_system::start_component_cleanup(reset_base) {
+                Ok(freed_bytes) => HelperResponse::Cleaned {
+                    freed_bytes,
+                    errors: Vec::new(),
+                },
+                Err(message) => HelperResponse::Error { message },
+            }
+        }
+        HelperRequest::ProfilesReport => match profiles::profiles_report() {
+            Ok(profiles) => HelperResponse::Profiles { profiles },
+            Err(message) => HelperResponse::Error { message },
+        },
+        HelperRequest::LinuxCleanup { cleanup } => match linux_system::run_cleanup(&cleanup) {
+            Ok(freed_bytes) => HelperResponse::Cleaned {
+                freed_bytes,
+                errors: Vec::new(),
+            },
+            Err(message) => HelperResponse::Error { message },
+        },
+    }
+}
+
+fn mft_size(volume: &str) -> Result<u64, String> {
+    if !platform::supports_mft_scan() {
+        return Err(platform::unsupported("读取 MFT"));
+    }
+    let output = platform::command("fsutil")
+        .args(["fsinfo", "ntfsinfo", volume])
+        .output()
+        .map_err(|e| format!("无法运行 fsutil: {}", e))?;
+    crate::space_map::parse_mft_size(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "无法读取 MFT 信息，卷可能不是 NTFS".to_string())
+}
+
+// 删除目录中的所有内容但保留目录本身，正在使用的文件删除失败时跳过
+pub(crate) fn clear_dir_contents(dir: &Path) -> (u64, Vec<String>) {
+    let mut freed = 0;
+    let mut errors = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return (0, vec![format!("{}: {}", dir.display(), e)]),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let (size, result) = if metadata.is_dir() {
+            (dir_size(&path), fs::remove_dir_all(&path))
+        } else {
+            (metadata.len(), fs::remove_file(&path))
+        };
+        match result {
+            Ok(()) => freed += size,
+            Err(e) if errors.len() < MAX_REPORTED_ERRORS => {
+                errors.push(format!("{}: {}", path.display(), e))
+            }
+            Err(_) => {}
+        }
+    }
+    (freed, errors)
+}
+
+// 主程序一侧与辅助进程的连接
+pub struct HelperClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl HelperClient {
+    // 以管理员权限启动辅助进程并等待其连接，用户拒绝提权或超时时返回错误
+    pub fn launch() -> Result<Self, String> {
+        let helper = helper_path()?;
+        let listener =
+            TcpListener::bind("127.0.0.1:0").map_err(|e| format!("无法监听本地端口: {}", e))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| e.to_string())?
+            .to_string();
+        let token = generate_token()?;
+        // 令牌文件在辅助进程连接或等待超时后删除
+        let _token_file = launch_elevated(&helper, &address, &token)?;
+
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    // 令牌不符、超时未发送令牌或出错的连接直接丢弃，继续等待真正的辅助进程
+                    if let Ok(client) = Self::authenticate(stream, &token) {
+                        return Ok(client);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if Instant::now() >= deadline {
+                        return Err("等待管理员权限确认超时，操作已取消".to_string());
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(e) => return Err(format!("辅助进程连接失败: {}", e)),
+            }
+        }
+    }
+
+    // 在 TOKEN_TIMEOUT 内读取并核对令牌，通过后取消读取超时（清理大目录可能需要很久）
+    fn authenticate(stream: TcpStream, token: &str) -> Result<Self, String> {
+        stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(TOKEN_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        let writer = stream.try_clone().map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(stream);
+        let mut received = String::new();
+        reader
+            .by_ref()
+            .take(MAX_TOKEN_LINE)
+            .read_line(&mut received)
+            .map_err(|e| e.to_string())?;
+        if !constant_time_eq(received.trim().as_bytes(), token.as_bytes()) {
+            return Err("令牌不符".to_string());
+        }
+        reader
+            .get_ref()
+            .set_read_timeout(None)
+            .map_err(|e| e.to_string())?;
+        Ok(HelperClient { reader, writer })
+    }
+
+    pub fn request(&mut self, request: &HelperRequest) -> Result<HelperResponse, String> {
+        let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        writeln!(self.writer, "{}", line).map_err(|e| format!("辅助进程已退出: {}", e))?;
+        let mut response = String::new();
+        let read = self
+            .reader
+            .read_line(&mut response)
+            .map_err(|e| format!("辅助进程已退出: {}", e))?;
+        if read == 0 {
+            return Err("辅助进程已退出".to_string());
+        }
+        serde_json::from_str(&response).map_err(|e| format!("辅助进程回复格式错误: {}", e))
+    }
+}
+
+impl Drop for HelperClient {
+    fn drop(&mut self) {
+        let _ = self.request(&HelperRequest::Shutdown);
+    }
+}
+
+// 按需启动并复用辅助进程，连接出错时丢弃，下次请求重新启动
+#[derive(Clone, Default)]
+pub struct ElevatedHelper {
+    client: Arc<Mutex<Option<HelperClient>>>,
+}
+
+impl ElevatedHelper {
+    // 发送请求，辅助进程返回的错误也转换为 Err
+    pub fn request(&self, request: HelperRequest) -> Result<HelperResponse, String> {
+        let mut client = self.client.lock().unwrap();
+        if client.is_none() {
+            *client = Some(HelperClient::launch()?);
+        }
+        let result = client.as_mut().unwrap().request(&request);
+        match result {
+            Ok(HelperResponse::Error { message }) => Err(message),
+            Ok(response) => Ok(response),
+            Err(e) => {
+                *client = None;
+                Err(e)
+            }
+        }
+    }
+}
+
+// 辅助程序与主程序位于同一目录
+fn helper_path() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("无法确定程序位置: {}", e))?;
+    let dir = exe.parent().ok_or("无法确定程序所在目录")?;
+    let path = dir.join(format!("{}{}", HELPER_NAME, std::env::consts::EXE_SUFFIX));
+    if !path.is_file() {
+        return Err(format!("找不到管理员辅助程序: {}", path.display()));
+    }
+    Ok(path)
+}
+
+// 只有当前用户可读的令牌文件，用于无法通过标准输入传入令牌的提权方式；drop 时删除
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+struct TokenFile(PathBuf);
+
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+impl TokenFile {
+    // 写在当前用户的临时目录（Windows 的 %TEMP%、macOS 的 $TMPDIR 只有该用户可以访问）
+    fn create(token: &str) -> Result<Self, String> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let name = format!("{}-{}.token", HELPER_NAME, &generate_token()?[..16]);
+        let path = std::env::temp_dir().join(name);
+        let mut file = options
+            .open(&path)
+            .map_err(|e| format!("无法创建令牌文件: {}", e))?;
+        let token_file = TokenFile(path);
+        writeln!(file, "{}", token).map_err(|e| format!("无法写入令牌文件: {}", e))?;
+        Ok(token_file)
+    }
+}
+
+impl Drop for TokenFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(windows)]
+fn launch_elevated(helper: &Path, address: &str, token: &str) -> Result<Option<TokenFile>, String> {
+    let token_file = TokenFile::create(token)?;
+    // Start-Process -Verb RunAs 弹出 UAC 确认，用户拒绝时返回非零状态
+    let status = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            &format!(
+                "Start-Process -FilePath '{}' -ArgumentList '--connect','{}','{}','\"{}\"' -Verb RunAs -WindowStyle Hidden",
+                helper.display().to_string().replace('\'', "''"),
+                address,
+                TOKEN_FILE_FLAG,
+                token_file.0.display().to_string().replace('\'', "''"),
+            ),
+        ])
+        .status()
+        .map_err(|e| format!("无法启动辅助程序: {}", e))?;
+    if !status.success() {
+        return Err("用户取消了管理员权限确认".to_string());
+    }
+    Ok(Some(token_file))
+}
+
+#[cfg(target_os = "macos")]
+fn launch_elevated(helper: &Path, address: &str, token: &str) -> Result<Option<TokenFile>, String> {
+    let token_file = TokenFile::create(token)?;
+    let quote = |value: String| format!("'{}'", value.replace('\'', "'\\''"));
+    let command = format!(
+        "{} --connect {} {} {} > /dev/null 2>&1 &",
+        quote(helper.display().to_string()),
+        address,
+        TOKEN_FILE_FLAG,
+        quote(token_file.0.display().to_string()),
+    );
+    let script = format!(
+        "do shell script \"{}\" with administrator privileges",
+        command.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let status = Command::new("osascript")
+        .args(["-e", &script])
+        .status()
+        .map_err(|e| format!("无法启动辅助程序: {}", e))?;
+    if !status.success() {
+        return Err("用户取消了管理员权限确认".to_string());
+    }
+    Ok(Some(token_file))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn launch_elevated(helper: &Path, address: &str, token: &str) -> Result<Option<TokenFile>, String> {
+    // pkexec 把标准输入原样交给辅助进程，令牌通过管道传入
+    let mut child = Command::new("pkexec")
+        .arg(helper)
+        .args(["--connect", address])
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法通过 pkexec 启动辅助程序: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", token).map_err(|e| format!("无法向辅助程序传入令牌: {}", e))?;
+    }
+    // pkexec 在辅助进程退出前不会返回，放到后台等待
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_contents_but_keeps_directory() {
+        let dir = std::env::temp_dir().join(format!("disksight-elevation-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.tmp"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("nested").join("b.tmp"), vec![0u8; 50]).unwrap();
+
+        let (freed, errors) = clear_dir_contents(&dir);
+        assert!(errors.is_empty());
+        assert_eq!(freed, 150);
+        assert!(dir.is_dir());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn silent_and_wrong_token_connections_are_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let _silent = TcpStream::connect(address).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let started = Instant::now();
+        assert!(HelperClient::authenticate(stream, "token").is_err());
+        assert!(started.elapsed() < TOKEN_TIMEOUT + Duration::from_secs(5));
+
+        let mut wrong = TcpStream::connect(address).unwrap();
+        writeln!(wrong, "guess").unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        assert!(HelperClient::authenticate(stream, "token").is_err());
+
+        let mut helper = TcpStream::connect(address).unwrap();
+        writeln!(helper, "token").unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let client = HelperClient::authenticate(stream, "token").unwrap();
+        // 通过验证后不再有读取超时
+        assert_eq!(client.reader.get_ref().read_timeout().unwrap(), None);
+    }
+
+    #[test]
+    fn token_file_is_private_and_removed_after_reading() {
+        let file = TokenFile::create("abc").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&file.0).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let path = file.0.to_string_lossy().into_owned();
+        assert_eq!(read_token(Some(&path)).unwrap(), "abc");
+        assert!(!file.0.exists());
+    }
+}
@@ -0,0 +1,179 @@
+// 扫描结果条目的树形索引：按路径查找条目、记录每个条目最近的上级目录条目，
+// 条目增删或大小变化时沿上级链修正大小，只需 O(深度)，不必遍历全部条目。
+// 索引与条目列表按下标一一对应，增删都必须通过这里进行以保持同步
+use crate::models::FileEntry;
+use crate::utils::human_readable_size;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, Debug, Default)]
+pub struct EntryTree {
+    index: HashMap<String, usize>,
+    /// 最近的上级目录条目，上级目录不在结果中时为 None
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl EntryTree {
+    pub fn build(entries: &[FileEntry]) -> Self {
+        let index: HashMap<String, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.path(), i))
+            .collect();
+        let mut tree = EntryTree {
+            parent: vec![None; entries.len()],
+            children: vec![Vec::new(); entries.len()],
+            index,
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            let parent = tree.nearest_ancestor(&entry.path());
+            tree.parent[i] = parent;
+            if let Some(p) = parent {
+                tree.children[p].push(i);
+            }
+        }
+        tree
+    }
+
+    pub fn find(&self, path: &str) -> Option<usize> {
+        self.index.get(path).copied()
+    }
+
+    // path 的上级目录中最近的一个在结果中的条目（不含 path 本身）
+    pub fn nearest_ancestor(&self, path: &str) -> Option<usize> {
+        Path::new(path)
+            .ancestors()
+            .skip(1)
+            .find_map(|dir| self.index.get(dir.to_str()?).copied())
+    }
+
+    pub fn parent_of(&self, index: usize) -> Option<usize> {
+        self.parent[index]
+    }
+
+    // 从 start 起沿上级链把计入的大小由 old_size 改为 new_size
+    pub fn propagate(
+        &self,
+        entries: &mut [FileEntry],
+        start: Option<usize>,
+        old_size: u64,
+        new_size: u64,
+    ) {
+        let mut current = start;
+        while let Some(i) = current {
+            let entry = &mut entries[i];
+            entry.size_raw = entry
+                .size_raw
+                .saturating_add(new_size)
+                .saturating_sub(old_size);
+            entry.size_display = human_readable_size(entry.size_raw);
+            current = self.parent[i];
+        }
+    }
+
+    // 追加条目并挂到最近的上级目录条目下，不修正大小
+    pub fn insert(&mut self, entries: &mut Vec<FileEntry>, entry: FileEntry) -> usize {
+        let path = entry.path();
+        let i = entries.len();
+        entries.push(entry);
+        let parent = self.nearest_ancestor(&path);
+        if let Some(p) = parent {
+            self.children[p].push(i);
+        }
+        self.parent.push(parent);
+        self.children.push(Vec::new());
+        self.index.insert(path, i);
+        i
+    }
+
+    // 移除条目及其下所有条目，不修正大小。条目列表的顺序会改变
+    pub fn remove(&mut self, entries: &mut Vec<FileEntry>, index: usize) {
+        if let Some(p) = self.parent[index] {
+            self.children[p].retain(|&c| c != index);
+        }
+        let mut subtree = vec![index];
+        let mut next = 0;
+        while next < subtree.len() {
+            subtree.extend(self.children[subtree[next]].iter().copied());
+            next += 1;
+        }
+        // 从大到小移除，被换到空位上的最后一个条目不会是尚未移除的子树成员
+        subtree.sort_unstable_by(|a, b| b.cmp(a));
+        for i in subtree {
+            self.swap_remove(entries, i);
+        }
+    }
+
+    fn swap_remove(&mut self, entries: &mut Vec<FileEntry>, i: usize) {
+        let last = entries.len() - 1;
+        self.index.remove(&entries[i].path());
+        entries.swap_remove(i);
+        self.parent.swap_remove(i);
+        self.children.swap_remove(i);
+        if i == last {
+            return;
+        }
+        // 原来的最后一个条目现在位于 i，更新指向它的引用
+        self.index.insert(entries[i].path(), i);
+        if let Some(p) = self.parent[i] {
+            for c in self.children[p].iter_mut().filter(|c| **c == last) {
+                *c = i;
+            }
+        }
+        for &c in &self.children[i] {
+            self.parent[c] = Some(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn entry(path: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: path.to_string().into(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            severity: None,
+        }
+    }
+
+    #[test]
+    fn removing_a_subtree_keeps_links_consistent() {
+        let mut entries = vec![
+            entry("/r/a", 'd', 600),
+            entry("/r/a/b", 'd', 400),
+            entry("/r/a/b/c.bin", '-', 400),
+            entry("/r/z", 'd', 50),
+            entry("/r/z/y", 'd', 50),
+        ];
+        let mut tree = EntryTree::build(&entries);
+        assert_eq!(tree.nearest_ancestor("/r/a/b/x/deep"), tree.find("/r/a/b"));
+
+        let b = tree.find("/r/a/b").unwrap();
+        tree.propagate(&mut entries, tree.parent_of(b), 400, 0);
+        tree.remove(&mut entries, b);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(tree.find("/r/a/b/c.bin"), None);
+        assert_eq!(entries[tree.find("/r/a").unwrap()].size_raw, 200);
+
+        // 被换位的条目仍能正确找到上级
+        let y = tree.find("/r/z/y").unwrap();
+        assert_eq!(entries[y].path(), "/r/z/y");
+        tree.propagate(&mut entries, tree.parent_of(y), 0, 10);
+        assert_eq!(entries[tree.find("/r/z").unwrap()].size_raw, 60);
+
+        let added = tree.insert(&mut entries, entry("/r/z/y/new", '-', 5));
+        tree.propagate(&mut entries, tree.parent_of(added), 0, 5);
+        assert_eq!(entries[tree.find("/r/z").unwrap()].size_raw, 65);
+        assert_eq!(entries[y].size_raw, 55);
+    }
+}
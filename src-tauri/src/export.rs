@@ -0,0 +1,459 @@
+use crate::duplicates::DuplicateGroup;
+use crate::models::{Completeness, FileEntry};
+use crate::utils::{human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// 对齐的纯文本
+    Text,
+    /// Markdown 表格
+    Markdown,
+    /// 逗号分隔值
+    Csv,
+}
+
+// 将条目格式化为指定格式的文本；结果不完整时纯文本和 Markdown 末尾附上说明，CSV 只保留数据行
+pub fn format_entries(
+    entries: &[FileEntry],
+    format: ExportFormat,
+    completeness: &Completeness,
+) -> String {
+    let note = completeness.describe();
+    match format {
+        ExportFormat::Text => match note {
+            Some(note) => format!("{}\n（{}）", entries_to_text(entries), note),
+            None => entries_to_text(entries),
+        },
+        ExportFormat::Markdown => match note {
+            Some(note) => format!("{}\n> {}\n", entries_to_markdown(entries), note),
+            None => entries_to_markdown(entries),
+        },
+        ExportFormat::Csv => entries_to_csv(entries),
+    }
+}
+
+pub fn entries_to_text(entries: &[FileEntry]) -> String {
+    let width = entries
+        .iter()
+        .map(|e| e.size_display.len())
+        .max()
+        .unwrap_or(0);
+    entries
+        .iter()
+        .map(|e| format!("{:>width$}  {}", e.size_display, e.path(), width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn entries_to_markdown(entries: &[FileEntry]) -> String {
+    let mut out = String::from("| 类型 | 名称 | 大小 | 路径 |\n| --- | --- | ---: | --- |\n");
+    for e in entries {
+        out.push_str(&format!(
+            "| {} | {} | {} | `{}` |\n",
+            if e.file_type == 'd' {
+                "目录"
+            } else {
+                "文件"
+            },
+            escape_markdown(&e.name),
+            e.size_display,
+            e.path().replace('`', "'"),
+        ));
+    }
+    let total: u64 = entries
+        .iter()
+        .fold(0u64, |acc, e| acc.saturating_add(e.size_raw));
+    out.push_str(&format!(
+        "| | **合计 {} 项** | **{}** | |\n",
+        entries.len(),
+        human_readable_size(total)
+    ));
+    out
+}
+
+pub fn entries_to_csv(entries: &[FileEntry]) -> String {
+    let mut out = String::from("name,path,type,size_bytes,size_display\n");
+    for e in entries {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&e.name),
+            csv_field(&e.path()),
+            e.file_type,
+            e.size_raw,
+            csv_field(&e.size_display),
+        ));
+    }
+    out
+}
+
+// 扁平表格：每个条目一行，带深度、上级路径及每一级目录名的列，便于在 Excel 中做数据透视。
+// 目录大小已包含其子项，透视求和时应使用 self_bytes（目录中未单独列出的部分，文件即自身大小）。
+// 每行末尾的 completeness 列标明整个结果是否完整，透视时也能看到。
+// 开头带 UTF-8 BOM，Excel 打开时才能正确识别中文
+pub fn entries_to_flat_table(
+    root: &Path,
+    entries: &[FileEntry],
+    completeness: &Completeness,
+) -> String {
+    let completeness = match completeness {
+        Completeness::Complete => "complete",
+        Completeness::Partial { .. } => "partial",
+        Completeness::Estimated => "estimated",
+    };
+    let rows: Vec<(Vec<String>, &FileEntry)> = entries
+        .iter()
+        .map(|entry| {
+            let path = entry.path();
+            let components = match Path::new(&path).strip_prefix(root) {
+                Ok(relative) => relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect(),
+                Err(_) => vec![entry.name.clone()],
+            };
+            (components, entry)
+        })
+        .collect();
+    let levels = rows.iter().map(|(c, _)| c.len()).max().unwrap_or(0);
+
+    // 各目录中已单独列出的直接子项大小之和
+    let mut listed_children: HashMap<String, u64> = HashMap::new();
+    for (_, entry) in &rows {
+        if let Some(parent) = Path::new(&entry.path()).parent() {
+            let total = listed_children
+                .entry(parent.to_string_lossy().into_owned())
+                .or_default();
+            *total = total.saturating_add(entry.size_raw);
+        }
+    }
+
+    let mut out = String::from("\u{feff}");
+    for level in 1..=levels {
+        out.push_str(&format!("level_{},", level));
+    }
+    out.push_str(
+        "depth,name,type,parent_path,path,size_bytes,self_bytes,size_display,completeness\n",
+    );
+    for (components, entry) in &rows {
+        let path = entry.path();
+        for level in 0..levels {
+            out.push_str(&csv_field(components.get(level).map_or("", String::as_str)));
+            out.push(',');
+        }
+        let parent = Path::new(&path)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let self_bytes = if entry.file_type == 'd' {
+            entry
+                .size_raw
+                .saturating_sub(listed_children.get(&path).copied().unwrap_or(0))
+        } else {
+            entry.size_raw
+        };
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            components.len().saturating_sub(1),
+            csv_field(&entry.name),
+            if entry.file_type == 'd' {
+                "dir"
+            } else {
+                "file"
+            },
+            csv_field(&parent),
+            csv_field(&path),
+            entry.size_raw,
+            self_bytes,
+            csv_field(&entry.size_display),
+            completeness,
+        ));
+    }
+    out
+}
+
+// 含逗号、引号或换行的字段需要用双引号包裹
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub(crate) fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionScriptFormat {
+    /// POSIX sh 脚本
+    Shell,
+    /// Windows PowerShell 脚本
+    PowerShell,
+    /// 待删除清单，交给其他流程审批执行
+    Csv,
+}
+
+// 一条待删除记录：删除哪个文件、保留哪个副本
+struct PlannedDeletion<'a> {
+    path: &'a str,
+    keep: &'a str,
+    group: &'a DuplicateGroup,
+}
+
+// 把重复文件结果中用户选中的删除项导出为脚本或清单，供在其他机器上或经审批后执行。
+// 脚本删除前会确认保留的副本仍存在、待删除文件的哈希未变
+pub fn duplicate_deletions(
+    groups: &[DuplicateGroup],
+    delete: &[String],
+    format: DeletionScriptFormat,
+) -> Result<String, String> {
+    let plan = plan_deletions(groups, delete)?;
+    Ok(match format {
+        DeletionScriptFormat::Shell => deletions_to_shell(&plan),
+        DeletionScriptFormat::PowerShell => deletions_to_powershell(&plan),
+        DeletionScriptFormat::Csv => deletions_to_csv(&plan),
+    })
+}
+
+fn plan_deletions<'a>(
+    groups: &'a [DuplicateGroup],
+    delete: &'a [String],
+) -> Result<Vec<PlannedDeletion<'a>>, String> {
+    if let Some(unknown) = delete
+        .iter()
+        .find(|path| !groups.iter().any(|g| g.paths.contains(path)))
+    {
+        return Err(format!("{} 不在重复文件结果中", unknown));
+    }
+    let mut plan = Vec::new();
+    for group in groups {
+        let Some(keep) = group.paths.iter().find(|p| !delete.contains(p)) else {
+            return Err(format!(
+                "不能删除全部副本，至少保留一个: {}",
+                group.paths[0]
+            ));
+        };
+        for path in group.paths.iter().filter(|p| delete.contains(p)) {
+            plan.push(PlannedDeletion { path, keep, group });
+        }
+    }
+    Ok(plan)
+}
+
+fn plan_total(plan: &[PlannedDeletion]) -> String {
+    human_readable_size(sum_sizes(plan.iter().map(|d| d.group.size_raw)))
+}
+
+fn deletions_to_shell(plan: &[PlannedDeletion]) -> String {
+    let mut out = format!(
+        "#!/bin/sh
+# DiskSight 重复文件删除脚本：共 {} 个文件，{}
+# 执行前请逐条检查。每个文件删除前都会确认保留的副本存在且内容未变化。
+set -u
+
+if command -v sha256sum >/dev/null 2>&1; then
+  sha256() {{ sha256sum \"$1\" | cut -d' ' -f1; }}
+else
+  sha256() {{ shasum -a 256 \"$1\" | cut -d' ' -f1; }}
+fi
+
+delete_duplicate() {{
+  if [ ! -f \"$2\" ]; then echo \"跳过 $1：保留的副本不存在\" >&2; return; fi
+  if [ \"$(sha256 \"$1\")\" != \"$3\" ]; then echo \"跳过 $1：内容已变化\" >&2; return; fi
+  rm -f -- \"$1\" && echo \"已删除 $1\"
+}}
+
+",
+        plan.len(),
+        plan_total(plan)
+    );
+    for d in plan {
+        out.push_str(&format!(
+            "delete_duplicate {} {} {}\n",
+            shell_quote(d.path),
+            shell_quote(d.keep),
+            d.group.hash
+        ));
+    }
+    out
+}
+
+fn deletions_to_powershell(plan: &[PlannedDeletion]) -> String {
+    let mut out = format!(
+        "# DiskSight 重复文件删除脚本：共 {} 个文件，{}
+# 执行前请逐条检查。每个文件删除前都会确认保留的副本存在且内容未变化。
+
+function Remove-Duplicate($Path, $Keep, $Hash) {{
+    if (-not (Test-Path -LiteralPath $Keep -PathType Leaf)) {{
+        Write-Warning \"跳过 ${{Path}}：保留的副本不存在\"; return
+    }}
+    if ((Get-FileHash -LiteralPath $Path -Algorithm SHA256).Hash -ne $Hash) {{
+        Write-Warning \"跳过 ${{Path}}：内容已变化\"; return
+    }}
+    Remove-Item -LiteralPath $Path -Force
+    Write-Output \"已删除 $Path\"
+}}
+
+",
+        plan.len(),
+        plan_total(plan)
+    );
+    for d in plan {
+        out.push_str(&format!(
+            "Remove-Duplicate {} {} '{}'\n",
+            powershell_quote(d.path),
+            powershell_quote(d.keep),
+            d.group.hash
+        ));
+    }
+    out
+}
+
+fn deletions_to_csv(plan: &[PlannedDeletion]) -> String {
+    let mut out = String::from("action,path,keep,sha256,size_bytes\n");
+    for d in plan {
+        out.push_str(&format!(
+            "delete,{},{},{},{}\n",
+            csv_field(d.path),
+            csv_field(d.keep),
+            d.group.hash,
+            d.group.size_raw
+        ));
+    }
+    out
+}
+
+// 单引号内除单引号外都按字面处理
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn entry(name: &str, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type: '-',
+            permissions: " -w-x".to_string(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: format!("/data/{}", name).into(),
+            name: name.to_string(),
+            severity: None,
+        }
+    }
+
+    #[test]
+    fn partial_results_are_noted_in_text_exports() {
+        let entries = [entry("a.txt", 10)];
+        let skipped = crate::models::SkippedCounts {
+            permission_denied: 2,
+            known_inaccessible: 1,
+            ..Default::default()
+        };
+        let partial = skipped.completeness();
+        assert_eq!(
+            partial,
+            Completeness::Partial {
+                reason: "2 个目录无权限读取，1 个已知无法访问的目录被跳过".to_string()
+            }
+        );
+        assert_eq!(
+            skipped.interrupted("扫描超时", 3),
+            Completeness::Partial {
+                reason:
+                    "扫描超时，仅统计了 3 个条目，2 个目录无权限读取，1 个已知无法访问的目录被跳过"
+                        .to_string()
+            }
+        );
+        let md = format_entries(&entries, ExportFormat::Markdown, &partial);
+        assert!(md.ends_with("> 结果不完整：2 个目录无权限读取，1 个已知无法访问的目录被跳过\n"));
+        let text = format_entries(&entries, ExportFormat::Text, &Completeness::Estimated);
+        assert!(text.ends_with("（大小为估算值）"));
+        let csv = format_entries(&entries, ExportFormat::Csv, &partial);
+        assert_eq!(csv, entries_to_csv(&entries));
+        assert_eq!(
+            format_entries(&entries, ExportFormat::Text, &Completeness::Complete),
+            entries_to_text(&entries)
+        );
+        assert!(crate::models::SkippedCounts::default()
+            .completeness()
+            .is_complete());
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let csv = entries_to_csv(&[entry("a,b \"c\".txt", 10)]);
+        assert!(csv.contains("\"a,b \"\"c\"\".txt\""));
+    }
+
+    #[test]
+    fn flat_table_has_level_columns_and_self_bytes() {
+        let mut dir = entry("logs", 300);
+        dir.file_type = 'd';
+        let mut nested = entry("a.log", 100);
+        nested.location = "/data/logs/a.log".to_string().into();
+        let csv = entries_to_flat_table(
+            Path::new("/data"),
+            &[dir, nested, entry("b.txt", 5)],
+            &Completeness::Complete,
+        );
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("\u{feff}level_1,level_2,depth,"));
+        assert!(lines[0].ends_with(",completeness"));
+        assert!(lines[1].starts_with("logs,,0,logs,dir,/data,/data/logs,300,200,"));
+        assert!(lines[1].ends_with(",complete"));
+        assert!(lines[2].starts_with("logs,a.log,1,a.log,file,/data/logs,"));
+        assert!(lines[3].starts_with("b.txt,,0,"));
+
+        let partial = Completeness::Partial {
+            reason: "x".to_string(),
+        };
+        let csv = entries_to_flat_table(Path::new("/data"), &[entry("b.txt", 5)], &partial);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",partial"));
+    }
+
+    #[test]
+    fn markdown_has_total_row() {
+        let md = entries_to_markdown(&[entry("x|y", 1024), entry("z", 1024)]);
+        assert!(md.contains("x\\|y"));
+        assert!(md.contains("**合计 2 项** | **2.0KB**"));
+    }
+
+    fn group(paths: &[&str]) -> DuplicateGroup {
+        DuplicateGroup {
+            hash: "abc123".to_string(),
+            size_raw: 100,
+            size_display: human_readable_size(100),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            wasted_bytes: 100,
+        }
+    }
+
+    #[test]
+    fn deletion_script_keeps_one_copy() {
+        let groups = [group(&["/a/x", "/b/it's x"])];
+        let all = vec!["/a/x".to_string(), "/b/it's x".to_string()];
+        assert!(duplicate_deletions(&groups, &all, DeletionScriptFormat::Shell).is_err());
+
+        let script = duplicate_deletions(&groups, &all[1..], DeletionScriptFormat::Shell).unwrap();
+        assert!(script.contains("delete_duplicate '/b/it'\\''s x' '/a/x' abc123"));
+
+        let ps = duplicate_deletions(&groups, &all[1..], DeletionScriptFormat::PowerShell).unwrap();
+        assert!(ps.contains("Remove-Duplicate '/b/it''s x' '/a/x' 'abc123'"));
+    }
+}
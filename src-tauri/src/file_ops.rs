@@ -0,0 +1,363 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameStatus {
+    /// 已重命名
+    Renamed,
+    /// 预览模式下将会重命名
+    Planned,
+    /// 名称未匹配或替换后没有变化
+    Unchanged,
+    /// 目标已存在或与其他条目的目标重名
+    Collision,
+    /// 替换后的名称不合法
+    Invalid,
+    /// 执行重命名时出错
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RenameOutcome {
+    pub from: String,
+    pub to: String,
+    pub status: RenameStatus,
+    pub error: Option<String>,
+}
+
+// 用正则替换文件名（不含父目录），支持 $1、${name} 等捕获组引用。
+// 只要有任意一项冲突或不合法，就不会执行任何重命名，只返回计划供用户调整
+pub fn bulk_rename(
+    paths: &[String],
+    pattern: &str,
+    replacement: &str,
+    dry_run: bool,
+) -> Result<Vec<RenameOutcome>, String> {
+    let regex = Regex::new(pattern).map_err(|e| format!("正则表达式无效: {}", e))?;
+
+    let mut outcomes = Vec::with_capacity(paths.len());
+    let mut targets: HashSet<PathBuf> = HashSet::new();
+    for path in paths {
+        let source = Path::new(path);
+        let Some(name) = source.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            outcomes.push(outcome(path, path, RenameStatus::Invalid, "无法获取文件名"));
+            continue;
+        };
+        let new_name = regex.replace_all(&name, replacement).to_string();
+        let target = source.with_file_name(&new_name);
+        let target_str = target.to_string_lossy().to_string();
+
+        let status = if new_name == name {
+            RenameStatus::Unchanged
+        } else if !is_valid_name(&new_name) {
+            RenameStatus::Invalid
+        } else if !targets.insert(normalized(&target))
+            || (target.exists() && !same_entry(source, &target))
+        {
+            RenameStatus::Collision
+        } else {
+            RenameStatus::Planned
+        };
+        outcomes.push(RenameOutcome {
+            from: path.clone(),
+            to: target_str,
+            status,
+            error: None,
+        });
+    }
+
+    let blocked = outcomes
+        .iter()
+        .any(|o| matches!(o.status, RenameStatus::Collision | RenameStatus::Invalid));
+    if dry_run || blocked {
+        return Ok(outcomes);
+    }
+
+    for item in outcomes
+        .iter_mut()
+        .filter(|o| o.status == RenameStatus::Planned)
+    {
+        match fs::rename(&item.from, &item.to) {
+            Ok(_) => item.status = RenameStatus::Renamed,
+            Err(e) => {
+                item.status = RenameStatus::Failed;
+                item.error = Some(e.to_string());
+            }
+        }
+    }
+    Ok(outcomes)
+}
+
+fn outcome(from: &str, to: &str, status: RenameStatus, error: &str) -> RenameOutcome {
+    RenameOutcome {
+        from: from.to_string(),
+        to: to.to_string(),
+        status,
+        error: Some(error.to_string()),
+    }
+}
+
+// 新文件名不能为空、不能包含路径分隔符或 Windows 保留字符
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.chars().any(|c| {
+            matches!(
+                c,
+                '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' | '\0'
+            )
+        })
+}
+
+// Windows、macOS 默认不区分大小写，用小写路径检测目标重名
+fn normalized(path: &Path) -> PathBuf {
+    if cfg!(any(windows, target_os = "macos")) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// 仅大小写变化时目标“已存在”的其实是源文件本身
+fn same_entry(source: &Path, target: &Path) -> bool {
+    normalized(source) == normalized(target)
+}
+
+// 新建目录（父目录不存在时一并创建），目标已存在时报错
+pub fn create_directory(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return Err("目标已存在".to_string());
+    }
+    fs::create_dir_all(path).map_err(|e| format!("创建目录失败: {}", e))
+}
+
+// 新建空文件，不会覆盖已有文件
+pub fn create_file(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            return Err("父目录不存在".to_string());
+        }
+        _ => {}
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map(|_| ())
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => "目标已存在".to_string(),
+            _ => format!("创建文件失败: {}", e),
+        })
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TouchRequest {
+    pub path: String,
+    /// 为空表示保持不变
+    pub created: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TouchOutcome {
+    pub path: String,
+    pub error: Option<String>,
+}
+
+// 批量修改时间戳，单项失败不影响其余条目
+pub fn touch_entries(requests: &[TouchRequest]) -> Vec<TouchOutcome> {
+    requests
+        .iter()
+        .map(|req| TouchOutcome {
+            path: req.path.clone(),
+            error: touch_entry(
+                Path::new(&req.path),
+                req.created,
+                req.modified,
+                req.accessed,
+            )
+            .err(),
+        })
+        .collect()
+}
+
+// 修改文件或目录的创建、修改、访问时间，常用于从备份恢复后修正被重置的时间。
+// 创建时间只有 Windows 和 macOS 支持修改
+pub fn touch_entry(
+    path: &Path,
+    created: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+) -> Result<(), String> {
+    if !path.exists() {
+        return Err("路径不存在".to_string());
+    }
+    let mut times = fs::FileTimes::new();
+    if let Some(modified) = modified {
+        times = times.set_modified(modified);
+    }
+    if let Some(accessed) = accessed {
+        times = times.set_accessed(accessed);
+    }
+    if let Some(created) = created {
+        times = with_created(times, created)?;
+    }
+    open_for_times(path)
+        .and_then(|file| file.set_times(times))
+        .map_err(|e| format!("修改时间失败: {}", e))
+}
+
+#[cfg(windows)]
+fn with_created(times: fs::FileTimes, created: SystemTime) -> Result<fs::FileTimes, String> {
+    use std::os::windows::fs::FileTimesExt;
+    Ok(times.set_created(created))
+}
+
+#[cfg(target_os = "macos")]
+fn with_created(times: fs::FileTimes, created: SystemTime) -> Result<fs::FileTimes, String> {
+    use std::os::macos::fs::FileTimesExt;
+    Ok(times.set_created(created))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn with_created(_times: fs::FileTimes, _created: SystemTime) -> Result<fs::FileTimes, String> {
+    Err("当前系统不支持修改创建时间".to_string())
+}
+
+// 目录也需要能打开，Windows 上须带 FILE_FLAG_BACKUP_SEMANTICS
+#[cfg(windows)]
+fn open_for_times(path: &Path) -> std::io::Result<fs::File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    const FILE_WRITE_ATTRIBUTES: u32 = 0x0100;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    fs::OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+#[cfg(not(windows))]
+fn open_for_times(path: &Path) -> std::io::Result<fs::File> {
+    fs::File::open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 在独立的临时目录中创建文件，返回目录和文件路径
+    fn setup(name: &str, files: &[&str]) -> (PathBuf, Vec<String>) {
+        let dir =
+            std::env::temp_dir().join(format!("disksight-rename-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let paths = files
+            .iter()
+            .map(|f| {
+                let path = dir.join(f);
+                fs::write(&path, f).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn statuses(outcomes: &[RenameOutcome]) -> Vec<RenameStatus> {
+        outcomes.iter().map(|o| o.status).collect()
+    }
+
+    #[test]
+    fn substitutes_capture_groups() {
+        let (dir, paths) = setup("groups", &["IMG_001.jpg", "IMG_002.jpg", "notes.txt"]);
+        let outcomes =
+            bulk_rename(&paths, r"^IMG_(?P<n>\d+)\.(\w+)$", "photo-${n}.${2}", false).unwrap();
+        assert_eq!(
+            statuses(&outcomes),
+            vec![
+                RenameStatus::Renamed,
+                RenameStatus::Renamed,
+                RenameStatus::Unchanged
+            ]
+        );
+        assert_eq!(
+            names(&dir),
+            vec!["notes.txt", "photo-001.jpg", "photo-002.jpg"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 冲突的一批中其他可以重命名的条目（b3.txt）也不会被重命名
+    #[test]
+    fn targets_colliding_in_one_batch_block_everything() {
+        let (dir, paths) = setup("batch", &["a1.txt", "a2.txt", "b3.txt"]);
+        let outcomes = bulk_rename(&paths, r"\d", "", false).unwrap();
+        assert_eq!(
+            statuses(&outcomes),
+            vec![
+                RenameStatus::Planned,
+                RenameStatus::Collision,
+                RenameStatus::Planned
+            ]
+        );
+        assert_eq!(names(&dir), vec!["a1.txt", "a2.txt", "b3.txt"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn existing_target_is_a_collision() {
+        let (dir, paths) = setup("existing", &["x.txt", "y.txt"]);
+        let outcomes = bulk_rename(&paths[..1], "x", "y", false).unwrap();
+        assert_eq!(statuses(&outcomes), vec![RenameStatus::Collision]);
+        assert_eq!(fs::read_to_string(dir.join("y.txt")).unwrap(), "y.txt");
+        assert_eq!(names(&dir), vec!["x.txt", "y.txt"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_names_block_the_batch() {
+        let (dir, paths) = setup("invalid", &["keep.txt", "report.txt"]);
+        for replacement in ["/", ":", ""] {
+            let outcomes = bulk_rename(&paths[1..], r"^report\.txt$", replacement, false).unwrap();
+            assert_eq!(statuses(&outcomes), vec![RenameStatus::Invalid]);
+        }
+        let outcomes = bulk_rename(&paths, r"^(keep|report)\.txt$", "${1}?.txt", false).unwrap();
+        assert!(outcomes.iter().all(|o| o.status == RenameStatus::Invalid));
+        assert_eq!(names(&dir), vec!["keep.txt", "report.txt"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn case_only_rename() {
+        let (dir, paths) = setup("case", &["readme.md"]);
+        let outcomes = bulk_rename(&paths, "readme", "README", false).unwrap();
+        assert_eq!(statuses(&outcomes), vec![RenameStatus::Renamed]);
+        assert_eq!(names(&dir), vec!["README.md"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dry_run_only_plans() {
+        let (dir, paths) = setup("dry", &["a.log"]);
+        let outcomes = bulk_rename(&paths, r"\.log$", ".txt", true).unwrap();
+        assert_eq!(statuses(&outcomes), vec![RenameStatus::Planned]);
+        assert_eq!(names(&dir), vec!["a.log"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+// 启动画面上的初始化流程：检测磁盘、首次运行时让用户选择监控目录、建立初始索引。
+// 每一步的进度通过 setup-progress 事件发给启动画面，画面打开较晚时用 setup_status 补齐
+use crate::drives::DriveInfo;
+use crate::index::VolumeStatus;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    DetectDrives,
+    /// 仅首次运行：等待用户选择监控目录
+    ChooseRoots,
+    /// 为选择的目录建立文件名索引
+    BuildCache,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    /// 等待用户在启动画面上操作
+    Waiting,
+    Done,
+    /// 非首次运行时不需要执行
+    Skipped,
+    Failed {
+        message: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetupProgress {
+    pub step: SetupStep,
+    pub status: StepStatus,
+    /// 已完成的数量，如已建立索引的目录数
+    pub done: u64,
+    pub total: u64,
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SetupReport {
+    pub first_run: bool,
+    /// 检测到的磁盘，供选择监控目录时使用
+    pub drives: Vec<DriveInfo>,
+    /// 各步骤的最新状态，按开始顺序排列
+    pub steps: Vec<SetupProgress>,
+}
+
+// 初始化流程的当前状态
+pub struct FirstRun {
+    report: Mutex<SetupReport>,
+}
+
+impl FirstRun {
+    pub fn new(first_run: bool) -> Self {
+        FirstRun {
+            report: Mutex::new(SetupReport {
+                first_run,
+                ..SetupReport::default()
+            }),
+        }
+    }
+
+    pub fn is_first_run(&self) -> bool {
+        self.report.lock().unwrap().first_run
+    }
+
+    pub fn report(&self) -> SetupReport {
+        self.report.lock().unwrap().clone()
+    }
+
+    pub fn set_drives(&self, drives: Vec<DriveInfo>) {
+        self.report.lock().unwrap().drives = drives;
+    }
+
+    // 记录步骤状态并通知所有窗口
+    pub fn progress(&self, app: &AppHandle, progress: SetupProgress) {
+        self.record(progress.clone());
+        let _ = app.emit("setup-progress", progress);
+    }
+
+    pub fn step(&self, app: &AppHandle, step: SetupStep, status: StepStatus) {
+        self.progress(
+            app,
+            SetupProgress {
+                step,
+                status,
+                done: 0,
+                total: 0,
+                detail: None,
+            },
+        );
+    }
+
+    fn record(&self, progress: SetupProgress) {
+        let mut report = self.report.lock().unwrap();
+        match report.steps.iter_mut().find(|s| s.step == progress.step) {
+            Some(slot) => *slot = progress,
+            None => report.steps.push(progress),
+        }
+    }
+
+    // 某一步当前的状态
+    pub fn status_of(&self, step: SetupStep) -> Option<StepStatus> {
+        self.report
+            .lock()
+            .unwrap()
+            .steps
+            .iter()
+            .find(|s| s.step == step)
+            .map(|s| s.status.clone())
+    }
+}
+
+// 建立初始索引的进度：已完成的目录数与仍在等待的目录。
+// 只有已登记且不在建立中的目录才算完成，尚未登记的目录视为等待中；重复的目录只计一次
+pub fn index_progress<'a>(roots: &'a [String], volumes: &[VolumeStatus]) -> (u64, Vec<&'a str>) {
+    let mut done = 0;
+    let mut waiting = Vec::new();
+    for (i, root) in roots.iter().enumerate() {
+        if roots[..i].contains(root) {
+            continue;
+        }
+        match volumes.iter().find(|v| &v.root == root) {
+            Some(volume) if !volume.building => done += 1,
+            _ => waiting.push(root.as_str()),
+        }
+    }
+    (done, waiting)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_status_per_step() {
+        let first_run = FirstRun::new(true);
+        let progress = |step, status| SetupProgress {
+            step,
+            status,
+            done: 0,
+            total: 0,
+            detail: None,
+        };
+        first_run.record(progress(SetupStep::DetectDrives, StepStatus::Running));
+        first_run.record(progress(SetupStep::ChooseRoots, StepStatus::Waiting));
+        first_run.record(progress(SetupStep::DetectDrives, StepStatus::Done));
+
+        let report = first_run.report();
+        assert!(report.first_run);
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[0].status, StepStatus::Done);
+        assert_eq!(
+            first_run.status_of(SetupStep::ChooseRoots),
+            Some(StepStatus::Waiting)
+        );
+        assert_eq!(first_run.status_of(SetupStep::BuildCache), None);
+    }
+
+    #[test]
+    fn counts_only_registered_roots_that_finished() {
+        let volume = |root: &str, building| VolumeStatus {
+            root: root.to_string(),
+            entry_count: 0,
+            built_at: None,
+            building,
+            error: None,
+        };
+        let roots: Vec<String> = ["/a", "/b", "/a", "/c"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+
+        // 目录尚未登记时不算完成
+        assert_eq!(index_progress(&roots, &[]), (0, vec!["/a", "/b", "/c"]));
+        let volumes = [volume("/a", false), volume("/b", true), volume("/d", false)];
+        assert_eq!(index_progress(&roots, &volumes), (1, vec!["/b", "/c"]));
+        let volumes = [
+            volume("/a", false),
+            volume("/b", false),
+            volume("/c", false),
+        ];
+        assert_eq!(index_progress(&roots, &volumes), (3, vec![]));
+    }
+}
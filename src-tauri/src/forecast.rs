@@ -0,0 +1,157 @@
+use crate::drives::volume_space;
+use crate::history::{SizeHistory, SizePoint};
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+// 预计在该天数内写满 90% 时发出提醒
+const ALERT_HORIZON_DAYS: f64 = 30.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Forecast {
+    pub path: String,
+    /// 参与拟合的历史记录数
+    pub samples: usize,
+    /// 拟合得到的每天增长量，负数表示在缩小
+    pub growth_per_day: i64,
+    pub growth_per_day_display: String,
+    pub volume_total: u64,
+    pub volume_used: u64,
+    /// 卷当前已用百分比
+    pub used_percent: f64,
+    /// 预计达到 90% 的时间（Unix 秒），已超过或不再增长时为 None
+    pub full_90_at: Option<u64>,
+    /// 预计写满的时间（Unix 秒）
+    pub full_100_at: Option<u64>,
+    /// 需要提醒用户时的说明
+    pub alert: Option<String>,
+}
+
+// 根据目录的大小历史和所在卷当前的容量做预测
+pub fn forecast_path(path: &str, history: &SizeHistory) -> Result<Forecast, String> {
+    let points = history.get(path);
+    let (total, available) =
+        volume_space(Path::new(path)).ok_or_else(|| "无法获取所在卷的容量".to_string())?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    forecast(path, &points, total, available, now)
+}
+
+// 对历史记录做最小二乘线性拟合，假设卷的增长都来自该目录，
+// 推算卷用量达到 90% 与 100% 的时间
+pub fn forecast(
+    path: &str,
+    points: &[SizePoint],
+    volume_total: u64,
+    volume_available: u64,
+    now: u64,
+) -> Result<Forecast, String> {
+    let slope = growth_rate(points).ok_or("历史记录不足，至少需要两次不同时间的扫描")?;
+    let volume_used = volume_total.saturating_sub(volume_available);
+    let used_percent = if volume_total == 0 {
+        0.0
+    } else {
+        volume_used as f64 / volume_total as f64 * 100.0
+    };
+    let eta = |ratio: f64| -> Option<u64> {
+        let remaining = volume_total as f64 * ratio - volume_used as f64;
+        if remaining <= 0.0 || slope <= 0.0 {
+            return None;
+        }
+        Some(now.saturating_add((remaining / slope) as u64))
+    };
+    let full_90_at = eta(0.9);
+    let full_100_at = eta(1.0);
+
+    let growth_per_day = (slope * DAY) as i64;
+    let alert = match full_90_at {
+        _ if used_percent >= 90.0 && slope > 0.0 => Some(format!(
+            "{} 所在卷已使用 {:.1}%，且仍在增长",
+            path, used_percent
+        )),
+        Some(at) if ((at - now) as f64) < ALERT_HORIZON_DAYS * DAY => Some(format!(
+            "按当前增长速度，{} 所在卷约 {} 天后将达到 90%",
+            path,
+            ((at - now) as f64 / DAY).ceil() as u64
+        )),
+        _ => None,
+    };
+    Ok(Forecast {
+        path: path.to_string(),
+        samples: points.len(),
+        growth_per_day,
+        growth_per_day_display: signed_size(growth_per_day),
+        volume_total,
+        volume_used,
+        used_percent,
+        full_90_at,
+        full_100_at,
+        alert,
+    })
+}
+
+// 每秒增长的字节数
+fn growth_rate(points: &[SizePoint]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    // 以第一条记录为时间原点，避免时间戳平方后损失精度
+    let origin = points[0].timestamp as f64;
+    let n = points.len() as f64;
+    let mean_t = points
+        .iter()
+        .map(|p| p.timestamp as f64 - origin)
+        .sum::<f64>()
+        / n;
+    let mean_s = points.iter().map(|p| p.size_raw as f64).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for p in points {
+        let dt = p.timestamp as f64 - origin - mean_t;
+        cov += dt * (p.size_raw as f64 - mean_s);
+        var += dt * dt;
+    }
+    if var == 0.0 {
+        return None;
+    }
+    Some(cov / var)
+}
+
+fn signed_size(value: i64) -> String {
+    if value < 0 {
+        format!("-{}", human_readable_size(value.unsigned_abs()))
+    } else {
+        human_readable_size(value.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(day: u64, size_raw: u64) -> SizePoint {
+        SizePoint {
+            timestamp: day * DAY as u64,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+        }
+    }
+
+    #[test]
+    fn predicts_linear_growth() {
+        let gb = 1024 * 1024 * 1024;
+        let points = [point(0, 10 * gb), point(1, 11 * gb), point(2, 12 * gb)];
+        let now = 2 * DAY as u64;
+        // 100GB 的卷已用 80GB，每天增长 1GB
+        let f = forecast("/var/log", &points, 100 * gb, 20 * gb, now).unwrap();
+        assert_eq!(f.growth_per_day, gb as i64);
+        assert_eq!(f.full_90_at, Some(now + 10 * DAY as u64));
+        assert_eq!(f.full_100_at, Some(now + 20 * DAY as u64));
+        assert!(f.alert.is_some());
+
+        assert!(forecast("/var/log", &points[..1], 100 * gb, 20 * gb, now).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+// 找出目录树中的 git 仓库并统计每个仓库的空间构成：.git 目录、其中的 LFS 对象和工作区。
+// 子模块和嵌套仓库单独列出，其大小不计入外层仓库的工作区。
+// 还可以在仓库历史中找出最大的文件内容，并给出用 git filter-repo 清理的参考数据
+use crate::platform;
+use crate::utils::{dir_size, human_readable_size, sum_sizes};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+pub const GIT_DIR: &str = ".git";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GitRepo {
+    pub path: String,
+    /// .git 目录的大小，含 LFS 对象
+    pub git_dir_bytes: u64,
+    pub git_dir_display: String,
+    /// .git/lfs/objects 的大小
+    pub lfs_bytes: u64,
+    /// 工作区中除 .git 与嵌套仓库外的内容
+    pub working_tree_bytes: u64,
+    pub working_tree_display: String,
+    /// .git 是指向其他位置的文件（子模块、git worktree），仓库数据不在此处
+    pub linked: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GitRepoReport {
+    pub root: String,
+    pub repo_count: usize,
+    /// 按 .git 大小降序
+    pub repos: Vec<GitRepo>,
+    pub git_dir_bytes: u64,
+    pub lfs_bytes: u64,
+    pub working_tree_bytes: u64,
+    pub total_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GcOutcome {
+    pub path: String,
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub error: Option<String>,
+}
+
+pub fn find_git_repos(root: &Path) -> GitRepoReport {
+    let mut repos = Vec::new();
+    walk(root, &mut repos);
+    repos.sort_by_key(|r| std::cmp::Reverse(r.git_dir_bytes));
+    let git_dir_bytes = sum_sizes(repos.iter().map(|r| r.git_dir_bytes));
+    let working_tree_bytes = sum_sizes(repos.iter().map(|r| r.working_tree_bytes));
+    GitRepoReport {
+        root: root.to_string_lossy().into_owned(),
+        repo_count: repos.len(),
+        lfs_bytes: sum_sizes(repos.iter().map(|r| r.lfs_bytes)),
+        total_display: human_readable_size(git_dir_bytes.saturating_add(working_tree_bytes)),
+        git_dir_bytes,
+        working_tree_bytes,
+        repos,
+    }
+}
+
+// 返回 dir 下不属于任何嵌套仓库的内容大小，找到的仓库追加到 repos
+fn walk(dir: &Path, repos: &mut Vec<GitRepo>) -> u64 {
+    let git = dir.join(GIT_DIR);
+    let git_metadata = fs::symlink_metadata(&git).ok();
+    let entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| *p != git)
+            .collect(),
+        Err(e) => {
+            eprintln!("无法读取目录 {}: {}", dir.display(), e);
+            return 0;
+        }
+    };
+
+    let children: Vec<(u64, Vec<GitRepo>)> = entries
+        .par_iter()
+        .filter_map(|path| {
+            // 不跟随符号链接
+            let metadata = fs::symlink_metadata(path).ok()?;
+            if metadata.is_dir() {
+                let mut nested = Vec::new();
+                let size = walk(path, &mut nested);
+                Some((size, nested))
+            } else {
+                Some((metadata.len(), Vec::new()))
+            }
+        })
+        .collect();
+    let mut size = 0u64;
+    for (child_size, nested) in children {
+        size = size.saturating_add(child_size);
+        repos.extend(nested);
+    }
+
+    let Some(git_metadata) = git_metadata else {
+        return size;
+    };
+    let (git_dir_bytes, lfs_bytes) = if git_metadata.is_dir() {
+        (dir_size(&git), dir_size(&git.join("lfs").join("objects")))
+    } else {
+        (0, 0)
+    };
+    repos.push(GitRepo {
+        path: dir.to_string_lossy().into_owned(),
+        git_dir_bytes,
+        git_dir_display: human_readable_size(git_dir_bytes),
+        lfs_bytes,
+        working_tree_bytes: size,
+        working_tree_display: human_readable_size(size),
+        linked: !git_metadata.is_dir(),
+    });
+    // 仓库整体属于它自己，不再计入外层
+    0
+}
+
+// 对仓库执行 git gc --aggressive，返回前后 .git 目录的大小
+pub fn gc_aggressive(repo: &Path) -> GcOutcome {
+    let git = repo.join(GIT_DIR);
+    let before_bytes = dir_size(&git);
+    let error = if !git.is_dir() {
+        Some("不是 git 仓库或仓库数据不在此目录".to_string())
+    } else {
+        match platform::command("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["gc", "--aggressive", "--quiet"])
+            .output()
+        {
+            Ok(output) if output.status.success() => None,
+            Ok(output) => Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Err(e) => Some(format!("无法运行 git: {}", e)),
+        }
+    };
+    GcOutcome {
+        path: repo.to_string_lossy().into_owned(),
+        before_bytes,
+        after_bytes: dir_size(&git),
+        error,
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LargeBlob {
+    pub hash: String,
+    /// 该内容在历史中出现时的路径，同一内容出现在多个路径时取第一个
+    pub path: String,
+    pub size_bytes: u64,
+    pub size_display: String,
+    /// 打包压缩后在 .git 中实际占用的空间
+    pub disk_bytes: u64,
+    /// 当前 HEAD 中仍包含该内容；为 false 时只存在于历史中，删除文件并不能缩小仓库
+    pub in_head: bool,
+}
+
+// 供 git filter-repo 使用的清理参考
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FilterRepoGuidance {
+    /// 只存在于历史中的大文件路径，可用 --path <路径> --invert-paths 从历史中移除
+    pub paths_to_remove: Vec<String>,
+    /// 可用于 --strip-blobs-bigger-than 的阈值（字节），恰好清除所有列出的大文件
+    pub strip_threshold_bytes: Option<u64>,
+    /// 按上述数据生成的示例命令，执行前应先备份仓库
+    pub example_commands: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LargeBlobReport {
+    pub repo: String,
+    /// 按大小降序
+    pub blobs: Vec<LargeBlob>,
+    pub total_disk_bytes: u64,
+    pub guidance: FilterRepoGuidance,
+}
+
+// 在仓库的全部历史中找出最大的 limit 个文件内容（blob）
+pub fn large_blobs(repo: &Path, limit: usize) -> Result<LargeBlobReport, String> {
+    if !repo.join(GIT_DIR).exists() {
+        return Err(format!("不是 git 仓库: {}", repo.display()));
+    }
+    let git = |args: &[&str]| {
+        let mut command = platform::command("git");
+        command.arg("-C").arg(repo).args(args);
+        command
+    };
+
+    // rev-list 列出所有可达对象及其路径，交给 cat-file 批量查询类型和大小
+    let mut rev_list = git(&["rev-list", "--objects", "--all"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法运行 git: {}", e))?;
+    let objects = rev_list
+        .stdout
+        .take()
+        .ok_or_else(|| "无法读取 git rev-list 输出".to_string())?;
+    let output = git(&[
+        "cat-file",
+        "--batch-check=%(objecttype) %(objectname) %(objectsize) %(objectsize:disk) %(rest)",
+    ])
+    .stdin(Stdio::from(objects))
+    .output()
+    .map_err(|e| format!("无法运行 git: {}", e))?;
+    let _ = rev_list.wait();
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let mut blobs: Vec<LargeBlob> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_batch_check)
+        .collect();
+    blobs.sort_by_key(|b| std::cmp::Reverse(b.size_bytes));
+    blobs.dedup_by(|a, b| a.hash == b.hash);
+    blobs.truncate(limit);
+
+    // HEAD 中仍存在的内容
+    let head = git(&["ls-tree", "-r", "HEAD"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+        .unwrap_or_default();
+    for blob in &mut blobs {
+        blob.in_head = head.contains(&blob.hash);
+    }
+
+    Ok(LargeBlobReport {
+        repo: repo.to_string_lossy().into_owned(),
+        total_disk_bytes: sum_sizes(blobs.iter().map(|b| b.disk_bytes)),
+        guidance: guidance(&blobs),
+        blobs,
+    })
+}
+
+// 解析 cat-file --batch-check 的一行，只保留 blob
+fn parse_batch_check(line: &str) -> Option<LargeBlob> {
+    let mut parts = line.splitn(5, ' ');
+    if parts.next()? != "blob" {
+        return None;
+    }
+    let hash = parts.next()?.to_string();
+    let size_bytes = parts.next()?.parse().ok()?;
+    let disk_bytes = parts.next()?.parse().ok()?;
+    Some(LargeBlob {
+        hash,
+        path: parts.next().unwrap_or_default().to_string(),
+        size_bytes,
+        size_display: human_readable_size(size_bytes),
+        disk_bytes,
+        in_head: false,
+    })
+}
+
+fn guidance(blobs: &[LargeBlob]) -> FilterRepoGuidance {
+    let mut paths_to_remove: Vec<String> = blobs
+        .iter()
+        .filter(|b| !b.in_head && !b.path.is_empty())
+        .map(|b| b.path.clone())
+        .collect();
+    paths_to_remove.sort();
+    paths_to_remove.dedup();
+    // 阈值取列出的大文件中最小的一个，HEAD 中仍在使用的文件也会被清除，需要改用 LFS 管理
+    let strip_threshold_bytes = blobs.iter().map(|b| b.size_bytes).min();
+
+    let mut example_commands = Vec::new();
+    if !paths_to_remove.is_empty() {
+        let args: Vec<String> = paths_to_remove
+            .iter()
+            .map(|p| format!("--path '{}'", p.replace('\'', "'\\''")))
+            .collect();
+        example_commands.push(format!("git filter-repo --invert-paths {}", args.join(" ")));
+    }
+    if let Some(threshold) = strip_threshold_bytes {
+        example_commands.push(format!(
+            "git filter-repo --strip-blobs-bigger-than {}",
+            threshold.saturating_sub(1)
+        ));
+    }
+    FilterRepoGuidance {
+        paths_to_remove,
+        strip_threshold_bytes,
+        example_commands,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_repos_are_reported_separately() {
+        let root = std::env::temp_dir().join(format!("disksight-git-{}", std::process::id()));
+        let outer = root.join("outer");
+        let inner = outer.join("vendor").join("inner");
+        fs::create_dir_all(outer.join(".git").join("lfs").join("objects")).unwrap();
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(outer.join(".git/lfs/objects/blob"), b"1234").unwrap();
+        fs::write(outer.join("src.rs"), b"12").unwrap();
+        fs::write(inner.join(".git"), b"gitdir: ../../.git/modules/inner").unwrap();
+        fs::write(inner.join("lib.rs"), b"123").unwrap();
+
+        let report = find_git_repos(&root);
+        assert_eq!(report.repo_count, 2);
+        let outer_repo = report.repos.iter().find(|r| !r.linked).unwrap();
+        assert_eq!(outer_repo.working_tree_bytes, 2);
+        assert_eq!(outer_repo.lfs_bytes, 4);
+        let inner_repo = report.repos.iter().find(|r| r.linked).unwrap();
+        assert_eq!(inner_repo.working_tree_bytes, 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn guidance_only_removes_history_only_paths() {
+        let mut big = parse_batch_check("blob aa 5000 4000 assets/video.mp4").unwrap();
+        let current = LargeBlob {
+            in_head: true,
+            ..parse_batch_check("blob bb 3000 2900 data/model.bin").unwrap()
+        };
+        assert!(parse_batch_check("tree cc 100 90 src").is_none());
+        big.in_head = false;
+
+        let guidance = guidance(&[big, current]);
+        assert_eq!(guidance.paths_to_remove, vec!["assets/video.mp4"]);
+        assert_eq!(guidance.strip_threshold_bytes, Some(3000));
+        assert_eq!(guidance.example_commands.len(), 2);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::models::FileEntry;
+use crate::owners::{owner_display, owner_uid, user_names};
+use crate::utils::{canonical_string, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKey {
+    /// 按扩展名，目录单独归为一组
+    Extension,
+    /// 按文件属主
+    Owner,
+    /// 按创建时间距今的区间
+    Age,
+    /// 按相对扫描根目录的第一级目录
+    TopLevelDir,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroupAggregate {
+    pub key: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub count: u64,
+}
+
+const DAY: u64 = 24 * 60 * 60;
+// 时间区间上限（天）及名称
+const AGE_BUCKETS: [(u64, &str); 4] = [
+    (7, "7 天内"),
+    (30, "7-30 天"),
+    (90, "30-90 天"),
+    (365, "90-365 天"),
+];
+
+// 对一组扫描结果按指定维度聚合，按大小降序
+pub fn group_entries(root: &Path, entries: &[FileEntry], key: GroupKey) -> Vec<GroupAggregate> {
+    let names = if key == GroupKey::Owner {
+        user_names()
+    } else {
+        HashMap::new()
+    };
+    let canonical_root = canonical_string(root).map(PathBuf::from);
+    let now = SystemTime::now();
+
+    let mut groups: HashMap<String, (u64, u64)> = HashMap::new();
+    for entry in entries {
+        let group = match key {
+            GroupKey::Extension => extension_key(entry),
+            GroupKey::Owner => {
+                let uid = std::fs::symlink_metadata(entry.path())
+                    .ok()
+                    .and_then(|m| owner_uid(&m));
+                owner_display(uid, &names)
+            }
+            GroupKey::Age => age_key(now, entry.created_time),
+            GroupKey::TopLevelDir => top_level_key(root, canonical_root.as_deref(), entry),
+        };
+        let slot = groups.entry(group).or_default();
+        slot.0 += entry.size_raw;
+        slot.1 += 1;
+    }
+
+    let mut result: Vec<GroupAggregate> = groups
+        .into_iter()
+        .map(|(key, (size_raw, count))| GroupAggregate {
+            key,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            count,
+        })
+        .collect();
+    result.sort_by_key(|g| std::cmp::Reverse(g.size_raw));
+    result
+}
+
+fn extension_key(entry: &FileEntry) -> String {
+    if entry.file_type == 'd' {
+        return "<目录>".to_string();
+    }
+    match Path::new(&entry.name).extension() {
+        Some(ext) => ext.to_string_lossy().to_lowercase(),
+        None => "<无扩展名>".to_string(),
+    }
+}
+
+fn age_key(now: SystemTime, time: SystemTime) -> String {
+    let age = now.duration_since(time).unwrap_or(Duration::ZERO).as_secs();
+    AGE_BUCKETS
+        .iter()
+        .find(|(days, _)| age < days * DAY)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| "1 年以上".to_string())
+}
+
+fn top_level_key(root: &Path, canonical_root: Option<&Path>, entry: &FileEntry) -> String {
+    let path = entry.path();
+    let path = Path::new(&path);
+    let relative = path
+        .strip_prefix(root)
+        .ok()
+        .or_else(|| canonical_root.and_then(|r| path.strip_prefix(r).ok()));
+    relative
+        .and_then(|r| r.components().next())
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .unwrap_or_else(|| entry.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, file_type: char, size_raw: u64, age_days: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::now() - Duration::from_secs(age_days * DAY + 60),
+            modified_time: None,
+            location: path.to_string().into(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            severity: None,
+        }
+    }
+
+    fn entries() -> Vec<FileEntry> {
+        vec![
+            entry("/disksight-test/photos/a.JPG", 'f', 300, 1),
+            entry("/disksight-test/photos/b.jpg", 'f', 200, 20),
+            entry("/disksight-test/photos/raw", 'd', 1000, 400),
+            entry("/disksight-test/docs/notes.txt", 'f', 50, 60),
+            entry("/disksight-test/Makefile", 'f', 5, 200),
+        ]
+    }
+
+    fn summary(groups: &[GroupAggregate]) -> Vec<(&str, u64, u64)> {
+        groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.size_raw, g.count))
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_extension_ignoring_case() {
+        let groups = group_entries(
+            Path::new("/disksight-test"),
+            &entries(),
+            GroupKey::Extension,
+        );
+        assert_eq!(
+            summary(&groups),
+            vec![
+                ("<目录>", 1000, 1),
+                ("jpg", 500, 2),
+                ("txt", 50, 1),
+                ("<无扩展名>", 5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_by_age_bucket() {
+        let groups = group_entries(Path::new("/disksight-test"), &entries(), GroupKey::Age);
+        assert_eq!(
+            summary(&groups),
+            vec![
+                ("1 年以上", 1000, 1),
+                ("7 天内", 300, 1),
+                ("7-30 天", 200, 1),
+                ("30-90 天", 50, 1),
+                ("90-365 天", 5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_by_top_level_directory() {
+        let groups = group_entries(
+            Path::new("/disksight-test"),
+            &entries(),
+            GroupKey::TopLevelDir,
+        );
+        assert_eq!(
+            summary(&groups),
+            vec![("photos", 1500, 3), ("docs", 50, 1), ("Makefile", 5, 1)]
+        );
+    }
+
+    #[test]
+    fn unreadable_owners_fall_into_one_group() {
+        let groups = group_entries(Path::new("/disksight-test"), &entries(), GroupKey::Owner);
+        assert_eq!(summary(&groups), vec![("unknown", 1555, 5)]);
+    }
+}
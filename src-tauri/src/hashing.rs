@@ -0,0 +1,218 @@
+use crate::drives::{disk_kind, mount_of, physical_device, DiskKind};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+// 机械硬盘上同时只读一个文件，并用大缓冲区顺序读取，避免磁头来回寻道
+const HDD_READERS: usize = 1;
+const HDD_BUFFER: usize = 4 * 1024 * 1024;
+const SSD_MAX_READERS: usize = 8;
+const SSD_BUFFER: usize = 1024 * 1024;
+// 无法判断磁盘类型时保守处理
+const UNKNOWN_READERS: usize = 2;
+
+#[derive(Clone, Debug)]
+pub struct HashResult {
+    pub path: PathBuf,
+    /// 十六进制 SHA-256，读取失败时为错误信息
+    pub hash: Result<String, String>,
+}
+
+// 按物理磁盘分组并行计算文件哈希：不同磁盘之间互不影响，同一磁盘按类型限制并发。
+// limit 为 Some 时只读取文件开头的若干字节，用于快速预筛；
+// on_progress 收到已完成数量和总数，完成比例每增加 1% 才调用一次
+pub fn hash_files(
+    paths: Vec<PathBuf>,
+    limit: Option<u64>,
+    on_progress: &(dyn Fn(usize, usize) + Sync),
+) -> Vec<HashResult> {
+    process_files(paths, on_progress, &|path, buffer| {
+        hash_file(path, limit, buffer)
+    })
+    .into_iter()
+    .map(|(path, hash)| HashResult { path, hash })
+    .collect()
+}
+
+// 按磁盘调度对每个文件执行 work，work 收到与磁盘类型匹配的读缓冲区
+pub fn process_files<T: Send>(
+    paths: Vec<PathBuf>,
+    on_progress: &(dyn Fn(usize, usize) + Sync),
+    work: &(dyn Fn(&Path, &mut [u8]) -> T + Sync),
+) -> Vec<(PathBuf, T)> {
+    let total = paths.len();
+    let progress = Progress::new(total, on_progress);
+    let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut kinds: HashMap<String, DiskKind> = HashMap::new();
+    // 同一卷只查询一次所在磁盘
+    let mut volumes: HashMap<String, String> = HashMap::new();
+    for path in paths {
+        let disk = volumes
+            .entry(volume_key(&path))
+            .or_insert_with(|| disk_of(&path))
+            .clone();
+        kinds
+            .entry(disk.clone())
+            .or_insert_with(|| disk_kind(&disk));
+        groups.entry(disk).or_default().push(path);
+    }
+
+    let mut results = Vec::with_capacity(total);
+    thread::scope(|scope| {
+        let handles: Vec<_> = groups
+            .into_iter()
+            .map(|(disk, mut files)| {
+                let kind = kinds[&disk];
+                // 按路径排序，同一目录下的文件在磁盘上通常相邻
+                files.sort();
+                let progress = &progress;
+                scope.spawn(move || process_group(files, kind, progress, work))
+            })
+            .collect();
+        for handle in handles {
+            results.extend(handle.join().unwrap_or_default());
+        }
+    });
+    results
+}
+
+// 汇总各磁盘线程的完成数量；文件很多时逐个上报会淹没前端，
+// 与扫描进度一样只在百分比增加时调用 on_progress
+struct Progress<'a> {
+    done: AtomicUsize,
+    total: usize,
+    percent: AtomicUsize,
+    on_progress: &'a (dyn Fn(usize, usize) + Sync),
+}
+
+impl<'a> Progress<'a> {
+    fn new(total: usize, on_progress: &'a (dyn Fn(usize, usize) + Sync)) -> Self {
+        Progress {
+            done: AtomicUsize::new(0),
+            total,
+            percent: AtomicUsize::new(0),
+            on_progress,
+        }
+    }
+
+    fn advance(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let percent = done * 100 / self.total.max(1);
+        if self.percent.fetch_max(percent, Ordering::Relaxed) < percent {
+            (self.on_progress)(done, self.total);
+        }
+    }
+}
+
+fn process_group<T: Send>(
+    files: Vec<PathBuf>,
+    kind: DiskKind,
+    progress: &Progress,
+    work: &(dyn Fn(&Path, &mut [u8]) -> T + Sync),
+) -> Vec<(PathBuf, T)> {
+    let (readers, buffer_size) = match kind {
+        DiskKind::Hdd => (HDD_READERS, HDD_BUFFER),
+        DiskKind::Ssd => (
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4)
+                .min(SSD_MAX_READERS),
+            SSD_BUFFER,
+        ),
+        DiskKind::Unknown => (UNKNOWN_READERS, SSD_BUFFER),
+    };
+    let next = AtomicUsize::new(0);
+    let files = &files;
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..readers.min(files.len()))
+            .map(|_| {
+                let next = &next;
+                scope.spawn(move || {
+                    let mut buffer = vec![0u8; buffer_size];
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = files.get(i) else {
+                            break;
+                        };
+                        results.push((path.clone(), work(path, &mut buffer)));
+                        progress.advance();
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+// 计算文件（或其前 limit 字节）的 SHA-256
+pub fn hash_file(path: &Path, limit: Option<u64>, buffer: &mut [u8]) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| format!("无法打开文件: {}", e))?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(limit) => Box::new(file.take(limit)),
+        None => Box::new(file),
+    };
+    let mut hasher = Sha256::new();
+    loop {
+        let n = reader
+            .read(buffer)
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// 文件所在的物理磁盘，无法确定时以挂载点代替
+fn disk_of(path: &Path) -> String {
+    match mount_of(path) {
+        Some(mount) => physical_device(&mount.device).unwrap_or(mount.mount_point),
+        None => String::new(),
+    }
+}
+
+#[cfg(unix)]
+fn volume_key(path: &Path) -> String {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path)
+        .map(|m| m.dev().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(not(unix))]
+fn volume_key(path: &Path) -> String {
+    path.components()
+        .next()
+        .map(|c| c.as_os_str().to_string_lossy().to_uppercase())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn progress_reports_once_per_percent() {
+        let calls = Mutex::new(Vec::new());
+        let on_progress = |done: usize, total: usize| calls.lock().unwrap().push((done, total));
+        let progress = Progress::new(1000, &on_progress);
+        for _ in 0..1000 {
+            progress.advance();
+        }
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls.len(), 100);
+        assert_eq!(calls.first(), Some(&(10, 1000)));
+        assert_eq!(calls.last(), Some(&(1000, 1000)));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::utils::{canonical_string, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "size_history.json";
+// 每个目录最多保留的记录数，超出后丢弃最早的
+const MAX_POINTS: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SizePoint {
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    pub size_raw: u64,
+    pub size_display: String,
+}
+
+// 被监控目录每次扫描后的总大小，用于绘制增长趋势
+pub struct SizeHistory {
+    path: PathBuf,
+    data: Mutex<Snapshot>,
+    /// 已写入文件的最新版本号；写文件时持有，不阻塞读取与新的记录
+    saved: Mutex<u64>,
+}
+
+struct Snapshot {
+    points: BTreeMap<String, Vec<SizePoint>>,
+    /// 每次修改加一，用于丢弃过时的写入
+    version: u64,
+}
+
+impl SizeHistory {
+    // 从数据目录加载，文件不存在或损坏时从空记录开始
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(HISTORY_FILE);
+        let data = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("大小历史解析失败，重新开始记录: {}", e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        SizeHistory {
+            path,
+            data: Mutex::new(Snapshot {
+                points: data,
+                version: 0,
+            }),
+            saved: Mutex::new(0),
+        }
+    }
+
+    // 追加一条记录；保存失败只打印错误，不影响扫描结果。
+    // 只在锁内修改并复制记录，序列化与写文件在锁外进行
+    pub fn record(&self, root: &str, size: u64) {
+        let (snapshot, version) = {
+            let mut data = self.data.lock().unwrap();
+            append(&mut data.points, root, size);
+            data.version += 1;
+            (data.points.clone(), data.version)
+        };
+        let mut saved = self.saved.lock().unwrap();
+        // 并发记录时较新的快照可能已经写入
+        if *saved >= version {
+            return;
+        }
+        match self.save(&snapshot) {
+            Ok(()) => *saved = version,
+            Err(e) => eprintln!("保存大小历史失败: {}", e),
+        }
+    }
+
+    // 按时间先后返回目录的历史记录
+    pub fn get(&self, root: &str) -> Vec<SizePoint> {
+        self.data
+            .lock()
+            .unwrap()
+            .points
+            .get(&history_key(root))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn save(&self, data: &BTreeMap<String, Vec<SizePoint>>) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string(data).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+// 追加一条记录，超过 MAX_POINTS 时丢弃最早的
+fn append(data: &mut BTreeMap<String, Vec<SizePoint>>, root: &str, size: u64) {
+    let points = data.entry(history_key(root)).or_default();
+    points.push(SizePoint {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        size_raw: size,
+        size_display: human_readable_size(size),
+    });
+    if points.len() > MAX_POINTS {
+        points.drain(..points.len() - MAX_POINTS);
+    }
+}
+
+// 同一目录的不同写法归为同一条历史
+pub fn history_key(root: &str) -> String {
+    canonical_string(Path::new(root)).unwrap_or_else(|| root.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("disksight-history-{}", std::process::id()));
+        let root = dir.to_string_lossy().into_owned();
+        fs::create_dir_all(&dir).unwrap();
+
+        let history = SizeHistory::load(&dir);
+        history.record(&root, 100);
+        history.record(&root, 250);
+
+        let reloaded = SizeHistory::load(&dir);
+        let sizes: Vec<u64> = reloaded.get(&root).iter().map(|p| p.size_raw).collect();
+        assert_eq!(sizes, vec![100, 250]);
+        assert_eq!(
+            reloaded.get(&root)[1].size_display,
+            human_readable_size(250)
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_only_the_newest_points() {
+        let mut data = BTreeMap::new();
+        for size in 0..(MAX_POINTS as u64 + 5) {
+            append(&mut data, "/data", size);
+        }
+        let points = &data[&history_key("/data")];
+        assert_eq!(points.len(), MAX_POINTS);
+        assert_eq!(points.first().map(|p| p.size_raw), Some(5));
+        assert_eq!(
+            points.last().map(|p| p.size_raw),
+            Some(MAX_POINTS as u64 + 4)
+        );
+    }
+}
@@ -0,0 +1,544 @@
+// 扫描时按 .gitignore 与 DiskSight 专用的 .dsignore 过滤内容，两者语法相同。
+// 支持 gitignore 的常用写法：# 注释、! 取反、末尾 / 只匹配目录、含 / 的模式相对所在目录匹配、
+// 不含 / 的模式匹配任意层级的名称，以及 *、?、[...] 和 **。
+// 与 git 一样，被忽略的目录不会再进入，其中的取反规则不起作用。
+// OnlyIgnored 模式反过来只统计被忽略的内容和常见构建产物，相当于在所有仓库执行 git clean -xdf 能释放的空间。
+// 设置中按路径附加的扫描选项（排除模式、跟随符号链接）也由这里的过滤器在扫描时应用
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const GITIGNORE: &str = ".gitignore";
+pub const DSIGNORE: &str = ".dsignore";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreMode {
+    /// 统计所有内容
+    #[default]
+    Off,
+    /// 跳过 .gitignore、.dsignore 忽略的内容和 .git 目录，只统计会提交或发布的内容
+    Respect,
+    /// 只统计被忽略的内容和常见构建产物（node_modules、target 等）
+    OnlyIgnored,
+}
+
+// 按路径附加的扫描选项，扫描该目录或其中的内容时自动应用
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOverride {
+    /// 规范化后的目录
+    pub path: String,
+    /// 相对该目录的排除模式，语法同 .gitignore，如 Unity 项目中的 Library/
+    pub exclude: Vec<String>,
+    /// 跟随该目录下的符号链接，统计链接指向的目录
+    pub follow_symlinks: bool,
+    /// 扫描根目录在该目录中时使用的忽略文件模式，未设置时使用全局设置
+    pub ignore_mode: Option<IgnoreMode>,
+}
+
+// 扫描根目录所在的、设置了忽略文件模式的最深一层目录的模式
+pub fn override_mode(overrides: &[ScanOverride], root: &Path) -> Option<IgnoreMode> {
+    overrides
+        .iter()
+        .filter(|o| o.ignore_mode.is_some() && root.starts_with(&o.path))
+        .max_by_key(|o| Path::new(&o.path).components().count())
+        .and_then(|o| o.ignore_mode)
+}
+
+// 最多连续跟随的符号链接层数，防止链接互相指向时无限递归
+const MAX_LINK_DEPTH: u8 = 8;
+
+// OnlyIgnored 模式下即使没有被忽略文件列出也视为可清理的构建产物和缓存
+const ARTIFACT_PATTERNS: &[&str] = &[
+    "node_modules/",
+    "target/",
+    "__pycache__/",
+    "*.pyc",
+    ".pytest_cache/",
+    ".mypy_cache/",
+    ".tox/",
+    ".gradle/",
+    ".next/",
+    ".nuxt/",
+    ".parcel-cache/",
+    ".turbo/",
+    "DerivedData/",
+];
+
+// 扫描到一个条目时的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// 不计入
+    Skip,
+    /// 连同其下所有内容完整计入
+    Whole,
+    /// 目录本身不确定，进入后逐个判断
+    Descend,
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// 模式中含 /，相对规则文件所在目录匹配完整路径，否则只匹配名称
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.strip_prefix('/').unwrap_or(line).to_string();
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Rule {
+            pattern,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, name: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let text = if self.anchored { relative } else { name };
+        glob_match(self.pattern.as_bytes(), text.as_bytes())
+    }
+}
+
+// 一个规则文件中的规则，路径相对 base 匹配
+#[derive(Debug)]
+struct RuleSet {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    fn load(base: &Path, file: &Path) -> Option<RuleSet> {
+        let content = fs::read_to_string(file).ok()?;
+        let rules: Vec<Rule> = content.lines().filter_map(Rule::parse).collect();
+        (!rules.is_empty()).then(|| RuleSet {
+            base: base.to_path_buf(),
+            rules,
+        })
+    }
+}
+
+// 从扫描根目录到当前目录的所有规则文件，进入子目录时追加该目录下的规则文件。
+// 规则集共享，进入子目录只复制指针
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreStack {
+    sets: Vec<Arc<RuleSet>>,
+}
+
+impl IgnoreStack {
+    // 为扫描根目录建立规则栈。
+    // 根目录位于 git 仓库中时，从仓库根目录起的上级 .gitignore 和 .git/info/exclude 同样生效
+    pub fn for_root(root: &Path) -> IgnoreStack {
+        let mut stack = IgnoreStack::default();
+        let repo_root = root.ancestors().find(|dir| dir.join(".git").exists());
+        if let Some(repo_root) = repo_root {
+            let exclude = repo_root.join(".git").join("info").join("exclude");
+            stack.push(RuleSet::load(repo_root, &exclude));
+        }
+        let mut chain: Vec<&Path> = root
+            .ancestors()
+            .take_while(|dir| repo_root.is_some_and(|repo| dir.starts_with(repo)))
+            .collect();
+        if chain.is_empty() {
+            chain.push(root);
+        }
+        for dir in chain.into_iter().rev() {
+            stack = stack.enter(dir);
+        }
+        stack
+    }
+
+    // 进入子目录，加载其中的 .gitignore 与 .dsignore
+    pub fn enter(&self, dir: &Path) -> IgnoreStack {
+        let mut stack = self.clone();
+        for file in [GITIGNORE, DSIGNORE] {
+            stack.push(RuleSet::load(dir, &dir.join(file)));
+        }
+        stack
+    }
+
+    fn push(&mut self, set: Option<RuleSet>) {
+        if let Some(set) = set {
+            self.sets.push(Arc::new(set));
+        }
+    }
+
+    // 按规则文件由浅到深、文件内由上到下的顺序匹配，最后命中的规则决定是否忽略
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let mut ignored = false;
+        for set in &self.sets {
+            let Ok(relative) = path.strip_prefix(&set.base) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            for rule in &set.rules {
+                if rule.matches(&relative, &name, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+// 与一次扫描相关的按路径选项
+#[derive(Debug, Default)]
+struct PathOptions {
+    /// 各目录的排除模式，与规则文件的匹配方式相同
+    excludes: IgnoreStack,
+    follow_symlinks: Vec<PathBuf>,
+}
+
+impl PathOptions {
+    // 只保留位于扫描根目录之上或之中的选项
+    fn for_root(root: &Path, overrides: &[ScanOverride]) -> PathOptions {
+        let mut options = PathOptions::default();
+        for o in overrides {
+            let dir = Path::new(&o.path);
+            if !root.starts_with(dir) && !dir.starts_with(root) {
+                continue;
+            }
+            let rules: Vec<Rule> = o.exclude.iter().filter_map(|p| Rule::parse(p)).collect();
+            if !rules.is_empty() {
+                options.excludes.push(Some(RuleSet {
+                    base: dir.to_path_buf(),
+                    rules,
+                }));
+            }
+            if o.follow_symlinks {
+                options.follow_symlinks.push(dir.to_path_buf());
+            }
+        }
+        options
+    }
+
+    fn is_empty(&self) -> bool {
+        self.excludes.sets.is_empty() && self.follow_symlinks.is_empty()
+    }
+}
+
+// 按模式过滤扫描内容的规则栈
+#[derive(Clone, Debug)]
+pub struct IgnoreFilter {
+    mode: IgnoreMode,
+    stack: IgnoreStack,
+    options: Arc<PathOptions>,
+    /// 到达当前目录时已跟随的符号链接层数
+    link_depth: u8,
+}
+
+impl IgnoreFilter {
+    // 既不按忽略文件过滤、也没有相关的按路径选项时返回 None，扫描不做任何额外工作
+    pub fn for_root(
+        root: &Path,
+        mode: IgnoreMode,
+        overrides: &[ScanOverride],
+    ) -> Option<IgnoreFilter> {
+        let options = PathOptions::for_root(root, overrides);
+        (mode != IgnoreMode::Off || !options.is_empty()).then(|| IgnoreFilter {
+            mode,
+            stack: if mode == IgnoreMode::Off {
+                IgnoreStack::default()
+            } else {
+                IgnoreStack::for_root(root)
+            },
+            options: Arc::new(options),
+            link_depth: 0,
+        })
+    }
+
+    pub fn mode(&self) -> IgnoreMode {
+        self.mode
+    }
+
+    pub fn enter(&self, dir: &Path) -> IgnoreFilter {
+        IgnoreFilter {
+            mode: self.mode,
+            stack: if self.mode == IgnoreMode::Off {
+                IgnoreStack::default()
+            } else {
+                self.stack.enter(dir)
+            },
+            options: self.options.clone(),
+            link_depth: self.link_depth,
+        }
+    }
+
+    // 完整计入的目录中仍需应用按路径选项；没有选项时返回 None
+    pub fn whole(&self) -> Option<IgnoreFilter> {
+        (!self.options.is_empty()).then(|| IgnoreFilter {
+            mode: IgnoreMode::Off,
+            stack: IgnoreStack::default(),
+            options: self.options.clone(),
+            link_depth: self.link_depth,
+        })
+    }
+
+    pub fn verdict(&self, path: &Path, is_dir: bool) -> Verdict {
+        if self.options.excludes.is_ignored(path, is_dir) {
+            return Verdict::Skip;
+        }
+        if self.mode == IgnoreMode::Off {
+            return if is_dir {
+                Verdict::Descend
+            } else {
+                Verdict::Whole
+            };
+        }
+        // 仓库数据既不会提交，git clean 也不会删除，两种模式下都不计入
+        if is_dir && path.file_name().is_some_and(|n| n == ".git") {
+            return Verdict::Skip;
+        }
+        let ignored = self.stack.is_ignored(path, is_dir);
+        match self.mode {
+            IgnoreMode::Respect if ignored => Verdict::Skip,
+            IgnoreMode::OnlyIgnored if ignored || is_artifact(path, is_dir) => Verdict::Whole,
+            IgnoreMode::OnlyIgnored if !is_dir => Verdict::Skip,
+            _ if is_dir => Verdict::Descend,
+            _ => Verdict::Whole,
+        }
+    }
+
+    // 在设置了跟随符号链接的目录中，path 是指向目录的链接时返回进入该目录使用的过滤器。
+    // 指向上级目录的链接不跟随
+    pub fn follow_link(&self, path: &Path) -> Option<IgnoreFilter> {
+        if self.link_depth >= MAX_LINK_DEPTH
+            || !self
+                .options
+                .follow_symlinks
+                .iter()
+                .any(|dir| path.starts_with(dir))
+        {
+            return None;
+        }
+        if !fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+            return None;
+        }
+        let target = fs::canonicalize(path).ok()?;
+        let parent = fs::canonicalize(path.parent()?).ok()?;
+        if !target.is_dir() || parent.starts_with(&target) {
+            return None;
+        }
+        let mut filter = self.enter(path);
+        filter.link_depth += 1;
+        Some(filter)
+    }
+}
+
+fn is_artifact(path: &Path, is_dir: bool) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    ARTIFACT_PATTERNS
+        .iter()
+        .filter_map(|pattern| Rule::parse(pattern))
+        .any(|rule| rule.matches(&name, &name, is_dir))
+}
+
+// gitignore 风格的通配符匹配：* 和 ? 不跨越 /，** 匹配任意层级目录
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = &pattern[2..];
+            match rest.first() {
+                // 末尾的 ** 匹配其下所有内容
+                None => true,
+                // **/ 匹配零个或多个目录
+                Some(b'/') => {
+                    let rest = &rest[1..];
+                    glob_match(rest, text)
+                        || text
+                            .iter()
+                            .enumerate()
+                            .any(|(i, &c)| c == b'/' && glob_match(rest, &text[i + 1..]))
+                }
+                Some(_) => glob_match(&pattern[1..], text),
+            }
+        }
+        Some(b'*') => {
+            let rest = &pattern[1..];
+            (0..=text.len())
+                .take_while(|&i| i == 0 || text[i - 1] != b'/')
+                .any(|i| glob_match(rest, &text[i..]))
+        }
+        Some(b'?') => match text.first() {
+            Some(&c) if c != b'/' => glob_match(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some(b'[') => match (text.first(), class_match(&pattern[1..], text.first())) {
+            (Some(_), Some((true, len))) => glob_match(&pattern[1 + len..], &text[1..]),
+            (_, None) => literal_match(b'[', &pattern[1..], text),
+            _ => false,
+        },
+        Some(b'\\') if pattern.len() > 1 => literal_match(pattern[1], &pattern[2..], text),
+        Some(&c) => literal_match(c, &pattern[1..], text),
+    }
+}
+
+fn literal_match(c: u8, rest: &[u8], text: &[u8]) -> bool {
+    text.first() == Some(&c) && glob_match(rest, &text[1..])
+}
+
+// 匹配 [ 之后的字符类，返回是否命中以及字符类（含结尾 ]）的长度；没有结尾的 ] 时返回 None
+fn class_match(class: &[u8], c: Option<&u8>) -> Option<(bool, usize)> {
+    let negated = matches!(class.first(), Some(b'!' | b'^'));
+    let mut i = usize::from(negated);
+    let mut matched = false;
+    let mut first = true;
+    while i < class.len() {
+        if class[i] == b']' && !first {
+            let hit = c.is_some_and(|&c| c != b'/') && matched != negated;
+            return Some((hit, i + 1));
+        }
+        first = false;
+        let low = class[i];
+        if class.get(i + 1) == Some(&b'-') && class.get(i + 2).is_some_and(|&h| h != b']') {
+            let high = class[i + 2];
+            matched |= c.is_some_and(|&c| low <= c && c <= high);
+            i += 3;
+        } else {
+            matched |= c == Some(&low);
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_gitignore_patterns() {
+        assert!(glob_match(b"*.log", b"debug.log"));
+        assert!(!glob_match(b"*.log", b"logs/debug.txt"));
+        assert!(glob_match(b"build/*.o", b"build/a.o"));
+        assert!(!glob_match(b"build/*.o", b"build/sub/a.o"));
+        assert!(glob_match(b"**/cache", b"cache"));
+        assert!(glob_match(b"**/cache", b"a/b/cache"));
+        assert!(glob_match(b"docs/**/*.pdf", b"docs/x/y/z.pdf"));
+        assert!(glob_match(b"out/**", b"out/any/thing"));
+        assert!(glob_match(b"file[0-9].txt", b"file7.txt"));
+        assert!(!glob_match(b"file[!0-9].txt", b"file7.txt"));
+    }
+
+    #[test]
+    fn stack_applies_nested_rules_and_negation() {
+        let root = std::env::temp_dir().join(format!("disksight-ignore-{}", std::process::id()));
+        let sub = root.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(root.join(GITIGNORE), "target/\n*.log\n/only-root.txt\n").unwrap();
+        fs::write(sub.join(DSIGNORE), "!keep.log\n").unwrap();
+
+        let stack = IgnoreStack::for_root(&root);
+        assert!(stack.is_ignored(&root.join("target"), true));
+        assert!(!stack.is_ignored(&root.join("target"), false));
+        assert!(stack.is_ignored(&root.join("a.log"), false));
+        assert!(stack.is_ignored(&root.join("only-root.txt"), false));
+
+        let nested = stack.enter(&sub);
+        assert!(nested.is_ignored(&sub.join("b.log"), false));
+        assert!(!nested.is_ignored(&sub.join("keep.log"), false));
+        assert!(!nested.is_ignored(&sub.join("only-root.txt"), false));
+
+        let respect = IgnoreFilter::for_root(&root, IgnoreMode::Respect, &[]).unwrap();
+        assert_eq!(respect.verdict(&root.join(".git"), true), Verdict::Skip);
+        assert_eq!(respect.verdict(&root.join("a.log"), false), Verdict::Skip);
+        assert_eq!(respect.verdict(&root.join("a.txt"), false), Verdict::Whole);
+        assert_eq!(respect.verdict(&sub, true), Verdict::Descend);
+
+        // 反向模式：被忽略的内容和构建产物完整计入，其余文件不计入
+        let inverse = IgnoreFilter::for_root(&root, IgnoreMode::OnlyIgnored, &[]).unwrap();
+        assert_eq!(inverse.verdict(&root.join("a.log"), false), Verdict::Whole);
+        assert_eq!(
+            inverse.verdict(&sub.join("node_modules"), true),
+            Verdict::Whole
+        );
+        assert_eq!(inverse.verdict(&root.join("a.txt"), false), Verdict::Skip);
+        assert_eq!(inverse.verdict(&sub, true), Verdict::Descend);
+        assert!(IgnoreFilter::for_root(&root, IgnoreMode::Off, &[]).is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn path_overrides_exclude_and_follow_links() {
+        let root = std::env::temp_dir().join(format!("disksight-override-{}", std::process::id()));
+        let game = root.join("game");
+        let shared = root.join("shared");
+        fs::create_dir_all(game.join("Library")).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        let overrides = vec![
+            ScanOverride {
+                path: game.to_string_lossy().into_owned(),
+                exclude: vec!["Library/".to_string()],
+                ignore_mode: Some(IgnoreMode::Respect),
+                ..Default::default()
+            },
+            ScanOverride {
+                path: root.to_string_lossy().into_owned(),
+                follow_symlinks: true,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            override_mode(&overrides, &game.join("Assets")),
+            Some(IgnoreMode::Respect)
+        );
+        assert_eq!(override_mode(&overrides, &root), None);
+
+        // 全局模式为 Off 时按路径的排除模式仍然生效
+        let filter = IgnoreFilter::for_root(&root, IgnoreMode::Off, &overrides).unwrap();
+        assert_eq!(filter.verdict(&game.join("Library"), true), Verdict::Skip);
+        assert_eq!(filter.verdict(&game.join(".git"), true), Verdict::Descend);
+        assert_eq!(
+            filter.verdict(&root.join("Library"), true),
+            Verdict::Descend
+        );
+        assert!(
+            IgnoreFilter::for_root(&root.join("other"), IgnoreMode::Off, &overrides[..1]).is_none()
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&shared, game.join("linked")).unwrap();
+            std::os::unix::fs::symlink(&root, shared.join("up")).unwrap();
+            assert!(filter.follow_link(&game.join("linked")).is_some());
+            assert!(filter.follow_link(&shared.join("up")).is_none());
+            let unfollowed =
+                IgnoreFilter::for_root(&root, IgnoreMode::Off, &overrides[..1]).unwrap();
+            assert!(unfollowed.follow_link(&game.join("linked")).is_none());
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,499 @@
+use crate::normalize::{fold, nfc};
+use crate::utils::{human_readable_size, sum_sizes};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+
+const INDEX_FILE: &str = "file_index.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+}
+
+// 增量更新的读取位置，如 NTFS USN 日志 ID 与下一条记录的 USN
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeCursor {
+    pub journal_id: u64,
+    pub position: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct VolumeIndex {
+    /// 路径 -> 条目，有序存储便于按目录前缀批量更新
+    entries: BTreeMap<String, IndexEntry>,
+    built_at: Option<SystemTime>,
+    /// 上次处理到的变更位置，重启后可从这里继续增量更新
+    #[serde(default)]
+    cursor: Option<ChangeCursor>,
+    #[serde(skip)]
+    building: bool,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl VolumeIndex {
+    fn start_building(&mut self) {
+        self.building = true;
+        self.error = None;
+        // 建立期间的变更由监视器从新的位置开始补上
+        self.cursor = None;
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct IndexData {
+    volumes: BTreeMap<String, VolumeIndex>,
+    /// 目录集合或建立状态每变化一次加一，供监视线程等待
+    #[serde(skip)]
+    generation: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VolumeStatus {
+    pub root: String,
+    pub entry_count: usize,
+    /// 最近一次完整建立索引的时间
+    pub built_at: Option<SystemTime>,
+    pub building: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexHit {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub size_display: String,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+}
+
+// 持久化的文件名索引，在后台线程中建立，查询时无需重新遍历磁盘
+#[derive(Clone)]
+pub struct FileIndex {
+    path: PathBuf,
+    data: Arc<Mutex<IndexData>>,
+    roots_changed: Arc<Condvar>,
+}
+
+impl FileIndex {
+    // 从数据目录加载已保存的索引，不存在或损坏时为空
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(INDEX_FILE);
+        let data = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("索引文件解析失败，将重新建立: {}", e);
+                IndexData::default()
+            }),
+            Err(_) => IndexData::default(),
+        };
+        FileIndex {
+            path,
+            data: Arc::new(Mutex::new(data)),
+            roots_changed: Arc::new(Condvar::new()),
+        }
+    }
+
+    // 设置需要索引的目录：移除不再需要的，为尚未建立索引的目录启动后台任务；
+    // force 为 true 时全部重建
+    pub fn set_roots(&self, roots: &[String], force: bool) {
+        let pending: Vec<String> = {
+            let mut data = self.data.lock().unwrap();
+            data.volumes.retain(|root, _| roots.contains(root));
+            data.generation += 1;
+            // 在登记目录的同一次加锁中标记为建立中，其他线程不会看到已登记但未开始建立的目录
+            roots
+                .iter()
+                .filter(|root| {
+                    let volume = data.volumes.entry(root.to_string()).or_default();
+                    let start = !volume.building && (force || volume.built_at.is_none());
+                    if start {
+                        volume.start_building();
+                    }
+                    start
+                })
+                .cloned()
+                .collect()
+        };
+        self.roots_changed.notify_all();
+        for root in pending {
+            self.spawn_build(root);
+        }
+    }
+
+    // 无法增量更新时的兜底：完整重建单个目录的索引
+    pub fn rebuild_root(&self, root: &str) {
+        {
+            let mut data = self.data.lock().unwrap();
+            match data.volumes.get_mut(root) {
+                Some(volume) if !volume.building => volume.start_building(),
+                _ => return,
+            }
+        }
+        self.spawn_build(root.to_string());
+    }
+
+    // 在后台线程中遍历目录，调用前需已通过 start_building 标记
+    fn spawn_build(&self, root: String) {
+        let index = self.clone();
+        std::thread::spawn(move || {
+            let mut entries = BTreeMap::new();
+            let result = if Path::new(&root).is_dir() {
+                walk(Path::new(&root), &mut entries);
+                Ok(())
+            } else {
+                Err("目录不存在".to_string())
+            };
+            {
+                let mut data = index.data.lock().unwrap();
+                // 建立期间该目录可能已被移出索引范围
+                let Some(volume) = data.volumes.get_mut(&root) else {
+                    return;
+                };
+                volume.building = false;
+                match result {
+                    Ok(()) => {
+                        volume.entries = entries;
+                        volume.built_at = Some(SystemTime::now());
+                    }
+                    Err(e) => volume.error = Some(e),
+                }
+                data.generation += 1;
+            }
+            index.roots_changed.notify_all();
+            if let Err(e) = index.save() {
+                eprintln!("保存索引失败: {}", e);
+            }
+        });
+    }
+
+    pub fn status(&self) -> Vec<VolumeStatus> {
+        let data = self.data.lock().unwrap();
+        data.volumes
+            .iter()
+            .map(|(root, volume)| VolumeStatus {
+                root: root.clone(),
+                entry_count: volume.entries.len(),
+                built_at: volume.built_at,
+                building: volume.building,
+                error: volume.error.clone(),
+            })
+            .collect()
+    }
+
+    // 按文件名查询，支持 * 和 ? 通配符；模式中含路径分隔符时匹配完整路径
+    pub fn query(&self, pattern: &str, limit: usize) -> Result<Vec<IndexHit>, String> {
+        let matcher = NameMatcher::new(pattern)?;
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .volumes
+            .values()
+            .flat_map(|volume| volume.entries.iter())
+            .filter(|(path, _)| matcher.matches(path))
+            .take(limit)
+            .map(|(path, entry)| IndexHit {
+                path: path.clone(),
+                name: file_name(path).to_string(),
+                size: entry.size,
+                size_display: human_readable_size(entry.size),
+                modified: entry.modified,
+                is_dir: entry.is_dir,
+            })
+            .collect())
+    }
+
+    // 所有已纳入索引的目录及其是否正在建立
+    pub fn roots(&self) -> Vec<(String, bool)> {
+        let data = self.data.lock().unwrap();
+        data.volumes
+            .iter()
+            .map(|(root, volume)| (root.clone(), volume.building))
+            .collect()
+    }
+
+    // 目录集合的当前版本，与 wait_roots_changed 配合使用
+    pub fn roots_generation(&self) -> u64 {
+        self.data.lock().unwrap().generation
+    }
+
+    // 阻塞到目录集合或建立状态相对 seen 版本发生变化，最多等待 timeout
+    pub fn wait_roots_changed(&self, seen: u64, timeout: Duration) {
+        let data = self.data.lock().unwrap();
+        let _ = self
+            .roots_changed
+            .wait_timeout_while(data, timeout, |data| data.generation == seen)
+            .unwrap();
+    }
+
+    pub fn built_at(&self, root: &str) -> Option<SystemTime> {
+        let data = self.data.lock().unwrap();
+        data.volumes.get(root).and_then(|volume| volume.built_at)
+    }
+
+    pub fn cursor(&self, root: &str) -> Option<ChangeCursor> {
+        let data = self.data.lock().unwrap();
+        data.volumes.get(root).and_then(|volume| volume.cursor)
+    }
+
+    pub fn set_cursor(&self, root: &str, cursor: Option<ChangeCursor>) {
+        if let Some(volume) = self.data.lock().unwrap().volumes.get_mut(root) {
+            volume.cursor = cursor;
+        }
+    }
+
+    // 记录监视器的错误，显示在 index_status 中
+    pub fn set_error(&self, root: &str, error: Option<String>) {
+        if let Some(volume) = self.data.lock().unwrap().volumes.get_mut(root) {
+            volume.error = error;
+        }
+    }
+
+    // 按单个路径的变更更新索引：路径已不存在时移除它及其子项；
+    // 新出现的目录（含改名后的目录）会遍历其内容。返回路径是否在索引范围内
+    pub fn refresh_path(&self, path: &Path) -> bool {
+        let key = path.to_string_lossy().into_owned();
+        let metadata = fs::symlink_metadata(path).ok();
+        let mut data = self.data.lock().unwrap();
+        let Some(volume) = data
+            .volumes
+            .iter_mut()
+            .find(|(root, volume)| !volume.building && path.starts_with(root.as_str()))
+            .map(|(_, volume)| volume)
+        else {
+            return false;
+        };
+
+        let Some(metadata) = metadata else {
+            volume.entries.remove(&key);
+            remove_children(&mut volume.entries, &key);
+            return true;
+        };
+        let is_dir = metadata.is_dir();
+        let known_dir = volume.entries.get(&key).is_some_and(|e| e.is_dir);
+        volume.entries.insert(
+            key.clone(),
+            IndexEntry {
+                size: if is_dir { 0 } else { metadata.len() },
+                modified: metadata.modified().ok(),
+                is_dir,
+            },
+        );
+        if is_dir && !known_dir {
+            remove_children(&mut volume.entries, &key);
+            walk(path, &mut volume.entries);
+        } else if !is_dir {
+            // 目录被同名文件替换
+            remove_children(&mut volume.entries, &key);
+        }
+        true
+    }
+
+    // 根据索引计算目录大小，无需遍历磁盘；目录不在索引范围内时返回 None
+    pub fn dir_size(&self, path: &Path) -> Option<u64> {
+        let key = path.to_string_lossy().into_owned();
+        let data = self.data.lock().unwrap();
+        let (_, volume) = data
+            .volumes
+            .iter()
+            .find(|(root, volume)| volume.built_at.is_some() && path.starts_with(root.as_str()))?;
+        Some(sum_sizes(
+            children(&volume.entries, &key).map(|(_, e)| e.size),
+        ))
+    }
+
+    pub fn flush(&self) -> Result<(), String> {
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = {
+            let data = self.data.lock().unwrap();
+            serde_json::to_string(&*data).map_err(|e| e.to_string())?
+        };
+        // 先写临时文件再改名，避免写到一半时退出导致索引损坏
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+// 不跟随符号链接，避免重复索引或陷入循环
+fn walk(dir: &Path, entries: &mut BTreeMap<String, IndexEntry>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let is_dir = metadata.is_dir();
+        entries.insert(
+            path.to_string_lossy().into_owned(),
+            IndexEntry {
+                size: if is_dir { 0 } else { metadata.len() },
+                modified: metadata.modified().ok(),
+                is_dir,
+            },
+        );
+        if is_dir {
+            walk(&path, entries);
+        }
+    }
+}
+
+// 某路径下的所有子项，利用 BTreeMap 的有序性按前缀取区间
+fn children<'a>(
+    entries: &'a BTreeMap<String, IndexEntry>,
+    key: &str,
+) -> impl Iterator<Item = (&'a String, &'a IndexEntry)> {
+    let prefix = format!(
+        "{}{}",
+        key.trim_end_matches(['/', '\\']),
+        std::path::MAIN_SEPARATOR
+    );
+    entries
+        .range(prefix.clone()..)
+        .take_while(move |(path, _)| path.starts_with(&prefix))
+}
+
+fn remove_children(entries: &mut BTreeMap<String, IndexEntry>, key: &str) {
+    let stale: Vec<String> = children(entries, key).map(|(p, _)| p.clone()).collect();
+    for path in stale {
+        entries.remove(&path);
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+pub(crate) enum NameMatcher {
+    Substring { needle: String, full_path: bool },
+    Glob { regex: Regex, full_path: bool },
+}
+
+impl NameMatcher {
+    pub(crate) fn new(pattern: &str) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("查询内容不能为空".to_string());
+        }
+        let full_path = pattern.contains(['/', '\\']);
+        if !pattern.contains(['*', '?']) {
+            return Ok(NameMatcher::Substring {
+                needle: fold(pattern),
+                full_path,
+            });
+        }
+        let regex = Regex::new(&glob_to_regex(&nfc(pattern)))
+            .map_err(|e| format!("查询模式无效: {}", e))?;
+        Ok(NameMatcher::Glob { regex, full_path })
+    }
+
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        match self {
+            NameMatcher::Substring { needle, full_path } => {
+                let target = if *full_path { path } else { file_name(path) };
+                fold(target).contains(needle.as_str())
+            }
+            NameMatcher::Glob { regex, full_path } => {
+                regex.is_match(&nfc(if *full_path { path } else { file_name(path) }))
+            }
+        }
+    }
+}
+
+// 通配符转为不区分大小写、整体匹配的正则
+fn glob_to_regex(pattern: &str) -> String {
+    let mut expr = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '*' => expr.push_str(".*"),
+            '?' => expr.push('.'),
+            c => expr.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expr.push('$');
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_names_case_insensitively() {
+        let plain = NameMatcher::new("Report").unwrap();
+        assert!(plain.matches("/home/a/annual-report.pdf"));
+        assert!(!plain.matches("/home/report/notes.txt"));
+
+        let full = NameMatcher::new("/report/").unwrap();
+        assert!(full.matches("/home/report/notes.txt"));
+    }
+
+    #[test]
+    fn converts_glob_to_anchored_regex() {
+        assert_eq!(glob_to_regex("*.iso"), r"(?i)^.*\.iso$");
+        assert_eq!(glob_to_regex("v?.log"), r"(?i)^v.\.log$");
+    }
+
+    #[test]
+    fn removes_only_descendants() {
+        let entry = || IndexEntry {
+            size: 1,
+            modified: None,
+            is_dir: false,
+        };
+        let sep = std::path::MAIN_SEPARATOR;
+        let mut entries = BTreeMap::new();
+        for path in ["a", "a{s}b", "a{s}b{s}c", "a{s}bc"] {
+            entries.insert(path.replace("{s}", &sep.to_string()), entry());
+        }
+        let key = format!("a{}b", sep);
+        assert_eq!(children(&entries, &key).count(), 1);
+        remove_children(&mut entries, &key);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains_key(&format!("a{}bc", sep)));
+    }
+
+    #[test]
+    fn set_roots_wakes_waiting_watchers() {
+        let index = FileIndex::load(&std::env::temp_dir().join("disksight-index-wait-missing"));
+        let seen = index.roots_generation();
+        let waiter = {
+            let index = index.clone();
+            std::thread::spawn(move || {
+                let started = std::time::Instant::now();
+                index.wait_roots_changed(seen, Duration::from_secs(30));
+                started.elapsed()
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        index.set_roots(&[], false);
+        assert!(waiter.join().unwrap() < Duration::from_secs(10));
+        assert_ne!(index.roots_generation(), seen);
+    }
+
+    #[test]
+    fn set_roots_registers_roots_as_building() {
+        let dir = std::env::temp_dir().join("disksight-index-roots");
+        let index = FileIndex::load(&dir);
+        let missing = dir.join("missing").to_string_lossy().into_owned();
+        index.set_roots(std::slice::from_ref(&missing), true);
+        // 返回时目录已登记，且要么仍在建立中，要么已经结束
+        let status = index.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].root, missing);
+        assert!(status[0].building || status[0].error.is_some());
+    }
+}
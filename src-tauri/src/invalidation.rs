@@ -0,0 +1,194 @@
+// 删除、移动、重命名成功后修正已保存的扫描结果，并通过 entries-invalidated 事件通知所有窗口
+// 受影响的条目与上级目录修正后的大小，各视图据此局部更新，不必重新扫描
+use crate::audit::{AuditAction, AuditRecord};
+use crate::scan_store::ScanStore;
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const EVENT: &str = "entries-invalidated";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InvalidatedParent {
+    pub path: String,
+    /// 在已保存的扫描结果中修正后的大小，没有任何扫描结果包含该目录时为 None
+    pub size_raw: Option<u64>,
+    pub size_display: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntriesInvalidated {
+    pub action: AuditAction,
+    /// 已不在原处的条目
+    pub removed: Vec<String>,
+    /// 移动、重命名后的新位置
+    pub added: Vec<String>,
+    pub parents: Vec<InvalidatedParent>,
+    /// 已修正的扫描结果
+    pub scan_ids: Vec<u64>,
+}
+
+// 按一条成功的审计记录修正所有已保存的扫描结果；不改变条目位置与大小的操作返回 None
+pub fn apply(store: &mut ScanStore, record: &AuditRecord) -> Option<EntriesInvalidated> {
+    if !record.success {
+        return None;
+    }
+    let added: Vec<String> = match record.action {
+        AuditAction::Delete | AuditAction::Trash => Vec::new(),
+        AuditAction::Move | AuditAction::Rename => record.target.iter().cloned().collect(),
+        // 替换为硬链接或共享数据块后路径和表观大小都不变
+        AuditAction::HardLink | AuditAction::Reflink | AuditAction::Settings => return None,
+    };
+    let removed = vec![record.path.clone()];
+
+    let mut scan_ids = Vec::new();
+    for (id, scan) in store.iter_mut() {
+        let mut touched = false;
+        for path in &removed {
+            if !Path::new(path).starts_with(&scan.root) {
+                continue;
+            }
+            // 扫描结果中没有该条目时（通常是更深层的内容）只修正上级目录
+            if scan.patch_entry(path, None).is_err() {
+                scan.adjust_ancestors(path, record.bytes, 0);
+            }
+            touched = true;
+        }
+        for path in &added {
+            if Path::new(path).starts_with(&scan.root) {
+                scan.adjust_ancestors(path, 0, record.bytes);
+                touched = true;
+            }
+        }
+        if touched {
+            scan_ids.push(id);
+        }
+    }
+
+    let mut parents: Vec<String> = removed
+        .iter()
+        .chain(&added)
+        .filter_map(|p| Path::new(p).parent())
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+    parents.dedup();
+    let parents = parents
+        .into_iter()
+        .map(|path| {
+            let size_raw = store.iter().find_map(|(_, scan)| {
+                scan.result
+                    .entries
+                    .iter()
+                    .find(|e| e.has_path(&path))
+                    .map(|e| e.size_raw)
+            });
+            InvalidatedParent {
+                path,
+                size_raw,
+                size_display: size_raw.map(human_readable_size),
+            }
+        })
+        .collect();
+
+    Some(EntriesInvalidated {
+        action: record.action,
+        removed,
+        added,
+        parents,
+        scan_ids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DirectoryResult, FileEntry};
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    fn entry(path: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: path.to_string().into(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            severity: None,
+        }
+    }
+
+    fn record(action: AuditAction, path: &str, target: Option<&str>, bytes: u64) -> AuditRecord {
+        AuditRecord {
+            timestamp: 0,
+            user: String::new(),
+            action,
+            path: path.to_string(),
+            target: target.map(String::from),
+            bytes,
+            success: true,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn moves_shrink_the_source_and_grow_the_target_parents() {
+        let mut store = ScanStore::default();
+        let id = store.insert(
+            PathBuf::from("/r"),
+            DirectoryResult {
+                scan_id: None,
+                entries: vec![
+                    entry("/r/a", 'd', 500),
+                    entry("/r/a/old", 'd', 200),
+                    entry("/r/b", 'd', 100),
+                ],
+                query_time: 0.0,
+                telemetry: None,
+                scan_plan: None,
+                completeness: Default::default(),
+                skipped: Default::default(),
+            },
+        );
+
+        let moved = apply(
+            &mut store,
+            &record(AuditAction::Move, "/r/a/old", Some("/r/b/old"), 200),
+        )
+        .unwrap();
+        assert_eq!(moved.scan_ids, vec![id]);
+        let sizes: Vec<(&str, Option<u64>)> = moved
+            .parents
+            .iter()
+            .map(|p| (p.path.as_str(), p.size_raw))
+            .collect();
+        assert_eq!(sizes, vec![("/r/a", Some(300)), ("/r/b", Some(300))]);
+
+        // 不在扫描结果中的更深层条目只修正上级目录
+        let deleted = apply(
+            &mut store,
+            &record(AuditAction::Delete, "/r/a/x/y.log", None, 100),
+        )
+        .unwrap();
+        assert_eq!(deleted.parents[0].path, "/r/a/x");
+        assert_eq!(deleted.parents[0].size_raw, None);
+        let a = store
+            .get(id)
+            .unwrap()
+            .result
+            .entries
+            .iter()
+            .find(|e| e.has_path("/r/a"));
+        assert_eq!(a.unwrap().size_raw, 200);
+
+        let outside = apply(&mut store, &record(AuditAction::Trash, "/other/f", None, 1)).unwrap();
+        assert!(outside.scan_ids.is_empty());
+        assert!(apply(
+            &mut store,
+            &record(AuditAction::HardLink, "/r/b/old", None, 0)
+        )
+        .is_none());
+    }
+}
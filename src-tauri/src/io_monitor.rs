@@ -0,0 +1,374 @@
+use crate::models::FileEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// 采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+// 平均占用超过该百分比即视为瓶颈
+const BOTTLENECK_PERCENT: f64 = 70.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bottleneck {
+    /// 磁盘繁忙，CPU 空闲
+    Io,
+    /// CPU 繁忙，磁盘空闲
+    Cpu,
+    /// 两者都接近饱和
+    Mixed,
+    /// 两者都不饱和（如网络延迟）或当前平台无法采样
+    Undetermined,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiskThroughput {
+    pub device: String,
+    /// 采样区间内读取的字节数
+    pub read_bytes: u64,
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+    /// 采样区间内磁盘处于忙碌状态的时间占比
+    pub busy_percent: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IoSample {
+    /// 距扫描开始的毫秒数
+    pub elapsed_ms: u64,
+    /// 本进程 CPU 占用（相对全部核心）
+    pub cpu_percent: f64,
+    /// 本进程当前常驻内存，当前平台无法采样时为 None
+    pub rss_bytes: Option<u64>,
+    pub disks: Vec<DiskThroughput>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// 扫描期间本进程消耗的 CPU 时间（用户态 + 内核态）
+    pub cpu_time_ms: Option<u64>,
+    /// 扫描期间采样到的最大常驻内存
+    pub peak_memory_bytes: Option<u64>,
+    /// 扫描期间本进程从存储设备读取的字节数，不含页缓存命中
+    pub bytes_read: Option<u64>,
+    pub duration_ms: u64,
+    /// 结果中顶层条目的数量，不含子目录内的条目
+    #[serde(alias = "entries")]
+    pub top_level_entries: u64,
+    #[serde(alias = "files")]
+    pub top_level_files: u64,
+    #[serde(alias = "dirs")]
+    pub top_level_dirs: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanTelemetry {
+    pub samples: Vec<IoSample>,
+    pub avg_cpu_percent: f64,
+    /// 最繁忙磁盘的平均忙碌占比
+    pub max_disk_busy_percent: f64,
+    /// 扫描期间所有磁盘累计读取字节数
+    pub total_read_bytes: u64,
+    pub bottleneck: Bottleneck,
+    /// 本次扫描的资源消耗，便于比较不同版本与扫描方式的性能
+    #[serde(default)]
+    pub resources: ResourceUsage,
+}
+
+// 扫描期间在后台线程中周期性采样磁盘吞吐与 CPU 占用
+pub struct IoMonitor {
+    // 发送或丢弃即让采样线程立即退出
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<Vec<IoSample>>>,
+    started: Instant,
+    start_cpu: Option<Duration>,
+    start_read_bytes: Option<u64>,
+}
+
+impl IoMonitor {
+    pub fn start() -> Self {
+        let (stop, stop_rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || sample_loop(stop_rx));
+        IoMonitor {
+            stop: Some(stop),
+            handle: Some(handle),
+            started: Instant::now(),
+            start_cpu: read_process_cpu(),
+            start_read_bytes: read_process_read_bytes(),
+        }
+    }
+
+    // 停止采样并汇总，entries 为本次扫描得到的顶层条目
+    pub fn finish(mut self, entries: &[FileEntry]) -> ScanTelemetry {
+        self.stop.take();
+        let samples = self
+            .handle
+            .take()
+            .and_then(|h| h.join().ok())
+            .unwrap_or_default();
+        let dirs = entries.iter().filter(|e| e.file_type == 'd').count() as u64;
+        let resources = ResourceUsage {
+            cpu_time_ms: match (self.start_cpu, read_process_cpu()) {
+                (Some(start), Some(end)) => Some(end.saturating_sub(start).as_millis() as u64),
+                _ => None,
+            },
+            // 采样间隔内的峰值可能漏掉，结束时再取一次
+            peak_memory_bytes: samples
+                .iter()
+                .filter_map(|s| s.rss_bytes)
+                .chain(read_process_rss())
+                .max(),
+            bytes_read: match (self.start_read_bytes, read_process_read_bytes()) {
+                (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+                _ => None,
+            },
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            top_level_entries: entries.len() as u64,
+            top_level_files: entries.len() as u64 - dirs,
+            top_level_dirs: dirs,
+        };
+        summarize(samples, resources)
+    }
+}
+
+impl Drop for IoMonitor {
+    fn drop(&mut self) {
+        self.stop.take();
+    }
+}
+
+fn sample_loop(stop: mpsc::Receiver<()>) -> Vec<IoSample> {
+    let started = Instant::now();
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1) as f64;
+    let mut samples = Vec::new();
+    let mut last_time = Instant::now();
+    let mut last_disks = read_disk_counters();
+    let mut last_cpu = read_process_cpu();
+
+    // 等待期间收到停止信号（或发送端被丢弃）时立即返回，不必等满采样间隔
+    while let Err(RecvTimeoutError::Timeout) = stop.recv_timeout(SAMPLE_INTERVAL) {
+        let now = Instant::now();
+        let secs = now.duration_since(last_time).as_secs_f64().max(0.001);
+        let disks = read_disk_counters();
+        let cpu = read_process_cpu();
+
+        let mut throughput = Vec::new();
+        for (device, current) in &disks {
+            let Some(previous) = last_disks.get(device) else {
+                continue;
+            };
+            let read_bytes = current.read_bytes.saturating_sub(previous.read_bytes);
+            throughput.push(DiskThroughput {
+                device: device.clone(),
+                read_bytes,
+                read_bytes_per_sec: (read_bytes as f64 / secs) as u64,
+                write_bytes_per_sec: (current.write_bytes.saturating_sub(previous.write_bytes)
+                    as f64
+                    / secs) as u64,
+                busy_percent: (current.busy_ms.saturating_sub(previous.busy_ms) as f64
+                    / (secs * 10.0))
+                    .min(100.0),
+            });
+        }
+        throughput.sort_by(|a, b| a.device.cmp(&b.device));
+
+        let cpu_percent = match (last_cpu, cpu) {
+            (Some(prev), Some(cur)) => {
+                (cur.saturating_sub(prev).as_secs_f64() / secs / cores * 100.0).clamp(0.0, 100.0)
+            }
+            _ => 0.0,
+        };
+
+        samples.push(IoSample {
+            elapsed_ms: now.duration_since(started).as_millis() as u64,
+            cpu_percent,
+            rss_bytes: read_process_rss(),
+            disks: throughput,
+        });
+        last_time = now;
+        last_disks = disks;
+        last_cpu = cpu;
+    }
+    samples
+}
+
+fn summarize(samples: Vec<IoSample>, resources: ResourceUsage) -> ScanTelemetry {
+    let count = samples.len().max(1) as f64;
+    let avg_cpu_percent = samples.iter().map(|s| s.cpu_percent).sum::<f64>() / count;
+
+    let mut busy: HashMap<&str, f64> = HashMap::new();
+    let mut total_read_bytes = 0u64;
+    for sample in &samples {
+        for disk in &sample.disks {
+            *busy.entry(disk.device.as_str()).or_default() += disk.busy_percent;
+            total_read_bytes = total_read_bytes.saturating_add(disk.read_bytes);
+        }
+    }
+    let max_disk_busy_percent = busy.values().fold(0.0f64, |acc, v| acc.max(v / count));
+
+    let has_disk_data = samples.iter().any(|s| !s.disks.is_empty());
+    let bottleneck = match (
+        has_disk_data && max_disk_busy_percent >= BOTTLENECK_PERCENT,
+        avg_cpu_percent >= BOTTLENECK_PERCENT,
+    ) {
+        (true, true) => Bottleneck::Mixed,
+        (true, false) => Bottleneck::Io,
+        (false, true) => Bottleneck::Cpu,
+        (false, false) => Bottleneck::Undetermined,
+    };
+
+    ScanTelemetry {
+        samples,
+        avg_cpu_percent,
+        max_disk_busy_percent,
+        total_read_bytes,
+        bottleneck,
+        resources,
+    }
+}
+
+struct DiskCounters {
+    read_bytes: u64,
+    write_bytes: u64,
+    busy_ms: u64,
+}
+
+// 读取 /proc/diskstats 中整盘（不含分区）的累计计数
+#[cfg(target_os = "linux")]
+fn read_disk_counters() -> HashMap<String, DiskCounters> {
+    let content = std::fs::read_to_string("/proc/diskstats").unwrap_or_default();
+    let mut disks = HashMap::new();
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 13 {
+            continue;
+        }
+        let name = parts[2];
+        // 只有整盘在 /sys/block 下有对应目录
+        if name.starts_with("loop")
+            || name.starts_with("ram")
+            || !std::path::Path::new("/sys/block").join(name).exists()
+        {
+            continue;
+        }
+        let field = |i: usize| parts[i].parse::<u64>().unwrap_or(0);
+        disks.insert(
+            name.to_string(),
+            DiskCounters {
+                // diskstats 中扇区固定按 512 字节计
+                read_bytes: field(5) * 512,
+                write_bytes: field(9) * 512,
+                busy_ms: field(12),
+            },
+        );
+    }
+    disks
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_disk_counters() -> HashMap<String, DiskCounters> {
+    HashMap::new()
+}
+
+// 本进程累计消耗的 CPU 时间（用户态 + 内核态）
+#[cfg(target_os = "linux")]
+fn read_process_cpu() -> Option<Duration> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // 进程名可能包含空格，从最后一个 ')' 之后开始解析
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let ticks = if ticks > 0 { ticks as u64 } else { 100 };
+    Some(Duration::from_millis((utime + stime) * 1000 / ticks))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process_cpu() -> Option<Duration> {
+    None
+}
+
+// 本进程当前常驻内存
+#[cfg(target_os = "linux")]
+fn read_process_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process_rss() -> Option<u64> {
+    None
+}
+
+// 本进程累计从存储设备读取的字节数
+#[cfg(target_os = "linux")]
+fn read_process_read_bytes() -> Option<u64> {
+    let io = std::fs::read_to_string("/proc/self/io").ok()?;
+    let line = io.lines().find(|l| l.starts_with("read_bytes:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process_read_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cpu_percent: f64, busy_percent: f64) -> IoSample {
+        IoSample {
+            elapsed_ms: 0,
+            cpu_percent,
+            rss_bytes: Some(1 << 20),
+            disks: vec![DiskThroughput {
+                device: "sda".to_string(),
+                read_bytes: 1024,
+                read_bytes_per_sec: 2048,
+                write_bytes_per_sec: 0,
+                busy_percent,
+            }],
+        }
+    }
+
+    #[test]
+    fn classifies_bottleneck() {
+        assert_eq!(
+            summarize(
+                vec![sample(10.0, 95.0), sample(12.0, 90.0)],
+                ResourceUsage::default()
+            )
+            .bottleneck,
+            Bottleneck::Io
+        );
+        assert_eq!(
+            summarize(vec![sample(90.0, 5.0)], ResourceUsage::default()).bottleneck,
+            Bottleneck::Cpu
+        );
+        assert_eq!(
+            summarize(Vec::new(), ResourceUsage::default()).bottleneck,
+            Bottleneck::Undetermined
+        );
+        assert_eq!(
+            summarize(vec![sample(10.0, 95.0)], ResourceUsage::default()).total_read_bytes,
+            1024
+        );
+    }
+
+    #[test]
+    fn finish_does_not_wait_for_the_sample_interval() {
+        let monitor = IoMonitor::start();
+        let started = Instant::now();
+        let telemetry = monitor.finish(&[]);
+        assert!(started.elapsed() < SAMPLE_INTERVAL / 2);
+        assert!(telemetry.samples.is_empty());
+    }
+}
@@ -1,19 +1,110 @@
+pub mod agent;
+pub mod alerts;
+pub mod analyzers;
+pub mod annotations;
+pub mod api_auth;
+pub mod archive_job;
+pub mod attributes;
+pub mod audit;
+pub mod baseline;
+pub mod chunking;
+pub mod clipboard;
+pub mod cold_storage;
+pub mod collation;
+pub mod compare;
+pub mod config;
+pub mod cow_volumes;
+pub mod dedupe;
+pub mod deletion;
 pub mod dir_listing;
 pub mod dir_listing_v2;
+pub mod dir_reader;
+pub mod drives;
+pub mod duplicates;
+pub mod elevation;
+pub mod entry_tree;
+pub mod export;
+pub mod file_ops;
+pub mod first_run;
+pub mod forecast;
+pub mod git_repos;
+pub mod grouping;
+pub mod hashing;
+pub mod history;
+pub mod ignore_rules;
+pub mod index;
+pub mod invalidation;
+pub mod io_monitor;
+pub mod links;
+pub mod media;
 pub mod models;
+pub mod normalize;
+pub mod owners;
+pub mod parallelism;
+pub mod paths;
+pub mod platform;
+pub mod power;
+pub mod profiles;
+pub mod projects;
+pub mod purgeable;
+pub mod quarantine;
+pub mod quota;
+pub mod recommendations;
+pub mod retention;
+pub mod scan_context;
+pub mod scan_replay;
+pub mod scan_store;
+pub mod scan_worker;
+pub mod scripts;
+pub mod sessions;
+pub mod settings_bundle;
+pub mod skip_list;
+pub mod slack;
+pub mod space_map;
+pub mod startup;
+pub mod summary;
+pub mod test_tree;
+pub mod time_format;
 pub mod utils;
+pub mod verify;
+pub mod vfs;
+pub mod warm_cache;
+pub mod watchdog;
+pub mod watchers;
+pub mod windows_system;
+use analyzers::AnalyzerRegistry;
+use archive_job::ArchiveJobs;
+use audit::{AuditAction, AuditLog};
+use baseline::BaselineStore;
+use cold_storage::ColdStorageIndex;
+use config::ConfigStore;
 pub use dir_listing::*;
 pub use dir_listing_v2::*;
+use elevation::{ElevatedHelper, HelperRequest, HelperResponse};
+use first_run::{FirstRun, SetupProgress, SetupStep, StepStatus};
+use history::SizeHistory;
+use index::FileIndex;
 pub use models::*;
+use paths::Expect;
+use scan_context::{ScanContexts, ScanEmitter, ScanSubscriptions};
+use scan_store::ScanStore;
+use scripts::ScriptStore;
+use sessions::SessionStore;
+use skip_list::{FailureKind, ScanFailures, SkipList};
+use startup::{StartupCoordinator, StartupTask};
 use std::fs;
 use std::path::Path;
 use std::sync::Mutex;
+use std::time::SystemTime;
 use tauri::async_runtime::spawn;
 use tauri::async_runtime::spawn_blocking;
 use tauri::Emitter;
 use tauri::{AppHandle, Manager, State};
+use vfs::RealFs;
+use warm_cache::WarmCache;
+use watchdog::{StallEvent, TimeoutSettings, Watchdog};
 
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, timeout, Duration};
 pub use utils::*;
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -22,8 +113,17 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn calculate_dir_size_simple_fast(path: String) -> Result<DirectoryResult, String> {
-    let cli = Cli {
+async fn calculate_dir_size_simple_fast(
+    path: String,
+    context: Option<String>,
+    webview: tauri::Webview,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+    history: State<'_, SizeHistory>,
+    contexts: State<'_, Mutex<ScanContexts>>,
+) -> Result<DirectoryResult, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let mut cli = Cli {
         file: None,
         long_format: true,
         human_readable: true,
@@ -33,204 +133,3130 @@ async fn calculate_dir_size_simple_fast(path: String) -> Result<DirectoryResult,
         sort: true,
         name: None,
         full_path: true,
+        collation: config.lock().unwrap().get().name_collation,
     };
+    let settings = config.lock().unwrap().get().parallelism.clone();
+    let scan_timeout = config.lock().unwrap().get().timeouts.scan_seconds;
 
     let start_time = std::time::Instant::now();
-    let result = spawn_blocking(move || match list_directory(Path::new(&path), &cli) {
-        Ok(entries) => {
-            let elapsed = start_time.elapsed().as_secs_f64();
-            Ok(DirectoryResult {
-                entries,
-                query_time: elapsed,
-            })
+    let monitor = io_monitor::IoMonitor::start();
+    let root = path.clone();
+    let result = run_blocking_with_timeout(scan_timeout, move || {
+        let plan = parallelism::plan_for(Path::new(&path), &settings);
+        let listed = plan.run(|parallel| {
+            cli.parallel = parallel;
+            list_directory(Path::new(&path), &cli)
+        });
+        match listed {
+            Ok(entries) => {
+                let elapsed = start_time.elapsed().as_secs_f64();
+                let telemetry = monitor.finish(&entries);
+                Ok(DirectoryResult {
+                    scan_id: None,
+                    entries,
+                    query_time: elapsed,
+                    telemetry: Some(telemetry),
+                    scan_plan: Some(plan),
+                    // list_directory 把无法读取的目录按 0 计算且不记录，结果无法确认完整
+                    completeness: Completeness::Estimated,
+                    skipped: SkippedCounts::default(),
+                })
+            }
+            Err(e) => Err(format!("Error listing directory: {}", e)),
         }
-        Err(e) => Err(format!("Error listing directory: {}", e)),
     })
-    .await
-    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    .await?;
 
-    result
+    let result = result
+        .map(|result| store_result(&webview, &store, &config, &history, &root, None, result))?;
+    record_context(&contexts, &webview, context.as_deref(), &root, &result);
+    Ok(result)
+}
+
+// 把扫描结果登记到发起它的窗口或标签页，未指定上下文时以窗口 label 为准
+fn record_context(
+    contexts: &State<'_, Mutex<ScanContexts>>,
+    webview: &tauri::Webview,
+    context: Option<&str>,
+    root: &str,
+    result: &DirectoryResult,
+) {
+    if let Some(scan_id) = result.scan_id {
+        let window = webview.label();
+        contexts
+            .lock()
+            .unwrap()
+            .record(context.unwrap_or(window), window, root, scan_id);
+    }
+}
+
+// 计算高亮级别并保存扫描结果，返回带有 scan_id 的结果。
+// 被监控的目录同时记录本次扫描的总大小，并按提醒设置发送 webhook、邮件通知；
+// 预计即将写满时还会向发起扫描的窗口发送 forecast-alert 事件
+fn store_result(
+    webview: &tauri::Webview,
+    store: &State<'_, Mutex<ScanStore>>,
+    config: &State<'_, Mutex<ConfigStore>>,
+    history: &State<'_, SizeHistory>,
+    root: &str,
+    scan_id: Option<u64>,
+    mut result: DirectoryResult,
+) -> DirectoryResult {
+    {
+        let config = config.lock().unwrap();
+        for entry in result.entries.iter_mut() {
+            entry.severity = config.get().severity_for(entry.size_raw);
+        }
+        let key = history::history_key(root);
+        if config.get().is_monitored(&key) {
+            let total = result
+                .entries
+                .iter()
+                .fold(0u64, |acc, e| acc.saturating_add(e.size_raw));
+            history.record(root, total);
+            // 用户已确认过的目录照常记录历史，但不再提醒
+            let acknowledged = config.get().is_acknowledged(&key);
+            let forecast = forecast::forecast_path(root, history).ok();
+            if let Some(forecast) = forecast
+                .as_ref()
+                .filter(|f| f.alert.is_some() && !acknowledged)
+            {
+                let _ = webview.emit_to(webview.label(), "forecast-alert", forecast);
+            }
+            let settings = config.get().alerts.clone();
+            let points = history.get(root);
+            let alerts = if acknowledged {
+                Vec::new()
+            } else {
+                let now = points.last().map_or(0, |p| p.timestamp);
+                webview.state::<alerts::AlertCooldown>().filter(
+                    alerts::evaluate(root, &points, forecast.as_ref(), &settings),
+                    now,
+                )
+            };
+            if !alerts.is_empty() && settings.has_targets() {
+                // 网络投递可能较慢，放到后台线程，不阻塞扫描结果返回
+                std::thread::spawn(move || {
+                    for error in alerts::deliver(&alerts, &settings) {
+                        eprintln!("{}", error);
+                    }
+                });
+            }
+        }
+    }
+    evaluate_retention(webview.app_handle(), root);
+    save_scan(store, root, scan_id, result)
+}
+
+// 把结果存入扫描存储，返回带有 scan_id 的结果
+fn save_scan(
+    store: &State<'_, Mutex<ScanStore>>,
+    root: &str,
+    scan_id: Option<u64>,
+    result: DirectoryResult,
+) -> DirectoryResult {
+    let mut store = store.lock().unwrap();
+    let id = match scan_id {
+        Some(id) => store.insert_reserved(id, root.into(), result),
+        None => store.insert(root.into(), result),
+    };
+    store.get(id).unwrap().result.clone()
+}
+
+// 扫描完成后在后台评估扫描范围内的保留策略。未确认执行或处于只读模式时只做演练，
+// 报告通过 retention-report 事件发给前端；Notify 策略的命中还会通过提醒渠道发送
+fn evaluate_retention(app_handle: &AppHandle, root: &str) {
+    let config = app_handle
+        .state::<Mutex<ConfigStore>>()
+        .lock()
+        .unwrap()
+        .get()
+        .clone();
+    let policies: Vec<retention::RetentionPolicy> =
+        retention::policies_for_scan(&config.retention_policies, &history::history_key(root))
+            .into_iter()
+            .cloned()
+            .collect();
+    if policies.is_empty() {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        for policy in policies {
+            let dry_run = !policy.enforce || config.read_only;
+            let report = match retention::apply(&policy, dry_run, &app_handle.state::<AuditLog>()) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("保留策略 {} 执行失败: {}", policy.name, e);
+                    continue;
+                }
+            };
+            if policy.action == retention::RetentionAction::Notify
+                && !report.matches.is_empty()
+                && config.alerts.has_targets()
+                && !config.is_acknowledged(&policy.root)
+            {
+                let alert = alerts::Alert {
+                    kind: alerts::AlertKind::Retention,
+                    path: policy.root.clone(),
+                    message: format!(
+                        "保留策略 {} 命中 {} 个文件，共 {}",
+                        policy.name,
+                        report.matches.len(),
+                        report.total_display
+                    ),
+                    size_raw: report.total_bytes,
+                };
+                for error in alerts::deliver(&[alert], &config.alerts) {
+                    eprintln!("{}", error);
+                }
+            }
+            let _ = app_handle.emit("retention-report", report);
+        }
+    });
 }
 // 发送进度事件的辅助函数
-fn emit_progress(app_handle: &AppHandle, current_path: &Path, current_file: &Path, status: &str) {
-    let _ = app_handle.emit(
-        "scan-progress",
+fn emit_progress(
+    emitter: &ScanEmitter,
+    current_path: &Path,
+    current_file: &Path,
+    status: ProgressStatus,
+) {
+    emitter.touch(current_file);
+    if status
+        == (ProgressStatus::Error {
+            kind: ScanErrorKind::PermissionDenied,
+        })
+    {
+        emitter.note_failure(
+            &current_file.to_string_lossy(),
+            FailureKind::PermissionDenied,
+        );
+    }
+    emitter.emit(
+        "progress",
         ProgressEvent {
             current_path: current_path.to_string_lossy().to_string(),
             current_file: current_file.to_string_lossy().to_string(),
-            status: status.to_string(),
+            status,
+            context: emitter.context(),
+            scan_id: Some(emitter.scan_id()),
         },
     );
 }
 
+// 订阅扫描事件。id 为空时为即将开始的扫描预留 ID，调用方监听 scan://{id}/... 后
+// 再把该 ID 传给 get_list_directory；传入进行中扫描的 ID 时加入其订阅者
+#[tauri::command]
+async fn subscribe_scan(
+    id: Option<u64>,
+    webview: tauri::Webview,
+    store: State<'_, Mutex<ScanStore>>,
+    subscriptions: State<'_, Mutex<ScanSubscriptions>>,
+) -> Result<u64, String> {
+    let mut subscriptions = subscriptions.lock().unwrap();
+    match id {
+        Some(id) => subscriptions.subscribe(id, webview.label()).map(|_| id),
+        None => {
+            let id = store.lock().unwrap().reserve_id();
+            subscriptions.register(id, webview.label());
+            Ok(id)
+        }
+    }
+}
+
+#[tauri::command]
+async fn unsubscribe_scan(
+    id: u64,
+    webview: tauri::Webview,
+    subscriptions: State<'_, Mutex<ScanSubscriptions>>,
+) -> Result<(), String> {
+    subscriptions
+        .lock()
+        .unwrap()
+        .unsubscribe(id, webview.label());
+    Ok(())
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn get_list_directory(
     path: String,
-
-    app_handle: AppHandle,
+    context: Option<String>,
+    scan_id: Option<u64>,
+    ignore: Option<ignore_rules::IgnoreMode>,
+    determinate: Option<bool>,
+    webview: tauri::Webview,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+    history: State<'_, SizeHistory>,
+    contexts: State<'_, Mutex<ScanContexts>>,
+    subscriptions: State<'_, Mutex<ScanSubscriptions>>,
+    skip_list: State<'_, SkipList>,
 ) -> Result<DirectoryResult, String> {
+    let path = input_path(&path, Expect::Directory)?;
     let start_time = std::time::Instant::now();
+    let monitor = io_monitor::IoMonitor::start();
 
-    // 在闭包前克隆 app_handle
-    let app_handle_clone = app_handle.clone();
+    // 未先调用 subscribe_scan 时在这里预留 ID；发起扫描的窗口总是订阅者
+    let scan_id = scan_id.unwrap_or_else(|| store.lock().unwrap().reserve_id());
+    let subscribers = subscriptions
+        .lock()
+        .unwrap()
+        .register(scan_id, webview.label());
+    let (settings, timeouts, in_worker, ignore, mut overrides, determinate, skip_known, collation) = {
+        let config = config.lock().unwrap();
+        let overrides = config.get().scan_overrides.clone();
+        (
+            config.get().parallelism.clone(),
+            config.get().timeouts.clone(),
+            config.get().scan_in_worker,
+            // 未指定时依次使用该路径的扫描选项和设置中的忽略文件模式
+            ignore
+                .or_else(|| ignore_rules::override_mode(&overrides, Path::new(&path)))
+                .unwrap_or(config.get().ignore_mode),
+            overrides,
+            determinate.unwrap_or(config.get().determinate_scans),
+            config.get().skip_known_inaccessible,
+            config.get().name_collation,
+        )
+    };
+    // 之前的扫描中反复无权限读取或无响应的路径默认跳过
+    let skipped = if skip_known {
+        skip_list.skipped_under(Path::new(&path))
+    } else {
+        Vec::new()
+    };
+    overrides.extend(skipped.iter().filter_map(|p| skip_list::skip_override(p)));
+    let known_inaccessible = skipped.len() as u64;
+    let failures = ScanFailures::default();
+    // 超时时已统计完成的顶层条目作为部分结果返回
+    let partial = scan_context::PartialEntries::default();
+    // 长时间没有进度（常见于失去响应的网络挂载）时发送 scan-stalled 事件
+    let watchdog = start_watchdog(
+        webview.app_handle(),
+        "get_list_directory",
+        Some(scan_id),
+        &path,
+        &timeouts,
+    );
+    // 事件只发给订阅了该扫描的窗口，多个窗口同时扫描时互不干扰
+    let emitter = ScanEmitter::new(
+        webview.app_handle().clone(),
+        scan_id,
+        subscribers,
+        context.clone(),
+    )
+    .with_watchdog(watchdog.clone())
+    .with_failures(failures.clone())
+    .with_partial(partial.clone());
+    let emitter_clone = emitter.clone();
     // 发送开始事件
-    let _ = app_handle.emit("scan-started", ());
-
-    let result = spawn_blocking(move || {
-        let cli = Cli {
-            file: None,
-            long_format: true,
-            human_readable: true,
-            all: true,
-            show_time: true,
-            parallel: true,
-            sort: true,
-            name: None,
-            full_path: true,
-        };
+    emitter.emit("started", context.clone());
+    if !skipped.is_empty() {
+        emitter.emit("skipped_known", skipped);
+    }
+    let root = path.clone();
+    // 超时返回或命令被取消时结束仍在运行的工作进程
+    let worker = scan_worker::WorkerHandle::default();
+    let _worker_guard = worker.kill_on_drop();
 
-        // 修改 list_directory 以接受进度回调
-        list_directory_with_events(Path::new(&path), &cli, &app_handle)
+    let result = run_blocking_with_timeout(timeouts.scan_seconds, move || {
+        // 默认在独立进程中扫描，扫描器崩溃时自动重启而不影响界面
+        if in_worker {
+            scan_worker::scan_in_worker(
+                Path::new(&path),
+                &settings,
+                ignore,
+                &overrides,
+                determinate,
+                collation,
+                &emitter,
+                &worker,
+            )
+        } else {
+            scan_worker::scan_directory(
+                Path::new(&path),
+                &settings,
+                ignore,
+                &overrides,
+                determinate,
+                collation,
+                &emitter,
+            )
+            .map_err(|e| e.to_string())
+        }
     })
-    .await
-    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    .await;
+    watchdog.finish();
+    subscriptions.lock().unwrap().finish(scan_id);
+    // 本次扫描未能统计的目录，决定结果是否完整
+    let skipped = {
+        let mut failures = failures.lock().unwrap();
+        for path in watchdog.stalled_paths() {
+            failures.insert(path, FailureKind::Timeout);
+        }
+        if let Err(e) = skip_list.record(&failures) {
+            eprintln!("{}", e);
+        }
+        skip_list::skipped_counts(&failures, known_inaccessible)
+    };
+    let result = match result {
+        Ok(result) => result,
+        // 超时或任务异常：返回已统计完成的部分，不计入大小历史
+        Err(e) => {
+            let mut entries = std::mem::take(&mut *partial.lock().unwrap());
+            entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+            let config = config.lock().unwrap();
+            for entry in entries.iter_mut() {
+                entry.severity = config.get().severity_for(entry.size_raw);
+            }
+            drop(config);
+            let result = DirectoryResult {
+                scan_id: None,
+                query_time: start_time.elapsed().as_secs_f64(),
+                telemetry: Some(monitor.finish(&entries)),
+                scan_plan: None,
+                completeness: skipped.interrupted(&e, entries.len()),
+                skipped,
+                entries,
+            };
+            emitter_clone.emit("completed", context.clone());
+            let result = save_scan(&store, &root, Some(scan_id), result);
+            record_context(&contexts, &webview, context.as_deref(), &root, &result);
+            return Ok(result);
+        }
+    };
 
     match result {
-        Ok(entries) => {
-            let _ = app_handle_clone.emit("scan-completed", ());
+        Ok((entries, plan)) => {
+            emitter_clone.emit("completed", context.clone());
             let elapsed = start_time.elapsed().as_secs_f64();
-            Ok(DirectoryResult {
+            let telemetry = monitor.finish(&entries);
+            let result = DirectoryResult {
+                scan_id: None,
                 entries,
                 query_time: elapsed,
-            })
+                telemetry: Some(telemetry),
+                scan_plan: Some(plan),
+                completeness: skipped.completeness(),
+                skipped,
+            };
+            let result = store_result(
+                &webview,
+                &store,
+                &config,
+                &history,
+                &root,
+                Some(scan_id),
+                result,
+            );
+            record_context(&contexts, &webview, context.as_deref(), &root, &result);
+            Ok(result)
         }
         Err(e) => {
-            let _ = app_handle_clone.emit("scan-error", e.to_string());
+            emitter_clone.emit("error", e.clone());
             Err(format!("Error listing directory: {}", e))
         }
     }
 }
-#[tauri::command]
-async fn delete_file(path: String, force: bool) -> Result<(), String> {
-    let path = Path::new(&path);
 
-    // 检查路径是否存在
-    if !path.exists() {
-        return Err("路径不存在".to_string());
+// 打开一个新的扫描窗口，用于并排比较两个目录
+#[tauri::command]
+async fn open_scan_window(
+    app_handle: AppHandle,
+    label: String,
+    title: Option<String>,
+) -> Result<(), String> {
+    // 权限配置按 label 前缀授予扫描窗口调用命令的能力
+    if !label.starts_with("scan-") {
+        return Err("扫描窗口的 label 必须以 scan- 开头".to_string());
     }
+    if let Some(window) = app_handle.get_webview_window(&label) {
+        return window
+            .set_focus()
+            .map_err(|e| format!("无法切换到窗口: {}", e));
+    }
+    tauri::WebviewWindowBuilder::new(
+        &app_handle,
+        label,
+        tauri::WebviewUrl::App("index.html".into()),
+    )
+    .title(title.unwrap_or_else(|| "DiskSight".to_string()))
+    .inner_size(1200.0, 800.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("无法创建窗口: {}", e))
+}
 
-    // 检查路径是否可写
-    match fs::metadata(&path) {
-        Ok(metadata) => {
-            if metadata.permissions().readonly() {
-                if !force {
-                    return Err("路径是只读的。如要强制删除，请设置 force 参数为 true".to_string());
-                }
-                // 尝试移除只读属性
-                let mut perms = metadata.permissions();
-                perms.set_readonly(false);
-                if let Err(e) = fs::set_permissions(path, perms) {
-                    return Err(format!("无法修改路径权限: {}", e));
-                }
-            }
+// 列出各窗口、标签页的扫描上下文
+#[tauri::command]
+async fn list_scan_contexts(
+    contexts: State<'_, Mutex<ScanContexts>>,
+) -> Result<Vec<scan_context::ScanContextInfo>, String> {
+    Ok(contexts.lock().unwrap().list())
+}
+
+// 关闭标签页时移除其扫描上下文，可选同时释放其扫描结果
+#[tauri::command]
+async fn close_scan_context(
+    context: String,
+    release_scans: bool,
+    contexts: State<'_, Mutex<ScanContexts>>,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<(), String> {
+    let removed = contexts.lock().unwrap().remove(&context);
+    if let (Some(info), true) = (removed, release_scans) {
+        let mut store = store.lock().unwrap();
+        for scan_id in info.scan_ids {
+            store.remove(scan_id);
         }
-        Err(e) => return Err(format!("无法访问路径: {}", e)),
     }
+    Ok(())
+}
+#[tauri::command]
+async fn delete_file(
+    path: String,
+    force: bool,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    let target = std::path::PathBuf::from(&path);
+    // 统计大小与删除都可能遍历整个目录
+    let (bytes, result) = spawn_blocking(move || {
+        let bytes = entry_size(&target);
+        (bytes, deletion::delete_path(&RealFs, &target, force))
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    audit.record(AuditAction::Delete, &path, None, bytes, &result);
+    result
+}
 
-    // 根据路径类型选择删除方法
-    let result = if path.is_file() {
-        fs::remove_file(path)
-    } else if path.is_dir() {
-        // 对于目录，需要递归删除
-        fs::remove_dir_all(path)
+// 审计记录中的数据量：目录为总大小，文件为文件大小
+fn entry_size(target: &Path) -> u64 {
+    if target.is_dir() {
+        dir_size(target)
     } else {
-        return Err("无效的路径类型".to_string());
-    };
+        fs::metadata(target).map(|m| m.len()).unwrap_or(0)
+    }
+}
 
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => match e.raw_os_error() {
-            Some(5) => Err("权限不足，请以管理员身份运行程序或检查路径权限".to_string()),
-            Some(32) => Err("文件或目录正在被其他程序使用".to_string()),
-            Some(2) => Err("文件或目录不存在".to_string()),
-            Some(145) => Err("目录不为空".to_string()),
-            _ => Err(format!("删除失败: {}", e)),
-        },
+// 把条目移入同一卷上当天的隔离目录，保留原来的相对路径，超过保留天数后自动清除
+#[tauri::command]
+async fn quarantine(
+    paths: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<quarantine::QuarantineOutcome, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Exists)?;
+    let outcome = spawn_blocking(move || quarantine::quarantine(&paths))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    for item in &outcome.moved {
+        audit.record(
+            AuditAction::Move,
+            &item.original,
+            Some(&item.quarantined),
+            item.bytes,
+            &Ok(()),
+        );
+    }
+    for (path, error) in &outcome.errors {
+        audit.record(AuditAction::Move, path, None, 0, &Err(error.clone()));
     }
+    Ok(outcome)
 }
-// 创建一个结构，用于跟踪前端任务完成情况
-// 设置相关任务
-struct SetupState {
-    frontend_task: bool,
-    backend_task: bool,
+
+// 各卷上的隔离批次及其自动清除日期
+#[tauri::command]
+async fn list_quarantine(
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<quarantine::QuarantineBatch>, String> {
+    let days = config.lock().unwrap().get().quarantine_days;
+    spawn_blocking(move || quarantine::list_batches(days))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
 }
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .manage(Mutex::new(SetupState {
-            frontend_task: false,
-            backend_task: false,
-        }))
-        // 添加我们用于检查的命令
-        .plugin(tauri_plugin_notification::init())
-        .plugin(tauri_plugin_positioner::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            get_list_directory,
-            calculate_dir_size_simple_fast,
-            delete_file,
-            set_complete
-        ])
-        .setup(|app| {
-            // Spawn 操作设置为一个非阻塞任务，以便在它执行的同时可以创建并运行窗口。
-            spawn(setup(app.handle().clone()));
-            // 钩子期望返回一个 Ok 的结果
-            Ok(())
-        })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+
+// 把隔离目录中的条目移回原处，返回原路径
+#[tauri::command]
+async fn restore_quarantined(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<String, String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    let result = quarantine::restore(Path::new(&path));
+    audit.record(
+        AuditAction::Move,
+        &path,
+        result.as_ref().ok().map(String::as_str),
+        0,
+        &result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+    );
+    result
 }
 
-// 一个用于设置初始化任务状态的自定义任务
+// 立即清除超过保留天数的隔离批次，返回释放的空间和失败的批次
 #[tauri::command]
-async fn set_complete(
-    app: AppHandle,
-    state: State<'_, Mutex<SetupState>>,
-    task: String,
-) -> Result<(), ()> {
-    // 以只读方式锁定 `State`
-    let mut state_lock = state.lock().unwrap();
-    match task.as_str() {
-        "frontend" => state_lock.frontend_task = true,
-        "backend" => state_lock.backend_task = true,
-        _ => panic!("invalid task completed!"),
-    }
-    // 检查两个任务是否都已完成
-    if state_lock.backend_task && state_lock.frontend_task {
-        // 设置都已完成，我们可以关闭启动画面并且显示 main 窗口了
-        let splash_window = app.get_webview_window("splashscreen").unwrap();
-        let main_window = app.get_webview_window("main").unwrap();
-        splash_window.close().unwrap();
-        main_window.show().unwrap();
+async fn purge_quarantine(
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<(u64, Vec<(String, String)>), String> {
+    ensure_writable(&config)?;
+    let days = config.lock().unwrap().get().quarantine_days;
+    let (freed, errors) = spawn_blocking(move || quarantine::purge_expired(days))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    audit.record(
+        AuditAction::Delete,
+        quarantine::QUARANTINE_DIR,
+        None,
+        freed,
+        &Ok(()),
+    );
+    Ok((freed, errors))
+}
+
+// 把目录压缩到 destination_dir，核对归档后再移到回收站。立即返回任务 ID，
+// 进度通过 archive-progress 事件报告（context 为任务 ID），结束时发送 archive-finished 事件
+#[tauri::command]
+async fn archive_then_delete(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    destination_dir: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    jobs: State<'_, ArchiveJobs>,
+) -> Result<u64, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Directory)?;
+    let destination = input_path(&destination_dir, Expect::Directory)?;
+    for path in &paths {
+        if let Some(reason) = deletion::refuse_deletion(Path::new(path)) {
+            return Err(format!("{}: {}", path, reason));
+        }
     }
-    Ok(())
+    let (job_id, cancel) = jobs.start();
+    spawn_blocking(move || {
+        let on_stage = |stage: archive_job::ArchiveStage, path: &str, done: usize, total: usize| {
+            let _ = app_handle.emit(
+                "archive-progress",
+                ProgressEvent {
+                    current_path: path.to_string(),
+                    current_file: String::new(),
+                    status: ProgressStatus::Stage {
+                        stage: stage.name().to_string(),
+                        done: done as u64,
+                        total: total as u64,
+                    },
+                    context: Some(job_id.to_string()),
+                    scan_id: None,
+                },
+            );
+        };
+        let report =
+            archive_job::run_job(job_id, &paths, Path::new(&destination), &cancel, &on_stage);
+        let audit = app_handle.state::<AuditLog>();
+        for item in &report.items {
+            let result = match &item.trash_error {
+                Some(e) => Err(e.clone()),
+                None => Ok(()),
+            };
+            audit.record(
+                AuditAction::Trash,
+                &item.source,
+                Some(&item.archive),
+                item.archive_bytes,
+                &result,
+            );
+        }
+        app_handle.state::<ArchiveJobs>().finish(job_id);
+        let _ = app_handle.emit("archive-finished", report);
+    });
+    Ok(job_id)
 }
 
-// 一个异步函数，用于执行一些耗时的设置任务
-async fn setup(app: AppHandle) -> Result<(), ()> {
-    // 模拟执行一些耗时的设置任务，3秒后完成
-    println!("Performing really heavy backend setup task...");
-    sleep(Duration::from_secs(1)).await;
-    println!("Backend setup task completed!");
-    // 设置后端任务为已完成
-    // 可以像普通函数一样运行命令，但需要自己处理输入参数
-    set_complete(
-        app.clone(),
-        app.state::<Mutex<SetupState>>(),
-        "backend".to_string(),
-    )
-    .await?;
-    Ok(())
+// 取消归档任务；已完成的目录不受影响
+#[tauri::command]
+async fn cancel_archive_job(job_id: u64, jobs: State<'_, ArchiveJobs>) -> Result<(), String> {
+    jobs.cancel(job_id)
+}
+
+// 查找 iOS 备份与 Android 模拟器镜像
+#[tauri::command]
+async fn find_mobile_backups() -> Result<Vec<analyzers::mobile_backups::MobileBackupEntry>, String>
+{
+    spawn_blocking(analyzers::mobile_backups::find_backups)
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 查找 Unity、Unreal 项目中可重新生成的缓存目录及引擎的全局缓存
+#[tauri::command]
+async fn find_engine_caches(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<analyzers::engine_caches::EngineCacheEntry>, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        analyzers::engine_caches::find_engine_caches(Path::new(&path))
+    })
+    .await
+}
+// 查找机器学习模型与数据集缓存、conda 软件包缓存以及目录下的大型模型文件
+#[tauri::command]
+async fn find_ml_caches(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<analyzers::ml_caches::MlCacheEntry>, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        analyzers::ml_caches::find_ml_caches(Path::new(&path))
+    })
+    .await
+}
+// 查找同时保存了 RAW 与 JPEG 的照片，按拍摄目录分组
+#[tauri::command]
+async fn find_raw_jpeg_pairs(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<analyzers::raw_jpeg::ShootFolder>, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        analyzers::raw_jpeg::find_raw_jpeg_pairs(Path::new(&path))
+    })
+    .await
+}
+// 统计截图、录屏文件，按所在目录和月份分组
+#[tauri::command]
+async fn find_screenshots(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<analyzers::screenshots::ScreenshotReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        analyzers::screenshots::find_screenshots(Path::new(&path))
+    })
+    .await
+}
+// 统计目录中最大的音视频文件的编码、分辨率、码率和每小时占用，找出可重新编码的文件
+#[tauri::command]
+async fn media_stats(
+    path: String,
+    limit: Option<usize>,
+    probe: Option<bool>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<media::MediaReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let timeout = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(timeout, move || {
+        media::media_report(Path::new(&path), limit.unwrap_or(50), probe.unwrap_or(true))
+    })
+    .await
+}
+// 列出内置分析器和已加载的插件分析器
+#[tauri::command]
+async fn list_analyzers(
+    registry: State<'_, Mutex<AnalyzerRegistry>>,
+) -> Result<Vec<analyzers::AnalyzerInfo>, String> {
+    Ok(registry.lock().unwrap().list())
+}
+
+// 用指定的分析器分析目录
+#[tauri::command]
+async fn run_analyzer(
+    name: String,
+    path: String,
+    registry: State<'_, Mutex<AnalyzerRegistry>>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<analyzers::AnalyzerReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let analyzer = registry
+        .lock()
+        .unwrap()
+        .get(&name)
+        .ok_or_else(|| format!("分析器不存在: {}", name))?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || analyzer.scan(Path::new(&path))).await?
+}
+
+// 重新加载插件目录下的分析器，返回加载失败的插件及原因
+#[tauri::command]
+async fn reload_analyzers(
+    app_handle: AppHandle,
+    registry: State<'_, Mutex<AnalyzerRegistry>>,
+) -> Result<Vec<(String, String)>, String> {
+    let dir = analyzer_plugin_dir(&app_handle)?;
+    Ok(registry.lock().unwrap().load_plugins(&dir))
+}
+
+fn analyzer_plugin_dir(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join("analyzers"))
+        .map_err(|e| format!("无法获取数据目录: {}", e))
+}
+// 当前系统支持的功能，前端据此隐藏不可用的操作
+#[tauri::command]
+async fn get_platform_capabilities() -> Result<platform::PlatformCapabilities, String> {
+    spawn_blocking(platform::capabilities)
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 各磁盘容量及 S.M.A.R.T. 健康状态
+#[tauri::command]
+async fn drive_health() -> Result<Vec<drives::DriveInfo>, String> {
+    spawn_blocking(drives::drive_health)
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 按属主汇总目录占用
+#[tauri::command]
+async fn owner_usage(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<owners::OwnerUsage>, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || owners::aggregate_by_owner(Path::new(&path))).await
+}
+
+// 列出每个用户配置文件的大小和最大的子目录；elevated 为 true 时通过管理员辅助进程统计，
+// 包括其他用户无权限读取的目录
+#[tauri::command]
+async fn profiles_report(
+    elevated: Option<bool>,
+    helper: State<'_, ElevatedHelper>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<profiles::UserProfile>, String> {
+    if elevated.unwrap_or(false) {
+        let helper = helper.inner().clone();
+        let response = spawn_blocking(move || helper.request(HelperRequest::ProfilesReport))
+            .await
+            .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+        return match response {
+            HelperResponse::Profiles { profiles } => Ok(profiles),
+            other => Err(format!("辅助进程回复异常: {:?}", other)),
+        };
+    }
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, profiles::profiles_report).await?
+}
+
+// 查询配置中的所有代理，汇总各主机的卷容量；path 不为空时各主机同时扫描该路径
+#[tauri::command]
+async fn agent_dashboard(
+    path: Option<String>,
+    limit: Option<usize>,
+    force: Option<bool>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<agent::MultiHostReport, String> {
+    let (agents, seconds, power) = {
+        let config = config.lock().unwrap();
+        let config = config.get();
+        (
+            config.agents.clone(),
+            config.timeouts.analysis_seconds,
+            config.power.clone(),
+        )
+    };
+    // 计费网络上只查询卷容量，远程扫描需要 force 确认
+    if path.is_some() && !force.unwrap_or(false) {
+        let state = spawn_blocking(move || power::current(&power))
+            .await
+            .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+        if state.defer_remote {
+            return Err(power::remote_deferred());
+        }
+    }
+    let timeout = seconds.map(std::time::Duration::from_secs);
+    spawn_blocking(move || {
+        agent::query_agents(&agents, path.as_deref(), limit.unwrap_or(20), timeout)
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 各用户实际占用与文件系统配额对比（Linux）
+#[tauri::command]
+async fn quota_report(path: String) -> Result<quota::QuotaReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    spawn_blocking(move || quota::quota_report(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+// 对比所在卷的已用空间与文件总大小，说明"消失的空间"去了哪里
+#[tauri::command]
+async fn space_reconciliation(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<space_map::SpaceReconciliation, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || space_map::reconcile_volume(Path::new(&path))).await?
+}
+// 统计 macOS 卷的可清除空间和 Time Machine 本地快照，说明与 Finder 显示的可用空间的差别
+#[tauri::command]
+async fn purgeable_space(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<purgeable::PurgeableReport, String> {
+    let path = input_path(&path, Expect::Any)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || purgeable::purgeable_report(Path::new(&path))).await?
+}
+// 删除 Time Machine 本地快照，直到释放指定的空间，返回实际增加的可用空间
+#[tauri::command]
+async fn thin_local_snapshots(
+    path: String,
+    bytes: u64,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<u64, String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Any)?;
+    let target = path.clone();
+    let result = spawn_blocking(move || purgeable::thin_local_snapshots(Path::new(&target), bytes))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    audit.record(
+        AuditAction::Delete,
+        &path,
+        None,
+        *result.as_ref().unwrap_or(&0),
+        &result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+    );
+    result
+}
+// 路径位于 btrfs 或 ZFS 时列出子卷、数据集的引用空间、独占空间和快照数量，否则返回 None
+#[tauri::command]
+async fn cow_volumes(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Option<cow_volumes::CowVolumeReport>, String> {
+    let path = input_path(&path, Expect::Any)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        cow_volumes::cow_volume_report(Path::new(&path))
+    })
+    .await?
+}
+// 统计按簇取整浪费的空间，找出适合打包归档或改用更小簇大小的目录
+#[tauri::command]
+async fn slack_report(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<slack::SlackReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || slack::slack_report(Path::new(&path))).await?
+}
+// 查找目录树中的 git 仓库，统计每个仓库的 .git、LFS 对象与工作区大小
+#[tauri::command]
+async fn find_git_repos(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<git_repos::GitRepoReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || git_repos::find_git_repos(Path::new(&path))).await
+}
+// 在仓库全部历史中找出最大的文件内容，附带 git filter-repo 清理参考
+#[tauri::command]
+async fn find_large_blobs(
+    path: String,
+    limit: Option<usize>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<git_repos::LargeBlobReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let timeout = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(timeout, move || {
+        git_repos::large_blobs(Path::new(&path), limit.unwrap_or(50))
+    })
+    .await?
+}
+// 对选中的仓库依次执行 git gc --aggressive，单个仓库失败不影响其他仓库
+#[tauri::command]
+async fn git_gc(
+    paths: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<git_repos::GcOutcome>, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Directory)?;
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || {
+        paths
+            .iter()
+            .map(|path| git_repos::gc_aggressive(Path::new(path)))
+            .collect()
+    })
+    .await
+}
+// 通过管理员辅助进程统计普通权限无法读取的目录大小，首次调用时弹出提权确认
+#[tauri::command]
+async fn elevated_dir_size(path: String, helper: State<'_, ElevatedHelper>) -> Result<u64, String> {
+    // 普通权限可能无法确认目录是否存在，交给辅助进程检查
+    let path = input_path(&path, Expect::Any)?;
+    let helper = helper.inner().clone();
+    let response = spawn_blocking(move || helper.request(HelperRequest::DirSize { path }))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    match response {
+        HelperResponse::Size { bytes } => Ok(bytes),
+        other => Err(format!("辅助进程回复异常: {:?}", other)),
+    }
+}
+// 通过管理员辅助进程读取 NTFS 卷的 MFT 大小（Windows）
+#[tauri::command]
+async fn elevated_mft_size(
+    volume: String,
+    helper: State<'_, ElevatedHelper>,
+) -> Result<u64, String> {
+    if !platform::supports_mft_scan() {
+        return Err(platform::unsupported("读取 MFT"));
+    }
+    let helper = helper.inner().clone();
+    let response = spawn_blocking(move || helper.request(HelperRequest::MftSize { volume }))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    match response {
+        HelperResponse::Size { bytes } => Ok(bytes),
+        other => Err(format!("辅助进程回复异常: {:?}", other)),
+    }
+}
+// 通过管理员辅助进程清理系统缓存目录，返回释放的空间及删除失败的条目
+#[tauri::command]
+async fn clean_system_cache(
+    cache: elevation::SystemCache,
+    helper: State<'_, ElevatedHelper>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<(u64, Vec<String>), String> {
+    ensure_writable(&config)?;
+    let helper = helper.inner().clone();
+    let response =
+        spawn_blocking(move || helper.request(HelperRequest::CleanSystemCache { cache }))
+            .await
+            .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    match response {
+        HelperResponse::Cleaned {
+            freed_bytes,
+            errors,
+        } => {
+            if let Some(dir) = cache.path() {
+                let result = if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors.join("\n"))
+                };
+                audit.record(
+                    AuditAction::Delete,
+                    &dir.to_string_lossy(),
+                    None,
+                    freed_bytes,
+                    &result,
+                );
+            }
+            Ok((freed_bytes, errors))
+        }
+        other => Err(format!("辅助进程回复异常: {:?}", other)),
+    }
+}
+// 统计 journald 日志、旧内核与软件包缓存（Linux）
+#[tauri::command]
+async fn linux_system_items() -> Result<Vec<analyzers::linux_system::LinuxSystemItem>, String> {
+    spawn_blocking(analyzers::linux_system::find_linux_system_items)
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 查找 flatpak 运行时与 snap 的各个版本，标出未使用的运行时和保留的旧版本（Linux）
+#[tauri::command]
+async fn find_flatpak_snap() -> Result<Vec<analyzers::flatpak_snap::SandboxEntry>, String> {
+    spawn_blocking(analyzers::flatpak_snap::find_sandbox_entries)
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 运行系统自带的清理命令，需要 root 的通过管理员辅助进程执行，返回根分区可用空间的增加量
+#[tauri::command]
+async fn linux_cleanup(
+    cleanup: analyzers::linux_system::LinuxCleanup,
+    helper: State<'_, ElevatedHelper>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<u64, String> {
+    ensure_writable(&config)?;
+    let command = cleanup
+        .command()
+        .ok_or_else(|| "不支持此清理操作".to_string())?
+        .join(" ");
+    let helper = helper.inner().clone();
+    let result = spawn_blocking(move || {
+        if !cleanup.needs_root() {
+            return analyzers::linux_system::run_cleanup(&cleanup);
+        }
+        match helper.request(HelperRequest::LinuxCleanup { cleanup })? {
+            HelperResponse::Cleaned { freed_bytes, .. } => Ok(freed_bytes),
+            other => Err(format!("辅助进程回复异常: {:?}", other)),
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    audit.record(
+        AuditAction::Delete,
+        &command,
+        None,
+        *result.as_ref().unwrap_or(&0),
+        &result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+    );
+    result
+}
+// 统计 Windows Installer、DriverStore 与 WinSxS 的大小，并说明能否手动处理
+#[tauri::command]
+async fn windows_system_folders(
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<windows_system::SystemFolder>, String> {
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, windows_system::system_folders).await?
+}
+// 通过管理员辅助进程运行 DISM 分析组件存储，得到实际占用和可清理的部分
+#[tauri::command]
+async fn analyze_component_store(
+    helper: State<'_, ElevatedHelper>,
+) -> Result<windows_system::ComponentStoreReport, String> {
+    let helper = helper.inner().clone();
+    let response = spawn_blocking(move || helper.request(HelperRequest::AnalyzeComponentStore))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    match response {
+        HelperResponse::ComponentStore { report } => Ok(report),
+        other => Err(format!("辅助进程回复异常: {:?}", other)),
+    }
+}
+// 通过管理员辅助进程运行 DISM 清理组件存储，返回系统盘可用空间的增加量
+#[tauri::command]
+async fn component_cleanup(
+    reset_base: bool,
+    helper: State<'_, ElevatedHelper>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<u64, String> {
+    ensure_writable(&config)?;
+    let helper = helper.inner().clone();
+    let result =
+        spawn_blocking(move || helper.request(HelperRequest::ComponentCleanup { reset_base }))
+            .await
+            .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+            .and_then(|response| match response {
+                HelperResponse::Cleaned { freed_bytes, .. } => Ok(freed_bytes),
+                other => Err(format!("辅助进程回复异常: {:?}", other)),
+            });
+    let path = windows_system::SystemFolderKind::WinSxS.path();
+    audit.record(
+        AuditAction::Delete,
+        &path.to_string_lossy(),
+        None,
+        *result.as_ref().unwrap_or(&0),
+        &result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+    );
+    result
+}
+// 汇总各类可清理内容，按可释放空间排序给出清理建议；重复文件和旧大文件默认在主目录下查找
+#[tauri::command]
+async fn get_recommendations(
+    app_handle: AppHandle,
+    path: Option<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<recommendations::Recommendation>, String> {
+    let root = match path {
+        Some(path) => input_path(&path, Expect::Directory)?,
+        None => home_dir()
+            .ok_or_else(|| "找不到用户主目录".to_string())?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let (app_config, timeouts) = {
+        let config = config.lock().unwrap();
+        (config.get().clone(), config.get().timeouts.clone())
+    };
+    let watchdog = start_watchdog(&app_handle, "get_recommendations", None, &root, &timeouts);
+    let guard = watchdog.clone();
+    let result = run_blocking_with_timeout(timeouts.analysis_seconds, move || {
+        let skip = |path: &str| {
+            watchdog.touch(Path::new(path));
+            app_config.is_acknowledged(path)
+        };
+        recommendations::recommend(Path::new(&root), &skip)
+    })
+    .await;
+    guard.finish();
+    result
+}
+// 执行清理建议附带的操作，返回释放的空间及失败的条目
+#[tauri::command]
+async fn apply_recommendation(
+    action: recommendations::RecommendationAction,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<(u64, Vec<String>), String> {
+    use recommendations::RecommendationAction;
+    ensure_writable(&config)?;
+    let paths = match &action {
+        RecommendationAction::Delete { paths } | RecommendationAction::Trash { paths } => {
+            input_paths(paths, Expect::Exists)?
+        }
+        RecommendationAction::EmptyTrash => {
+            return spawn_blocking(recommendations::empty_trash)
+                .await
+                .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+        }
+    };
+    let trash = matches!(action, RecommendationAction::Trash { .. });
+    let results = spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| {
+                let target = Path::new(&path);
+                let bytes = entry_size(target);
+                let (kind, result) = if trash {
+                    (
+                        AuditAction::Trash,
+                        match deletion::refuse_deletion(target) {
+                            Some(reason) => Err(reason.to_string()),
+                            None if !platform::supports_trash() => {
+                                Err(platform::unsupported("回收站"))
+                            }
+                            None => {
+                                trash::delete(target).map_err(|e| format!("移到回收站失败: {}", e))
+                            }
+                        },
+                    )
+                } else {
+                    (
+                        AuditAction::Delete,
+                        deletion::delete_path(&RealFs, target, false),
+                    )
+                };
+                (path, kind, bytes, result)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    let mut freed = 0u64;
+    let mut errors = Vec::new();
+    for (path, kind, bytes, result) in results {
+        audit.record(kind, &path, None, bytes, &result);
+        match result {
+            Ok(()) => freed += bytes,
+            Err(e) => errors.push(format!("{}: {}", path, e)),
+        }
+    }
+    Ok((freed, errors))
+}
+// 对已保存的扫描结果按扩展名、属主、时间或一级目录分组汇总
+#[tauri::command]
+async fn group_entries(
+    scan_id: u64,
+    key: grouping::GroupKey,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<Vec<grouping::GroupAggregate>, String> {
+    let scan = store.lock().unwrap().get(scan_id)?.clone();
+    spawn_blocking(move || grouping::group_entries(&scan.root, &scan.result.entries, key))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 完整扫描目录（不按忽略文件跳过内容，只应用该路径的扫描选项），供基准清单保存与对比使用
+async fn scan_for_baseline(
+    path: &str,
+    config: &State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<FileEntry>, String> {
+    let (settings, limit, overrides) = {
+        let config = config.lock().unwrap();
+        let config = config.get();
+        (
+            config.parallelism.clone(),
+            config.timeouts.scan_seconds,
+            config.scan_overrides.clone(),
+        )
+    };
+    let path = path.to_string();
+    run_blocking_with_timeout(limit, move || {
+        let emitter = ScanEmitter::forwarding(0, None, |_, _| {});
+        scan_worker::scan_directory(
+            Path::new(&path),
+            &settings,
+            ignore_rules::IgnoreMode::Off,
+            &overrides,
+            false,
+            collation::NameCollation::default(),
+            &emitter,
+        )
+        .map(|(entries, _)| entries)
+        .map_err(|e| e.to_string())
+    })
+    .await?
+}
+
+// 扫描目录并保存为基准清单，替换该目录原有的清单
+#[tauri::command]
+async fn save_baseline(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    baselines: State<'_, BaselineStore>,
+) -> Result<baseline::BaselineSummary, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let entries = scan_for_baseline(&path, &config).await?;
+    baselines.save(Path::new(&path), &entries)
+}
+
+#[tauri::command]
+async fn get_baseline(
+    path: String,
+    baselines: State<'_, BaselineStore>,
+) -> Result<baseline::BaselineSummary, String> {
+    let path = input_path(&path, Expect::Any)?;
+    baselines.summary(Path::new(&path))
+}
+
+#[tauri::command]
+async fn delete_baseline(path: String, baselines: State<'_, BaselineStore>) -> Result<(), String> {
+    let path = input_path(&path, Expect::Any)?;
+    baselines.delete(Path::new(&path))
+}
+
+// 重新扫描目录并与基准清单对比，列出超过阈值（默认 100MB）的意外新增、增长与缺失
+#[tauri::command]
+async fn compare_to_baseline(
+    path: String,
+    threshold_bytes: Option<u64>,
+    config: State<'_, Mutex<ConfigStore>>,
+    baselines: State<'_, BaselineStore>,
+) -> Result<baseline::BaselineReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let entries = scan_for_baseline(&path, &config).await?;
+    baselines.compare(
+        Path::new(&path),
+        &entries,
+        threshold_bytes.unwrap_or(100 * 1024 * 1024),
+    )
+}
+
+// 生成扫描结果的 Markdown 摘要，可直接粘贴到工单中
+#[tauri::command]
+async fn summarize_markdown(
+    scan_id: u64,
+    top_n: Option<usize>,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<String, String> {
+    let scan = store.lock().unwrap().get(scan_id)?.clone();
+    spawn_blocking(move || summary::summarize_markdown(&scan, top_n.unwrap_or(10)))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 项目视图：按项目类型归类已扫描目录下的项目，并拆分源码、依赖与构建产物的占用
+#[tauri::command]
+async fn project_view(
+    scan_id: u64,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<projects::ProjectReport, String> {
+    let root = store.lock().unwrap().get(scan_id)?.root.clone();
+    let limit = config.lock().unwrap().get().timeouts.analysis_seconds;
+    run_blocking_with_timeout(limit, move || projects::project_report(&root)).await
+}
+// 只重新计算结果中的某个条目并修正保存的结果，无需重新扫描整个目录
+#[tauri::command]
+async fn rescan_entry(
+    scan_id: u64,
+    path: String,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<DirectoryResult, String> {
+    let path = input_path(&path, Expect::Any)?;
+    if !store
+        .lock()
+        .unwrap()
+        .get(scan_id)?
+        .result
+        .entries
+        .iter()
+        .any(|e| e.has_path(&path))
+    {
+        return Err(format!("{} 不在扫描结果中", path));
+    }
+    let target = path.clone();
+    let updated = spawn_blocking(move || {
+        let target = Path::new(&target);
+        if fs::symlink_metadata(target).is_err() {
+            return Ok(None);
+        }
+        stat_entry(target, true)
+            .map(Some)
+            .map_err(|e| format!("无法重新计算 {}: {}", target.display(), e))
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+
+    let mut store = store.lock().unwrap();
+    let scan = store.get_mut(scan_id)?;
+    scan.patch_entry(&path, updated)?;
+    let config = config.lock().unwrap();
+    for entry in scan.result.entries.iter_mut() {
+        entry.severity = config.get().severity_for(entry.size_raw);
+    }
+    Ok(scan.result.clone())
+}
+// 对齐两次扫描结果，供左右分栏对比视图直接渲染
+#[tauri::command]
+async fn compare_view(
+    scan_a: u64,
+    scan_b: u64,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<compare::CompareView, String> {
+    let (a, b) = {
+        let store = store.lock().unwrap();
+        (store.get(scan_a)?.clone(), store.get(scan_b)?.clone())
+    };
+    spawn_blocking(move || compare::compare_view(&a, &b))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 查找内容相同的文件，按物理磁盘调度哈希计算，进度通过 hash-progress 事件报告
+#[tauri::command]
+async fn find_duplicates(
+    app_handle: AppHandle,
+    path: String,
+    min_size: u64,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<duplicates::DuplicateReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let root = Path::new(&path).to_path_buf();
+    let timeouts = config.lock().unwrap().get().timeouts.clone();
+    let watchdog = start_watchdog(&app_handle, "find_duplicates", None, &path, &timeouts);
+    let guard = watchdog.clone();
+    let result = run_blocking_with_timeout(timeouts.analysis_seconds, move || {
+        let on_progress = |stage: &str, done: usize, total: usize| {
+            watchdog.touch(&root);
+            if done.is_multiple_of(100) || done == total {
+                let _ = app_handle.emit(
+                    "hash-progress",
+                    ProgressEvent {
+                        current_path: path.clone(),
+                        current_file: String::new(),
+                        status: ProgressStatus::Stage {
+                            stage: stage.to_string(),
+                            done: done as u64,
+                            total: total as u64,
+                        },
+                        context: None,
+                        scan_id: None,
+                    },
+                );
+            }
+        };
+        duplicates::find_duplicates(&root, min_size, &on_progress)
+    })
+    .await;
+    guard.finish();
+    result
+}
+
+// 查找大部分内容相同的大文件，报告相似度与共享数据量
+#[tauri::command]
+async fn find_similar_files(
+    app_handle: AppHandle,
+    path: String,
+    min_size: u64,
+    min_similarity: f64,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<duplicates::SimilarityReport, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let root = Path::new(&path).to_path_buf();
+    let timeouts = config.lock().unwrap().get().timeouts.clone();
+    let watchdog = start_watchdog(&app_handle, "find_similar_files", None, &path, &timeouts);
+    let guard = watchdog.clone();
+    let result = run_blocking_with_timeout(timeouts.analysis_seconds, move || {
+        let on_progress = |done: usize, total: usize| {
+            watchdog.touch(&root);
+            let _ = app_handle.emit(
+                "hash-progress",
+                ProgressEvent {
+                    current_path: path.clone(),
+                    current_file: String::new(),
+                    status: ProgressStatus::Stage {
+                        stage: "chunk".to_string(),
+                        done: done as u64,
+                        total: total as u64,
+                    },
+                    context: None,
+                    scan_id: None,
+                },
+            );
+        };
+        duplicates::find_similar(&root, min_size, min_similarity, &on_progress)
+    })
+    .await;
+    guard.finish();
+    result
+}
+
+// 用 reflink 让重复文件共享数据块，paths[0] 为保留的源文件
+#[tauri::command]
+async fn deduplicate(
+    paths: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<dedupe::DedupeReport, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Exists)?;
+    let report = spawn_blocking(move || dedupe::deduplicate(&paths))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    record_dedupe(&audit, AuditAction::Reflink, &report);
+    Ok(report)
+}
+
+// 不支持 reflink 时，把重复文件替换为 paths[0] 的硬链接
+#[tauri::command]
+async fn hardlink_duplicates(
+    paths: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<dedupe::DedupeReport, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Exists)?;
+    let report = spawn_blocking(move || dedupe::hardlink_duplicates(&paths))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    record_dedupe(&audit, AuditAction::HardLink, &report);
+    Ok(report)
+}
+
+// 为去重中替换过（或尝试替换）的每个文件写入审计记录，内容不同而跳过的不记录
+fn record_dedupe(audit: &AuditLog, action: AuditAction, report: &dedupe::DedupeReport) {
+    for outcome in &report.outcomes {
+        let result = match outcome.status {
+            dedupe::DedupeStatus::Deduplicated => Ok(()),
+            dedupe::DedupeStatus::Failed => Err(outcome.error.clone().unwrap_or_default()),
+            dedupe::DedupeStatus::Skipped => continue,
+        };
+        audit.record(
+            action,
+            &outcome.path,
+            Some(&report.source),
+            outcome.reclaimed_bytes,
+            &result,
+        );
+    }
+}
+
+// 保存当前会话：所有扫描结果及前端传来的筛选、选中、导航等状态
+#[tauri::command]
+async fn save_session(
+    name: String,
+    ui_state: serde_json::Value,
+    store: State<'_, Mutex<ScanStore>>,
+    sessions: State<'_, SessionStore>,
+) -> Result<(), String> {
+    let store = store.lock().unwrap();
+    sessions.save(&name, &store, ui_state)
+}
+
+// 恢复会话，扫描结果重新放入内存并分配新的扫描 ID
+#[tauri::command]
+async fn load_session(
+    name: String,
+    store: State<'_, Mutex<ScanStore>>,
+    sessions: State<'_, SessionStore>,
+) -> Result<sessions::LoadedSession, String> {
+    let mut store = store.lock().unwrap();
+    sessions.load(&name, &mut store)
+}
+
+#[tauri::command]
+async fn list_sessions(
+    sessions: State<'_, SessionStore>,
+) -> Result<Vec<sessions::SessionSummary>, String> {
+    Ok(sessions.list())
+}
+
+#[tauri::command]
+async fn delete_session(name: String, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    sessions.delete(&name)
+}
+
+// 重新检查已保存扫描结果中的条目，标出扫描后发生变化或已消失的
+#[tauri::command]
+async fn verify_result(
+    scan_id: u64,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<verify::VerifyReport, String> {
+    let scan = store.lock().unwrap().get(scan_id)?.clone();
+    spawn_blocking(move || verify::verify_result(scan_id, &scan))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+// 按正则批量重命名，dry_run 为 true 时只返回重命名计划
+#[tauri::command]
+async fn bulk_rename(
+    paths: Vec<String>,
+    pattern: String,
+    replacement: String,
+    dry_run: bool,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<Vec<file_ops::RenameOutcome>, String> {
+    if !dry_run {
+        ensure_writable(&config)?;
+    }
+    let paths = input_paths(&paths, Expect::Exists)?;
+    let outcomes =
+        spawn_blocking(move || file_ops::bulk_rename(&paths, &pattern, &replacement, dry_run))
+            .await
+            .map_err(|e| format!("Failed to execute blocking task: {}", e))??;
+    for outcome in &outcomes {
+        let result = match outcome.status {
+            file_ops::RenameStatus::Renamed => Ok(()),
+            file_ops::RenameStatus::Failed => Err(outcome.error.clone().unwrap_or_default()),
+            _ => continue,
+        };
+        let bytes = fs::symlink_metadata(&outcome.to)
+            .or_else(|_| fs::symlink_metadata(&outcome.from))
+            .map(|m| m.len())
+            .unwrap_or(0);
+        audit.record(
+            AuditAction::Rename,
+            &outcome.from,
+            Some(&outcome.to),
+            bytes,
+            &result,
+        );
+    }
+    Ok(outcomes)
+}
+#[tauri::command]
+async fn create_directory(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Any)?;
+    file_ops::create_directory(Path::new(&path))
+}
+
+#[tauri::command]
+async fn create_file(path: String, config: State<'_, Mutex<ConfigStore>>) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Any)?;
+    file_ops::create_file(Path::new(&path))
+}
+// 设置 Windows 只读、隐藏属性，参数为空表示保持不变
+#[tauri::command]
+async fn set_attributes(
+    path: String,
+    readonly: Option<bool>,
+    hidden: Option<bool>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    attributes::set_attributes(Path::new(&path), readonly, hidden)
+}
+
+// 设置 Unix 权限位
+#[tauri::command]
+async fn chmod(
+    path: String,
+    mode: u32,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    attributes::chmod(Path::new(&path), mode)
+}
+
+// 创建符号链接、目录联接或硬链接
+#[tauri::command]
+async fn create_link(
+    target: String,
+    link_path: String,
+    kind: links::LinkKind,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let target = input_path(&target, Expect::Exists)?;
+    let link_path = input_path(&link_path, Expect::Any)?;
+    links::create_link(Path::new(&target), Path::new(&link_path), kind)
+}
+
+// 把目录迁移到其他位置（通常是另一块磁盘），并在原处留下链接
+#[tauri::command]
+async fn relocate_folder(
+    source: String,
+    destination_dir: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<links::RelocateOutcome, String> {
+    ensure_writable(&config)?;
+    let source = input_path(&source, Expect::Directory)?;
+    let destination_dir = input_path(&destination_dir, Expect::Directory)?;
+    let source_clone = source.clone();
+    let result = spawn_blocking(move || {
+        links::relocate_folder(Path::new(&source_clone), Path::new(&destination_dir))
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    match &result {
+        Ok(outcome) => audit.record(
+            AuditAction::Move,
+            &source,
+            Some(&outcome.destination),
+            outcome.bytes_moved,
+            &Ok(()),
+        ),
+        Err(e) => audit.record(AuditAction::Move, &source, None, 0, &Err(e.clone())),
+    }
+    result
+}
+
+// 把旧数据移到其他卷或 NAS：在目标目录写入清单，在原处留下 .where 说明文件并记入冷存储索引。
+// 通过 cold-storage-progress 事件报告进度
+#[tauri::command]
+async fn move_to_cold_storage(
+    app_handle: AppHandle,
+    paths: Vec<String>,
+    destination_dir: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+    cold_index: State<'_, ColdStorageIndex>,
+) -> Result<cold_storage::ColdStorageOutcome, String> {
+    ensure_writable(&config)?;
+    let paths = input_paths(&paths, Expect::Exists)?;
+    let destination_dir = input_path(&destination_dir, Expect::Directory)?;
+    let mut outcome = spawn_blocking(move || {
+        let on_item = |path: &str, done: usize, total: usize| {
+            let _ = app_handle.emit(
+                "cold-storage-progress",
+                ProgressEvent {
+                    current_path: path.to_string(),
+                    current_file: String::new(),
+                    status: ProgressStatus::Stage {
+                        stage: "move".to_string(),
+                        done: done as u64,
+                        total: total as u64,
+                    },
+                    context: None,
+                    scan_id: None,
+                },
+            );
+        };
+        cold_storage::move_to_cold_storage(&paths, Path::new(&destination_dir), &on_item)
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    for record in &outcome.moved {
+        audit.record(
+            AuditAction::Move,
+            &record.original,
+            Some(&record.stored),
+            record.size_raw,
+            &Ok(()),
+        );
+    }
+    for (path, error) in &outcome.errors {
+        audit.record(AuditAction::Move, path, None, 0, &Err(error.clone()));
+    }
+    if let Err(e) = cold_index.add(&outcome.moved) {
+        outcome.warnings.push(e);
+    }
+    Ok(outcome)
+}
+
+// 按原路径或现位置查找移到冷存储的条目
+#[tauri::command]
+async fn search_cold_storage(
+    query: String,
+    cold_index: State<'_, ColdStorageIndex>,
+) -> Result<Vec<cold_storage::ColdRecord>, String> {
+    Ok(cold_index.search(&query))
+}
+
+// 修改属主和属组（仅 Unix），递归时通过 chown-progress 事件报告进度
+#[tauri::command]
+async fn change_owner(
+    app_handle: AppHandle,
+    root: String,
+    user: Option<String>,
+    group: Option<String>,
+    recursive: bool,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<owners::ChownReport, String> {
+    ensure_writable(&config)?;
+    let root = input_path(&root, Expect::Exists)?;
+    spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        let on_progress = |current: &Path, processed: u64| {
+            let _ = app_handle.emit(
+                "chown-progress",
+                ProgressEvent {
+                    current_path: root.clone(),
+                    current_file: current.to_string_lossy().to_string(),
+                    status: ProgressStatus::FileProcessed {
+                        count: processed,
+                        bytes: 0,
+                        total: None,
+                    },
+                    context: None,
+                    scan_id: None,
+                },
+            );
+        };
+        owners::change_owner(
+            root_path,
+            user.as_deref(),
+            group.as_deref(),
+            recursive,
+            &on_progress,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+
+// 修改单个条目的时间戳，参数为空的时间保持不变
+#[tauri::command]
+async fn touch_entry(
+    path: String,
+    created: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    spawn_blocking(move || file_ops::touch_entry(Path::new(&path), created, modified, accessed))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+
+// 批量修改时间戳，逐项返回结果
+#[tauri::command]
+async fn touch_entries(
+    mut entries: Vec<file_ops::TouchRequest>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<file_ops::TouchOutcome>, String> {
+    ensure_writable(&config)?;
+    // 只做规范化，不存在的条目由 touch_entries 逐项报告
+    for entry in entries.iter_mut() {
+        entry.path = input_path(&entry.path, Expect::Any)?;
+    }
+    spawn_blocking(move || file_ops::touch_entries(&entries))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+#[tauri::command]
+async fn copy_to_clipboard(text: String) -> Result<(), String> {
+    clipboard::copy_to_clipboard(&text)
+}
+
+// 复制选中的路径，每行一个
+#[tauri::command]
+async fn copy_paths(paths: Vec<String>) -> Result<(), String> {
+    clipboard::copy_to_clipboard(&paths.join("\n"))
+}
+
+// 将扫描结果中选中的条目格式化为文本、Markdown 表格或 CSV 并复制，返回复制的内容
+#[tauri::command]
+async fn copy_selection(
+    scan_id: u64,
+    paths: Vec<String>,
+    format: export::ExportFormat,
+    store: State<'_, Mutex<ScanStore>>,
+) -> Result<String, String> {
+    let (selected, completeness): (Vec<FileEntry>, Completeness) = {
+        let store = store.lock().unwrap();
+        let result = &store.get(scan_id)?.result;
+        (
+            result
+                .entries
+                .iter()
+                .filter(|e| paths.iter().any(|p| e.has_path(p)))
+                .cloned()
+                .collect(),
+            result.completeness.clone(),
+        )
+    };
+    let text = export::format_entries(&selected, format, &completeness);
+    clipboard::copy_to_clipboard(&text)?;
+    Ok(text)
+}
+// 将扫描结果导出为带层级列的扁平 CSV，便于在 Excel 中做数据透视；
+// output_path 不为空时同时写入文件
+#[tauri::command]
+async fn export_flat_table(
+    scan_id: u64,
+    output_path: Option<String>,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<String, String> {
+    // 只读模式下仍可取得导出内容，但不写文件
+    if output_path.is_some() {
+        ensure_writable(&config)?;
+    }
+    let text = {
+        let store = store.lock().unwrap();
+        let scan = store.get(scan_id)?;
+        export::entries_to_flat_table(&scan.root, &scan.result.entries, &scan.result.completeness)
+    };
+    if let Some(output_path) = output_path {
+        let output_path = input_path(&output_path, Expect::Any)?;
+        fs::write(&output_path, &text).map_err(|e| format!("写入文件失败: {}", e))?;
+    }
+    Ok(text)
+}
+
+// 把重复文件结果中选中的删除项导出为 sh/PowerShell 脚本或 CSV，
+// output_path 不为空时同时写入文件
+#[tauri::command]
+async fn export_duplicate_deletions(
+    groups: Vec<duplicates::DuplicateGroup>,
+    delete: Vec<String>,
+    format: export::DeletionScriptFormat,
+    output_path: Option<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<String, String> {
+    if output_path.is_some() {
+        ensure_writable(&config)?;
+    }
+    let text = export::duplicate_deletions(&groups, &delete, format)?;
+    if let Some(output_path) = output_path {
+        let output_path = input_path(&output_path, Expect::Any)?;
+        fs::write(&output_path, &text).map_err(|e| format!("写入文件失败: {}", e))?;
+    }
+    Ok(text)
+}
+// 命令的路径参数统一经过 paths::sanitize_path 规范化，相对路径按用户主目录解析
+fn input_path(path: &str, expect: Expect) -> Result<String, String> {
+    let path = paths::sanitize_path(path, home_dir().as_deref(), expect)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn input_paths(paths: &[String], expect: Expect) -> Result<Vec<String>, String> {
+    let paths = paths::sanitize_paths(paths, home_dir().as_deref(), expect)?;
+    Ok(paths
+        .into_iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect())
+}
+
+// 在超时限制内等待阻塞任务。超时后命令立即返回错误；后台线程无法中断，会在完成后自行退出
+async fn run_blocking_with_timeout<T: Send + 'static>(
+    limit_seconds: Option<u64>,
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, String> {
+    let handle = spawn_blocking(task);
+    let joined = match limit_seconds {
+        Some(seconds) => timeout(Duration::from_secs(seconds), handle)
+            .await
+            .map_err(|_| {
+                format!(
+                    "操作超时（超过 {} 秒），目标可能位于无响应的网络挂载上",
+                    seconds
+                )
+            })?,
+        None => handle.await,
+    };
+    joined.map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 启动停滞检测，超过阈值没有进度时广播 scan-stalled 事件
+fn start_watchdog(
+    app_handle: &AppHandle,
+    command: &str,
+    scan_id: Option<u64>,
+    path: &str,
+    timeouts: &TimeoutSettings,
+) -> Watchdog {
+    let app_handle = app_handle.clone();
+    let command = command.to_string();
+    Watchdog::start(
+        Duration::from_secs(timeouts.stall_seconds),
+        Path::new(path),
+        move |last_path, stalled_seconds| {
+            let _ = app_handle.emit(
+                "scan-stalled",
+                StallEvent {
+                    command: command.clone(),
+                    scan_id,
+                    last_path: last_path.to_string(),
+                    stalled_seconds,
+                },
+            );
+        },
+    )
+}
+
+// 只读模式下拒绝所有修改文件系统的命令
+fn ensure_writable(config: &State<'_, Mutex<ConfigStore>>) -> Result<(), String> {
+    config.lock().unwrap().get().ensure_writable()
+}
+
+// 任何窗口都可以开启只读模式；关闭只能在主窗口中进行，扫描窗口不能解除。每次切换都记入审计日志
+#[tauri::command]
+async fn set_read_only(
+    enabled: bool,
+    webview: tauri::Webview,
+    config: State<'_, Mutex<ConfigStore>>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let result = if !enabled && webview.label() != startup::MAIN_WINDOW {
+        Err("只能在主窗口中关闭只读模式".to_string())
+    } else {
+        config.lock().unwrap().update(|c| c.read_only = enabled)
+    };
+    let setting = if enabled {
+        "read_only=on"
+    } else {
+        "read_only=off"
+    };
+    audit.record(AuditAction::Settings, setting, None, 0, &result);
+    result
+}
+
+// 读取最近的破坏性操作审计记录
+#[tauri::command]
+async fn get_audit_log(
+    limit: usize,
+    audit: State<'_, AuditLog>,
+) -> Result<Vec<audit::AuditRecord>, String> {
+    audit.read(limit)
+}
+
+// 设置需要建立文件名索引的目录，新增的目录会在后台开始建立索引
+#[tauri::command]
+async fn set_indexed_roots(
+    roots: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+    index: State<'_, FileIndex>,
+) -> Result<(), String> {
+    let roots = input_paths(&roots, Expect::Directory)?;
+    config
+        .lock()
+        .unwrap()
+        .update(|c| c.indexed_roots = roots.clone())?;
+    index.set_roots(&roots, false);
+    Ok(())
+}
+
+// 重新完整建立所有目录的索引
+#[tauri::command]
+async fn rebuild_index(
+    config: State<'_, Mutex<ConfigStore>>,
+    index: State<'_, FileIndex>,
+) -> Result<(), String> {
+    let roots = config.lock().unwrap().get().indexed_roots.clone();
+    index.set_roots(&roots, true);
+    Ok(())
+}
+
+#[tauri::command]
+async fn index_status(index: State<'_, FileIndex>) -> Result<Vec<index::VolumeStatus>, String> {
+    Ok(index.status())
+}
+
+// 根据索引得到目录大小，目录不在索引范围内时返回 None
+#[tauri::command]
+async fn indexed_dir_size(
+    path: String,
+    index: State<'_, FileIndex>,
+) -> Result<Option<u64>, String> {
+    let path = input_path(&path, Expect::Any)?;
+    let index = index.inner().clone();
+    spawn_blocking(move || index.dir_size(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 在索引中按文件名查找，无需重新遍历磁盘
+#[tauri::command]
+async fn query_index(
+    pattern: String,
+    limit: usize,
+    index: State<'_, FileIndex>,
+) -> Result<Vec<index::IndexHit>, String> {
+    let index = index.inner().clone();
+    spawn_blocking(move || index.query(&pattern, limit))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+
+#[tauri::command]
+async fn get_config(config: State<'_, Mutex<ConfigStore>>) -> Result<config::AppConfig, String> {
+    Ok(config.lock().unwrap().get().clone())
+}
+
+// 修改大小显示格式，之后生成的结果按新格式显示
+#[tauri::command]
+async fn set_size_format(
+    format: utils::SizeFormat,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    config.lock().unwrap().update(|c| c.size_format = format)
+}
+
+// 修改名称排序规则，之后的扫描结果按新规则排序
+#[tauri::command]
+async fn set_name_collation(
+    collation: collation::NameCollation,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    config
+        .lock()
+        .unwrap()
+        .update(|c| c.name_collation = collation)
+}
+
+// 当前是否使用电池、是否处于计费网络，以及因此被推迟的任务
+#[tauri::command]
+async fn get_power_state(
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<power::PowerState, String> {
+    let settings = config.lock().unwrap().get().power.clone();
+    spawn_blocking(move || power::current(&settings))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 修改电池与计费网络下的推迟规则及强制指定的状态
+#[tauri::command]
+async fn set_power_settings(
+    settings: power::PowerSettings,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    config.lock().unwrap().update(|c| c.power = settings)
+}
+
+// 修改启动时预扫描监控目录的设置，下次启动时生效
+#[tauri::command]
+async fn set_warm_cache(
+    settings: warm_cache::WarmCacheSettings,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    config.lock().unwrap().update(|c| c.warm_cache = settings)
+}
+
+// 取出目录的预扫描结果，没有或已过期时返回 None，由前端改为正常扫描
+#[tauri::command]
+async fn take_warm_scan(
+    path: String,
+    context: Option<String>,
+    webview: tauri::Webview,
+    store: State<'_, Mutex<ScanStore>>,
+    contexts: State<'_, Mutex<ScanContexts>>,
+    warm: State<'_, WarmCache>,
+) -> Result<Option<DirectoryResult>, String> {
+    let path = input_path(&path, Expect::Directory)?;
+    let Some(scan_id) = warm.take(&history::history_key(&path)) else {
+        return Ok(None);
+    };
+    // 预扫描结果可能已被更新的预扫描挤出存储
+    let scan = {
+        let mut store = store.lock().unwrap();
+        store.claim_warm(scan_id);
+        store.get(scan_id).map(|s| s.result.clone())
+    };
+    let Ok(scan) = scan else {
+        return Ok(None);
+    };
+    record_context(&contexts, &webview, context.as_deref(), &path, &scan);
+    Ok(Some(scan))
+}
+
+// 修改时间显示格式（相对时间或本地化日期），之后返回的条目按新格式显示
+#[tauri::command]
+async fn set_time_format(
+    format: time_format::TimeFormat,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    config.lock().unwrap().update(|c| c.time_format = format)
+}
+
+#[tauri::command]
+async fn set_config(
+    mut new_config: config::AppConfig,
+    webview: tauri::Webview,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    // 扫描窗口只能修改单项设置，不能整体替换配置
+    if webview.label() != startup::MAIN_WINDOW {
+        return Err("只能在主窗口中修改配置".to_string());
+    }
+    let mut config = config.lock().unwrap();
+    config.get().ensure_change_allowed(&new_config)?;
+    // 只读模式只能通过 set_read_only 切换
+    new_config.read_only = config.get().read_only;
+    config.set(new_config)
+}
+// 当前使用的配置档案，启动时由 --profile 或 DISKSIGHT_PROFILE 选择
+#[tauri::command]
+async fn get_profile(config: State<'_, Mutex<ConfigStore>>) -> Result<config::Profile, String> {
+    Ok(config.lock().unwrap().profile())
+}
+// 导出配置和脚本，用于复制到其他机器
+#[tauri::command]
+async fn export_settings(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    scripts: State<'_, ScriptStore>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Any)?;
+    settings_bundle::export_settings(Path::new(&path), &config.lock().unwrap(), &scripts)
+}
+// 从导出文件导入配置和脚本，并按新的索引目录重建索引
+#[tauri::command]
+async fn import_settings(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+    scripts: State<'_, ScriptStore>,
+    index: State<'_, FileIndex>,
+    audit: State<'_, AuditLog>,
+) -> Result<settings_bundle::ImportSummary, String> {
+    // 导入会替换全部设置并安装定时脚本，只读模式下不允许
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Exists)?;
+    let mut config = config.lock().unwrap();
+    let result = settings_bundle::import_settings(Path::new(&path), &mut config, &scripts);
+    audit.record(
+        AuditAction::Settings,
+        "import_settings",
+        Some(&path),
+        0,
+        &result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+    );
+    let summary = result?;
+    index.set_roots(&config.get().indexed_roots, false);
+    Ok(summary)
+}
+// 将目录标记为“已知晓”，不再出现在占用排行中
+#[tauri::command]
+async fn acknowledge_path(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    let path = input_path(&path, Expect::Any)?;
+    let path = canonical_string(Path::new(&path)).unwrap_or(path);
+    config.lock().unwrap().update(|c| {
+        if !c.acknowledged_paths.contains(&path) {
+            c.acknowledged_paths.push(path);
+        }
+    })
+}
+
+#[tauri::command]
+async fn unacknowledge_path(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    let path = input_path(&path, Expect::Any)?;
+    let canonical = canonical_string(Path::new(&path));
+    config.lock().unwrap().update(|c| {
+        c.acknowledged_paths
+            .retain(|p| *p != path && Some(p) != canonical.as_ref())
+    })
+}
+
+#[tauri::command]
+async fn list_acknowledged(config: State<'_, Mutex<ConfigStore>>) -> Result<Vec<String>, String> {
+    Ok(config.lock().unwrap().get().acknowledged_paths.clone())
+}
+
+// 设置路径的标签和备注，替换原有的内容；两者都为空时移除该路径的标注
+#[tauri::command]
+async fn tag_entry(
+    path: String,
+    tags: Vec<String>,
+    note: Option<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    let path = input_path(&path, Expect::Any)?;
+    config
+        .lock()
+        .unwrap()
+        .update(|c| annotations::set_annotation(&mut c.annotations, path, tags, note))
+}
+
+// 添加或替换某个目录的扫描选项，之后扫描该目录或其中的内容时自动应用
+#[tauri::command]
+async fn set_scan_override(
+    scan_override: ignore_rules::ScanOverride,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    let path = input_path(&scan_override.path, Expect::Directory)?;
+    let scan_override = ignore_rules::ScanOverride {
+        path,
+        exclude: scan_override
+            .exclude
+            .iter()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        ..scan_override
+    };
+    config.lock().unwrap().update(|c| {
+        c.scan_overrides.retain(|o| o.path != scan_override.path);
+        c.scan_overrides.push(scan_override);
+    })
+}
+
+#[tauri::command]
+async fn remove_scan_override(
+    path: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    // 与 set_scan_override 保存时同样规范化；目录可能已被删除，不要求存在。
+    // 含 .. 或经过符号链接的写法按解析后的实际目录匹配
+    let path = input_path(&path, Expect::Any)?;
+    let canonical = canonical_string(Path::new(&path));
+    let matches = |o: &ignore_rules::ScanOverride| {
+        o.path == path
+            || canonical
+                .as_ref()
+                .is_some_and(|c| canonical_string(Path::new(&o.path)).as_ref() == Some(c))
+    };
+    let mut config = config.lock().unwrap();
+    if !config.get().scan_overrides.iter().any(matches) {
+        return Err(format!("没有该路径的扫描选项: {}", path));
+    }
+    config.update(|c| c.scan_overrides.retain(|o| !matches(o)))
+}
+
+// 列出扫描中失败过的路径，skipped 为 true 的在之后的扫描中默认跳过
+#[tauri::command]
+async fn list_skip_list(
+    skip_list: State<'_, SkipList>,
+) -> Result<Vec<skip_list::SkipEntry>, String> {
+    Ok(skip_list.list())
+}
+
+// 忘记某个路径的失败记录，下次扫描重新尝试；path 为空时全部重置
+#[tauri::command]
+async fn reset_skip_list(
+    path: Option<String>,
+    skip_list: State<'_, SkipList>,
+) -> Result<(), String> {
+    skip_list.reset(path.as_deref())
+}
+
+// 扫描目录并把文件系统的原始应答录制到 output，用于复现特殊文件系统上的统计问题
+#[tauri::command]
+async fn record_scan(
+    path: String,
+    output: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<DirectoryResult, String> {
+    // 录制结果会写入 output
+    ensure_writable(&config)?;
+    let path = input_path(&path, Expect::Directory)?;
+    let output = input_path(&output, Expect::Any)?;
+    let (settings, limit, collation) = {
+        let config = config.lock().unwrap();
+        let config = config.get();
+        (
+            config.parallelism.clone(),
+            config.timeouts.scan_seconds,
+            config.name_collation,
+        )
+    };
+    run_blocking_with_timeout(limit, move || {
+        let start_time = std::time::Instant::now();
+        let failures = ScanFailures::default();
+        let emitter = ScanEmitter::forwarding(0, None, |_, _| {}).with_failures(failures.clone());
+        let recording_fs = scan_replay::RecordingFs::new(&RealFs, Path::new(&path));
+        let (entries, plan) = scan_worker::scan_directory_in(
+            &recording_fs,
+            Path::new(&path),
+            &settings,
+            ignore_rules::IgnoreMode::Off,
+            &[],
+            false,
+            collation,
+            &emitter,
+        )
+        .map_err(|e| format!("Error listing directory: {}", e))?;
+        recording_fs.finish().save(Path::new(&output))?;
+        let skipped = skip_list::skipped_counts(&failures.lock().unwrap(), 0);
+        Ok(DirectoryResult {
+            scan_id: None,
+            entries,
+            query_time: start_time.elapsed().as_secs_f64(),
+            telemetry: None,
+            scan_plan: Some(plan),
+            completeness: skipped.completeness(),
+            skipped,
+        })
+    })
+    .await?
+}
+
+// 按录制文件回放扫描，不访问被录制的目录
+#[tauri::command]
+async fn replay_scan(
+    recording: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<DirectoryResult, String> {
+    let recording = input_path(&recording, Expect::Exists)?;
+    let collation = config.lock().unwrap().get().name_collation;
+    spawn_blocking(move || {
+        let start_time = std::time::Instant::now();
+        let replay_fs =
+            scan_replay::ReplayFs::new(scan_replay::ScanRecording::load(Path::new(&recording))?);
+        // 录制时无权限读取的目录回放时同样报告为未统计
+        let failures = ScanFailures::default();
+        let emitter = ScanEmitter::forwarding(0, None, |_, _| {}).with_failures(failures.clone());
+        let (entries, plan) = scan_worker::scan_directory_in(
+            &replay_fs,
+            replay_fs.root(),
+            &parallelism::ParallelismSettings::default(),
+            ignore_rules::IgnoreMode::Off,
+            &[],
+            false,
+            collation,
+            &emitter,
+        )
+        .map_err(|e| format!("Error listing directory: {}", e))?;
+        let skipped = skip_list::skipped_counts(&failures.lock().unwrap(), 0);
+        Ok(DirectoryResult {
+            scan_id: None,
+            entries,
+            query_time: start_time.elapsed().as_secs_f64(),
+            telemetry: None,
+            scan_plan: Some(plan),
+            completeness: skipped.completeness(),
+            skipped,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+
+// 列出带有标注的路径，tag 不为空时只返回带有该标签的路径
+#[tauri::command]
+async fn list_tags(
+    tag: Option<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<annotations::PathAnnotation>, String> {
+    let config = config.lock().unwrap();
+    Ok(config
+        .get()
+        .annotations
+        .iter()
+        .filter(|a| tag.as_deref().is_none_or(|tag| a.has_tag(tag)))
+        .cloned()
+        .collect())
+}
+
+// 所有使用过的标签及其路径数，供界面的标签筛选
+#[tauri::command]
+async fn tag_summary(
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<(String, usize)>, String> {
+    Ok(annotations::all_tags(
+        &config.lock().unwrap().get().annotations,
+    ))
+}
+
+// 设置需要记录大小历史的目录
+#[tauri::command]
+async fn set_monitored_roots(
+    roots: Vec<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    let roots = input_paths(&roots, Expect::Directory)?;
+    let roots: Vec<String> = roots.iter().map(|r| history::history_key(r)).collect();
+    config.lock().unwrap().update(|c| c.monitored_roots = roots)
+}
+
+// 监控目录按时间排列的总大小记录，供前端绘制趋势图
+#[tauri::command]
+async fn get_size_history(
+    path: String,
+    history: State<'_, SizeHistory>,
+) -> Result<Vec<history::SizePoint>, String> {
+    let path = input_path(&path, Expect::Any)?;
+    Ok(history.get(&path))
+}
+
+// 根据大小历史预测所在卷达到 90% 与写满的时间
+#[tauri::command]
+async fn forecast(
+    path: String,
+    history: State<'_, SizeHistory>,
+) -> Result<forecast::Forecast, String> {
+    let path = input_path(&path, Expect::Any)?;
+    forecast::forecast_path(&path, &history)
+}
+
+// 演练保留策略：列出命中的文件，不做任何修改。name 为空时演练所有已启用的策略
+#[tauri::command]
+async fn dry_run_retention(
+    app_handle: AppHandle,
+    name: Option<String>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<retention::RetentionReport>, String> {
+    let (policies, limit): (Vec<retention::RetentionPolicy>, _) = {
+        let config = config.lock().unwrap();
+        let config = config.get();
+        let policies = config
+            .retention_policies
+            .iter()
+            .filter(|p| match &name {
+                Some(name) => &p.name == name,
+                None => p.enabled,
+            })
+            .cloned()
+            .collect();
+        (policies, config.timeouts.analysis_seconds)
+    };
+    if policies.is_empty() {
+        return Err("没有匹配的保留策略".to_string());
+    }
+    // 遍历策略目录可能很慢，不能占用异步运行时的线程
+    run_blocking_with_timeout(limit, move || {
+        let audit = app_handle.state::<AuditLog>();
+        policies
+            .iter()
+            .map(|p| retention::apply(p, true, &audit))
+            .collect()
+    })
+    .await?
+}
+
+// 立即执行一条保留策略
+#[tauri::command]
+async fn enforce_retention(
+    app_handle: AppHandle,
+    name: String,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<retention::RetentionReport, String> {
+    ensure_writable(&config)?;
+    let policy = config
+        .lock()
+        .unwrap()
+        .get()
+        .retention_policies
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| format!("保留策略不存在: {}", name))?;
+    // 执行中的移动不能中途放弃，不设超时
+    spawn_blocking(move || retention::apply(&policy, false, &app_handle.state::<AuditLog>()))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?
+}
+
+// 新建或修改自动化脚本；定时脚本会在后台修改文件，只读模式下不允许
+#[tauri::command]
+async fn save_script(
+    name: String,
+    source: String,
+    schedule: scripts::ScriptSchedule,
+    enabled: bool,
+    scripts: State<'_, ScriptStore>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<(), String> {
+    ensure_writable(&config)?;
+    scripts.save(&name, source, schedule, enabled)
+}
+
+#[tauri::command]
+async fn delete_script(name: String, scripts: State<'_, ScriptStore>) -> Result<(), String> {
+    scripts.delete(&name)
+}
+
+#[tauri::command]
+async fn list_scripts(scripts: State<'_, ScriptStore>) -> Result<Vec<scripts::Script>, String> {
+    Ok(scripts.list())
+}
+
+// 立即运行脚本，dry_run 为 true 时只列出将要执行的操作
+#[tauri::command]
+async fn run_script(
+    app_handle: AppHandle,
+    name: String,
+    dry_run: bool,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<scripts::ScriptRun, String> {
+    if !dry_run {
+        ensure_writable(&config)?;
+    }
+    let script = app_handle
+        .state::<ScriptStore>()
+        .get(&name)
+        .ok_or_else(|| format!("脚本不存在: {}", name))?;
+    spawn_blocking(move || {
+        let run = scripts::run_script(&script, dry_run, &app_handle.state::<AuditLog>());
+        app_handle.state::<ScriptStore>().record_run(&run, false);
+        run
+    })
+    .await
+    .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+
+// 向所有已配置的渠道发送一条测试提醒，用于检查 webhook 和邮件设置
+#[tauri::command]
+async fn test_alert_delivery(config: State<'_, Mutex<ConfigStore>>) -> Result<(), String> {
+    let settings = config.lock().unwrap().get().alerts.clone();
+    if !settings.has_targets() {
+        return Err("尚未配置 webhook 或邮件".to_string());
+    }
+    let alert = alerts::Alert {
+        kind: alerts::AlertKind::Threshold,
+        path: String::new(),
+        message: "这是一条测试提醒".to_string(),
+        size_raw: 0,
+    };
+    let errors = spawn_blocking(move || alerts::deliver(&[alert], &settings))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))?;
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+// 扫描结果中占用最大的条目，排除已标记为忽略的目录
+#[tauri::command]
+async fn top_offenders(
+    scan_id: u64,
+    limit: usize,
+    store: State<'_, Mutex<ScanStore>>,
+    config: State<'_, Mutex<ConfigStore>>,
+) -> Result<Vec<FileEntry>, String> {
+    let config = config.lock().unwrap().get().clone();
+    let store = store.lock().unwrap();
+    let mut entries: Vec<FileEntry> = store
+        .get(scan_id)?
+        .result
+        .entries
+        .iter()
+        .filter(|e| !config.is_acknowledged(&e.path()))
+        .cloned()
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    entries.truncate(limit);
+    Ok(entries)
+}
+// 预估删除选中路径能释放多少空间，用于删除确认对话框
+#[tauri::command]
+async fn deletion_impact(paths: Vec<String>) -> Result<deletion::DeletionImpact, String> {
+    // 已不存在的路径由 deletion_impact 列入 missing，这里只做规范化
+    let paths = input_paths(&paths, Expect::Any)?;
+    spawn_blocking(move || deletion::deletion_impact(&paths))
+        .await
+        .map_err(|e| format!("Failed to execute blocking task: {}", e))
+}
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .manage(StartupCoordinator::default())
+        .manage(ElevatedHelper::default())
+        .manage(ArchiveJobs::default())
+        .manage(alerts::AlertCooldown::default())
+        .manage(Mutex::new(ScanStore::default()))
+        .manage(Mutex::new(ScanContexts::default()))
+        .manage(Mutex::new(ScanSubscriptions::default()))
+        // 添加我们用于检查的命令
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_positioner::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            get_list_directory,
+            calculate_dir_size_simple_fast,
+            open_scan_window,
+            list_scan_contexts,
+            subscribe_scan,
+            unsubscribe_scan,
+            close_scan_context,
+            delete_file,
+            quarantine,
+            list_quarantine,
+            restore_quarantined,
+            purge_quarantine,
+            archive_then_delete,
+            cancel_archive_job,
+            move_to_cold_storage,
+            search_cold_storage,
+            set_scan_override,
+            remove_scan_override,
+            list_skip_list,
+            reset_skip_list,
+            record_scan,
+            set_warm_cache,
+            take_warm_scan,
+            get_power_state,
+            set_power_settings,
+            replay_scan,
+            find_mobile_backups,
+            find_engine_caches,
+            find_ml_caches,
+            find_raw_jpeg_pairs,
+            find_screenshots,
+            media_stats,
+            list_analyzers,
+            run_analyzer,
+            reload_analyzers,
+            drive_health,
+            get_platform_capabilities,
+            owner_usage,
+            profiles_report,
+            agent_dashboard,
+            quota_report,
+            space_reconciliation,
+            purgeable_space,
+            thin_local_snapshots,
+            cow_volumes,
+            slack_report,
+            find_git_repos,
+            find_large_blobs,
+            git_gc,
+            elevated_dir_size,
+            elevated_mft_size,
+            clean_system_cache,
+            linux_system_items,
+            find_flatpak_snap,
+            linux_cleanup,
+            windows_system_folders,
+            analyze_component_store,
+            component_cleanup,
+            get_recommendations,
+            apply_recommendation,
+            group_entries,
+            summarize_markdown,
+            save_baseline,
+            get_baseline,
+            delete_baseline,
+            compare_to_baseline,
+            project_view,
+            compare_view,
+            rescan_entry,
+            verify_result,
+            save_session,
+            load_session,
+            list_sessions,
+            delete_session,
+            find_duplicates,
+            find_similar_files,
+            deduplicate,
+            hardlink_duplicates,
+            bulk_rename,
+            create_directory,
+            create_file,
+            create_link,
+            relocate_folder,
+            set_attributes,
+            change_owner,
+            chmod,
+            touch_entry,
+            touch_entries,
+            copy_to_clipboard,
+            copy_paths,
+            copy_selection,
+            export_duplicate_deletions,
+            export_flat_table,
+            get_config,
+            set_config,
+            get_profile,
+            export_settings,
+            import_settings,
+            set_size_format,
+            set_name_collation,
+            set_time_format,
+            set_read_only,
+            get_audit_log,
+            set_indexed_roots,
+            rebuild_index,
+            index_status,
+            query_index,
+            indexed_dir_size,
+            acknowledge_path,
+            unacknowledge_path,
+            list_acknowledged,
+            tag_entry,
+            list_tags,
+            tag_summary,
+            set_monitored_roots,
+            get_size_history,
+            forecast,
+            test_alert_delivery,
+            dry_run_retention,
+            enforce_retention,
+            save_script,
+            delete_script,
+            list_scripts,
+            run_script,
+            top_offenders,
+            deletion_impact,
+            set_complete,
+            startup_errors,
+            setup_status,
+            finish_first_run
+        ])
+        .on_window_event(|window, event| {
+            // 窗口关闭后其扫描上下文随之失效
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Some(contexts) = window.try_state::<Mutex<ScanContexts>>() {
+                    contexts.lock().unwrap().remove_window(window.label());
+                }
+                if let Some(subscriptions) = window.try_state::<Mutex<ScanSubscriptions>>() {
+                    subscriptions.lock().unwrap().remove_window(window.label());
+                }
+                if window.label() == startup::SPLASH_WINDOW {
+                    if let Some(startup) = window.try_state::<StartupCoordinator>() {
+                        startup.splash_closed(window.app_handle());
+                    }
+                }
+            }
+        })
+        .setup(|app| {
+            // 加载持久化配置
+            let config_dir = app.path().app_config_dir()?;
+            // 加载启动时选择的配置档案（桌面或服务器）
+            let config = ConfigStore::load(&config_dir, config::Profile::from_startup());
+            let data_dir = app.path().app_data_dir()?;
+            let audit = AuditLog::new(&data_dir);
+            // 操作成功后在后台修正已保存的扫描结果并通知所有窗口；
+            // 记录可能在持有 ScanStore 锁时写入，不能在这里直接加锁
+            let handle = app.handle().clone();
+            audit.observe(move |record| {
+                let handle = handle.clone();
+                let record = record.clone();
+                spawn_blocking(move || {
+                    let store = handle.state::<Mutex<ScanStore>>();
+                    let event = invalidation::apply(&mut store.lock().unwrap(), &record);
+                    if let Some(event) = event {
+                        let _ = handle.emit(invalidation::EVENT, event);
+                    }
+                });
+            });
+            app.manage(audit);
+            app.manage(SessionStore::new(&data_dir));
+            app.manage(BaselineStore::new(&data_dir));
+            app.manage(ColdStorageIndex::load(&data_dir));
+            app.manage(SkipList::load(&data_dir));
+            app.manage(WarmCache::default());
+            app.manage(SizeHistory::load(&data_dir));
+            // 内置分析器加上数据目录 analyzers/ 下的插件
+            let mut registry = AnalyzerRegistry::with_builtins();
+            for (path, error) in registry.load_plugins(&data_dir.join("analyzers")) {
+                eprintln!("分析器插件 {} 加载失败: {}", path, error);
+            }
+            app.manage(Mutex::new(registry));
+            app.manage(ScriptStore::load(&data_dir));
+            // 加载已保存的文件名索引，尚未建立索引的目录在后台开始建立
+            let index = FileIndex::load(&data_dir);
+            // 使用电池时推迟到接通电源后，由 power::start_monitor 开始建立；
+            // 这里只检测电源，检测计费网络可能较慢，不阻塞启动
+            let power = config.get().power.resolve(platform::on_battery(), None);
+            if !power.defer_background {
+                index.set_roots(&config.get().indexed_roots, false);
+            }
+            watchers::start(index.clone());
+            app.manage(index);
+            app.manage(FirstRun::new(config.is_first_run()));
+            app.manage(Mutex::new(config));
+            scripts::start_scheduler(app.handle().clone());
+            power::start_monitor(app.handle().clone());
+            // Spawn 操作设置为一个非阻塞任务，以便在它执行的同时可以创建并运行窗口。
+            spawn(setup(app.handle().clone()));
+            // 钩子期望返回一个 Ok 的结果
+            Ok(())
+        })
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+// 启动画面打开时获取初始化流程的当前状态，之后的变化通过 setup-progress 事件获得
+#[tauri::command]
+async fn setup_status(first_run: State<'_, FirstRun>) -> Result<first_run::SetupReport, String> {
+    Ok(first_run.report())
+}
+
+// 首次运行时在启动画面上选好监控目录后调用：保存为监控目录和索引目录，
+// 建立初始索引，完成后关闭启动画面并显示主窗口
+#[tauri::command]
+async fn finish_first_run(
+    app: AppHandle,
+    roots: Vec<String>,
+    first_run: State<'_, FirstRun>,
+    config: State<'_, Mutex<ConfigStore>>,
+    index: State<'_, FileIndex>,
+) -> Result<(), String> {
+    if first_run.status_of(SetupStep::ChooseRoots) != Some(StepStatus::Waiting) {
+        return Err("当前不在首次运行设置中".to_string());
+    }
+    let roots = input_paths(&roots, Expect::Directory)?;
+    let monitored: Vec<String> = roots.iter().map(|r| history::history_key(r)).collect();
+    config.lock().unwrap().update(|c| {
+        c.indexed_roots = roots.clone();
+        c.monitored_roots = monitored;
+    })?;
+    first_run.step(&app, SetupStep::ChooseRoots, StepStatus::Done);
+    build_initial_cache(&app, &first_run, &index, &roots).await;
+    app.state::<StartupCoordinator>()
+        .complete(&app, StartupTask::Backend)
+}
+
+// 前端或后端初始化任务完成时调用，两者都完成后关闭启动画面并显示主窗口。
+// error 不为空表示该任务失败，错误通过 startup-error 事件通知前端
+#[tauri::command]
+async fn set_complete(
+    app: AppHandle,
+    startup: State<'_, StartupCoordinator>,
+    task: StartupTask,
+    error: Option<String>,
+) -> Result<(), String> {
+    match error {
+        Some(message) => startup.fail(&app, task, message),
+        None => startup.complete(&app, task),
+    }
+}
+
+// 主窗口加载后获取启动过程中已发生的错误
+#[tauri::command]
+async fn startup_errors(
+    startup: State<'_, StartupCoordinator>,
+) -> Result<Vec<startup::StartupError>, String> {
+    Ok(startup.errors())
+}
+
+// 启动时的后端初始化：检测磁盘；首次运行时等待在启动画面上选择监控目录，否则直接完成
+async fn setup(app: AppHandle) {
+    let first_run = app.state::<FirstRun>();
+    first_run.step(&app, SetupStep::DetectDrives, StepStatus::Running);
+    match spawn_blocking(drives::list_drives).await {
+        Ok(drives) => {
+            first_run.set_drives(drives);
+            first_run.step(&app, SetupStep::DetectDrives, StepStatus::Done);
+        }
+        Err(e) => first_run.step(
+            &app,
+            SetupStep::DetectDrives,
+            StepStatus::Failed {
+                message: e.to_string(),
+            },
+        ),
+    }
+    // 清除超过保留天数的隔离批次，只读模式下跳过
+    let (read_only, quarantine_days) = {
+        let config = app.state::<Mutex<ConfigStore>>();
+        let config = config.lock().unwrap();
+        (config.get().read_only, config.get().quarantine_days)
+    };
+    if !read_only {
+        let handle = app.clone();
+        spawn_blocking(move || {
+            let (freed, errors) = quarantine::purge_expired(quarantine_days);
+            if freed > 0 {
+                handle.state::<AuditLog>().record(
+                    AuditAction::Delete,
+                    quarantine::QUARANTINE_DIR,
+                    None,
+                    freed,
+                    &Ok(()),
+                );
+            }
+            for (path, error) in errors {
+                eprintln!("清除隔离目录 {} 失败: {}", path, error);
+            }
+        });
+    }
+    if first_run.is_first_run() {
+        // 由 finish_first_run 命令继续
+        first_run.step(&app, SetupStep::ChooseRoots, StepStatus::Waiting);
+        return;
+    }
+    first_run.step(&app, SetupStep::ChooseRoots, StepStatus::Skipped);
+    first_run.step(&app, SetupStep::BuildCache, StepStatus::Skipped);
+    // 设置后端任务为已完成
+    if let Err(e) = app
+        .state::<StartupCoordinator>()
+        .complete(&app, StartupTask::Backend)
+    {
+        eprintln!("{}", e);
+    }
+    warm_monitored_roots(&app);
+}
+
+// 按设置在后台预扫描监控目录。逐个目录单线程扫描并降低线程优先级，
+// 不与界面和用户发起的扫描争抢资源；每个目录开始前重新检查电源状态
+fn warm_monitored_roots(app: &AppHandle) {
+    let config = app
+        .state::<Mutex<ConfigStore>>()
+        .lock()
+        .unwrap()
+        .get()
+        .clone();
+    if !config
+        .warm_cache
+        .should_warm(power::current(&config.power).on_battery)
+    {
+        return;
+    }
+    let roots = warm_cache::roots_to_warm(&config.monitored_roots);
+    if roots.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        platform::lower_thread_priority();
+        let settings = parallelism::ParallelismSettings {
+            threads: Some(1),
+            ..config.parallelism.clone()
+        };
+        for root in roots {
+            if !config
+                .warm_cache
+                .should_warm(power::current(&config.power).on_battery)
+            {
+                return;
+            }
+            let path = Path::new(&root);
+            let ignore = ignore_rules::override_mode(&config.scan_overrides, path)
+                .unwrap_or(config.ignore_mode);
+            let mut overrides = config.scan_overrides.clone();
+            let mut known_inaccessible = 0;
+            if config.skip_known_inaccessible {
+                let skipped = app.state::<SkipList>().skipped_under(path);
+                known_inaccessible = skipped.len() as u64;
+                overrides.extend(skipped.iter().filter_map(|p| skip_list::skip_override(p)));
+            }
+            let start_time = std::time::Instant::now();
+            let failures = ScanFailures::default();
+            let emitter =
+                ScanEmitter::forwarding(0, None, |_, _| {}).with_failures(failures.clone());
+            let scanned = if config.scan_in_worker {
+                scan_worker::scan_in_worker(
+                    path,
+                    &settings,
+                    ignore,
+                    &overrides,
+                    false,
+                    config.name_collation,
+                    &emitter,
+                    // 线程的低优先级在 macOS 与 Windows 上不会传给工作进程
+                    &scan_worker::WorkerHandle::background(),
+                )
+            } else {
+                scan_worker::scan_directory(
+                    path,
+                    &settings,
+                    ignore,
+                    &overrides,
+                    false,
+                    config.name_collation,
+                    &emitter,
+                )
+                .map_err(|e| e.to_string())
+            };
+            let (mut entries, plan) = match scanned {
+                Ok(scanned) => scanned,
+                Err(e) => {
+                    eprintln!("预扫描 {} 失败: {}", root, e);
+                    continue;
+                }
+            };
+            for entry in entries.iter_mut() {
+                entry.severity = config.severity_for(entry.size_raw);
+            }
+            let skipped = skip_list::skipped_counts(&failures.lock().unwrap(), known_inaccessible);
+            let result = DirectoryResult {
+                scan_id: None,
+                entries,
+                query_time: start_time.elapsed().as_secs_f64(),
+                telemetry: None,
+                scan_plan: Some(plan),
+                completeness: skipped.completeness(),
+                skipped,
+            };
+            let scan_id = app
+                .state::<Mutex<ScanStore>>()
+                .lock()
+                .unwrap()
+                .insert_warm(root.clone().into(), result);
+            app.state::<WarmCache>().record(&root, scan_id);
+        }
+    });
+}
+
+// 为首次运行选择的目录建立索引，定期把已完成的目录数报告给启动画面
+async fn build_initial_cache(
+    app: &AppHandle,
+    first_run: &FirstRun,
+    index: &FileIndex,
+    roots: &[String],
+) {
+    if roots.is_empty() {
+        first_run.step(app, SetupStep::BuildCache, StepStatus::Skipped);
+        return;
+    }
+    index.set_roots(roots, true);
+    loop {
+        let volumes: Vec<index::VolumeStatus> = index
+            .status()
+            .into_iter()
+            .filter(|v| roots.contains(&v.root))
+            .collect();
+        let (done, waiting) = first_run::index_progress(roots, &volumes);
+        let total = done + waiting.len() as u64;
+        if waiting.is_empty() {
+            let errors: Vec<String> = volumes
+                .iter()
+                .filter_map(|v| v.error.as_ref().map(|e| format!("{}: {}", v.root, e)))
+                .collect();
+            let entries: usize = volumes.iter().map(|v| v.entry_count).sum();
+            first_run.progress(
+                app,
+                SetupProgress {
+                    step: SetupStep::BuildCache,
+                    status: if errors.is_empty() {
+                        StepStatus::Done
+                    } else {
+                        StepStatus::Failed {
+                            message: errors.join("\n"),
+                        }
+                    },
+                    done,
+                    total,
+                    detail: Some(format!("已索引 {} 个条目", entries)),
+                },
+            );
+            return;
+        }
+        first_run.progress(
+            app,
+            SetupProgress {
+                step: SetupStep::BuildCache,
+                status: StepStatus::Running,
+                done,
+                total,
+                detail: Some(waiting[0].to_string()),
+            },
+        );
+        sleep(Duration::from_millis(500)).await;
+    }
 }
@@ -0,0 +1,344 @@
+use crate::utils::{dir_size, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// 符号链接，可跨卷；Windows 上需要管理员权限或开发者模式
+    Symlink,
+    /// Windows 目录联接，只能指向本机目录，不需要管理员权限
+    Junction,
+    /// 硬链接，只能用于同一卷上的文件
+    HardLink,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RelocateOutcome {
+    /// 原位置，现在是指向新位置的链接
+    pub source: String,
+    pub destination: String,
+    pub link_kind: LinkKind,
+    pub bytes_moved: u64,
+    pub bytes_display: String,
+    /// 迁移已完成但有需要用户注意的问题，如原目录残留未能清理
+    pub warning: Option<String>,
+}
+
+// 在 link 处创建指向 target 的链接
+pub fn create_link(target: &Path, link: &Path, kind: LinkKind) -> Result<(), String> {
+    if !target.exists() {
+        return Err("链接目标不存在".to_string());
+    }
+    if fs::symlink_metadata(link).is_ok() {
+        return Err("链接路径已存在".to_string());
+    }
+    match kind {
+        LinkKind::Symlink => symlink(target, link),
+        LinkKind::Junction => junction(target, link),
+        LinkKind::HardLink => {
+            if target.is_dir() {
+                return Err("硬链接不能用于目录".to_string());
+            }
+            fs::hard_link(target, link).map_err(|e| format!("创建硬链接失败: {}", e))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, link).map_err(|e| format!("创建符号链接失败: {}", e))
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> Result<(), String> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+    let result = if target.is_dir() {
+        symlink_dir(target, link)
+    } else {
+        symlink_file(target, link)
+    };
+    result.map_err(|e| match e.raw_os_error() {
+        Some(1314) => "创建符号链接需要管理员权限或开启开发者模式，可改用目录联接".to_string(),
+        _ => format!("创建符号链接失败: {}", e),
+    })
+}
+
+#[cfg(windows)]
+fn junction(target: &Path, link: &Path) -> Result<(), String> {
+    if !target.is_dir() {
+        return Err("目录联接只能指向目录".to_string());
+    }
+    let target = fs::canonicalize(target).map_err(|e| format!("无法解析目标路径: {}", e))?;
+    let target = target.to_string_lossy();
+    let target = target.strip_prefix(r"\\?\").unwrap_or(&target);
+    let output = crate::platform::command("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(target)
+        .output()
+        .map_err(|e| format!("无法执行 mklink: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("创建目录联接失败: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn junction(_target: &Path, _link: &Path) -> Result<(), String> {
+    Err("目录联接仅支持 Windows，请使用符号链接".to_string())
+}
+
+// 迁移中可能失败的步骤，测试中替换为会失败的实现以检查回滚
+struct Steps {
+    link: fn(&Path, &Path, LinkKind) -> Result<(), String>,
+    copy: fn(&Path, &Path) -> Result<(), String>,
+}
+
+const STEPS: Steps = Steps {
+    link: create_link,
+    copy: copy_contents,
+};
+
+// 把大目录迁移到另一个位置并在原处留下链接，程序仍可按原路径访问。
+// 任一步骤失败都会回滚，原目录保持不变
+pub fn relocate_folder(source: &Path, destination_dir: &Path) -> Result<RelocateOutcome, String> {
+    relocate_with(source, destination_dir, &STEPS)
+}
+
+fn relocate_with(
+    source: &Path,
+    destination_dir: &Path,
+    steps: &Steps,
+) -> Result<RelocateOutcome, String> {
+    let metadata = fs::symlink_metadata(source).map_err(|e| format!("无法访问源目录: {}", e))?;
+    if !metadata.is_dir() {
+        return Err("只能迁移目录（不能是链接或文件）".to_string());
+    }
+    if !destination_dir.is_dir() {
+        return Err("目标位置不存在或不是目录".to_string());
+    }
+    let name = source
+        .file_name()
+        .ok_or_else(|| "无法获取目录名".to_string())?;
+    let destination = destination_dir.join(name);
+    if destination.exists() {
+        return Err(format!("目标位置已存在 {}", destination.display()));
+    }
+    if destination.starts_with(source) {
+        return Err("不能迁移到自身的子目录中".to_string());
+    }
+    let kind = if cfg!(windows) {
+        LinkKind::Junction
+    } else {
+        LinkKind::Symlink
+    };
+    let bytes = dir_size(source);
+
+    // 同一卷上直接重命名即可
+    let warning = if fs::rename(source, &destination).is_ok() {
+        if let Err(e) = (steps.link)(&destination, source, kind) {
+            let _ = fs::rename(&destination, source);
+            return Err(e);
+        }
+        None
+    } else {
+        move_across_volumes(source, &destination, kind, steps)?
+    };
+
+    Ok(RelocateOutcome {
+        source: source.to_string_lossy().into_owned(),
+        destination: destination.to_string_lossy().into_owned(),
+        link_kind: kind,
+        bytes_moved: bytes,
+        bytes_display: human_readable_size(bytes),
+        warning,
+    })
+}
+
+// 跨卷迁移：先复制并校验大小，再把原目录改名暂存、创建链接，最后删除暂存目录
+fn move_across_volumes(
+    source: &Path,
+    destination: &Path,
+    kind: LinkKind,
+    steps: &Steps,
+) -> Result<Option<String>, String> {
+    let cleanup = |e: String| {
+        let _ = fs::remove_dir_all(destination);
+        e
+    };
+    fs::create_dir(destination).map_err(|e| format!("无法创建目标目录: {}", e))?;
+    (steps.copy)(source, destination).map_err(cleanup)?;
+    if dir_size(destination) != dir_size(source) {
+        return Err(cleanup("复制后大小不一致，已取消迁移".to_string()));
+    }
+
+    let staging = staging_path(source);
+    fs::rename(source, &staging)
+        .map_err(|e| cleanup(format!("无法移走原目录（可能有文件正在使用）: {}", e)))?;
+    if let Err(e) = (steps.link)(destination, source, kind) {
+        let _ = fs::rename(&staging, source);
+        return Err(cleanup(e));
+    }
+    Ok(fs::remove_dir_all(&staging).err().map(|e| {
+        format!(
+            "迁移完成，但未能删除原目录残留 {}: {}",
+            staging.display(),
+            e
+        )
+    }))
+}
+
+// 把 source 中的内容复制到已存在的 destination 中
+fn copy_contents(source: &Path, destination: &Path) -> Result<(), String> {
+    let options = fs_extra::dir::CopyOptions::new().content_only(true);
+    fs_extra::dir::copy(source, destination, &options)
+        .map(|_| ())
+        .map_err(|e| format!("复制失败: {}", e))
+}
+
+fn staging_path(source: &Path) -> PathBuf {
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    source.with_file_name(format!(".{}.relocating", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 临时目录下的 src/data（含一个文件）与空的 dst
+    fn setup(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!(
+            "disksight-relocate-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        let source = root.join("src").join("data");
+        let destination_dir = root.join("dst");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::create_dir_all(&destination_dir).unwrap();
+        fs::write(source.join("a.bin"), vec![1u8; 4096]).unwrap();
+        fs::write(source.join("nested").join("b.bin"), vec![2u8; 1024]).unwrap();
+        (root, source, destination_dir)
+    }
+
+    // 不依赖 fs_extra 的复制，模拟跨卷迁移
+    fn copy_tree(source: &Path, destination: &Path) -> Result<(), String> {
+        for entry in fs::read_dir(source).map_err(|e| e.to_string())?.flatten() {
+            let target = destination.join(entry.file_name());
+            if entry.path().is_dir() {
+                fs::create_dir(&target).map_err(|e| e.to_string())?;
+                copy_tree(&entry.path(), &target)?;
+            } else {
+                fs::copy(entry.path(), &target).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    // 复制后截断一个文件，模拟复制不完整
+    fn copy_truncated(source: &Path, destination: &Path) -> Result<(), String> {
+        copy_tree(source, destination)?;
+        fs::write(destination.join("a.bin"), b"short").map_err(|e| e.to_string())
+    }
+
+    fn failing_link(_: &Path, _: &Path, _: LinkKind) -> Result<(), String> {
+        Err("模拟创建链接失败".to_string())
+    }
+
+    fn assert_untouched(source: &Path, destination_dir: &Path) {
+        let metadata = fs::symlink_metadata(source).unwrap();
+        assert!(metadata.is_dir() && !metadata.file_type().is_symlink());
+        assert_eq!(fs::read(source.join("a.bin")).unwrap().len(), 4096);
+        assert_eq!(fs::read_dir(destination_dir).unwrap().count(), 0);
+        assert!(!staging_path(source).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn renames_and_links_back() {
+        let (root, source, destination_dir) = setup("rename");
+        let outcome = relocate_folder(&source, &destination_dir).unwrap();
+        let destination = destination_dir.join("data");
+        assert_eq!(outcome.bytes_moved, 5120);
+        assert!(outcome.warning.is_none());
+        assert!(fs::symlink_metadata(&source)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_link(&source).unwrap(), destination);
+        assert_eq!(
+            fs::read(source.join("nested").join("b.bin")).unwrap().len(),
+            1024
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rolls_back_when_the_link_fails() {
+        let (root, source, destination_dir) = setup("rollback");
+        let steps = Steps {
+            link: failing_link,
+            copy: copy_tree,
+        };
+        assert!(relocate_with(&source, &destination_dir, &steps).is_err());
+        assert_untouched(&source, &destination_dir);
+
+        // 跨卷迁移在原目录已移到暂存位置之后失败，同样恢复原状
+        let destination = destination_dir.join("data");
+        assert!(move_across_volumes(&source, &destination, LinkKind::Symlink, &steps).is_err());
+        assert_untouched(&source, &destination_dir);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn refuses_destination_inside_source() {
+        let (root, source, _) = setup("inside");
+        let inside = source.join("nested");
+        assert!(relocate_folder(&source, &inside).is_err());
+        assert!(fs::symlink_metadata(&source).unwrap().is_dir());
+        assert!(!inside.join("data").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cross_volume_copy_is_checked_by_size() {
+        let (root, source, destination_dir) = setup("size");
+        let destination = destination_dir.join("data");
+        let steps = Steps {
+            link: create_link,
+            copy: copy_truncated,
+        };
+        let error =
+            move_across_volumes(&source, &destination, LinkKind::Symlink, &steps).unwrap_err();
+        assert!(error.contains("大小不一致"));
+        assert_untouched(&source, &destination_dir);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cross_volume_move_links_and_removes_staging() {
+        let (root, source, destination_dir) = setup("cross");
+        let destination = destination_dir.join("data");
+        let steps = Steps {
+            link: create_link,
+            copy: copy_tree,
+        };
+        let warning =
+            move_across_volumes(&source, &destination, LinkKind::Symlink, &steps).unwrap();
+        assert!(warning.is_none());
+        assert_eq!(fs::read_link(&source).unwrap(), destination);
+        assert_eq!(fs::read(destination.join("a.bin")).unwrap().len(), 4096);
+        assert!(!staging_path(&source).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -2,5 +2,18 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // 扫描工作进程复用同一个可执行文件
+    if std::env::args().any(|arg| arg == disk_sight_lib::scan_worker::WORKER_FLAG) {
+        return disk_sight_lib::scan_worker::run_worker();
+    }
+    // 多主机模式下作为代理运行，不启动界面
+    if std::env::args().any(|arg| arg == disk_sight_lib::agent::AGENT_FLAG) {
+        return disk_sight_lib::agent::run_agent();
+    }
+    // 开发用：生成合成测试目录树，发布版本不提供
+    #[cfg(debug_assertions)]
+    if std::env::args().any(|arg| arg == disk_sight_lib::test_tree::GENERATE_FLAG) {
+        return disk_sight_lib::test_tree::run_generate();
+    }
     disk_sight_lib::run()
 }
@@ -0,0 +1,254 @@
+// 音视频文件的编码参数统计：对目录中最大的媒体文件调用 ffprobe 读取编码、分辨率、码率和时长，
+// 计算每小时内容占用的空间，找出值得重新编码的文件。未安装 ffprobe 时只列出文件大小
+use crate::platform;
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VIDEO_EXTENSIONS: [&str; 11] = [
+    "mp4", "mkv", "mov", "avi", "wmv", "m4v", "webm", "mpg", "mpeg", "ts", "flv",
+];
+const AUDIO_EXTENSIONS: [&str; 9] = [
+    "mp3", "flac", "wav", "m4a", "aac", "ogg", "opus", "wma", "aiff",
+];
+// 已有更高效替代的视频编码
+const LEGACY_VIDEO_CODECS: [&str; 6] =
+    ["mpeg2video", "mpeg4", "msmpeg4v3", "wmv2", "wmv3", "mjpeg"];
+// 现代编码下每像素每秒的合理码率（按 30fps、0.1 bpp 估算），超过两倍视为可重新编码
+const REASONABLE_BITS_PER_PIXEL_SECOND: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Video,
+    Audio,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaProbe {
+    /// 视频文件为视频流的编码，音频文件为音频流的编码
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_seconds: Option<f64>,
+    /// 整个文件的平均码率（bit/s）
+    pub bit_rate: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MediaFile {
+    pub path: String,
+    pub kind: MediaKind,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// ffprobe 不可用或读取失败时为 None
+    pub probe: Option<MediaProbe>,
+    /// 每小时内容占用的空间
+    pub bytes_per_hour: Option<u64>,
+    pub cost_per_hour_display: Option<String>,
+    pub reencode_candidate: bool,
+    /// 建议重新编码的原因
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MediaReport {
+    pub root: String,
+    pub ffprobe_available: bool,
+    /// 按大小降序
+    pub files: Vec<MediaFile>,
+    /// 建议重新编码的文件的总大小
+    pub candidate_bytes: u64,
+}
+
+// 统计 root 下最大的 limit 个音视频文件，probe 为 false 时不调用 ffprobe
+pub fn media_report(root: &Path, limit: usize, probe: bool) -> MediaReport {
+    let mut found = Vec::new();
+    collect_media(root, &mut found);
+    found.sort_by_key(|(_, _, size)| std::cmp::Reverse(*size));
+    found.truncate(limit);
+
+    let ffprobe_available = probe && ffprobe_available();
+    let files: Vec<MediaFile> = found
+        .into_iter()
+        .map(|(path, kind, size_raw)| {
+            let probe = ffprobe_available.then(|| run_ffprobe(&path)).flatten();
+            describe(path, kind, size_raw, probe)
+        })
+        .collect();
+    MediaReport {
+        root: root.to_string_lossy().into_owned(),
+        ffprobe_available,
+        candidate_bytes: files
+            .iter()
+            .filter(|f| f.reencode_candidate)
+            .fold(0u64, |acc, f| acc.saturating_add(f.size_raw)),
+        files,
+    }
+}
+
+fn describe(path: PathBuf, kind: MediaKind, size_raw: u64, probe: Option<MediaProbe>) -> MediaFile {
+    let bytes_per_hour = probe
+        .as_ref()
+        .and_then(|p| p.duration_seconds)
+        .filter(|&d| d > 0.0)
+        .map(|d| (size_raw as f64 * 3600.0 / d) as u64);
+    let reason = probe.as_ref().and_then(|p| reencode_reason(kind, p));
+    MediaFile {
+        path: path.to_string_lossy().into_owned(),
+        kind,
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        probe,
+        bytes_per_hour,
+        cost_per_hour_display: bytes_per_hour.map(|b| format!("{}/小时", human_readable_size(b))),
+        reencode_candidate: reason.is_some(),
+        reason,
+    }
+}
+
+fn reencode_reason(kind: MediaKind, probe: &MediaProbe) -> Option<String> {
+    let codec = probe.codec.as_deref().unwrap_or_default();
+    match kind {
+        MediaKind::Video => {
+            if LEGACY_VIDEO_CODECS.contains(&codec) {
+                return Some(format!(
+                    "使用较旧的 {} 编码，改用 H.265/AV1 可大幅缩小",
+                    codec
+                ));
+            }
+            let pixels = probe.width? as f64 * probe.height? as f64;
+            let reasonable = pixels * REASONABLE_BITS_PER_PIXEL_SECOND;
+            let bit_rate = probe.bit_rate? as f64;
+            (pixels > 0.0 && bit_rate > reasonable * 2.0).then(|| {
+                format!(
+                    "码率 {:.1} Mbps，约为该分辨率常见码率的 {:.0} 倍",
+                    bit_rate / 1_000_000.0,
+                    bit_rate / reasonable
+                )
+            })
+        }
+        // 未压缩的 PCM 音频
+        MediaKind::Audio => codec
+            .starts_with("pcm_")
+            .then(|| "未压缩音频，可改用 FLAC 无损压缩".to_string()),
+    }
+}
+
+fn media_kind(path: &Path) -> Option<MediaKind> {
+    let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaKind::Video)
+    } else if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaKind::Audio)
+    } else {
+        None
+    }
+}
+
+fn collect_media(dir: &Path, found: &mut Vec<(PathBuf, MediaKind, u64)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_media(&path, found);
+        } else if let Some(kind) = media_kind(&path) {
+            found.push((path, kind, metadata.len()));
+        }
+    }
+}
+
+fn ffprobe_available() -> bool {
+    platform::command("ffprobe")
+        .arg("-version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+fn run_ffprobe(path: &Path) -> Option<MediaProbe> {
+    let output = platform::command("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "stream=codec_type,codec_name,width,height:format=duration,bit_rate",
+            "-of",
+            "default=noprint_wrappers=1",
+        ])
+        .arg(path)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_ffprobe(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// 解析 ffprobe 的 key=value 输出。每个流依次给出 codec_name、codec_type 等字段，
+// 优先取第一个视频流，没有视频流时取第一个音频流
+fn parse_ffprobe(output: &str) -> MediaProbe {
+    let mut probe = MediaProbe::default();
+    let mut codec_name: Option<String> = None;
+    let mut audio_codec: Option<String> = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let value = Some(value).filter(|v| !v.is_empty() && *v != "N/A");
+        match key {
+            "codec_name" => codec_name = value.map(str::to_string),
+            "codec_type" if value == Some("video") && probe.codec.is_none() => {
+                probe.codec = codec_name.take();
+            }
+            "codec_type" if value == Some("audio") && audio_codec.is_none() => {
+                audio_codec = codec_name.take();
+            }
+            "width" if probe.width.is_none() => probe.width = value.and_then(|v| v.parse().ok()),
+            "height" if probe.height.is_none() => probe.height = value.and_then(|v| v.parse().ok()),
+            "duration" => probe.duration_seconds = value.and_then(|v| v.parse().ok()),
+            "bit_rate" => probe.bit_rate = value.and_then(|v| v.parse().ok()),
+            _ => {}
+        }
+    }
+    if probe.codec.is_none() {
+        probe.codec = audio_codec;
+    }
+    probe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_high_bitrate_video() {
+        let probe = parse_ffprobe(
+            "codec_name=h264\ncodec_type=video\nwidth=1920\nheight=1080\n\
+             codec_name=aac\ncodec_type=audio\nwidth=N/A\nheight=N/A\n\
+             duration=1800.000000\nbit_rate=40000000\n",
+        );
+        assert_eq!(probe.codec.as_deref(), Some("h264"));
+        assert_eq!(probe.width, Some(1920));
+        assert_eq!(probe.duration_seconds, Some(1800.0));
+
+        let file = describe(
+            PathBuf::from("a.mp4"),
+            MediaKind::Video,
+            9_000_000_000,
+            Some(probe),
+        );
+        assert_eq!(file.bytes_per_hour, Some(18_000_000_000));
+        assert!(file.reencode_candidate);
+
+        let audio = parse_ffprobe("codec_name=pcm_s16le\ncodec_type=audio\nduration=60\n");
+        assert_eq!(audio.codec.as_deref(), Some("pcm_s16le"));
+        assert!(reencode_reason(MediaKind::Audio, &audio).is_some());
+    }
+}
@@ -1,7 +1,17 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
+use crate::collation::NameCollation;
+use crate::config::Severity;
+use crate::io_monitor::ScanTelemetry;
+use crate::normalize::nfc;
+use crate::parallelism::ScanPlan;
+use crate::time_format::display_time;
+
 #[derive(Clone, Debug)]
 pub struct Cli {
     pub file: Option<String>,
@@ -13,9 +23,41 @@ pub struct Cli {
     pub sort: bool,
     pub name: Option<String>,
     pub full_path: bool,
+    /// 名称的排序规则
+    pub collation: NameCollation,
 }
 
+// 条目的路径。同一目录下的条目共享父目录字符串，完整路径在读取时才拼接，
+// 百万级结果中省去了每个条目一份完整路径的内存
+#[derive(Clone, Debug)]
+pub enum EntryPath {
+    /// 父目录 + 条目名称
+    Child(Arc<str>),
+    /// 无法由父目录拼出的路径，如解析到目标位置的符号链接
+    Full(Box<str>),
+}
+
+impl EntryPath {
+    pub fn resolve(&self, name: &str) -> String {
+        match self {
+            EntryPath::Child(parent) => Path::new(&**parent)
+                .join(name)
+                .to_string_lossy()
+                .into_owned(),
+            EntryPath::Full(path) => path.to_string(),
+        }
+    }
+}
+
+impl From<String> for EntryPath {
+    fn from(path: String) -> Self {
+        EntryPath::Full(path.into_boxed_str())
+    }
+}
+
+// 对外（IPC、导出、持久化）仍使用包含完整路径的格式
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "FileEntryRecord", from = "FileEntryRecord")]
 pub struct FileEntry {
     /// 文件类型
     pub file_type: char,
@@ -27,20 +69,243 @@ pub struct FileEntry {
     pub size_display: String,
     /// 文件创建时间
     pub created_time: SystemTime,
-    pub path: String,
+    /// 文件修改时间，平台不支持时为 None
+    pub modified_time: Option<SystemTime>,
+    /// 通过 path() 读取完整路径
+    pub location: EntryPath,
     /// 文件名
     pub name: String,
+    /// 按大小高亮规则计算出的级别
+    pub severity: Option<Severity>,
+}
+
+impl FileEntry {
+    pub fn path(&self) -> String {
+        self.location.resolve(&self.name)
+    }
+
+    // 与 path() == path 等价，但不需要拼接完整路径
+    pub fn has_path(&self, path: &str) -> bool {
+        match &self.location {
+            EntryPath::Child(parent) => {
+                let path = Path::new(path);
+                path.parent() == Some(Path::new(&**parent))
+                    && path.file_name() == Some(OsStr::new(&self.name))
+            }
+            EntryPath::Full(full) => &**full == path,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
+struct FileEntryRecord {
+    file_type: char,
+    permissions: String,
+    size_raw: u64,
+    /// size_raw 的十进制字符串。超过 2^53 的大小在 JS number 中会丢失精度，前端需要精确值时读取该字段
+    #[serde(default)]
+    size_raw_exact: String,
+    size_display: String,
+    created_time: SystemTime,
+    #[serde(default)]
+    modified_time: Option<SystemTime>,
+    /// 按配置的时间格式生成，前端直接显示；读取时忽略，序列化时重新生成
+    #[serde(skip_deserializing)]
+    created_display: String,
+    #[serde(skip_deserializing)]
+    modified_display: String,
+    path: String,
+    name: String,
+    /// name 的 NFC 形式，供显示与搜索；读取时忽略，文件操作使用 path 与 name
+    #[serde(skip_deserializing)]
+    display_name: String,
+    #[serde(default)]
+    severity: Option<Severity>,
+}
+
+impl From<FileEntry> for FileEntryRecord {
+    fn from(entry: FileEntry) -> Self {
+        FileEntryRecord {
+            path: entry.path(),
+            file_type: entry.file_type,
+            permissions: entry.permissions,
+            size_raw: entry.size_raw,
+            size_raw_exact: entry.size_raw.to_string(),
+            size_display: entry.size_display,
+            created_display: display_time(entry.created_time),
+            modified_display: entry.modified_time.map(display_time).unwrap_or_default(),
+            created_time: entry.created_time,
+            modified_time: entry.modified_time,
+            display_name: nfc(&entry.name).into_owned(),
+            name: entry.name,
+            severity: entry.severity,
+        }
+    }
+}
+
+impl From<FileEntryRecord> for FileEntry {
+    fn from(record: FileEntryRecord) -> Self {
+        FileEntry {
+            file_type: record.file_type,
+            permissions: record.permissions,
+            // 经过前端回传的 size_raw 可能已丢失精度，优先使用字符串；旧记录没有该字段
+            size_raw: record.size_raw_exact.parse().unwrap_or(record.size_raw),
+            size_display: record.size_display,
+            created_time: record.created_time,
+            modified_time: record.modified_time,
+            location: record.path.into(),
+            name: record.name,
+            severity: record.severity,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DirectoryResult {
+    /// 扫描 ID，可用于对已保存的结果做二次分析
+    pub scan_id: Option<u64>,
     pub entries: Vec<FileEntry>,
     pub query_time: f64,
+    /// 扫描期间的磁盘 I/O 与 CPU 采样，用于排查性能瓶颈
+    pub telemetry: Option<ScanTelemetry>,
+    /// 本次扫描按存储类型选择的线程数与读取方式
+    #[serde(default)]
+    pub scan_plan: Option<ScanPlan>,
+    /// 结果是否完整；旧版本保存的结果按完整处理
+    #[serde(default)]
+    pub completeness: Completeness,
+    /// 未能统计的目录数，按原因分别计数
+    #[serde(default)]
+    pub skipped: SkippedCounts,
+}
+
+// 扫描结果的完整性，序列化为 {"kind": "partial", "reason": "..."}
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Completeness {
+    /// 所有条目都已逐项统计
+    #[default]
+    Complete,
+    /// 部分目录未能读取或被跳过，相关目录及其上级的大小偏小
+    Partial { reason: String },
+    /// 快速统计不记录读取失败的目录，无法确认是否有遗漏，大小只能作为估算
+    Estimated,
+}
+
+impl Completeness {
+    pub fn is_complete(&self) -> bool {
+        *self == Completeness::Complete
+    }
+
+    // 不完整时的说明，用于导出和摘要
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            Completeness::Complete => None,
+            Completeness::Partial { reason } => Some(format!("结果不完整：{}", reason)),
+            Completeness::Estimated => Some("大小为估算值".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkippedCounts {
+    /// 无权限读取的目录
+    pub permission_denied: u64,
+    /// 长时间没有进度的目录
+    pub timed_out: u64,
+    /// 按跳过列表未扫描的已知无法访问的目录
+    pub known_inaccessible: u64,
+}
+
+impl SkippedCounts {
+    pub fn total(&self) -> u64 {
+        self.permission_denied + self.timed_out + self.known_inaccessible
+    }
+
+    // 有目录未统计时结果为部分结果，原因列出各类目录数
+    pub fn completeness(&self) -> Completeness {
+        self.partial_with(None)
+    }
+
+    // 扫描被中断（超时或取消）时的结果：只含已统计完成的 counted 个条目
+    pub fn interrupted(&self, reason: &str, counted: usize) -> Completeness {
+        self.partial_with(Some(format!("{}，仅统计了 {} 个条目", reason, counted)))
+    }
+
+    fn partial_with(&self, interrupted: Option<String>) -> Completeness {
+        let reasons: Vec<String> = interrupted
+            .into_iter()
+            .chain(
+                [
+                    (self.permission_denied, "个目录无权限读取"),
+                    (self.timed_out, "个目录长时间无响应"),
+                    (self.known_inaccessible, "个已知无法访问的目录被跳过"),
+                ]
+                .iter()
+                .filter(|(count, _)| *count > 0)
+                .map(|(count, what)| format!("{} {}", count, what)),
+            )
+            .collect();
+        if reasons.is_empty() {
+            Completeness::Complete
+        } else {
+            Completeness::Partial {
+                reason: reasons.join("，"),
+            }
+        }
+    }
+}
+
+// 进度事件的状态，序列化为 {"type": "file_processed", "count": 3, ...}
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressStatus {
+    /// 开始读取 current_file 指向的目录
+    EnteringDir,
+    /// 已处理的条目数及其累计大小；total 为已知的条目总数
+    FileProcessed {
+        count: u64,
+        bytes: u64,
+        total: Option<u64>,
+    },
+    /// current_file 指向的目录大小计算完成
+    DirCompleted { size: u64 },
+    /// 哈希计算等多阶段操作的进度
+    Stage {
+        stage: String,
+        done: u64,
+        total: u64,
+    },
+    /// 读取 current_file 失败，扫描继续
+    Error { kind: ScanErrorKind },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    PermissionDenied,
+    NotFound,
+    Other,
+}
+
+impl From<&std::io::Error> for ScanErrorKind {
+    fn from(e: &std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ScanErrorKind::PermissionDenied,
+            std::io::ErrorKind::NotFound => ScanErrorKind::NotFound,
+            _ => ScanErrorKind::Other,
+        }
+    }
 }
 
 #[derive(Clone, Serialize)]
 pub struct ProgressEvent {
     pub current_path: String,
     pub current_file: String,
-    pub status: String,
+    pub status: ProgressStatus,
+    /// 所属扫描的 ID，与事件频道 scan://{id}/progress 一致；非扫描类进度为 None
+    pub scan_id: Option<u64>,
+    /// 发起扫描的窗口或标签页，非扫描类进度为 None
+    pub context: Option<String>,
 }
@@ -0,0 +1,30 @@
+// 名称的 Unicode 规范化。macOS 上的文件名多为分解形式（NFD），用户输入与其他系统上的同名文件
+// 为组合形式（NFC），显示、搜索与按名称对齐时统一转为 NFC；文件操作仍使用原始名称
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+pub fn nfc(s: &str) -> Cow<'_, str> {
+    if is_nfc(s) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.nfc().collect())
+    }
+}
+
+// 不区分大小写与规范化形式的比较键，用于搜索和按名称配对
+pub fn fold(s: &str) -> String {
+    nfc(s).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decomposed_names_match_composed_queries() {
+        let decomposed = "Cafe\u{301}.txt";
+        assert_eq!(nfc(decomposed), "Caf\u{e9}.txt");
+        assert!(matches!(nfc("Caf\u{e9}.txt"), Cow::Borrowed(_)));
+        assert_eq!(fold(decomposed), fold("CAF\u{c9}.TXT"));
+    }
+}
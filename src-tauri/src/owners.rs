@@ -0,0 +1,315 @@
+use crate::utils::human_readable_size;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OwnerUsage {
+    /// 用户名，无法解析时为数字 ID
+    pub owner: String,
+    /// Unix 用户 ID，Windows 下为 None
+    pub uid: Option<u32>,
+    pub size_raw: u64,
+    pub size_display: String,
+    pub file_count: u64,
+}
+
+// 文件属主的用户 ID，Windows 下不可用
+#[cfg(unix)]
+pub fn owner_uid(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+pub fn owner_uid(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+// 读取 /etc/passwd 得到 uid -> 用户名 映射
+pub fn user_names() -> HashMap<u32, String> {
+    parse_id_file("/etc/passwd")
+}
+
+// 读取 /etc/group 得到 gid -> 组名 映射
+pub fn group_names() -> HashMap<u32, String> {
+    parse_id_file("/etc/group")
+}
+
+// 用户名或组名解析为 ID，也接受直接传入数字 ID
+fn resolve_id(name: &str, names: &HashMap<u32, String>) -> Option<u32> {
+    name.parse().ok().or_else(|| {
+        names
+            .iter()
+            .find(|(_, n)| n.as_str() == name)
+            .map(|(id, _)| *id)
+    })
+}
+
+fn parse_id_file(path: &str) -> HashMap<u32, String> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+pub fn owner_display(uid: Option<u32>, names: &HashMap<u32, String>) -> String {
+    match uid {
+        Some(uid) => names.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+        None => "unknown".to_string(),
+    }
+}
+
+// 按属主汇总目录下所有文件的大小和数量，按大小降序
+pub fn aggregate_by_owner(root: &Path) -> Vec<OwnerUsage> {
+    let totals = walk_owner_totals(root);
+    let names = user_names();
+    let mut usages: Vec<OwnerUsage> = totals
+        .into_iter()
+        .map(|(uid, (size_raw, file_count))| OwnerUsage {
+            owner: owner_display(uid, &names),
+            uid,
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            file_count,
+        })
+        .collect();
+    usages.sort_by_key(|u| std::cmp::Reverse(u.size_raw));
+    usages
+}
+
+type OwnerTotals = HashMap<Option<u32>, (u64, u64)>;
+
+fn walk_owner_totals(dir: &Path) -> OwnerTotals {
+    let entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().collect(),
+        Err(e) => {
+            eprintln!("无法读取目录 {}: {}", dir.display(), e);
+            return HashMap::new();
+        }
+    };
+
+    entries
+        .par_iter()
+        .map(|entry| {
+            let mut totals = OwnerTotals::new();
+            // 不跟随符号链接，避免重复统计或陷入循环
+            let metadata = match fs::symlink_metadata(entry.path()) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("无法获取文件元数据 {}", e);
+                    return totals;
+                }
+            };
+            if metadata.is_dir() {
+                totals = walk_owner_totals(&entry.path());
+            } else {
+                let slot = totals.entry(owner_uid(&metadata)).or_default();
+                slot.0 += metadata.len();
+                slot.1 += 1;
+            }
+            totals
+        })
+        .reduce(OwnerTotals::new, merge_totals)
+}
+
+fn merge_totals(mut a: OwnerTotals, b: OwnerTotals) -> OwnerTotals {
+    for (uid, (size, count)) in b {
+        let slot = a.entry(uid).or_default();
+        slot.0 += size;
+        slot.1 += count;
+    }
+    a
+}
+
+// 出错条目只保留前若干条，避免整棵树无权限时返回过大的结果
+const MAX_CHOWN_ERRORS: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChownError {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChownReport {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// 成功修改的条目数
+    pub changed: u64,
+    /// 修改失败的条目总数
+    pub failed: u64,
+    /// 失败详情，最多 MAX_CHOWN_ERRORS 条
+    pub errors: Vec<ChownError>,
+}
+
+// 修改属主和属组，user、group 为空表示保持不变。
+// 不跟随符号链接，只修改链接本身；on_progress 收到当前路径和已处理数量
+pub fn change_owner(
+    root: &Path,
+    user: Option<&str>,
+    group: Option<&str>,
+    recursive: bool,
+    on_progress: &dyn Fn(&Path, u64),
+) -> Result<ChownReport, String> {
+    if !cfg!(unix) {
+        return Err("修改属主仅支持 Unix".to_string());
+    }
+    if fs::symlink_metadata(root).is_err() {
+        return Err("路径不存在".to_string());
+    }
+    let uid = match user {
+        Some(user) => {
+            Some(resolve_id(user, &user_names()).ok_or_else(|| format!("用户不存在: {}", user))?)
+        }
+        None => None,
+    };
+    let gid = match group {
+        Some(group) => {
+            Some(resolve_id(group, &group_names()).ok_or_else(|| format!("组不存在: {}", group))?)
+        }
+        None => None,
+    };
+    if uid.is_none() && gid.is_none() {
+        return Err("请至少指定用户或组".to_string());
+    }
+
+    let mut report = ChownReport {
+        uid,
+        gid,
+        ..ChownReport::default()
+    };
+    chown_tree(root, uid, gid, recursive, &mut report, on_progress);
+    Ok(report)
+}
+
+fn chown_tree(
+    path: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    recursive: bool,
+    report: &mut ChownReport,
+    on_progress: &dyn Fn(&Path, u64),
+) {
+    match lchown(path, uid, gid) {
+        Ok(()) => report.changed += 1,
+        Err(e) => record_chown_error(report, path, e),
+    }
+    let processed = report.changed + report.failed;
+    if processed.is_multiple_of(500) {
+        on_progress(path, processed);
+    }
+
+    let is_dir = fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    if !recursive || !is_dir {
+        return;
+    }
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                chown_tree(&entry.path(), uid, gid, true, report, on_progress);
+            }
+        }
+        Err(e) => record_chown_error(report, path, format!("无法读取目录: {}", e)),
+    }
+}
+
+fn record_chown_error(report: &mut ChownReport, path: &Path, error: String) {
+    report.failed += 1;
+    if report.errors.len() < MAX_CHOWN_ERRORS {
+        report.errors.push(ChownError {
+            path: path.to_string_lossy().into_owned(),
+            error,
+        });
+    }
+}
+
+#[cfg(unix)]
+fn lchown(path: &Path, uid: Option<u32>, gid: Option<u32>) -> Result<(), String> {
+    std::os::unix::fs::lchown(path, uid, gid).map_err(|e| match e.raw_os_error() {
+        Some(libc::EPERM) => "权限不足，修改属主通常需要 root 权限".to_string(),
+        _ => e.to_string(),
+    })
+}
+
+#[cfg(not(unix))]
+fn lchown(_path: &Path, _uid: Option<u32>, _gid: Option<u32>) -> Result<(), String> {
+    Err("修改属主仅支持 Unix".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_names_and_numeric_ids() {
+        let names = HashMap::from([(1000, "alice".to_string()), (0, "root".to_string())]);
+        assert_eq!(resolve_id("alice", &names), Some(1000));
+        assert_eq!(resolve_id("42", &names), Some(42));
+        assert_eq!(resolve_id("bob", &names), None);
+    }
+
+    #[test]
+    fn refuses_invalid_requests() {
+        let missing = std::env::temp_dir().join("disksight-chown-missing-path");
+        assert!(change_owner(&missing, Some("0"), None, false, &|_, _| {}).is_err());
+
+        let existing = std::env::temp_dir();
+        assert!(change_owner(&existing, None, None, false, &|_, _| {}).is_err());
+        assert!(change_owner(
+            &existing,
+            Some("disksight-no-such-user"),
+            None,
+            false,
+            &|_, _| {}
+        )
+        .is_err());
+        assert!(change_owner(
+            &existing,
+            None,
+            Some("disksight-no-such-group"),
+            false,
+            &|_, _| {}
+        )
+        .is_err());
+    }
+
+    // 改为当前属主不需要特权，可验证遍历范围与不跟随符号链接
+    #[cfg(unix)]
+    #[test]
+    fn changes_tree_without_following_links() {
+        use std::os::unix::fs::MetadataExt;
+        let dir = std::env::temp_dir().join(format!("disksight-chown-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), b"a").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), b"b").unwrap();
+        // 指向不存在目标的链接：跟随链接时会失败
+        std::os::unix::fs::symlink(dir.join("missing"), dir.join("link")).unwrap();
+        let metadata = fs::metadata(dir.join("a.txt")).unwrap();
+        let (uid, gid) = (metadata.uid().to_string(), metadata.gid().to_string());
+
+        let report = change_owner(&dir, Some(&uid), Some(&gid), false, &|_, _| {}).unwrap();
+        assert_eq!((report.changed, report.failed), (1, 0));
+
+        let report = change_owner(&dir, Some(&uid), Some(&gid), true, &|_, _| {}).unwrap();
+        assert_eq!((report.changed, report.failed), (5, 0));
+        assert_eq!(report.uid, Some(metadata.uid()));
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            fs::symlink_metadata(dir.join("link")).unwrap().uid(),
+            metadata.uid()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use crate::dir_reader::DirChild;
+use crate::drives::{disk_kind, mount_of, physical_device, DiskKind};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// 固态盘（尤其 NVMe）队列深度大，线程数可以超过核心数
+const SSD_MAX_THREADS: usize = 16;
+// 网络共享的瓶颈在往返延迟，用更多线程掩盖等待时间
+const NETWORK_MAX_THREADS: usize = 32;
+
+// 视为网络共享的文件系统类型
+const NETWORK_FILE_SYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smb2",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "9p",
+    "davfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKind {
+    Ssd,
+    /// 机械硬盘，只用单线程并按 inode 顺序读取元数据以减少寻道
+    Hdd,
+    /// NFS、SMB 等网络共享
+    Network,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParallelismSettings {
+    /// 手动指定存储类型，覆盖自动检测结果
+    pub storage: Option<StorageKind>,
+    /// 手动指定扫描线程数，为 1 时按顺序读取
+    pub threads: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanPlan {
+    pub storage: StorageKind,
+    pub threads: usize,
+    /// 单线程并按 inode 顺序遍历，适合机械硬盘
+    pub sequential: bool,
+    /// 存储类型或线程数来自用户配置
+    pub overridden: bool,
+}
+
+impl ScanPlan {
+    // 在按计划配置的线程池中执行扫描，线程池创建失败时退回全局线程池
+    pub fn run<T: Send>(&self, f: impl FnOnce(bool) -> T + Send) -> T {
+        let parallel = !self.sequential;
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+        {
+            Ok(pool) => pool.install(|| f(parallel)),
+            Err(e) => {
+                eprintln!("无法创建扫描线程池，使用默认线程池: {}", e);
+                f(parallel)
+            }
+        }
+    }
+}
+
+// 检测路径所在存储的类型
+pub fn detect_storage(path: &Path) -> StorageKind {
+    if cfg!(windows) && path.to_string_lossy().starts_with(r"\\") {
+        return StorageKind::Network;
+    }
+    let Some(mount) = mount_of(path) else {
+        return StorageKind::Unknown;
+    };
+    if NETWORK_FILE_SYSTEMS.contains(&mount.file_system.as_str()) {
+        return StorageKind::Network;
+    }
+    match physical_device(&mount.device).map(|d| disk_kind(&d)) {
+        Some(DiskKind::Ssd) => StorageKind::Ssd,
+        Some(DiskKind::Hdd) => StorageKind::Hdd,
+        _ => StorageKind::Unknown,
+    }
+}
+
+// 根据存储类型和用户配置决定扫描线程数及读取方式
+pub fn plan_for(path: &Path, settings: &ParallelismSettings) -> ScanPlan {
+    let storage = settings.storage.unwrap_or_else(|| detect_storage(path));
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    plan(storage, cores, settings)
+}
+
+fn plan(storage: StorageKind, cores: usize, settings: &ParallelismSettings) -> ScanPlan {
+    let threads = settings
+        .threads
+        .filter(|&n| n > 0)
+        .unwrap_or(match storage {
+            StorageKind::Hdd => 1,
+            StorageKind::Ssd => (cores * 2).min(SSD_MAX_THREADS),
+            StorageKind::Network => (cores * 4).min(NETWORK_MAX_THREADS),
+            StorageKind::Unknown => cores,
+        });
+    ScanPlan {
+        storage,
+        threads,
+        sequential: threads == 1,
+        overridden: settings.storage.is_some() || settings.threads.is_some_and(|n| n > 0),
+    }
+}
+
+// 按 inode 排序目录项，机械硬盘上 inode 相近的元数据在磁盘上也相邻
+pub fn sort_for_sequential_reads(entries: &mut [DirChild]) {
+    entries.sort_by_key(|e| e.ino);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threads_follow_storage_unless_overridden() {
+        let auto = ParallelismSettings::default();
+        let hdd = plan(StorageKind::Hdd, 8, &auto);
+        assert_eq!(hdd.threads, 1);
+        assert!(hdd.sequential && !hdd.overridden);
+        assert_eq!(plan(StorageKind::Ssd, 12, &auto).threads, SSD_MAX_THREADS);
+        assert_eq!(plan(StorageKind::Network, 4, &auto).threads, 16);
+        assert_eq!(plan(StorageKind::Unknown, 4, &auto).threads, 4);
+
+        let forced = ParallelismSettings {
+            storage: None,
+            threads: Some(3),
+        };
+        let ssd = plan(StorageKind::Ssd, 8, &forced);
+        assert_eq!(ssd.threads, 3);
+        assert!(!ssd.sequential && ssd.overridden);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::utils::home_dir;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+// 命令接收的路径需要满足的条件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expect {
+    /// 只做规范化，不检查是否存在，用于新建文件、导出目标等
+    Any,
+    /// 必须存在，文件或目录均可
+    Exists,
+    /// 必须是已存在的目录
+    Directory,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    Empty,
+    /// 路径中包含空字符，系统调用会在此截断
+    NullByte,
+    /// 无法确定主目录，~ 无法展开
+    NoHomeDirectory,
+    /// 相对路径且调用方没有声明基准目录
+    Relative {
+        path: String,
+    },
+    NotFound {
+        path: String,
+    },
+    NotADirectory {
+        path: String,
+    },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "路径为空"),
+            PathError::NullByte => write!(f, "路径中包含空字符"),
+            PathError::NoHomeDirectory => write!(f, "无法确定用户主目录，不能展开 ~"),
+            PathError::Relative { path } => write!(f, "需要绝对路径: {}", path),
+            PathError::NotFound { path } => write!(f, "路径不存在: {}", path),
+            PathError::NotADirectory { path } => write!(f, "不是目录: {}", path),
+        }
+    }
+}
+
+impl From<PathError> for String {
+    fn from(e: PathError) -> Self {
+        e.to_string()
+    }
+}
+
+// 所有命令接收路径参数时调用：去掉首尾空白和成对引号（从资源管理器复制的路径常带引号），
+// 拒绝空字符，展开 ~，相对路径按 base 解析（没有 base 时报错），去掉多余的 . 和末尾分隔符，
+// 最后按 expect 检查路径是否存在、是否为目录
+pub fn sanitize_path(
+    input: &str,
+    base: Option<&Path>,
+    expect: Expect,
+) -> Result<PathBuf, PathError> {
+    let trimmed = strip_quotes(input.trim()).trim();
+    if trimmed.is_empty() {
+        return Err(PathError::Empty);
+    }
+    if trimmed.contains('\0') {
+        return Err(PathError::NullByte);
+    }
+
+    let expanded = expand_tilde(trimmed)?;
+    let path = if expanded.is_absolute() || has_prefix(&expanded) {
+        expanded
+    } else {
+        match base {
+            Some(base) => base.join(expanded),
+            None => {
+                return Err(PathError::Relative {
+                    path: trimmed.to_string(),
+                })
+            }
+        }
+    };
+    let path: PathBuf = path
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect();
+
+    let display = || path.to_string_lossy().into_owned();
+    match expect {
+        Expect::Any => {}
+        Expect::Exists => {
+            // symlink_metadata：指向不存在目标的符号链接本身也算存在
+            if std::fs::symlink_metadata(&path).is_err() {
+                return Err(PathError::NotFound { path: display() });
+            }
+        }
+        Expect::Directory => match std::fs::metadata(&path) {
+            Ok(m) if m.is_dir() => {}
+            Ok(_) => return Err(PathError::NotADirectory { path: display() }),
+            Err(_) => return Err(PathError::NotFound { path: display() }),
+        },
+    }
+    Ok(path)
+}
+
+// 对一组路径逐个调用 sanitize_path，遇到第一个错误即返回
+pub fn sanitize_paths(
+    inputs: &[String],
+    base: Option<&Path>,
+    expect: Expect,
+) -> Result<Vec<PathBuf>, PathError> {
+    inputs
+        .iter()
+        .map(|input| sanitize_path(input, base, expect))
+        .collect()
+}
+
+fn strip_quotes(s: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    s
+}
+
+fn expand_tilde(path: &str) -> Result<PathBuf, PathError> {
+    let rest = if path == "~" {
+        ""
+    } else if let Some(rest) = path
+        .strip_prefix("~/")
+        .or_else(|| cfg!(windows).then(|| path.strip_prefix("~\\")).flatten())
+    {
+        rest
+    } else {
+        // ~user 形式不支持，按普通路径处理
+        return Ok(PathBuf::from(path));
+    };
+    let home = home_dir().ok_or(PathError::NoHomeDirectory)?;
+    Ok(if rest.is_empty() {
+        home
+    } else {
+        home.join(rest)
+    })
+}
+
+// Windows 上 "C:foo" 这类带盘符的相对路径不应再拼接基准目录
+fn has_prefix(path: &Path) -> bool {
+    matches!(path.components().next(), Some(Component::Prefix(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn normalizes_user_input() {
+        let base = std::env::temp_dir().join(format!("disksight-paths-{}", std::process::id()));
+        fs::create_dir_all(base.join("sub")).unwrap();
+        fs::write(base.join("file.txt"), b"").unwrap();
+
+        let sub = base.join("sub");
+        let quoted = format!("  \"{}\" ", sub.display());
+        assert_eq!(
+            sanitize_path(&quoted, None, Expect::Directory),
+            Ok(sub.clone())
+        );
+        assert_eq!(
+            sanitize_path("./sub/", Some(&base), Expect::Directory),
+            Ok(sub.clone())
+        );
+        assert_eq!(
+            sanitize_path("'file.txt'", Some(&base), Expect::Exists),
+            Ok(base.join("file.txt"))
+        );
+        if let Some(home) = home_dir() {
+            assert_eq!(sanitize_path("~", None, Expect::Any), Ok(home.clone()));
+            assert_eq!(
+                sanitize_path("~/a/b", None, Expect::Any),
+                Ok(home.join("a").join("b"))
+            );
+        }
+
+        assert_eq!(
+            sanitize_path("  ", None, Expect::Any),
+            Err(PathError::Empty)
+        );
+        assert_eq!(
+            sanitize_path("\"\"", None, Expect::Any),
+            Err(PathError::Empty)
+        );
+        assert_eq!(
+            sanitize_path("a\0b", Some(&base), Expect::Any),
+            Err(PathError::NullByte)
+        );
+        assert!(matches!(
+            sanitize_path("sub", None, Expect::Any),
+            Err(PathError::Relative { .. })
+        ));
+        assert!(matches!(
+            sanitize_path("missing", Some(&base), Expect::Exists),
+            Err(PathError::NotFound { .. })
+        ));
+        assert!(matches!(
+            sanitize_path("file.txt", Some(&base), Expect::Directory),
+            Err(PathError::NotADirectory { .. })
+        ));
+        assert_eq!(
+            sanitize_path("missing", Some(&base), Expect::Any),
+            Ok(base.join("missing"))
+        );
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+// 各平台差异的集中入口：启动外部程序时隐藏控制台窗口、电源与计费网络状态、后台线程优先级，
+// 并报告当前系统支持哪些功能，前端据此隐藏不可用的操作，而不是点击后才收到“不支持”的错误
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+// Windows 上启动控制台程序时不创建控制台窗口
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+// 批量读取目录项使用的系统接口，见 dir_reader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanBackend {
+    /// Linux 的 getdents64 + fstatat
+    Getdents,
+    /// Windows 的 FindFirstFileExW 大批量模式
+    FindFirstFile,
+    /// 标准库 read_dir
+    ReadDir,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlatformCapabilities {
+    /// 与 std::env::consts::OS 相同，如 "windows"、"macos"、"linux"
+    pub os: String,
+    /// 可以通过管理员辅助进程读取 NTFS 卷的 MFT
+    pub supports_mft_scan: bool,
+    /// 可以把文件移到回收站
+    pub supports_trash: bool,
+    /// 可以逐项列出并清空回收站，否则按回收站目录处理
+    pub supports_trash_listing: bool,
+    /// 已安装 smartctl，可以读取磁盘健康状态
+    pub supports_smart: bool,
+    /// 可以修改只读、隐藏等 Windows 文件属性
+    pub supports_file_attributes: bool,
+    /// 可以按八进制权限位修改 Unix 权限
+    pub supports_chmod: bool,
+    pub scan_backend: ScanBackend,
+}
+
+// 创建外部程序的 Command，Windows 上不弹出控制台窗口
+pub fn command(program: impl AsRef<OsStr>) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+pub fn supports_mft_scan() -> bool {
+    cfg!(windows)
+}
+
+pub fn supports_trash() -> bool {
+    cfg!(any(windows, target_os = "macos", target_os = "linux"))
+}
+
+pub fn supports_trash_listing() -> bool {
+    cfg!(windows)
+}
+
+// 是否能运行 smartctl；只检测一次，安装后需要重启应用
+pub fn supports_smart() -> bool {
+    static SMARTCTL: OnceLock<bool> = OnceLock::new();
+    *SMARTCTL.get_or_init(|| {
+        command("smartctl")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+pub fn supports_file_attributes() -> bool {
+    cfg!(windows)
+}
+
+pub fn supports_chmod() -> bool {
+    cfg!(unix)
+}
+
+pub fn scan_backend() -> ScanBackend {
+    if cfg!(target_os = "linux") {
+        ScanBackend::Getdents
+    } else if cfg!(windows) {
+        ScanBackend::FindFirstFile
+    } else {
+        ScanBackend::ReadDir
+    }
+}
+
+pub fn capabilities() -> PlatformCapabilities {
+    PlatformCapabilities {
+        os: std::env::consts::OS.to_string(),
+        supports_mft_scan: supports_mft_scan(),
+        supports_trash: supports_trash(),
+        supports_trash_listing: supports_trash_listing(),
+        supports_smart: supports_smart(),
+        supports_file_attributes: supports_file_attributes(),
+        supports_chmod: supports_chmod(),
+        scan_backend: scan_backend(),
+    }
+}
+
+// 是否正在使用电池供电，无法判断（如台式机、不支持的平台）时返回 None
+pub fn on_battery() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut found = false;
+        for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let read = |name: &str| {
+                std::fs::read_to_string(entry.path().join(name))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default()
+            };
+            match read("type").as_str() {
+                "Mains" if read("online") == "1" => return Some(false),
+                "Battery" => {
+                    found = true;
+                    if read("status") == "Discharging" {
+                        return Some(true);
+                    }
+                }
+                _ => {}
+            }
+        }
+        found.then_some(false)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = command("pmset").args(["-g", "batt"]).output().ok()?;
+        parse_pmset(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(windows)]
+    {
+        use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        match status.ACLineStatus {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        None
+    }
+}
+
+// 当前网络连接是否按流量计费，无法判断时返回 None。
+// Linux 读取 NetworkManager 的 Metered 属性，Windows 读取当前连接的费用类型
+pub fn is_metered() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = command("busctl")
+            .args([
+                "get-property",
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+                "Metered",
+            ])
+            .output()
+            .ok()?;
+        parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(windows)]
+    {
+        let output = command("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType",
+            ])
+            .output()
+            .ok()?;
+        parse_cost_type(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        None
+    }
+}
+
+// NetworkManager 的 Metered 属性，如 "u 4"：1、3 为计费（3 为推测），2、4 为不计费，0 为未知
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.trim().strip_prefix("u ")?.trim() {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+// NetworkCostType：Unrestricted 为不计费，Fixed、Variable 为计费，Unknown 或没有连接时无法判断
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_cost_type(output: &str) -> Option<bool> {
+    match output.trim() {
+        "Unrestricted" => Some(false),
+        "Fixed" | "Variable" => Some(true),
+        _ => None,
+    }
+}
+
+// 解析 pmset -g batt 的第一行，如 "Now drawing from 'Battery Power'"
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> Option<bool> {
+    let first = output.lines().next()?;
+    if first.contains("'Battery Power'") {
+        Some(true)
+    } else if first.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// 降低当前线程的 CPU 与 I/O 优先级，用于不急于完成的后台任务。
+// 之后由该线程创建的线程（如扫描线程池）在 Linux 上继承同样的优先级
+pub fn lower_thread_priority() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        // Linux 的 nice 值按线程生效，who 为 0 表示调用线程
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+    }
+    #[cfg(target_os = "macos")]
+    unsafe {
+        libc::setpriority(libc::PRIO_DARWIN_THREAD, 0, libc::PRIO_DARWIN_BG);
+    }
+    #[cfg(windows)]
+    unsafe {
+        use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+        use winapi::um::winbase::THREAD_MODE_BACKGROUND_BEGIN;
+        SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN as i32);
+    }
+}
+
+// 降低整个进程的 CPU 与 I/O 优先级，用于后台扫描的工作进程。
+// macOS 与 Windows 的线程后台模式不会传给子进程，需由子进程自己设置
+pub fn lower_process_priority() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        // 在主线程上设置，之后创建的扫描线程继承
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+    }
+    #[cfg(target_os = "macos")]
+    unsafe {
+        libc::setpriority(libc::PRIO_DARWIN_PROCESS, 0, libc::PRIO_DARWIN_BG);
+    }
+    #[cfg(windows)]
+    unsafe {
+        use winapi::um::processthreadsapi::{GetCurrentProcess, SetPriorityClass};
+        use winapi::um::winbase::PROCESS_MODE_BACKGROUND_BEGIN;
+        SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN);
+    }
+}
+
+// 功能不可用时返回的错误
+pub fn unsupported(feature: &str) -> String {
+    format!("{}在当前系统（{}）上不可用", feature, std::env::consts::OS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pmset_reports_the_power_source() {
+        assert_eq!(
+            parse_pmset("Now drawing from 'Battery Power'\n -InternalBattery-0\t80%; discharging"),
+            Some(true)
+        );
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'\n"), Some(false));
+        assert_eq!(parse_pmset(""), None);
+    }
+
+    #[test]
+    fn metered_state_is_parsed_from_each_platform() {
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 4\n"), Some(false));
+        assert_eq!(parse_nm_metered("u 0\n"), None);
+        assert_eq!(parse_nm_metered(""), None);
+        assert_eq!(parse_cost_type("Variable\r\n"), Some(true));
+        assert_eq!(parse_cost_type("Unrestricted"), Some(false));
+        assert_eq!(parse_cost_type("Unknown"), None);
+    }
+}
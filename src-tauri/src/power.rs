@@ -0,0 +1,154 @@
+// 电池供电与按流量计费网络的检测结果，以及据此推迟哪些后台任务：
+// 使用电池时推迟定时脚本、后台索引和启动预扫描；计费网络上推迟对代理的远程扫描。
+// 用户发起的扫描不受影响；检测不准确时可在设置中强制指定
+use crate::config::ConfigStore;
+use crate::index::FileIndex;
+use crate::platform;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const EVENT: &str = "power-state";
+
+// 检测结果的缓存时间，Windows 上检测计费网络需要启动 PowerShell
+const DETECT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerSettings {
+    /// 使用电池时推迟后台任务
+    pub defer_on_battery: bool,
+    /// 计费网络上推迟远程扫描
+    pub defer_on_metered: bool,
+    /// 强制视为使用电池（true）或接通电源（false），为 None 时自动检测
+    pub on_battery: Option<bool>,
+    /// 强制视为计费网络（true）或不计费（false），为 None 时自动检测
+    pub metered: Option<bool>,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        PowerSettings {
+            defer_on_battery: true,
+            defer_on_metered: true,
+            on_battery: None,
+            metered: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerState {
+    /// 无法判断时为 None，按接通电源处理
+    pub on_battery: Option<bool>,
+    /// 无法判断时为 None，按不计费处理
+    pub metered: Option<bool>,
+    /// 定时脚本、后台索引与启动预扫描当前被推迟
+    pub defer_background: bool,
+    /// 对代理的远程扫描当前被推迟
+    pub defer_remote: bool,
+}
+
+impl PowerSettings {
+    // 以设置中的强制值覆盖检测结果，得出当前要推迟的任务
+    pub fn resolve(&self, on_battery: Option<bool>, metered: Option<bool>) -> PowerState {
+        let on_battery = self.on_battery.or(on_battery);
+        let metered = self.metered.or(metered);
+        PowerState {
+            on_battery,
+            metered,
+            defer_background: self.defer_on_battery && on_battery == Some(true),
+            defer_remote: self.defer_on_metered && metered == Some(true),
+        }
+    }
+}
+
+type Detected = (Option<bool>, Option<bool>);
+
+static LAST_DETECTED: Mutex<Option<(Instant, Detected)>> = Mutex::new(None);
+
+// 检测电源与网络状态，DETECT_INTERVAL 内重复调用返回缓存的结果
+fn detect() -> Detected {
+    let mut last = LAST_DETECTED.lock().unwrap();
+    if let Some((at, detected)) = *last {
+        if at.elapsed() < DETECT_INTERVAL {
+            return detected;
+        }
+    }
+    let detected = (platform::on_battery(), platform::is_metered());
+    *last = Some((Instant::now(), detected));
+    detected
+}
+
+// 当前的电源与网络状态
+pub fn current(settings: &PowerSettings) -> PowerState {
+    let (on_battery, metered) = detect();
+    settings.resolve(on_battery, metered)
+}
+
+// 定期检测电源与网络状态，变化时发送 power-state 事件；
+// 首次检测或后台任务不再需要推迟时，开始建立尚未建立（或之前被推迟）的索引
+pub fn start_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last: Option<PowerState> = None;
+        loop {
+            let (settings, roots) = {
+                let config = app.state::<Mutex<ConfigStore>>();
+                let config = config.lock().unwrap();
+                (
+                    config.get().power.clone(),
+                    config.get().indexed_roots.clone(),
+                )
+            };
+            let state = current(&settings);
+            if last.as_ref() != Some(&state) {
+                let resumed =
+                    last.as_ref().is_none_or(|l| l.defer_background) && !state.defer_background;
+                if resumed {
+                    app.state::<FileIndex>().set_roots(&roots, false);
+                }
+                let _ = app.emit(EVENT, &state);
+                last = Some(state);
+            }
+            std::thread::sleep(DETECT_INTERVAL);
+        }
+    });
+}
+
+// 远程扫描被推迟时的错误
+pub fn remote_deferred() -> String {
+    "当前网络按流量计费，已推迟远程扫描；可在设置中关闭此行为或强制执行".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_detection() {
+        let settings = PowerSettings::default();
+        let state = settings.resolve(Some(true), None);
+        assert!(state.defer_background);
+        assert!(!state.defer_remote);
+        assert!(!settings.resolve(None, None).defer_background);
+
+        let forced = PowerSettings {
+            on_battery: Some(false),
+            metered: Some(true),
+            ..Default::default()
+        };
+        let state = forced.resolve(Some(true), Some(false));
+        assert_eq!((state.on_battery, state.metered), (Some(false), Some(true)));
+        assert!(!state.defer_background);
+        assert!(state.defer_remote);
+
+        let never = PowerSettings {
+            defer_on_battery: false,
+            defer_on_metered: false,
+            ..Default::default()
+        };
+        let state = never.resolve(Some(true), Some(true));
+        assert!(!state.defer_background && !state.defer_remote);
+    }
+}
@@ -0,0 +1,132 @@
+// 各用户配置文件目录（Windows 的 C:\Users）的大小及其中最大的子目录，供管理员清理共用电脑。
+// 普通权限无法读取其他用户的目录，完整统计需要通过管理员辅助进程执行
+use crate::utils::{human_readable_size, sum_sizes};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 每个配置文件列出的最大子目录数
+const TOP_FOLDERS: usize = 8;
+// 系统自带的共享配置文件，不属于任何用户
+const SHARED_PROFILES: [&str; 4] = ["Public", "Default", "Default User", "All Users"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileFolder {
+    pub name: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// 用户名，即配置文件目录名
+    pub name: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// 按大小降序，最多 TOP_FOLDERS 个
+    pub top_folders: Vec<ProfileFolder>,
+    /// 无权限读取的目录数，大于 0 时大小偏小
+    pub inaccessible_dirs: u64,
+}
+
+// 存放用户配置文件的目录
+pub fn users_dir() -> PathBuf {
+    if cfg!(windows) {
+        let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+        PathBuf::from(format!("{}\\Users", drive))
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Users")
+    } else {
+        PathBuf::from("/home")
+    }
+}
+
+// 列出所有用户配置文件，按大小降序
+pub fn profiles_report() -> Result<Vec<UserProfile>, String> {
+    let root = users_dir();
+    let entries = fs::read_dir(&root).map_err(|e| format!("{}: {}", root.display(), e))?;
+    let dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            !SHARED_PROFILES.contains(&name.as_ref()) && !name.starts_with('.')
+        })
+        .map(|e| e.path())
+        .collect();
+    let mut profiles: Vec<UserProfile> = dirs.par_iter().map(|dir| measure_profile(dir)).collect();
+    profiles.sort_by_key(|p| std::cmp::Reverse(p.size_raw));
+    Ok(profiles)
+}
+
+fn measure_profile(dir: &Path) -> UserProfile {
+    let mut folders = Vec::new();
+    let mut files = 0u64;
+    let mut inaccessible_dirs = 0;
+    match fs::read_dir(dir) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // 不跟随符号链接和目录联接（如 Application Data）
+                let Ok(metadata) = fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    let (size, denied) = walk(&path);
+                    inaccessible_dirs += denied;
+                    folders.push(ProfileFolder {
+                        name: entry.file_name().to_string_lossy().into_owned(),
+                        path: path.to_string_lossy().into_owned(),
+                        size_raw: size,
+                        size_display: human_readable_size(size),
+                    });
+                } else if metadata.is_file() {
+                    files = files.saturating_add(metadata.len());
+                }
+            }
+        }
+        Err(_) => inaccessible_dirs += 1,
+    }
+
+    let size_raw = sum_sizes(folders.iter().map(|f| f.size_raw).chain([files]));
+    folders.sort_by_key(|f| std::cmp::Reverse(f.size_raw));
+    folders.truncate(TOP_FOLDERS);
+    UserProfile {
+        name: dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: dir.to_string_lossy().into_owned(),
+        size_raw,
+        size_display: human_readable_size(size_raw),
+        top_folders: folders,
+        inaccessible_dirs,
+    }
+}
+
+// 返回 (总大小, 无权限读取的目录数)
+fn walk(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 1);
+    };
+    let mut bytes = 0u64;
+    let mut denied = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            let (size, sub_denied) = walk(&path);
+            bytes = bytes.saturating_add(size);
+            denied += sub_denied;
+        } else if metadata.is_file() {
+            bytes = bytes.saturating_add(metadata.len());
+        }
+    }
+    (bytes, denied)
+}
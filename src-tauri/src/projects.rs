@@ -0,0 +1,234 @@
+// 按项目类型识别目录（Cargo、npm、Python、Unity、Unreal），并把每个项目的占用
+// 分为源码、依赖和构建产物三部分。识别到项目后不再向下查找，monorepo 按最外层项目统计
+use crate::utils::{dir_size, human_readable_size, sum_sizes};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Cargo,
+    Npm,
+    Python,
+    Unity,
+    Unreal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Footprint {
+    Source,
+    /// 包管理器安装的依赖，可重新安装
+    Dependencies,
+    /// 编译输出和缓存，可重新生成
+    BuildOutput,
+}
+
+impl ProjectKind {
+    // 按标志文件识别项目类型，引擎项目优先于其中可能附带的 package.json 等文件
+    pub fn detect(dir: &Path) -> Option<ProjectKind> {
+        let has = |name: &str| dir.join(name).exists();
+        if has_extension(dir, "uproject") {
+            Some(ProjectKind::Unreal)
+        } else if dir.join("Assets").is_dir() && dir.join("ProjectSettings").is_dir() {
+            Some(ProjectKind::Unity)
+        } else if has("Cargo.toml") {
+            Some(ProjectKind::Cargo)
+        } else if has("package.json") {
+            Some(ProjectKind::Npm)
+        } else if ["pyproject.toml", "setup.py", "requirements.txt", "Pipfile"]
+            .iter()
+            .any(|name| has(name))
+        {
+            Some(ProjectKind::Python)
+        } else {
+            None
+        }
+    }
+
+    // 按项目根目录下一级条目的名称判断其属于哪部分
+    pub fn classify(self, name: &str) -> Footprint {
+        let (dependencies, build): (&[&str], &[&str]) = match self {
+            ProjectKind::Cargo => (&["vendor"], &["target"]),
+            ProjectKind::Npm => (
+                &["node_modules", ".pnpm-store", ".yarn"],
+                &[
+                    "dist",
+                    "build",
+                    "out",
+                    "coverage",
+                    ".next",
+                    ".nuxt",
+                    ".turbo",
+                    ".parcel-cache",
+                ],
+            ),
+            ProjectKind::Python => (
+                &[".venv", "venv", "env", "__pypackages__"],
+                &[
+                    "build",
+                    "dist",
+                    "__pycache__",
+                    ".pytest_cache",
+                    ".mypy_cache",
+                    ".tox",
+                ],
+            ),
+            ProjectKind::Unity => (&["Packages"], &["Library", "Temp", "Obj", "Logs", "Builds"]),
+            ProjectKind::Unreal => (
+                &[],
+                &[
+                    "Binaries",
+                    "Intermediate",
+                    "Saved",
+                    "DerivedDataCache",
+                    "Build",
+                ],
+            ),
+        };
+        if dependencies.iter().any(|d| d.eq_ignore_ascii_case(name)) {
+            Footprint::Dependencies
+        } else if build.iter().any(|b| b.eq_ignore_ascii_case(name))
+            || (self == ProjectKind::Python && name.ends_with(".egg-info"))
+        {
+            Footprint::BuildOutput
+        } else {
+            Footprint::Source
+        }
+    }
+}
+
+fn has_extension(dir: &Path, extension: &str) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|e| e.path().extension().is_some_and(|ext| ext == extension))
+    })
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Project {
+    pub path: String,
+    pub name: String,
+    pub kind: ProjectKind,
+    pub source_bytes: u64,
+    pub dependency_bytes: u64,
+    pub build_bytes: u64,
+    pub total_bytes: u64,
+    pub total_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectReport {
+    pub root: String,
+    /// 按总大小降序
+    pub projects: Vec<Project>,
+    pub source_bytes: u64,
+    pub dependency_bytes: u64,
+    pub build_bytes: u64,
+}
+
+pub fn project_report(root: &Path) -> ProjectReport {
+    let mut projects = find_projects(root);
+    projects.sort_by_key(|p| std::cmp::Reverse(p.total_bytes));
+    ProjectReport {
+        root: root.to_string_lossy().into_owned(),
+        source_bytes: sum_sizes(projects.iter().map(|p| p.source_bytes)),
+        dependency_bytes: sum_sizes(projects.iter().map(|p| p.dependency_bytes)),
+        build_bytes: sum_sizes(projects.iter().map(|p| p.build_bytes)),
+        projects,
+    }
+}
+
+fn find_projects(dir: &Path) -> Vec<Project> {
+    if let Some(kind) = ProjectKind::detect(dir) {
+        return vec![measure(dir, kind)];
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let dirs: Vec<_> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    let found: Vec<Vec<Project>> = dirs.par_iter().map(|d| find_projects(d)).collect();
+    found.concat()
+}
+
+fn measure(dir: &Path, kind: ProjectKind) -> Project {
+    let mut totals = [0u64; 3];
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // 不跟随符号链接
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let size = if metadata.is_dir() {
+                dir_size(&path)
+            } else {
+                metadata.len()
+            };
+            let slot = match kind.classify(&entry.file_name().to_string_lossy()) {
+                Footprint::Source => 0,
+                Footprint::Dependencies => 1,
+                Footprint::BuildOutput => 2,
+            };
+            totals[slot] = totals[slot].saturating_add(size);
+        }
+    }
+    let total_bytes = sum_sizes(totals);
+    Project {
+        path: dir.to_string_lossy().into_owned(),
+        name: dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        kind,
+        source_bytes: totals[0],
+        dependency_bytes: totals[1],
+        build_bytes: totals[2],
+        total_bytes,
+        total_display: human_readable_size(total_bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_projects_by_footprint() {
+        let root = std::env::temp_dir().join(format!("disksight-projects-{}", std::process::id()));
+        let crate_dir = root.join("tool");
+        let web = root.join("apps").join("web");
+        fs::create_dir_all(crate_dir.join("target")).unwrap();
+        fs::create_dir_all(web.join("node_modules")).unwrap();
+        fs::write(crate_dir.join("Cargo.toml"), b"[package]").unwrap();
+        fs::write(crate_dir.join("target").join("tool"), b"12345678").unwrap();
+        fs::write(web.join("package.json"), b"{}").unwrap();
+        fs::write(web.join("node_modules").join("dep.js"), b"1234").unwrap();
+
+        let report = project_report(&root);
+        assert_eq!(report.projects.len(), 2);
+        let cargo = report
+            .projects
+            .iter()
+            .find(|p| p.kind == ProjectKind::Cargo)
+            .unwrap();
+        assert_eq!(cargo.build_bytes, 8);
+        assert_eq!(cargo.source_bytes, 9);
+        let npm = report
+            .projects
+            .iter()
+            .find(|p| p.kind == ProjectKind::Npm)
+            .unwrap();
+        assert_eq!(npm.dependency_bytes, 4);
+        assert_eq!(npm.name, "web");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+// macOS 的可清除空间与 Time Machine 本地快照。Finder 显示的可用空间包含系统在需要时
+// 会自动释放的可清除空间（本地快照、可重新下载的 iCloud 文件和缓存），比 statvfs 报告的可用空间大
+use crate::drives::{mount_of, volume_space};
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+const SNAPSHOT_PREFIX: &str = "com.apple.TimeMachine.";
+// tmutil thinlocalsnapshots 的紧急程度，4 为最高，尽可能删除快照
+const THIN_URGENCY: &str = "4";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LocalSnapshot {
+    /// 如 com.apple.TimeMachine.2024-03-01-101245.local
+    pub name: String,
+    /// 快照创建时间，如 2024-03-01-101245
+    pub date: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PurgeableReport {
+    pub mount_point: String,
+    /// statvfs 报告的可用空间，即 DiskSight 其他地方显示的可用空间
+    pub available_bytes: u64,
+    /// Finder 显示的可用空间，包含可清除空间
+    pub finder_available_bytes: Option<u64>,
+    pub purgeable_bytes: Option<u64>,
+    pub available_display: String,
+    pub finder_available_display: Option<String>,
+    pub purgeable_display: Option<String>,
+    /// 按时间升序；APFS 不提供单个快照的大小，其占用计入可清除空间
+    pub snapshots: Vec<LocalSnapshot>,
+}
+
+// 统计路径所在卷的可清除空间和本地快照（仅 macOS）
+pub fn purgeable_report(path: &Path) -> Result<PurgeableReport, String> {
+    if !cfg!(target_os = "macos") {
+        return Err("仅支持 macOS".to_string());
+    }
+    let mount = mount_of(path).ok_or_else(|| "无法确定路径所在的卷".to_string())?;
+    let root = Path::new(&mount.mount_point);
+    let (_, available) = volume_space(root).ok_or_else(|| "无法读取卷容量".to_string())?;
+    let finder_available = important_usage_capacity(root);
+    let purgeable = finder_available.map(|f| f.saturating_sub(available));
+
+    Ok(PurgeableReport {
+        available_bytes: available,
+        finder_available_bytes: finder_available,
+        purgeable_bytes: purgeable,
+        available_display: human_readable_size(available),
+        finder_available_display: finder_available.map(human_readable_size),
+        purgeable_display: purgeable.map(human_readable_size),
+        snapshots: list_local_snapshots(root),
+        mount_point: mount.mount_point,
+    })
+}
+
+// 让 Time Machine 删除本地快照，直到卷上至少释放 bytes 字节或没有可删除的快照。
+// 返回卷可用空间的增加量
+pub fn thin_local_snapshots(path: &Path, bytes: u64) -> Result<u64, String> {
+    if !cfg!(target_os = "macos") {
+        return Err("仅支持 macOS".to_string());
+    }
+    let mount = mount_of(path).ok_or_else(|| "无法确定路径所在的卷".to_string())?;
+    let root = Path::new(&mount.mount_point);
+    let before = volume_space(root).map(|(_, free)| free);
+    let output = Command::new("tmutil")
+        .arg("thinlocalsnapshots")
+        .arg(root)
+        .args([&bytes.to_string(), THIN_URGENCY])
+        .output()
+        .map_err(|e| format!("无法运行 tmutil: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "tmutil 执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let after = volume_space(root).map(|(_, free)| free);
+    Ok(match (before, after) {
+        (Some(before), Some(after)) => after.saturating_sub(before),
+        _ => 0,
+    })
+}
+
+// Finder 使用的 NSURLVolumeAvailableCapacityForImportantUsageKey，通过 JavaScript for Automation 读取
+fn important_usage_capacity(root: &Path) -> Option<u64> {
+    let script = format!(
+        "ObjC.import('Foundation');\
+         var key = $.NSURLVolumeAvailableCapacityForImportantUsageKey;\
+         var url = $.NSURL.fileURLWithPath({:?});\
+         var values = url.resourceValuesForKeysError($([key]), null);\
+         String(values.objectForKey(key).longLongValue);",
+        root.to_string_lossy()
+    );
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", &script])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn list_local_snapshots(root: &Path) -> Vec<LocalSnapshot> {
+    let Ok(output) = Command::new("tmutil")
+        .arg("listlocalsnapshots")
+        .arg(root)
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_snapshots(&String::from_utf8_lossy(&output.stdout))
+}
+
+// 每行一个快照名；较新的系统在第一行输出 "Snapshots for disk /:"
+fn parse_snapshots(output: &str) -> Vec<LocalSnapshot> {
+    let mut snapshots: Vec<LocalSnapshot> = output
+        .lines()
+        .map(str::trim)
+        .filter_map(|name| {
+            let date = name
+                .strip_prefix(SNAPSHOT_PREFIX)?
+                .trim_end_matches(".local");
+            Some(LocalSnapshot {
+                name: name.to_string(),
+                date: date.to_string(),
+            })
+        })
+        .collect();
+    snapshots.sort_by(|a, b| a.date.cmp(&b.date));
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tmutil_snapshot_list() {
+        let output = "Snapshots for disk /:\n\
+                      com.apple.TimeMachine.2024-03-02-091500.local\n\
+                      com.apple.TimeMachine.2024-03-01-101245.local\n";
+        let snapshots = parse_snapshots(output);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].date, "2024-03-01-101245");
+        assert_eq!(
+            snapshots[1].name,
+            "com.apple.TimeMachine.2024-03-02-091500.local"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+// 隔离：介于保留和删除之间的做法。把条目移到同一卷上按日期命名的隔离目录中，保留原来的相对路径，
+// 需要时可以原样恢复，超过保留天数后自动清除
+use crate::deletion::refuse_deletion;
+use crate::drives::{list_drives, mount_of};
+use crate::time_format::{date, parse_date};
+use crate::utils::{dir_size, home_dir, human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const QUARANTINE_DIR: &str = ".disksight-quarantine";
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantinedItem {
+    pub original: String,
+    pub quarantined: String,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuarantineOutcome {
+    pub moved: Vec<QuarantinedItem>,
+    /// (路径, 原因)
+    pub errors: Vec<(String, String)>,
+}
+
+// 某一天隔离的所有条目
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantineBatch {
+    /// 隔离目录所在的目录（卷根目录或用户主目录），即原路径的起点
+    pub anchor: String,
+    pub date: String,
+    pub path: String,
+    pub size_raw: u64,
+    pub size_display: String,
+    /// 自动清除的日期，日期名无法识别时为 None
+    pub purge_after: Option<String>,
+}
+
+// 用户主目录下的条目隔离到主目录中，不需要卷根目录的写权限；其余的隔离到所在卷的根目录
+fn anchor_of(path: &Path) -> Option<PathBuf> {
+    let mount = mount_of(path).map(|m| PathBuf::from(m.mount_point))?;
+    match home_dir() {
+        Some(home) if path.starts_with(&home) && home.starts_with(&mount) => Some(home),
+        _ => Some(mount),
+    }
+}
+
+fn target_for(path: &Path, anchor: &Path, day: &str) -> Option<PathBuf> {
+    let relative = path.strip_prefix(anchor).ok()?;
+    Some(anchor.join(QUARANTINE_DIR).join(day).join(relative))
+}
+
+// 把条目移入当天的隔离目录，逐项处理，单项失败不影响其他条目
+pub fn quarantine(paths: &[String]) -> QuarantineOutcome {
+    let day = date(SystemTime::now());
+    let mut outcome = QuarantineOutcome::default();
+    for path in paths {
+        match quarantine_one(Path::new(path), &day) {
+            Ok(item) => outcome.moved.push(item),
+            Err(e) => outcome.errors.push((path.clone(), e)),
+        }
+    }
+    outcome
+}
+
+fn quarantine_one(path: &Path, day: &str) -> Result<QuarantinedItem, String> {
+    let path = resolve(path)?;
+    let anchor = anchor_of(&path).ok_or_else(|| "无法确定所在的卷".to_string())?;
+    quarantine_into(&path, &anchor, day)
+}
+
+// 只规范化上级目录再拼上原名称：条目本身是符号链接时隔离的是链接，而不是它指向的目标
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    let name = path.file_name().ok_or_else(|| "无法获取名称".to_string())?;
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let path = parent
+        .canonicalize()
+        .map_err(|e| format!("无法访问: {}", e))?
+        .join(name);
+    fs::symlink_metadata(&path).map_err(|e| format!("无法访问: {}", e))?;
+    Ok(path)
+}
+
+fn quarantine_into(path: &Path, anchor: &Path, day: &str) -> Result<QuarantinedItem, String> {
+    if let Some(reason) = refuse_deletion(path) {
+        return Err(reason.to_string());
+    }
+    if path.components().any(|c| c.as_os_str() == QUARANTINE_DIR) {
+        return Err("已在隔离目录中".to_string());
+    }
+    let target = target_for(path, anchor, day).ok_or_else(|| "无法确定隔离位置".to_string())?;
+    if fs::symlink_metadata(&target).is_ok() {
+        return Err(format!("隔离目录中已有同名条目: {}", target.display()));
+    }
+    let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    let bytes = if metadata.is_dir() {
+        dir_size(path)
+    } else {
+        metadata.len()
+    };
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建隔离目录: {}", e))?;
+    }
+    // 同一卷上只是重命名，不复制数据
+    fs::rename(path, &target).map_err(|e| format!("移入隔离目录失败: {}", e))?;
+    Ok(QuarantinedItem {
+        original: path.to_string_lossy().into_owned(),
+        quarantined: target.to_string_lossy().into_owned(),
+        bytes,
+    })
+}
+
+// 把隔离目录中的条目移回原处
+pub fn restore(quarantined: &Path) -> Result<String, String> {
+    let original = original_path(quarantined).ok_or_else(|| "不是隔离目录中的条目".to_string())?;
+    if fs::symlink_metadata(&original).is_ok() {
+        return Err(format!("原位置已有同名条目: {}", original.display()));
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建原目录: {}", e))?;
+    }
+    fs::rename(quarantined, &original).map_err(|e| format!("恢复失败: {}", e))?;
+    Ok(original.to_string_lossy().into_owned())
+}
+
+// <anchor>/.disksight-quarantine/<日期>/<相对路径> 对应 <anchor>/<相对路径>
+fn original_path(quarantined: &Path) -> Option<PathBuf> {
+    let mut anchor = PathBuf::new();
+    let mut components = quarantined.components();
+    for component in components.by_ref() {
+        if component.as_os_str() == QUARANTINE_DIR {
+            break;
+        }
+        anchor.push(component);
+    }
+    components.next()?;
+    let relative = components.as_path();
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    Some(anchor.join(relative))
+}
+
+// 所有卷与用户主目录下的隔离批次，按日期先后
+pub fn list_batches(keep_days: u64) -> Vec<QuarantineBatch> {
+    let mut anchors: Vec<PathBuf> = list_drives()
+        .into_iter()
+        .map(|d| PathBuf::from(d.mount_point))
+        .collect();
+    anchors.extend(home_dir());
+    anchors.dedup();
+
+    let mut batches = Vec::new();
+    for anchor in anchors {
+        let Ok(entries) = fs::read_dir(anchor.join(QUARANTINE_DIR)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let day = entry.file_name().to_string_lossy().into_owned();
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            let size_raw = dir_size(&entry.path());
+            batches.push(QuarantineBatch {
+                anchor: anchor.to_string_lossy().into_owned(),
+                purge_after: expires_at(&day, keep_days).map(date),
+                date: day,
+                path: entry.path().to_string_lossy().into_owned(),
+                size_raw,
+                size_display: human_readable_size(size_raw),
+            });
+        }
+    }
+    batches.sort_by(|a, b| a.date.cmp(&b.date));
+    batches
+}
+
+// 批次在这一时刻之后可以清除；日期名无法识别时为 None，不会被自动清除
+fn expires_at(day: &str, keep_days: u64) -> Option<SystemTime> {
+    parse_date(day).map(|t| t + Duration::from_secs(keep_days * DAY))
+}
+
+// 删除隔离超过 keep_days 天的批次，返回释放的空间和失败的批次
+pub fn purge_expired(keep_days: u64) -> (u64, Vec<(String, String)>) {
+    let now = SystemTime::now();
+    let mut freed = Vec::new();
+    let mut errors = Vec::new();
+    for batch in list_batches(keep_days) {
+        if expires_at(&batch.date, keep_days).is_none_or(|t| t > now) {
+            continue;
+        }
+        match fs::remove_dir_all(&batch.path) {
+            Ok(()) => freed.push(batch.size_raw),
+            Err(e) => errors.push((batch.path, e.to_string())),
+        }
+    }
+    (sum_sizes(freed), errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantined_paths_map_back_to_originals() {
+        let anchor = Path::new("/home/alice");
+        let target = target_for(
+            Path::new("/home/alice/projects/old/build"),
+            anchor,
+            "2026-03-09",
+        )
+        .unwrap();
+        assert_eq!(
+            target,
+            Path::new("/home/alice/.disksight-quarantine/2026-03-09/projects/old/build")
+        );
+        assert_eq!(
+            original_path(&target).unwrap(),
+            Path::new("/home/alice/projects/old/build")
+        );
+        assert_eq!(
+            original_path(Path::new("/home/alice/.disksight-quarantine/2026-03-09")),
+            None
+        );
+        let expires = expires_at("2026-03-09", 30).unwrap();
+        assert_eq!(date(expires), "2026-04-08");
+        assert_eq!(expires_at("notes", 30), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn quarantining_a_symlink_moves_the_link_not_its_target() {
+        let base =
+            std::env::temp_dir().join(format!("disksight-quarantine-{}", std::process::id()));
+        let outside = base.join("outside");
+        let tree = base.join("tree");
+        fs::create_dir_all(&outside).unwrap();
+        fs::create_dir_all(&tree).unwrap();
+        fs::write(outside.join("real.txt"), b"keep").unwrap();
+        std::os::unix::fs::symlink(outside.join("real.txt"), tree.join("link.txt")).unwrap();
+
+        let path = resolve(&tree.join("link.txt")).unwrap();
+        assert_eq!(path.file_name().unwrap(), "link.txt");
+        let base = base.canonicalize().unwrap();
+        let item = quarantine_into(&path, &base, "2026-03-09").unwrap();
+        let moved = fs::symlink_metadata(&item.quarantined).unwrap();
+        let kept = fs::read(outside.join("real.txt"));
+        let link_left = fs::symlink_metadata(tree.join("link.txt")).is_ok();
+        fs::remove_dir_all(&base).unwrap();
+
+        assert!(moved.file_type().is_symlink());
+        assert_eq!(kept.unwrap(), b"keep");
+        assert!(!link_left);
+        assert_eq!(
+            item.quarantined,
+            base.join(QUARANTINE_DIR)
+                .join("2026-03-09/tree/link.txt")
+                .to_string_lossy()
+        );
+    }
+}
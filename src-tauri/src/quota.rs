@@ -0,0 +1,193 @@
+use crate::drives::mount_of;
+use crate::owners::{aggregate_by_owner, user_names};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserQuotaUsage {
+    pub user: String,
+    pub uid: Option<u32>,
+    /// 本次扫描在目录下统计到的占用
+    pub scanned_bytes: u64,
+    /// 文件系统配额记录的已用空间
+    pub quota_used_bytes: u64,
+    /// 软限制，0 表示不限制
+    pub soft_limit_bytes: u64,
+    /// 硬限制，0 表示不限制
+    pub hard_limit_bytes: u64,
+    pub files_used: u64,
+    pub files_soft_limit: u64,
+    pub files_hard_limit: u64,
+    /// 已用空间占硬限制（无硬限制时取软限制）的百分比
+    pub percent_of_limit: Option<f64>,
+    /// 已超过软限制
+    pub over_soft_limit: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuotaReport {
+    /// 目录所在的挂载点
+    pub mount_point: String,
+    /// 文件系统是否启用了用户配额
+    pub quotas_enabled: bool,
+    /// 无法读取配额时的原因
+    pub message: Option<String>,
+    pub users: Vec<UserQuotaUsage>,
+}
+
+struct QuotaLine {
+    uid: u32,
+    used: u64,
+    soft: u64,
+    hard: u64,
+    files_used: u64,
+    files_soft: u64,
+    files_hard: u64,
+}
+
+// 对比目录下各用户的实际占用与其所在文件系统的配额
+pub fn quota_report(root: &Path) -> Result<QuotaReport, String> {
+    if !cfg!(target_os = "linux") {
+        return Err("配额报告仅支持 Linux".to_string());
+    }
+    if !root.is_dir() {
+        return Err("路径不存在或不是目录".to_string());
+    }
+    let mount_point = mount_of(root)
+        .map(|m| m.mount_point)
+        .unwrap_or_else(|| "/".to_string());
+    let usages = aggregate_by_owner(root);
+
+    let (quotas, message) = match read_quotas(&mount_point) {
+        Ok(quotas) => (quotas, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let names = user_names();
+    let mut users: Vec<UserQuotaUsage> = usages
+        .iter()
+        .map(|usage| {
+            let quota = usage
+                .uid
+                .and_then(|uid| quotas.iter().find(|q| q.uid == uid));
+            build_usage(usage.owner.clone(), usage.uid, usage.size_raw, quota)
+        })
+        .collect();
+    // 配额中有记录、但本目录下没有文件的用户也一并列出
+    for quota in &quotas {
+        if !users.iter().any(|u| u.uid == Some(quota.uid)) && quota.used > 0 {
+            let name = names
+                .get(&quota.uid)
+                .cloned()
+                .unwrap_or_else(|| quota.uid.to_string());
+            users.push(build_usage(name, Some(quota.uid), 0, Some(quota)));
+        }
+    }
+    users.sort_by(|a, b| {
+        b.percent_of_limit
+            .unwrap_or(0.0)
+            .total_cmp(&a.percent_of_limit.unwrap_or(0.0))
+            .then(b.scanned_bytes.cmp(&a.scanned_bytes))
+    });
+
+    Ok(QuotaReport {
+        mount_point,
+        quotas_enabled: message.is_none(),
+        message,
+        users,
+    })
+}
+
+fn build_usage(
+    user: String,
+    uid: Option<u32>,
+    scanned_bytes: u64,
+    quota: Option<&QuotaLine>,
+) -> UserQuotaUsage {
+    let (used, soft, hard, files_used, files_soft, files_hard) = quota
+        .map(|q| {
+            (
+                q.used,
+                q.soft,
+                q.hard,
+                q.files_used,
+                q.files_soft,
+                q.files_hard,
+            )
+        })
+        .unwrap_or_default();
+    let limit = if hard > 0 { hard } else { soft };
+    UserQuotaUsage {
+        user,
+        uid,
+        scanned_bytes,
+        quota_used_bytes: used,
+        soft_limit_bytes: soft,
+        hard_limit_bytes: hard,
+        files_used,
+        files_soft_limit: files_soft,
+        files_hard_limit: files_hard,
+        percent_of_limit: (limit > 0).then(|| used as f64 * 100.0 / limit as f64),
+        over_soft_limit: soft > 0 && used > soft,
+    }
+}
+
+// 通过 repquota 读取用户配额（需要 root 权限且文件系统已开启配额）
+fn read_quotas(mount_point: &str) -> Result<Vec<QuotaLine>, String> {
+    let output = Command::new("repquota")
+        .args(["-u", "-n", "-p", mount_point])
+        .output()
+        .map_err(|e| format!("无法执行 repquota: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("未启用配额或权限不足: {}", stderr.trim()));
+    }
+    Ok(parse_repquota(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// 解析 `repquota -n -p` 的输出，块单位为 KiB
+fn parse_repquota(output: &str) -> Vec<QuotaLine> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let uid = fields.first()?.strip_prefix('#')?.parse().ok()?;
+            let num = |i: usize| -> Option<u64> { fields.get(i)?.parse().ok() };
+            Some(QuotaLine {
+                uid,
+                used: num(2)? * 1024,
+                soft: num(3)? * 1024,
+                hard: num(4)? * 1024,
+                files_used: num(6)?,
+                files_soft: num(7)?,
+                files_hard: num(8)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_repquota_output() {
+        let output = "\
+*** Report for user quotas on device /dev/sda1
+Block grace time: 604800; Inode grace time: 604800
+                        Block limits                File limits
+User            used    soft    hard  grace    used  soft  hard  grace
+----------------------------------------------------------------------
+#0        --      20       0       0      0       2     0     0      0
+#1000     +-    5000    4000    6000  12345      10     0     0      0
+";
+        let quotas = parse_repquota(output);
+        assert_eq!(quotas.len(), 2);
+        assert_eq!(quotas[1].uid, 1000);
+        assert_eq!(quotas[1].used, 5000 * 1024);
+
+        let usage = build_usage("dev".to_string(), Some(1000), 0, Some(&quotas[1]));
+        assert!(usage.over_soft_limit);
+        assert_eq!(usage.percent_of_limit.map(|p| p.round()), Some(83.0));
+    }
+}
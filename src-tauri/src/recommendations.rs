@@ -0,0 +1,382 @@
+// 类似 Windows“存储感知”的清理建议：汇总临时文件、回收站、旧下载、开发工具缓存、
+// 重复文件和长期未修改的大文件，按可释放空间从大到小排列，每条建议附带可以直接执行的操作
+use crate::duplicates::find_duplicates;
+use crate::utils::{dir_size, home_dir, human_readable_size, sum_sizes};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+const MB: u64 = 1024 * 1024;
+// 临时目录中修改时间早于该天数的条目才建议删除，避免删掉正在使用的文件
+const TEMP_MIN_AGE_DAYS: u64 = 7;
+const DOWNLOADS_MIN_AGE_DAYS: u64 = 90;
+const OLD_FILE_MIN_AGE_DAYS: u64 = 365;
+const OLD_FILE_MIN_BYTES: u64 = 500 * MB;
+const DUPLICATE_MIN_BYTES: u64 = MB;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationKind {
+    TempFiles,
+    Trash,
+    OldDownloads,
+    /// npm、cargo、pip 等可重新下载的缓存
+    DevCaches,
+    Duplicates,
+    OldLargeFiles,
+}
+
+// 建议对应的一键操作，由 apply_recommendation 命令执行
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecommendationAction {
+    /// 直接删除，用于可再生成的临时文件和缓存
+    Delete {
+        paths: Vec<String>,
+    },
+    /// 移到回收站，用户仍可恢复
+    Trash {
+        paths: Vec<String>,
+    },
+    EmptyTrash,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub kind: RecommendationKind,
+    pub title: String,
+    pub description: String,
+    pub reclaimable_bytes: u64,
+    pub reclaimable_display: String,
+    /// 涉及的文件或目录数
+    pub item_count: usize,
+    pub action: RecommendationAction,
+}
+
+impl Recommendation {
+    fn new(
+        kind: RecommendationKind,
+        title: &str,
+        description: String,
+        reclaimable_bytes: u64,
+        item_count: usize,
+        action: RecommendationAction,
+    ) -> Self {
+        Recommendation {
+            kind,
+            title: title.to_string(),
+            description,
+            reclaimable_bytes,
+            reclaimable_display: human_readable_size(reclaimable_bytes),
+            item_count,
+            action,
+        }
+    }
+}
+
+// 找到的条目及其大小
+struct Found {
+    paths: Vec<String>,
+    bytes: u64,
+}
+
+impl Found {
+    fn from_items(items: Vec<(PathBuf, u64)>) -> Self {
+        Found {
+            bytes: sum_sizes(items.iter().map(|(_, size)| *size)),
+            paths: items
+                .into_iter()
+                .map(|(p, _)| p.to_string_lossy().into_owned())
+                .collect(),
+        }
+    }
+}
+
+// 生成清理建议。重复文件和旧大文件只在 root 下查找；skip 为 true 的路径（如已标记为忽略）不计入
+pub fn recommend(root: &Path, skip: &dyn Fn(&str) -> bool) -> Vec<Recommendation> {
+    let now = now_secs();
+    let mut recommendations = Vec::new();
+    let mut push = |recommendation: Option<Recommendation>| {
+        if let Some(r) = recommendation.filter(|r| r.reclaimable_bytes > 0) {
+            recommendations.push(r);
+        }
+    };
+
+    let temp = stale_children(&std::env::temp_dir(), TEMP_MIN_AGE_DAYS, now, skip);
+    push(Some(Recommendation::new(
+        RecommendationKind::TempFiles,
+        "临时文件",
+        format!("临时目录中超过 {} 天未修改的文件", TEMP_MIN_AGE_DAYS),
+        temp.bytes,
+        temp.paths.len(),
+        RecommendationAction::Delete { paths: temp.paths },
+    )));
+
+    push(trash_size().map(|bytes| {
+        Recommendation::new(
+            RecommendationKind::Trash,
+            "回收站",
+            "回收站中的文件仍然占用磁盘空间".to_string(),
+            bytes,
+            0,
+            RecommendationAction::EmptyTrash,
+        )
+    }));
+
+    if let Some(downloads) = home_dir().map(|h| h.join("Downloads")) {
+        let old = stale_children(&downloads, DOWNLOADS_MIN_AGE_DAYS, now, skip);
+        push(Some(Recommendation::new(
+            RecommendationKind::OldDownloads,
+            "旧下载",
+            format!("下载目录中超过 {} 天未修改的文件", DOWNLOADS_MIN_AGE_DAYS),
+            old.bytes,
+            old.paths.len(),
+            RecommendationAction::Trash { paths: old.paths },
+        )));
+    }
+
+    let caches = Found::from_items(
+        dev_cache_dirs()
+            .into_iter()
+            .filter(|p| !skip(&p.to_string_lossy()))
+            .map(|p| {
+                let size = dir_size(&p);
+                (p, size)
+            })
+            .collect(),
+    );
+    push(Some(Recommendation::new(
+        RecommendationKind::DevCaches,
+        "开发工具缓存",
+        "npm、cargo、pip 等包管理器的下载缓存，删除后需要时会重新下载".to_string(),
+        caches.bytes,
+        caches.paths.len(),
+        RecommendationAction::Delete {
+            paths: caches.paths,
+        },
+    )));
+
+    let duplicates = find_duplicates(root, DUPLICATE_MIN_BYTES, &|_, _, _| {});
+    // 每组保留第一份，其余移到回收站
+    let extras = Found::from_items(
+        duplicates
+            .groups
+            .iter()
+            .flat_map(|g| {
+                g.paths
+                    .iter()
+                    .skip(1)
+                    .map(|p| (PathBuf::from(p), g.size_raw))
+            })
+            .filter(|(p, _)| !skip(&p.to_string_lossy()))
+            .collect(),
+    );
+    push(Some(Recommendation::new(
+        RecommendationKind::Duplicates,
+        "重复文件",
+        format!("{} 组内容相同的文件，每组保留一份", duplicates.groups.len()),
+        extras.bytes,
+        extras.paths.len(),
+        RecommendationAction::Trash {
+            paths: extras.paths,
+        },
+    )));
+
+    let mut old_files = Vec::new();
+    collect_old_large_files(root, now, skip, &mut old_files);
+    let old_files = Found::from_items(old_files);
+    push(Some(Recommendation::new(
+        RecommendationKind::OldLargeFiles,
+        "长期未使用的大文件",
+        format!(
+            "超过 {} 天未修改且不小于 {} 的文件",
+            OLD_FILE_MIN_AGE_DAYS,
+            human_readable_size(OLD_FILE_MIN_BYTES)
+        ),
+        old_files.bytes,
+        old_files.paths.len(),
+        RecommendationAction::Trash {
+            paths: old_files.paths,
+        },
+    )));
+
+    recommendations.sort_by_key(|r| std::cmp::Reverse(r.reclaimable_bytes));
+    recommendations
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn age_days(metadata: &fs::Metadata, now: u64) -> u64 {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(now);
+    now.saturating_sub(modified) / DAY
+}
+
+// 目录的直接子项中修改时间早于 min_age_days 的条目
+fn stale_children(dir: &Path, min_age_days: u64, now: u64, skip: &dyn Fn(&str) -> bool) -> Found {
+    let mut items = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if age_days(&metadata, now) < min_age_days || skip(&path.to_string_lossy()) {
+                continue;
+            }
+            let size = if metadata.is_dir() {
+                dir_size(&path)
+            } else {
+                metadata.len()
+            };
+            items.push((path, size));
+        }
+    }
+    Found::from_items(items)
+}
+
+fn collect_old_large_files(
+    dir: &Path,
+    now: u64,
+    skip: &dyn Fn(&str) -> bool,
+    found: &mut Vec<(PathBuf, u64)>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if skip(&path.to_string_lossy()) {
+            continue;
+        }
+        if metadata.is_dir() {
+            collect_old_large_files(&path, now, skip, found);
+        } else if metadata.len() >= OLD_FILE_MIN_BYTES
+            && age_days(&metadata, now) >= OLD_FILE_MIN_AGE_DAYS
+        {
+            found.push((path, metadata.len()));
+        }
+    }
+}
+
+// 常见包管理器的全局下载缓存目录
+fn dev_cache_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = home_dir() {
+        dirs.push(home.join(".npm").join("_cacache"));
+        dirs.push(home.join(".cargo").join("registry").join("cache"));
+        dirs.push(home.join(".cache").join("pip"));
+        dirs.push(home.join(".cache").join("yarn"));
+        dirs.push(home.join(".gradle").join("caches"));
+        dirs.push(home.join("Library/Caches/pip"));
+        dirs.push(home.join("Library/Caches/Yarn"));
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        let local = PathBuf::from(local);
+        dirs.push(local.join("npm-cache"));
+        dirs.push(local.join("pip").join("Cache"));
+        dirs.push(local.join("Yarn").join("Cache"));
+    }
+    dirs.retain(|d| d.is_dir());
+    dirs.dedup();
+    dirs
+}
+
+// 当前用户回收站中的文件占用的空间，无法确定时返回 None
+pub fn trash_size() -> Option<u64> {
+    #[cfg(windows)]
+    {
+        let items = trash::os_limited::list().ok()?;
+        Some(sum_sizes(items.iter().map(|item| {
+            match trash::os_limited::metadata(item).map(|m| m.size) {
+                Ok(trash::TrashItemSize::Bytes(bytes)) => bytes,
+                _ => 0,
+            }
+        })))
+    }
+    #[cfg(not(windows))]
+    {
+        user_trash_dir().map(|dir| dir_size(&dir))
+    }
+}
+
+// 清空当前用户的回收站，返回释放的空间和失败的条目
+pub fn empty_trash() -> Result<(u64, Vec<String>), String> {
+    let freed = trash_size().unwrap_or(0);
+    #[cfg(windows)]
+    {
+        let items = trash::os_limited::list().map_err(|e| format!("无法读取回收站: {}", e))?;
+        trash::os_limited::purge_all(items).map_err(|e| format!("清空回收站失败: {}", e))?;
+        Ok((freed, Vec::new()))
+    }
+    #[cfg(not(windows))]
+    {
+        let dir = user_trash_dir().ok_or_else(|| "找不到回收站目录".to_string())?;
+        // freedesktop 回收站中 files 保存文件本身，info 保存原位置等信息，两者都要清空
+        let targets = if dir.join("files").is_dir() {
+            vec![dir.join("files"), dir.join("info")]
+        } else {
+            vec![dir]
+        };
+        let mut errors = Vec::new();
+        for target in targets {
+            errors.extend(crate::elevation::clear_dir_contents(&target).1);
+        }
+        let remaining = trash_size().unwrap_or(0);
+        Ok((freed.saturating_sub(remaining), errors))
+    }
+}
+
+#[cfg(not(windows))]
+fn user_trash_dir() -> Option<PathBuf> {
+    let home = home_dir()?;
+    let dir = if cfg!(target_os = "macos") {
+        home.join(".Trash")
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local").join("share"))
+            .join("Trash")
+    };
+    dir.is_dir().then_some(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_children_skips_recent_and_skipped_entries() {
+        let dir = std::env::temp_dir().join(format!("disksight-recommend-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.bin"), b"12345").unwrap();
+        fs::write(dir.join("sub/b.bin"), b"123").unwrap();
+
+        // 刚创建的文件不满足天数要求
+        let now = now_secs();
+        assert!(stale_children(&dir, 1, now, &|_| false).paths.is_empty());
+
+        // 以两天后的时间计算，跳过 sub 目录
+        let later = now + 2 * DAY;
+        let found = stale_children(&dir, 1, later, &|p| p.ends_with("sub"));
+        assert_eq!(found.paths.len(), 1);
+        assert_eq!(found.bytes, 5);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+use crate::audit::{AuditAction, AuditLog};
+use crate::history::history_key;
+use crate::index::NameMatcher;
+use crate::utils::human_readable_size;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// 只提醒，不做修改
+    Notify,
+    Trash,
+    /// 移动到 archive_dir，保留相对目录结构
+    Archive,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub name: String,
+    /// 策略作用的目录
+    pub root: String,
+    /// 文件名匹配模式，规则与索引查询相同，如 "*.tmp"
+    pub pattern: String,
+    /// 修改时间早于该天数的文件才命中
+    pub min_age_days: Option<u64>,
+    /// 大小不小于该值（字节）的文件才命中
+    pub min_size_bytes: Option<u64>,
+    pub action: RetentionAction,
+    pub archive_dir: Option<String>,
+    pub enabled: bool,
+    /// 确认过演练结果后才打开；为 false 时扫描后只生成演练报告
+    pub enforce: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RetentionMatch {
+    pub path: String,
+    pub bytes: u64,
+    pub age_days: u64,
+    pub executed: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub policy: String,
+    pub action: RetentionAction,
+    pub dry_run: bool,
+    pub matches: Vec<RetentionMatch>,
+    pub total_bytes: u64,
+    pub total_display: String,
+}
+
+// 检查策略配置是否完整
+pub fn validate(policy: &RetentionPolicy) -> Result<(), String> {
+    if policy.name.trim().is_empty() {
+        return Err("策略名称不能为空".to_string());
+    }
+    if !Path::new(&policy.root).is_dir() {
+        return Err(format!(
+            "策略 {} 的目录不存在: {}",
+            policy.name, policy.root
+        ));
+    }
+    NameMatcher::new(&policy.pattern)?;
+    if policy.action == RetentionAction::Archive && policy.archive_dir.is_none() {
+        return Err(format!("策略 {} 未指定归档目录", policy.name));
+    }
+    Ok(())
+}
+
+// 找出策略命中的文件；dry_run 为 false 且动作不是 Notify 时执行动作并记录审计日志
+pub fn apply(
+    policy: &RetentionPolicy,
+    dry_run: bool,
+    audit: &AuditLog,
+) -> Result<RetentionReport, String> {
+    validate(policy)?;
+    let matcher = NameMatcher::new(&policy.pattern)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut matches = Vec::new();
+    collect_matches(Path::new(&policy.root), policy, &matcher, now, &mut matches);
+
+    if !dry_run && policy.action != RetentionAction::Notify {
+        for m in matches.iter_mut() {
+            let (kind, target, result) = match policy.action {
+                RetentionAction::Trash => (
+                    AuditAction::Trash,
+                    None,
+                    trash::delete(&m.path).map_err(|e| format!("移到回收站失败: {}", e)),
+                ),
+                RetentionAction::Archive => {
+                    let target = archive_target(policy, &m.path);
+                    let result = archive(Path::new(&m.path), &target);
+                    (
+                        AuditAction::Move,
+                        Some(target.to_string_lossy().into_owned()),
+                        result,
+                    )
+                }
+                RetentionAction::Notify => unreachable!(),
+            };
+            audit.record(kind, &m.path, target.as_deref(), m.bytes, &result);
+            m.executed = result.is_ok();
+            m.error = result.err();
+        }
+    }
+
+    let total_bytes = matches
+        .iter()
+        .filter(|m| m.error.is_none())
+        .fold(0u64, |acc, m| acc.saturating_add(m.bytes));
+    Ok(RetentionReport {
+        policy: policy.name.clone(),
+        action: policy.action,
+        dry_run: dry_run || policy.action == RetentionAction::Notify,
+        matches,
+        total_bytes,
+        total_display: human_readable_size(total_bytes),
+    })
+}
+
+// 扫描了 root 之后需要重新评估的策略：策略目录位于扫描范围内
+pub fn policies_for_scan<'a>(
+    policies: &'a [RetentionPolicy],
+    scanned_root: &str,
+) -> Vec<&'a RetentionPolicy> {
+    policies
+        .iter()
+        .filter(|p| p.enabled && Path::new(&history_key(&p.root)).starts_with(scanned_root))
+        .collect()
+}
+
+fn collect_matches(
+    dir: &Path,
+    policy: &RetentionPolicy,
+    matcher: &NameMatcher,
+    now: u64,
+    matches: &mut Vec<RetentionMatch>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接，避免作用到策略目录之外
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_matches(&path, policy, matcher, now, matches);
+            continue;
+        }
+        let path_str = path.to_string_lossy().into_owned();
+        if !matcher.matches(&path_str) {
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(now);
+        let age_days = now.saturating_sub(modified) / DAY;
+        if policy.min_age_days.is_some_and(|min| age_days < min)
+            || policy
+                .min_size_bytes
+                .is_some_and(|min| metadata.len() < min)
+        {
+            continue;
+        }
+        matches.push(RetentionMatch {
+            path: path_str,
+            bytes: metadata.len(),
+            age_days,
+            executed: false,
+            error: None,
+        });
+    }
+}
+
+fn archive_target(policy: &RetentionPolicy, path: &str) -> PathBuf {
+    let archive_dir = Path::new(policy.archive_dir.as_deref().unwrap_or_default());
+    match Path::new(path).strip_prefix(&policy.root) {
+        Ok(relative) => archive_dir.join(relative),
+        Err(_) => archive_dir.join(Path::new(path).file_name().unwrap_or_default()),
+    }
+}
+
+// 先尝试重命名，跨卷时复制后删除源文件
+fn archive(source: &Path, target: &Path) -> Result<(), String> {
+    if target.exists() {
+        return Err(format!("归档目标已存在: {}", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建归档目录: {}", e))?;
+    }
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, target).map_err(|e| format!("复制到归档目录失败: {}", e))?;
+    fs::remove_file(source).map_err(|e| {
+        let _ = fs::remove_file(target);
+        format!("删除源文件失败: {}", e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dry_run_lists_matches_without_touching_files() {
+        let dir = std::env::temp_dir().join(format!("disksight-retention-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.tmp"), b"12345").unwrap();
+        fs::write(dir.join("sub/b.TMP"), b"1").unwrap();
+        fs::write(dir.join("keep.txt"), b"123").unwrap();
+
+        let policy = RetentionPolicy {
+            name: "tmp".to_string(),
+            root: dir.to_string_lossy().into_owned(),
+            pattern: ".tmp".to_string(),
+            min_age_days: None,
+            min_size_bytes: Some(2),
+            action: RetentionAction::Trash,
+            archive_dir: None,
+            enabled: true,
+            enforce: false,
+        };
+        let audit = AuditLog::new(&dir.join("data"));
+        let report = apply(&policy, true, &audit).unwrap();
+        assert_eq!(report.matches.len(), 1);
+        assert!(report.matches[0].path.ends_with("a.tmp"));
+        assert_eq!(report.total_bytes, 5);
+        assert!(dir.join("a.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn enforcing_moves_only_matching_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "disksight-retention-enforce-{}",
+            std::process::id()
+        ));
+        let root = dir.join("root");
+        let archive_dir = dir.join("archive");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("a.tmp"), b"12345").unwrap();
+        fs::write(root.join("sub/c.tmp"), b"123").unwrap();
+        fs::write(root.join("sub/b.TMP"), b"1").unwrap();
+        fs::write(root.join("keep.txt"), b"123").unwrap();
+
+        let policy = RetentionPolicy {
+            name: "tmp".to_string(),
+            root: root.to_string_lossy().into_owned(),
+            pattern: ".tmp".to_string(),
+            min_age_days: None,
+            min_size_bytes: Some(2),
+            action: RetentionAction::Archive,
+            archive_dir: Some(archive_dir.to_string_lossy().into_owned()),
+            enabled: true,
+            enforce: true,
+        };
+        let audit = AuditLog::new(&dir.join("data"));
+        let report = apply(&policy, false, &audit).unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.matches.len(), 2);
+        assert!(report.matches.iter().all(|m| m.executed));
+        assert_eq!(report.total_bytes, 8);
+
+        assert!(!root.join("a.tmp").exists());
+        assert!(!root.join("sub/c.tmp").exists());
+        assert_eq!(fs::read(archive_dir.join("a.tmp")).unwrap(), b"12345");
+        assert_eq!(fs::read(archive_dir.join("sub/c.tmp")).unwrap(), b"123");
+        // 太小或不匹配的文件留在原处
+        assert!(root.join("sub/b.TMP").exists());
+        assert!(root.join("keep.txt").exists());
+        assert_eq!(audit.read(10).unwrap().len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
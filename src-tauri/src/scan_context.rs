@@ -0,0 +1,303 @@
+use crate::models::{FileEntry, ProgressEvent, ProgressStatus};
+use crate::skip_list::{FailureKind, ScanFailures};
+use crate::watchdog::Watchdog;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+type Subscribers = Arc<Mutex<BTreeSet<String>>>;
+type Forward = Arc<dyn Fn(&str, serde_json::Value) + Send + Sync>;
+// 已统计完成的顶层条目，扫描被中断时作为部分结果返回
+pub type PartialEntries = Arc<Mutex<Vec<FileEntry>>>;
+
+// 工作进程把统计完成的顶层条目转发给主进程的事件名，不发给窗口
+pub const ENTRY_DONE_EVENT: &str = "entry_done";
+
+#[derive(Clone)]
+enum EventSink {
+    /// 发给订阅了该扫描的窗口
+    Windows {
+        app: AppHandle,
+        /// 订阅该扫描的 webview label，扫描进行中也可以加入
+        subscribers: Subscribers,
+    },
+    /// 扫描工作进程中没有窗口，事件交给回调转发回主进程
+    Forward(Forward),
+}
+
+// 把扫描事件发到以扫描 ID 区分的频道 scan://{id}/{event}，只发给订阅了该扫描的窗口，
+// 并在事件中带上扫描上下文（标签页）标识，多个窗口或标签页同时扫描时互不干扰
+#[derive(Clone)]
+pub struct ScanEmitter {
+    sink: EventSink,
+    scan_id: u64,
+    context: Option<String>,
+    /// 扫描进度同时报告给停滞检测
+    watchdog: Option<Watchdog>,
+    /// 两阶段扫描的第二阶段：按第一阶段统计的条目总数报告百分比
+    determinate: Option<Arc<Determinate>>,
+    /// 收集无权限读取的路径，供跳过列表学习
+    failures: Option<ScanFailures>,
+    /// 收集已统计完成的顶层条目
+    partial: Option<PartialEntries>,
+}
+
+struct Determinate {
+    root: String,
+    total: u64,
+    done: AtomicU64,
+    /// 最近一次报告的百分比，每增加 1% 才发送一次事件
+    percent: AtomicU64,
+}
+
+impl ScanEmitter {
+    pub fn new(
+        app: AppHandle,
+        scan_id: u64,
+        subscribers: Subscribers,
+        context: Option<String>,
+    ) -> Self {
+        ScanEmitter {
+            sink: EventSink::Windows { app, subscribers },
+            scan_id,
+            context,
+            watchdog: None,
+            determinate: None,
+            failures: None,
+            partial: None,
+        }
+    }
+
+    // 扫描工作进程使用：事件不直接发给窗口，而是序列化后交给 forward
+    pub fn forwarding(
+        scan_id: u64,
+        context: Option<String>,
+        forward: impl Fn(&str, serde_json::Value) + Send + Sync + 'static,
+    ) -> Self {
+        ScanEmitter {
+            sink: EventSink::Forward(Arc::new(forward)),
+            scan_id,
+            context,
+            watchdog: None,
+            determinate: None,
+            failures: None,
+            partial: None,
+        }
+    }
+
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
+    pub fn with_failures(mut self, failures: ScanFailures) -> Self {
+        self.failures = Some(failures);
+        self
+    }
+
+    pub fn with_partial(mut self, partial: PartialEntries) -> Self {
+        self.partial = Some(partial);
+        self
+    }
+
+    // 一个顶层条目统计完成；工作进程中没有收集者，转发给主进程
+    pub fn entry_done(&self, entry: &FileEntry) {
+        match (&self.partial, &self.sink) {
+            (Some(partial), _) => partial.lock().unwrap().push(entry.clone()),
+            (None, EventSink::Forward(_)) => self.emit(ENTRY_DONE_EVENT, entry),
+            (None, EventSink::Windows { .. }) => {}
+        }
+    }
+
+    pub fn note_failure(&self, path: &str, kind: FailureKind) {
+        if let Some(failures) = &self.failures {
+            failures.lock().unwrap().insert(path.to_string(), kind);
+        }
+    }
+
+    // 已知 root 下共有 total 个条目，之后每处理一个条目调用 advance 报告百分比
+    pub fn with_total(&self, root: &Path, total: u64) -> Self {
+        ScanEmitter {
+            determinate: Some(Arc::new(Determinate {
+                root: root.to_string_lossy().into_owned(),
+                total,
+                done: AtomicU64::new(0),
+                percent: AtomicU64::new(0),
+            })),
+            ..self.clone()
+        }
+    }
+
+    // 第二阶段处理完一个条目；百分比增加时发送 size 阶段的进度事件
+    pub fn advance(&self) {
+        let Some(progress) = &self.determinate else {
+            return;
+        };
+        let total = progress.total.max(1);
+        // 第一阶段之后新增的条目可能使已处理数超过总数
+        let done = (progress.done.fetch_add(1, Ordering::Relaxed) + 1).min(total);
+        let percent = done * 100 / total;
+        if progress.percent.fetch_max(percent, Ordering::Relaxed) < percent {
+            self.emit(
+                "progress",
+                ProgressEvent {
+                    current_path: progress.root.clone(),
+                    current_file: progress.root.clone(),
+                    status: ProgressStatus::Stage {
+                        stage: "size".to_string(),
+                        done,
+                        total,
+                    },
+                    context: self.context(),
+                    scan_id: Some(self.scan_id),
+                },
+            );
+        }
+    }
+
+    // 报告扫描到了某个路径，重置停滞计时
+    pub fn touch(&self, path: &Path) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.touch(path);
+        }
+    }
+
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        match &self.sink {
+            EventSink::Windows { app, subscribers } => {
+                let event = format!("scan://{}/{}", self.scan_id, event);
+                for label in subscribers.lock().unwrap().iter() {
+                    let _ = app.emit_to(label.as_str(), &event, payload.clone());
+                }
+            }
+            EventSink::Forward(forward) => match serde_json::to_value(payload) {
+                Ok(value) => forward(event, value),
+                Err(e) => eprintln!("扫描事件序列化失败: {}", e),
+            },
+        }
+    }
+
+    pub fn scan_id(&self) -> u64 {
+        self.scan_id
+    }
+
+    pub fn context(&self) -> Option<String> {
+        self.context.clone()
+    }
+}
+
+// 进行中（或已预留 ID 尚未开始）的扫描及订阅其事件的窗口
+#[derive(Default)]
+pub struct ScanSubscriptions {
+    scans: BTreeMap<u64, Subscribers>,
+}
+
+impl ScanSubscriptions {
+    // 为预留的扫描 ID 登记订阅者，扫描开始前调用
+    pub fn register(&mut self, scan_id: u64, label: &str) -> Subscribers {
+        let subscribers = self.scans.entry(scan_id).or_default();
+        subscribers.lock().unwrap().insert(label.to_string());
+        subscribers.clone()
+    }
+
+    // 订阅已登记的扫描，扫描已结束或 ID 不存在时报错
+    pub fn subscribe(&self, scan_id: u64, label: &str) -> Result<(), String> {
+        let subscribers = self
+            .scans
+            .get(&scan_id)
+            .ok_or_else(|| format!("扫描 {} 不存在或已结束", scan_id))?;
+        subscribers.lock().unwrap().insert(label.to_string());
+        Ok(())
+    }
+
+    pub fn unsubscribe(&self, scan_id: u64, label: &str) {
+        if let Some(subscribers) = self.scans.get(&scan_id) {
+            subscribers.lock().unwrap().remove(label);
+        }
+    }
+
+    // 扫描结束后移除，之后的订阅请求会失败
+    pub fn finish(&mut self, scan_id: u64) {
+        self.scans.remove(&scan_id);
+    }
+
+    // 窗口关闭时取消其所有订阅
+    pub fn remove_window(&self, label: &str) {
+        for subscribers in self.scans.values() {
+            subscribers.lock().unwrap().remove(label);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanContextInfo {
+    /// 上下文标识，默认与窗口 label 相同，同一窗口内的多个标签页各自指定
+    pub context: String,
+    /// 所属窗口的 label
+    pub window: String,
+    /// 最近一次扫描的根目录
+    pub root: Option<String>,
+    /// 该上下文产生的扫描结果，最新的在最后
+    pub scan_ids: Vec<u64>,
+}
+
+// 各窗口、标签页独立的扫描上下文
+#[derive(Default)]
+pub struct ScanContexts {
+    contexts: BTreeMap<String, ScanContextInfo>,
+}
+
+impl ScanContexts {
+    // 记录一次扫描的结果归属
+    pub fn record(&mut self, context: &str, window: &str, root: &str, scan_id: u64) {
+        let info = self
+            .contexts
+            .entry(context.to_string())
+            .or_insert_with(|| ScanContextInfo {
+                context: context.to_string(),
+                window: window.to_string(),
+                root: None,
+                scan_ids: Vec::new(),
+            });
+        info.window = window.to_string();
+        info.root = Some(root.to_string());
+        info.scan_ids.push(scan_id);
+    }
+
+    pub fn list(&self) -> Vec<ScanContextInfo> {
+        self.contexts.values().cloned().collect()
+    }
+
+    pub fn remove(&mut self, context: &str) -> Option<ScanContextInfo> {
+        self.contexts.remove(context)
+    }
+
+    // 窗口关闭时移除其下所有上下文
+    pub fn remove_window(&mut self, window: &str) {
+        self.contexts.retain(|_, info| info.window != window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_end_with_the_scan() {
+        let mut subscriptions = ScanSubscriptions::default();
+        let subscribers = subscriptions.register(7, "main");
+        subscriptions.subscribe(7, "scan-2").unwrap();
+        assert!(subscriptions.subscribe(8, "scan-2").is_err());
+        assert_eq!(subscribers.lock().unwrap().len(), 2);
+
+        subscriptions.unsubscribe(7, "main");
+        subscriptions.remove_window("scan-2");
+        assert!(subscribers.lock().unwrap().is_empty());
+
+        subscriptions.finish(7);
+        assert!(subscriptions.subscribe(7, "main").is_err());
+    }
+}
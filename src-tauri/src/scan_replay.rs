@@ -0,0 +1,306 @@
+// 录制与回放扫描：RecordingFs 在扫描时记下每次目录读取与元数据查询的原始结果（含错误），
+// 保存为 JSON；ReplayFs 在内存中按录制内容应答，不访问磁盘。
+// 用户在特殊文件系统（如某些 NAS）上得到错误的统计时，可以附上录制文件以便复现和回归测试
+use crate::dir_reader::DirChild;
+use crate::vfs::{Fs, FsMetadata};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedErrorKind {
+    NotFound,
+    PermissionDenied,
+    TimedOut,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedError {
+    pub kind: RecordedErrorKind,
+    pub message: String,
+}
+
+impl From<&io::Error> for RecordedError {
+    fn from(e: &io::Error) -> Self {
+        let kind = match e.kind() {
+            io::ErrorKind::NotFound => RecordedErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied => RecordedErrorKind::PermissionDenied,
+            io::ErrorKind::TimedOut => RecordedErrorKind::TimedOut,
+            _ => RecordedErrorKind::Other,
+        };
+        RecordedError {
+            kind,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<&RecordedError> for io::Error {
+    fn from(e: &RecordedError) -> Self {
+        let kind = match e.kind {
+            RecordedErrorKind::NotFound => io::ErrorKind::NotFound,
+            RecordedErrorKind::PermissionDenied => io::ErrorKind::PermissionDenied,
+            RecordedErrorKind::TimedOut => io::ErrorKind::TimedOut,
+            RecordedErrorKind::Other => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e.message.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedChild {
+    /// 非 UTF-8 的名称按有损转换保存
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub ino: u64,
+}
+
+type Recorded<T> = Result<T, RecordedError>;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScanRecording {
+    /// 被扫描的目录
+    pub root: String,
+    pub recorded_at: Option<SystemTime>,
+    /// 以下均以完整路径为键，同一路径只保留最后一次的结果
+    pub read_dir: BTreeMap<String, Recorded<Vec<RecordedChild>>>,
+    /// 不跟随符号链接的元数据
+    pub metadata: BTreeMap<String, Recorded<FsMetadata>>,
+    /// 跟随符号链接的元数据
+    pub stat: BTreeMap<String, Recorded<FsMetadata>>,
+}
+
+impl ScanRecording {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("读取录制文件失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("录制文件格式错误: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("录制内容序列化失败: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("保存录制文件失败: {}", e))
+    }
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn record<T: Clone>(map: &mut BTreeMap<String, Recorded<T>>, path: &Path, result: &io::Result<T>) {
+    let recorded = match result {
+        Ok(value) => Ok(value.clone()),
+        Err(e) => Err(e.into()),
+    };
+    map.insert(key(path), recorded);
+}
+
+// 把读取操作转交给 inner 并记录结果；删除操作一律拒绝，录制不应修改磁盘
+pub struct RecordingFs<'a> {
+    inner: &'a dyn Fs,
+    recording: Mutex<ScanRecording>,
+}
+
+impl<'a> RecordingFs<'a> {
+    pub fn new(inner: &'a dyn Fs, root: &Path) -> Self {
+        RecordingFs {
+            inner,
+            recording: Mutex::new(ScanRecording {
+                root: key(root),
+                recorded_at: Some(SystemTime::now()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn finish(self) -> ScanRecording {
+        self.recording.into_inner().unwrap()
+    }
+}
+
+const READ_ONLY: &str = "录制与回放扫描时不能修改文件";
+
+fn read_only<T>() -> io::Result<T> {
+    Err(io::Error::new(io::ErrorKind::PermissionDenied, READ_ONLY))
+}
+
+impl Fs for RecordingFs<'_> {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirChild>> {
+        let result = self.inner.read_dir(dir);
+        let recorded = match &result {
+            Ok(children) => Ok(children
+                .iter()
+                .map(|child| RecordedChild {
+                    name: child.name.to_string_lossy().into_owned(),
+                    is_dir: child.is_dir,
+                    size: child.size,
+                    ino: child.ino,
+                })
+                .collect()),
+            Err(e) => Err(e.into()),
+        };
+        self.recording
+            .lock()
+            .unwrap()
+            .read_dir
+            .insert(key(dir), recorded);
+        result
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        let result = self.inner.metadata(path);
+        record(&mut self.recording.lock().unwrap().metadata, path, &result);
+        result
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<FsMetadata> {
+        let result = self.inner.stat(path);
+        record(&mut self.recording.lock().unwrap().stat, path, &result);
+        result
+    }
+
+    fn remove_file(&self, _path: &Path) -> io::Result<()> {
+        read_only()
+    }
+
+    fn remove_dir_all(&self, _path: &Path) -> io::Result<()> {
+        read_only()
+    }
+
+    fn clear_readonly(&self, _path: &Path) -> Result<(), String> {
+        Err(READ_ONLY.to_string())
+    }
+
+    fn clear_readonly_recursive(&self, path: &Path) -> Vec<(PathBuf, String)> {
+        vec![(path.to_path_buf(), READ_ONLY.to_string())]
+    }
+}
+
+// 按录制内容应答的文件系统，未录制的路径视为不存在
+pub struct ReplayFs {
+    recording: ScanRecording,
+}
+
+impl ReplayFs {
+    pub fn new(recording: ScanRecording) -> Self {
+        ReplayFs { recording }
+    }
+
+    pub fn root(&self) -> &Path {
+        Path::new(&self.recording.root)
+    }
+}
+
+fn replay<T: Clone>(map: &BTreeMap<String, Recorded<T>>, path: &Path) -> io::Result<T> {
+    match map.get(&key(path)) {
+        Some(Ok(value)) => Ok(value.clone()),
+        Some(Err(e)) => Err(e.into()),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("录制中没有 {}", path.display()),
+        )),
+    }
+}
+
+impl Fs for ReplayFs {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirChild>> {
+        replay(&self.recording.read_dir, dir).map(|children| {
+            children
+                .into_iter()
+                .map(|child| DirChild {
+                    name: child.name.into(),
+                    is_dir: child.is_dir,
+                    size: child.size,
+                    ino: child.ino,
+                })
+                .collect()
+        })
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        replay(&self.recording.metadata, path)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<FsMetadata> {
+        replay(&self.recording.stat, path)
+    }
+
+    fn remove_file(&self, _path: &Path) -> io::Result<()> {
+        read_only()
+    }
+
+    fn remove_dir_all(&self, _path: &Path) -> io::Result<()> {
+        read_only()
+    }
+
+    fn clear_readonly(&self, _path: &Path) -> Result<(), String> {
+        Err(READ_ONLY.to_string())
+    }
+
+    fn clear_readonly_recursive(&self, path: &Path) -> Vec<(PathBuf, String)> {
+        vec![(path.to_path_buf(), READ_ONLY.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dir_listing::dir_size_in;
+    use crate::dir_listing_v2::count_entries;
+    use crate::ignore_rules::IgnoreMode;
+    use crate::scan_context::ScanEmitter;
+    use crate::vfs::RealFs;
+    use indicatif::ProgressBar;
+
+    // 总大小、条目数与顶层文件的大小
+    fn walk(fs: &dyn Fs, root: &Path) -> (u64, u64, Option<u64>) {
+        let emitter = ScanEmitter::forwarding(0, None, |_, _| {});
+        (
+            dir_size_in(fs, root, &ProgressBar::hidden(), false),
+            count_entries(fs, root, IgnoreMode::Off, &[], false, &emitter),
+            fs.stat(&root.join("top")).ok().map(|m| m.len),
+        )
+    }
+
+    #[test]
+    fn replay_reproduces_the_recorded_scan() {
+        let root = std::env::temp_dir().join(format!("disksight-replay-{}", std::process::id()));
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/one"), vec![0u8; 300]).unwrap();
+        fs::write(root.join("a/b/two"), vec![0u8; 200]).unwrap();
+        fs::write(root.join("top"), vec![0u8; 50]).unwrap();
+
+        let recording_fs = RecordingFs::new(&RealFs, &root);
+        let live = walk(&recording_fs, &root);
+        let recording = recording_fs.finish();
+        fs::remove_dir_all(&root).unwrap();
+
+        // 原目录已删除，回放只依赖录制内容
+        assert_eq!(live.1, 5);
+        assert_eq!(live.2, Some(50));
+        assert_eq!(walk(&ReplayFs::new(recording.clone()), &root), live);
+        assert!(ReplayFs::new(recording.clone())
+            .remove_file(&root.join("top"))
+            .is_err());
+
+        // 录制中的错误原样重现：无法读取的子目录不计入大小
+        let mut broken = recording;
+        broken.read_dir.insert(
+            key(&root.join("a/b")),
+            Err(RecordedError {
+                kind: RecordedErrorKind::PermissionDenied,
+                message: "Permission denied".to_string(),
+            }),
+        );
+        let replayed = walk(&ReplayFs::new(broken), &root);
+        assert_eq!(replayed.0, live.0 - 200);
+        assert_eq!(replayed.1, 4);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::entry_tree::EntryTree;
+use crate::models::{DirectoryResult, FileEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+// 内存中最多保留的扫描结果数，超出后淘汰最早的
+const MAX_STORED_SCANS: usize = 20;
+// 尚未被打开的预扫描结果单独计数，不挤占用户扫描的名额
+const MAX_WARM_SCANS: usize = 20;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredScan {
+    /// 扫描根目录
+    pub root: PathBuf,
+    /// 条目的增删只能通过 patch_entry 等方法进行，以保持树形索引同步
+    pub result: DirectoryResult,
+    /// 扫描完成时间
+    pub finished_at: SystemTime,
+    /// 条目的树形索引，首次修改时建立
+    #[serde(skip)]
+    tree: Option<EntryTree>,
+}
+
+impl StoredScan {
+    pub fn new(root: PathBuf, result: DirectoryResult, finished_at: SystemTime) -> Self {
+        StoredScan {
+            root,
+            result,
+            finished_at,
+            tree: None,
+        }
+    }
+
+    fn tree(&mut self) -> (&mut EntryTree, &mut Vec<FileEntry>) {
+        let entries = &mut self.result.entries;
+        let tree = self.tree.get_or_insert_with(|| EntryTree::build(entries));
+        (tree, entries)
+    }
+
+    // 用重新计算的条目替换结果中的同路径条目，None 表示该条目（连同其下的条目）已不存在。
+    // 包含它的上级目录条目按大小变化量一并修正
+    pub fn patch_entry(&mut self, path: &str, updated: Option<FileEntry>) -> Result<(), String> {
+        let (tree, entries) = self.tree();
+        let index = tree
+            .find(path)
+            .ok_or_else(|| format!("{} 不在扫描结果中", path))?;
+        let old_size = entries[index].size_raw;
+        let new_size = updated.as_ref().map_or(0, |e| e.size_raw);
+        tree.propagate(entries, tree.parent_of(index), old_size, new_size);
+        match updated {
+            Some(entry) => entries[index] = entry,
+            None => tree.remove(entries, index),
+        }
+        Ok(())
+    }
+
+    // 新增条目，上级目录条目的大小一并增加；已有同路径条目时改为替换
+    pub fn insert_entry(&mut self, entry: FileEntry) {
+        let path = entry.path();
+        if self.patch_entry(&path, Some(entry.clone())).is_ok() {
+            return;
+        }
+        let (tree, entries) = self.tree();
+        let size = entry.size_raw;
+        let index = tree.insert(entries, entry);
+        tree.propagate(entries, tree.parent_of(index), 0, size);
+    }
+
+    // 结果中包含 path 的上级目录条目的大小由 old_size 改为计入 new_size
+    pub fn adjust_ancestors(&mut self, path: &str, old_size: u64, new_size: u64) {
+        let (tree, entries) = self.tree();
+        let start = tree.nearest_ancestor(path);
+        tree.propagate(entries, start, old_size, new_size);
+    }
+}
+
+// 保存最近的扫描结果，供分组、对比等功能复用而无需重新扫描
+#[derive(Default)]
+pub struct ScanStore {
+    next_id: u64,
+    scans: BTreeMap<u64, StoredScan>,
+    /// 尚未被用户打开的预扫描结果
+    warm: BTreeSet<u64>,
+}
+
+impl ScanStore {
+    // 保存结果并返回分配的扫描 ID
+    pub fn insert(&mut self, root: PathBuf, result: DirectoryResult) -> u64 {
+        self.insert_scan(StoredScan::new(root, result, SystemTime::now()))
+    }
+
+    // 为即将开始的扫描预留 ID，扫描过程中的事件和最终保存的结果使用同一 ID
+    pub fn reserve_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    // 以预留的 ID 保存结果
+    pub fn insert_reserved(&mut self, id: u64, root: PathBuf, result: DirectoryResult) -> u64 {
+        self.put(id, StoredScan::new(root, result, SystemTime::now()))
+    }
+
+    // 保存已有的扫描（如从会话恢复），保留原来的完成时间
+    pub fn insert_scan(&mut self, scan: StoredScan) -> u64 {
+        let id = self.reserve_id();
+        self.put(id, scan)
+    }
+
+    // 保存后台预扫描的结果，与用户扫描分开限制数量
+    pub fn insert_warm(&mut self, root: PathBuf, result: DirectoryResult) -> u64 {
+        let id = self.reserve_id();
+        let mut scan = StoredScan::new(root, result, SystemTime::now());
+        scan.result.scan_id = Some(id);
+        self.scans.insert(id, scan);
+        self.warm.insert(id);
+        while self.warm.len() > MAX_WARM_SCANS {
+            if let Some(oldest) = self.warm.pop_first() {
+                self.scans.remove(&oldest);
+            }
+        }
+        id
+    }
+
+    // 用户打开了预扫描结果，之后按用户扫描计数
+    pub fn claim_warm(&mut self, id: u64) {
+        if self.warm.remove(&id) {
+            self.evict_user_scans();
+        }
+    }
+
+    fn put(&mut self, id: u64, mut scan: StoredScan) -> u64 {
+        scan.result.scan_id = Some(id);
+        self.scans.insert(id, scan);
+        self.evict_user_scans();
+        id
+    }
+
+    // 超出上限时移除最早的用户扫描，预扫描结果不受影响
+    fn evict_user_scans(&mut self) {
+        while self.scans.len() - self.warm.len() > MAX_STORED_SCANS {
+            let Some(oldest) = self
+                .scans
+                .keys()
+                .find(|id| !self.warm.contains(id))
+                .copied()
+            else {
+                break;
+            };
+            self.scans.remove(&oldest);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &StoredScan)> {
+        self.scans.iter().map(|(id, scan)| (*id, scan))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u64, &mut StoredScan)> {
+        self.scans.iter_mut().map(|(id, scan)| (*id, scan))
+    }
+
+    pub fn get(&self, id: u64) -> Result<&StoredScan, String> {
+        self.scans
+            .get(&id)
+            .ok_or_else(|| format!("扫描结果 {} 不存在或已过期", id))
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Result<&mut StoredScan, String> {
+        self.scans
+            .get_mut(&id)
+            .ok_or_else(|| format!("扫描结果 {} 不存在或已过期", id))
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<StoredScan> {
+        self.warm.remove(&id);
+        self.scans.remove(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntryPath;
+    use crate::utils::human_readable_size;
+    use std::sync::Arc;
+
+    fn entry(path: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            // 与扫描结果一样共享父目录，覆盖 has_path 的拼接比较
+            location: EntryPath::Child(Arc::from(path.rsplit_once('/').unwrap().0)),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            severity: None,
+        }
+    }
+
+    #[test]
+    fn patch_entry_updates_ancestors() {
+        let mut scan = StoredScan::new(
+            PathBuf::from("/r"),
+            DirectoryResult {
+                scan_id: None,
+                entries: vec![
+                    entry("/r/a", 'd', 500),
+                    entry("/r/a/b", 'd', 300),
+                    entry("/r/ab", '-', 50),
+                ],
+                query_time: 0.0,
+                telemetry: None,
+                scan_plan: None,
+                completeness: Default::default(),
+                skipped: Default::default(),
+            },
+            SystemTime::UNIX_EPOCH,
+        );
+        scan.patch_entry("/r/a/b", Some(entry("/r/a/b", 'd', 100)))
+            .unwrap();
+        let sizes = |scan: &StoredScan| {
+            let mut sizes: Vec<_> = scan
+                .result
+                .entries
+                .iter()
+                .map(|e| (e.path(), e.size_raw))
+                .collect();
+            sizes.sort();
+            sizes
+        };
+        assert_eq!(
+            sizes(&scan),
+            [
+                ("/r/a".to_string(), 300),
+                ("/r/a/b".to_string(), 100),
+                ("/r/ab".to_string(), 50)
+            ]
+        );
+
+        scan.insert_entry(entry("/r/a/b/new.bin", '-', 25));
+        assert_eq!(
+            sizes(&scan),
+            [
+                ("/r/a".to_string(), 325),
+                ("/r/a/b".to_string(), 125),
+                ("/r/a/b/new.bin".to_string(), 25),
+                ("/r/ab".to_string(), 50)
+            ]
+        );
+
+        // 移除目录时其下的条目一并移除
+        scan.patch_entry("/r/a/b", None).unwrap();
+        assert_eq!(
+            sizes(&scan),
+            [("/r/a".to_string(), 200), ("/r/ab".to_string(), 50)]
+        );
+        assert!(scan.patch_entry("/r/missing", None).is_err());
+    }
+
+    #[test]
+    fn warm_scans_do_not_evict_user_scans() {
+        let result = || DirectoryResult {
+            scan_id: None,
+            entries: Vec::new(),
+            query_time: 0.0,
+            telemetry: None,
+            scan_plan: None,
+            completeness: Default::default(),
+            skipped: Default::default(),
+        };
+        let mut store = ScanStore::default();
+        let first_user = store.insert(PathBuf::from("/u"), result());
+        let warm: Vec<u64> = (0..MAX_WARM_SCANS + 1)
+            .map(|_| store.insert_warm(PathBuf::from("/w"), result()))
+            .collect();
+        assert!(store.get(first_user).is_ok());
+        assert!(store.get(warm[0]).is_err());
+        for _ in 1..MAX_STORED_SCANS {
+            store.insert(PathBuf::from("/u"), result());
+        }
+        assert!(store.get(first_user).is_ok());
+        assert!(store.get(warm[1]).is_ok());
+
+        // 打开后的预扫描结果按用户扫描计数，挤出最早的用户扫描
+        store.claim_warm(warm[1]);
+        assert!(store.get(first_user).is_err());
+        assert!(store.get(warm[1]).is_ok());
+    }
+}
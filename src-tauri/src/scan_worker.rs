@@ -0,0 +1,395 @@
+// 在子进程中执行扫描，扫描器在异常文件系统上崩溃或耗尽内存时不会拖垮界面。
+// 主进程以 --scan-worker 参数启动自身，通过 stdin 发送一行 WorkerRequest，
+// 工作进程在 stdout 上每行输出一个 WorkerMessage：扫描事件原样转发，最后是结果或错误。
+// 工作进程异常退出时自动重启，多次失败后才向前端报告
+use crate::collation::NameCollation;
+use crate::dir_listing_v2::{count_entries, list_directory_with_events};
+use crate::ignore_rules::{IgnoreMode, ScanOverride};
+use crate::models::{Cli, FileEntry};
+use crate::parallelism::{plan_for, ParallelismSettings, ScanPlan};
+use crate::platform;
+use crate::scan_context::{ScanEmitter, ENTRY_DONE_EVENT};
+use crate::skip_list::FailureKind;
+use crate::vfs::{Fs, RealFs};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+
+pub const WORKER_FLAG: &str = "--scan-worker";
+// 工作进程异常退出后最多重启的次数
+const MAX_RESTARTS: u32 = 2;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkerRequest {
+    pub path: String,
+    pub scan_id: u64,
+    pub context: Option<String>,
+    pub settings: ParallelismSettings,
+    pub ignore: IgnoreMode,
+    /// 设置中按路径附加的扫描选项
+    pub overrides: Vec<ScanOverride>,
+    /// 先统计条目总数再计算大小，报告准确的百分比
+    pub determinate: bool,
+    /// 名称的排序规则
+    pub collation: NameCollation,
+    /// 后台预扫描：工作进程以低 CPU 与 I/O 优先级运行
+    #[serde(default)]
+    pub background: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerMessage {
+    /// 扫描事件，由主进程转发到 scan://{id}/{event}
+    Event {
+        event: String,
+        payload: serde_json::Value,
+    },
+    Done {
+        entries: Vec<FileEntry>,
+        plan: ScanPlan,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+// 按目标所在存储类型选择线程数扫描目录，进程内扫描和工作进程共用
+pub fn scan_directory(
+    path: &Path,
+    settings: &ParallelismSettings,
+    ignore: IgnoreMode,
+    overrides: &[ScanOverride],
+    determinate: bool,
+    collation: NameCollation,
+    emitter: &ScanEmitter,
+) -> Result<(Vec<FileEntry>, ScanPlan), std::io::Error> {
+    scan_directory_in(
+        &RealFs,
+        path,
+        settings,
+        ignore,
+        overrides,
+        determinate,
+        collation,
+        emitter,
+    )
+}
+
+// 通过 fs 访问文件系统的 scan_directory，录制与回放扫描时使用
+#[allow(clippy::too_many_arguments)]
+pub fn scan_directory_in(
+    fs: &dyn Fs,
+    path: &Path,
+    settings: &ParallelismSettings,
+    ignore: IgnoreMode,
+    overrides: &[ScanOverride],
+    determinate: bool,
+    collation: NameCollation,
+    emitter: &ScanEmitter,
+) -> Result<(Vec<FileEntry>, ScanPlan), std::io::Error> {
+    // 机械硬盘上顺序读取
+    let plan = plan_for(path, settings);
+    let listed = plan.run(|parallel| {
+        let emitter = if determinate {
+            let total = count_entries(fs, path, ignore, overrides, parallel, emitter);
+            emitter.with_total(path, total)
+        } else {
+            emitter.clone()
+        };
+        let cli = Cli {
+            file: None,
+            long_format: true,
+            human_readable: true,
+            all: true,
+            show_time: true,
+            parallel,
+            sort: true,
+            name: None,
+            full_path: true,
+            collation,
+        };
+        list_directory_with_events(fs, path, &cli, ignore, overrides, &emitter)
+    });
+    listed.map(|entries| (entries, plan))
+}
+
+// 工作进程入口，main 检测到 --scan-worker 参数时调用
+pub fn run_worker() {
+    let stdout = Arc::new(Mutex::new(std::io::stdout()));
+    let send = {
+        let stdout = stdout.clone();
+        move |message: &WorkerMessage| {
+            if let Ok(line) = serde_json::to_string(message) {
+                let mut out = stdout.lock().unwrap();
+                let _ = writeln!(out, "{}", line);
+                let _ = out.flush();
+            }
+        }
+    };
+
+    let mut line = String::new();
+    if let Err(e) = std::io::stdin().lock().read_line(&mut line) {
+        send(&WorkerMessage::Failed {
+            message: format!("无法读取扫描请求: {}", e),
+        });
+        return;
+    }
+    let request: WorkerRequest = match serde_json::from_str(&line) {
+        Ok(request) => request,
+        Err(e) => {
+            send(&WorkerMessage::Failed {
+                message: format!("扫描请求格式错误: {}", e),
+            });
+            return;
+        }
+    };
+
+    if request.background {
+        platform::lower_process_priority();
+    }
+    let forward = send.clone();
+    let emitter =
+        ScanEmitter::forwarding(request.scan_id, request.context, move |event, payload| {
+            forward(&WorkerMessage::Event {
+                event: event.to_string(),
+                payload,
+            })
+        });
+    send(&match scan_directory(
+        Path::new(&request.path),
+        &request.settings,
+        request.ignore,
+        &request.overrides,
+        request.determinate,
+        request.collation,
+        &emitter,
+    ) {
+        Ok((entries, plan)) => WorkerMessage::Done { entries, plan },
+        Err(e) => WorkerMessage::Failed {
+            message: e.to_string(),
+        },
+    });
+}
+
+// 正在运行的工作进程，放在阻塞任务之外，扫描超时或被放弃时由调用方结束进程；
+// 结束后不再启动新的工作进程
+#[derive(Clone, Default)]
+pub struct WorkerHandle(Arc<Mutex<WorkerSlot>>);
+
+#[derive(Default)]
+struct WorkerSlot {
+    child: Option<Child>,
+    killed: bool,
+    /// 工作进程以低优先级运行
+    background: bool,
+}
+
+impl WorkerHandle {
+    // 后台预扫描使用的句柄
+    pub fn background() -> Self {
+        WorkerHandle(Arc::new(Mutex::new(WorkerSlot {
+            background: true,
+            ..WorkerSlot::default()
+        })))
+    }
+
+    // 登记新启动的工作进程；已被结束时直接结束它并返回 false
+    fn attach(&self, mut child: Child) -> bool {
+        let mut slot = self.0.lock().unwrap();
+        if slot.killed {
+            let _ = child.kill();
+            let _ = child.wait();
+            return false;
+        }
+        slot.child = Some(child);
+        true
+    }
+
+    // 取回工作进程以等待其退出，已被 kill 回收时为 None
+    fn detach(&self) -> Option<Child> {
+        self.0.lock().unwrap().child.take()
+    }
+
+    fn is_background(&self) -> bool {
+        self.0.lock().unwrap().background
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.0.lock().unwrap().killed
+    }
+
+    // 结束并回收当前工作进程
+    pub fn kill(&self) {
+        let mut slot = self.0.lock().unwrap();
+        slot.killed = true;
+        if let Some(mut child) = slot.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    // 在返回值被丢弃时结束工作进程，覆盖超时返回和命令被取消两种情况
+    pub fn kill_on_drop(&self) -> KillOnDrop {
+        KillOnDrop(self.clone())
+    }
+}
+
+pub struct KillOnDrop(WorkerHandle);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        self.0.kill();
+    }
+}
+
+enum Attempt {
+    Finished(Result<(Vec<FileEntry>, ScanPlan), String>),
+    /// 工作进程没有给出结果就退出了
+    Crashed(String),
+}
+
+// 在工作进程中扫描，事件经 emitter 转发给前端；进程崩溃时重启，无法启动工作进程时退回进程内扫描。
+// 工作进程登记在 worker 中，调用方可随时结束
+#[allow(clippy::too_many_arguments)]
+pub fn scan_in_worker(
+    path: &Path,
+    settings: &ParallelismSettings,
+    ignore: IgnoreMode,
+    overrides: &[ScanOverride],
+    determinate: bool,
+    collation: NameCollation,
+    emitter: &ScanEmitter,
+    worker: &WorkerHandle,
+) -> Result<(Vec<FileEntry>, ScanPlan), String> {
+    let request = WorkerRequest {
+        path: path.to_string_lossy().into_owned(),
+        scan_id: emitter.scan_id(),
+        context: emitter.context(),
+        settings: settings.clone(),
+        ignore,
+        overrides: overrides.to_vec(),
+        determinate,
+        collation,
+        background: worker.is_background(),
+    };
+    let mut restarts = 0;
+    loop {
+        match run_attempt(&request, emitter, worker) {
+            Ok(Attempt::Finished(result)) => return result,
+            Ok(Attempt::Crashed(_)) if worker.is_killed() => return Err("扫描已终止".to_string()),
+            Ok(Attempt::Crashed(reason)) if restarts < MAX_RESTARTS => {
+                restarts += 1;
+                eprintln!("扫描工作进程异常退出（{}），第 {} 次重启", reason, restarts);
+                emitter.emit("worker_restarted", restarts);
+            }
+            Ok(Attempt::Crashed(reason)) => {
+                return Err(format!(
+                    "扫描进程异常退出（{}），已重启 {} 次仍未完成",
+                    reason, restarts
+                ))
+            }
+            Err(e) => {
+                eprintln!("无法启动扫描工作进程，改为在主进程中扫描: {}", e);
+                return scan_directory(
+                    path,
+                    settings,
+                    ignore,
+                    overrides,
+                    determinate,
+                    collation,
+                    emitter,
+                )
+                .map_err(|e| e.to_string());
+            }
+        }
+    }
+}
+
+fn run_attempt(
+    request: &WorkerRequest,
+    emitter: &ScanEmitter,
+    worker: &WorkerHandle,
+) -> Result<Attempt, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut child = platform::command(exe)
+        .arg(WORKER_FLAG)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 写入失败说明进程已退出，下面读取 stdout 时按崩溃处理
+        let _ = writeln!(stdin, "{}", line);
+    }
+    let stdout = child.stdout.take();
+    if !worker.attach(child) {
+        return Ok(Attempt::Crashed("已终止".to_string()));
+    }
+
+    let mut outcome = None;
+    if let Some(stdout) = stdout {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            // 扫描代码打印到 stdout 的其他内容不是协议消息，忽略
+            let Ok(message) = serde_json::from_str::<WorkerMessage>(&line) else {
+                continue;
+            };
+            match message {
+                WorkerMessage::Event { event, payload } if event == ENTRY_DONE_EVENT => {
+                    if let Ok(entry) = serde_json::from_value::<FileEntry>(payload) {
+                        emitter.entry_done(&entry);
+                    }
+                }
+                WorkerMessage::Event { event, payload } => {
+                    if let Some(file) = payload.get("current_file").and_then(|v| v.as_str()) {
+                        emitter.touch(Path::new(file));
+                        let kind = payload
+                            .get("status")
+                            .and_then(|s| s.get("kind"))
+                            .and_then(|k| k.as_str());
+                        if kind == Some("permission_denied") {
+                            emitter.note_failure(file, FailureKind::PermissionDenied);
+                        }
+                    }
+                    emitter.emit(&event, payload);
+                }
+                WorkerMessage::Done { entries, plan } => outcome = Some(Ok((entries, plan))),
+                WorkerMessage::Failed { message } => outcome = Some(Err(message)),
+            }
+        }
+    }
+    // 被 kill 结束的进程已经回收
+    let status = match worker.detach() {
+        Some(mut child) => child.wait().map_err(|e| e.to_string())?.to_string(),
+        None => "已终止".to_string(),
+    };
+    Ok(match outcome {
+        Some(result) => Attempt::Finished(result),
+        None => Attempt::Crashed(status),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn killing_the_handle_stops_the_worker_and_later_attempts() {
+        let worker = WorkerHandle::default();
+        let started = Instant::now();
+        assert!(worker.attach(Command::new("sleep").arg("30").spawn().unwrap()));
+        drop(worker.kill_on_drop());
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(worker.is_killed());
+        assert!(worker.detach().is_none());
+        assert!(!worker.attach(Command::new("sleep").arg("30").spawn().unwrap()));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,532 @@
+// 基于 Rhai 的自动化脚本。脚本通过 files()/list() 获取文件，用 Rhai 自带的
+// filter 等方法筛选，再调用 trash()/delete() 登记要执行的操作。
+// 脚本运行结束后才统一执行登记的操作，因此可以先以演练模式查看结果
+use crate::audit::{AuditAction, AuditLog};
+use crate::config::ConfigStore;
+use crate::deletion::{delete_path, refuse_deletion};
+use crate::index::NameMatcher;
+use crate::platform;
+use crate::power;
+use crate::utils::{dir_size, human_readable_size};
+use crate::vfs::RealFs;
+use rhai::{Array, Dynamic, Engine, Map};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+const SCRIPTS_FILE: &str = "scripts.json";
+// 防止死循环脚本一直占用后台线程
+const MAX_OPERATIONS: u64 = 50_000_000;
+// 单次运行最多登记的操作数
+const MAX_ACTIONS: usize = 100_000;
+const DAY: u64 = 24 * 60 * 60;
+// 检查定时脚本是否到期的间隔
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ScriptSchedule {
+    /// 只手动运行
+    Manual,
+    /// 从 start（Unix 秒）开始每隔 interval_hours 小时运行一次，
+    /// 如“每周日 3 点”即从某个周日 3 点开始每 168 小时
+    Every { start: u64, interval_hours: u64 },
+}
+
+impl ScriptSchedule {
+    // now 之前最近一次应运行的时间
+    fn last_due(&self, now: u64) -> Option<u64> {
+        match self {
+            ScriptSchedule::Manual => None,
+            ScriptSchedule::Every {
+                start,
+                interval_hours,
+            } => {
+                let interval = interval_hours.saturating_mul(3600).max(60);
+                if now < *start {
+                    return None;
+                }
+                Some(start + (now - start) / interval * interval)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptActionKind {
+    Trash,
+    Delete,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptAction {
+    pub action: ScriptActionKind,
+    pub path: String,
+    pub bytes: u64,
+    /// 被拒绝（卷根目录、主目录等）或实际执行失败时的错误信息
+    pub error: Option<String>,
+    pub executed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptRun {
+    pub name: String,
+    /// Unix 时间戳（秒）
+    pub started_at: u64,
+    pub dry_run: bool,
+    /// print() 与 log() 的输出
+    pub log: Vec<String>,
+    pub actions: Vec<ScriptAction>,
+    pub total_bytes: u64,
+    pub total_display: String,
+    /// 脚本本身出错或有操作被拒绝时的信息，此时不会执行任何操作
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Script {
+    pub name: String,
+    pub source: String,
+    pub schedule: ScriptSchedule,
+    pub enabled: bool,
+    /// 上次运行时间（Unix 秒）
+    pub last_run: Option<u64>,
+    pub last_result: Option<ScriptRun>,
+}
+
+// 保存在数据目录中的脚本及其运行记录
+pub struct ScriptStore {
+    path: PathBuf,
+    scripts: Mutex<Vec<Script>>,
+}
+
+impl ScriptStore {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SCRIPTS_FILE);
+        let scripts = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("脚本文件解析失败: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        ScriptStore {
+            path,
+            scripts: Mutex::new(scripts),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Script> {
+        self.scripts.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Script> {
+        self.scripts
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+    }
+
+    // 新建或覆盖同名脚本，保存前先检查语法
+    pub fn save(
+        &self,
+        name: &str,
+        source: String,
+        schedule: ScriptSchedule,
+        enabled: bool,
+    ) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("脚本名称不能为空".to_string());
+        }
+        Engine::new()
+            .compile(&source)
+            .map_err(|e| format!("脚本语法错误: {}", e))?;
+        let mut scripts = self.scripts.lock().unwrap();
+        match scripts.iter_mut().find(|s| s.name == name) {
+            Some(script) => {
+                script.source = source;
+                script.schedule = schedule;
+                script.enabled = enabled;
+            }
+            None => scripts.push(Script {
+                name: name.to_string(),
+                source,
+                schedule,
+                enabled,
+                last_run: None,
+                last_result: None,
+            }),
+        }
+        self.write(&scripts)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        let mut scripts = self.scripts.lock().unwrap();
+        scripts.retain(|s| s.name != name);
+        self.write(&scripts)
+    }
+
+    // 已启用且到期尚未运行的脚本
+    pub fn due(&self, now: u64) -> Vec<Script> {
+        self.scripts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.enabled)
+            .filter(|s| {
+                s.schedule
+                    .last_due(now)
+                    .is_some_and(|due| s.last_run.is_none_or(|last| last < due))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // 记录运行结果。手动演练不算作一次运行，不影响定时；
+    // 定时运行在只读模式下会退化为演练，但仍算作已运行，避免每分钟重复
+    pub fn record_run(&self, run: &ScriptRun, scheduled: bool) {
+        let mut scripts = self.scripts.lock().unwrap();
+        if let Some(script) = scripts.iter_mut().find(|s| s.name == run.name) {
+            if scheduled || !run.dry_run {
+                script.last_run = Some(run.started_at);
+            }
+            script.last_result = Some(run.clone());
+        }
+        if let Err(e) = self.write(&scripts) {
+            eprintln!("保存脚本运行结果失败: {}", e);
+        }
+    }
+
+    fn write(&self, scripts: &[Script]) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("无法创建数据目录: {}", e))?;
+        }
+        let content =
+            serde_json::to_string_pretty(scripts).map_err(|e| format!("脚本序列化失败: {}", e))?;
+        fs::write(&self.path, content).map_err(|e| format!("保存脚本失败: {}", e))
+    }
+}
+
+// 后台定时运行到期的脚本，结果通过 script-finished 事件通知前端
+pub fn start_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULER_INTERVAL);
+        // 使用电池时推迟，接通电源后的下一次检查补上错过的运行
+        let settings = app
+            .state::<Mutex<ConfigStore>>()
+            .lock()
+            .unwrap()
+            .get()
+            .power
+            .clone();
+        if power::current(&settings).defer_background {
+            continue;
+        }
+        let store = app.state::<ScriptStore>();
+        for script in store.due(now_secs()) {
+            let read_only = app
+                .state::<Mutex<ConfigStore>>()
+                .lock()
+                .unwrap()
+                .get()
+                .read_only;
+            let run = run_script(&script, read_only, &app.state::<AuditLog>());
+            store.record_run(&run, true);
+            let _ = app.emit("script-finished", run);
+        }
+    });
+}
+
+// 运行脚本。dry_run 为 true 时只返回登记的操作，不做任何修改
+pub fn run_script(script: &Script, dry_run: bool, audit: &AuditLog) -> ScriptRun {
+    let started_at = now_secs();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let actions = Arc::new(Mutex::new(Vec::new()));
+    let engine = build_engine(log.clone(), actions.clone());
+    let error = engine.run(&script.source).err().map(|e| e.to_string());
+
+    let mut actions = std::mem::take(&mut *actions.lock().unwrap());
+    // 登记时已检查每个操作，只要有一个被拒绝就全部不执行
+    let refused = actions.iter().filter(|a| a.error.is_some()).count();
+    let error = error
+        .or_else(|| (refused > 0).then(|| format!("{} 个操作被拒绝，未执行任何操作", refused)));
+    if error.is_none() && !dry_run {
+        for action in actions.iter_mut() {
+            execute(action, audit);
+        }
+    }
+    let total_bytes = actions
+        .iter()
+        .filter(|a| a.error.is_none())
+        .fold(0u64, |acc, a| acc.saturating_add(a.bytes));
+    let log = std::mem::take(&mut *log.lock().unwrap());
+    ScriptRun {
+        name: script.name.clone(),
+        started_at,
+        dry_run,
+        log,
+        actions,
+        total_bytes,
+        total_display: human_readable_size(total_bytes),
+        error,
+    }
+}
+
+fn build_engine(log: Arc<Mutex<Vec<String>>>, actions: Arc<Mutex<Vec<ScriptAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let print_log = log.clone();
+    engine.on_print(move |s| print_log.lock().unwrap().push(s.to_string()));
+    engine.register_fn("log", move |s: &str| {
+        log.lock().unwrap().push(s.to_string())
+    });
+
+    // files(root)：root 下所有文件（递归），list(root)：root 的直接子项，目录带总大小
+    engine.register_fn("files", |root: &str| -> Array {
+        let mut files = Array::new();
+        collect_files(Path::new(root), &mut files);
+        files
+    });
+    engine.register_fn("list", |root: &str| -> Array {
+        match fs::read_dir(root) {
+            Ok(entries) => entries
+                .flatten()
+                .filter_map(|e| fs::symlink_metadata(e.path()).ok().map(|m| (e.path(), m)))
+                .map(|(path, metadata)| {
+                    let size = if metadata.is_dir() {
+                        dir_size(&path)
+                    } else {
+                        metadata.len()
+                    };
+                    file_info(&path, &metadata, size)
+                })
+                .collect(),
+            Err(_) => Array::new(),
+        }
+    });
+    // 与索引查询相同的匹配规则：不含通配符时按子串匹配，含路径分隔符时匹配完整路径
+    engine.register_fn("matches", |path: &str, pattern: &str| -> bool {
+        NameMatcher::new(pattern).is_ok_and(|m| m.matches(path))
+    });
+    engine.register_fn("human_size", |bytes: i64| -> String {
+        human_readable_size(bytes.max(0) as u64)
+    });
+
+    let trash_actions = actions.clone();
+    engine.register_fn("trash", move |path: &str| {
+        queue(&trash_actions, ScriptActionKind::Trash, path)
+    });
+    engine.register_fn("delete", move |path: &str| {
+        queue(&actions, ScriptActionKind::Delete, path)
+    });
+    engine
+}
+
+fn queue(
+    actions: &Mutex<Vec<ScriptAction>>,
+    action: ScriptActionKind,
+    path: &str,
+) -> Result<(), Box<rhai::EvalAltResult>> {
+    let mut actions = actions.lock().unwrap();
+    if actions.len() >= MAX_ACTIONS {
+        return Err(format!("单次运行最多执行 {} 个操作", MAX_ACTIONS).into());
+    }
+    let target = Path::new(path);
+    let error = refusal(action, target);
+    // 被拒绝的路径可能是卷根目录，不统计大小
+    let bytes = match fs::symlink_metadata(target) {
+        _ if error.is_some() => 0,
+        Ok(m) if m.is_dir() => dir_size(target),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+    };
+    actions.push(ScriptAction {
+        action,
+        path: path.to_string(),
+        bytes,
+        error,
+        executed: false,
+    });
+    Ok(())
+}
+
+// 与界面中的删除和移到回收站相同的检查：不允许操作卷根目录、主目录和安装目录
+fn refusal(action: ScriptActionKind, path: &Path) -> Option<String> {
+    if let Some(reason) = refuse_deletion(path) {
+        return Some(reason.to_string());
+    }
+    if action == ScriptActionKind::Trash && !platform::supports_trash() {
+        return Some(platform::unsupported("回收站"));
+    }
+    None
+}
+
+fn execute(action: &mut ScriptAction, audit: &AuditLog) {
+    let path = Path::new(&action.path);
+    let (kind, result) = match action.action {
+        ScriptActionKind::Trash => (
+            AuditAction::Trash,
+            trash::delete(path).map_err(|e| format!("移到回收站失败: {}", e)),
+        ),
+        ScriptActionKind::Delete => (AuditAction::Delete, delete_path(&RealFs, path, false)),
+    };
+    audit.record(kind, &action.path, None, action.bytes, &result);
+    action.executed = result.is_ok();
+    action.error = result.err();
+}
+
+fn collect_files(dir: &Path, files: &mut Array) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接，避免陷入循环或操作到扫描范围之外
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(file_info(&path, &metadata, metadata.len()));
+        }
+    }
+}
+
+// 传给脚本的文件信息：path、name、ext、size、is_dir、modified（Unix 秒）、age_days
+fn file_info(path: &Path, metadata: &fs::Metadata, size: u64) -> Dynamic {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut map = Map::new();
+    map.insert(
+        "path".into(),
+        Dynamic::from(path.to_string_lossy().into_owned()),
+    );
+    map.insert(
+        "name".into(),
+        Dynamic::from(
+            path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        ),
+    );
+    map.insert(
+        "ext".into(),
+        Dynamic::from(
+            path.extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default(),
+        ),
+    );
+    map.insert("size".into(), Dynamic::from(size as i64));
+    map.insert("is_dir".into(), Dynamic::from(metadata.is_dir()));
+    map.insert("modified".into(), Dynamic::from(modified as i64));
+    map.insert(
+        "age_days".into(),
+        Dynamic::from((now_secs().saturating_sub(modified) / DAY) as i64),
+    );
+    Dynamic::from_map(map)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_due_times() {
+        let weekly = ScriptSchedule::Every {
+            start: 1000,
+            interval_hours: 168,
+        };
+        assert_eq!(weekly.last_due(999), None);
+        assert_eq!(weekly.last_due(1000), Some(1000));
+        assert_eq!(
+            weekly.last_due(1000 + 168 * 3600 + 5),
+            Some(1000 + 168 * 3600)
+        );
+        assert_eq!(ScriptSchedule::Manual.last_due(5000), None);
+    }
+
+    fn script(source: String) -> Script {
+        Script {
+            name: "cleanup".to_string(),
+            source,
+            schedule: ScriptSchedule::Manual,
+            enabled: true,
+            last_run: None,
+            last_result: None,
+        }
+    }
+
+    #[test]
+    fn dry_run_leaves_files_untouched() {
+        let dir = std::env::temp_dir().join(format!("disksight-script-dry-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("old.tmp");
+        fs::write(&file, b"12345").unwrap();
+        let audit = AuditLog::new(&dir);
+
+        let run = run_script(
+            &script(format!("delete({:?});", file.to_string_lossy())),
+            true,
+            &audit,
+        );
+        assert!(run.error.is_none());
+        assert_eq!(run.actions.len(), 1);
+        assert!(!run.actions[0].executed);
+        assert_eq!(run.total_bytes, 5);
+        assert!(file.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refused_action_aborts_the_run() {
+        let dir =
+            std::env::temp_dir().join(format!("disksight-script-refused-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("old.tmp");
+        fs::write(&file, b"12345").unwrap();
+        let audit = AuditLog::new(&dir);
+        // 安装目录（测试程序所在目录）不允许删除
+        let install_dir = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_path_buf();
+
+        let run = run_script(
+            &script(format!(
+                "delete({:?}); trash({:?});",
+                file.to_string_lossy(),
+                install_dir.to_string_lossy()
+            )),
+            false,
+            &audit,
+        );
+        assert!(run.error.is_some());
+        assert!(run.actions.iter().all(|a| !a.executed));
+        assert!(run.actions[1].error.is_some());
+        assert!(file.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
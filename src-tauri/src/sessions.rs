@@ -0,0 +1,241 @@
+use crate::scan_store::{ScanStore, StoredScan};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const SESSIONS_DIR: &str = "sessions";
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SessionFile {
+    name: String,
+    saved_at: SystemTime,
+    /// 保存时的扫描 ID 及扫描结果
+    scans: Vec<(u64, StoredScan)>,
+    /// 前端的筛选、选中项、导航位置等，后端只负责原样保存
+    ui_state: Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub name: String,
+    pub saved_at: SystemTime,
+    pub scan_count: usize,
+    /// 各扫描的根目录
+    pub roots: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoadedSession {
+    pub name: String,
+    pub saved_at: SystemTime,
+    /// 保存时的扫描 ID -> 恢复后分配的新 ID，前端据此更新 ui_state 中的引用
+    pub scan_ids: Vec<(u64, u64)>,
+    pub ui_state: Value,
+}
+
+// 把整个清理会话（所有已保存的扫描结果和前端状态）保存到数据目录，
+// 以便几天后继续
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(data_dir: &Path) -> Self {
+        SessionStore {
+            dir: data_dir.join(SESSIONS_DIR),
+        }
+    }
+
+    pub fn save(&self, name: &str, store: &ScanStore, ui_state: Value) -> Result<(), String> {
+        let path = self.path_of(name)?;
+        let session = SessionFile {
+            name: name.to_string(),
+            saved_at: SystemTime::now(),
+            scans: store.iter().map(|(id, scan)| (id, scan.clone())).collect(),
+            ui_state,
+        };
+        fs::create_dir_all(&self.dir).map_err(|e| format!("无法创建会话目录: {}", e))?;
+        let content =
+            serde_json::to_string(&session).map_err(|e| format!("会话序列化失败: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("保存会话失败: {}", e))
+    }
+
+    // 把会话中的扫描重新放入 ScanStore，返回新旧 ID 对照和前端状态
+    pub fn load(&self, name: &str, store: &mut ScanStore) -> Result<LoadedSession, String> {
+        let session = self.read(name)?;
+        let scan_ids = session
+            .scans
+            .into_iter()
+            .map(|(old_id, scan)| (old_id, store.insert_scan(scan)))
+            .collect();
+        Ok(LoadedSession {
+            name: session.name,
+            saved_at: session.saved_at,
+            scan_ids,
+            ui_state: session.ui_state,
+        })
+    }
+
+    pub fn list(&self) -> Vec<SessionSummary> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<SessionSummary> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.path().file_stem()?.to_string_lossy().into_owned();
+                let session = self.read(&name).ok()?;
+                Some(SessionSummary {
+                    name: session.name,
+                    saved_at: session.saved_at,
+                    scan_count: session.scans.len(),
+                    roots: session
+                        .scans
+                        .iter()
+                        .map(|(_, scan)| scan.root.to_string_lossy().into_owned())
+                        .collect(),
+                })
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.saved_at));
+        sessions
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        fs::remove_file(self.path_of(name)?).map_err(|e| format!("删除会话失败: {}", e))
+    }
+
+    fn read(&self, name: &str) -> Result<SessionFile, String> {
+        let content =
+            fs::read_to_string(self.path_of(name)?).map_err(|_| format!("会话 {} 不存在", name))?;
+        serde_json::from_str(&content).map_err(|e| format!("会话文件已损坏: {}", e))
+    }
+
+    // 会话名直接作为文件名，不允许包含路径分隔符等字符
+    fn path_of(&self, name: &str) -> Result<PathBuf, String> {
+        let valid = !name.trim().is_empty()
+            && !name.starts_with('.')
+            && !name
+                .chars()
+                .any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'));
+        if !valid {
+            return Err("会话名称不合法".to_string());
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DirectoryResult, FileEntry};
+    use crate::utils::human_readable_size;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "disksight-sessions-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn result(paths: &[(&str, u64)]) -> DirectoryResult {
+        DirectoryResult {
+            scan_id: None,
+            entries: paths
+                .iter()
+                .map(|(path, size_raw)| FileEntry {
+                    file_type: '-',
+                    permissions: String::new(),
+                    size_raw: *size_raw,
+                    size_display: human_readable_size(*size_raw),
+                    created_time: SystemTime::UNIX_EPOCH,
+                    modified_time: None,
+                    location: path.to_string().into(),
+                    name: Path::new(path)
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned(),
+                    severity: None,
+                })
+                .collect(),
+            query_time: 0.0,
+            telemetry: None,
+            scan_plan: None,
+            completeness: Default::default(),
+            skipped: Default::default(),
+        }
+    }
+
+    #[test]
+    fn saved_session_restores_scans_and_ui_state() {
+        let dir = temp_dir("round-trip");
+        let sessions = SessionStore::new(&dir);
+        let mut store = ScanStore::default();
+        let first = store.insert(
+            PathBuf::from("/r"),
+            result(&[("/r/a.iso", 700), ("/r/b", 30)]),
+        );
+        let second = store.insert(PathBuf::from("/s"), result(&[("/s/c", 5)]));
+        let ui_state = json!({ "filter": "*.iso", "selected": [first] });
+        sessions.save("cleanup", &store, ui_state.clone()).unwrap();
+
+        let mut restored = ScanStore::default();
+        restored.insert(PathBuf::from("/other"), result(&[]));
+        let loaded = sessions.load("cleanup", &mut restored).unwrap();
+        assert_eq!(loaded.name, "cleanup");
+        assert_eq!(loaded.ui_state, ui_state);
+        assert_eq!(loaded.scan_ids.len(), 2);
+        for (old_id, new_id) in &loaded.scan_ids {
+            let original = store.get(*old_id).unwrap();
+            let scan = restored.get(*new_id).unwrap();
+            assert_eq!(scan.root, original.root);
+            let paths: Vec<String> = scan.result.entries.iter().map(|e| e.path()).collect();
+            let expected: Vec<String> = original.result.entries.iter().map(|e| e.path()).collect();
+            assert_eq!(paths, expected);
+        }
+        assert!(loaded.scan_ids.iter().any(|(old, _)| *old == second));
+
+        let listed = sessions.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].scan_count, 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_session_is_reported_and_skipped() {
+        let dir = temp_dir("corrupt");
+        let sessions = SessionStore::new(&dir);
+        fs::create_dir_all(dir.join(SESSIONS_DIR)).unwrap();
+        fs::write(
+            dir.join(SESSIONS_DIR).join("broken.json"),
+            "{\"name\": \"broken\", ",
+        )
+        .unwrap();
+
+        let mut store = ScanStore::default();
+        let error = sessions.load("broken", &mut store).err().unwrap();
+        assert!(error.contains("已损坏"));
+        assert_eq!(store.iter().count(), 0);
+        assert!(sessions.list().is_empty());
+        assert!(sessions.load("missing", &mut store).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_file_names() {
+        let sessions = SessionStore::new(&temp_dir("names"));
+        for name in ["", "  ", "../escape", "a/b", ".hidden", "c:d"] {
+            assert!(sessions.path_of(name).is_err(), "{:?}", name);
+        }
+        assert!(sessions.path_of("周末清理").is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+// 导出与导入设置，把排除目录、保留策略、定时脚本和监控目录等一整套配置复制到另一台机器
+use crate::config::{AppConfig, ConfigStore, Profile};
+use crate::scripts::{ScriptSchedule, ScriptStore};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 导出文件格式版本，格式不兼容时递增
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportedScript {
+    pub name: String,
+    pub source: String,
+    pub schedule: ScriptSchedule,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    /// 导出时使用的配置档案
+    pub profile: Profile,
+    /// 导出时间（Unix 秒）
+    pub exported_at: u64,
+    /// 不含任何密钥（见 AppConfig::without_secrets），导入时由目标机器的配置补回
+    pub config: AppConfig,
+    /// 脚本及其定时设置，不含运行记录
+    pub scripts: Vec<ExportedScript>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// 导出文件来自的配置档案
+    pub profile: Profile,
+    pub scripts_imported: usize,
+    /// 导入失败的脚本及原因
+    pub script_errors: Vec<(String, String)>,
+    /// 本机不存在的目录，已从索引目录和监控目录中去掉
+    pub missing_paths: Vec<String>,
+    /// 导出文件不含密钥，本机也没有对应的值，需要重新填写的项
+    pub missing_secrets: Vec<String>,
+}
+
+pub fn export_settings(
+    path: &Path,
+    config: &ConfigStore,
+    scripts: &ScriptStore,
+) -> Result<(), String> {
+    let bundle = SettingsBundle {
+        version: BUNDLE_VERSION,
+        profile: config.profile(),
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        config: config.get().without_secrets(),
+        scripts: scripts
+            .list()
+            .into_iter()
+            .map(|s| ExportedScript {
+                name: s.name,
+                source: s.source,
+                schedule: s.schedule,
+                enabled: s.enabled,
+            })
+            .collect(),
+    };
+    let content =
+        serde_json::to_string_pretty(&bundle).map_err(|e| format!("设置序列化失败: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("写入导出文件失败: {}", e))
+}
+
+// 用导出文件替换当前配置。本机的密钥和只读模式保持不变，同名脚本被覆盖
+pub fn import_settings(
+    path: &Path,
+    config: &mut ConfigStore,
+    scripts: &ScriptStore,
+) -> Result<ImportSummary, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let bundle: SettingsBundle =
+        serde_json::from_str(&content).map_err(|e| format!("导入文件格式错误: {}", e))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "导入文件由更新的版本导出（格式版本 {}），请先升级",
+            bundle.version
+        ));
+    }
+
+    let mut imported = bundle.config;
+    let missing_paths = drop_missing_roots(&mut imported);
+    let missing_secrets = imported.restore_secrets(config.get());
+    imported.read_only = config.get().read_only;
+    config.set(imported)?;
+
+    let mut script_errors = Vec::new();
+    let mut scripts_imported = 0;
+    for script in bundle.scripts {
+        match scripts.save(&script.name, script.source, script.schedule, script.enabled) {
+            Ok(()) => scripts_imported += 1,
+            Err(e) => script_errors.push((script.name, e)),
+        }
+    }
+    Ok(ImportSummary {
+        profile: bundle.profile,
+        scripts_imported,
+        script_errors,
+        missing_paths,
+        missing_secrets,
+    })
+}
+
+// 去掉本机不存在的索引目录和监控目录，返回被去掉的路径
+fn drop_missing_roots(config: &mut AppConfig) -> Vec<String> {
+    let mut missing = Vec::new();
+    for roots in [&mut config.indexed_roots, &mut config.monitored_roots] {
+        roots.retain(|root| {
+            let exists = Path::new(root).is_dir();
+            if !exists && !missing.contains(root) {
+                missing.push(root.clone());
+            }
+            exists
+        });
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_roots_missing_on_this_machine() {
+        let existing = std::env::temp_dir().to_string_lossy().into_owned();
+        let gone = std::env::temp_dir()
+            .join(format!("disksight-bundle-gone-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let mut config = AppConfig {
+            indexed_roots: vec![gone.clone()],
+            monitored_roots: vec![existing.clone(), gone.clone()],
+            ..AppConfig::default()
+        };
+        assert_eq!(drop_missing_roots(&mut config), vec![gone]);
+        assert!(config.indexed_roots.is_empty());
+        assert_eq!(config.monitored_roots, vec![existing]);
+    }
+}
@@ -0,0 +1,187 @@
+// 记住扫描中反复无权限读取或长时间无响应的路径（如 System Volume Information、失去响应的网络挂载），
+// 之后的扫描默认跳过，并告知跳过了多少个已知无法访问的路径；可以按路径或全部重置
+use crate::ignore_rules::ScanOverride;
+use crate::models::SkippedCounts;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const SKIP_LIST_FILE: &str = "skip_list.json";
+// 在这么多次扫描中失败后才跳过，偶尔的失败不算
+pub const FAILURES_BEFORE_SKIP: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    PermissionDenied,
+    /// 扫描在该路径上长时间没有进度
+    Timeout,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkipEntry {
+    pub path: String,
+    pub kind: FailureKind,
+    /// 失败过的扫描次数
+    pub failures: u32,
+    pub last_failed: SystemTime,
+    /// 已达到阈值，扫描时默认跳过
+    pub skipped: bool,
+}
+
+// 一次扫描中失败的路径，扫描事件与停滞检测都会写入
+pub type ScanFailures = Arc<Mutex<BTreeMap<String, FailureKind>>>;
+
+// 按原因统计一次扫描中未能统计的目录；known_inaccessible 为按跳过列表未扫描的目录数
+pub fn skipped_counts(
+    failures: &BTreeMap<String, FailureKind>,
+    known_inaccessible: u64,
+) -> SkippedCounts {
+    let count = |kind| failures.values().filter(|k| **k == kind).count() as u64;
+    SkippedCounts {
+        permission_denied: count(FailureKind::PermissionDenied),
+        timed_out: count(FailureKind::Timeout),
+        known_inaccessible,
+    }
+}
+
+pub struct SkipList {
+    path: PathBuf,
+    entries: Mutex<BTreeMap<String, SkipEntry>>,
+}
+
+impl SkipList {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SKIP_LIST_FILE);
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        SkipList {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    // 记录一次扫描中失败的路径
+    pub fn record(&self, failures: &BTreeMap<String, FailureKind>) -> Result<(), String> {
+        if failures.is_empty() {
+            return Ok(());
+        }
+        let mut entries = self.entries.lock().unwrap();
+        add_failures(&mut entries, failures, SystemTime::now());
+        self.save(&entries)
+    }
+
+    pub fn list(&self) -> Vec<SkipEntry> {
+        self.entries.lock().unwrap().values().cloned().collect()
+    }
+
+    // 扫描 root 时要跳过的路径
+    pub fn skipped_under(&self, root: &Path) -> Vec<String> {
+        skipped_under(&self.entries.lock().unwrap(), root)
+    }
+
+    // 忘记某个路径的失败记录，path 为 None 时全部清除
+    pub fn reset(&self, path: Option<&str>) -> Result<(), String> {
+        let mut entries = self.entries.lock().unwrap();
+        match path {
+            Some(path) => {
+                entries.remove(path);
+            }
+            None => entries.clear(),
+        }
+        self.save(&entries)
+    }
+
+    fn save(&self, entries: &BTreeMap<String, SkipEntry>) -> Result<(), String> {
+        let content =
+            serde_json::to_string(entries).map_err(|e| format!("跳过列表序列化失败: {}", e))?;
+        fs::write(&self.path, content).map_err(|e| format!("保存跳过列表失败: {}", e))
+    }
+}
+
+fn add_failures(
+    entries: &mut BTreeMap<String, SkipEntry>,
+    failures: &BTreeMap<String, FailureKind>,
+    now: SystemTime,
+) {
+    for (path, kind) in failures {
+        let entry = entries.entry(path.clone()).or_insert(SkipEntry {
+            path: path.clone(),
+            kind: *kind,
+            failures: 0,
+            last_failed: now,
+            skipped: false,
+        });
+        entry.kind = *kind;
+        entry.failures += 1;
+        entry.last_failed = now;
+        entry.skipped = entry.failures >= FAILURES_BEFORE_SKIP;
+    }
+}
+
+fn skipped_under(entries: &BTreeMap<String, SkipEntry>, root: &Path) -> Vec<String> {
+    entries
+        .values()
+        .filter(|e| e.skipped && Path::new(&e.path).starts_with(root))
+        .map(|e| e.path.clone())
+        .collect()
+}
+
+// 把要跳过的路径表示为其上级目录的排除模式，与按路径的扫描选项一起应用
+pub fn skip_override(path: &str) -> Option<ScanOverride> {
+    let path = Path::new(path);
+    let parent = path.parent()?;
+    let name = path.file_name()?.to_string_lossy();
+    let mut pattern = String::from("/");
+    for c in name.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    Some(ScanOverride {
+        path: parent.to_string_lossy().into_owned(),
+        exclude: vec![pattern],
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore_rules::{IgnoreFilter, IgnoreMode, Verdict};
+
+    #[test]
+    fn paths_are_skipped_after_repeated_failures() {
+        let mut entries = BTreeMap::new();
+        let failures = BTreeMap::from([(
+            "/mnt/c/System Volume Information".to_string(),
+            FailureKind::PermissionDenied,
+        )]);
+        add_failures(&mut entries, &failures, SystemTime::UNIX_EPOCH);
+        assert!(skipped_under(&entries, Path::new("/mnt/c")).is_empty());
+        add_failures(&mut entries, &failures, SystemTime::UNIX_EPOCH);
+        assert_eq!(
+            skipped_under(&entries, Path::new("/mnt/c")),
+            vec!["/mnt/c/System Volume Information"]
+        );
+        assert!(skipped_under(&entries, Path::new("/mnt/d")).is_empty());
+
+        let root = Path::new("/mnt/c");
+        let overrides = [skip_override("/mnt/c/System Volume Information").unwrap()];
+        let filter = IgnoreFilter::for_root(root, IgnoreMode::Off, &overrides).unwrap();
+        assert_eq!(
+            filter.verdict(&root.join("System Volume Information"), true),
+            Verdict::Skip
+        );
+        assert_eq!(
+            filter.verdict(&root.join("Users/System Volume Information"), true),
+            Verdict::Descend
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::drives::{cluster_size, mount_of};
+use crate::utils::human_readable_size;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+// 对比的更小簇大小
+const CANDIDATE_CLUSTERS: [u64; 4] = [512, 1024, 2048, 4096];
+// NTFS 上不超过该大小的文件内容直接存放在 MFT 记录中，不占用簇
+const NTFS_RESIDENT_LIMIT: u64 = 700;
+// 至少包含这么多文件且浪费超过该值的目录才值得报告
+const MIN_FILES: u64 = 1000;
+const MIN_SLACK_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_REPORTED: usize = 100;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClusterSaving {
+    pub cluster_size: u64,
+    /// 改用该簇大小后能节省的空间
+    pub saved_bytes: u64,
+    pub saved_display: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlackDirectory {
+    pub path: String,
+    /// 目录树下的文件数量
+    pub file_count: u64,
+    pub logical_bytes: u64,
+    pub allocated_bytes: u64,
+    /// 分配空间中因按簇取整而浪费的部分
+    pub slack_bytes: u64,
+    pub slack_display: String,
+    /// 浪费占分配空间的百分比
+    pub slack_percent: f64,
+    /// 打包成单个归档文件（不压缩）后能节省的空间
+    pub archive_saved_bytes: u64,
+    /// 使用更小簇大小重新格式化后能节省的空间，只列出比当前簇小的大小
+    pub smaller_cluster_savings: Vec<ClusterSaving>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlackReport {
+    pub root: String,
+    pub cluster_size: u64,
+    pub file_count: u64,
+    pub slack_bytes: u64,
+    pub slack_display: String,
+    /// 浪费集中的目录，按浪费大小降序
+    pub directories: Vec<SlackDirectory>,
+}
+
+#[derive(Clone, Default)]
+struct SlackTotals {
+    files: u64,
+    logical: u64,
+    /// 依次对应当前簇大小和 CANDIDATE_CLUSTERS 的浪费
+    slack: [u64; 1 + CANDIDATE_CLUSTERS.len()],
+}
+
+impl SlackTotals {
+    fn add_file(&mut self, len: u64, cluster: u64, resident_limit: u64) {
+        self.files += 1;
+        self.logical += len;
+        // 空文件和驻留在 MFT 中的小文件不占用簇
+        if len == 0 || len <= resident_limit {
+            return;
+        }
+        for (slot, size) in self
+            .slack
+            .iter_mut()
+            .zip(std::iter::once(cluster).chain(CANDIDATE_CLUSTERS))
+        {
+            *slot += slack_of(len, size);
+        }
+    }
+
+    fn merge(mut self, other: SlackTotals) -> SlackTotals {
+        self.files += other.files;
+        self.logical += other.logical;
+        for (a, b) in self.slack.iter_mut().zip(other.slack) {
+            *a += b;
+        }
+        self
+    }
+
+    fn qualifies(&self) -> bool {
+        self.files >= MIN_FILES && self.slack[0] >= MIN_SLACK_BYTES
+    }
+}
+
+// 按簇取整后多占用的字节数
+fn slack_of(len: u64, cluster: u64) -> u64 {
+    len.div_ceil(cluster) * cluster - len
+}
+
+// 统计目录树中每个文件按簇取整造成的浪费，找出大量小文件集中、
+// 值得打包归档或改用更小簇大小的目录
+pub fn slack_report(root: &Path) -> Result<SlackReport, String> {
+    let cluster = cluster_size(root).ok_or_else(|| "无法读取卷的簇大小".to_string())?;
+    let resident_limit = match mount_of(root) {
+        Some(mount) if mount.file_system.eq_ignore_ascii_case("ntfs") => NTFS_RESIDENT_LIMIT,
+        _ => 0,
+    };
+    let (totals, mut directories) = walk(root, cluster, resident_limit);
+    directories.sort_by_key(|d| std::cmp::Reverse(d.slack_bytes));
+    directories.truncate(MAX_REPORTED);
+
+    Ok(SlackReport {
+        root: root.to_string_lossy().into_owned(),
+        cluster_size: cluster,
+        file_count: totals.files,
+        slack_bytes: totals.slack[0],
+        slack_display: human_readable_size(totals.slack[0]),
+        directories,
+    })
+}
+
+// 返回目录树的统计及其中值得报告的目录。某个子目录已占父目录一半以上的浪费时只报告子目录，
+// 避免同一批小文件沿祖先目录重复出现
+fn walk(dir: &Path, cluster: u64, resident_limit: u64) -> (SlackTotals, Vec<SlackDirectory>) {
+    let entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().collect(),
+        Err(e) => {
+            eprintln!("无法读取目录 {}: {}", dir.display(), e);
+            return (SlackTotals::default(), Vec::new());
+        }
+    };
+
+    let children: Vec<(SlackTotals, Vec<SlackDirectory>, bool)> = entries
+        .par_iter()
+        .filter_map(|entry| {
+            // 不跟随符号链接
+            let metadata = fs::symlink_metadata(entry.path()).ok()?;
+            if metadata.is_dir() {
+                let (totals, reported) = walk(&entry.path(), cluster, resident_limit);
+                Some((totals, reported, true))
+            } else if metadata.is_file() {
+                let mut totals = SlackTotals::default();
+                totals.add_file(metadata.len(), cluster, resident_limit);
+                Some((totals, Vec::new(), false))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut totals = SlackTotals::default();
+    let mut reported = Vec::new();
+    let mut dominated = false;
+    let total_slack: u64 = children.iter().map(|(t, _, _)| t.slack[0]).sum();
+    for (child, child_reported, is_dir) in children {
+        if is_dir && child.qualifies() && child.slack[0] * 2 >= total_slack {
+            dominated = true;
+        }
+        totals = totals.merge(child);
+        reported.extend(child_reported);
+    }
+    if totals.qualifies() && !dominated {
+        reported.push(describe(dir, &totals, cluster));
+    }
+    (totals, reported)
+}
+
+fn describe(dir: &Path, totals: &SlackTotals, cluster: u64) -> SlackDirectory {
+    let slack = totals.slack[0];
+    let allocated = totals.logical + slack;
+    // 打包后只在归档文件末尾浪费不到一个簇
+    let archive_slack = slack_of(totals.logical.max(1), cluster);
+    let smaller_cluster_savings = CANDIDATE_CLUSTERS
+        .iter()
+        .zip(&totals.slack[1..])
+        .filter(|(&size, _)| size < cluster)
+        .map(|(&size, &candidate)| {
+            let saved = slack.saturating_sub(candidate);
+            ClusterSaving {
+                cluster_size: size,
+                saved_bytes: saved,
+                saved_display: human_readable_size(saved),
+            }
+        })
+        .collect();
+    SlackDirectory {
+        path: dir.to_string_lossy().into_owned(),
+        file_count: totals.files,
+        logical_bytes: totals.logical,
+        allocated_bytes: allocated,
+        slack_bytes: slack,
+        slack_display: human_readable_size(slack),
+        slack_percent: if allocated == 0 {
+            0.0
+        } else {
+            slack as f64 * 100.0 / allocated as f64
+        },
+        archive_saved_bytes: slack.saturating_sub(archive_slack),
+        smaller_cluster_savings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_directory_holding_tiny_files() {
+        let root = std::env::temp_dir().join(format!("disksight-slack-{}", std::process::id()));
+        let tiny = root.join("cache").join("tiny");
+        fs::create_dir_all(&tiny).unwrap();
+        fs::write(root.join("big.bin"), vec![0u8; 8192]).unwrap();
+        for i in 0..MIN_FILES {
+            fs::write(tiny.join(format!("{}.txt", i)), b"x").unwrap();
+        }
+
+        // 用 64KB 的簇放大浪费，使测试目录达到报告阈值
+        let (totals, reported) = walk(&root, 64 * 1024, 0);
+        assert_eq!(totals.files, MIN_FILES + 1);
+        assert_eq!(totals.logical, MIN_FILES + 8192);
+        assert_eq!(reported.len(), 1);
+        let dir = &reported[0];
+        assert_eq!(Path::new(&dir.path), tiny);
+        assert_eq!(dir.slack_bytes, MIN_FILES * (64 * 1024 - 1));
+        assert_eq!(
+            dir.smaller_cluster_savings.first().map(|s| s.saved_bytes),
+            Some(MIN_FILES * (64 * 1024 - 512))
+        );
+        assert!(dir.archive_saved_bytes > 0);
+
+        // MFT 驻留的小文件不产生浪费
+        let (resident, _) = walk(&root, 64 * 1024, NTFS_RESIDENT_LIMIT);
+        assert_eq!(resident.slack[0], 64 * 1024 - 8192);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,422 @@
+use crate::drives::{inode_count, mount_of, reserved_space, volume_space};
+use crate::platform;
+use crate::purgeable::purgeable_report;
+use crate::utils::human_readable_size;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// ext4 默认的 inode 大小
+const EXT_INODE_SIZE: u64 = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HiddenSpaceKind {
+    /// NTFS 的 MFT、ext 系列的 inode 表等文件系统元数据
+    FileSystemMetadata,
+    /// pagefile.sys、交换文件
+    PageFile,
+    /// hiberfil.sys、sleepimage
+    Hibernation,
+    /// Windows 卷影副本
+    ShadowCopies,
+    /// 仅 root 可用的保留块
+    ReservedBlocks,
+    /// 扫描时无权限读取的目录
+    Inaccessible,
+    /// 无法归因的差额
+    Unexplained,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HiddenSpace {
+    pub kind: HiddenSpaceKind,
+    pub bytes: u64,
+    pub bytes_display: String,
+    /// 来源说明，如文件路径或估算方式
+    pub detail: String,
+    /// 为估算值而非精确读取
+    pub estimated: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpaceReconciliation {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    /// 卷报告的已用空间（总容量减去可用空间）
+    pub used_bytes: u64,
+    /// 扫描到的文件实际占用（按分配大小，硬链接只计一次）
+    pub files_bytes: u64,
+    pub file_count: u64,
+    /// 无权限读取的目录数量
+    pub inaccessible_dirs: u64,
+    /// 已用空间中不属于普通文件的部分，按大小降序
+    pub categories: Vec<HiddenSpace>,
+    /// macOS 的可清除空间，计入已用空间，但 Finder 将其显示为可用
+    pub purgeable_bytes: Option<u64>,
+    pub used_display: String,
+    pub files_display: String,
+}
+
+#[derive(Default)]
+struct WalkTotals {
+    bytes: u64,
+    files: u64,
+    inaccessible_dirs: u64,
+}
+
+impl WalkTotals {
+    fn merge(mut self, other: WalkTotals) -> WalkTotals {
+        self.bytes += other.bytes;
+        self.files += other.files;
+        self.inaccessible_dirs += other.inaccessible_dirs;
+        self
+    }
+}
+
+// 对比卷的已用空间与扫描到的文件总大小，把差额归因到文件系统元数据、交换文件、休眠文件、
+// 卷影副本、保留块和无权限目录，剩余部分列为无法解释
+pub fn reconcile_volume(path: &Path) -> Result<SpaceReconciliation, String> {
+    let mount = mount_of(path).ok_or_else(|| "无法确定路径所在的卷".to_string())?;
+    let root = PathBuf::from(&mount.mount_point);
+    let (total, available) = volume_space(&root).ok_or_else(|| "无法读取卷容量".to_string())?;
+    let used = total.saturating_sub(available);
+
+    let known = system_files(&root);
+    let skip: HashSet<PathBuf> = known.iter().map(|(path, _)| path.clone()).collect();
+    let totals = walk_volume(&root, &skip);
+
+    let mut categories: Vec<HiddenSpace> = known.into_iter().map(|(_, space)| space).collect();
+    categories.extend(metadata_space(&root, &mount.file_system, &mount.device));
+    categories.extend(shadow_copy_space(&mount.mount_point));
+    if let Some(reserved) = reserved_space(&root).filter(|&r| r > 0) {
+        categories.push(hidden(
+            HiddenSpaceKind::ReservedBlocks,
+            reserved,
+            "保留给 root 的块".to_string(),
+            false,
+        ));
+    }
+    let categories = attribute_remainder(used, totals.bytes, categories, totals.inaccessible_dirs);
+
+    Ok(SpaceReconciliation {
+        mount_point: mount.mount_point,
+        file_system: mount.file_system,
+        total_bytes: total,
+        used_bytes: used,
+        files_bytes: totals.bytes,
+        file_count: totals.files,
+        inaccessible_dirs: totals.inaccessible_dirs,
+        categories,
+        purgeable_bytes: cfg!(target_os = "macos")
+            .then(|| purgeable_report(&root).ok()?.purgeable_bytes)
+            .flatten(),
+        used_display: human_readable_size(used),
+        files_display: human_readable_size(totals.bytes),
+    })
+}
+
+fn hidden(kind: HiddenSpaceKind, bytes: u64, detail: String, estimated: bool) -> HiddenSpace {
+    HiddenSpace {
+        kind,
+        bytes,
+        bytes_display: human_readable_size(bytes),
+        detail,
+        estimated,
+    }
+}
+
+// 已用空间减去文件和已知类别后剩余的部分：有无权限目录时归为无权限区域（是其大小的上限），
+// 否则列为无法解释。已知类别超出差额时（估算偏大或扫描期间有写入）不再追加
+fn attribute_remainder(
+    used: u64,
+    files: u64,
+    mut categories: Vec<HiddenSpace>,
+    inaccessible_dirs: u64,
+) -> Vec<HiddenSpace> {
+    let explained: u64 = categories.iter().map(|c| c.bytes).sum();
+    let remainder = used.saturating_sub(files).saturating_sub(explained);
+    if remainder > 0 {
+        categories.push(if inaccessible_dirs > 0 {
+            hidden(
+                HiddenSpaceKind::Inaccessible,
+                remainder,
+                format!("{} 个目录无权限读取，此为其占用的上限", inaccessible_dirs),
+                true,
+            )
+        } else {
+            hidden(
+                HiddenSpaceKind::Unexplained,
+                remainder,
+                "可能是扫描期间的写入、稀疏文件或文件系统快照".to_string(),
+                true,
+            )
+        });
+    }
+    categories.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+    categories
+}
+
+// 遍历整个卷，不跨越挂载点、不跟随符号链接，跳过已单独统计的系统文件
+fn walk_volume(root: &Path, skip: &HashSet<PathBuf>) -> WalkTotals {
+    let device = fs::symlink_metadata(root).ok().map(|m| device_id(&m));
+    let seen = Mutex::new(HashSet::new());
+    walk(root, device, skip, &seen)
+}
+
+fn walk(
+    dir: &Path,
+    device: Option<u64>,
+    skip: &HashSet<PathBuf>,
+    seen: &Mutex<HashSet<(u64, u64)>>,
+) -> WalkTotals {
+    let entries: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().collect(),
+        Err(e) => {
+            return WalkTotals {
+                inaccessible_dirs: u64::from(e.kind() == std::io::ErrorKind::PermissionDenied),
+                ..WalkTotals::default()
+            };
+        }
+    };
+
+    entries
+        .par_iter()
+        .map(|entry| {
+            let path = entry.path();
+            if skip.contains(&path) {
+                return WalkTotals::default();
+            }
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                return WalkTotals::default();
+            };
+            if metadata.is_dir() {
+                if device.is_some_and(|d| d != device_id(&metadata)) {
+                    return WalkTotals::default();
+                }
+                return walk(&path, device, skip, seen);
+            }
+            // 硬链接的多个路径只计一次
+            if let Some(key) = hard_link_key(&metadata) {
+                if !seen.lock().unwrap().insert(key) {
+                    return WalkTotals::default();
+                }
+            }
+            WalkTotals {
+                bytes: allocated_size(&metadata),
+                files: 1,
+                inaccessible_dirs: 0,
+            }
+        })
+        .reduce(WalkTotals::default, WalkTotals::merge)
+}
+
+#[cfg(unix)]
+fn device_id(metadata: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::dev(metadata)
+}
+
+#[cfg(not(unix))]
+fn device_id(_metadata: &fs::Metadata) -> u64 {
+    0
+}
+
+#[cfg(unix)]
+fn hard_link_key(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn hard_link_key(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// 文件实际分配的空间，稀疏文件和压缩文件可能小于文件长度
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::blocks(metadata) * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+// 卷上的交换文件和休眠文件，普通用户通常无法读取其元数据或不会意识到它们的存在
+fn system_files(root: &Path) -> Vec<(PathBuf, HiddenSpace)> {
+    let mut candidates: Vec<(PathBuf, HiddenSpaceKind)> = Vec::new();
+    if cfg!(windows) {
+        candidates.push((root.join("pagefile.sys"), HiddenSpaceKind::PageFile));
+        candidates.push((root.join("swapfile.sys"), HiddenSpaceKind::PageFile));
+        candidates.push((root.join("hiberfil.sys"), HiddenSpaceKind::Hibernation));
+    } else if cfg!(target_os = "macos") {
+        let vm = root.join("private/var/vm");
+        candidates.push((vm.join("sleepimage"), HiddenSpaceKind::Hibernation));
+        if let Ok(entries) = fs::read_dir(&vm) {
+            candidates.extend(
+                entries
+                    .flatten()
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("swapfile"))
+                    .map(|e| (e.path(), HiddenSpaceKind::PageFile)),
+            );
+        }
+    } else {
+        candidates.extend(
+            swap_files()
+                .into_iter()
+                .filter(|path| path.starts_with(root))
+                .map(|path| (path, HiddenSpaceKind::PageFile)),
+        );
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|(path, kind)| {
+            let metadata = fs::symlink_metadata(&path).ok()?;
+            let detail = path.to_string_lossy().into_owned();
+            let space = hidden(kind, allocated_size(&metadata), detail, false);
+            Some((path, space))
+        })
+        .collect()
+}
+
+// /proc/swaps 中类型为 file 的交换文件
+fn swap_files() -> Vec<PathBuf> {
+    fs::read_to_string("/proc/swaps")
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let path = parts.next()?;
+            (parts.next()? == "file").then(|| PathBuf::from(path.replace("\\040", " ")))
+        })
+        .collect()
+}
+
+// 文件系统元数据：NTFS 读取 MFT 实际大小，ext 系列按 inode 数量估算 inode 表
+fn metadata_space(root: &Path, file_system: &str, device: &str) -> Option<HiddenSpace> {
+    match file_system.to_ascii_lowercase().as_str() {
+        "ntfs" => {
+            let output = platform::command("fsutil")
+                .args(["fsinfo", "ntfsinfo", device])
+                .output()
+                .ok()?;
+            let bytes = parse_mft_size(&String::from_utf8_lossy(&output.stdout))?;
+            Some(hidden(
+                HiddenSpaceKind::FileSystemMetadata,
+                bytes,
+                "MFT".to_string(),
+                false,
+            ))
+        }
+        "ext2" | "ext3" | "ext4" => {
+            let inodes = inode_count(root)?;
+            Some(hidden(
+                HiddenSpaceKind::FileSystemMetadata,
+                inodes * EXT_INODE_SIZE,
+                format!("{} 个 inode 的 inode 表", inodes),
+                true,
+            ))
+        }
+        _ => None,
+    }
+}
+
+// 解析 fsutil fsinfo ntfsinfo 输出中的 "Mft Valid Data Length"
+pub(crate) fn parse_mft_size(output: &str) -> Option<u64> {
+    let line = output
+        .lines()
+        .find(|line| line.trim_start().starts_with("Mft Valid Data Length"))?;
+    let value = line.split(':').nth(1)?.trim();
+    // 新版本输出 "1.25 GB"，旧版本输出十六进制字节数
+    if let Some(hex) = value.strip_prefix("0x") {
+        return u64::from_str_radix(hex, 16).ok();
+    }
+    parse_size_with_unit(value)
+}
+
+// Windows 卷影副本占用的空间
+fn shadow_copy_space(mount_point: &str) -> Option<HiddenSpace> {
+    if !cfg!(windows) {
+        return None;
+    }
+    let volume = mount_point.trim_end_matches('\\');
+    let output = platform::command("vssadmin")
+        .args(["list", "shadowstorage", &format!("/for={}", volume)])
+        .output()
+        .ok()?;
+    let bytes = parse_shadow_storage(&String::from_utf8_lossy(&output.stdout))?;
+    Some(hidden(
+        HiddenSpaceKind::ShadowCopies,
+        bytes,
+        "系统还原点和卷影副本".to_string(),
+        false,
+    ))
+}
+
+// 解析 vssadmin 输出中的 "Used Shadow Copy Storage space: 1.23 GB (2%)"，多个存储区累加
+fn parse_shadow_storage(output: &str) -> Option<u64> {
+    let sizes: Vec<u64> = output
+        .lines()
+        .filter(|line| {
+            line.trim_start()
+                .starts_with("Used Shadow Copy Storage space")
+        })
+        .filter_map(|line| {
+            let value = line.split(':').nth(1)?;
+            parse_size_with_unit(value.split('(').next()?.trim())
+        })
+        .collect();
+    (!sizes.is_empty()).then(|| sizes.iter().sum())
+}
+
+// 解析 "1.23 GB" 形式的大小
+fn parse_size_with_unit(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let number: f64 = parts.next()?.replace(',', "").parse().ok()?;
+    let multiplier = match parts.next().unwrap_or("B").to_ascii_uppercase().as_str() {
+        "B" | "BYTES" => 1u64,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_difference_and_parses_windows_tools() {
+        let known = vec![hidden(HiddenSpaceKind::PageFile, 300, String::new(), false)];
+        let categories = attribute_remainder(1000, 500, known.clone(), 0);
+        assert_eq!(categories[0].kind, HiddenSpaceKind::PageFile);
+        assert_eq!(categories[1].kind, HiddenSpaceKind::Unexplained);
+        assert_eq!(categories[1].bytes, 200);
+        let categories = attribute_remainder(1000, 500, known.clone(), 3);
+        assert_eq!(categories[1].kind, HiddenSpaceKind::Inaccessible);
+        // 已知类别超出差额时不追加
+        assert_eq!(attribute_remainder(700, 500, known, 0).len(), 1);
+
+        assert_eq!(
+            parse_mft_size("Bytes Per Cluster :  4096\nMft Valid Data Length :  1.50 GB\n"),
+            Some(3 << 29)
+        );
+        assert_eq!(
+            parse_mft_size("Mft Valid Data Length :           0x0000000010000000"),
+            Some(0x1000_0000)
+        );
+        let vss = "Shadow Copy Storage association\n   Used Shadow Copy Storage space: 512 MB (1%)\n   Used Shadow Copy Storage space: 1.5 GB (2%)\n";
+        assert_eq!(parse_shadow_storage(vss), Some((1 << 29) + (3 << 29)));
+        assert_eq!(parse_shadow_storage("No items found"), None);
+    }
+}
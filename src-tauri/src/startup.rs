@@ -0,0 +1,129 @@
+// 协调启动画面与主窗口：前端和后端的初始化任务都完成后关闭启动画面并显示主窗口。
+// 重复完成同一任务不会重复切换；启动画面被提前关闭时直接显示主窗口，避免程序没有可见窗口
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const MAIN_WINDOW: &str = "main";
+pub const SPLASH_WINDOW: &str = "splashscreen";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupTask {
+    Frontend,
+    Backend,
+}
+
+// startup-error 事件的内容
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StartupError {
+    pub task: StartupTask,
+    pub message: String,
+}
+
+#[derive(Default)]
+struct StartupState {
+    frontend: bool,
+    backend: bool,
+    /// 主窗口已显示
+    shown: bool,
+    errors: Vec<StartupError>,
+}
+
+impl StartupState {
+    // 标记任务完成，返回是否需要在此时显示主窗口
+    fn complete(&mut self, task: StartupTask) -> bool {
+        match task {
+            StartupTask::Frontend => self.frontend = true,
+            StartupTask::Backend => self.backend = true,
+        }
+        self.take_show(self.frontend && self.backend)
+    }
+
+    fn take_show(&mut self, ready: bool) -> bool {
+        if ready && !self.shown {
+            self.shown = true;
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Default)]
+pub struct StartupCoordinator {
+    state: Mutex<StartupState>,
+}
+
+impl StartupCoordinator {
+    pub fn complete(&self, app: &AppHandle, task: StartupTask) -> Result<(), String> {
+        let show = self.state.lock().unwrap().complete(task);
+        if show {
+            self.show_main(app)?;
+        }
+        Ok(())
+    }
+
+    // 任务失败时记录错误并通知前端，仍视为完成，让用户能进入主窗口查看
+    pub fn fail(&self, app: &AppHandle, task: StartupTask, message: String) -> Result<(), String> {
+        eprintln!("启动任务 {:?} 失败: {}", task, message);
+        let error = StartupError { task, message };
+        self.state.lock().unwrap().errors.push(error.clone());
+        let _ = app.emit("startup-error", error);
+        self.complete(app, task)
+    }
+
+    // 主窗口打开较晚时用来获取已发生的启动错误
+    pub fn errors(&self) -> Vec<StartupError> {
+        self.state.lock().unwrap().errors.clone()
+    }
+
+    // 启动画面被用户提前关闭：初始化继续在后台进行，立即显示主窗口
+    pub fn splash_closed(&self, app: &AppHandle) {
+        let show = self.state.lock().unwrap().take_show(true);
+        if show {
+            if let Err(e) = self.show_main(app) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    fn show_main(&self, app: &AppHandle) -> Result<(), String> {
+        let shown = match app.get_webview_window(MAIN_WINDOW) {
+            Some(main) => main.show().map_err(|e| format!("无法显示主窗口: {}", e)),
+            None => Err("主窗口不存在".to_string()),
+        };
+        if shown.is_err() {
+            // 允许之后再次尝试
+            self.state.lock().unwrap().shown = false;
+        }
+        shown?;
+        // 启动画面可能已被关闭
+        if let Some(splash) = app.get_webview_window(SPLASH_WINDOW) {
+            if let Err(e) = splash.close() {
+                eprintln!("关闭启动画面失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_main_window_once() {
+        let mut state = StartupState::default();
+        assert!(!state.complete(StartupTask::Frontend));
+        // 重复完成同一任务不触发切换
+        assert!(!state.complete(StartupTask::Frontend));
+        assert!(state.complete(StartupTask::Backend));
+        assert!(!state.complete(StartupTask::Backend));
+
+        // 启动画面提前关闭后，任务完成时不再重复显示
+        let mut closed_early = StartupState::default();
+        assert!(closed_early.take_show(true));
+        assert!(!closed_early.complete(StartupTask::Frontend));
+        assert!(!closed_early.complete(StartupTask::Backend));
+    }
+}
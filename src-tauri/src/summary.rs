@@ -0,0 +1,253 @@
+// 把一次扫描结果整理成简短的 Markdown 摘要（总量、最大的目录、主要文件类型和值得注意的发现），
+// 可以直接粘贴到 Jira 或 GitHub 的磁盘空间工单中
+use crate::drives::volume_space;
+use crate::export::escape_markdown;
+use crate::grouping::{group_entries, GroupKey};
+use crate::models::FileEntry;
+use crate::scan_store::StoredScan;
+use crate::time_format::display_time;
+use crate::utils::{human_readable_size, sum_sizes};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const GB: u64 = 1024 * 1024 * 1024;
+// 所在卷可用空间低于该比例时提示
+const LOW_FREE_PERCENT: f64 = 10.0;
+// 单个文件超过该大小时列为大文件
+const LARGE_FILE_BYTES: u64 = GB;
+const STALE_FILE_BYTES: u64 = 100 * 1024 * 1024;
+const STALE_DAYS: u64 = 365;
+// 可以重新生成的构建产物与缓存目录
+const REBUILDABLE_DIRS: [&str; 10] = [
+    "node_modules",
+    "target",
+    "__pycache__",
+    ".gradle",
+    ".next",
+    ".nuxt",
+    ".parcel-cache",
+    ".turbo",
+    ".cache",
+    "DerivedData",
+];
+
+pub fn summarize_markdown(scan: &StoredScan, top_n: usize) -> String {
+    let root = scan.root.as_path();
+    let entries = &scan.result.entries;
+    let depth = |e: &FileEntry| relative_depth(root, e);
+    let total = sum_sizes(entries.iter().filter(|e| depth(e) == 0).map(|e| e.size_raw));
+    let files = entries.iter().filter(|e| e.file_type != 'd').count();
+    let dirs = entries.len() - files;
+
+    let mut out = format!("## 磁盘占用摘要：`{}`\n\n", root.display());
+    out.push_str(&format!(
+        "- **扫描大小**：{}（{} 个文件，{} 个目录）\n",
+        human_readable_size(total),
+        files,
+        dirs
+    ));
+    let space = volume_space(root);
+    if let Some((capacity, available)) = space {
+        out.push_str(&format!(
+            "- **所在卷**：已用 {} / {}，剩余 {}（{:.1}%）\n",
+            human_readable_size(capacity.saturating_sub(available)),
+            human_readable_size(capacity),
+            human_readable_size(available),
+            percent(available, capacity)
+        ));
+    }
+    out.push_str(&format!(
+        "- **扫描时间**：{}\n",
+        display_time(scan.finished_at)
+    ));
+    if let Some(note) = scan.result.completeness.describe() {
+        out.push_str(&format!("- **注意**：{}\n", note));
+    }
+
+    let mut top_dirs: Vec<&FileEntry> = entries.iter().filter(|e| e.file_type == 'd').collect();
+    top_dirs.sort_by_key(|e| std::cmp::Reverse(e.size_raw));
+    top_dirs.truncate(top_n);
+    if !top_dirs.is_empty() {
+        out.push_str(&format!(
+            "\n### 最大的 {} 个目录\n\n| 目录 | 大小 | 占比 |\n| --- | ---: | ---: |\n",
+            top_dirs.len()
+        ));
+        for e in top_dirs {
+            out.push_str(&format!(
+                "| `{}` | {} | {:.1}% |\n",
+                relative_path(root, e).replace('`', "'"),
+                e.size_display,
+                percent(e.size_raw, total)
+            ));
+        }
+    }
+
+    let types: Vec<_> = group_entries(root, entries, GroupKey::Extension)
+        .into_iter()
+        .filter(|g| g.key != "<目录>")
+        .take(top_n)
+        .collect();
+    if !types.is_empty() {
+        out.push_str(
+            "\n### 主要文件类型\n\n| 类型 | 文件数 | 大小 | 占比 |\n| --- | ---: | ---: | ---: |\n",
+        );
+        for g in types {
+            out.push_str(&format!(
+                "| {} | {} | {} | {:.1}% |\n",
+                escape_markdown(&g.key),
+                g.count,
+                g.size_display,
+                percent(g.size_raw, total)
+            ));
+        }
+    }
+
+    let findings = findings(root, entries, space);
+    if !findings.is_empty() {
+        out.push_str("\n### 值得注意\n\n");
+        for finding in findings {
+            out.push_str(&format!("- {}\n", finding));
+        }
+    }
+    out
+}
+
+fn findings(root: &Path, entries: &[FileEntry], space: Option<(u64, u64)>) -> Vec<String> {
+    let mut findings = Vec::new();
+    if let Some((capacity, available)) = space {
+        let free = percent(available, capacity);
+        if capacity > 0 && free < LOW_FREE_PERCENT {
+            findings.push(format!("所在卷可用空间仅剩 {:.1}%", free));
+        }
+    }
+
+    // 只统计最外层的构建产物目录，嵌套的已包含在内
+    let rebuildable: Vec<&FileEntry> = entries
+        .iter()
+        .filter(|e| e.file_type == 'd' && REBUILDABLE_DIRS.contains(&e.name.as_str()))
+        .collect();
+    let outermost: Vec<&&FileEntry> = rebuildable
+        .iter()
+        .filter(|e| {
+            let path = e.path();
+            !rebuildable
+                .iter()
+                .any(|other| !other.has_path(&path) && Path::new(&path).starts_with(other.path()))
+        })
+        .collect();
+    if !outermost.is_empty() {
+        let bytes = sum_sizes(outermost.iter().map(|e| e.size_raw));
+        findings.push(format!(
+            "{} 个可重新生成的构建产物或缓存目录（node_modules、target 等），共 {}",
+            outermost.len(),
+            human_readable_size(bytes)
+        ));
+    }
+
+    let files = || entries.iter().filter(|e| e.file_type != 'd');
+    let large: Vec<&FileEntry> = files().filter(|e| e.size_raw >= LARGE_FILE_BYTES).collect();
+    if !large.is_empty() {
+        let largest = large.iter().max_by_key(|e| e.size_raw).unwrap();
+        findings.push(format!(
+            "{} 个超过 {} 的文件，共 {}；最大的是 `{}`（{}）",
+            large.len(),
+            human_readable_size(LARGE_FILE_BYTES),
+            human_readable_size(sum_sizes(large.iter().map(|e| e.size_raw))),
+            relative_path(root, largest).replace('`', "'"),
+            largest.size_display
+        ));
+    }
+
+    let cutoff = SystemTime::now() - Duration::from_secs(STALE_DAYS * 24 * 60 * 60);
+    let stale: Vec<&FileEntry> = files()
+        .filter(|e| e.size_raw >= STALE_FILE_BYTES && e.modified_time.is_some_and(|t| t < cutoff))
+        .collect();
+    if !stale.is_empty() {
+        findings.push(format!(
+            "{} 个超过 {} 且一年以上未修改的文件，共 {}",
+            stale.len(),
+            human_readable_size(STALE_FILE_BYTES),
+            human_readable_size(sum_sizes(stale.iter().map(|e| e.size_raw)))
+        ));
+    }
+    findings
+}
+
+fn relative_path(root: &Path, entry: &FileEntry) -> String {
+    let path = entry.path();
+    match Path::new(&path).strip_prefix(root) {
+        Ok(relative) => relative.to_string_lossy().into_owned(),
+        Err(_) => path,
+    }
+}
+
+// 相对扫描根目录的深度，根目录的直接子项为 0
+fn relative_depth(root: &Path, entry: &FileEntry) -> usize {
+    let path = entry.path();
+    Path::new(&path)
+        .strip_prefix(root)
+        .map(|r| r.components().count().saturating_sub(1))
+        .unwrap_or(0)
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DirectoryResult;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: path.to_string().into(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            severity: None,
+        }
+    }
+
+    #[test]
+    fn summary_lists_directories_types_and_findings() {
+        let scan = StoredScan::new(
+            PathBuf::from("/srv/app"),
+            DirectoryResult {
+                scan_id: None,
+                entries: vec![
+                    entry("/srv/app/web", 'd', 3 * GB),
+                    entry("/srv/app/web/node_modules", 'd', GB),
+                    entry("/srv/app/web/node_modules/a/node_modules", 'd', 1024),
+                    entry("/srv/app/web/dump.sql", '-', 2 * GB),
+                    entry("/srv/app/notes.md", '-', GB),
+                ],
+                query_time: 0.0,
+                telemetry: None,
+                scan_plan: None,
+                completeness: Default::default(),
+                skipped: Default::default(),
+            },
+            SystemTime::now(),
+        );
+        let md = summarize_markdown(&scan, 5);
+        assert!(md.contains("**扫描大小**：4.0GB"));
+        assert!(md.contains("| `web` | 3.0GB | 75.0% |"));
+        assert!(md.contains("| sql | 1 | 2.0GB | 50.0% |"));
+        assert!(md.contains("1 个可重新生成的构建产物或缓存目录"));
+        assert!(md.contains("2 个超过 1.0GB 的文件，共 3.0GB；最大的是 `web/dump.sql`"));
+    }
+}
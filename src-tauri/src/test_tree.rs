@@ -0,0 +1,254 @@
+// 开发用：按规格生成可复现的合成目录树（大量目录与文件、大小分布、符号链接、深层嵌套），
+// 供基准测试与集成测试使用。文件默认为稀疏文件，表观大小符合分布但几乎不占磁盘空间
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const GENERATE_FLAG: &str = "--generate-test-tree";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeDistribution {
+    /// 所有文件都是 max_size
+    Fixed,
+    Uniform,
+    /// 按数量级均匀分布，小文件多、大文件少，接近真实目录
+    LogUniform,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreeSpec {
+    /// 目录数，不含根目录
+    pub dirs: usize,
+    pub files: usize,
+    /// 最深一条目录链的层数，计入 dirs
+    pub depth: usize,
+    pub min_size: u64,
+    pub max_size: u64,
+    pub distribution: SizeDistribution,
+    /// 指向树中随机目录的符号链接数，可能指向自身的上级而形成环
+    pub symlinks: usize,
+    /// 相同的种子生成相同的树
+    pub seed: u64,
+    /// 为 false 时写入实际数据
+    pub sparse: bool,
+}
+
+impl Default for TreeSpec {
+    fn default() -> Self {
+        TreeSpec {
+            dirs: 100,
+            files: 1000,
+            depth: 8,
+            min_size: 0,
+            max_size: 1024 * 1024,
+            distribution: SizeDistribution::LogUniform,
+            symlinks: 0,
+            seed: 1,
+            sparse: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TreeSummary {
+    pub dirs: usize,
+    pub files: usize,
+    pub symlinks: usize,
+    /// 所有文件的表观大小之和
+    pub total_bytes: u64,
+}
+
+// splitmix64，不依赖随机数库，各平台结果一致
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // [0, n)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn file_size(spec: &TreeSpec, rng: &mut Rng) -> u64 {
+    let (min, max) = (spec.min_size.min(spec.max_size), spec.max_size);
+    match spec.distribution {
+        SizeDistribution::Fixed => max,
+        SizeDistribution::Uniform => min + (rng.unit() * (max - min) as f64) as u64,
+        SizeDistribution::LogUniform => {
+            let (low, high) = ((min.max(1) as f64).ln(), (max.max(1) as f64).ln());
+            ((low + rng.unit() * (high - low)).exp() as u64).clamp(min, max)
+        }
+    }
+}
+
+// 各目录相对根目录的路径：先是 depth 层的链，其余挂在随机选中的已有目录下
+fn plan_dirs(spec: &TreeSpec, rng: &mut Rng) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::new()];
+    for i in 0..spec.dirs {
+        let parent = if i < spec.depth {
+            dirs.len() - 1
+        } else {
+            rng.below(dirs.len())
+        };
+        let dir = dirs[parent].join(format!("d{:06}", i));
+        dirs.push(dir);
+    }
+    dirs
+}
+
+// 在 root 下生成目录树；root 必须不存在或为空目录
+pub fn generate_test_tree(root: &Path, spec: &TreeSpec) -> Result<TreeSummary, String> {
+    if fs::read_dir(root).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!("{} 不是空目录", root.display()));
+    }
+    let mut rng = Rng(spec.seed);
+    let dirs = plan_dirs(spec, &mut rng);
+    let mut summary = TreeSummary {
+        dirs: spec.dirs,
+        ..Default::default()
+    };
+    for dir in &dirs {
+        fs::create_dir_all(root.join(dir)).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+
+    for i in 0..spec.files {
+        let path = root
+            .join(&dirs[rng.below(dirs.len())])
+            .join(format!("f{:07}.bin", i));
+        let size = file_size(spec, &mut rng);
+        let written = if spec.sparse {
+            fs::File::create(&path).and_then(|file| file.set_len(size))
+        } else {
+            fs::write(&path, vec![(i % 251) as u8; size as usize])
+        };
+        written.map_err(|e| format!("创建文件 {} 失败: {}", path.display(), e))?;
+        summary.files += 1;
+        summary.total_bytes = summary.total_bytes.saturating_add(size);
+    }
+
+    for i in 0..spec.symlinks {
+        let link = root
+            .join(&dirs[rng.below(dirs.len())])
+            .join(format!("l{:05}", i));
+        let target = root.join(&dirs[rng.below(dirs.len())]);
+        #[cfg(unix)]
+        let created = std::os::unix::fs::symlink(&target, &link);
+        #[cfg(windows)]
+        let created = std::os::windows::fs::symlink_dir(&target, &link);
+        created.map_err(|e| format!("创建符号链接 {} 失败: {}", link.display(), e))?;
+        summary.symlinks += 1;
+    }
+    Ok(summary)
+}
+
+fn flag_value<T: std::str::FromStr>(args: &[String], flag: &str) -> Option<T> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    args.get(i + 1)?.parse().ok()
+}
+
+// 命令行入口：--generate-test-tree <目录> [--dirs N] [--files N] [--depth N] [--symlinks N]
+// [--min-size 字节] [--max-size 字节] [--distribution fixed|uniform|log_uniform] [--seed N] [--dense]
+pub fn run_generate() {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(root) = flag_value::<PathBuf>(&args, GENERATE_FLAG) else {
+        eprintln!("用法: {} <目录> [选项]", GENERATE_FLAG);
+        std::process::exit(2);
+    };
+    let defaults = TreeSpec::default();
+    let distribution = match args
+        .iter()
+        .position(|arg| arg == "--distribution")
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+    {
+        Some("fixed") => SizeDistribution::Fixed,
+        Some("uniform") => SizeDistribution::Uniform,
+        _ => defaults.distribution,
+    };
+    let spec = TreeSpec {
+        dirs: flag_value(&args, "--dirs").unwrap_or(defaults.dirs),
+        files: flag_value(&args, "--files").unwrap_or(defaults.files),
+        depth: flag_value(&args, "--depth").unwrap_or(defaults.depth),
+        min_size: flag_value(&args, "--min-size").unwrap_or(defaults.min_size),
+        max_size: flag_value(&args, "--max-size").unwrap_or(defaults.max_size),
+        distribution,
+        symlinks: flag_value(&args, "--symlinks").unwrap_or(defaults.symlinks),
+        seed: flag_value(&args, "--seed").unwrap_or(defaults.seed),
+        sparse: !args.iter().any(|arg| arg == "--dense"),
+    };
+    match generate_test_tree(&root, &spec) {
+        Ok(summary) => println!(
+            "已生成 {} 个目录、{} 个文件、{} 个符号链接，共 {} 字节",
+            summary.dirs, summary.files, summary.symlinks, summary.total_bytes
+        ),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(root: &Path) -> Vec<(String, u64)> {
+        let mut entries = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).unwrap().flatten() {
+                let path = entry.path();
+                let metadata = fs::symlink_metadata(&path).unwrap();
+                if metadata.is_dir() {
+                    pending.push(path.clone());
+                }
+                let relative = path.strip_prefix(root).unwrap().to_string_lossy();
+                entries.push((relative.into_owned(), metadata.len()));
+            }
+        }
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_tree() {
+        let base = std::env::temp_dir().join(format!("disksight-tree-{}", std::process::id()));
+        let spec = TreeSpec {
+            dirs: 20,
+            files: 50,
+            depth: 6,
+            min_size: 10,
+            max_size: 100_000,
+            symlinks: if cfg!(unix) { 3 } else { 0 },
+            ..Default::default()
+        };
+        let first = generate_test_tree(&base.join("a"), &spec).unwrap();
+        generate_test_tree(&base.join("b"), &spec).unwrap();
+        assert_eq!(listing(&base.join("a")), listing(&base.join("b")));
+        assert_eq!((first.dirs, first.files), (20, 50));
+        assert!(base
+            .join("a/d000000/d000001/d000002/d000003/d000004/d000005")
+            .is_dir());
+        let sizes: u64 = listing(&base.join("a"))
+            .iter()
+            .filter(|(name, _)| name.ends_with(".bin"))
+            .map(|(_, size)| *size)
+            .sum();
+        assert_eq!(sizes, first.total_bytes);
+        assert!(generate_test_tree(&base.join("a"), &spec).is_err());
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const MONTH: u64 = 30 * DAY;
+const YEAR: u64 = 365 * DAY;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeStyle {
+    /// 如 "3 天前"
+    Relative,
+    /// 按语言环境显示日期和时间
+    Absolute,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeFormat {
+    pub style: TimeStyle,
+    /// 语言环境，如 zh-CN、en-US、de-DE；未识别的语言使用 ISO 8601 格式
+    pub locale: String,
+    /// 本地时区相对 UTC 的偏移（分钟），由前端按系统时区写入
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat {
+            style: TimeStyle::Absolute,
+            locale: "zh-CN".to_string(),
+            utc_offset_minutes: 0,
+        }
+    }
+}
+
+// 当前生效的时间显示格式，随配置加载和修改更新；配置加载前为 None，使用默认格式
+static TIME_FORMAT: RwLock<Option<TimeFormat>> = RwLock::new(None);
+
+// 配置加载或修改后调用
+pub fn apply_time_format(format: TimeFormat) {
+    *TIME_FORMAT.write().unwrap() = Some(format);
+}
+
+// 按当前配置的格式显示时间
+pub fn display_time(time: SystemTime) -> String {
+    let now = SystemTime::now();
+    match &*TIME_FORMAT.read().unwrap() {
+        Some(format) => format_time(time, now, format),
+        None => format_time(time, now, &TimeFormat::default()),
+    }
+}
+
+// 按配置的时区取时间所在的年月，如 2024-03，用于按月分组
+pub fn year_month(time: SystemTime) -> String {
+    let (year, month, _) = local_date(time);
+    format!("{}-{:02}", year, month)
+}
+
+// 按配置的时区取时间所在的日期，如 2024-03-09，按字符串比较即按时间先后
+pub fn date(time: SystemTime) -> String {
+    let (year, month, day) = local_date(time);
+    format!("{}-{:02}-{:02}", year, month, day)
+}
+
+// date 的逆运算：按配置的时区取该日期 0 点的时间，格式不正确时为 None
+pub fn parse_date(value: &str) -> Option<SystemTime> {
+    let mut parts = value.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let local = days_from_civil(year, month, day) * DAY as i64;
+    let secs = local - utc_offset_minutes() as i64 * 60;
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + std::time::Duration::from_secs(secs))
+}
+
+fn utc_offset_minutes() -> i32 {
+    match &*TIME_FORMAT.read().unwrap() {
+        Some(format) => format.utc_offset_minutes,
+        None => TimeFormat::default().utc_offset_minutes,
+    }
+}
+
+fn local_date(time: SystemTime) -> (i64, u32, u32) {
+    let offset = utc_offset_minutes();
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let local = secs + offset as i64 * 60;
+    civil_from_days(local.div_euclid(DAY as i64))
+}
+
+pub fn format_time(time: SystemTime, now: SystemTime, format: &TimeFormat) -> String {
+    match format.style {
+        TimeStyle::Relative => relative(time, now, &format.locale),
+        TimeStyle::Absolute => absolute(time, format),
+    }
+}
+
+fn is_chinese(locale: &str) -> bool {
+    locale.starts_with("zh")
+}
+
+fn relative(time: SystemTime, now: SystemTime, locale: &str) -> String {
+    let (secs, future) = match now.duration_since(time) {
+        Ok(age) => (age.as_secs(), false),
+        Err(e) => (e.duration().as_secs(), true),
+    };
+    let zh = is_chinese(locale);
+    if secs < MINUTE {
+        return if zh { "刚刚" } else { "just now" }.to_string();
+    }
+    let (count, unit) = [
+        (YEAR, ("年", "year")),
+        (MONTH, ("个月", "month")),
+        (DAY, ("天", "day")),
+        (HOUR, ("小时", "hour")),
+        (MINUTE, ("分钟", "minute")),
+    ]
+    .into_iter()
+    .find(|(span, _)| secs >= *span)
+    .map(|(span, unit)| (secs / span, unit))
+    .unwrap();
+
+    if zh {
+        format!("{} {}{}", count, unit.0, if future { "后" } else { "前" })
+    } else {
+        let plural = if count == 1 { "" } else { "s" };
+        if future {
+            format!("in {} {}{}", count, unit.1, plural)
+        } else {
+            format!("{} {}{} ago", count, unit.1, plural)
+        }
+    }
+}
+
+fn absolute(time: SystemTime, format: &TimeFormat) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let local = secs + format.utc_offset_minutes as i64 * 60;
+    let (year, month, day) = civil_from_days(local.div_euclid(DAY as i64));
+    let seconds_of_day = local.rem_euclid(DAY as i64);
+    let (hour, minute) = (seconds_of_day / 3600, seconds_of_day % 3600 / 60);
+
+    let language = format.locale.split(['-', '_']).next().unwrap_or_default();
+    match (language, format.locale.as_str()) {
+        ("zh", _) => format!("{}年{}月{}日 {:02}:{:02}", year, month, day, hour, minute),
+        ("ja", _) => format!("{}/{:02}/{:02} {:02}:{:02}", year, month, day, hour, minute),
+        (_, "en-US") => format!(
+            "{} {}, {} {}:{:02} {}",
+            MONTH_NAMES[month as usize - 1],
+            day,
+            year,
+            (hour + 11) % 12 + 1,
+            minute,
+            if hour < 12 { "AM" } else { "PM" }
+        ),
+        ("en", _) => format!(
+            "{} {} {}, {:02}:{:02}",
+            day,
+            MONTH_NAMES[month as usize - 1],
+            year,
+            hour,
+            minute
+        ),
+        ("de" | "ru" | "pl" | "cs" | "fi" | "nb", _) => {
+            format!("{:02}.{:02}.{} {:02}:{:02}", day, month, year, hour, minute)
+        }
+        ("fr" | "es" | "it" | "pt", _) => {
+            format!("{:02}/{:02}/{} {:02}:{:02}", day, month, year, hour, minute)
+        }
+        _ => format!("{}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute),
+    }
+}
+
+// 由 Unix 纪元起的天数换算公历日期（Howard Hinnant 的 civil_from_days 算法）
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// civil_from_days 的逆运算
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn format(style: TimeStyle, locale: &str, utc_offset_minutes: i32) -> TimeFormat {
+        TimeFormat {
+            style,
+            locale: locale.to_string(),
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn relative_times() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let zh = format(TimeStyle::Relative, "zh-CN", 0);
+        let en = format(TimeStyle::Relative, "en-US", 0);
+        assert_eq!(format_time(now - Duration::from_secs(30), now, &zh), "刚刚");
+        assert_eq!(
+            format_time(now - Duration::from_secs(3 * DAY), now, &zh),
+            "3 天前"
+        );
+        assert_eq!(
+            format_time(now - Duration::from_secs(3 * DAY), now, &en),
+            "3 days ago"
+        );
+        assert_eq!(
+            format_time(now - Duration::from_secs(HOUR), now, &en),
+            "1 hour ago"
+        );
+        assert_eq!(
+            format_time(now - Duration::from_secs(400 * DAY), now, &en),
+            "1 year ago"
+        );
+        assert_eq!(
+            format_time(now + Duration::from_secs(2 * HOUR), now, &en),
+            "in 2 hours"
+        );
+    }
+
+    #[test]
+    fn absolute_times_follow_locale_and_offset() {
+        // 2024-03-05 14:30:00 UTC
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_649_000);
+        let now = SystemTime::now();
+        let at = |locale: &str, offset: i32| {
+            format_time(time, now, &format(TimeStyle::Absolute, locale, offset))
+        };
+        assert_eq!(at("zh-CN", 480), "2024年3月5日 22:30");
+        assert_eq!(at("en-US", 0), "Mar 5, 2024 2:30 PM");
+        assert_eq!(at("en-GB", 0), "5 Mar 2024, 14:30");
+        assert_eq!(at("de-DE", -600), "05.03.2024 04:30");
+        assert_eq!(at("", 0), "2024-03-05 14:30");
+        assert_eq!(
+            format_time(
+                UNIX_EPOCH - Duration::from_secs(DAY),
+                now,
+                &format(TimeStyle::Absolute, "", 0)
+            ),
+            "1969-12-31 00:00"
+        );
+    }
+}
@@ -1,23 +1,153 @@
+use crate::dir_listing::calculate_dir_size;
+use crate::models::EntryPath;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeUnits {
+    /// 按 1024 换算，单位写作 KB/MB（传统显示方式）
+    Jedec,
+    /// 按 1024 换算，单位写作 KiB/MiB
+    Binary,
+    /// 按 1000 换算，单位写作 KB/MB
+    Decimal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitScale {
+    Byte,
+    Kilo,
+    Mega,
+    Giga,
+    Tera,
+    Peta,
+    Exa,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeFormat {
+    pub units: SizeUnits,
+    /// 固定使用某一级单位，为 None 时自动选择
+    pub fixed_unit: Option<UnitScale>,
+    /// 小数点，部分语言环境使用逗号
+    pub decimal_separator: char,
+    /// 原始字节数的千位分隔符，为 None 时不分组
+    pub thousands_separator: Option<char>,
+    /// 单位写成完整的英文单词（如 3.2 gigabytes），便于屏幕阅读器朗读，界面标签与导出都使用
+    pub spelled_out: bool,
+}
+
+impl SizeFormat {
+    pub const DEFAULT: SizeFormat = SizeFormat {
+        units: SizeUnits::Jedec,
+        fixed_unit: None,
+        decimal_separator: '.',
+        thousands_separator: None,
+        spelled_out: false,
+    };
+}
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        SizeFormat::DEFAULT
+    }
+}
+
+// 当前生效的大小显示格式，随配置加载和修改更新
+static SIZE_FORMAT: RwLock<SizeFormat> = RwLock::new(SizeFormat::DEFAULT);
+
+// 配置加载或修改后调用
+pub fn apply_size_format(format: SizeFormat) {
+    *SIZE_FORMAT.write().unwrap() = format;
+}
+
+// 按当前配置的格式显示大小
 pub fn human_readable_size(bytes: u64) -> String {
-    // 定义单位数组
-    let units = ["B", "KB", "MB", "GB", "TB"];
+    format_size(bytes, &SIZE_FORMAT.read().unwrap())
+}
+
+pub fn format_size(bytes: u64, format: &SizeFormat) -> String {
+    let (base, units) = match format.units {
+        SizeUnits::Jedec => (1024.0, ["B", "KB", "MB", "GB", "TB", "PB", "EB"]),
+        SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+        SizeUnits::Decimal => (1000.0, ["B", "KB", "MB", "GB", "TB", "PB", "EB"]),
+    };
+    // 添加目录大小处理
+    if bytes == 0 && format.fixed_unit.is_none() && !format.spelled_out {
+        return String::from("0B");
+    }
+
     // 将字节数转换为浮点数
     let mut size = bytes as f64;
     // 初始化单位索引
     let mut unit = 0;
+    match format.fixed_unit {
+        Some(scale) => {
+            unit = scale as usize;
+            size /= f64::powi(base, unit as i32);
+        }
+        None => {
+            while size >= base && unit < units.len() - 1 {
+                size /= base;
+                unit += 1;
+            }
+        }
+    }
 
-    while size >= 1024.0 && unit < units.len() - 1 {
-        size /= 1024.0;
-        unit += 1;
+    if format.spelled_out {
+        return spell_out(size, unit, format);
     }
+    let number = format!("{:.1}", size);
+    let number = match format.decimal_separator {
+        '.' => number,
+        separator => number.replace('.', &separator.to_string()),
+    };
+    format!("{}{}", number, units[unit])
+}
 
-    // 添加目录大小处理
-    if bytes == 0 {
-        return String::from("0B");
+// 完整写出单位，字节数不带小数，如 "1 byte"、"3.2 gigabytes"
+fn spell_out(size: f64, unit: usize, format: &SizeFormat) -> String {
+    let names = match format.units {
+        SizeUnits::Binary => [
+            "byte", "kibibyte", "mebibyte", "gibibyte", "tebibyte", "pebibyte", "exbibyte",
+        ],
+        SizeUnits::Jedec | SizeUnits::Decimal => [
+            "byte", "kilobyte", "megabyte", "gigabyte", "terabyte", "petabyte", "exabyte",
+        ],
+    };
+    let number = if unit == 0 {
+        format!("{:.0}", size)
+    } else {
+        format!("{:.1}", size).replace('.', &format.decimal_separator.to_string())
+    };
+    let plural = if number == "1" { "" } else { "s" };
+    format!("{} {}{}", number, names[unit], plural)
+}
+
+// 原始字节数按千位分组，如 1,234,567
+pub fn group_digits(bytes: u64, separator: Option<char>) -> String {
+    let digits = bytes.to_string();
+    let Some(separator) = separator else {
+        return digits;
+    };
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(c);
     }
+    grouped
+}
 
-    format!("{:.1}{}", size, units[unit])
+// 累加大小，超出 u64 范围时饱和而不是回绕；稀疏文件的表观大小可以远超磁盘容量
+pub fn sum_sizes(sizes: impl IntoIterator<Item = u64>) -> u64 {
+    sizes.into_iter().fold(0u64, u64::saturating_add)
 }
 
 // 引入 ProgressBar 类型，假设它来自 indicatif 库
@@ -38,3 +168,137 @@ pub fn progress_bar_init(
     pb.set_style(style.progress_chars("#>-"));
     Ok(pb)
 }
+
+// 获取当前用户主目录（Windows 下为 USERPROFILE）
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+// 静默计算目录大小（不显示进度条），供各分析器复用
+pub fn dir_size(path: &Path) -> u64 {
+    calculate_dir_size(path, false, &ProgressBar::hidden(), true).0
+}
+
+// 规范化路径并去掉 Windows 的 \\?\ 前缀
+pub fn canonical_string(path: &Path) -> Option<String> {
+    let canonical = path.canonicalize().ok()?;
+    let path_str = canonical.to_string_lossy();
+    Some(
+        path_str
+            .strip_prefix(r"\\?\")
+            .unwrap_or(&path_str)
+            .to_string(),
+    )
+}
+
+// 目录子项的规范化路径。canonical_parent 为 canonical_string 得到的父目录，每个目录只需解析一次：
+// 普通子项只引用共享的父目录，拼接名称后与逐个 canonicalize 的结果相同；
+// 只有符号链接需要再解析到目标位置。在网络共享上可省去每个条目一次完整的路径解析
+pub fn child_location(canonical_parent: Option<&Arc<str>>, child: &Path) -> EntryPath {
+    let is_symlink = std::fs::symlink_metadata(child)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(true);
+    match canonical_parent {
+        Some(parent) if !is_symlink && child.file_name().is_some() => {
+            EntryPath::Child(parent.clone())
+        }
+        _ => canonical_string(child)
+            .unwrap_or_else(|| child.to_string_lossy().into_owned())
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn child_location_matches_canonicalize() {
+        let dir = std::env::temp_dir().join(format!("disksight-canonical-{}", std::process::id()));
+        fs::create_dir_all(dir.join("real")).unwrap();
+        fs::write(dir.join("real/file.txt"), b"x").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+
+        for name in ["real", "real/file.txt", "link"] {
+            let child = dir.join(name);
+            if !child.exists() {
+                continue;
+            }
+            let parent: Arc<str> = canonical_string(child.parent().unwrap()).unwrap().into();
+            let name = child.file_name().unwrap().to_string_lossy();
+            assert_eq!(
+                Some(child_location(Some(&parent), &child).resolve(&name)),
+                canonical_string(&child)
+            );
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sizes_saturate_and_format_beyond_terabytes() {
+        let pb = 1u64 << 50;
+        assert_eq!(sum_sizes([pb; 1024]), 1 << 60);
+        assert_eq!(sum_sizes([u64::MAX, 1]), u64::MAX);
+        assert_eq!(format_size(pb, &SizeFormat::DEFAULT), "1.0PB");
+        assert_eq!(format_size(u64::MAX, &SizeFormat::DEFAULT), "16.0EB");
+    }
+
+    #[test]
+    fn formats_sizes_per_unit_mode() {
+        let format = |units, fixed_unit, decimal_separator| SizeFormat {
+            units,
+            fixed_unit,
+            decimal_separator,
+            thousands_separator: None,
+            spelled_out: false,
+        };
+        let bytes = 1_500_000;
+        assert_eq!(
+            format_size(bytes, &format(SizeUnits::Jedec, None, '.')),
+            "1.4MB"
+        );
+        assert_eq!(
+            format_size(bytes, &format(SizeUnits::Binary, None, '.')),
+            "1.4MiB"
+        );
+        assert_eq!(
+            format_size(bytes, &format(SizeUnits::Decimal, None, ',')),
+            "1,5MB"
+        );
+        assert_eq!(
+            format_size(
+                bytes,
+                &format(SizeUnits::Decimal, Some(UnitScale::Kilo), '.')
+            ),
+            "1500.0KB"
+        );
+        assert_eq!(
+            format_size(0, &format(SizeUnits::Binary, Some(UnitScale::Giga), '.')),
+            "0.0GiB"
+        );
+        let spelled = |units| SizeFormat {
+            units,
+            spelled_out: true,
+            ..SizeFormat::DEFAULT
+        };
+        assert_eq!(
+            format_size(3_435_973_837, &spelled(SizeUnits::Jedec)),
+            "3.2 gigabytes"
+        );
+        assert_eq!(
+            format_size(bytes, &spelled(SizeUnits::Binary)),
+            "1.4 mebibytes"
+        );
+        assert_eq!(format_size(1, &spelled(SizeUnits::Decimal)), "1 byte");
+        assert_eq!(format_size(0, &spelled(SizeUnits::Jedec)), "0 bytes");
+        assert_eq!(group_digits(1_234_567, Some(',')), "1,234,567");
+        assert_eq!(group_digits(123, Some(' ')), "123");
+        assert_eq!(group_digits(1_234_567, None), "1234567");
+    }
+}
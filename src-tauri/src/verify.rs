@@ -0,0 +1,199 @@
+use crate::scan_store::StoredScan;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryState {
+    Unchanged,
+    /// 文件大小变了
+    Resized,
+    /// 扫描之后被修改过（目录表示其直接子项有增删）
+    Modified,
+    /// 文件变成目录或目录变成文件
+    TypeChanged,
+    Disappeared,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntryCheck {
+    pub path: String,
+    pub state: EntryState,
+    pub scanned_size: u64,
+    /// 当前文件大小，目录和已消失的条目为 None
+    pub current_size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub scan_id: u64,
+    pub scanned_at: SystemTime,
+    pub checked: usize,
+    pub unchanged: usize,
+    /// 有变化的条目，未变化的不列出
+    pub changes: Vec<EntryCheck>,
+}
+
+// 只重新读取扫描结果中各条目的元数据（不重新遍历目录），
+// 检查结果是否仍然准确
+pub fn verify_result(scan_id: u64, scan: &StoredScan) -> VerifyReport {
+    let checks: Vec<EntryCheck> = scan
+        .result
+        .entries
+        .par_iter()
+        .map(|entry| {
+            let (state, current_size) = match fs::symlink_metadata(entry.path()) {
+                Err(_) => (EntryState::Disappeared, None),
+                Ok(metadata) => {
+                    let was_dir = entry.file_type == 'd';
+                    let modified_after = metadata
+                        .modified()
+                        .is_ok_and(|modified| modified > scan.finished_at);
+                    if metadata.is_dir() != was_dir {
+                        (EntryState::TypeChanged, None)
+                    } else if was_dir {
+                        let state = if modified_after {
+                            EntryState::Modified
+                        } else {
+                            EntryState::Unchanged
+                        };
+                        (state, None)
+                    } else if metadata.len() != entry.size_raw {
+                        (EntryState::Resized, Some(metadata.len()))
+                    } else if modified_after {
+                        (EntryState::Modified, Some(metadata.len()))
+                    } else {
+                        (EntryState::Unchanged, Some(metadata.len()))
+                    }
+                }
+            };
+            EntryCheck {
+                path: entry.path(),
+                state,
+                scanned_size: entry.size_raw,
+                current_size,
+            }
+        })
+        .collect();
+
+    let checked = checks.len();
+    let changes: Vec<EntryCheck> = checks
+        .into_iter()
+        .filter(|check| check.state != EntryState::Unchanged)
+        .collect();
+    VerifyReport {
+        scan_id,
+        scanned_at: scan.finished_at,
+        checked,
+        unchanged: checked - changes.len(),
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DirectoryResult, FileEntry};
+    use crate::utils::human_readable_size;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    fn entry(path: &Path, file_type: char, size_raw: u64) -> FileEntry {
+        FileEntry {
+            file_type,
+            permissions: String::new(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            created_time: SystemTime::UNIX_EPOCH,
+            modified_time: None,
+            location: path.to_string_lossy().into_owned().into(),
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            severity: None,
+        }
+    }
+
+    // dir 下的 a.bin（5 字节）、b.bin（3 字节）和子目录 sub 及其扫描结果
+    fn setup(name: &str, finished_at: SystemTime) -> (PathBuf, StoredScan) {
+        let dir =
+            std::env::temp_dir().join(format!("disksight-verify-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.bin"), b"12345").unwrap();
+        fs::write(dir.join("b.bin"), b"123").unwrap();
+        let result = DirectoryResult {
+            scan_id: None,
+            entries: vec![
+                entry(&dir.join("a.bin"), '-', 5),
+                entry(&dir.join("b.bin"), '-', 3),
+                entry(&dir.join("sub"), 'd', 0),
+            ],
+            query_time: 0.0,
+            telemetry: None,
+            scan_plan: None,
+            completeness: Default::default(),
+            skipped: Default::default(),
+        };
+        let scan = StoredScan::new(dir.clone(), result, finished_at);
+        (dir, scan)
+    }
+
+    #[test]
+    fn untouched_entries_verify() {
+        // 扫描时间设在将来，避免与文件的修改时间比较时受时钟精度影响
+        let (dir, scan) = setup("same", SystemTime::now() + Duration::from_secs(60));
+        let report = verify_result(7, &scan);
+        assert_eq!(report.scan_id, 7);
+        assert_eq!((report.checked, report.unchanged), (3, 3));
+        assert!(report.changes.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn changed_entries_are_reported() {
+        let (dir, scan) = setup("changed", SystemTime::now() + Duration::from_secs(60));
+        fs::remove_file(dir.join("a.bin")).unwrap();
+        fs::write(dir.join("b.bin"), b"1234").unwrap();
+        fs::remove_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub"), b"").unwrap();
+
+        let report = verify_result(1, &scan);
+        assert_eq!((report.checked, report.unchanged), (3, 0));
+        let states: Vec<(String, EntryState, Option<u64>)> = report
+            .changes
+            .iter()
+            .map(|c| {
+                let name = Path::new(&c.path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned();
+                (name, c.state, c.current_size)
+            })
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("a.bin".to_string(), EntryState::Disappeared, None),
+                ("b.bin".to_string(), EntryState::Resized, Some(4)),
+                ("sub".to_string(), EntryState::TypeChanged, None),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn same_size_written_after_the_scan_is_modified() {
+        let (dir, scan) = setup("modified", SystemTime::UNIX_EPOCH);
+        let report = verify_result(1, &scan);
+        assert_eq!(report.unchanged, 0);
+        assert!(report
+            .changes
+            .iter()
+            .all(|c| c.state == EntryState::Modified));
+        assert_eq!(report.changes[0].current_size, Some(5));
+        assert_eq!(report.changes[2].current_size, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+// 文件系统访问的抽象层：列出目录、读取元数据、删除。RealFs 访问真实磁盘，
+// MemoryFs 在内存中模拟目录树并可注入错误，列表与删除逻辑的测试不必接触真实磁盘
+use crate::attributes;
+use crate::dir_reader::{read_children, DirChild};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// 条目的元数据
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FsMetadata {
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub len: u64,
+    pub readonly: bool,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// 所在设备与 inode 号，平台不支持时为 0
+    pub dev: u64,
+    pub ino: u64,
+    /// 硬链接数，平台不支持时为 1
+    pub nlink: u64,
+}
+
+pub trait Fs: Send + Sync {
+    // 目录的直接子项，不跟随符号链接
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirChild>>;
+    // 不跟随符号链接
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata>;
+    // 跟随符号链接，读取链接指向的条目
+    fn stat(&self, path: &Path) -> io::Result<FsMetadata>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn clear_readonly(&self, path: &Path) -> Result<(), String>;
+    // 去掉整棵树的只读限制，返回无法修改的条目及原因
+    fn clear_readonly_recursive(&self, path: &Path) -> Vec<(PathBuf, String)>;
+}
+
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirChild>> {
+        read_children(dir)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        fs::symlink_metadata(path).map(|m| convert(&m))
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<FsMetadata> {
+        fs::metadata(path).map(|m| convert(&m))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        // Windows 上指向目录的符号链接需要用 remove_dir 删除
+        fs::remove_file(path).or_else(|e| {
+            if cfg!(windows) && path.is_dir() {
+                fs::remove_dir(path)
+            } else {
+                Err(e)
+            }
+        })
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn clear_readonly(&self, path: &Path) -> Result<(), String> {
+        attributes::clear_readonly(path)
+    }
+
+    fn clear_readonly_recursive(&self, path: &Path) -> Vec<(PathBuf, String)> {
+        attributes::clear_readonly_recursive(path)
+    }
+}
+
+fn convert(metadata: &fs::Metadata) -> FsMetadata {
+    #[cfg(unix)]
+    let (dev, ino, nlink) = {
+        use std::os::unix::fs::MetadataExt;
+        (metadata.dev(), metadata.ino(), metadata.nlink())
+    };
+    #[cfg(not(unix))]
+    let (dev, ino, nlink) = (0, 0, 1);
+    FsMetadata {
+        is_dir: metadata.is_dir(),
+        is_symlink: metadata.file_type().is_symlink(),
+        len: metadata.len(),
+        readonly: metadata.permissions().readonly(),
+        modified: metadata.modified().ok(),
+        created: metadata.created().ok(),
+        dev,
+        ino,
+        nlink,
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    is_dir: bool,
+    size: u64,
+    readonly: bool,
+    ino: u64,
+}
+
+// 内存中的目录树。只读文件与 Windows 上一样不能直接删除；
+// fail 注入的错误在之后访问该路径时返回
+#[derive(Default)]
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+    errors: Mutex<HashMap<PathBuf, io::ErrorKind>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 添加目录及其所有上级目录
+    pub fn add_dir(&self, path: impl AsRef<Path>) {
+        let mut nodes = self.nodes.lock().unwrap();
+        for dir in path.as_ref().ancestors() {
+            if dir.as_os_str().is_empty() || nodes.contains_key(dir) {
+                continue;
+            }
+            let ino = nodes.len() as u64 + 1;
+            nodes.insert(
+                dir.to_path_buf(),
+                Node {
+                    is_dir: true,
+                    size: 0,
+                    readonly: false,
+                    ino,
+                },
+            );
+        }
+    }
+
+    pub fn add_file(&self, path: impl AsRef<Path>, size: u64) {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            self.add_dir(parent);
+        }
+        let mut nodes = self.nodes.lock().unwrap();
+        let ino = nodes.len() as u64 + 1;
+        nodes.insert(
+            path.to_path_buf(),
+            Node {
+                is_dir: false,
+                size,
+                readonly: false,
+                ino,
+            },
+        );
+    }
+
+    pub fn set_readonly(&self, path: impl AsRef<Path>, readonly: bool) {
+        if let Some(node) = self.nodes.lock().unwrap().get_mut(path.as_ref()) {
+            node.readonly = readonly;
+        }
+    }
+
+    // 之后对 path 的任何操作都返回 kind 错误
+    pub fn fail(&self, path: impl AsRef<Path>, kind: io::ErrorKind) {
+        self.errors
+            .lock()
+            .unwrap()
+            .insert(path.as_ref().to_path_buf(), kind);
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.nodes.lock().unwrap().contains_key(path.as_ref())
+    }
+
+    fn check(&self, path: &Path) -> io::Result<()> {
+        match self.errors.lock().unwrap().get(path) {
+            Some(kind) => Err(io::Error::from(*kind)),
+            None => Ok(()),
+        }
+    }
+
+    fn node(&self, path: &Path) -> io::Result<Node> {
+        self.check(path)?;
+        self.nodes
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn subtree(&self, path: &Path) -> Vec<PathBuf> {
+        self.nodes
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|p| p.starts_with(path))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Fs for MemoryFs {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<DirChild>> {
+        if !self.node(dir)?.is_dir {
+            return Err(io::Error::from(io::ErrorKind::NotADirectory));
+        }
+        let nodes = self.nodes.lock().unwrap();
+        Ok(nodes
+            .iter()
+            .filter(|(path, _)| path.parent() == Some(dir))
+            .map(|(path, node)| DirChild {
+                name: path.file_name().unwrap_or_default().to_os_string(),
+                is_dir: node.is_dir,
+                size: node.size,
+                ino: node.ino,
+            })
+            .collect())
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<FsMetadata> {
+        self.metadata(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        let node = self.node(path)?;
+        Ok(FsMetadata {
+            is_dir: node.is_dir,
+            is_symlink: false,
+            len: node.size,
+            readonly: node.readonly,
+            modified: None,
+            created: None,
+            dev: 1,
+            ino: node.ino,
+            nlink: 1,
+        })
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let node = self.node(path)?;
+        if node.is_dir {
+            return Err(io::Error::from(io::ErrorKind::IsADirectory));
+        }
+        if node.readonly {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        }
+        self.nodes.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let subtree = self.subtree(path);
+        // 与真实文件系统不同，遇到无法删除的条目时整棵树保持不变
+        for p in &subtree {
+            self.check(p)?;
+            if self.nodes.lock().unwrap()[p].readonly {
+                return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+            }
+        }
+        if subtree.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        let mut nodes = self.nodes.lock().unwrap();
+        for p in subtree {
+            nodes.remove(&p);
+        }
+        Ok(())
+    }
+
+    fn clear_readonly(&self, path: &Path) -> Result<(), String> {
+        self.node(path)
+            .map_err(|e| format!("无法访问路径: {}", e))?;
+        self.set_readonly(path, false);
+        Ok(())
+    }
+
+    fn clear_readonly_recursive(&self, path: &Path) -> Vec<(PathBuf, String)> {
+        let mut failures = Vec::new();
+        for p in self.subtree(path) {
+            if let Err(e) = self.clear_readonly(&p) {
+                failures.push((p, e));
+            }
+        }
+        failures
+    }
+}
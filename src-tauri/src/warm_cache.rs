@@ -0,0 +1,115 @@
+// 启动后在后台以低优先级预先扫描书签（监控目录），结果存入扫描结果存储。
+// 用户随后打开这些目录时直接取用预扫描的结果，不必等待完整扫描
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+// 预扫描结果超过该时长后不再使用，打开目录时重新扫描
+const MAX_AGE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarmCacheSettings {
+    /// 启动后预先扫描监控目录
+    pub enabled: bool,
+    /// 使用电池供电时不预扫描
+    pub skip_on_battery: bool,
+}
+
+impl Default for WarmCacheSettings {
+    fn default() -> Self {
+        WarmCacheSettings {
+            enabled: false,
+            skip_on_battery: true,
+        }
+    }
+}
+
+impl WarmCacheSettings {
+    // 按设置和当前电源状态决定本次启动是否预扫描，无法判断电源状态时视为接通电源
+    pub fn should_warm(&self, on_battery: Option<bool>) -> bool {
+        self.enabled && !(self.skip_on_battery && on_battery == Some(true))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct WarmScan {
+    scan_id: u64,
+    finished_at: SystemTime,
+}
+
+// 已预扫描的目录（按 history_key 规范化）到扫描 ID 的映射
+#[derive(Default)]
+pub struct WarmCache {
+    scans: Mutex<HashMap<String, WarmScan>>,
+}
+
+impl WarmCache {
+    pub fn record(&self, root: &str, scan_id: u64) {
+        self.scans.lock().unwrap().insert(
+            root.to_string(),
+            WarmScan {
+                scan_id,
+                finished_at: SystemTime::now(),
+            },
+        );
+    }
+
+    // 取出目录的预扫描结果 ID，每个结果只使用一次；之后刷新时总是重新扫描
+    pub fn take(&self, root: &str) -> Option<u64> {
+        let warm = self.scans.lock().unwrap().remove(root)?;
+        let age = warm.finished_at.elapsed().unwrap_or(Duration::MAX);
+        (age <= MAX_AGE).then_some(warm.scan_id)
+    }
+}
+
+// 要预扫描的目录：去重并跳过已不存在的目录
+pub fn roots_to_warm(monitored: &[String]) -> Vec<String> {
+    let mut roots: Vec<String> = Vec::new();
+    for root in monitored {
+        if !roots.contains(root) && std::path::Path::new(root).is_dir() {
+            roots.push(root.clone());
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warms_only_when_enabled_and_not_on_battery() {
+        let settings = WarmCacheSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(settings.should_warm(Some(false)));
+        assert!(settings.should_warm(None));
+        assert!(!settings.should_warm(Some(true)));
+        let anywhere = WarmCacheSettings {
+            skip_on_battery: false,
+            ..settings
+        };
+        assert!(anywhere.should_warm(Some(true)));
+        assert!(!WarmCacheSettings::default().should_warm(Some(false)));
+    }
+
+    #[test]
+    fn each_warm_scan_is_used_once() {
+        let cache = WarmCache::default();
+        cache.record("/data", 7);
+        assert_eq!(cache.take("/data"), Some(7));
+        assert_eq!(cache.take("/data"), None);
+
+        cache.scans.lock().unwrap().insert(
+            "/old".to_string(),
+            WarmScan {
+                scan_id: 8,
+                finished_at: SystemTime::now() - MAX_AGE - Duration::from_secs(1),
+            },
+        );
+        assert_eq!(cache.take("/old"), None);
+    }
+}
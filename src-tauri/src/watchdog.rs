@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 检测线程的最长轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutSettings {
+    /// 扫描命令的超时时间（秒），为 None 时不限制
+    pub scan_seconds: Option<u64>,
+    /// 重复文件、分析器等分析类命令的超时时间（秒），为 None 时不限制
+    pub analysis_seconds: Option<u64>,
+    /// 超过该秒数没有进度时发送 scan-stalled 事件，为 0 时不检测
+    pub stall_seconds: u64,
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        TimeoutSettings {
+            scan_seconds: None,
+            analysis_seconds: None,
+            stall_seconds: 30,
+        }
+    }
+}
+
+// scan-stalled 事件的内容
+#[derive(Clone, Debug, Serialize)]
+pub struct StallEvent {
+    /// 发生停滞的命令，如 get_list_directory
+    pub command: String,
+    /// 扫描 ID，非扫描类命令为 None
+    pub scan_id: Option<u64>,
+    /// 最后一次有进度时处理的路径，通常就是卡住的网络挂载点
+    pub last_path: String,
+    /// 已持续多少秒没有进度
+    pub stalled_seconds: u64,
+}
+
+struct Progress {
+    at: Instant,
+    path: String,
+    /// 本次停滞已报告过，有新进度后重置
+    reported: bool,
+}
+
+struct Inner {
+    progress: Mutex<Progress>,
+    finished: AtomicBool,
+    /// 报告过停滞的路径
+    stalled: Mutex<Vec<String>>,
+}
+
+// 长时间无进度检测。扫描过程中调用 touch 报告进度，检测线程发现超过阈值没有进度时
+// 调用一次 on_stall，恢复进度后再次停滞会再报告。结束时调用 finish 停止检测线程
+#[derive(Clone)]
+pub struct Watchdog {
+    inner: Arc<Inner>,
+}
+
+impl Watchdog {
+    pub fn start(
+        stall_after: Duration,
+        start_path: &Path,
+        on_stall: impl Fn(&str, u64) + Send + 'static,
+    ) -> Self {
+        let inner = Arc::new(Inner {
+            progress: Mutex::new(Progress {
+                at: Instant::now(),
+                path: start_path.to_string_lossy().into_owned(),
+                reported: false,
+            }),
+            // 阈值为 0 表示不检测，不启动线程
+            finished: AtomicBool::new(stall_after.is_zero()),
+            stalled: Mutex::new(Vec::new()),
+        });
+        if !stall_after.is_zero() {
+            let watched = inner.clone();
+            let interval = POLL_INTERVAL
+                .min(stall_after / 4)
+                .max(Duration::from_millis(10));
+            std::thread::spawn(move || {
+                while !watched.finished.load(Ordering::Relaxed) {
+                    std::thread::sleep(interval);
+                    let mut progress = watched.progress.lock().unwrap();
+                    let idle = progress.at.elapsed();
+                    if idle >= stall_after && !progress.reported {
+                        progress.reported = true;
+                        let path = progress.path.clone();
+                        drop(progress);
+                        watched.stalled.lock().unwrap().push(path.clone());
+                        on_stall(&path, idle.as_secs());
+                    }
+                }
+            });
+        }
+        Watchdog { inner }
+    }
+
+    pub fn touch(&self, path: &Path) {
+        let mut progress = self.inner.progress.lock().unwrap();
+        progress.at = Instant::now();
+        progress.reported = false;
+        progress.path = path.to_string_lossy().into_owned();
+    }
+
+    pub fn finish(&self) {
+        self.inner.finished.store(true, Ordering::Relaxed);
+    }
+
+    // 本次检测中报告过停滞的路径
+    pub fn stalled_paths(&self) -> Vec<String> {
+        self.inner.stalled.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_each_stall_once_with_last_path() {
+        let (tx, rx) = mpsc::channel();
+        let watchdog = Watchdog::start(
+            Duration::from_millis(50),
+            Path::new("/start"),
+            move |path, _| {
+                let _ = tx.send(path.to_string());
+            },
+        );
+        watchdog.touch(Path::new("/mnt/nfs/dead"));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(2)).unwrap(),
+            "/mnt/nfs/dead"
+        );
+        // 同一次停滞不重复报告
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+
+        watchdog.touch(Path::new("/mnt/nfs/other"));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(2)).unwrap(),
+            "/mnt/nfs/other"
+        );
+        assert_eq!(
+            watchdog.stalled_paths(),
+            ["/mnt/nfs/dead", "/mnt/nfs/other"]
+        );
+        watchdog.finish();
+    }
+}
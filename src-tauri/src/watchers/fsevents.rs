@@ -0,0 +1,414 @@
+// 通过 FSEvents 监视已索引目录的变更。FSEvents 的事件 ID 可以跨重启续读，
+// 因此和 Windows 的 USN 日志一样把读取位置保存在索引里
+use super::{Flusher, FALLBACK_REBUILD_INTERVAL};
+use crate::index::{ChangeCursor, FileIndex};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::ptr::null;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+type CFRef = *const c_void;
+type FSEventStreamRef = *mut c_void;
+type FSEventStreamCallback = extern "C" fn(
+    stream: FSEventStreamRef,
+    info: *mut c_void,
+    num_events: usize,
+    paths: *mut c_void,
+    flags: *const u32,
+    ids: *const u64,
+);
+
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const EVENT_ID_SINCE_NOW: u64 = u64::MAX;
+const CREATE_FLAG_NO_DEFER: u32 = 0x02;
+const CREATE_FLAG_FILE_EVENTS: u32 = 0x10;
+const EVENT_FLAG_MUST_SCAN_SUB_DIRS: u32 = 0x01;
+const EVENT_FLAG_HISTORY_DONE: u32 = 0x10;
+const LATENCY_SECS: f64 = 1.0;
+const RUN_SLICE_SECS: f64 = 5.0;
+
+#[repr(C)]
+struct FSEventStreamContext {
+    version: isize,
+    info: *mut c_void,
+    retain: *const c_void,
+    release: *const c_void,
+    copy_description: *const c_void,
+}
+
+#[repr(C)]
+struct CFArrayCallBacks {
+    version: isize,
+    retain: *const c_void,
+    release: *const c_void,
+    copy_description: *const c_void,
+    equal: *const c_void,
+}
+
+#[link(name = "CoreServices", kind = "framework")]
+extern "C" {
+    fn FSEventStreamCreate(
+        allocator: CFRef,
+        callback: FSEventStreamCallback,
+        context: *const FSEventStreamContext,
+        paths: CFRef,
+        since_when: u64,
+        latency: f64,
+        flags: u32,
+    ) -> FSEventStreamRef;
+    fn FSEventStreamScheduleWithRunLoop(stream: FSEventStreamRef, run_loop: CFRef, mode: CFRef);
+    fn FSEventStreamStart(stream: FSEventStreamRef) -> u8;
+    fn FSEventStreamStop(stream: FSEventStreamRef);
+    fn FSEventStreamInvalidate(stream: FSEventStreamRef);
+    fn FSEventStreamRelease(stream: FSEventStreamRef);
+    fn FSEventsGetCurrentEventId() -> u64;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopDefaultMode: CFRef;
+    static kCFTypeArrayCallBacks: CFArrayCallBacks;
+    fn CFRunLoopGetCurrent() -> CFRef;
+    fn CFRunLoopRunInMode(mode: CFRef, seconds: f64, return_after_source_handled: u8) -> i32;
+    fn CFStringCreateWithBytes(
+        allocator: CFRef,
+        bytes: *const u8,
+        len: isize,
+        encoding: u32,
+        external: u8,
+    ) -> CFRef;
+    fn CFArrayCreate(
+        allocator: CFRef,
+        values: *const CFRef,
+        count: isize,
+        callbacks: *const CFArrayCallBacks,
+    ) -> CFRef;
+    fn CFRelease(cf: CFRef);
+}
+
+// 回调中收到的事件：路径、标志、事件 ID
+type Pending = Mutex<Vec<(PathBuf, u32, u64)>>;
+
+extern "C" fn on_events(
+    _stream: FSEventStreamRef,
+    info: *mut c_void,
+    num_events: usize,
+    paths: *mut c_void,
+    flags: *const u32,
+    ids: *const u64,
+) {
+    let pending = unsafe { &*(info as *const Pending) };
+    let paths = paths as *const *const c_char;
+    let mut pending = pending.lock().unwrap();
+    for i in 0..num_events {
+        unsafe {
+            let path = CStr::from_ptr(*paths.add(i)).to_string_lossy().into_owned();
+            pending.push((PathBuf::from(path), *flags.add(i), *ids.add(i)));
+        }
+    }
+}
+
+// 覆盖当前所有索引目录的事件流，目录集合变化时重新创建
+struct Stream {
+    raw: FSEventStreamRef,
+    roots: Vec<String>,
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        unsafe {
+            FSEventStreamStop(self.raw);
+            FSEventStreamInvalidate(self.raw);
+            FSEventStreamRelease(self.raw);
+        }
+    }
+}
+
+impl Stream {
+    // pending 由回调通过 info 指针写入，需在整个监视线程内有效
+    fn start(roots: Vec<String>, since: u64, pending: &'static Pending) -> Option<Stream> {
+        unsafe {
+            let strings: Vec<CFRef> = roots
+                .iter()
+                .map(|root| {
+                    CFStringCreateWithBytes(
+                        null(),
+                        root.as_ptr(),
+                        root.len() as isize,
+                        K_CF_STRING_ENCODING_UTF8,
+                        0,
+                    )
+                })
+                .collect();
+            let array = CFArrayCreate(
+                null(),
+                strings.as_ptr(),
+                strings.len() as isize,
+                &kCFTypeArrayCallBacks,
+            );
+            for string in &strings {
+                CFRelease(*string);
+            }
+            let context = FSEventStreamContext {
+                version: 0,
+                info: pending as *const Pending as *mut c_void,
+                retain: null(),
+                release: null(),
+                copy_description: null(),
+            };
+            let raw = FSEventStreamCreate(
+                null(),
+                on_events,
+                &context,
+                array,
+                since,
+                LATENCY_SECS,
+                CREATE_FLAG_NO_DEFER | CREATE_FLAG_FILE_EVENTS,
+            );
+            CFRelease(array);
+            if raw.is_null() {
+                return None;
+            }
+            FSEventStreamScheduleWithRunLoop(raw, CFRunLoopGetCurrent(), kCFRunLoopDefaultMode);
+            if FSEventStreamStart(raw) == 0 {
+                FSEventStreamInvalidate(raw);
+                FSEventStreamRelease(raw);
+                return None;
+            }
+            Some(Stream { raw, roots })
+        }
+    }
+}
+
+pub(super) fn spawn(index: FileIndex) {
+    std::thread::spawn(move || {
+        let started_at = SystemTime::now();
+        let pending: &'static Pending = Box::leak(Box::new(Mutex::new(Vec::new())));
+        let mut stream: Option<Stream> = None;
+        let mut fallback: HashMap<String, Instant> = HashMap::new();
+        let mut flusher = Flusher::new();
+        loop {
+            let generation = index.roots_generation();
+            let roots: Vec<String> = index
+                .roots()
+                .into_iter()
+                .filter(|(_, building)| !building)
+                .map(|(root, _)| root)
+                .collect();
+            if stream.as_ref().map(|s| &s.roots) != Some(&roots) {
+                // 先停止旧的事件流再创建新的
+                drop(stream.take());
+                stream = open_stream(&index, roots, started_at, pending);
+                if stream.is_none() {
+                    for (root, _) in index.roots() {
+                        fallback.entry(root).or_insert_with(Instant::now);
+                    }
+                }
+            }
+            for (root, last) in fallback.iter_mut() {
+                if last.elapsed() >= FALLBACK_REBUILD_INTERVAL {
+                    index.rebuild_root(root);
+                    *last = Instant::now();
+                }
+            }
+
+            // 没有事件流时运行循环里没有任何源，CFRunLoopRunInMode 会立即返回；
+            // 改为等待目录集合变化，兜底重建仍按时间片检查
+            if stream.is_some() {
+                unsafe {
+                    CFRunLoopRunInMode(kCFRunLoopDefaultMode, RUN_SLICE_SECS, 0);
+                }
+            } else {
+                index.wait_roots_changed(generation, Duration::from_secs_f64(RUN_SLICE_SECS));
+            }
+            let events: Vec<_> = std::mem::take(&mut *pending.lock().unwrap());
+            if apply_events(&index, &events) {
+                flusher.mark_dirty();
+            }
+            flusher.maybe_flush(&index);
+        }
+    });
+}
+
+// 从各目录保存的位置中最早的一个开始续读；程序启动前建立、又没有位置记录的目录先完整重建
+fn open_stream(
+    index: &FileIndex,
+    roots: Vec<String>,
+    started_at: SystemTime,
+    pending: &'static Pending,
+) -> Option<Stream> {
+    if roots.is_empty() {
+        return None;
+    }
+    let now = unsafe { FSEventsGetCurrentEventId() };
+    let mut since = EVENT_ID_SINCE_NOW;
+    for root in &roots {
+        match index.cursor(root) {
+            Some(cursor) => since = since.min(cursor.position),
+            None => {
+                if index.built_at(root).is_some_and(|t| t < started_at) {
+                    index.rebuild_root(root);
+                }
+                index.set_cursor(
+                    root,
+                    Some(ChangeCursor {
+                        journal_id: 0,
+                        position: now,
+                    }),
+                );
+            }
+        }
+    }
+    Stream::start(roots, since, pending)
+}
+
+// 一批事件需要对索引进行的操作
+#[derive(Debug, Default, PartialEq)]
+struct Plan {
+    /// 需要刷新的路径
+    refresh: Vec<PathBuf>,
+    /// 需要完整重建的索引目录
+    rebuild: Vec<String>,
+    /// 批次中最大的事件 ID，没有事件时为 0
+    last_id: u64,
+}
+
+fn plan_events(roots: &[String], events: &[(PathBuf, u32, u64)]) -> Plan {
+    let mut plan = Plan::default();
+    for (path, flags, id) in events {
+        plan.last_id = plan.last_id.max(*id);
+        if flags & EVENT_FLAG_HISTORY_DONE != 0 {
+            continue;
+        }
+        // 系统合并或丢弃了事件，需要重新遍历该目录所在的索引
+        if flags & EVENT_FLAG_MUST_SCAN_SUB_DIRS != 0 {
+            if let Some(root) = root_of(roots, path) {
+                if !plan.rebuild.contains(root) {
+                    plan.rebuild.push(root.clone());
+                }
+            }
+            continue;
+        }
+        plan.refresh.push(path.clone());
+    }
+    plan
+}
+
+fn apply_events(index: &FileIndex, events: &[(PathBuf, u32, u64)]) -> bool {
+    let roots: Vec<String> = index.roots().into_iter().map(|(root, _)| root).collect();
+    let plan = plan_events(&roots, events);
+    for root in &plan.rebuild {
+        index.rebuild_root(root);
+    }
+    let mut changed = false;
+    for path in &plan.refresh {
+        changed |= index.refresh_path(path);
+    }
+    if plan.last_id > 0 {
+        for root in &roots {
+            if index
+                .cursor(root)
+                .is_some_and(|c| c.position < plan.last_id)
+            {
+                index.set_cursor(
+                    root,
+                    Some(ChangeCursor {
+                        journal_id: 0,
+                        position: plan.last_id,
+                    }),
+                );
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn root_of<'a>(roots: &'a [String], path: &Path) -> Option<&'a String> {
+    roots.iter().find(|root| path.starts_with(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEM_CREATED: u32 = 0x100;
+    const ITEM_RENAMED: u32 = 0x800;
+    const ITEM_IS_FILE: u32 = 0x10000;
+
+    fn roots() -> Vec<String> {
+        vec![
+            "/Users/me/Documents".to_string(),
+            "/Volumes/Data".to_string(),
+        ]
+    }
+
+    #[test]
+    fn file_events_refresh_their_paths() {
+        let events = [
+            (
+                PathBuf::from("/Users/me/Documents/a.txt"),
+                ITEM_CREATED | ITEM_IS_FILE,
+                10,
+            ),
+            (
+                PathBuf::from("/Volumes/Data/photos/b.jpg"),
+                ITEM_RENAMED | ITEM_IS_FILE,
+                12,
+            ),
+            (
+                PathBuf::from("/Users/me/Documents/a.txt"),
+                ITEM_RENAMED | ITEM_IS_FILE,
+                11,
+            ),
+        ];
+        assert_eq!(
+            plan_events(&roots(), &events),
+            Plan {
+                refresh: vec![
+                    PathBuf::from("/Users/me/Documents/a.txt"),
+                    PathBuf::from("/Volumes/Data/photos/b.jpg"),
+                    PathBuf::from("/Users/me/Documents/a.txt"),
+                ],
+                rebuild: Vec::new(),
+                last_id: 12,
+            }
+        );
+    }
+
+    #[test]
+    fn dropped_events_rebuild_the_containing_root_once() {
+        let events = [
+            (
+                PathBuf::from("/Volumes/Data/projects"),
+                EVENT_FLAG_MUST_SCAN_SUB_DIRS,
+                20,
+            ),
+            (
+                PathBuf::from("/Volumes/Data"),
+                EVENT_FLAG_MUST_SCAN_SUB_DIRS,
+                21,
+            ),
+            // 不在任何索引目录下
+            (
+                PathBuf::from("/private/tmp"),
+                EVENT_FLAG_MUST_SCAN_SUB_DIRS,
+                22,
+            ),
+            // 历史事件结束的标记只推进位置
+            (PathBuf::new(), EVENT_FLAG_HISTORY_DONE, 30),
+        ];
+        assert_eq!(
+            plan_events(&roots(), &events),
+            Plan {
+                refresh: Vec::new(),
+                rebuild: vec!["/Volumes/Data".to_string()],
+                last_id: 30,
+            }
+        );
+        assert_eq!(plan_events(&roots(), &[]), Plan::default());
+    }
+}
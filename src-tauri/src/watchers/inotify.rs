@@ -0,0 +1,355 @@
+// 通过 inotify 监视已索引目录的变更。fanotify 虽然能整卷监视，但需要 CAP_SYS_ADMIN，
+// 普通用户运行时不可用，这里统一使用 inotify
+use super::{Flusher, FALLBACK_REBUILD_INTERVAL};
+use crate::index::FileIndex;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+const POLL_TIMEOUT_MS: i32 = 5000;
+const WATCH_MASK: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_CLOSE_WRITE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_ATTRIB
+    | libc::IN_DELETE_SELF
+    | libc::IN_ONLYDIR
+    | libc::IN_DONT_FOLLOW;
+
+struct Watcher {
+    fd: i32,
+    /// 监视描述符 -> 目录
+    watches: HashMap<i32, PathBuf>,
+    /// 已建立监视的索引目录
+    attached: HashSet<String>,
+    /// 超出监视数量上限、改为定期重建的目录及上次重建时间
+    fallback: HashMap<String, Instant>,
+    /// 程序启动前建立的索引需要先重建一次，补上程序未运行期间的变更
+    refreshed: HashSet<String>,
+    started_at: SystemTime,
+}
+
+// 从原始事件得到的变更
+#[derive(Debug, Default)]
+struct Changes {
+    /// 需要刷新的路径
+    paths: Vec<PathBuf>,
+    /// 新建或移入、需要添加监视的目录
+    new_dirs: Vec<PathBuf>,
+    /// 内核事件队列溢出
+    overflow: bool,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+pub(super) fn spawn(index: FileIndex) {
+    std::thread::spawn(move || {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+        if fd < 0 {
+            eprintln!("inotify 初始化失败: {}", std::io::Error::last_os_error());
+            return;
+        }
+        let mut watcher = Watcher {
+            fd,
+            watches: HashMap::new(),
+            attached: HashSet::new(),
+            fallback: HashMap::new(),
+            refreshed: HashSet::new(),
+            started_at: SystemTime::now(),
+        };
+        let mut flusher = Flusher::new();
+        loop {
+            watcher.sync_roots(&index);
+            if watcher.wait_readable() && watcher.drain(&index) {
+                flusher.mark_dirty();
+            }
+            flusher.maybe_flush(&index);
+        }
+    });
+}
+
+impl Watcher {
+    // 按索引目录的变化增删监视
+    fn sync_roots(&mut self, index: &FileIndex) {
+        let roots = index.roots();
+        let removed: Vec<String> = self
+            .attached
+            .iter()
+            .filter(|root| !roots.iter().any(|(r, _)| r == *root))
+            .cloned()
+            .collect();
+        for root in removed {
+            self.detach(&root);
+        }
+        self.fallback
+            .retain(|root, _| roots.iter().any(|(r, _)| r == root));
+
+        for (root, building) in roots {
+            if building || self.attached.contains(&root) {
+                continue;
+            }
+            if let Some(last) = self.fallback.get(&root) {
+                if last.elapsed() >= FALLBACK_REBUILD_INTERVAL {
+                    index.rebuild_root(&root);
+                    self.fallback.insert(root, Instant::now());
+                }
+                continue;
+            }
+            if !self.refreshed.contains(&root)
+                && index.built_at(&root).is_some_and(|t| t < self.started_at)
+            {
+                self.refreshed.insert(root.clone());
+                index.rebuild_root(&root);
+                continue;
+            }
+            match self.watch_tree(Path::new(&root)) {
+                Ok(()) => {
+                    self.attached.insert(root.clone());
+                    index.set_error(&root, None);
+                }
+                Err(e) => {
+                    self.detach(&root);
+                    index.set_error(&root, Some(format!("{}，改为定期完整重建", e)));
+                    self.fallback.insert(root, Instant::now());
+                }
+            }
+        }
+    }
+
+    // 为目录及其所有子目录添加监视
+    fn watch_tree(&mut self, dir: &Path) -> Result<(), String> {
+        self.add_watch(dir)?;
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                self.watch_tree(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_watch(&mut self, dir: &Path) -> Result<(), String> {
+        let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+            return Ok(());
+        };
+        let wd = unsafe { libc::inotify_add_watch(self.fd, c_path.as_ptr(), WATCH_MASK) };
+        if wd >= 0 {
+            self.watches.insert(wd, dir.to_path_buf());
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::ENOSPC) => {
+                Err("inotify 监视数量已达上限，可调大 fs.inotify.max_user_watches".to_string())
+            }
+            // 无权限或已被删除的目录跳过即可
+            _ => Ok(()),
+        }
+    }
+
+    fn detach(&mut self, root: &str) {
+        let fd = self.fd;
+        self.watches.retain(|wd, dir| {
+            let keep = !dir.starts_with(root);
+            if !keep {
+                unsafe {
+                    libc::inotify_rm_watch(fd, *wd);
+                }
+            }
+            keep
+        });
+        self.attached.remove(root);
+    }
+
+    fn wait_readable(&self) -> bool {
+        let mut pfd = libc::pollfd {
+            fd: self.fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        unsafe { libc::poll(&mut pfd, 1, POLL_TIMEOUT_MS) > 0 }
+    }
+
+    // 读取并处理所有待处理事件，返回索引是否有变化
+    fn drain(&mut self, index: &FileIndex) -> bool {
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut changes = Changes::default();
+        loop {
+            let n = unsafe {
+                libc::read(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            parse_events(&buffer[..n as usize], &mut self.watches, &mut changes);
+        }
+
+        // 队列溢出意味着丢失了事件，只能完整重建
+        if changes.overflow {
+            for root in self.attached.clone() {
+                self.detach(&root);
+                self.refreshed.remove(&root);
+                index.rebuild_root(&root);
+            }
+            return false;
+        }
+        for dir in changes.new_dirs {
+            if let Err(e) = self.watch_tree(&dir) {
+                eprintln!("无法监视新目录 {}: {}", dir.display(), e);
+            }
+        }
+        let mut paths = changes.paths;
+        paths.sort();
+        paths.dedup();
+        let mut changed = false;
+        for path in paths {
+            changed |= index.refresh_path(&path);
+        }
+        changed
+    }
+}
+
+// 解析一次 read 得到的事件：按监视描述符找到目录，拼上事件中的文件名得到变更路径。
+// 收到 IN_IGNORED 的监视已被内核移除，同时从 watches 中移除
+fn parse_events(buffer: &[u8], watches: &mut HashMap<i32, PathBuf>, changes: &mut Changes) {
+    let header = std::mem::size_of::<libc::inotify_event>();
+    let mut offset = 0usize;
+    while offset + header <= buffer.len() {
+        let event: libc::inotify_event =
+            unsafe { std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const _) };
+        let end = (offset + header + event.len as usize).min(buffer.len());
+        let name_bytes = &buffer[offset + header..end];
+        offset = end;
+
+        if event.mask & libc::IN_Q_OVERFLOW != 0 {
+            changes.overflow = true;
+            continue;
+        }
+        if event.mask & libc::IN_IGNORED != 0 {
+            watches.remove(&event.wd);
+            continue;
+        }
+        let Some(dir) = watches.get(&event.wd) else {
+            continue;
+        };
+        let name_len = name_bytes
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(name_bytes.len());
+        let path = if name_len == 0 {
+            dir.clone()
+        } else {
+            dir.join(std::ffi::OsStr::from_bytes(&name_bytes[..name_len]))
+        };
+        if event.mask & libc::IN_ISDIR != 0
+            && event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0
+        {
+            changes.new_dirs.push(path.clone());
+        }
+        changes.paths.push(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按内核的格式编码一个事件，文件名以 NUL 补齐到 4 字节对齐
+    fn event(wd: i32, mask: u32, name: &str) -> Vec<u8> {
+        let mut name = name.as_bytes().to_vec();
+        if !name.is_empty() {
+            name.resize((name.len() / 4 + 1) * 4, 0);
+        }
+        let header = libc::inotify_event {
+            wd,
+            mask,
+            cookie: 0,
+            len: name.len() as u32,
+        };
+        let mut bytes = vec![0u8; std::mem::size_of::<libc::inotify_event>()];
+        unsafe { std::ptr::write_unaligned(bytes.as_mut_ptr() as *mut _, header) };
+        bytes.extend(name);
+        bytes
+    }
+
+    fn watches() -> HashMap<i32, PathBuf> {
+        HashMap::from([
+            (1, PathBuf::from("/home/me")),
+            (2, PathBuf::from("/home/me/docs")),
+        ])
+    }
+
+    #[test]
+    fn maps_events_to_paths_under_the_watched_directory() {
+        let buffer = [
+            event(1, libc::IN_CREATE, "notes.txt"),
+            event(2, libc::IN_MOVED_FROM, "old.md"),
+            event(2, libc::IN_MOVED_TO, "new.md"),
+            event(1, libc::IN_CREATE | libc::IN_ISDIR, "photos"),
+            event(1, libc::IN_MOVED_TO | libc::IN_ISDIR, "music"),
+            event(1, libc::IN_DELETE | libc::IN_ISDIR, "tmp"),
+            // 目录自身的事件没有文件名
+            event(2, libc::IN_DELETE_SELF, ""),
+            // 未知的监视描述符
+            event(9, libc::IN_CREATE, "stray"),
+        ]
+        .concat();
+
+        let mut watches = watches();
+        let mut changes = Changes::default();
+        parse_events(&buffer, &mut watches, &mut changes);
+        assert_eq!(
+            changes.paths,
+            [
+                "/home/me/notes.txt",
+                "/home/me/docs/old.md",
+                "/home/me/docs/new.md",
+                "/home/me/photos",
+                "/home/me/music",
+                "/home/me/tmp",
+                "/home/me/docs",
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(
+            changes.new_dirs,
+            ["/home/me/photos", "/home/me/music"].map(PathBuf::from)
+        );
+        assert!(!changes.overflow);
+        assert_eq!(watches.len(), 2);
+    }
+
+    #[test]
+    fn ignored_watches_are_dropped_and_overflow_is_reported() {
+        let buffer = [
+            event(2, libc::IN_IGNORED, ""),
+            // 移除后同一描述符的事件不再有对应目录
+            event(2, libc::IN_CREATE, "late.txt"),
+            event(-1, libc::IN_Q_OVERFLOW, ""),
+        ]
+        .concat();
+
+        let mut watches = watches();
+        let mut changes = Changes::default();
+        parse_events(&buffer, &mut watches, &mut changes);
+        assert!(changes.paths.is_empty());
+        assert!(changes.overflow);
+        assert_eq!(watches.keys().collect::<Vec<_>>(), [&1]);
+    }
+}
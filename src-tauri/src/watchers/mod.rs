@@ -0,0 +1,62 @@
+// 监视文件系统变更并增量更新文件名索引，避免定期完整重建
+use crate::index::FileIndex;
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "macos")]
+mod fsevents;
+#[cfg(target_os = "linux")]
+mod inotify;
+#[cfg(windows)]
+mod usn;
+
+// 增量更新失败时，退回到按此间隔完整重建
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+pub(crate) const FALLBACK_REBUILD_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+// 两次写回索引文件的最小间隔
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+// 启动当前平台的变更监视线程
+pub fn start(index: FileIndex) {
+    #[cfg(windows)]
+    usn::spawn(index);
+    #[cfg(target_os = "linux")]
+    inotify::spawn(index);
+    #[cfg(target_os = "macos")]
+    fsevents::spawn(index);
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    let _ = index;
+}
+
+// 有变更时按间隔把索引写回磁盘
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+pub(crate) struct Flusher {
+    dirty: bool,
+    last_flush: Instant,
+}
+
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+impl Flusher {
+    pub(crate) fn new() -> Self {
+        Flusher {
+            dirty: false,
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub(crate) fn maybe_flush(&mut self, index: &FileIndex) {
+        if !self.dirty || self.last_flush.elapsed() < FLUSH_INTERVAL {
+            return;
+        }
+        if let Err(e) = index.flush() {
+            eprintln!("保存索引失败: {}", e);
+        }
+        self.dirty = false;
+        self.last_flush = Instant::now();
+    }
+}
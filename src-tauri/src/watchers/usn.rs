@@ -0,0 +1,417 @@
+// 读取 NTFS USN 变更日志，把新增、删除、改名、写入的文件同步到索引
+use super::{Flusher, FALLBACK_REBUILD_INTERVAL};
+use crate::drives::to_wide;
+use crate::index::{ChangeCursor, FileIndex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::ptr::null_mut;
+use std::time::{Duration, Instant};
+use winapi::ctypes::c_void;
+use winapi::um::fileapi::{CreateFileW, GetFinalPathNameByHandleW};
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::ioapiset::DeviceIoControl;
+use winapi::um::winnt::HANDLE;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const GENERIC_READ: u32 = 0x8000_0000;
+const FILE_SHARE_ALL: u32 = 0x1 | 0x2 | 0x4;
+const OPEN_EXISTING: u32 = 3;
+const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+const FSCTL_QUERY_USN_JOURNAL: u32 = 0x0009_00f4;
+const FSCTL_READ_USN_JOURNAL: u32 = 0x0009_00bb;
+const ERROR_JOURNAL_ENTRY_DELETED: i32 = 1181;
+
+#[repr(C)]
+#[derive(Default)]
+struct UsnJournalData {
+    journal_id: u64,
+    first_usn: i64,
+    next_usn: i64,
+    lowest_valid_usn: i64,
+    max_usn: i64,
+    maximum_size: u64,
+    allocation_delta: u64,
+}
+
+#[repr(C)]
+struct ReadUsnJournalData {
+    start_usn: i64,
+    reason_mask: u32,
+    return_only_on_close: u32,
+    timeout: u64,
+    bytes_to_wait_for: u64,
+    journal_id: u64,
+}
+
+#[repr(C)]
+struct FileIdDescriptor {
+    size: u32,
+    kind: u32,
+    id: [u64; 2],
+}
+
+extern "system" {
+    fn OpenFileById(
+        volume: HANDLE,
+        id: *const FileIdDescriptor,
+        access: u32,
+        share: u32,
+        security: *mut c_void,
+        flags: u32,
+    ) -> HANDLE;
+}
+
+struct Handle(HANDLE);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
+
+// 一条 USN 记录里需要的部分
+struct UsnChange {
+    parent_id: u64,
+    name: String,
+}
+
+pub(super) fn spawn(index: FileIndex) {
+    std::thread::spawn(move || {
+        let mut flusher = Flusher::new();
+        let mut fallback: HashMap<String, Instant> = HashMap::new();
+        loop {
+            for (root, building) in index.roots() {
+                if let Some(last) = fallback.get(&root) {
+                    // USN 不可用的目录定期完整重建
+                    if last.elapsed() >= FALLBACK_REBUILD_INTERVAL {
+                        index.rebuild_root(&root);
+                        fallback.insert(root, Instant::now());
+                    }
+                    continue;
+                }
+                match poll_root(&index, &root, building) {
+                    Ok(changed) => {
+                        if changed {
+                            flusher.mark_dirty();
+                        }
+                    }
+                    Err(e) => {
+                        index.set_error(&root, Some(format!("{}，改为定期完整重建", e)));
+                        fallback.insert(root, Instant::now());
+                    }
+                }
+            }
+            fallback.retain(|root, _| index.roots().iter().any(|(r, _)| r == root));
+            flusher.maybe_flush(&index);
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+// 读取自上次位置以来的变更并应用到索引，返回索引是否有变化
+fn poll_root(index: &FileIndex, root: &str, building: bool) -> Result<bool, String> {
+    let volume = open_volume(root)?;
+    let journal = query_journal(&volume)?;
+    let cursor = index.cursor(root);
+    let start = match cursor {
+        Some(c) if c.journal_id == journal.journal_id && c.position as i64 >= journal.first_usn => {
+            c.position as i64
+        }
+        Some(_) => {
+            // 日志被重建或记录已被覆盖，只能完整重建
+            index.rebuild_root(root);
+            return Ok(false);
+        }
+        None => {
+            index.set_cursor(
+                root,
+                Some(ChangeCursor {
+                    journal_id: journal.journal_id,
+                    position: journal.next_usn as u64,
+                }),
+            );
+            return Ok(false);
+        }
+    };
+    // 建立索引期间先不应用，完成后从记下的位置补上
+    if building || start >= journal.next_usn {
+        return Ok(false);
+    }
+
+    let (changes, next) = match read_changes(&volume, journal.journal_id, start) {
+        Ok(result) => result,
+        Err(code) if code == ERROR_JOURNAL_ENTRY_DELETED => {
+            index.rebuild_root(root);
+            return Ok(false);
+        }
+        Err(code) => {
+            return Err(format!(
+                "读取 USN 日志失败: {}",
+                std::io::Error::from_raw_os_error(code)
+            ))
+        }
+    };
+
+    let mut changed = false;
+    for path in change_paths(&changes, |id| resolve_path(&volume, id)) {
+        changed |= index.refresh_path(&path);
+    }
+    index.set_cursor(
+        root,
+        Some(ChangeCursor {
+            journal_id: journal.journal_id,
+            position: next as u64,
+        }),
+    );
+    Ok(changed)
+}
+
+// 打开目录所在卷，需要管理员权限
+fn open_volume(root: &str) -> Result<Handle, String> {
+    let drive = Path::new(root)
+        .components()
+        .next()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .filter(|c| c.ends_with(':'))
+        .ok_or_else(|| "仅支持带盘符的本地卷".to_string())?;
+    let wide = to_wide(&format!(r"\\.\{}", drive));
+    let handle = unsafe {
+        CreateFileW(
+            wide.as_ptr(),
+            GENERIC_READ,
+            FILE_SHARE_ALL,
+            null_mut(),
+            OPEN_EXISTING,
+            0,
+            null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(format!(
+            "无法打开卷 {}（需要管理员权限）: {}",
+            drive,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(Handle(handle))
+}
+
+fn query_journal(volume: &Handle) -> Result<UsnJournalData, String> {
+    let mut data = UsnJournalData::default();
+    let mut returned = 0u32;
+    let ok = unsafe {
+        DeviceIoControl(
+            volume.0,
+            FSCTL_QUERY_USN_JOURNAL,
+            null_mut(),
+            0,
+            &mut data as *mut _ as *mut c_void,
+            std::mem::size_of::<UsnJournalData>() as u32,
+            &mut returned,
+            null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(format!(
+            "该卷未启用 USN 日志或不是 NTFS: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(data)
+}
+
+// 从 start 开始读到日志末尾，返回变更和下一次的起始位置；失败时返回系统错误码
+fn read_changes(
+    volume: &Handle,
+    journal_id: u64,
+    mut start: i64,
+) -> Result<(Vec<UsnChange>, i64), i32> {
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut changes = Vec::new();
+    loop {
+        let request = ReadUsnJournalData {
+            start_usn: start,
+            reason_mask: u32::MAX,
+            return_only_on_close: 0,
+            timeout: 0,
+            bytes_to_wait_for: 0,
+            journal_id,
+        };
+        let mut returned = 0u32;
+        let ok = unsafe {
+            DeviceIoControl(
+                volume.0,
+                FSCTL_READ_USN_JOURNAL,
+                &request as *const _ as *mut c_void,
+                std::mem::size_of::<ReadUsnJournalData>() as u32,
+                buffer.as_mut_ptr() as *mut c_void,
+                buffer.len() as u32,
+                &mut returned,
+                null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(std::io::Error::last_os_error().raw_os_error().unwrap_or(0));
+        }
+        let returned = returned as usize;
+        if returned < 8 {
+            return Ok((changes, start));
+        }
+        let next = i64::from_le_bytes(buffer[0..8].try_into().unwrap());
+        parse_records(&buffer[8..returned], &mut changes);
+        if next == start || returned == 8 {
+            return Ok((changes, next));
+        }
+        start = next;
+    }
+}
+
+// 解析 USN_RECORD_V2 序列
+fn parse_records(mut data: &[u8], changes: &mut Vec<UsnChange>) {
+    let u16_at = |d: &[u8], i: usize| u16::from_le_bytes([d[i], d[i + 1]]) as usize;
+    while data.len() >= 60 {
+        let length = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+        if length < 60 || length > data.len() {
+            break;
+        }
+        let record = &data[..length];
+        let major = u16_at(record, 4);
+        if major == 2 {
+            let parent_id = u64::from_le_bytes(record[16..24].try_into().unwrap());
+            let name_len = u16_at(record, 56);
+            let name_offset = u16_at(record, 58);
+            if name_offset + name_len <= length {
+                let name: Vec<u16> = record[name_offset..name_offset + name_len]
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .collect();
+                changes.push(UsnChange {
+                    parent_id,
+                    name: String::from_utf16_lossy(&name),
+                });
+            }
+        }
+        data = &data[length..];
+    }
+}
+
+// 把记录中的父目录引用号解析为路径并拼上文件名，每个父目录只解析一次；
+// 无法解析的父目录（已删除或无权限）跳过
+fn change_paths(
+    changes: &[UsnChange],
+    mut resolve: impl FnMut(u64) -> Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut parents: HashMap<u64, Option<PathBuf>> = HashMap::new();
+    changes
+        .iter()
+        .filter_map(|change| {
+            let parent = parents
+                .entry(change.parent_id)
+                .or_insert_with(|| resolve(change.parent_id));
+            parent.as_ref().map(|p| p.join(&change.name))
+        })
+        .collect()
+}
+
+// 通过文件引用号取得目录的完整路径
+fn resolve_path(volume: &Handle, file_id: u64) -> Option<PathBuf> {
+    let descriptor = FileIdDescriptor {
+        size: std::mem::size_of::<FileIdDescriptor>() as u32,
+        kind: 0,
+        id: [file_id, 0],
+    };
+    let handle = unsafe {
+        OpenFileById(
+            volume.0,
+            &descriptor,
+            0,
+            FILE_SHARE_ALL,
+            null_mut(),
+            FILE_FLAG_BACKUP_SEMANTICS,
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    let handle = Handle(handle);
+    let mut buffer = vec![0u16; 1024];
+    let len =
+        unsafe { GetFinalPathNameByHandleW(handle.0, buffer.as_mut_ptr(), buffer.len() as u32, 0) }
+            as usize;
+    if len == 0 || len >= buffer.len() {
+        return None;
+    }
+    let path = String::from_utf16_lossy(&buffer[..len]);
+    Some(PathBuf::from(
+        path.strip_prefix(r"\\?\").unwrap_or(&path).to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按 USN_RECORD_V2 的布局编码一条记录，长度按 8 字节对齐
+    fn record(major: u16, parent_id: u64, name: &str) -> Vec<u8> {
+        let name: Vec<u8> = name.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let length = (60 + name.len()).div_ceil(8) * 8;
+        let mut bytes = vec![0u8; length];
+        bytes[0..4].copy_from_slice(&(length as u32).to_le_bytes());
+        bytes[4..6].copy_from_slice(&major.to_le_bytes());
+        bytes[16..24].copy_from_slice(&parent_id.to_le_bytes());
+        bytes[56..58].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes[58..60].copy_from_slice(&60u16.to_le_bytes());
+        bytes[60..60 + name.len()].copy_from_slice(&name);
+        bytes
+    }
+
+    #[test]
+    fn maps_records_to_paths_under_their_parent() {
+        let data = [
+            record(2, 7, "report.docx"),
+            record(2, 9, "照片.jpg"),
+            // 不支持的记录版本
+            record(3, 7, "v3.txt"),
+            record(2, 7, "notes.txt"),
+            // 父目录已删除
+            record(2, 404, "gone.txt"),
+        ]
+        .concat();
+        let mut changes = Vec::new();
+        parse_records(&data, &mut changes);
+
+        let dirs = HashMap::from([
+            (7u64, PathBuf::from(r"C:\Users\me\Documents")),
+            (9u64, PathBuf::from(r"D:\Photos")),
+        ]);
+        let mut lookups = Vec::new();
+        let paths = change_paths(&changes, |id| {
+            lookups.push(id);
+            dirs.get(&id).cloned()
+        });
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from(r"C:\Users\me\Documents").join("report.docx"),
+                PathBuf::from(r"D:\Photos").join("照片.jpg"),
+                PathBuf::from(r"C:\Users\me\Documents").join("notes.txt"),
+            ]
+        );
+        assert_eq!(lookups, [7, 9, 404]);
+    }
+
+    #[test]
+    fn stops_at_a_truncated_record() {
+        let mut data = record(2, 7, "first.txt");
+        let second = record(2, 7, "second.txt");
+        data.extend(&second[..second.len() - 8]);
+        let mut changes = Vec::new();
+        parse_records(&data, &mut changes);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].parent_id, 7);
+        assert_eq!(changes[0].name, "first.txt");
+    }
+}
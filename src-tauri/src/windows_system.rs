@@ -0,0 +1,240 @@
+// Windows 自身占用的大目录：Installer、DriverStore 与 WinSxS。给出能否手动处理的说明，
+// 并通过 DISM 分析和清理组件存储。DISM 需要管理员权限，由提权辅助进程执行
+use crate::drives::volume_space;
+use crate::platform;
+use crate::utils::{dir_size, human_readable_size};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemFolderKind {
+    /// Windows\Installer，已安装程序的 MSI 与补丁缓存
+    Installer,
+    /// System32\DriverStore\FileRepository，驱动程序包
+    DriverStore,
+    /// Windows\WinSxS，组件存储
+    WinSxS,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TouchSafety {
+    /// 不能手动删除任何内容
+    DoNotTouch,
+    /// 只能通过系统工具移除
+    ToolOnly,
+    /// 只能通过 DISM 清理
+    DismOnly,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemFolder {
+    pub kind: SystemFolderKind,
+    pub path: String,
+    /// 按文件统计的大小，WinSxS 中大量硬链接与系统目录共享，实际占用远小于此值
+    pub size_raw: u64,
+    pub size_display: String,
+    pub safety: TouchSafety,
+    pub guidance: String,
+}
+
+// DISM /AnalyzeComponentStore 的结果
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComponentStoreReport {
+    /// 资源管理器显示的大小，重复计算了硬链接
+    pub explorer_reported_bytes: Option<u64>,
+    pub actual_bytes: Option<u64>,
+    /// 与 Windows 目录共享的硬链接，无法释放
+    pub shared_with_windows_bytes: Option<u64>,
+    /// 被替换组件的备份和已禁用的功能，清理后释放
+    pub backups_bytes: Option<u64>,
+    pub cache_bytes: Option<u64>,
+    pub reclaimable_packages: Option<u32>,
+    pub cleanup_recommended: bool,
+}
+
+fn windows_dir() -> PathBuf {
+    std::env::var_os("SystemRoot")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\Windows"))
+}
+
+impl SystemFolderKind {
+    pub fn path(self) -> PathBuf {
+        let windows = windows_dir();
+        match self {
+            SystemFolderKind::Installer => windows.join("Installer"),
+            SystemFolderKind::DriverStore => windows
+                .join("System32")
+                .join("DriverStore")
+                .join("FileRepository"),
+            SystemFolderKind::WinSxS => windows.join("WinSxS"),
+        }
+    }
+
+    fn safety(self) -> TouchSafety {
+        match self {
+            SystemFolderKind::Installer => TouchSafety::DoNotTouch,
+            SystemFolderKind::DriverStore => TouchSafety::ToolOnly,
+            SystemFolderKind::WinSxS => TouchSafety::DismOnly,
+        }
+    }
+
+    fn guidance(self) -> &'static str {
+        match self {
+            SystemFolderKind::Installer => {
+                "已安装程序的卸载、修复和更新都依赖这里的 MSI 与补丁文件，手动删除会导致程序无法卸载或更新。\
+                 只有不再被任何程序引用的孤立补丁可以用专门的工具移除"
+            }
+            SystemFolderKind::DriverStore => {
+                "驱动程序包，删除正在使用的驱动会导致设备失效。\
+                 旧版本驱动可通过磁盘清理的“设备驱动程序包”或 pnputil /delete-driver 移除"
+            }
+            SystemFolderKind::WinSxS => {
+                "组件存储，其中大部分文件是系统目录的硬链接，实际占用远小于显示的大小。\
+                 不能手动删除，可用 DISM 清理被替换组件的备份"
+            }
+        }
+    }
+}
+
+// 统计三个系统目录的大小；普通权限无法读取的部分不计入，可改用管理员辅助进程统计
+pub fn system_folders() -> Result<Vec<SystemFolder>, String> {
+    if !cfg!(windows) {
+        return Err("仅支持 Windows".to_string());
+    }
+    Ok([
+        SystemFolderKind::Installer,
+        SystemFolderKind::DriverStore,
+        SystemFolderKind::WinSxS,
+    ]
+    .into_iter()
+    .map(|kind| {
+        let path = kind.path();
+        let size_raw = dir_size(&path);
+        SystemFolder {
+            kind,
+            path: path.to_string_lossy().into_owned(),
+            size_raw,
+            size_display: human_readable_size(size_raw),
+            safety: kind.safety(),
+            guidance: kind.guidance().to_string(),
+        }
+    })
+    .collect())
+}
+
+// 以下在管理员辅助进程中执行
+
+pub(crate) fn analyze_component_store() -> Result<ComponentStoreReport, String> {
+    let output = run_dism(&["/AnalyzeComponentStore"])?;
+    Ok(parse_component_store(&output))
+}
+
+// 清理被替换组件的备份；reset_base 同时移除所有被替换的组件版本，之后无法卸载已安装的更新。
+// 返回系统盘可用空间的增加量
+pub(crate) fn start_component_cleanup(reset_base: bool) -> Result<u64, String> {
+    let windows = windows_dir();
+    let before = volume_space(&windows).map(|(_, free)| free);
+    let mut args = vec!["/StartComponentCleanup"];
+    if reset_base {
+        args.push("/ResetBase");
+    }
+    run_dism(&args)?;
+    let after = volume_space(&windows).map(|(_, free)| free);
+    Ok(match (before, after) {
+        (Some(before), Some(after)) => after.saturating_sub(before),
+        _ => 0,
+    })
+}
+
+fn run_dism(args: &[&str]) -> Result<String, String> {
+    if !cfg!(windows) {
+        return Err("DISM 仅支持 Windows".to_string());
+    }
+    // /English 使输出不随系统语言变化，便于解析
+    let output = platform::command("dism")
+        .args(["/English", "/Online", "/Cleanup-Image"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("无法运行 DISM: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        let message = stdout
+            .lines()
+            .find(|l| l.starts_with("Error"))
+            .unwrap_or("DISM 执行失败");
+        return Err(message.trim().to_string());
+    }
+    Ok(stdout)
+}
+
+fn parse_component_store(output: &str) -> ComponentStoreReport {
+    let mut report = ComponentStoreReport::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Windows Explorer Reported Size of Component Store" => {
+                report.explorer_reported_bytes = parse_dism_size(value)
+            }
+            "Actual Size of Component Store" => report.actual_bytes = parse_dism_size(value),
+            "Shared with Windows" => report.shared_with_windows_bytes = parse_dism_size(value),
+            "Backups and Disabled Features" => report.backups_bytes = parse_dism_size(value),
+            "Cache and Temporary Data" => report.cache_bytes = parse_dism_size(value),
+            "Number of Reclaimable Packages" => report.reclaimable_packages = value.parse().ok(),
+            "Component Store Cleanup Recommended" => {
+                report.cleanup_recommended = value.eq_ignore_ascii_case("yes")
+            }
+            _ => {}
+        }
+    }
+    report
+}
+
+// DISM 的大小形如 "8.99 GB"、"310.29 MB"，按 1024 进制
+fn parse_dism_size(value: &str) -> Option<u64> {
+    let (number, unit) = value.split_once(' ')?;
+    let number: f64 = number.trim().parse().ok()?;
+    let scale = match unit.trim() {
+        "bytes" | "B" => 1u64,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * scale as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_analyze_component_store() {
+        let output = "Component Store (WinSxS) information:\n\n\
+            Windows Explorer Reported Size of Component Store : 9.28 GB\n\n\
+            Actual Size of Component Store : 8.99 GB\n\n\
+            \x20   Shared with Windows : 6.02 GB\n\
+            \x20   Backups and Disabled Features : 2.66 GB\n\
+            \x20   Cache and Temporary Data :  310.29 MB\n\n\
+            Date of Last Cleanup : 2024-03-01 10:12:45\n\n\
+            Number of Reclaimable Packages : 2\n\
+            Component Store Cleanup Recommended : Yes\n";
+        let report = parse_component_store(output);
+        assert_eq!(
+            report.backups_bytes,
+            Some((2.66 * (1u64 << 30) as f64) as u64)
+        );
+        assert_eq!(
+            report.cache_bytes,
+            Some((310.29 * (1u64 << 20) as f64) as u64)
+        );
+        assert_eq!(report.reclaimable_packages, Some(2));
+        assert!(report.cleanup_recommended);
+    }
+}
@@ -7,14 +7,17 @@ import { Input } from "@/components/ui/input"
 import { Badge } from "@/components/ui/badge"
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table"
 import { Separator } from "@/components/ui/separator"
-import { FolderOpen, File, RefreshCw, FolderSearch, Moon, Sun, HardDrive, Settings, Clock, Files, Loader2, X } from "lucide-react"
+import { FolderOpen, File, RefreshCw, FolderSearch, Moon, Sun, HardDrive, Settings, Clock, Files, Loader2, X, Tag } from "lucide-react"
 import { open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
-import { listen, UnlistenFn } from '@tauri-apps/api/event';
-import { conversionTime } from 'sunrise-utils'
+import { UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { sendNotification } from '@tauri-apps/plugin-notification';
 import { cn } from "./lib/utils"
+import { SizeFormat, TimeFormat, defaultSizeFormat, defaultTimeFormat, formatSize, groupDigits } from "./lib/format"
 import { SettingsDialog } from "@/components/settings-dialog"
 import { FileActions } from '@/components/file-actions'
+import { TagEditor, PathAnnotation } from '@/components/tag-editor'
 interface ICreatedTime {
   nanos_since_epoch: number
   secs_since_epoch: number
@@ -24,31 +27,64 @@ interface FileItem {
   file_type: string
   permissions: string
   size_raw: number
+  // size_raw 的精确十进制值，超过 2^53 时 number 会丢失精度
+  size_raw_exact: string
   size_display: string
   path: string
   name: string
+  // name 的 NFC 形式，显示时使用；文件操作仍使用 path
+  display_name?: string
   created_time: ICreatedTime
+  // 按设置中的时间格式由后端生成
+  created_display: string
+  modified_display: string
 }
 
+// 与后端 models::Completeness 对应，按 kind 区分
+type Completeness =
+  | { kind: "complete" }
+  | { kind: "partial"; reason: string }
+  | { kind: "estimated" }
+
 interface DirectoryResult {
+  scan_id: number | null,
   entries: FileItem[],
   query_time: number
+  completeness: Completeness
+  skipped: { permission_denied: number; timed_out: number; known_inaccessible: number }
 }
 
+// 与后端 ProgressStatus 对应，按 type 区分
+type ProgressStatus =
+  | { type: "entering_dir" }
+  | { type: "file_processed"; count: number; bytes: number; total: number | null }
+  | { type: "dir_completed"; size: number }
+  | { type: "stage"; stage: string; done: number; total: number }
+  | { type: "error"; kind: "permission_denied" | "not_found" | "other" }
+
 // 进度事件接口
 interface ProgressEvent {
   current_path: string
   current_file: string
-  status: string
+  status: ProgressStatus
+  context: string | null
+  scan_id: number | null
+}
+
+function formatBytes(bytes: number | bigint, humanReadable: boolean, format: SizeFormat): string {
+  if (!humanReadable) return `${groupDigits(bytes, format.thousands_separator)}B`
+  return formatSize(bytes, format)
+}
+
+type NameCollation = "natural" | "pinyin"
+
+// 与后端的名称排序规则一致：数字按数值比较，pinyin 时汉字按拼音排序
+function nameCollator(collation: NameCollation): Intl.Collator {
+  return new Intl.Collator(collation === "pinyin" ? "zh-u-co-pinyin" : undefined, { numeric: true })
 }
 
-function formatBytes(bytes: number, humanReadable: boolean): string {
-  if (!humanReadable) return `${bytes}B`
-  if (bytes === 0) return "0B"
-  const k = 1024
-  const sizes = ["B", "KB", "MB", "GB", "TB"]
-  const i = Math.floor(Math.log(bytes) / Math.log(k))
-  return `${Number.parseFloat((bytes / Math.pow(k, i)).toFixed(1))}${sizes[i]}`
+function exactSize(file: FileItem): bigint {
+  return BigInt(file.size_raw_exact ?? file.size_raw)
 }
 
 export default function DiskSight() {
@@ -62,6 +98,8 @@ export default function DiskSight() {
   const [files, setFiles] = useState<FileItem[]>([])
   const [currentPath, setCurrentPath] = useState("")
   const [refreshTime, setRefreshTime] = useState(0)
+  // 当前结果的完整性，不完整时在统计栏显示标记
+  const [completeness, setCompleteness] = useState<Completeness | null>(null)
   const [isRefreshing, setIsRefreshing] = useState(false)
   const [isLoading, setIsLoading] = useState(false)
   const [scanProgress, setScanProgress] = useState<ProgressEvent | null>(null)
@@ -69,41 +107,134 @@ export default function DiskSight() {
   // 是否开启文件扫描详情
   const [showScanDetails, setShowScanDetails] = useState(false)
   const [settingsOpen, setSettingsOpen] = useState(false)
+  const [sizeFormat, setSizeFormat] = useState<SizeFormat>(defaultSizeFormat)
+  const [timeFormat, setTimeFormat] = useState<TimeFormat>(defaultTimeFormat)
+  const [nameCollation, setNameCollation] = useState<NameCollation>("natural")
+
+  // 大小显示格式保存在后端配置中，后端生成的 size_display 也使用同一格式
+  useEffect(() => {
+    invoke<{ size_format: SizeFormat; time_format: TimeFormat; name_collation: NameCollation }>("get_config")
+      .then((config) => {
+        setSizeFormat(config.size_format)
+        setTimeFormat(config.time_format)
+        setNameCollation(config.name_collation)
+        // 时区以当前系统为准，偏移变化（如夏令时）时同步给后端
+        const offset = -new Date().getTimezoneOffset()
+        if (config.time_format.utc_offset_minutes !== offset) {
+          const format = { ...config.time_format, utc_offset_minutes: offset }
+          setTimeFormat(format)
+          invoke("set_time_format", { format }).catch((e) => console.error("保存时间格式失败:", e))
+        }
+      })
+      .catch((e) => console.error("读取配置失败:", e))
+  }, [])
+
+  const handleSizeFormatChange = useCallback(async (format: SizeFormat) => {
+    setSizeFormat(format)
+    try {
+      await invoke("set_size_format", { format })
+    } catch (e) {
+      console.error("保存大小格式失败:", e)
+    }
+  }, [])
+
+  // 已显示的列表在下次扫描后按新格式显示
+  const handleTimeFormatChange = useCallback(async (format: TimeFormat) => {
+    setTimeFormat(format)
+    try {
+      await invoke("set_time_format", { format })
+    } catch (e) {
+      console.error("保存时间格式失败:", e)
+    }
+  }, [])
 
   // 新增状态：历史记录和文件详情
   const [history, setHistory] = useState<string[]>([])
   const [historyIndex, setHistoryIndex] = useState(-1)
   const [showFileDetail, setShowFileDetail] = useState(false)
   const [selectedFile, setSelectedFile] = useState<FileItem | null>(null)
+  // 路径的标签与备注，保存在后端配置中，跨扫描保留
+  const [annotations, setAnnotations] = useState<Map<string, PathAnnotation>>(new Map())
+  const [tagFilter, setTagFilter] = useState<string | null>(null)
+  const [taggingPath, setTaggingPath] = useState<string | null>(null)
+
+  const loadAnnotations = useCallback(() => {
+    invoke<PathAnnotation[]>("list_tags", { tag: null })
+      .then((list) => setAnnotations(new Map(list.map((a) => [a.path, a]))))
+      .catch((e) => console.error("读取标签失败:", e))
+  }, [])
+
+  useEffect(() => {
+    loadAnnotations()
+  }, [loadAnnotations])
+
+  const allTags = useMemo(() => {
+    const tags = new Set<string>()
+    annotations.forEach((a) => a.tags.forEach((t) => tags.add(t)))
+    return [...tags].sort()
+  }, [annotations])
+  // 监听某次扫描的事件频道 scan://{id}/...，只收到该扫描的事件，多个视图同时扫描时互不干扰
+  const listenToScan = useCallback(async (scanId: number): Promise<UnlistenFn> => {
+    const appWindow = getCurrentWebviewWindow();
+    const channel = `scan://${scanId}`;
+    const unlisteners = await Promise.all([
+      appWindow.listen(`${channel}/started`, () => {
+        setIsLoading(true);
+        setError(null);
+        setScanProgress(null);
+      }),
+      appWindow.listen(`${channel}/progress`, (event: { payload: ProgressEvent }) => {
+        setScanProgress(event.payload);
+      }),
+      appWindow.listen(`${channel}/completed`, () => {
+        setIsLoading(false);
+        setScanProgress(null);
+      }),
+      appWindow.listen(`${channel}/error`, (event) => {
+        setIsLoading(false);
+        setScanProgress(null);
+        setError(event.payload as string);
+      }),
+      // 扫描工作进程异常退出后已自动重启，扫描从头开始
+      appWindow.listen(`${channel}/worker_restarted`, (event: { payload: number }) => {
+        setScanProgress(null);
+        console.warn(`扫描进程已重启（第 ${event.payload} 次）`);
+      }),
+      // 之前反复无法访问的路径本次已跳过，可通过 reset_skip_list 重新尝试
+      appWindow.listen(`${channel}/skipped_known`, (event: { payload: string[] }) => {
+        console.info(`已跳过 ${event.payload.length} 个已知无法访问的路径`, event.payload);
+      }),
+    ]);
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  }, []);
+
   // 事件监听
   useEffect(() => {
-    let unlistenStarted: UnlistenFn | undefined;
-    let unlistenProgress: UnlistenFn | undefined;
-    let unlistenCompleted: UnlistenFn | undefined;
-    let unlistenError: UnlistenFn | undefined;
+    let unlistenForecast: UnlistenFn | undefined;
+    let unlistenStalled: UnlistenFn | undefined;
+    let unlistenStartup: UnlistenFn | undefined;
 
+    const appWindow = getCurrentWebviewWindow();
     const setupListeners = async () => {
       try {
-        unlistenStarted = await listen('scan-started', () => {
-          setIsLoading(true);
-          setError(null);
-          setScanProgress(null);
-        });
-
-        unlistenProgress = await listen('scan-progress', (event: { payload: ProgressEvent }) => {
-          setScanProgress(event.payload);
+        // 监控目录所在卷预计即将写满
+        unlistenForecast = await appWindow.listen('forecast-alert', async (event: { payload: { alert: string | null } }) => {
+          if (event.payload.alert) {
+            await sendNotification({ title: '磁盘空间预警', body: event.payload.alert });
+          }
         });
-
-        unlistenCompleted = await listen('scan-completed', () => {
-          setIsLoading(false);
-          setScanProgress(null);
+        // 扫描或分析长时间没有进度，通常是网络挂载失去响应
+        unlistenStalled = await appWindow.listen('scan-stalled', (event: { payload: { last_path: string; stalled_seconds: number } }) => {
+          setError(`已有 ${event.payload.stalled_seconds} 秒没有进度，可能卡在: ${event.payload.last_path}`);
         });
-
-        unlistenError = await listen('scan-error', (event) => {
-          setIsLoading(false);
-          setScanProgress(null);
-          setError(event.payload as string);
+        // 启动时的初始化错误，主窗口打开前发生的通过 startup_errors 补齐
+        unlistenStartup = await appWindow.listen('startup-error', (event: { payload: { message: string } }) => {
+          setError(`初始化失败: ${event.payload.message}`);
         });
+        const startupErrors = await invoke<{ message: string }[]>('startup_errors');
+        if (startupErrors.length > 0) {
+          setError(`初始化失败: ${startupErrors.map((e) => e.message).join('; ')}`);
+        }
       } catch (error) {
         console.error('Failed to setup event listeners:', error);
       }
@@ -112,10 +243,9 @@ export default function DiskSight() {
     setupListeners();
 
     return () => {
-      unlistenStarted?.();
-      unlistenProgress?.();
-      unlistenCompleted?.();
-      unlistenError?.();
+      unlistenForecast?.();
+      unlistenStalled?.();
+      unlistenStartup?.();
     };
   }, []);
 
@@ -124,26 +254,36 @@ export default function DiskSight() {
     if (!showHiddenFiles) {
       result = result.filter((f) => !f.name.startsWith("."))
     }
+    if (tagFilter) {
+      result = result.filter((f) => annotations.get(f.path)?.tags.includes(tagFilter))
+    }
     if (sortBySize) {
-      result.sort((a, b) => b.size_raw - a.size_raw)
+      result.sort((a, b) => {
+        const diff = exactSize(b) - exactSize(a)
+        return diff > 0n ? 1 : diff < 0n ? -1 : 0
+      })
+    } else {
+      const collator = nameCollator(nameCollation)
+      result.sort((a, b) => collator.compare(a.display_name ?? a.name, b.display_name ?? b.name))
     }
     return result.length > 0 ? result : []
-  }, [files, showHiddenFiles, sortBySize])
+  }, [files, showHiddenFiles, sortBySize, tagFilter, annotations, nameCollation])
 
   const totalSize = useMemo(() => {
-    return filteredFiles.reduce((acc, f) => acc + f.size_raw, 0)
+    return filteredFiles.reduce((acc, f) => acc + exactSize(f), 0n)
   }, [filteredFiles])
 
   const handleRefresh = useCallback(() => {
     console.log("Refreshing...", showScanDetails)
     if (currentPath) {
       setIsRefreshing(true)
-      fetchDirectory(currentPath, showScanDetails)
+      fetchDirectory(currentPath, showScanDetails, false)
       setTimeout(() => setIsRefreshing(false), 500)
     }
   }, [currentPath, showScanDetails])
 
-  const fetchDirectory = useCallback(async (path: string, showDetails: boolean = false) => {
+  // useWarm 为 true 时优先使用启动时的预扫描结果，刷新时传 false 强制重新扫描
+  const fetchDirectory = useCallback(async (path: string, showDetails: boolean = false, useWarm: boolean = true) => {
     if (!path) return
 
     setIsLoading(true)
@@ -151,10 +291,22 @@ export default function DiskSight() {
     let result: DirectoryResult
     console.log("Fetching directory:", path, showScanDetails)
     try {
-      if (showDetails) {
-        result = await invoke<DirectoryResult>("get_list_directory", {
-          path,
-        })
+      const warm = useWarm ? await invoke<DirectoryResult | null>("take_warm_scan", { path }) : null
+      if (warm) {
+        result = warm
+        setIsLoading(false)
+      } else if (showDetails) {
+        // 先预留扫描 ID 并订阅其事件，再开始扫描，避免错过开始阶段的事件
+        const scanId = await invoke<number>("subscribe_scan", { id: null })
+        const unlisten = await listenToScan(scanId)
+        try {
+          result = await invoke<DirectoryResult>("get_list_directory", {
+            path,
+            scanId,
+          })
+        } finally {
+          unlisten()
+        }
       } else {
         result = await invoke<DirectoryResult>("calculate_dir_size_simple_fast", {
           path,
@@ -167,11 +319,13 @@ export default function DiskSight() {
       setCurrentPath(path)
       console.log("Directory fetched:", result)
       setRefreshTime(Number(result.query_time.toFixed(2)))
+      // 部分目录未能统计时标记出来，避免把偏小的大小当作完整结果
+      setCompleteness(result.completeness ?? null)
     } catch (err) {
       console.error("Failed to fetch directory:", err)
       setError(err instanceof Error ? err.message : "获取目录失败")
     }
-  }, [parallelProcessing, humanReadableSize, showHiddenFiles, sortBySize, showTimeInfo, showFullPath])
+  }, [parallelProcessing, humanReadableSize, showHiddenFiles, sortBySize, showTimeInfo, showFullPath, listenToScan])
 
   // 选择目录
   const handleSelectFile = async () => {
@@ -197,20 +351,30 @@ export default function DiskSight() {
   }
 
   // 获取状态显示文本
-  const getStatusText = (status: string) => {
-    const statusMap: Record<string, string> = {
-      'processing': '处理中',
-      'processing_batch': '批量处理中',
-      'calculating_directory_size': '计算目录大小',
-      'directory_calculation_completed': '目录计算完成',
-      'completed': '完成',
-      'searching_in_directory': '在目录中搜索',
-      'checking_file': '检查文件',
-      'calculating_matching_directory': '计算匹配目录',
-      'matching_directory_completed': '匹配目录完成',
-      'processing_file': '处理文件'
+  const getStatusText = (status: ProgressStatus) => {
+    switch (status.type) {
+      case 'entering_dir':
+        return '读取目录'
+      case 'file_processed':
+        return status.total === null
+          ? `已处理 ${status.count} 项`
+          : `已处理 ${status.count}/${status.total} 项，${formatBytes(status.bytes, true, sizeFormat)}`
+      case 'dir_completed':
+        return `目录计算完成，${formatBytes(status.size, true, sizeFormat)}`
+      case 'stage':
+        // 两阶段扫描：count 阶段总数未知，size 阶段按统计的总数显示百分比
+        if (status.stage === 'count') return `统计条目 ${status.done} 项`
+        if (status.stage === 'size' && status.total > 0) {
+          return `计算大小 ${Math.floor((status.done * 100) / status.total)}%（${status.done}/${status.total}）`
+        }
+        return `${status.stage} ${status.done}/${status.total}`
+      case 'error':
+        return {
+          permission_denied: '无权限访问',
+          not_found: '文件不存在',
+          other: '读取失败',
+        }[status.kind]
     }
-    return statusMap[status] || status
   }
 
   // 处理表格行点击
@@ -301,7 +465,7 @@ export default function DiskSight() {
           <div className="flex items-center gap-1.5">
             <HardDrive className="h-3.5 w-3.5 text-chart-2" />
             <span className="text-muted-foreground">总大小:</span>
-            <span className="font-semibold">{formatBytes(totalSize, humanReadableSize)}</span>
+            <span className="font-semibold">{formatBytes(totalSize, humanReadableSize, sizeFormat)}</span>
           </div>
           <Separator orientation="vertical" className="h-4" />
           <div className="flex items-center gap-1.5">
@@ -309,6 +473,18 @@ export default function DiskSight() {
             <span className="text-muted-foreground">耗时:</span>
             <span className="font-semibold">{refreshTime}s</span>
           </div>
+          {completeness && completeness.kind !== "complete" && (
+            <>
+              <Separator orientation="vertical" className="h-4" />
+              <Badge
+                variant="outline"
+                className="h-5 px-1.5 text-[10px]"
+                title={completeness.kind === "partial" ? completeness.reason : "快速统计不记录无法读取的目录"}
+              >
+                {completeness.kind === "partial" ? "部分结果" : "估算值"}
+              </Badge>
+            </>
+          )}
         </div>
 
         {/* Options Panel */}
@@ -375,6 +551,18 @@ export default function DiskSight() {
                 />
                 <span>大小排序</span>
               </label>
+              <label className="flex items-center gap-1.5 cursor-pointer">
+                <Checkbox
+                  checked={nameCollation === "pinyin"}
+                  onCheckedChange={(checked) => {
+                    const collation: NameCollation = checked ? "pinyin" : "natural"
+                    setNameCollation(collation)
+                    invoke("set_name_collation", { collation }).catch((e) => console.error("保存排序规则失败:", e))
+                  }}
+                  className="h-3.5 w-3.5"
+                />
+                <span>拼音排序</span>
+              </label>
               <label className="flex items-center gap-1.5 cursor-pointer">
                 <Checkbox
                   checked={showScanDetails}
@@ -449,6 +637,30 @@ export default function DiskSight() {
           </Button>
         </div>
 
+        {/* 按标签筛选 */}
+        {allTags.length > 0 && (
+          <div className="flex items-center gap-1.5 px-4 py-1.5 border-b border-border">
+            <Tag className="h-3 w-3 text-muted-foreground" />
+            <Badge
+              variant={tagFilter === null ? "default" : "outline"}
+              className="h-5 px-1.5 text-[10px] cursor-pointer"
+              onClick={() => setTagFilter(null)}
+            >
+              全部
+            </Badge>
+            {allTags.map((tag) => (
+              <Badge
+                key={tag}
+                variant={tagFilter === tag ? "default" : "outline"}
+                className="h-5 px-1.5 text-[10px] cursor-pointer"
+                onClick={() => setTagFilter(tagFilter === tag ? null : tag)}
+              >
+                {tag}
+              </Badge>
+            ))}
+          </div>
+        )}
+
         {/* 错误提示 */}
         {error && (
           <div className="mx-4 mt-2 p-3 bg-destructive/10 border border-destructive/20 rounded-lg">
@@ -576,23 +788,46 @@ export default function DiskSight() {
                       <code className="text-[10px] font-mono text-muted-foreground">{file.permissions}</code>
                     </TableCell>
                     <TableCell className="py-1.5 px-3 text-right font-mono text-xs tabular-nums">
-                      {humanReadableSize ? file.size_display : formatBytes(file.size_raw, false)}
+                      {humanReadableSize ? file.size_display : formatBytes(exactSize(file), false, sizeFormat)}
                     </TableCell>
                     {showTimeInfo && (
                       <TableCell className="py-1.5 px-3 text-xs text-muted-foreground">
-                        {conversionTime(file.created_time.secs_since_epoch)}
+                        {file.created_display}
                       </TableCell>
                     )}
                     <TableCell className="py-1.5 px-3">
                       <span
                         className="font-mono text-xs group-hover:text-primary transition-colors truncate block max-w-[300px]"
-                        title={showFullPath ? file.path : file.name}
+                        title={showFullPath ? file.path : file.display_name ?? file.name}
                       >
-                        {showFullPath ? file.path : file.name}
+                        {showFullPath ? file.path : file.display_name ?? file.name}
                       </span>
+                      {annotations.has(file.path) && (
+                        <div className="flex flex-wrap gap-1 mt-0.5" title={annotations.get(file.path)?.note ?? undefined}>
+                          {annotations.get(file.path)?.tags.map((tag) => (
+                            <Badge key={tag} variant="secondary" className="h-4 px-1 text-[9px]">
+                              {tag}
+                            </Badge>
+                          ))}
+                          {annotations.get(file.path)?.note && (
+                            <span className="text-[10px] text-muted-foreground truncate max-w-[200px]">
+                              {annotations.get(file.path)?.note}
+                            </span>
+                          )}
+                        </div>
+                      )}
                     </TableCell>
                     <TableCell className="py-1.5 px-3 w-10">
-                      <div onClick={(e) => e.stopPropagation()}>
+                      <div className="flex items-center gap-1" onClick={(e) => e.stopPropagation()}>
+                        <Button
+                          variant="outline"
+                          size="sm"
+                          className="h-8 w-8 p-0"
+                          title="标签与备注"
+                          onClick={() => setTaggingPath(file.path)}
+                        >
+                          <Tag className="h-3.5 w-3.5" />
+                        </Button>
                         <FileActions filePath={file.path} onRefresh={handleRefresh}></FileActions>
                       </div>
                     </TableCell>
@@ -610,6 +845,13 @@ export default function DiskSight() {
           <span>© 2025 All rights reserved</span>
         </div>
       </div>
+      <TagEditor
+        open={taggingPath !== null}
+        onOpenChange={(open) => !open && setTaggingPath(null)}
+        path={taggingPath ?? ""}
+        annotation={taggingPath ? annotations.get(taggingPath) : undefined}
+        onSaved={loadAnnotations}
+      />
       {/* Settings Dialog */}
       <SettingsDialog
         open={settingsOpen}
@@ -622,6 +864,10 @@ export default function DiskSight() {
         onParallelByDefaultChange={setParallelProcessing}
         showHiddenByDefault={showHiddenFiles}
         onShowHiddenByDefaultChange={setShowHiddenFiles}
+        sizeFormat={sizeFormat}
+        onSizeFormatChange={handleSizeFormatChange}
+        timeFormat={timeFormat}
+        onTimeFormatChange={handleTimeFormatChange}
       />
 
       {/* 文件详情弹窗 */}
@@ -638,7 +884,7 @@ export default function DiskSight() {
             <div className="space-y-3 text-xs">
               <div className="flex justify-between">
                 <span className="text-muted-foreground">名称:</span>
-                <span className="font-medium">{selectedFile.name}</span>
+                <span className="font-medium">{selectedFile.display_name ?? selectedFile.name}</span>
               </div>
               <div className="flex justify-between">
                 <span className="text-muted-foreground">类型:</span>
@@ -653,7 +899,7 @@ export default function DiskSight() {
               <div className="flex justify-between">
                 <span className="text-muted-foreground">大小:</span>
                 <span className="font-medium font-mono">
-                  {humanReadableSize ? selectedFile.size_display : formatBytes(selectedFile.size_raw, false)}
+                  {humanReadableSize ? selectedFile.size_display : formatBytes(exactSize(selectedFile), false, sizeFormat)}
                 </span>
               </div>
               <div className="flex justify-between">
@@ -662,10 +908,14 @@ export default function DiskSight() {
               </div>
               <div className="flex justify-between">
                 <span className="text-muted-foreground">创建时间:</span>
-                <span className="font-medium">
-                  {conversionTime(selectedFile.created_time.secs_since_epoch)}
-                </span>
+                <span className="font-medium">{selectedFile.created_display}</span>
               </div>
+              {selectedFile.modified_display && (
+                <div className="flex justify-between">
+                  <span className="text-muted-foreground">修改时间:</span>
+                  <span className="font-medium">{selectedFile.modified_display}</span>
+                </div>
+              )}
             </div>
 
             <div className="mt-6 flex justify-end gap-2">
@@ -21,6 +21,7 @@ import { Slider } from "@/components/ui/slider"
 import { Separator } from "@/components/ui/separator"
 import { Settings, Monitor, Palette, Cog, RotateCcw, Save } from "lucide-react"
 import { cn } from "@/lib/utils"
+import type { SizeFormat, SizeUnits, TimeFormat } from "@/lib/format"
 import {
     Select,
     SelectContent,
@@ -29,6 +30,7 @@ import {
     SelectValue,
 } from "@/components/ui/select"
 import { moveWindow, Position } from '@tauri-apps/plugin-positioner';
+import { invoke } from '@tauri-apps/api/core';
 const isTauri = typeof window !== "undefined" && "__TAURI__" in window
 
 
@@ -60,6 +62,12 @@ interface AppConfig {
     language: string
 }
 
+// 与后端 warm_cache::WarmCacheSettings 对应
+interface WarmCacheSettings {
+    enabled: boolean
+    skip_on_battery: boolean
+}
+
 interface SettingsDialogProps {
     open?: boolean
     onOpenChange?: (open: boolean) => void
@@ -71,6 +79,10 @@ interface SettingsDialogProps {
     onParallelByDefaultChange: (value: boolean) => void
     showHiddenByDefault: boolean
     onShowHiddenByDefaultChange: (value: boolean) => void
+    sizeFormat: SizeFormat
+    onSizeFormatChange: (value: SizeFormat) => void
+    timeFormat: TimeFormat
+    onTimeFormatChange: (value: TimeFormat) => void
     trigger?: React.ReactNode
 }
 
@@ -85,6 +97,10 @@ export function SettingsDialog({
     onParallelByDefaultChange,
     showHiddenByDefault,
     onShowHiddenByDefaultChange,
+    sizeFormat,
+    onSizeFormatChange,
+    timeFormat,
+    onTimeFormatChange,
     trigger,
 }: SettingsDialogProps) {
     const [windowConfig, setWindowConfig] = useState<WindowConfig>({
@@ -105,6 +121,22 @@ export function SettingsDialog({
     })
 
     const [isLoading, setIsLoading] = useState(false)
+    const [warmCache, setWarmCache] = useState<WarmCacheSettings>({ enabled: false, skip_on_battery: true })
+
+    useEffect(() => {
+        if (open && isTauri) {
+            invoke<{ warm_cache: WarmCacheSettings }>("get_config")
+                .then((config) => setWarmCache(config.warm_cache))
+                .catch((err) => console.error("读取预扫描设置失败:", err))
+        }
+    }, [open])
+
+    // 预扫描设置直接保存到后端，下次启动时生效
+    const updateWarmCache = (patch: Partial<WarmCacheSettings>) => {
+        const settings = { ...warmCache, ...patch }
+        setWarmCache(settings)
+        invoke("set_warm_cache", { settings }).catch((err) => console.error("保存预扫描设置失败:", err))
+    }
 
     // Fetch current window position and size from Tauri
     const fetchWindowConfig = useCallback(async () => {
@@ -357,13 +389,105 @@ export function SettingsDialog({
                                         variant={appConfig.language === lang.value ? "default" : "outline"}
                                         size="sm"
                                         className="h-8 text-xs"
-                                        onClick={() => setAppConfig((prev) => ({ ...prev, language: lang.value }))}
+                                        onClick={() => {
+                                            setAppConfig((prev) => ({ ...prev, language: lang.value }))
+                                            onTimeFormatChange({ ...timeFormat, locale: lang.value })
+                                        }}
                                     >
                                         {lang.label}
                                     </Button>
                                 ))}
                             </div>
                         </div>
+
+                        <Separator />
+
+                        <div className="space-y-3">
+                            <h4 className="text-sm font-medium">时间显示</h4>
+                            <div className="grid grid-cols-2 gap-2">
+                                {[
+                                    { value: "relative", label: "相对时间（3 天前）" },
+                                    { value: "absolute", label: "日期时间" },
+                                ].map((style) => (
+                                    <Button
+                                        key={style.value}
+                                        variant={timeFormat.style === style.value ? "default" : "outline"}
+                                        size="sm"
+                                        className="h-8 text-xs"
+                                        onClick={() => onTimeFormatChange({ ...timeFormat, style: style.value as TimeFormat["style"] })}
+                                    >
+                                        {style.label}
+                                    </Button>
+                                ))}
+                            </div>
+                        </div>
+
+                        <Separator />
+
+                        <div className="space-y-3">
+                            <h4 className="text-sm font-medium">大小单位</h4>
+                            <div className="grid grid-cols-3 gap-2">
+                                {[
+                                    { value: "jedec", label: "KB (1024)" },
+                                    { value: "binary", label: "KiB (1024)" },
+                                    { value: "decimal", label: "KB (1000)" },
+                                ].map((units) => (
+                                    <Button
+                                        key={units.value}
+                                        variant={sizeFormat.units === units.value ? "default" : "outline"}
+                                        size="sm"
+                                        className="h-8 text-xs"
+                                        onClick={() => onSizeFormatChange({ ...sizeFormat, units: units.value as SizeUnits })}
+                                    >
+                                        {units.label}
+                                    </Button>
+                                ))}
+                            </div>
+
+                            <div className="flex items-center justify-between">
+                                <div className="space-y-0.5">
+                                    <Label className="text-sm">小数点使用逗号</Label>
+                                    <p className="text-xs text-muted-foreground">例如 1,5 GB</p>
+                                </div>
+                                <Switch
+                                    checked={sizeFormat.decimal_separator === ","}
+                                    onCheckedChange={(value) =>
+                                        onSizeFormatChange({
+                                            ...sizeFormat,
+                                            decimal_separator: value ? "," : ".",
+                                            thousands_separator: sizeFormat.thousands_separator ? (value ? "." : ",") : null,
+                                        })
+                                    }
+                                />
+                            </div>
+
+                            <div className="flex items-center justify-between">
+                                <div className="space-y-0.5">
+                                    <Label className="text-sm">千位分隔符</Label>
+                                    <p className="text-xs text-muted-foreground">精确字节数按千位分组显示</p>
+                                </div>
+                                <Switch
+                                    checked={sizeFormat.thousands_separator !== null}
+                                    onCheckedChange={(value) =>
+                                        onSizeFormatChange({
+                                            ...sizeFormat,
+                                            thousands_separator: value ? (sizeFormat.decimal_separator === "," ? "." : ",") : null,
+                                        })
+                                    }
+                                />
+                            </div>
+
+                            <div className="flex items-center justify-between">
+                                <div className="space-y-0.5">
+                                    <Label className="text-sm">完整读出单位</Label>
+                                    <p className="text-xs text-muted-foreground">例如 3.2 gigabytes，便于屏幕阅读器朗读，导出时同样适用</p>
+                                </div>
+                                <Switch
+                                    checked={sizeFormat.spelled_out}
+                                    onCheckedChange={(value) => onSizeFormatChange({ ...sizeFormat, spelled_out: value })}
+                                />
+                            </div>
+                        </div>
                     </TabsContent>
 
                     {/* General Settings Tab */}
@@ -417,6 +541,30 @@ export function SettingsDialog({
                                     onCheckedChange={(value) => setAppConfig((prev) => ({ ...prev, parallelByDefault: value }))}
                                 />
                             </div>
+
+                            <div className="flex items-center justify-between">
+                                <div className="space-y-0.5">
+                                    <Label className="text-sm">启动时预扫描</Label>
+                                    <p className="text-xs text-muted-foreground">在后台低优先级扫描监控目录，打开时立即显示结果</p>
+                                </div>
+                                <Switch
+                                    checked={warmCache.enabled}
+                                    onCheckedChange={(value) => updateWarmCache({ enabled: value })}
+                                />
+                            </div>
+
+                            {warmCache.enabled && (
+                                <div className="flex items-center justify-between">
+                                    <div className="space-y-0.5">
+                                        <Label className="text-sm">使用电池时不预扫描</Label>
+                                        <p className="text-xs text-muted-foreground">笔记本未接通电源时跳过</p>
+                                    </div>
+                                    <Switch
+                                        checked={warmCache.skip_on_battery}
+                                        onCheckedChange={(value) => updateWarmCache({ skip_on_battery: value })}
+                                    />
+                                </div>
+                            )}
                         </div>
 
                         <Separator />
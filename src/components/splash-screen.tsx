@@ -0,0 +1,168 @@
+import React, { useEffect, useState } from 'react';
+import { Button } from '@/components/ui/button';
+import { Checkbox } from '@/components/ui/checkbox';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { CheckCircle2, Circle, HardDrive, Loader2, MinusCircle, XCircle } from 'lucide-react';
+
+type SetupStep = 'detect_drives' | 'choose_roots' | 'build_cache';
+
+type StepStatus =
+    | { state: 'running' }
+    | { state: 'waiting' }
+    | { state: 'done' }
+    | { state: 'skipped' }
+    | { state: 'failed'; message: string };
+
+interface SetupProgress {
+    step: SetupStep;
+    status: StepStatus;
+    done: number;
+    total: number;
+    detail: string | null;
+}
+
+interface DriveInfo {
+    mount_point: string;
+    device: string;
+    file_system: string;
+    total_display: string;
+    available_display: string;
+}
+
+interface SetupReport {
+    first_run: boolean;
+    drives: DriveInfo[];
+    steps: SetupProgress[];
+}
+
+const stepLabels: Record<SetupStep, string> = {
+    detect_drives: '检测磁盘',
+    choose_roots: '选择监控目录',
+    build_cache: '建立索引',
+};
+
+const stepOrder: SetupStep[] = ['detect_drives', 'choose_roots', 'build_cache'];
+
+// 启动画面：显示初始化各步骤的进度，首次运行时让用户选择要监控的磁盘
+export const SplashScreen: React.FC = () => {
+    const [report, setReport] = useState<SetupReport | null>(null);
+    const [selected, setSelected] = useState<string[]>([]);
+    const [submitting, setSubmitting] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        let disposed = false;
+        const unlisten = listen<SetupProgress>('setup-progress', async (event) => {
+            // 磁盘检测完成后重新获取完整状态以拿到磁盘列表
+            if (event.payload.step === 'detect_drives') {
+                const latest = await invoke<SetupReport>('setup_status');
+                if (!disposed) setReport(latest);
+                return;
+            }
+            setReport((current) => current && {
+                ...current,
+                steps: [
+                    ...current.steps.filter((s) => s.step !== event.payload.step),
+                    event.payload,
+                ],
+            });
+        });
+        // 先订阅再获取当前状态，避免错过启动画面打开前已发出的事件
+        invoke<SetupReport>('setup_status').then((latest) => {
+            if (!disposed) setReport(latest);
+        });
+        return () => {
+            disposed = true;
+            unlisten.then((f) => f());
+        };
+    }, []);
+
+    const statusOf = (step: SetupStep) => report?.steps.find((s) => s.step === step);
+    const waitingForRoots = statusOf('choose_roots')?.status.state === 'waiting';
+
+    const toggle = (mountPoint: string, checked: boolean) => {
+        setSelected((current) =>
+            checked ? [...current, mountPoint] : current.filter((m) => m !== mountPoint)
+        );
+    };
+
+    const handleFinish = async () => {
+        setSubmitting(true);
+        setError(null);
+        try {
+            await invoke('finish_first_run', { roots: selected });
+        } catch (err) {
+            setError(String(err));
+            setSubmitting(false);
+        }
+    };
+
+    return (
+        <div className="flex h-screen flex-col items-center justify-center gap-6 p-8">
+            <h1 className="text-2xl font-semibold">DiskSight</h1>
+            <ul className="w-full max-w-md space-y-2">
+                {stepOrder.map((step) => {
+                    const progress = statusOf(step);
+                    return (
+                        <li key={step} className="flex items-start gap-2 text-sm">
+                            <StepIcon status={progress?.status} />
+                            <div className="flex-1">
+                                <div>
+                                    {stepLabels[step]}
+                                    {progress && progress.total > 0 && ` (${progress.done}/${progress.total})`}
+                                </div>
+                                {progress?.detail && (
+                                    <div className="truncate text-xs text-muted-foreground">{progress.detail}</div>
+                                )}
+                                {progress?.status.state === 'failed' && (
+                                    <div className="whitespace-pre-wrap text-xs text-destructive">{progress.status.message}</div>
+                                )}
+                            </div>
+                        </li>
+                    );
+                })}
+            </ul>
+
+            {waitingForRoots && report && (
+                <div className="w-full max-w-md space-y-3">
+                    <p className="text-sm text-muted-foreground">首次使用，请选择需要监控并建立索引的磁盘，之后可在设置中修改。</p>
+                    {report.drives.map((drive) => (
+                        <label key={drive.mount_point} className="flex items-center gap-3 rounded-md border p-2 text-sm">
+                            <Checkbox
+                                checked={selected.includes(drive.mount_point)}
+                                onCheckedChange={(checked) => toggle(drive.mount_point, checked === true)}
+                            />
+                            <HardDrive className="size-4" />
+                            <span className="flex-1">{drive.mount_point}</span>
+                            <span className="text-xs text-muted-foreground">
+                                {drive.available_display} 可用 / {drive.total_display}
+                            </span>
+                        </label>
+                    ))}
+                    {error && <p className="text-sm text-destructive">{error}</p>}
+                    <Button className="w-full" disabled={submitting} onClick={handleFinish}>
+                        {submitting ? '正在建立索引...' : selected.length > 0 ? '开始' : '跳过'}
+                    </Button>
+                </div>
+            )}
+        </div>
+    );
+};
+
+const StepIcon: React.FC<{ status?: StepStatus }> = ({ status }) => {
+    switch (status?.state) {
+        case 'running':
+            return <Loader2 className="size-4 animate-spin" />;
+        case 'waiting':
+            return <Circle className="size-4 text-primary" />;
+        case 'done':
+            return <CheckCircle2 className="size-4 text-green-500" />;
+        case 'skipped':
+            return <MinusCircle className="size-4 text-muted-foreground" />;
+        case 'failed':
+            return <XCircle className="size-4 text-destructive" />;
+        default:
+            return <Circle className="size-4 text-muted-foreground" />;
+    }
+};
@@ -0,0 +1,134 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { Button } from "@/components/ui/button"
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogFooter,
+    DialogHeader,
+    DialogTitle,
+} from "@/components/ui/dialog"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Tag } from "lucide-react"
+import { invoke } from '@tauri-apps/api/core';
+
+// 与后端 PathAnnotation 对应
+export interface PathAnnotation {
+    path: string
+    note: string | null
+    tags: string[]
+}
+
+// 常用标签，点击即可添加
+const suggestedTags = ["保留", "待团队确认", "发布后删除"]
+
+interface TagEditorProps {
+    open: boolean
+    onOpenChange: (open: boolean) => void
+    path: string
+    annotation?: PathAnnotation
+    onSaved: () => void
+}
+
+export function TagEditor({ open, onOpenChange, path, annotation, onSaved }: TagEditorProps) {
+    const [tags, setTags] = useState("")
+    const [note, setNote] = useState("")
+    const [isSaving, setIsSaving] = useState(false)
+
+    useEffect(() => {
+        if (open) {
+            setTags(annotation?.tags.join(", ") ?? "")
+            setNote(annotation?.note ?? "")
+        }
+    }, [open, annotation])
+
+    const addTag = (tag: string) => {
+        const current = tags.split(",").map((t) => t.trim()).filter(Boolean)
+        if (!current.includes(tag)) setTags([...current, tag].join(", "))
+    }
+
+    const handleSave = async () => {
+        setIsSaving(true)
+        try {
+            // 标签和备注都为空时后端会移除该路径的标注
+            await invoke("tag_entry", {
+                path,
+                tags: tags.split(",").map((t) => t.trim()).filter(Boolean),
+                note: note.trim() || null,
+            })
+            onSaved()
+            onOpenChange(false)
+        } catch (e) {
+            console.error("保存标签失败:", e)
+        } finally {
+            setIsSaving(false)
+        }
+    }
+
+    return (
+        <Dialog open={open} onOpenChange={onOpenChange}>
+            <DialogContent className="max-w-[420px] p-0 gap-0">
+                <DialogHeader className="px-4 py-3 border-b border-border">
+                    <DialogTitle className="flex items-center gap-2 text-base">
+                        <Tag className="h-4 w-4" />
+                        标签与备注
+                    </DialogTitle>
+                    <DialogDescription className="text-xs truncate" title={path}>{path}</DialogDescription>
+                </DialogHeader>
+                <div className="p-4 space-y-3">
+                    <div className="space-y-1.5">
+                        <Label htmlFor="entry-tags" className="text-xs text-muted-foreground">
+                            标签（用逗号分隔）
+                        </Label>
+                        <Input
+                            id="entry-tags"
+                            value={tags}
+                            onChange={(e) => setTags(e.target.value)}
+                            className="h-8 text-xs"
+                        />
+                        <div className="flex flex-wrap gap-1">
+                            {suggestedTags.map((tag) => (
+                                <Button
+                                    key={tag}
+                                    variant="outline"
+                                    size="sm"
+                                    className="h-6 px-2 text-[10px]"
+                                    onClick={() => addTag(tag)}
+                                >
+                                    {tag}
+                                </Button>
+                            ))}
+                        </div>
+                    </div>
+                    <div className="space-y-1.5">
+                        <Label htmlFor="entry-note" className="text-xs text-muted-foreground">
+                            备注
+                        </Label>
+                        <Input
+                            id="entry-note"
+                            value={note}
+                            onChange={(e) => setNote(e.target.value)}
+                            className="h-8 text-xs"
+                        />
+                    </div>
+                </div>
+                <DialogFooter className="px-4 py-3 border-t border-border">
+                    <Button
+                        variant="outline"
+                        size="sm"
+                        className="h-8 text-xs bg-transparent"
+                        onClick={() => onOpenChange(false)}
+                    >
+                        取消
+                    </Button>
+                    <Button size="sm" className="h-8 text-xs" onClick={handleSave} disabled={isSaving}>
+                        保存
+                    </Button>
+                </DialogFooter>
+            </DialogContent>
+        </Dialog>
+    )
+}
@@ -0,0 +1,72 @@
+// 与后端 utils::SizeFormat 对应的大小显示格式
+export type SizeUnits = "jedec" | "binary" | "decimal"
+export type UnitScale = "byte" | "kilo" | "mega" | "giga" | "tera" | "peta" | "exa"
+
+export interface SizeFormat {
+  units: SizeUnits
+  fixed_unit: UnitScale | null
+  decimal_separator: string
+  thousands_separator: string | null
+  // 单位写成完整的英文单词，便于屏幕阅读器朗读
+  spelled_out: boolean
+}
+
+// 与后端 time_format::TimeFormat 对应的时间显示格式
+export interface TimeFormat {
+  style: "relative" | "absolute"
+  locale: string
+  utc_offset_minutes: number
+}
+
+export const defaultTimeFormat: TimeFormat = {
+  style: "absolute",
+  locale: "zh-CN",
+  utc_offset_minutes: -new Date().getTimezoneOffset(),
+}
+
+export const defaultSizeFormat: SizeFormat = {
+  units: "jedec",
+  fixed_unit: null,
+  decimal_separator: ".",
+  thousands_separator: null,
+  spelled_out: false,
+}
+
+const scales: UnitScale[] = ["byte", "kilo", "mega", "giga", "tera", "peta", "exa"]
+
+// 原始字节数按千位分组
+export function groupDigits(bytes: number | bigint, separator: string | null): string {
+  const digits = BigInt(bytes).toString()
+  if (!separator) return digits
+  return digits.replace(/\B(?=(\d{3})+(?!\d))/g, separator)
+}
+
+export function formatSize(bytes: number | bigint, format: SizeFormat): string {
+  const base = format.units === "decimal" ? 1000 : 1024
+  const labels = format.units === "binary"
+    ? ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]
+    : ["B", "KB", "MB", "GB", "TB", "PB", "EB"]
+  let size = Number(bytes)
+  if (size === 0 && !format.fixed_unit && !format.spelled_out) return "0B"
+  let unit = 0
+  if (format.fixed_unit) {
+    unit = scales.indexOf(format.fixed_unit)
+    size /= Math.pow(base, unit)
+  } else {
+    while (size >= base && unit < labels.length - 1) {
+      size /= base
+      unit += 1
+    }
+  }
+  if (format.spelled_out) return spellOut(size, unit, format)
+  return `${size.toFixed(1).replace(".", format.decimal_separator)}${labels[unit]}`
+}
+
+// 与后端 utils::spell_out 一致，如 "1 byte"、"3.2 gigabytes"
+function spellOut(size: number, unit: number, format: SizeFormat): string {
+  const names = format.units === "binary"
+    ? ["byte", "kibibyte", "mebibyte", "gibibyte", "tebibyte", "pebibyte", "exbibyte"]
+    : ["byte", "kilobyte", "megabyte", "gigabyte", "terabyte", "petabyte", "exabyte"]
+  const number = unit === 0 ? size.toFixed(0) : size.toFixed(1).replace(".", format.decimal_separator)
+  return `${number} ${names[unit]}${number === "1" ? "" : "s"}`
+}
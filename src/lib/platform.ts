@@ -0,0 +1,38 @@
+import { invoke } from '@tauri-apps/api/core';
+
+// 与后端 platform::PlatformCapabilities 对应，不支持的功能应在界面上隐藏
+export interface PlatformCapabilities {
+  os: string
+  supports_mft_scan: boolean
+  supports_trash: boolean
+  supports_trash_listing: boolean
+  supports_smart: boolean
+  supports_file_attributes: boolean
+  supports_chmod: boolean
+  scan_backend: "getdents" | "find_first_file" | "read_dir"
+}
+
+let capabilities: Promise<PlatformCapabilities> | null = null
+
+// 运行期间功能不会变化，只向后端查询一次
+export function platformCapabilities(): Promise<PlatformCapabilities> {
+  if (!capabilities) {
+    capabilities = invoke<PlatformCapabilities>("get_platform_capabilities")
+    capabilities.catch(() => { capabilities = null })
+  }
+  return capabilities
+}
+
+// 与后端 power::PowerState 对应，变化时后端发送 power-state 事件
+export interface PowerState {
+  on_battery: boolean | null
+  metered: boolean | null
+  // 定时脚本、后台索引与启动预扫描被推迟
+  defer_background: boolean
+  // 对代理的远程扫描被推迟
+  defer_remote: boolean
+}
+
+export function powerState(): Promise<PowerState> {
+  return invoke<PowerState>("get_power_state")
+}
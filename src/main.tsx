@@ -2,31 +2,22 @@ import React from "react";
 import ReactDOM from "react-dom/client";
 import "./App.css";
 import App from "./App";
+import { SplashScreen } from "@/components/splash-screen";
 import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+
+// 启动画面窗口显示初始化进度，其他窗口显示主界面
+const isSplash = getCurrentWebviewWindow().label === "splashscreen";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {isSplash ? <SplashScreen /> : <App />}
   </React.StrictMode>,
 );
-// 这些语句可以复制粘贴到现有的代码下面，但不要替换整个文件！！
-
-// 在 TypeScript 中实现的一个 sleep 函数
-function sleep(seconds: number): Promise<void> {
-  return new Promise(resolve => setTimeout(resolve, seconds * 1000));
-}
-
-// 设置函数
-async function setup() {
-  // 模拟执行一个很重的前端设置任务
-  console.log('Performing really heavy frontend setup task...')
-  await sleep(1);
-  console.log('Frontend setup task complete!')
-  // 设置前端任务为完成
-  invoke('set_complete', { task: 'frontend' })
-}
 
-// 实际上的 JavaScript main 函数
+// 主窗口加载完成后通知后端前端任务已完成
 window.addEventListener("DOMContentLoaded", () => {
-  setup()
-});
\ No newline at end of file
+  if (!isSplash) {
+    invoke('set_complete', { task: 'frontend' })
+  }
+});